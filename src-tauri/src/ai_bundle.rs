@@ -1,35 +1,184 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+use crate::gps_backend::{OperationInfo, OperationRegistry};
+use crate::logging::recent_log_lines;
+use crate::metrics::{MetricsControl, SystemMetrics};
+use crate::settings::SettingsState;
+use crate::update::{self, UpdateHistoryEntry};
 
 // src-tauri/src/ai_bundle.rs
 // Used by: src-tauri/src/lib.rs
-// Purpose: Provides Tauri commands to write debug bundles (.ai/bundles/latest.bundle.md).
+// Purpose: Provides Tauri commands to write debug bundles (<bundle dir>/latest.bundle.md).
 // Trigger: Called via invoke() from frontend TaskFlow runtime.
-// Event Flow: Frontend calls write_debug_bundle -> finds repo root -> creates .ai/bundles/ -> writes latest.bundle.md
+// Event Flow: Frontend calls write_debug_bundle -> bundle_paths() -> creates the bundle dir -> writes latest.bundle.md
+//             -> appends backend system context -> redacts (home, username, secrets) -> archives a timestamped copy in .ai/bundles/history/ -> prunes old copies
 // Functions:
-//   - find_repo_root(): Walks up directories to locate package.json
+//   - find_repo_root(): Walks up directories to locate package.json (dev builds)
+//   - bundle_paths(): Canonical bundle dir (repo in dev, app data dir in release)
 //   - ensure_parent_dir(): Creates parent directories if needed
+//   - collect_system_context(): Version, OS/arch, running ops, last update event, metrics, log tail
+//   - Redactor: Strips home paths, usernames, emails, API keys + user patterns
+//   - write_bundle(): Shared write + context + redaction + archive + prune
 //   - write_latest_bundle(): Legacy command that returns path
 //   - write_debug_bundle(): New command for TaskFlow runtime (returns ())
+//   - write_latest_bundle_json(): Structured payload -> latest.bundle.json + rendered latest.bundle.md
+//   - list_bundles() / read_bundle(name): Browse latest + archived bundles
+
+const LATEST_NAME: &str = "latest.bundle.md";
+const HISTORY_DIR: &str = "history";
+const BUNDLE_SUFFIX: &str = ".bundle.md";
+const LATEST_JSON_NAME: &str = "latest.bundle.json";
+/// Backend log lines appended to each bundle.
+const BUNDLE_LOG_LINES: usize = 100;
+
+/// Version of the latest.bundle.json layout.
+/// Why: Downstream agents validate against it; bump on breaking changes.
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+/// Structured bundle sent by the frontend.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundlePayload {
+  #[serde(default)]
+  pub title: Option<String>,
+  #[serde(default)]
+  pub sections: Vec<BundleSection>,
+  #[serde(default)]
+  pub facts: Vec<BundleFact>,
+  #[serde(default)]
+  pub logs: Vec<BundleLog>,
+}
+
+/// Free-form Markdown section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSection {
+  pub heading: String,
+  pub body: String,
+}
+
+/// Single key/value fact (e.g. "flow" = "copy-paste").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFact {
+  pub key: String,
+  pub value: String,
+}
+
+/// Attached log, rendered as a code block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleLog {
+  pub name: String,
+  pub content: String,
+}
+
+/// What lands in latest.bundle.json.
+#[derive(Clone, Serialize)]
+struct BundleDocument<'a> {
+  schema_version: u32,
+  /// RFC 3339 UTC.
+  generated_at: String,
+  #[serde(flatten)]
+  payload: &'a BundlePayload,
+  system: &'a SystemContext,
+}
+
+/// Backend-generated facts appended to every bundle.
+/// Why: The frontend shouldn't have to collect version/OS/metrics itself.
+#[derive(Clone, Serialize)]
+pub struct SystemContext {
+  /// Version of the running binary.
+  app_version: String,
+  /// From version_state.json.
+  current_version: Option<String>,
+  previous_version: Option<String>,
+  os: String,
+  arch: String,
+  /// Operations registered in GPS at write time.
+  operations: Vec<OperationInfo>,
+  last_update_event: Option<UpdateHistoryEntry>,
+  /// None until the metrics loop has produced a sample.
+  metrics: Option<SystemMetrics>,
+  /// Tail of the backend log files (see logging.rs).
+  recent_logs: Vec<String>,
+}
+
+/// Paths written by write_latest_bundle_json.
+#[derive(Debug, Clone, Serialize)]
+pub struct WrittenBundle {
+  json_path: String,
+  md_path: String,
+}
+
+/// Where bundles live; see bundle_paths().
+#[derive(Debug, Clone, Serialize)]
+pub struct BundlePaths {
+  /// "repo" (dev build inside a checkout) or "app_data".
+  pub source: &'static str,
+  pub dir: PathBuf,
+  pub latest_md: PathBuf,
+  pub latest_json: PathBuf,
+  pub history_dir: PathBuf,
+}
+
+/// One bundle file, as listed by list_bundles.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleInfo {
+  /// File name; pass to read_bundle.
+  name: String,
+  size: u64,
+  /// Seconds since UNIX_EPOCH.
+  modified: u64,
+  is_latest: bool,
+}
 
 /// Find the repository root by walking up directories until package.json is found.
-/// Why: Tauri runs from src-tauri/ but we need to write to repo root.
-fn find_repo_root() -> PathBuf {
+/// Why: `tauri dev` runs from src-tauri/ but the agent reads <repo>/.ai/bundles/.
+fn find_repo_root() -> Option<PathBuf> {
   // Best-effort: walk up a few levels and stop where package.json exists.
-  let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+  let mut dir = std::env::current_dir().ok()?;
   for _ in 0..6 {
     if dir.join("package.json").exists() {
-      return dir;
+      return Some(dir);
     }
     if !dir.pop() {
       break;
     }
   }
-  std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+  None
+}
+
+/// Canonical bundle locations, used by every read and write.
+/// Dev builds run inside a checkout: <repo>/.ai/bundles/.
+/// Release builds (or dev outside a checkout): <app data dir>/ai/bundles/.
+/// Why: A packaged app has no repo; the cwd there may be "/" or read-only.
+pub fn bundle_paths(app: &AppHandle) -> Result<BundlePaths, String> {
+  let repo = if cfg!(debug_assertions) { find_repo_root() } else { None };
+  let (source, dir) = match repo {
+    Some(root) => ("repo", root.join(".ai").join("bundles")),
+    None => {
+      let data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data dir error: {}", e))?;
+      ("app_data", data.join("ai").join("bundles"))
+    }
+  };
+  Ok(BundlePaths {
+    source,
+    latest_md: dir.join(LATEST_NAME),
+    latest_json: dir.join(LATEST_JSON_NAME),
+    history_dir: dir.join(HISTORY_DIR),
+    dir,
+  })
 }
 
 /// Create parent directories if they don't exist.
-/// Why: Ensures .ai/bundles/ path exists before writing bundle.
+/// Why: Ensures the bundle dir exists before writing bundle.
 fn ensure_parent_dir(path: &Path) -> std::io::Result<()> {
   if let Some(parent) = path.parent() {
     fs::create_dir_all(parent)?;
@@ -37,16 +186,292 @@ fn ensure_parent_dir(path: &Path) -> std::io::Result<()> {
   Ok(())
 }
 
-/// Write `.ai/bundles/latest.bundle.md` into the repo root (best-effort located).
-/// Returns the absolute path written to, as a string.
-#[tauri::command]
-pub fn write_latest_bundle(markdown: String) -> Result<String, String> {
-  let root = find_repo_root();
-  let path = root.join(".ai").join("bundles").join("latest.bundle.md");
+/// Snapshot backend state for the bundle; every part is best-effort.
+fn collect_system_context(app: &AppHandle) -> SystemContext {
+  let state = update::load_version_state(app).ok();
+  SystemContext {
+    app_version: app.package_info().version.to_string(),
+    current_version: state.as_ref().map(|s| s.current.clone()),
+    previous_version: state.and_then(|s| s.previous),
+    os: std::env::consts::OS.to_string(),
+    arch: std::env::consts::ARCH.to_string(),
+    operations: app
+      .try_state::<OperationRegistry>()
+      .map(|r| r.list())
+      .unwrap_or_default(),
+    last_update_event: update::load_update_history(app, Some(1))
+      .ok()
+      .and_then(|h| h.into_iter().next()),
+    metrics: app.try_state::<MetricsControl>().and_then(|m| m.latest()),
+    recent_logs: recent_log_lines(app, BUNDLE_LOG_LINES).unwrap_or_default(),
+  }
+}
+
+/// Serde name of an enum value ("folder-scan", "success", ...).
+fn serde_name<T: Serialize>(value: &T) -> String {
+  serde_json::to_value(value)
+    .ok()
+    .and_then(|v| v.as_str().map(str::to_string))
+    .unwrap_or_default()
+}
+
+fn render_system_context(ctx: &SystemContext) -> String {
+  const MIB: u64 = 1024 * 1024;
+  let mut md = String::from("## System Context\n\n_Appended by the backend._\n\n");
+
+  md.push_str(&format!("- App version: {}\n", ctx.app_version));
+  md.push_str(&format!(
+    "- Version state: current {}, previous {}\n",
+    ctx.current_version.as_deref().unwrap_or("-"),
+    ctx.previous_version.as_deref().unwrap_or("-")
+  ));
+  md.push_str(&format!("- OS / arch: {} / {}\n", ctx.os, ctx.arch));
+
+  md.push_str("\n### Operations\n\n");
+  if ctx.operations.is_empty() {
+    md.push_str("- none running\n");
+  }
+  for op in &ctx.operations {
+    md.push_str(&format!(
+      "- `{}` {} ({}), started {}\n",
+      op.op_id,
+      serde_name(&op.kind),
+      serde_name(&op.state),
+      op.started_at
+    ));
+  }
+
+  md.push_str("\n### Last update event\n\n");
+  match &ctx.last_update_event {
+    Some(e) => {
+      md.push_str(&format!(
+        "- {} {} at {} ({} -> {})\n",
+        serde_name(&e.kind),
+        serde_name(&e.outcome),
+        e.timestamp,
+        e.from_version.as_deref().unwrap_or("-"),
+        e.to_version.as_deref().unwrap_or("-")
+      ));
+      if let Some(err) = &e.error {
+        md.push_str(&format!("- error: {}\n", err));
+      }
+    }
+    None => md.push_str("- none recorded\n"),
+  }
+
+  md.push_str("\n### Metrics\n\n");
+  match &ctx.metrics {
+    Some(m) => {
+      md.push_str(&format!("- CPU: {:.1}%\n", m.cpu_total));
+      md.push_str(&format!("- Memory: {} / {} MiB\n", m.mem_used / MIB, m.mem_total / MIB));
+      if let Some(d) = &m.disk_max {
+        md.push_str(&format!("- Fullest disk: {} ({:.1}%)\n", d.mount_point, d.used_percent));
+      }
+    }
+    None => md.push_str("- no sample yet\n"),
+  }
+
+  md.push_str("\n### Backend log (tail)\n\n");
+  if ctx.recent_logs.is_empty() {
+    md.push_str("- empty\n");
+  } else {
+    md.push_str(&format!("```\n{}\n```\n", ctx.recent_logs.join("\n")));
+  }
+
+  md
+}
+
+/// Ordered find/replace rules applied to bundle text before it is written.
+/// Why: Bundles get pasted into chats and tickets; they must not leak who
+/// the user is or any credentials that ended up in logs.
+struct Redactor {
+  rules: Vec<(Regex, String)>,
+}
+
+/// Built-in secret patterns (replacement may use capture groups).
+const SECRET_RULES: &[(&str, &str)] = &[
+  // key=value / key: value style credentials
+  (
+    r#"(?i)\b(api[_-]?key|access[_-]?key|secret|token|password|passwd)\b(\s*[:=]\s*"?)[^\s"',;]+"#,
+    "${1}${2}<secret>",
+  ),
+  (r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]{8,}", "Bearer <secret>"),
+  // well-known key formats: OpenAI/Anthropic, GitHub, AWS, Slack
+  (
+    r"\b(sk-[A-Za-z0-9_-]{16,}|gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,}|AKIA[0-9A-Z]{16}|xox[abprs]-[A-Za-z0-9-]{10,})\b",
+    "<secret>",
+  ),
+  (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "<email>"),
+];
+
+impl Redactor {
+  fn new(app: &AppHandle, extra_patterns: &[String]) -> Self {
+    let mut rules = Vec::new();
+
+    // Home first, so "/home/alice/x" becomes "~/x" rather than "/home/<user>/x".
+    if let Ok(home) = app.path().home_dir() {
+      let home = home.to_string_lossy().into_owned();
+      if home.len() > 1 {
+        let mut variants = vec![home.clone()];
+        if home.contains('\\') {
+          variants.push(home.replace('\\', "/"));
+        }
+        for v in variants {
+          if let Ok(re) = RegexBuilder::new(&regex::escape(&v))
+            .case_insensitive(cfg!(windows))
+            .build()
+          {
+            rules.push((re, "~".to_string()));
+          }
+        }
+      }
+    }
+
+    let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok();
+    // Very short names would shred ordinary words.
+    if let Some(user) = user.filter(|u| u.chars().count() >= 3) {
+      if let Ok(re) = RegexBuilder::new(&format!(r"\b{}\b", regex::escape(&user)))
+        .case_insensitive(true)
+        .build()
+      {
+        rules.push((re, "<user>".to_string()));
+      }
+    }
+
+    for (pattern, replacement) in SECRET_RULES {
+      if let Ok(re) = Regex::new(pattern) {
+        rules.push((re, replacement.to_string()));
+      }
+    }
+
+    // Validated when settings are saved; skip anything that still fails.
+    for pattern in extra_patterns {
+      match Regex::new(pattern) {
+        Ok(re) => rules.push((re, "<redacted>".to_string())),
+        Err(e) => tracing::warn!("[AiBundle] Ignoring invalid redaction pattern {:?}: {e}", pattern),
+      }
+    }
+
+    Redactor { rules }
+  }
+
+  fn redact(&self, text: &str) -> String {
+    let mut out = text.to_string();
+    for (re, replacement) in &self.rules {
+      if let std::borrow::Cow::Owned(s) = re.replace_all(&out, replacement.as_str()) {
+        out = s;
+      }
+    }
+    out
+  }
+
+  /// Redact every string (keys untouched) in a JSON document.
+  fn redact_json(&self, value: &mut Value) {
+    match value {
+      Value::String(s) => *s = self.redact(s),
+      Value::Array(items) => items.iter_mut().for_each(|v| self.redact_json(v)),
+      Value::Object(map) => map.values_mut().for_each(|v| self.redact_json(v)),
+      _ => {}
+    }
+  }
+}
+
+/// Per-write options taken from settings.
+struct BundleOptions {
+  keep: usize,
+  /// None when system.ai_bundle_redact is off.
+  redactor: Option<Redactor>,
+}
+
+impl BundleOptions {
+  fn from_settings(app: &AppHandle, settings: &SettingsState) -> Self {
+    let sys = settings.get().system;
+    BundleOptions {
+      keep: sys.ai_bundle_history_keep as usize,
+      redactor: sys
+        .ai_bundle_redact
+        .then(|| Redactor::new(app, &sys.ai_bundle_redact_patterns)),
+    }
+  }
+
+  fn redact(&self, text: String) -> String {
+    match &self.redactor {
+      Some(r) => r.redact(&text),
+      None => text,
+    }
+  }
+}
+
+/// Write latest.bundle.md, archive a copy as history/<timestamp>.bundle.md
+/// and keep only the newest `keep` archived copies.
+/// Why: The agent needs earlier bundles to diff against the current one.
+fn write_bundle(
+  paths: &BundlePaths,
+  md: &str,
+  ctx: &SystemContext,
+  opts: &BundleOptions,
+) -> Result<PathBuf, String> {
+  let path = paths.latest_md.clone();
+  let md = opts.redact(format!("{}\n\n{}", md.trim_end(), render_system_context(ctx)));
+  let md = md.as_str();
+  let keep = opts.keep;
 
   ensure_parent_dir(&path).map_err(|e| e.to_string())?;
-  fs::write(&path, markdown).map_err(|e| e.to_string())?;
+  fs::write(&path, md).map_err(|e| e.to_string())?;
+
+  if keep > 0 {
+    let history = &paths.history_dir;
+    let name = format!(
+      "{}{}",
+      chrono::Local::now().format("%Y%m%d-%H%M%S-%3f"),
+      BUNDLE_SUFFIX
+    );
+    let archived = history.join(name);
+    ensure_parent_dir(&archived).map_err(|e| e.to_string())?;
+    fs::write(&archived, md).map_err(|e| e.to_string())?;
+    prune_history(history, keep);
+  }
+
+  Ok(path)
+}
+
+/// Delete the oldest archived bundles beyond `keep` (names sort by time).
+fn prune_history(history: &Path, keep: usize) {
+  let mut names = history_names(history);
+  if names.len() <= keep {
+    return;
+  }
+  names.sort();
+  let excess = names.len() - keep;
+  for name in &names[..excess] {
+    if let Err(e) = fs::remove_file(history.join(name)) {
+      tracing::warn!("[AiBundle] Failed to prune {}: {}", name, e);
+    }
+  }
+}
+
+fn history_names(history: &Path) -> Vec<String> {
+  fs::read_dir(history)
+    .map(|rd| {
+      rd.flatten()
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|n| n.ends_with(BUNDLE_SUFFIX))
+        .collect()
+    })
+    .unwrap_or_default()
+}
 
+/// Write `.ai/bundles/latest.bundle.md` into the repo root (best-effort located).
+/// Returns the absolute path written to, as a string.
+#[tauri::command]
+pub fn write_latest_bundle(
+  app: AppHandle,
+  settings: State<'_, SettingsState>,
+  markdown: String,
+) -> Result<String, String> {
+  let ctx = collect_system_context(&app);
+  let opts = BundleOptions::from_settings(&app, &settings);
+  let path = write_bundle(&bundle_paths(&app)?, &markdown, &ctx, &opts)?;
   Ok(path.to_string_lossy().into_owned())
 }
 
@@ -54,12 +479,164 @@ pub fn write_latest_bundle(markdown: String) -> Result<String, String> {
 /// Why: TaskFlow runtime needs a consistent command name for bundle evidence.
 /// Called by: src/qaTaskFlow/runtime/writeBundle.ts
 #[tauri::command]
-pub fn write_debug_bundle(md: String) -> Result<(), String> {
-  let root = find_repo_root();
-  let path = root.join(".ai").join("bundles").join("latest.bundle.md");
+pub fn write_debug_bundle(
+  app: AppHandle,
+  settings: State<'_, SettingsState>,
+  md: String,
+) -> Result<(), String> {
+  let ctx = collect_system_context(&app);
+  let opts = BundleOptions::from_settings(&app, &settings);
+  write_bundle(&bundle_paths(&app)?, &md, &ctx, &opts)?;
+  Ok(())
+}
 
-  ensure_parent_dir(&path).map_err(|e| e.to_string())?;
-  fs::write(&path, md).map_err(|e| e.to_string())?;
+/// Write `.ai/bundles/latest.bundle.json` plus the same content rendered to
+/// latest.bundle.md (archived like any other Markdown bundle).
+/// Why: Markdown is for humans; tooling reads the JSON.
+/// Frontend can call:
+///   invoke<WrittenBundle>('write_latest_bundle_json', {
+///     payload: { title: 'Copy flow', sections: [...], facts: [{ key: 'flow', value: 'copy' }], logs: [] }
+///   })
+#[tauri::command]
+pub fn write_latest_bundle_json(
+  app: AppHandle,
+  settings: State<'_, SettingsState>,
+  payload: BundlePayload,
+) -> Result<WrittenBundle, String> {
+  let paths = bundle_paths(&app)?;
+  let ctx = collect_system_context(&app);
+  let doc = BundleDocument {
+    schema_version: BUNDLE_SCHEMA_VERSION,
+    generated_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    payload: &payload,
+    system: &ctx,
+  };
+  let opts = BundleOptions::from_settings(&app, &settings);
+  let mut value = serde_json::to_value(&doc).map_err(|e| e.to_string())?;
+  if let Some(r) = &opts.redactor {
+    r.redact_json(&mut value);
+  }
+  let json = serde_json::to_string_pretty(&value).map_err(|e| e.to_string())?;
 
-  Ok(())
+  let json_path = paths.latest_json.clone();
+  ensure_parent_dir(&json_path).map_err(|e| e.to_string())?;
+  let tmp = json_path.with_extension("json.tmp");
+  fs::write(&tmp, json).map_err(|e| e.to_string())?;
+  fs::rename(&tmp, &json_path).map_err(|e| e.to_string())?;
+
+  let md_path = write_bundle(&paths, &render_markdown(&doc), &ctx, &opts)?;
+
+  Ok(WrittenBundle {
+    json_path: json_path.to_string_lossy().into_owned(),
+    md_path: md_path.to_string_lossy().into_owned(),
+  })
+}
+
+/// Render a structured bundle the way hand-written bundles look:
+/// title, facts table, sections, then logs in fenced blocks.
+fn render_markdown(doc: &BundleDocument) -> String {
+  let p = doc.payload;
+  let mut md = String::new();
+
+  md.push_str(&format!("# {}\n\n", p.title.as_deref().unwrap_or("Debug Bundle")));
+  md.push_str(&format!(
+    "_schema v{} · generated {}_\n\n",
+    doc.schema_version, doc.generated_at
+  ));
+
+  if !p.facts.is_empty() {
+    md.push_str("## Facts\n\n| Key | Value |\n| --- | --- |\n");
+    for f in &p.facts {
+      md.push_str(&format!("| {} | {} |\n", table_cell(&f.key), table_cell(&f.value)));
+    }
+    md.push('\n');
+  }
+
+  for s in &p.sections {
+    md.push_str(&format!("## {}\n\n{}\n\n", s.heading, s.body.trim_end()));
+  }
+
+  if !p.logs.is_empty() {
+    md.push_str("## Logs\n\n");
+    for l in &p.logs {
+      // Longer fence than any backtick run inside the log.
+      let fence = "`".repeat(longest_backtick_run(&l.content).max(2) + 1);
+      md.push_str(&format!("### {}\n\n{}\n{}\n{}\n\n", l.name, fence, l.content.trim_end(), fence));
+    }
+  }
+
+  md
+}
+
+fn table_cell(s: &str) -> String {
+  s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn longest_backtick_run(s: &str) -> usize {
+  s.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// List latest.bundle.md (first) and archived bundles (newest first).
+/// Why: Lets the agent pick a previous bundle to diff against.
+/// Frontend can call:
+///   invoke<BundleInfo[]>('list_bundles')
+#[tauri::command]
+pub fn list_bundles(app: AppHandle) -> Result<Vec<BundleInfo>, String> {
+  let paths = bundle_paths(&app)?;
+  let mut out = Vec::new();
+
+  if let Some(info) = bundle_info(&paths.latest_md, true) {
+    out.push(info);
+  }
+
+  let history = &paths.history_dir;
+  let mut names = history_names(history);
+  names.sort_by(|a, b| b.cmp(a));
+  out.extend(names.iter().filter_map(|n| bundle_info(&history.join(n), false)));
+
+  Ok(out)
+}
+
+fn bundle_info(path: &Path, is_latest: bool) -> Option<BundleInfo> {
+  let meta = fs::metadata(path).ok()?;
+  Some(BundleInfo {
+    name: path.file_name()?.to_string_lossy().into_owned(),
+    size: meta.len(),
+    modified: meta
+      .modified()
+      .ok()
+      .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+      .map(|d| d.as_secs())
+      .unwrap_or(0),
+    is_latest,
+  })
+}
+
+/// Read a bundle by name from list_bundles ("latest.bundle.md" or an
+/// archived "<timestamp>.bundle.md").
+/// Frontend can call:
+///   invoke<string>('read_bundle', { name: '20260101-120000-000.bundle.md' })
+#[tauri::command]
+pub fn read_bundle(app: AppHandle, name: String) -> Result<String, String> {
+  // Plain file names only: no separators, no "..".
+  if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") || !name.ends_with(BUNDLE_SUFFIX) {
+    return Err(format!("Invalid bundle name: {}", name));
+  }
+
+  let paths = bundle_paths(&app)?;
+  let path = if name == LATEST_NAME {
+    paths.latest_md
+  } else {
+    paths.history_dir.join(&name)
+  };
+  fs::read_to_string(&path).map_err(|e| format!("Failed to read bundle {}: {}", name, e))
+}
+
+/// Where bundles are written (and read from) in this build.
+/// Frontend can call:
+///   invoke<BundlePaths>('get_bundle_dir')
+///   // { source: 'repo', dir: '/src/filesUp/.ai/bundles', latest_md: ..., latest_json: ..., history_dir: ... }
+#[tauri::command]
+pub fn get_bundle_dir(app: AppHandle) -> Result<BundlePaths, String> {
+  bundle_paths(&app)
 }
@@ -0,0 +1,688 @@
+// src-tauri/src/archive.rs
+//
+// User-facing archive commands (the update module only extracts bundles).
+//
+// create_archive:
+//   - Zips files/folders into a target .zip using the shared `zip` crate.
+//   - Entry names are relative to each source's parent folder, so zipping
+//     "C:\Photos" produces "Photos/..." inside the archive.
+//   - Streams throttled `fu:archive_progress` events and a final
+//     `fu:archive_completed`.
+//   - The archive is written to a hidden ".<name>.fu-partial" next to the
+//     target and renamed over it when complete, so a cancelled or failed
+//     job deletes only its own partial file and an overwritten target
+//     survives until the new archive is whole.
+//
+// extract_archive:
+//   - Extracts .zip, .tar.gz / .tgz and .tar into a destination folder.
+//   - Path traversal protection: entries with absolute paths or ".." are
+//     skipped and counted as unsafe; tar symlinks/hardlinks are skipped too.
+//   - Conflict policy for existing files: skip / overwrite / rename.
+//   - Fails with INSUFFICIENT_SPACE before writing anything when the
+//     uncompressed size can't fit: exact for ZIP, the file length for .tar,
+//     the gzip trailer's size field (mod 4 GiB, so a lower bound) for .tar.gz.
+//   - Streams `fu:extract_progress` and a final `fu:extract_completed`.
+//
+// Both are recorded in the audit log (audit.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::unique_path;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::metrics::ensure_free_space;
+use crate::path_guard::PathGuard;
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+use zip::read::ZipArchive;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const ARCHIVE_CHUNK_BYTES: usize = 1024 * 1024;
+/// Deflate level used when the caller doesn't pick one.
+const DEFAULT_COMPRESSION_LEVEL: i32 = 6;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveProgress {
+    op_id: String,
+    files_done: u64,
+    files_total: u64,
+    bytes_done: u64,
+    bytes_total: u64,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    archive_path: String,
+    files_done: u64,
+    bytes_done: u64,
+    error_message: Option<String>,
+}
+
+/// Command from TS:
+/// invoke("create_archive", { opId, sources, target, compressionLevel, overwrite })
+///
+/// - `compression_level`: 0 = store only, 1..9 = deflate level (default 6).
+/// - `overwrite`: replace an existing target file (default false).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_archive(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    sources: Vec<String>,
+    target: String,
+    compression_level: Option<i32>,
+    overwrite: Option<bool>,
+) -> Result<(), String> {
+    let sources = guard.check_all(&sources)?;
+    let target = guard.check(&target)?;
+    if sources.is_empty() {
+        return Err("No source paths given".to_string());
+    }
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(format!("Target already exists: {}", target.to_string_lossy()));
+    }
+    let level = compression_level
+        .unwrap_or(DEFAULT_COMPRESSION_LEVEL)
+        .clamp(0, 9);
+
+    let token = registry.register(&op_id, OperationKind::Compress);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut job = ArchiveJob {
+            app: &app,
+            op_id: &op_id,
+            token: &token,
+            files_done: 0,
+            files_total: 0,
+            bytes_done: 0,
+            bytes_total: 0,
+            last_emit: Instant::now(),
+        };
+
+        let name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let partial = unique_path(&target.with_file_name(format!(".{}.fu-partial", name)));
+        let res = job
+            .run(&sources, &target, &partial, level)
+            .and_then(|()| fs::rename(&partial, &target).map_err(ArchiveError::from));
+        let (status, error_message) = match res {
+            Ok(()) => ("ok", None),
+            Err(ArchiveError::Cancelled) => ("cancelled", None),
+            Err(ArchiveError::Failed(msg)) => ("error", Some(msg)),
+            Err(ArchiveError::App(e)) => ("error", Some(e.message)),
+        };
+        if status != "ok" {
+            let _ = fs::remove_file(&partial);
+        }
+        let sources: Vec<String> = sources.iter().map(|p| p.to_string_lossy().to_string()).collect();
+        audit::record(
+            &app,
+            AuditEntry::new(AuditAction::CreateArchive)
+                .op_id(&op_id)
+                .sources(&sources)
+                .destination(target.to_string_lossy())
+                .result(status, error_message.clone()),
+        );
+
+        let _ = app.emit(
+            "fu:archive_completed",
+            ArchiveCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                archive_path: target.to_string_lossy().to_string(),
+                files_done: job.files_done,
+                bytes_done: job.bytes_done,
+                error_message,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+enum ArchiveError {
+    Cancelled,
+    Failed(String),
+    /// Failure with a structured payload (INSUFFICIENT_SPACE).
+    App(AppError),
+}
+
+impl From<std::io::Error> for ArchiveError {
+    fn from(e: std::io::Error) -> Self {
+        ArchiveError::Failed(format!("I/O error: {}", e))
+    }
+}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(e: zip::result::ZipError) -> Self {
+        ArchiveError::Failed(format!("ZIP error: {}", e))
+    }
+}
+
+/// One entry to put into the archive.
+struct ArchiveItem {
+    path: PathBuf,
+    /// Name inside the archive ("/"-separated).
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+struct ArchiveJob<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    token: &'a CancellationToken,
+    files_done: u64,
+    files_total: u64,
+    bytes_done: u64,
+    bytes_total: u64,
+    last_emit: Instant,
+}
+
+impl ArchiveJob<'_> {
+    /// Zip `sources` into `partial`; `target` is where it will end up.
+    fn run(&mut self, sources: &[PathBuf], target: &Path, partial: &Path, level: i32) -> Result<(), ArchiveError> {
+        let items = self.collect_items(sources, target)?;
+        self.files_total = items.iter().filter(|i| !i.is_dir).count() as u64;
+        self.bytes_total = items.iter().map(|i| i.size).sum();
+        self.emit_progress("", true);
+
+        let method = if level == 0 {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let options = FileOptions::default()
+            .compression_method(method)
+            .compression_level(if level == 0 { None } else { Some(level) })
+            .large_file(true);
+
+        let mut zip = ZipWriter::new(File::create(partial)?);
+        let mut buf = vec![0u8; ARCHIVE_CHUNK_BYTES];
+
+        for item in &items {
+            if self.token.is_cancelled() {
+                return Err(ArchiveError::Cancelled);
+            }
+            if item.is_dir {
+                zip.add_directory(item.name.as_str(), options)?;
+                continue;
+            }
+
+            zip.start_file(item.name.as_str(), options)?;
+            let mut file = File::open(&item.path)?;
+            loop {
+                if self.token.is_cancelled() {
+                    return Err(ArchiveError::Cancelled);
+                }
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                zip.write_all(&buf[..n])?;
+                self.bytes_done += n as u64;
+                self.emit_progress(&item.name, false);
+            }
+            self.files_done += 1;
+        }
+
+        zip.finish()?;
+        self.emit_progress("", true);
+        Ok(())
+    }
+
+    /// Expand sources into a flat list of archive entries.
+    fn collect_items(&self, sources: &[PathBuf], target: &Path) -> Result<Vec<ArchiveItem>, ArchiveError> {
+        let mut items = Vec::new();
+        for src in sources {
+            let base = src.parent().unwrap_or(src);
+            if !src.exists() {
+                return Err(ArchiveError::Failed(format!(
+                    "Source does not exist: {}",
+                    src.to_string_lossy()
+                )));
+            }
+
+            for entry in WalkDir::new(src).into_iter() {
+                if self.token.is_cancelled() {
+                    return Err(ArchiveError::Cancelled);
+                }
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(err) => {
+                        tracing::warn!("[Archive] WalkDir error: {err}");
+                        continue;
+                    }
+                };
+                // Don't try to zip the archive into itself.
+                if entry.path() == target {
+                    continue;
+                }
+                let rel = match entry.path().strip_prefix(base) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+                let name = rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if name.is_empty() {
+                    continue;
+                }
+
+                let file_type = entry.file_type();
+                if file_type.is_dir() {
+                    items.push(ArchiveItem {
+                        path: entry.into_path(),
+                        name: format!("{}/", name),
+                        is_dir: true,
+                        size: 0,
+                    });
+                } else if file_type.is_file() {
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    items.push(ArchiveItem {
+                        path: entry.into_path(),
+                        name,
+                        is_dir: false,
+                        size,
+                    });
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    fn emit_progress(&mut self, current: &str, force: bool) {
+        if !force && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:archive_progress",
+            ArchiveProgress {
+                op_id: self.op_id.to_string(),
+                files_done: self.files_done,
+                files_total: self.files_total,
+                bytes_done: self.bytes_done,
+                bytes_total: self.bytes_total,
+                current_file: current.to_string(),
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
+
+// ======================= Extraction =======================
+
+/// What to do when an extracted file already exists at the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Keep both: write "name (1).ext" next to the existing file.
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+    Tar,
+}
+
+impl ArchiveFormat {
+    fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExtractProgress {
+    op_id: String,
+    files_extracted: u64,
+    bytes_written: u64,
+    /// Uncompressed total when known up front (ZIP); None for tar streams.
+    bytes_total: Option<u64>,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExtractCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    destination: String,
+    files_extracted: u64,
+    files_skipped: u64,
+    /// Entries rejected by path traversal protection.
+    unsafe_entries: u64,
+    bytes_written: u64,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// Command from TS:
+/// invoke("extract_archive", { opId, archivePath, destination, conflictPolicy })
+///
+/// - `conflict_policy`: "skip" (default) | "overwrite" | "rename"
+/// - `destination` is created if missing.
+#[tauri::command]
+pub async fn extract_archive(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    archive_path: String,
+    destination: String,
+    conflict_policy: Option<ConflictPolicy>,
+) -> Result<(), String> {
+    let archive_path = guard.check(&archive_path)?;
+    let destination = guard.check(&destination)?;
+    if !archive_path.is_file() {
+        return Err(format!(
+            "Archive does not exist: {}",
+            archive_path.to_string_lossy()
+        ));
+    }
+    let format = ArchiveFormat::detect(&archive_path).ok_or_else(|| {
+        "Unsupported archive format (expected .zip, .tar.gz, .tgz or .tar)".to_string()
+    })?;
+    let policy = conflict_policy.unwrap_or_default();
+
+    let token = registry.register(&op_id, OperationKind::Extract);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut job = ExtractJob {
+            app: &app,
+            op_id: &op_id,
+            token: &token,
+            destination: &destination,
+            policy,
+            files_extracted: 0,
+            files_skipped: 0,
+            unsafe_entries: 0,
+            bytes_written: 0,
+            bytes_total: None,
+            last_emit: Instant::now(),
+        };
+
+        let res = fs::create_dir_all(&destination)
+            .map_err(ArchiveError::from)
+            .and_then(|_| match format {
+                ArchiveFormat::Zip => job.extract_zip(&archive_path),
+                ArchiveFormat::TarGz => File::open(&archive_path)
+                    .map_err(ArchiveError::from)
+                    .and_then(|mut f| {
+                        job.ensure_space(gzip_size_hint(&mut f)?)?;
+                        job.extract_tar(GzDecoder::new(f))
+                    }),
+                ArchiveFormat::Tar => File::open(&archive_path)
+                    .map_err(ArchiveError::from)
+                    .and_then(|f| {
+                        job.ensure_space(f.metadata()?.len())?;
+                        job.extract_tar(f)
+                    }),
+            });
+
+        let (status, error) = match res {
+            Ok(()) => ("ok", None),
+            Err(ArchiveError::Cancelled) => ("cancelled", Some(AppError::cancelled())),
+            Err(ArchiveError::Failed(msg)) => ("error", Some(AppError::new(ErrorCode::Io, msg))),
+            Err(ArchiveError::App(e)) => ("error", Some(e)),
+        };
+        let error_message = error.as_ref().filter(|_| status == "error").map(|e| e.message.clone());
+        audit::record(
+            &app,
+            AuditEntry::new(AuditAction::ExtractArchive)
+                .op_id(&op_id)
+                .sources(&[archive_path.to_string_lossy()])
+                .destination(destination.to_string_lossy())
+                .result(status, error_message.clone()),
+        );
+
+        let _ = app.emit(
+            "fu:extract_completed",
+            ExtractCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                destination: destination.to_string_lossy().to_string(),
+                files_extracted: job.files_extracted,
+                files_skipped: job.files_skipped,
+                unsafe_entries: job.unsafe_entries,
+                bytes_written: job.bytes_written,
+                error_message,
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Uncompressed size from a gzip file's trailer (ISIZE: the size of the
+/// last member mod 4 GiB), but at least the compressed length. Leaves the
+/// file positioned at the start.
+fn gzip_size_hint(file: &mut File) -> std::io::Result<u64> {
+    let len = file.metadata()?.len();
+    if len < 18 {
+        return Ok(len);
+    }
+    let mut trailer = [0u8; 4];
+    file.seek(SeekFrom::End(-4))?;
+    file.read_exact(&mut trailer)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(u64::from(u32::from_le_bytes(trailer)).max(len))
+}
+
+/// Validate an entry path from an archive and make it relative.
+/// Returns None for absolute paths, drive prefixes and any ".." component.
+fn sanitize_entry_path(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if out.as_os_str().is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+struct ExtractJob<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    token: &'a CancellationToken,
+    destination: &'a Path,
+    policy: ConflictPolicy,
+    files_extracted: u64,
+    files_skipped: u64,
+    unsafe_entries: u64,
+    bytes_written: u64,
+    bytes_total: Option<u64>,
+    last_emit: Instant,
+}
+
+impl ExtractJob<'_> {
+    fn ensure_space(&self, required: u64) -> Result<(), ArchiveError> {
+        ensure_free_space(self.destination, required).map_err(ArchiveError::App)
+    }
+
+    fn extract_zip(&mut self, archive_path: &Path) -> Result<(), ArchiveError> {
+        let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+
+        let mut total = 0u64;
+        for i in 0..archive.len() {
+            total += archive.by_index_raw(i)?.size();
+        }
+        self.bytes_total = Some(total);
+        self.ensure_space(total)?;
+
+        for i in 0..archive.len() {
+            if self.token.is_cancelled() {
+                return Err(ArchiveError::Cancelled);
+            }
+            let mut entry = archive.by_index(i)?;
+            // enclosed_name() already rejects "..", absolute paths and NULs.
+            let rel = match entry.enclosed_name().and_then(sanitize_entry_path) {
+                Some(p) => p,
+                None => {
+                    self.unsafe_entries += 1;
+                    continue;
+                }
+            };
+
+            if entry.is_dir() {
+                fs::create_dir_all(self.destination.join(&rel))?;
+                continue;
+            }
+
+            #[cfg(unix)]
+            let mode = entry.unix_mode();
+            let written = self.write_entry(&mut entry, &rel)?;
+
+            #[cfg(unix)]
+            if let (Some(path), Some(mode)) = (written, mode) {
+                use std::os::unix::fs::PermissionsExt;
+                let _ = fs::set_permissions(&path, fs::Permissions::from_mode(mode & 0o777));
+            }
+            #[cfg(not(unix))]
+            let _ = written;
+        }
+
+        self.emit_progress("", true);
+        Ok(())
+    }
+
+    fn extract_tar<R: Read>(&mut self, reader: R) -> Result<(), ArchiveError> {
+        let mut archive = tar::Archive::new(reader);
+
+        for entry in archive.entries()? {
+            if self.token.is_cancelled() {
+                return Err(ArchiveError::Cancelled);
+            }
+            let mut entry = entry?;
+            let rel = match entry.path().ok().and_then(|p| sanitize_entry_path(&p)) {
+                Some(p) => p,
+                None => {
+                    self.unsafe_entries += 1;
+                    continue;
+                }
+            };
+
+            let kind = entry.header().entry_type();
+            if kind.is_dir() {
+                fs::create_dir_all(self.destination.join(&rel))?;
+            } else if kind.is_file() {
+                self.write_entry(&mut entry, &rel)?;
+            } else {
+                // Links can point outside the destination; device files
+                // make no sense for a file manager. Skip them all.
+                self.unsafe_entries += 1;
+            }
+        }
+
+        self.emit_progress("", true);
+        Ok(())
+    }
+
+    /// Write one file entry, applying the conflict policy.
+    /// Returns the written path, or None if the entry was skipped.
+    fn write_entry(&mut self, reader: &mut dyn Read, rel: &Path) -> Result<Option<PathBuf>, ArchiveError> {
+        let mut out_path = self.destination.join(rel);
+        if let Ok(existing) = fs::symlink_metadata(&out_path) {
+            match self.policy {
+                ConflictPolicy::Skip => {
+                    self.files_skipped += 1;
+                    return Ok(None);
+                }
+                // Replace a symlink instead of writing through it,
+                // which could land outside the destination.
+                ConflictPolicy::Overwrite if existing.file_type().is_symlink() => {
+                    fs::remove_file(&out_path)?;
+                }
+                ConflictPolicy::Overwrite => {}
+                ConflictPolicy::Rename => out_path = unique_path(&out_path),
+            }
+        }
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let name = rel.to_string_lossy().to_string();
+        let mut out = File::create(&out_path)?;
+        let mut buf = vec![0u8; ARCHIVE_CHUNK_BYTES];
+        loop {
+            if self.token.is_cancelled() {
+                drop(out);
+                let _ = fs::remove_file(&out_path);
+                return Err(ArchiveError::Cancelled);
+            }
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            out.write_all(&buf[..n])?;
+            self.bytes_written += n as u64;
+            self.emit_progress(&name, false);
+        }
+
+        self.files_extracted += 1;
+        Ok(Some(out_path))
+    }
+
+    fn emit_progress(&mut self, current: &str, force: bool) {
+        if !force && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:extract_progress",
+            ExtractProgress {
+                op_id: self.op_id.to_string(),
+                files_extracted: self.files_extracted,
+                bytes_written: self.bytes_written,
+                bytes_total: self.bytes_total,
+                current_file: current.to_string(),
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
+
@@ -0,0 +1,329 @@
+// src-tauri/src/audit.rs
+//
+// Audit log of everything that changes files: copies, moves, deletes,
+// restores, renames, new files / folders, secure deletes, archives, media
+// organizing, image conversion and cleanups. For shared machines: who
+// (OS account), when, what, from where to where, and how it ended.
+//
+// One JSON object per line, appended once the operation has finished:
+//   <app data dir>/audit/audit.jsonl
+// When it reaches MAX_AUDIT_BYTES it is renamed to
+// audit-<ms since epoch>.jsonl (the time of the rotation) and a new file
+// starts; the newest MAX_ROTATED_FILES rotated files are kept. Entries are
+// never rewritten.
+//
+// Commands:
+//   - get_audit_log(from, to, limit): entries in a time range, newest first
+//   - export_audit_log(dest, format, from, to): CSV or JSON, oldest first
+//
+// A failed audit write is logged; it never fails the operation itself.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::task;
+
+use crate::error::{AppError, ErrorCode};
+use crate::path_guard::PathGuard;
+use crate::scan_report::{csv_field, ReportFormat};
+
+const AUDIT_DIR: &str = "audit";
+const AUDIT_FILE: &str = "audit.jsonl";
+const ROTATED_PREFIX: &str = "audit-";
+const MAX_AUDIT_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 20;
+
+/// Default for get_audit_log when `limit` is omitted.
+const DEFAULT_LIMIT: usize = 1000;
+
+const CSV_HEADER: &str = "time,user,action,opId,sources,destination,result,detail";
+
+/// Serializes appends and rotation.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    Copy,
+    Move,
+    Trash,
+    Delete,
+    RestoreFromTrash,
+    Rename,
+    BatchRename,
+    CreateFolder,
+    CreateFile,
+    SecureDelete,
+    CreateArchive,
+    ExtractArchive,
+    OrganizeMedia,
+    UndoOrganizeMedia,
+    ConvertImages,
+    Cleanup,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    /// Milliseconds since UNIX_EPOCH, when the operation finished.
+    pub time: u64,
+    /// OS account the app runs as.
+    pub user: String,
+    pub action: AuditAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub op_id: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// "ok" | "partial" | "cancelled" | "error"
+    pub result: String,
+    /// Error message, or what failed for "partial".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction) -> Self {
+        AuditEntry {
+            time: 0,
+            user: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default(),
+            action,
+            op_id: None,
+            sources: Vec::new(),
+            destination: None,
+            result: "ok".to_string(),
+            detail: None,
+        }
+    }
+
+    pub fn op_id(mut self, op_id: &str) -> Self {
+        self.op_id = Some(op_id.to_string());
+        self
+    }
+
+    pub fn sources<S: AsRef<str>>(mut self, sources: &[S]) -> Self {
+        self.sources = sources.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn destination(mut self, destination: impl Into<String>) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    pub fn result(mut self, result: &str, detail: Option<String>) -> Self {
+        self.result = result.to_string();
+        self.detail = detail;
+        self
+    }
+
+    /// "ok", or "error" with the message, from a command's result.
+    pub fn outcome<T, E: std::fmt::Display>(self, res: &Result<T, E>) -> Self {
+        match res {
+            Ok(_) => self.result("ok", None),
+            Err(e) => self.result("error", Some(e.to_string())),
+        }
+    }
+
+    /// "ok" / "partial" / "error" for a batch that reports per-item
+    /// failures: `failed` of `total` items.
+    pub fn batch_outcome(self, total: usize, failed: usize) -> Self {
+        match failed {
+            0 => self.result("ok", None),
+            n if n >= total => self.result("error", Some(format!("All {} failed", n))),
+            n => self.result("partial", Some(format!("{} of {} failed", n, total))),
+        }
+    }
+}
+
+/// Append `entry`, stamped with the current time.
+pub fn record(app: &AppHandle, mut entry: AuditEntry) {
+    entry.time = now_millis();
+    if let Err(e) = audit_dir(app).and_then(|dir| append(&dir, &entry)) {
+        tracing::warn!("[Audit] Failed to record {:?}: {}", entry.action, e.message);
+    }
+}
+
+/// Frontend can call:
+///   invoke<AuditEntry[]>('get_audit_log', { from: null, to: null, limit: 500 })
+///
+/// `from` / `to`: milliseconds since UNIX_EPOCH, inclusive, both optional.
+#[tauri::command]
+pub async fn get_audit_log(
+    app: AppHandle,
+    from: Option<u64>,
+    to: Option<u64>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, AppError> {
+    let dir = audit_dir(&app)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    task::spawn_blocking(move || {
+        let mut entries = read_entries(&dir, from, to)?;
+        entries.reverse();
+        entries.truncate(limit);
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Audit task failed: {}", e)))?
+}
+
+/// Frontend can call:
+///   invoke<number>('export_audit_log', { dest: 'C:\\Users\\me\\audit.csv', format: 'csv', from: null, to: null })
+///
+/// Writes the entries in the range oldest first and returns how many.
+/// CSV times are RFC 3339 in local time; sources are joined with "; ".
+#[tauri::command]
+pub async fn export_audit_log(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    dest: String,
+    format: ReportFormat,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<u64, AppError> {
+    let dest = guard.check(&dest)?;
+    let dir = audit_dir(&app)?;
+    task::spawn_blocking(move || {
+        let entries = read_entries(&dir, from, to)?;
+        write_export(&entries, format, &dest).map_err(|e| {
+            let _ = fs::remove_file(&dest);
+            AppError::io(&e).with_path(&dest)
+        })?;
+        Ok(entries.len() as u64)
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Audit export task failed: {}", e)))?
+}
+
+fn append(dir: &Path, entry: &AuditEntry) -> Result<(), AppError> {
+    let line = serde_json::to_string(entry)
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Failed to serialize audit entry: {}", e)))?;
+    let _lock = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    fs::create_dir_all(dir).map_err(|e| AppError::io(&e).with_path(dir))?;
+    let path = dir.join(AUDIT_FILE);
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_AUDIT_BYTES) {
+        rotate(dir, &path)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| AppError::io(&e).with_path(&path))?;
+    writeln!(file, "{}", line).map_err(|e| AppError::io(&e).with_path(&path))
+}
+
+fn rotate(dir: &Path, path: &Path) -> Result<(), AppError> {
+    let rotated = dir.join(format!("{}{:013}.jsonl", ROTATED_PREFIX, now_millis()));
+    fs::rename(path, &rotated).map_err(|e| AppError::io(&e).with_path(path))?;
+    let files = rotated_files(dir);
+    for old in files.iter().take(files.len().saturating_sub(MAX_ROTATED_FILES)) {
+        if let Err(e) = fs::remove_file(&old.1) {
+            tracing::warn!("[Audit] Failed to remove old audit file {:?}: {e}", old.1);
+        }
+    }
+    Ok(())
+}
+
+/// Rotated files with their rotation time, oldest first.
+fn rotated_files(dir: &Path) -> Vec<(u64, PathBuf)> {
+    let mut files: Vec<(u64, PathBuf)> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let time = name.strip_prefix(ROTATED_PREFIX)?.strip_suffix(".jsonl")?.parse().ok()?;
+            Some((time, entry.path()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Entries with from <= time <= to, oldest first. Rotated files that
+/// ended before `from` aren't read.
+fn read_entries(dir: &Path, from: Option<u64>, to: Option<u64>) -> Result<Vec<AuditEntry>, AppError> {
+    let _lock = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut files: Vec<PathBuf> = rotated_files(dir)
+        .into_iter()
+        .filter(|(rotated_at, _)| from.is_none_or(|from| *rotated_at >= from))
+        .map(|(_, path)| path)
+        .collect();
+    files.push(dir.join(AUDIT_FILE));
+
+    let mut entries = Vec::new();
+    for path in files {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(AppError::io(&e).with_path(&path)),
+        };
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| AppError::io(&e).with_path(&path))?;
+            match serde_json::from_str::<AuditEntry>(&line) {
+                Ok(entry) if from.is_none_or(|t| entry.time >= t) && to.is_none_or(|t| entry.time <= t) => {
+                    entries.push(entry)
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("[Audit] Bad audit line in {:?}: {e}", path),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn write_export(entries: &[AuditEntry], format: ReportFormat, dest: &Path) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(dest)?);
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut out, entries)?,
+        ReportFormat::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            for entry in entries {
+                let time = DateTime::from_timestamp_millis(entry.time as i64)
+                    .map(|t| t.with_timezone(&Local).to_rfc3339_opts(SecondsFormat::Secs, false))
+                    .unwrap_or_default();
+                let action = serde_json::to_value(entry.action)
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{}",
+                    csv_field(&time),
+                    csv_field(&entry.user),
+                    csv_field(&action),
+                    csv_field(entry.op_id.as_deref().unwrap_or("")),
+                    csv_field(&entry.sources.join("; ")),
+                    csv_field(entry.destination.as_deref().unwrap_or("")),
+                    csv_field(&entry.result),
+                    csv_field(entry.detail.as_deref().unwrap_or("")),
+                )?;
+            }
+        }
+    }
+    out.flush()
+}
+
+fn audit_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("App data dir error: {}", e)))?;
+    Ok(dir.join(AUDIT_DIR))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,97 @@
+// src-tauri/src/background_priority.rs
+//
+// Low-priority IO mode: with settings `system.background_priority` on,
+// folder scans, duplicate scans, checksums, manifests and search index
+// builds run at background CPU and IO priority, so a full-drive scan no
+// longer makes the machine feel sluggish. They just take longer while
+// something else needs the disk.
+//
+//   - Windows: SetThreadPriority(THREAD_MODE_BACKGROUND_BEGIN) (low CPU,
+//              IO and memory priority)
+//   - Linux:   nice 19 and the idle IO class (ioprio_set) for the thread
+//   - macOS:   QOS_CLASS_BACKGROUND (also throttles disk IO)
+//   - other:   no-op
+//
+// Unix can't raise a thread's priority back without privileges, so the
+// work never runs on a shared pool thread: spawn_blocking starts its own
+// thread for it, and rayon pools lower their workers in start_handler
+// (the pools are per operation and go away with it).
+
+use tauri::async_runtime::{self, JoinHandle};
+use tauri::{AppHandle, Manager};
+
+use crate::settings::SettingsState;
+
+/// Whether scans and hashing should run at background priority.
+pub fn enabled(app: &AppHandle) -> bool {
+    app.try_state::<SettingsState>().is_some_and(|s| {
+        let system = s.system_handle();
+        let enabled = system.read().unwrap_or_else(|e| e.into_inner()).background_priority;
+        enabled
+    })
+}
+
+/// Like async_runtime::spawn_blocking; with `low` the closure runs on a
+/// thread of its own at background priority.
+pub fn spawn_blocking<F, R>(low: bool, f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    async_runtime::spawn_blocking(move || {
+        if !low {
+            return f();
+        }
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                lower_current_thread();
+                f()
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })
+    })
+}
+
+/// Start handler for rayon pools: `.start_handler(move |_| if low { lower_current_thread() })`.
+#[cfg(windows)]
+pub fn lower_current_thread() {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN};
+
+    // SAFETY: GetCurrentThread returns a pseudo handle that needs no closing.
+    if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN) } == 0 {
+        tracing::warn!("[BackgroundPriority] {}", std::io::Error::last_os_error());
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn lower_current_thread() {
+    // ioprio_set(2) has no libc wrapper.
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+    // SAFETY: plain syscalls on the calling thread's id.
+    unsafe {
+        let tid = libc::gettid();
+        if libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, 19) != 0 {
+            tracing::warn!("[BackgroundPriority] nice: {}", std::io::Error::last_os_error());
+        }
+        let prio = IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT;
+        if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, prio) != 0 {
+            tracing::warn!("[BackgroundPriority] ioprio: {}", std::io::Error::last_os_error());
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn lower_current_thread() {
+    // SAFETY: only changes the calling thread's QoS class.
+    let res = unsafe { libc::pthread_set_qos_class_self_np(libc::qos_class_t::QOS_CLASS_BACKGROUND, 0) };
+    if res != 0 {
+        tracing::warn!("[BackgroundPriority] {}", std::io::Error::from_raw_os_error(res));
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "android", target_os = "macos")))]
+pub fn lower_current_thread() {}
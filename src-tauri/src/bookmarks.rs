@@ -0,0 +1,247 @@
+// src-tauri/src/bookmarks.rs
+//
+// Bookmarks / favorite locations for the sidebar.
+// Persisted at <app config dir>/bookmarks.json as a JSON array:
+//
+//   [
+//     { "name": "Projects", "path": "D:\\Projects", "icon": "folder-code", "order": 0 },
+//     { "name": "Downloads", "path": "C:\\Users\\me\\Downloads", "order": 1 }
+//   ]
+//
+// A path can be bookmarked once; the path is the bookmark's identity for
+// remove / reorder. Targets are not checked on load (a USB stick may just
+// be unplugged); list_bookmarks reports `exists: false` for those instead.
+// Every change emits `fu:bookmarks_changed` with the new list.
+//
+// State: .manage(Bookmarks::load(app.handle()))
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::path_guard::PathGuard;
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub name: String,
+    pub path: String,
+    /// Icon id understood by the frontend; None = default folder/file icon.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Position in the sidebar, 0-based and contiguous.
+    #[serde(default)]
+    pub order: u32,
+}
+
+/// Bookmark plus whether its target is still there.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookmarkStatus {
+    #[serde(flatten)]
+    pub bookmark: Bookmark,
+    pub exists: bool,
+    pub is_dir: bool,
+}
+
+#[derive(Default)]
+pub struct Bookmarks {
+    list: RwLock<Vec<Bookmark>>,
+}
+
+impl Bookmarks {
+    /// Load the persisted list; a missing or broken file means no bookmarks.
+    pub fn load(app: &AppHandle) -> Self {
+        let mut list: Vec<Bookmark> = bookmarks_path(app)
+            .ok()
+            .filter(|p| p.exists())
+            .and_then(|p| {
+                let data = fs::read_to_string(&p).ok()?;
+                match serde_json::from_str(&data) {
+                    Ok(list) => Some(list),
+                    Err(e) => {
+                        tracing::warn!("[Bookmarks] Invalid {:?} ({e}), ignoring", p);
+                        None
+                    }
+                }
+            })
+            .unwrap_or_default();
+        list.sort_by_key(|b| b.order);
+        renumber(&mut list);
+        Bookmarks {
+            list: RwLock::new(list),
+        }
+    }
+
+    fn statuses(&self) -> Vec<BookmarkStatus> {
+        self.list
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|b| {
+                let meta = fs::metadata(&b.path).ok();
+                BookmarkStatus {
+                    bookmark: b.clone(),
+                    exists: meta.is_some(),
+                    is_dir: meta.is_some_and(|m| m.is_dir()),
+                }
+            })
+            .collect()
+    }
+
+    /// Apply `change` to a copy of the list, persist it, then swap it in
+    /// and emit `fu:bookmarks_changed`.
+    fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut Vec<Bookmark>) -> Result<(), String>,
+    ) -> Result<Vec<BookmarkStatus>, String> {
+        {
+            let mut list = self.list.write().unwrap_or_else(|e| e.into_inner());
+            let mut next = list.clone();
+            change(&mut next)?;
+            renumber(&mut next);
+            save(app, &next)?;
+            *list = next;
+        }
+        let statuses = self.statuses();
+        let _ = app.emit("fu:bookmarks_changed", &statuses);
+        Ok(statuses)
+    }
+}
+
+fn renumber(list: &mut [Bookmark]) {
+    for (i, b) in list.iter_mut().enumerate() {
+        b.order = i as u32;
+    }
+}
+
+fn bookmarks_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("App config dir error: {}", e))?;
+    Ok(dir.join(BOOKMARKS_FILE))
+}
+
+fn save(app: &AppHandle, list: &[Bookmark]) -> Result<(), String> {
+    let path = bookmarks_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config dir {:?}: {}", parent, e))?;
+    }
+    let data = serde_json::to_string_pretty(list)
+        .map_err(|e| format!("Failed to serialize bookmarks: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write bookmarks: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace bookmarks file: {}", e))?;
+    Ok(())
+}
+
+/// Bookmarks are matched case-insensitively where the filesystem usually is.
+fn same_path(a: &str, b: &str) -> bool {
+    if cfg!(any(windows, target_os = "macos")) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// All bookmarks in sidebar order, with `exists: false` for missing targets.
+///
+/// Frontend can call:
+///   invoke<BookmarkStatus[]>('list_bookmarks')
+#[tauri::command]
+pub fn list_bookmarks(bookmarks: State<'_, Bookmarks>) -> Vec<BookmarkStatus> {
+    bookmarks.statuses()
+}
+
+/// Append a bookmark. The target must exist; `name` defaults to the last
+/// path component.
+///
+/// Frontend can call:
+///   invoke<BookmarkStatus[]>('add_bookmark', { path, name: 'Projects', icon: null })
+#[tauri::command]
+pub fn add_bookmark(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    bookmarks: State<'_, Bookmarks>,
+    path: String,
+    name: Option<String>,
+    icon: Option<String>,
+) -> Result<Vec<BookmarkStatus>, String> {
+    let checked = guard.check(&path)?;
+    fs::metadata(&checked).map_err(|e| format!("Cannot bookmark {}: {}", path, e))?;
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| {
+            Path::new(&path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| path.clone());
+
+    bookmarks.update(&app, |list| {
+        if list.iter().any(|b| same_path(&b.path, &path)) {
+            return Err(format!("Already bookmarked: {}", path));
+        }
+        list.push(Bookmark {
+            name,
+            path: path.clone(),
+            icon,
+            order: 0,
+        });
+        Ok(())
+    })
+}
+
+/// Remove the bookmark for `path`.
+///
+/// Frontend can call:
+///   invoke<BookmarkStatus[]>('remove_bookmark', { path })
+#[tauri::command]
+pub fn remove_bookmark(
+    app: AppHandle,
+    bookmarks: State<'_, Bookmarks>,
+    path: String,
+) -> Result<Vec<BookmarkStatus>, String> {
+    bookmarks.update(&app, |list| {
+        let before = list.len();
+        list.retain(|b| !same_path(&b.path, &path));
+        if list.len() == before {
+            return Err(format!("Not bookmarked: {}", path));
+        }
+        Ok(())
+    })
+}
+
+/// New sidebar order, given as bookmark paths. Bookmarks not in `paths`
+/// keep their relative order after the listed ones.
+///
+/// Frontend can call:
+///   invoke<BookmarkStatus[]>('reorder_bookmarks', { paths: [pathB, pathA] })
+#[tauri::command]
+pub fn reorder_bookmarks(
+    app: AppHandle,
+    bookmarks: State<'_, Bookmarks>,
+    paths: Vec<String>,
+) -> Result<Vec<BookmarkStatus>, String> {
+    bookmarks.update(&app, |list| {
+        let rank = |b: &Bookmark| {
+            paths
+                .iter()
+                .position(|p| same_path(p, &b.path))
+                .unwrap_or(usize::MAX)
+        };
+        // Stable sort: unlisted bookmarks (usize::MAX) keep their order.
+        list.sort_by_key(|b| rank(b));
+        Ok(())
+    })
+}
@@ -0,0 +1,339 @@
+// src-tauri/src/catalog.rs
+//
+// User catalog: tags, a color label and a free-text note per file or
+// folder, stored in SQLite under the app config dir:
+//   <app config dir>/catalog.sqlite3
+//
+// Rows are keyed by canonical path (path_guard::resolve), so the same file
+// reached through a symlink or "..": one entry. Renames done through the
+// app (rename_entry, batch_rename) re-key the entry and, for folders,
+// everything below it. Renames done outside the app orphan the entry;
+// query_by_tag reports those with `exists: false`.
+//
+// Commands:
+//   - set_tags(path, tags, color?, note?)
+//   - get_tags(path)
+//   - query_by_tag(tag)
+
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::path_guard::{resolve, simplified, PathGuard};
+
+const CATALOG_FILE: &str = "catalog.sqlite3";
+/// 2: keys in the plain Windows form (path_guard::simplified), not \\?\.
+const SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    path       TEXT PRIMARY KEY,
+    color      TEXT,
+    note       TEXT,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tags (
+    path TEXT NOT NULL REFERENCES entries(path) ON UPDATE CASCADE ON DELETE CASCADE,
+    tag  TEXT NOT NULL,
+    PRIMARY KEY (path, tag)
+);
+CREATE INDEX IF NOT EXISTS tags_by_tag ON tags(tag);
+";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub path: String,
+    pub tags: Vec<String>,
+    pub color: Option<String>,
+    pub note: Option<String>,
+    /// Milliseconds since UNIX_EPOCH; 0 when the path has no entry.
+    pub updated_at: u64,
+    /// False when the file was moved/deleted outside the app.
+    pub exists: bool,
+}
+
+/// SQLite handle in Tauri managed state (cheap Arc clone for workers).
+#[derive(Clone)]
+pub struct Catalog {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Catalog {
+    /// Open (or create) the catalog. Falls back to an in-memory database
+    /// so the app still starts with an unreadable file.
+    pub fn load(app: &AppHandle) -> Self {
+        let conn = catalog_path(app)
+            .and_then(|path| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                Connection::open(&path).map_err(|e| e.to_string())
+            })
+            .and_then(|conn| init_schema(&conn).map(|_| conn))
+            .unwrap_or_else(|e| {
+                tracing::error!("[Catalog] Failed to open catalog, using in-memory: {e}");
+                let conn = Connection::open_in_memory().expect("in-memory SQLite");
+                let _ = init_schema(&conn);
+                conn
+            });
+        Catalog {
+            conn: Arc::new(Mutex::new(conn)),
+        }
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Move the entry at `from` (and, for folders, every entry below it)
+    /// to `to`. Both paths must already be canonical.
+    pub fn rekey(&self, from: &Path, to: &Path) {
+        self.rekey_all(&[(from.to_path_buf(), to.to_path_buf())]);
+    }
+
+    /// rekey() for a batch, in one transaction. Goes through temporary keys
+    /// so swaps (a -> b, b -> a) don't collide on the primary key.
+    pub fn rekey_all(&self, moves: &[(PathBuf, PathBuf)]) {
+        let moves: Vec<(String, String)> = moves
+            .iter()
+            .map(|(f, t)| (f.to_string_lossy().to_string(), t.to_string_lossy().to_string()))
+            .filter(|(f, t)| f != t)
+            .collect();
+        if moves.is_empty() {
+            return;
+        }
+
+        let mut conn = self.conn();
+        let res = (|| -> rusqlite::Result<()> {
+            let tx = conn.transaction()?;
+            for (n, (from, _)) in moves.iter().enumerate() {
+                move_prefix(&tx, from, &temp_key(n))?;
+            }
+            for (n, (_, to)) in moves.iter().enumerate() {
+                move_prefix(&tx, &temp_key(n), to)?;
+            }
+            tx.commit()
+        })();
+        if let Err(e) = res {
+            tracing::warn!("[Catalog] Failed to re-key {} renamed entries: {e}", moves.len());
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<CatalogEntry, String> {
+        let conn = self.conn();
+        let row = conn
+            .query_row(
+                "SELECT color, note, updated_at FROM entries WHERE path = ?1",
+                params![key],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get::<_, i64>(2)?)),
+            )
+            .optional()
+            .map_err(db_error)?;
+        let tags = load_tags(&conn, key)?;
+        let (color, note, updated_at) = row.unwrap_or((None, None, 0));
+        Ok(CatalogEntry {
+            path: key.to_string(),
+            tags,
+            color,
+            note,
+            updated_at: updated_at as u64,
+            exists: Path::new(key).exists(),
+        })
+    }
+}
+
+/// Placeholder key that can't clash with a real (absolute) path.
+/// (No NUL trick: SQLite's length() stops at the first NUL.)
+fn temp_key(n: usize) -> String {
+    format!("rekey:{}", n)
+}
+
+/// `from` itself and everything below it now lives under `to`.
+fn move_prefix(conn: &Connection, from: &str, to: &str) -> rusqlite::Result<usize> {
+    let prefix = format!("{}{}", from.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR);
+    conn.execute(
+        "UPDATE entries SET path = ?2 || substr(path, length(?1) + 1)
+         WHERE path = ?1 OR substr(path, 1, length(?3)) = ?3",
+        params![from, to, prefix],
+    )
+}
+
+fn catalog_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("App config dir error: {}", e))?;
+    Ok(dir.join(CATALOG_FILE))
+}
+
+fn init_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("PRAGMA foreign_keys = ON; PRAGMA journal_mode = WAL;")
+        .map_err(db_error)?;
+    conn.execute_batch(SCHEMA).map_err(db_error)?;
+    let version: i32 = conn
+        .pragma_query_value(None, "user_version", |r| r.get(0))
+        .map_err(db_error)?;
+    if version < 2 {
+        simplify_keys(conn).map_err(db_error)?;
+    }
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(db_error)?;
+    Ok(())
+}
+
+/// Re-key \\?\ entries to the plain form resolve() now returns. Tags
+/// follow via ON UPDATE CASCADE; a key that already exists in plain
+/// form wins and the verbatim duplicate is left orphaned.
+fn simplify_keys(conn: &Connection) -> rusqlite::Result<()> {
+    let keys = conn
+        .prepare(r"SELECT path FROM entries WHERE substr(path, 1, 4) = '\\?\'")?
+        .query_map([], |r| r.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    for key in keys {
+        let plain = simplified(PathBuf::from(&key)).to_string_lossy().to_string();
+        if plain != key {
+            conn.execute("UPDATE OR IGNORE entries SET path = ?2 WHERE path = ?1", params![key, plain])?;
+        }
+    }
+    Ok(())
+}
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("Catalog error: {}", e)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn load_tags(conn: &Connection, key: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM tags WHERE path = ?1 ORDER BY tag")
+        .map_err(db_error)?;
+    let tags = stmt
+        .query_map(params![key], |r| r.get(0))
+        .map_err(db_error)?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(db_error)?;
+    Ok(tags)
+}
+
+/// Trimmed, de-duplicated, empty tags dropped.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    out.sort();
+    out.dedup();
+    out
+}
+
+/// Replace the tags of `path`; `color` / `note` are only changed when
+/// given ("" clears them). An entry with nothing left is removed.
+///
+/// Frontend can call:
+///   invoke<CatalogEntry>('set_tags', { path, tags: ['invoice', '2026'], color: '#e5484d', note: null })
+#[tauri::command]
+pub fn set_tags(
+    guard: State<'_, PathGuard>,
+    catalog: State<'_, Catalog>,
+    path: String,
+    tags: Vec<String>,
+    color: Option<String>,
+    note: Option<String>,
+) -> Result<CatalogEntry, String> {
+    let key = guard.check(&path)?.to_string_lossy().to_string();
+    let tags = normalize_tags(tags);
+    let blank_to_none = |v: String| {
+        let v = v.trim().to_string();
+        (!v.is_empty()).then_some(v)
+    };
+
+    {
+        let mut conn = catalog.conn();
+        let tx = conn.transaction().map_err(db_error)?;
+
+        let current: Option<(Option<String>, Option<String>)> = tx
+            .query_row(
+                "SELECT color, note FROM entries WHERE path = ?1",
+                params![key],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()
+            .map_err(db_error)?;
+        let (cur_color, cur_note) = current.unwrap_or((None, None));
+        let color = match color {
+            Some(c) => blank_to_none(c),
+            None => cur_color,
+        };
+        let note = match note {
+            Some(n) => blank_to_none(n),
+            None => cur_note,
+        };
+
+        if tags.is_empty() && color.is_none() && note.is_none() {
+            tx.execute("DELETE FROM entries WHERE path = ?1", params![key])
+                .map_err(db_error)?;
+        } else {
+            tx.execute(
+                "INSERT INTO entries (path, color, note, updated_at) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(path) DO UPDATE SET color = ?2, note = ?3, updated_at = ?4",
+                params![key, color, note, now_millis()],
+            )
+            .map_err(db_error)?;
+            tx.execute("DELETE FROM tags WHERE path = ?1", params![key])
+                .map_err(db_error)?;
+            for tag in &tags {
+                tx.execute(
+                    "INSERT INTO tags (path, tag) VALUES (?1, ?2)",
+                    params![key, tag],
+                )
+                .map_err(db_error)?;
+            }
+        }
+        tx.commit().map_err(db_error)?;
+    }
+
+    catalog.get(&key)
+}
+
+/// Tags / color / note of one path (empty entry if never tagged).
+///
+/// Frontend can call:
+///   invoke<CatalogEntry>('get_tags', { path })
+#[tauri::command]
+pub fn get_tags(catalog: State<'_, Catalog>, path: String) -> Result<CatalogEntry, String> {
+    let key = resolve(Path::new(&path))?.to_string_lossy().to_string();
+    catalog.get(&key)
+}
+
+/// Every entry carrying `tag` (exact match), sorted by path.
+///
+/// Frontend can call:
+///   invoke<CatalogEntry[]>('query_by_tag', { tag: 'invoice' })
+#[tauri::command]
+pub fn query_by_tag(catalog: State<'_, Catalog>, tag: String) -> Result<Vec<CatalogEntry>, String> {
+    let paths: Vec<String> = {
+        let conn = catalog.conn();
+        let mut stmt = conn
+            .prepare("SELECT path FROM tags WHERE tag = ?1 ORDER BY path")
+            .map_err(db_error)?;
+        let rows = stmt
+            .query_map(params![tag.trim()], |r| r.get(0))
+            .map_err(db_error)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(db_error)?;
+        rows
+    };
+    paths.iter().map(|p| catalog.get(p)).collect()
+}
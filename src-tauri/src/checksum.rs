@@ -0,0 +1,206 @@
+// src-tauri/src/checksum.rs
+//
+// File checksums (MD5 / SHA-256 / BLAKE3) for verifying downloads.
+// The command hashes the files one after another on a blocking thread,
+// streams `fu:checksum_progress` while large files are read, and returns
+// the hex digests once everything is done. The op_id is registered in
+// OperationRegistry so cancel_operation can stop it mid-file.
+
+use crate::background_priority;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+
+const READ_CHUNK_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    Md5,
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ChecksumProgress {
+    op_id: String,
+    path: String,
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: u64,
+    files_total: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChecksum {
+    pub path: String,
+    pub algorithm: ChecksumAlgorithm,
+    /// Lowercase hex digest; None if the file couldn't be read.
+    pub hash: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Command from TS:
+/// invoke<FileChecksum[]>("compute_checksum", { opId, paths, algorithm: "sha256" })
+///
+/// Resolves when every file was hashed; a file that can't be read gets an
+/// `error` entry instead of failing the whole call. Rejects with
+/// "cancelled" if cancel_operation(opId) was called.
+#[tauri::command]
+pub async fn compute_checksum(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    paths: Vec<String>,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> Result<Vec<FileChecksum>, String> {
+    let files = guard.check_all(&paths)?;
+    if paths.is_empty() {
+        return Err("No files given".to_string());
+    }
+    let algorithm = algorithm.unwrap_or_default();
+
+    let token = registry.register(&op_id, OperationKind::Checksum);
+    let registry = registry.inner().clone();
+
+    let worker_op_id = op_id.clone();
+    let worker_token = token.clone();
+    let low = background_priority::enabled(&app);
+    let res = background_priority::spawn_blocking(low, move || {
+        run_checksums_blocking(&app, &worker_op_id, &paths, &files, algorithm, &worker_token)
+    })
+    .await;
+
+    registry.finish(&op_id, &token);
+
+    match res {
+        Ok(Some(results)) => Ok(results),
+        Ok(None) => Err("cancelled".to_string()),
+        Err(e) => Err(format!("Checksum worker failed: {}", e)),
+    }
+}
+
+/// Hashes `files`, reported under the matching `paths`. Returns None
+/// when cancelled.
+fn run_checksums_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    paths: &[String],
+    files: &[PathBuf],
+    algorithm: ChecksumAlgorithm,
+    token: &CancellationToken,
+) -> Option<Vec<FileChecksum>> {
+    let mut results = Vec::with_capacity(paths.len());
+    let mut progress = ChecksumProgress {
+        op_id: op_id.to_string(),
+        path: String::new(),
+        bytes_done: 0,
+        bytes_total: 0,
+        files_done: 0,
+        files_total: paths.len() as u64,
+    };
+
+    for (path, file) in paths.iter().zip(files) {
+        progress.path = path.clone();
+        let (hash, error) = match hash_file(app, file, algorithm, token, &mut progress) {
+            Ok(Some(hash)) => (Some(hash), None),
+            Ok(None) => return None,
+            Err(e) => (None, Some(e.to_string())),
+        };
+        results.push(FileChecksum {
+            path: path.clone(),
+            algorithm,
+            hash,
+            error,
+        });
+        progress.files_done += 1;
+    }
+
+    Some(results)
+}
+
+enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Md5(h) => to_hex(&h.finalize()),
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Hash one file in chunks. Ok(None) = cancelled.
+fn hash_file(
+    app: &AppHandle,
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    token: &CancellationToken,
+    progress: &mut ChecksumProgress,
+) -> std::io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    progress.bytes_total = file.metadata()?.len();
+    progress.bytes_done = 0;
+
+    let mut hasher = Hasher::new(algorithm);
+    let mut buf = vec![0u8; READ_CHUNK_BYTES];
+    let mut last_emit = Instant::now();
+
+    loop {
+        if token.is_cancelled() {
+            return Ok(None);
+        }
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        progress.bytes_done += n as u64;
+
+        if last_emit.elapsed().as_millis() >= 100 {
+            let _ = app.emit("fu:checksum_progress", progress.clone());
+            last_emit = Instant::now();
+        }
+    }
+
+    Ok(Some(hasher.finalize_hex()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,479 @@
+// src-tauri/src/cleanup.rs
+//
+// Cleanup scanner: finds clutter under a root and lets the user remove the
+// findings they confirm.
+//
+// start_cleanup_scan walks the root once (symlinks not followed) and
+// reports, in batches of `fu:cleanup_findings`:
+//   - emptyDir:   folders with nothing but (possibly nested) empty folders;
+//                 only the topmost such folder is reported
+//   - emptyFile:  zero-byte files, except placeholders that are empty on
+//                 purpose (.gitkeep, __init__.py, ...)
+//   - brokenLink: symlinks whose target is gone, and on Windows .lnk
+//                 shortcuts pointing at a missing local path (shortcuts to
+//                 network shares or unmounted drives are left alone)
+//   - tempFile:   leftovers of interrupted downloads / copies / editors
+//                 (*.tmp, *.crdownload, ~$doc.docx, our own *.fu-partial, ...)
+//                 not modified for `tempMinAgeHours`, so files still in use
+//                 are not offered
+// then `fu:cleanup_scan_completed` with per-kind counts.
+//
+// apply_cleanup re-checks every selected finding (a folder may have been
+// filled since the scan) and moves it to the trash, or removes it with
+// `permanent: true`, recording the run in the audit log (audit.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::delete_ops::{remove_permanently, DeleteResult};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::FileOpFailure;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+/// Findings per `fu:cleanup_findings` event (also flushed every 100ms).
+const FINDINGS_BATCH: usize = 200;
+
+/// Empty files that mark something (a kept folder, a Python package, ...).
+const KEEP_EMPTY_FILES: &[&str] = &[".gitkeep", ".keep", ".nomedia", "__init__.py", "py.typed"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CleanupKind {
+    EmptyDir,
+    EmptyFile,
+    BrokenLink,
+    TempFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupFinding {
+    pub path: String,
+    pub kind: CleanupKind,
+    /// Bytes freed by removing it (0 for folders, empty files and links).
+    #[serde(default)]
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CleanupScanOptions {
+    pub empty_dirs: bool,
+    pub empty_files: bool,
+    pub broken_links: bool,
+    pub temp_files: bool,
+    /// Temp files modified more recently than this are skipped.
+    pub temp_min_age_hours: u64,
+}
+
+impl Default for CleanupScanOptions {
+    fn default() -> Self {
+        CleanupScanOptions {
+            empty_dirs: true,
+            empty_files: true,
+            broken_links: true,
+            temp_files: true,
+            temp_min_age_hours: 24,
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CleanupFindings {
+    op_id: String,
+    findings: Vec<CleanupFinding>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CleanupScanProgress {
+    op_id: String,
+    entries_seen: u64,
+    findings: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct CleanupCounts {
+    empty_dirs: u64,
+    empty_files: u64,
+    broken_links: u64,
+    temp_files: u64,
+    /// Total size of the temp files found.
+    reclaimable_bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CleanupScanCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    counts: CleanupCounts,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// Command from TS:
+/// invoke("start_cleanup_scan", { opId, path, options: { emptyDirs: true, emptyFiles: true, brokenLinks: true, tempFiles: true, tempMinAgeHours: 24 } })
+#[tauri::command]
+pub async fn start_cleanup_scan(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+    options: Option<CleanupScanOptions>,
+) -> Result<(), AppError> {
+    let root = guard.check(&path)?;
+    let options = options.unwrap_or_default();
+
+    let token = registry.register(&op_id, OperationKind::CleanupScan);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut scan = CleanupScan {
+            app: &app,
+            op_id: &op_id,
+            options: &options,
+            token: &token,
+            counts: CleanupCounts::default(),
+            batch: Vec::new(),
+            entries_seen: 0,
+            last_emit: Instant::now(),
+        };
+        let res = scan.run(&root);
+        scan.flush(true);
+
+        let error = match &res {
+            Ok(()) => None,
+            Err(None) => Some(AppError::cancelled()),
+            Err(Some(e)) => Some(e.clone()),
+        };
+        let status = match &res {
+            Ok(()) => "ok",
+            Err(None) => "cancelled",
+            Err(Some(_)) => "error",
+        };
+        let _ = app.emit(
+            "fu:cleanup_scan_completed",
+            CleanupScanCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                counts: scan.counts,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Remove confirmed findings; each is re-checked first and reported as
+/// failed if it no longer qualifies.
+///
+/// Frontend can call:
+///   invoke<DeleteResult>('apply_cleanup', { selection: findings, permanent: false })
+#[tauri::command]
+pub async fn apply_cleanup(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    selection: Vec<CleanupFinding>,
+    permanent: Option<bool>,
+) -> Result<DeleteResult, AppError> {
+    let targets = selection
+        .iter()
+        .map(|f| guard.check_entry(&f.path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let permanent = permanent.unwrap_or(false);
+
+    task::spawn_blocking(move || {
+        let mut result = DeleteResult {
+            deleted: Vec::new(),
+            failed: Vec::new(),
+            permanent,
+        };
+        let paths: Vec<&str> = selection.iter().map(|f| f.path.as_str()).collect();
+        let audit = AuditEntry::new(AuditAction::Cleanup).sources(&paths);
+        let total = selection.len();
+
+        for (finding, target) in selection.into_iter().zip(targets) {
+            let path = target.as_path();
+            let res = still_qualifies(path, finding.kind).and_then(|_| {
+                if permanent {
+                    remove_permanently(path).map_err(|e| AppError::io(&e))
+                } else {
+                    trash::delete(path).map_err(|e| AppError::new(ErrorCode::Io, e.to_string()))
+                }
+            });
+            match res {
+                Ok(()) => result.deleted.push(finding.path),
+                Err(error) => result.failed.push(FileOpFailure::new(finding.path, error)),
+            }
+        }
+
+        audit::record(&app, audit.batch_outcome(total, result.failed.len()));
+        result
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Cleanup task failed: {}", e)))
+}
+
+fn still_qualifies(path: &Path, kind: CleanupKind) -> Result<(), AppError> {
+    let meta = fs::symlink_metadata(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    let ok = match kind {
+        CleanupKind::EmptyDir => meta.is_dir() && only_empty_dirs(path),
+        CleanupKind::EmptyFile => meta.is_file() && meta.len() == 0,
+        CleanupKind::BrokenLink => is_broken_link(path, &meta),
+        CleanupKind::TempFile => meta.is_file(),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(AppError::new(ErrorCode::InvalidInput, "Changed since the scan, not removed").with_path(path))
+    }
+}
+
+/// True if the tree under `dir` holds no files or links at all.
+fn only_empty_dirs(dir: &Path) -> bool {
+    WalkDir::new(dir)
+        .into_iter()
+        .all(|entry| entry.is_ok_and(|e| e.file_type().is_dir()))
+}
+
+fn is_broken_link(path: &Path, meta: &fs::Metadata) -> bool {
+    if meta.file_type().is_symlink() {
+        return fs::metadata(path).is_err();
+    }
+    meta.is_file() && is_broken_shortcut(path)
+}
+
+/// Leftover of an interrupted download, copy or editor session.
+fn is_temp_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    const SUFFIXES: &[&str] = &[".tmp", ".temp", ".crdownload", ".part", ".partial", ".download", ".fu-partial"];
+    SUFFIXES.iter().any(|s| lower.ends_with(s))
+        // Office owner files ("~$report.docx"), LibreOffice locks (".~lock.x.odt#")
+        || lower.starts_with("~$")
+        || (lower.starts_with(".~lock.") && lower.ends_with('#'))
+}
+
+/// Windows shell shortcut whose local target is gone. Shortcuts to network
+/// paths, or to drives that aren't mounted right now, are not "broken".
+#[cfg(windows)]
+fn is_broken_shortcut(path: &Path) -> bool {
+    let is_lnk = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("lnk"));
+    if !is_lnk {
+        return false;
+    }
+    let Some(target) = fs::read(path).ok().and_then(|data| lnk_local_target(&data)) else {
+        return false;
+    };
+    let target = PathBuf::from(target);
+    let drive_mounted = target.ancestors().last().is_some_and(|root| root.exists());
+    drive_mounted && fs::symlink_metadata(&target).is_err()
+}
+
+#[cfg(not(windows))]
+fn is_broken_shortcut(_path: &Path) -> bool {
+    false
+}
+
+/// Local target path stored in a .lnk file (MS-SHLLINK LinkInfo), or None
+/// for network / non-file targets and anything that doesn't parse.
+#[cfg(windows)]
+fn lnk_local_target(data: &[u8]) -> Option<String> {
+    const HAS_ID_LIST: u32 = 0x1;
+    const HAS_LINK_INFO: u32 = 0x2;
+    const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+
+    let u16_at = |at: usize| Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize);
+    let c_str = |at: usize| {
+        let bytes = data.get(at..)?;
+        let end = bytes.iter().position(|&b| b == 0)?;
+        // ANSI code page: only trust plain ASCII.
+        bytes[..end].is_ascii().then(|| String::from_utf8_lossy(&bytes[..end]).to_string())
+    };
+    let wide_str = |at: usize| {
+        let units: Vec<u16> = (at..)
+            .step_by(2)
+            .map_while(|i| u16_at(i).filter(|&u| u != 0))
+            .collect();
+        Some(String::from_utf16_lossy(&units))
+    };
+
+    if u32_at(0)? != 0x4C {
+        return None;
+    }
+    let flags = u32_at(0x14)? as u32;
+    if flags & HAS_LINK_INFO == 0 {
+        return None;
+    }
+    let mut at = 0x4C;
+    if flags & HAS_ID_LIST != 0 {
+        at += 2 + u16_at(at)? as usize;
+    }
+
+    let info = at;
+    let header_size = u32_at(info + 4)?;
+    let info_flags = u32_at(info + 8)? as u32;
+    if info_flags & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+    let suffix = c_str(info + u32_at(info + 24)?).unwrap_or_default();
+    let base = if header_size >= 0x24 {
+        wide_str(info + u32_at(info + 28)?)?
+    } else {
+        c_str(info + u32_at(info + 16)?)?
+    };
+    let target = format!("{}{}", base, suffix);
+    (!target.is_empty()).then_some(target)
+}
+
+struct CleanupScan<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    options: &'a CleanupScanOptions,
+    token: &'a CancellationToken,
+    counts: CleanupCounts,
+    batch: Vec<CleanupFinding>,
+    entries_seen: u64,
+    last_emit: Instant,
+}
+
+impl CleanupScan<'_> {
+    /// Err(None) = cancelled.
+    fn run(&mut self, root: &Path) -> Result<(), Option<AppError>> {
+        fs::metadata(root).map_err(|e| Some(AppError::io(&e).with_path(root)))?;
+        let temp_cutoff = SystemTime::now()
+            .checked_sub(Duration::from_secs(self.options.temp_min_age_hours * 3600))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+
+        // Children come before their folder, so a folder's emptiness is
+        // known when it is reached. Empty folders wait in `pending` until
+        // their parent turns out non-empty; then only the topmost is shown.
+        let mut non_empty: HashSet<PathBuf> = HashSet::new();
+        let mut pending: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+
+        for entry in WalkDir::new(root).contents_first(true) {
+            if self.token.is_cancelled() {
+                return Err(None);
+            }
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    tracing::warn!("[Cleanup] WalkDir error: {err}");
+                    // Unreadable folder: neither it nor its parent is empty.
+                    if let Some(path) = err.path() {
+                        non_empty.insert(path.to_path_buf());
+                        non_empty.extend(path.parent().map(Path::to_path_buf));
+                    }
+                    continue;
+                }
+            };
+            self.entries_seen += 1;
+            let path = entry.path();
+            let parent = path.parent().map(Path::to_path_buf);
+
+            if entry.file_type().is_dir() {
+                let children = pending.remove(path).unwrap_or_default();
+                let empty = !non_empty.remove(path);
+                if path == root {
+                    self.report_dirs(children);
+                } else if empty {
+                    // Replaces its empty children as the finding.
+                    pending.entry(parent.unwrap_or_default()).or_default().push(path.to_path_buf());
+                } else {
+                    self.report_dirs(children);
+                    non_empty.insert(parent.unwrap_or_default());
+                }
+            } else {
+                if let Some(parent) = parent {
+                    non_empty.insert(parent);
+                }
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                let name = entry.file_name().to_string_lossy();
+                if entry.path_is_symlink() || (self.options.broken_links && is_broken_shortcut(path)) {
+                    if self.options.broken_links && is_broken_link(path, &meta) {
+                        self.counts.broken_links += 1;
+                        self.push(path, CleanupKind::BrokenLink, 0);
+                    }
+                } else if self.options.temp_files
+                    && is_temp_name(&name)
+                    && meta.modified().is_ok_and(|m| m < temp_cutoff)
+                {
+                    self.counts.temp_files += 1;
+                    self.counts.reclaimable_bytes += meta.len();
+                    self.push(path, CleanupKind::TempFile, meta.len());
+                } else if self.options.empty_files && meta.len() == 0 && !KEEP_EMPTY_FILES.contains(&name.as_ref()) {
+                    self.counts.empty_files += 1;
+                    self.push(path, CleanupKind::EmptyFile, 0);
+                }
+            }
+            self.flush(false);
+        }
+        Ok(())
+    }
+
+    fn report_dirs(&mut self, dirs: Vec<PathBuf>) {
+        if !self.options.empty_dirs {
+            return;
+        }
+        for dir in dirs {
+            self.counts.empty_dirs += 1;
+            self.push(&dir, CleanupKind::EmptyDir, 0);
+        }
+    }
+
+    fn push(&mut self, path: &Path, kind: CleanupKind, size: u64) {
+        self.batch.push(CleanupFinding {
+            path: path.to_string_lossy().to_string(),
+            kind,
+            size,
+        });
+    }
+
+    /// Emit buffered findings and progress (throttled unless `force`).
+    fn flush(&mut self, force: bool) {
+        if !force && self.batch.len() < FINDINGS_BATCH && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        if !self.batch.is_empty() {
+            let _ = self.app.emit(
+                "fu:cleanup_findings",
+                CleanupFindings {
+                    op_id: self.op_id.to_string(),
+                    findings: std::mem::take(&mut self.batch),
+                },
+            );
+        }
+        let c = &self.counts;
+        let _ = self.app.emit(
+            "fu:cleanup_scan_progress",
+            CleanupScanProgress {
+                op_id: self.op_id.to_string(),
+                entries_seen: self.entries_seen,
+                findings: c.empty_dirs + c.empty_files + c.broken_links + c.temp_files,
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
@@ -0,0 +1,210 @@
+// src-tauri/src/cli.rs
+//
+// Command-line arguments.
+//
+//   filesup-asc [<path>]                 start the UI, opened at <path>
+//   filesup-asc --scan <path> [--json] [--follow-symlinks] [--categories]
+//                                        folder scan without a window
+//   filesup-asc --check-updates [--json] TUF update check
+//   filesup-asc --help | --version
+//
+// --scan runs without Tauri at all (works over SSH / in scripts); it is not
+// limited by the sandbox roots, which only guard the webview.
+// --check-updates needs the app's config dir and settings, so it builds the
+// app without windows and exits once the check is done.
+//
+// `--elevated-helper <endpoint> <app pid>` is internal: the app starts itself that way
+// with admin rights to run one vetted operation (elevation.rs).
+//
+// Exit codes: 0 success, 1 the scan / check failed, 2 bad arguments.
+// The UI ignores flags it doesn't know (the OS may add its own).
+
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, State};
+
+use crate::elevation;
+use crate::error::AppError;
+use crate::folder_scan::{scan_folder_headless, FolderScanOptions};
+use crate::single_instance::{launch_path, open_request, OpenPathRequest};
+use crate::update;
+
+pub const USAGE: &str = "\
+Usage:
+  filesup-asc [<path>]
+  filesup-asc --scan <path> [--json] [--follow-symlinks] [--categories]
+  filesup-asc --check-updates [--json]
+  filesup-asc --help | --version";
+
+pub enum Cli {
+    /// Start the UI.
+    Gui(LaunchPath),
+    Scan {
+        path: PathBuf,
+        json: bool,
+        options: FolderScanOptions,
+    },
+    CheckUpdates {
+        json: bool,
+    },
+    /// Elevated helper for one operation (elevation.rs).
+    ElevatedHelper {
+        endpoint: String,
+        app_pid: u32,
+    },
+    Help,
+    Version,
+}
+
+/// Path given on the command line at startup (managed state).
+pub struct LaunchPath(pub Option<PathBuf>);
+
+/// `args` without the executable.
+pub fn parse(args: &[String]) -> Result<Cli, String> {
+    let cwd = std::env::current_dir().ok();
+    let mut json = false;
+    let mut scan = None;
+    let mut check_updates = false;
+    let mut options = FolderScanOptions::default();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--scan" => scan = Some(iter.next().ok_or("--scan needs a path")?),
+            "--check-updates" => check_updates = true,
+            "--json" => json = true,
+            "--follow-symlinks" => options.follow_symlinks = true,
+            "--categories" => options.categories = true,
+            elevation::HELPER_FLAG => {
+                let endpoint = iter.next().ok_or("--elevated-helper needs an endpoint")?;
+                let app_pid = iter
+                    .next()
+                    .and_then(|pid| pid.parse().ok())
+                    .ok_or("--elevated-helper needs the app's process id")?;
+                return Ok(Cli::ElevatedHelper {
+                    endpoint: endpoint.clone(),
+                    app_pid,
+                });
+            }
+            "-h" | "--help" => return Ok(Cli::Help),
+            "-V" | "--version" => return Ok(Cli::Version),
+            _ => {}
+        }
+    }
+
+    match (scan, check_updates) {
+        (Some(_), true) => Err("--scan and --check-updates can't be combined".to_string()),
+        (Some(path), false) => Ok(Cli::Scan {
+            path: launch_path(std::slice::from_ref(path), cwd.as_deref())
+                .ok_or_else(|| format!("Invalid scan path: {}", path))?,
+            json,
+            options,
+        }),
+        (None, true) => Ok(Cli::CheckUpdates { json }),
+        (None, false) => Ok(Cli::Gui(LaunchPath(launch_path(args, cwd.as_deref())))),
+    }
+}
+
+/// Run a non-UI command; returns the process exit code.
+pub fn run_headless(cli: Cli, mut context: tauri::Context<tauri::Wry>) -> i32 {
+    attach_console();
+    match cli {
+        Cli::Gui(_) => 0,
+        Cli::Help => {
+            println!("{}", USAGE);
+            0
+        }
+        Cli::Version => {
+            println!("{}", context.package_info().version);
+            0
+        }
+        Cli::Scan { path, json, options } => scan(&path, json, &options),
+        Cli::ElevatedHelper { endpoint, app_pid } => elevation::run_helper(&endpoint, app_pid),
+        Cli::CheckUpdates { json } => {
+            context.config_mut().app.windows.clear();
+            match tauri::Builder::default().build(context) {
+                Ok(app) => check_updates(app.handle(), json),
+                Err(e) => {
+                    eprintln!("Failed to start: {}", e);
+                    1
+                }
+            }
+        }
+    }
+}
+
+fn scan(path: &Path, json: bool, options: &FolderScanOptions) -> i32 {
+    let completed = serde_json::to_value(scan_folder_headless(path, options)).unwrap_or_default();
+    let ok = completed["status"] == "ok";
+    if json {
+        println!("{}", serde_json::to_string_pretty(&completed).unwrap_or_default());
+    } else if ok {
+        println!(
+            "{}: {} folders, {} files, {} bytes",
+            path.display(),
+            completed["folderCount"],
+            completed["fileCount"],
+            completed["totalSize"]
+        );
+    } else {
+        eprintln!("Scan failed: {}", completed["errorMessage"].as_str().unwrap_or("unknown error"));
+    }
+    if ok {
+        0
+    } else {
+        1
+    }
+}
+
+fn check_updates(app: &AppHandle, json: bool) -> i32 {
+    let res = tauri::async_runtime::block_on(update::check_for_updates(
+        app,
+        update::current_version(app),
+        update::platform_id(),
+        None,
+    ));
+    match res {
+        Ok(result) if json => {
+            println!("{}", serde_json::to_string_pretty(&result).unwrap_or_default());
+            0
+        }
+        Ok(result) => {
+            match (&result.latest_version, result.update_available) {
+                (Some(latest), true) => println!("Update available: {} -> {}", result.current_version, latest),
+                _ => println!("Up to date ({})", result.current_version),
+            }
+            0
+        }
+        Err(e) => {
+            if json {
+                let error = AppError::update(e);
+                println!("{}", serde_json::to_string_pretty(&error).unwrap_or_default());
+            } else {
+                eprintln!("Update check failed: {:#}", e);
+            }
+            1
+        }
+    }
+}
+
+/// Release builds use the GUI subsystem on Windows; print to the console
+/// we were started from.
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // SAFETY: no pointers involved; fails harmlessly without a parent console.
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Command from TS, once on startup:
+/// invoke<OpenPathRequest | null>("get_launch_path")
+/// Later paths (from a second launch) arrive as `fu:open_path_request`.
+#[tauri::command]
+pub fn get_launch_path(app: AppHandle, launch: State<'_, LaunchPath>) -> Result<Option<OpenPathRequest>, AppError> {
+    launch.0.as_deref().map(|path| open_request(&app, path)).transpose()
+}
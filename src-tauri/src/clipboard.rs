@@ -0,0 +1,466 @@
+// src-tauri/src/clipboard.rs
+//
+// File copy / cut / paste through the OS clipboard, so FilesUP and
+// Explorer / Finder / Nautilus can paste each other's files.
+//
+// Formats:
+//   - Windows: CF_HDROP + "Preferred DropEffect" (copy vs move), exactly
+//     what Explorer writes and reads.
+//   - Linux: cut  -> x-special/gnome-copied-files ("cut\nfile:///...")
+//            copy -> text/uri-list
+//     via wl-copy / wl-paste on Wayland, xclip on X11 (the clipboard tools
+//     must be installed; errors say which one is missing).
+//   - macOS: file URLs on the general pasteboard (osascript / JXA).
+//     Finder has no "cut" on the pasteboard, so a cut is remembered
+//     in-process and applies while the pasteboard still holds those paths.
+//
+// Paste reuses the copy / move engine (file_ops.rs): same op_id, same
+// `fu:file_op_progress` / `fu:file_op_completed` events.
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::{start_file_op, ConflictPolicy, FileOpKind, FileOpOptions};
+use crate::gps_backend::OperationRegistry;
+use crate::path_guard::{PathGuard, PathGuardError};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tokio::task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardMode {
+    Copy,
+    Cut,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardFiles {
+    pub paths: Vec<String>,
+    pub mode: ClipboardMode,
+}
+
+/// Paths of our last cut, for formats that can't carry "cut" themselves.
+static LAST_CUT: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+fn write_files(paths: Vec<String>, mode: ClipboardMode) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("Nothing to put on the clipboard".to_string());
+    }
+    platform::write(&paths, mode)?;
+    *LAST_CUT.lock().unwrap_or_else(|e| e.into_inner()) =
+        (mode == ClipboardMode::Cut).then_some(paths);
+    Ok(())
+}
+
+fn read_files() -> Result<Option<ClipboardFiles>, String> {
+    let Some(mut files) = platform::read()? else {
+        return Ok(None);
+    };
+    if files.paths.is_empty() {
+        return Ok(None);
+    }
+    if files.mode == ClipboardMode::Copy {
+        let last_cut = LAST_CUT.lock().unwrap_or_else(|e| e.into_inner());
+        if last_cut.as_ref() == Some(&files.paths) {
+            files.mode = ClipboardMode::Cut;
+        }
+    }
+    Ok(Some(files))
+}
+
+/// Put `paths` on the OS clipboard as files to copy.
+///
+/// Frontend can call:
+///   invoke('clipboard_copy_paths', { paths })
+#[tauri::command]
+pub async fn clipboard_copy_paths(guard: State<'_, PathGuard>, paths: Vec<String>) -> Result<(), String> {
+    let paths = checked_strings(&guard, &paths)?;
+    task::spawn_blocking(move || write_files(paths, ClipboardMode::Copy))
+        .await
+        .map_err(|e| format!("Clipboard task failed: {}", e))?
+}
+
+/// Put `paths` on the OS clipboard as files to move (Explorer "Cut").
+///
+/// Frontend can call:
+///   invoke('clipboard_cut_paths', { paths })
+#[tauri::command]
+pub async fn clipboard_cut_paths(guard: State<'_, PathGuard>, paths: Vec<String>) -> Result<(), String> {
+    let paths = checked_strings(&guard, &paths)?;
+    task::spawn_blocking(move || write_files(paths, ClipboardMode::Cut))
+        .await
+        .map_err(|e| format!("Clipboard task failed: {}", e))?
+}
+
+/// Files currently on the clipboard (null if none), e.g. to enable "Paste".
+///
+/// Frontend can call:
+///   invoke<ClipboardFiles | null>('clipboard_read_paths')
+#[tauri::command]
+pub async fn clipboard_read_paths() -> Result<Option<ClipboardFiles>, String> {
+    task::spawn_blocking(read_files)
+        .await
+        .map_err(|e| format!("Clipboard task failed: {}", e))?
+}
+
+/// Paste clipboard files into `dir`: copy, or move for a cut.
+/// Starts a regular copy / move op under `op_id` and returns what is
+/// being pasted. A completed cut-paste clears our cut marker, so pasting
+/// again copies (the sources are gone anyway).
+///
+/// Frontend can call:
+///   invoke<ClipboardFiles>('clipboard_paste_into', { opId, dir, conflictPolicy: 'ask' })
+#[tauri::command]
+pub async fn clipboard_paste_into(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    dir: String,
+    conflict_policy: Option<ConflictPolicy>,
+) -> Result<ClipboardFiles, AppError> {
+    let dir = guard.check(&dir)?.to_string_lossy().to_string();
+    let files = task::spawn_blocking(read_files)
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Clipboard task failed: {}", e)))?
+        .map_err(|e| AppError::new(ErrorCode::Io, e))?
+        .ok_or_else(|| AppError::invalid_input("The clipboard holds no files"))?;
+    let sources = checked_strings(&guard, &files.paths)?;
+
+    let kind = match files.mode {
+        ClipboardMode::Copy => FileOpKind::Copy,
+        ClipboardMode::Cut => FileOpKind::Move,
+    };
+    start_file_op(
+        app,
+        registry.inner().clone(),
+        op_id,
+        kind,
+        sources,
+        dir,
+        FileOpOptions {
+            conflict_policy,
+            ..Default::default()
+        },
+    )?;
+    if files.mode == ClipboardMode::Cut {
+        *LAST_CUT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+    Ok(files)
+}
+
+/// check_entries() as strings: the files themselves go on / come off
+/// the clipboard, not what a symlink among them points to.
+fn checked_strings(guard: &PathGuard, paths: &[String]) -> Result<Vec<String>, PathGuardError> {
+    Ok(guard
+        .check_entries(paths)?
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect())
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{ClipboardFiles, ClipboardMode};
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::{mem, ptr, thread, time::Duration};
+    use windows_sys::Win32::Foundation::{GlobalFree, POINT};
+    use windows_sys::Win32::System::DataExchange::{
+        CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, RegisterClipboardFormatW,
+        SetClipboardData,
+    };
+    use windows_sys::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use windows_sys::Win32::UI::Shell::{DragQueryFileW, DROPFILES};
+
+    const CF_HDROP: u32 = 15;
+    const DROPEFFECT_COPY: u32 = 1;
+    const DROPEFFECT_MOVE: u32 = 2;
+
+    /// Closes the clipboard when dropped.
+    struct Open;
+
+    impl Open {
+        /// Another app may hold the clipboard for a moment; retry briefly.
+        fn new() -> Result<Self, String> {
+            for _ in 0..10 {
+                if unsafe { OpenClipboard(ptr::null_mut()) } != 0 {
+                    return Ok(Open);
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            Err("The clipboard is in use by another application".to_string())
+        }
+    }
+
+    impl Drop for Open {
+        fn drop(&mut self) {
+            unsafe {
+                CloseClipboard();
+            }
+        }
+    }
+
+    fn drop_effect_format() -> u32 {
+        let name: Vec<u16> = "Preferred DropEffect\0".encode_utf16().collect();
+        unsafe { RegisterClipboardFormatW(name.as_ptr()) }
+    }
+
+    /// Movable global memory holding `bytes`.
+    unsafe fn global_bytes(bytes: &[u8]) -> Result<*mut core::ffi::c_void, String> {
+        let handle = GlobalAlloc(GMEM_MOVEABLE, bytes.len());
+        if handle.is_null() {
+            return Err("Out of memory for clipboard data".to_string());
+        }
+        let dst = GlobalLock(handle) as *mut u8;
+        if dst.is_null() {
+            GlobalFree(handle);
+            return Err("Failed to lock clipboard memory".to_string());
+        }
+        ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len());
+        GlobalUnlock(handle);
+        Ok(handle)
+    }
+
+    pub fn write(paths: &[String], mode: ClipboardMode) -> Result<(), String> {
+        // DROPFILES header, then NUL-separated wide paths, then one more NUL.
+        let header = DROPFILES {
+            pFiles: mem::size_of::<DROPFILES>() as u32,
+            pt: POINT { x: 0, y: 0 },
+            fNC: 0,
+            fWide: 1,
+        };
+        let mut bytes: Vec<u8> = unsafe {
+            std::slice::from_raw_parts(&header as *const DROPFILES as *const u8, mem::size_of::<DROPFILES>())
+        }
+        .to_vec();
+        for p in paths {
+            for unit in std::path::Path::new(p).as_os_str().encode_wide().chain([0]) {
+                bytes.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+
+        let effect = match mode {
+            ClipboardMode::Copy => DROPEFFECT_COPY,
+            ClipboardMode::Cut => DROPEFFECT_MOVE,
+        };
+
+        let _open = Open::new()?;
+        unsafe {
+            EmptyClipboard();
+            let files = global_bytes(&bytes)?;
+            if SetClipboardData(CF_HDROP, files).is_null() {
+                GlobalFree(files);
+                return Err("Failed to set clipboard files".to_string());
+            }
+            let effect_mem = global_bytes(&effect.to_le_bytes())?;
+            if SetClipboardData(drop_effect_format(), effect_mem).is_null() {
+                GlobalFree(effect_mem);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read() -> Result<Option<ClipboardFiles>, String> {
+        let _open = Open::new()?;
+        unsafe {
+            let hdrop = GetClipboardData(CF_HDROP);
+            if hdrop.is_null() {
+                return Ok(None);
+            }
+            let count = DragQueryFileW(hdrop, u32::MAX, ptr::null_mut(), 0);
+            let mut paths = Vec::with_capacity(count as usize);
+            for i in 0..count {
+                let len = DragQueryFileW(hdrop, i, ptr::null_mut(), 0);
+                let mut buf = vec![0u16; len as usize + 1];
+                let got = DragQueryFileW(hdrop, i, buf.as_mut_ptr(), len + 1);
+                buf.truncate(got as usize);
+                paths.push(OsString::from_wide(&buf).to_string_lossy().to_string());
+            }
+
+            let mut mode = ClipboardMode::Copy;
+            let effect = GetClipboardData(drop_effect_format());
+            if !effect.is_null() {
+                let p = GlobalLock(effect) as *const u32;
+                if !p.is_null() {
+                    if p.read_unaligned() & DROPEFFECT_MOVE != 0 {
+                        mode = ClipboardMode::Cut;
+                    }
+                    GlobalUnlock(effect);
+                }
+            }
+            Ok(Some(ClipboardFiles { paths, mode }))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{ClipboardFiles, ClipboardMode};
+    use std::process::Command;
+
+    const WRITE_JXA: &str = "function run(argv) {
+  ObjC.import('AppKit');
+  var pb = $.NSPasteboard.generalPasteboard;
+  pb.clearContents;
+  var urls = $.NSMutableArray.alloc.init;
+  argv.forEach(function (p) { urls.addObject($.NSURL.fileURLWithPath(p)); });
+  pb.writeObjects(urls);
+}";
+
+    const READ_JXA: &str = "function run() {
+  ObjC.import('AppKit');
+  var pb = $.NSPasteboard.generalPasteboard;
+  var items = pb.readObjectsForClassesOptions($([$.NSURL]), $({}));
+  var out = [];
+  if (items) {
+    for (var i = 0; i < items.count; i++) {
+      var u = items.objectAtIndex(i);
+      if (u.isFileURL) out.push(u.path.js);
+    }
+  }
+  return out.join('\\n');
+}";
+
+    fn osascript(script: &str, args: &[String]) -> Result<String, String> {
+        let out = Command::new("osascript")
+            .args(["-l", "JavaScript", "-e", script])
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run osascript: {}", e))?;
+        if !out.status.success() {
+            return Err(format!(
+                "osascript failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&out.stdout).to_string())
+    }
+
+    pub fn write(paths: &[String], _mode: ClipboardMode) -> Result<(), String> {
+        osascript(WRITE_JXA, paths).map(|_| ())
+    }
+
+    pub fn read() -> Result<Option<ClipboardFiles>, String> {
+        let out = osascript(READ_JXA, &[])?;
+        let paths: Vec<String> = out
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok((!paths.is_empty()).then_some(ClipboardFiles {
+            paths,
+            mode: ClipboardMode::Copy,
+        }))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{ClipboardFiles, ClipboardMode};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    use url::Url;
+
+    const GNOME_FILES: &str = "x-special/gnome-copied-files";
+    const URI_LIST: &str = "text/uri-list";
+
+    fn wayland() -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+
+    fn to_uri(path: &str) -> String {
+        Url::from_file_path(path)
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| format!("file://{}", path))
+    }
+
+    fn set(mime: &str, data: &str) -> Result<(), String> {
+        let (tool, args): (&str, Vec<&str>) = if wayland() {
+            ("wl-copy", vec!["--type", mime])
+        } else {
+            ("xclip", vec!["-selection", "clipboard", "-t", mime, "-i"])
+        };
+        let mut child = Command::new(tool)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run {} (is it installed?): {}", tool, e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(data.as_bytes())
+                .map_err(|e| format!("Failed to write to {}: {}", tool, e))?;
+        }
+        // Both tools fork a background owner and exit.
+        let status = child
+            .wait()
+            .map_err(|e| format!("{} failed: {}", tool, e))?;
+        if !status.success() {
+            return Err(format!("{} exited with {}", tool, status));
+        }
+        Ok(())
+    }
+
+    /// None when the clipboard doesn't offer `mime` (or the tool is missing).
+    fn get(mime: &str) -> Option<String> {
+        let out = if wayland() {
+            Command::new("wl-paste")
+                .args(["--no-newline", "--type", mime])
+                .output()
+        } else {
+            Command::new("xclip")
+                .args(["-selection", "clipboard", "-t", mime, "-o"])
+                .output()
+        }
+        .ok()?;
+        out.status
+            .success()
+            .then(|| String::from_utf8_lossy(&out.stdout).to_string())
+    }
+
+    fn parse_uris<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<String> {
+        lines
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|l| Url::parse(l).ok()?.to_file_path().ok())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    pub fn write(paths: &[String], mode: ClipboardMode) -> Result<(), String> {
+        let uris: Vec<String> = paths.iter().map(|p| to_uri(p)).collect();
+        match mode {
+            ClipboardMode::Cut => set(GNOME_FILES, &format!("cut\n{}", uris.join("\n"))),
+            ClipboardMode::Copy => set(URI_LIST, &format!("{}\r\n", uris.join("\r\n"))),
+        }
+    }
+
+    pub fn read() -> Result<Option<ClipboardFiles>, String> {
+        if let Some(data) = get(GNOME_FILES) {
+            let mut lines = data.lines();
+            let mode = match lines.next().map(str::trim) {
+                Some("cut") => ClipboardMode::Cut,
+                _ => ClipboardMode::Copy,
+            };
+            let paths = parse_uris(lines);
+            if !paths.is_empty() {
+                return Ok(Some(ClipboardFiles { paths, mode }));
+            }
+        }
+        if let Some(data) = get(URI_LIST) {
+            let paths = parse_uris(data.lines());
+            if !paths.is_empty() {
+                return Ok(Some(ClipboardFiles {
+                    paths,
+                    mode: ClipboardMode::Copy,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,487 @@
+// src-tauri/src/compare.rs
+//
+// Two-folder compare (backbone of the sync preview).
+// Pipeline:
+//   1) Walk left and right into sorted maps keyed by relative path.
+//   2) Merge both maps in order. Entries on one side only are reported
+//      once at their top-most folder (children are implied).
+//   3) Files on both sides are compared by size + mtime ("metadata") or,
+//      in "hash" mode, by size and then BLAKE3 of the contents.
+// Differences are streamed in batches as `fu:compare_diff`; the op is
+// registered in GPS (kind: compare) and cancellable like any other.
+
+use crate::file_filter::{FileFilter, FileFilterSpec, FilterEntry};
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+/// Differences per `fu:compare_diff` event.
+const DIFF_BATCH: usize = 200;
+/// FAT / exFAT / SMB round mtimes to 2 s; closer than this counts as equal.
+const MTIME_TOLERANCE_SECS: u64 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareMode {
+    /// Size + modification time only (fast, no file reads).
+    #[default]
+    Metadata,
+    /// Size, then full-content BLAKE3 when sizes match (mtime ignored).
+    Hash,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum DiffKind {
+    OnlyLeft,
+    OnlyRight,
+    /// Same name, different size / mtime / content.
+    Different,
+    /// File on one side, folder on the other.
+    TypeMismatch,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct SideInfo {
+    is_dir: bool,
+    size: u64,
+    /// Seconds since UNIX_EPOCH (0 if unknown).
+    modified: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CompareDiff {
+    /// Relative to both roots, "/"-separated.
+    relative_path: String,
+    kind: DiffKind,
+    /// For "different": "size" | "mtime" | "content".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'static str>,
+    left: Option<SideInfo>,
+    right: Option<SideInfo>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CompareDiffBatch {
+    op_id: String,
+    items: Vec<CompareDiff>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CompareProgress {
+    op_id: String,
+    phase: String, // "scanning-left" | "scanning-right" | "comparing"
+    entries_seen: u64,
+    compared: u64,
+    differences: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct CompareSummary {
+    only_left: u64,
+    only_right: u64,
+    different: u64,
+    type_mismatch: u64,
+    identical: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CompareCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    #[serde(flatten)]
+    summary: CompareSummary,
+    error_message: Option<String>,
+}
+
+enum CompareError {
+    Cancelled,
+    IoError(std::io::Error),
+}
+
+/// Command from TS:
+/// invoke("compare_directories", { opId, left, right, mode: "metadata" | "hash", filter: null })
+///
+/// `filter` (file_filter.rs) is applied to both sides before comparing; a
+/// file that passes on one side only (e.g. its size changed across
+/// maxSize) is reported as one-sided.
+///
+/// Events: `fu:compare_progress`, `fu:compare_diff` (batches),
+/// `fu:compare_completed` (totals; on cancel, totals so far).
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn compare_directories(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    left: String,
+    right: String,
+    mode: Option<CompareMode>,
+    filter: Option<FileFilterSpec>,
+) -> Result<(), String> {
+    let left = guard.check(&left)?;
+    let right = guard.check(&right)?;
+    if left.starts_with(&right) || right.starts_with(&left) {
+        return Err("Cannot compare a folder with its own subfolder".to_string());
+    }
+    let mode = mode.unwrap_or_default();
+    let filter = FileFilter::from_spec(filter.as_ref())?;
+
+    let token = registry.register(&op_id, OperationKind::Compare);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut summary = CompareSummary::default();
+        let res = run_compare_blocking(&app, &op_id, &left, &right, mode, filter.as_ref(), &token, &mut summary);
+
+        let (status, error_message) = match res {
+            Ok(()) => ("ok".to_string(), None),
+            Err(CompareError::Cancelled) => ("cancelled".to_string(), None),
+            Err(CompareError::IoError(e)) => ("error".to_string(), Some(format!("I/O error: {}", e))),
+        };
+
+        let _ = app.emit(
+            "fu:compare_completed",
+            CompareCompleted {
+                op_id: op_id.clone(),
+                status,
+                summary,
+                error_message,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+struct DiffSink<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    batch: Vec<CompareDiff>,
+    last_progress: Instant,
+}
+
+impl DiffSink<'_> {
+    fn diff(&mut self, diff: CompareDiff) {
+        self.batch.push(diff);
+        if self.batch.len() >= DIFF_BATCH {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:compare_diff",
+            CompareDiffBatch {
+                op_id: self.op_id.to_string(),
+                items: std::mem::take(&mut self.batch),
+            },
+        );
+    }
+
+    fn progress(&mut self, phase: &str, entries_seen: u64, compared: u64, differences: u64, force: bool) {
+        if !force && self.last_progress.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:compare_progress",
+            CompareProgress {
+                op_id: self.op_id.to_string(),
+                phase: phase.to_string(),
+                entries_seen,
+                compared,
+                differences,
+            },
+        );
+        self.last_progress = Instant::now();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_compare_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    left: &Path,
+    right: &Path,
+    mode: CompareMode,
+    filter: Option<&FileFilter>,
+    token: &CancellationToken,
+    summary: &mut CompareSummary,
+) -> Result<(), CompareError> {
+    std::fs::metadata(left).map_err(CompareError::IoError)?;
+    std::fs::metadata(right).map_err(CompareError::IoError)?;
+
+    let mut out = DiffSink {
+        app,
+        op_id,
+        batch: Vec::new(),
+        last_progress: Instant::now(),
+    };
+
+    let left_tree = collect_tree(left, "scanning-left", filter, token, &mut out)?;
+    let right_tree = collect_tree(right, "scanning-right", filter, token, &mut out)?;
+    let total = (left_tree.len() + right_tree.len()) as u64;
+
+    // ==== Merge ====
+    let mut l = left_tree.into_iter().peekable();
+    let mut r = right_tree.into_iter().peekable();
+    // Folders reported as one-sided / type mismatch; descendants are skipped.
+    // (Not contiguous in sort order: "a-b" sorts between "a" and "a/x".)
+    let mut skipped: HashSet<String> = HashSet::new();
+    let mut compared = 0u64;
+    let differences = |s: &CompareSummary| s.only_left + s.only_right + s.different + s.type_mismatch;
+
+    loop {
+        if token.is_cancelled() {
+            out.flush();
+            return Err(CompareError::Cancelled);
+        }
+
+        let order = match (l.peek(), r.peek()) {
+            (None, None) => break,
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (Some((a, _)), Some((b, _))) => a.cmp(b),
+        };
+        let (rel, left_side, right_side) = match order {
+            std::cmp::Ordering::Less => {
+                let (k, v) = l.next().unwrap_or_default();
+                (k, Some(v), None)
+            }
+            std::cmp::Ordering::Greater => {
+                let (k, v) = r.next().unwrap_or_default();
+                (k, None, Some(v))
+            }
+            std::cmp::Ordering::Equal => {
+                let (k, a) = l.next().unwrap_or_default();
+                let (_, b) = r.next().unwrap_or_default();
+                (k, Some(a), Some(b))
+            }
+        };
+        compared += 1;
+
+        if has_skipped_ancestor(&rel, &skipped) {
+            continue;
+        }
+
+        match (left_side, right_side) {
+            (Some(a), None) => {
+                summary.only_left += 1;
+                if a.is_dir {
+                    skipped.insert(rel.clone());
+                }
+                out.diff(one_sided(rel, DiffKind::OnlyLeft, Some(a), None));
+            }
+            (None, Some(b)) => {
+                summary.only_right += 1;
+                if b.is_dir {
+                    skipped.insert(rel.clone());
+                }
+                out.diff(one_sided(rel, DiffKind::OnlyRight, None, Some(b)));
+            }
+            (Some(a), Some(b)) if a.is_dir != b.is_dir => {
+                summary.type_mismatch += 1;
+                // Whatever is below the folder side is one-sided by definition.
+                skipped.insert(rel.clone());
+                out.diff(one_sided(rel, DiffKind::TypeMismatch, Some(a), Some(b)));
+            }
+            (Some(a), Some(_)) if a.is_dir => {}
+            (Some(mut a), Some(mut b)) => {
+                match compare_files(left, right, &rel, &mut a, &mut b, mode, token)? {
+                    Some(reason) => {
+                        summary.different += 1;
+                        out.diff(CompareDiff {
+                            relative_path: rel,
+                            kind: DiffKind::Different,
+                            reason: Some(reason),
+                            left: Some(a),
+                            right: Some(b),
+                        });
+                    }
+                    None => summary.identical += 1,
+                }
+            }
+            (None, None) => {}
+        }
+
+        out.progress("comparing", total, compared, differences(summary), false);
+    }
+
+    out.flush();
+    out.progress("comparing", total, compared, differences(summary), true);
+    Ok(())
+}
+
+fn has_skipped_ancestor(rel: &str, skipped: &HashSet<String>) -> bool {
+    !skipped.is_empty()
+        && rel
+            .match_indices('/')
+            .any(|(i, _)| skipped.contains(&rel[..i]))
+}
+
+fn one_sided(rel: String, kind: DiffKind, left: Option<SideInfo>, right: Option<SideInfo>) -> CompareDiff {
+    CompareDiff {
+        relative_path: rel,
+        kind,
+        reason: None,
+        left,
+        right,
+    }
+}
+
+/// None = equal; Some(reason) = different.
+fn compare_files(
+    left_root: &Path,
+    right_root: &Path,
+    rel: &str,
+    a: &mut SideInfo,
+    b: &mut SideInfo,
+    mode: CompareMode,
+    token: &CancellationToken,
+) -> Result<Option<&'static str>, CompareError> {
+    if a.size != b.size {
+        return Ok(Some("size"));
+    }
+    match mode {
+        CompareMode::Metadata => {
+            if a.modified.abs_diff(b.modified) > MTIME_TOLERANCE_SECS {
+                return Ok(Some("mtime"));
+            }
+            Ok(None)
+        }
+        CompareMode::Hash => {
+            let (ha, hb) = match (
+                hash_file(&left_root.join(rel), token),
+                hash_file(&right_root.join(rel), token),
+            ) {
+                (Err(CompareError::Cancelled), _) | (_, Err(CompareError::Cancelled)) => {
+                    return Err(CompareError::Cancelled)
+                }
+                (Ok(ha), Ok(hb)) => (ha, hb),
+                // Unreadable on either side: can't prove equal.
+                (Err(CompareError::IoError(err)), _) | (_, Err(CompareError::IoError(err))) => {
+                    tracing::warn!("[Compare] Hash error for {}: {err}", rel);
+                    return Ok(Some("content"));
+                }
+            };
+            let equal = ha == hb;
+            a.hash = Some(ha);
+            b.hash = Some(hb);
+            Ok((!equal).then_some("content"))
+        }
+    }
+}
+
+/// Relative path ("/"-separated) -> entry info for everything below `root`.
+fn collect_tree(
+    root: &Path,
+    phase: &str,
+    filter: Option<&FileFilter>,
+    token: &CancellationToken,
+    out: &mut DiffSink,
+) -> Result<BTreeMap<String, SideInfo>, CompareError> {
+    let mut tree = BTreeMap::new();
+    let walker = WalkDir::new(root).min_depth(1).into_iter().filter_entry(|e| {
+        // Folders the filter excludes are not entered.
+        let (Some(filter), true) = (filter, e.file_type().is_dir()) else {
+            return true;
+        };
+        let rel = e.path().strip_prefix(root).unwrap_or(e.path());
+        e.metadata().is_ok_and(|m| filter.allows_dir(&FilterEntry::from_metadata(rel, &m)))
+    });
+    for entry in walker {
+        if token.is_cancelled() {
+            return Err(CompareError::Cancelled);
+        }
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::warn!("[Compare] WalkDir error: {err}");
+                continue;
+            }
+        };
+        let meta = match entry.metadata() {
+            Ok(m) => m,
+            Err(err) => {
+                tracing::warn!("[Compare] Metadata error: {err}");
+                continue;
+            }
+        };
+        if !meta.is_dir() {
+            let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            if filter.is_some_and(|f| !f.matches(&FilterEntry::from_metadata(rel, &meta))) {
+                continue;
+            }
+        }
+        let Some(rel) = relative_key(root, entry.path()) else {
+            continue;
+        };
+        tree.insert(
+            rel,
+            SideInfo {
+                is_dir: meta.is_dir(),
+                size: if meta.is_dir() { 0 } else { meta.len() },
+                modified: meta
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                hash: None,
+            },
+        );
+        out.progress(phase, tree.len() as u64, 0, 0, false);
+    }
+    Ok(tree)
+}
+
+/// "sub/dir/file.txt" on every OS, so both sides sort and match the same.
+pub fn relative_key(root: &Path, path: &Path) -> Option<String> {
+    let rel: PathBuf = path.strip_prefix(root).ok()?.to_path_buf();
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// BLAKE3 of a whole file, checking the cancel token between 1 MiB reads.
+fn hash_file(path: &Path, token: &CancellationToken) -> Result<String, CompareError> {
+    let mut file = File::open(path).map_err(CompareError::IoError)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        if token.is_cancelled() {
+            return Err(CompareError::Cancelled);
+        }
+        let n = file.read(&mut buf).map_err(CompareError::IoError)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
@@ -0,0 +1,309 @@
+// src-tauri/src/dedupe.rs
+//
+// Recursive duplicate file finder.
+// Pipeline (each stage only looks at survivors of the previous one):
+//   1) Walk the root (like folder_scan) and group regular files by size.
+//   2) For sizes with 2+ files, hash the first 64 KiB to split cheap misses.
+//   3) Full BLAKE3 hash of the remaining candidates.
+// Every confirmed group is streamed as `fu:duplicate_group` right away,
+// so the UI can show results long before the scan ends.
+
+use crate::background_priority;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+/// Bytes hashed in the "partial" stage.
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateScanProgress {
+    op_id: String,
+    phase: String, // "collecting" | "hashing"
+    files_seen: u64,
+    candidates: u64,
+    hashed: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateGroup {
+    op_id: String,
+    size: u64,
+    hash: String,
+    paths: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateScanCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    group_count: u64,
+    duplicate_files: u64,
+    reclaimable_bytes: u64,
+    error_message: Option<String>,
+}
+
+/// Command from TS:
+/// invoke("start_duplicate_scan", { opId, path, minSize })
+///
+/// - `min_size`: ignore files smaller than this (default 1 byte, so empty
+///   files are never reported as duplicates of each other).
+#[tauri::command]
+pub async fn start_duplicate_scan(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+    min_size: Option<u64>,
+) -> Result<(), String> {
+    let root = guard.check(&path)?;
+    let min_size = min_size.unwrap_or(1).max(1);
+
+    let token = registry.register(&op_id, OperationKind::DuplicateScan);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let mut summary = DuplicateSummary::default();
+        let res = run_duplicate_scan_blocking(&app, &op_id, &root, min_size, &token, &mut summary);
+
+        let (status, error_message) = match res {
+            Ok(()) => ("ok".to_string(), None),
+            Err(DedupeError::Cancelled) => ("cancelled".to_string(), None),
+            Err(DedupeError::IoError(e)) => ("error".to_string(), Some(format!("I/O error: {}", e))),
+        };
+
+        let _ = app.emit(
+            "fu:duplicate_scan_completed",
+            DuplicateScanCompleted {
+                op_id: op_id.clone(),
+                status,
+                group_count: summary.groups,
+                duplicate_files: summary.duplicate_files,
+                reclaimable_bytes: summary.reclaimable_bytes,
+                error_message,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Running totals; on cancel they describe the groups emitted so far.
+#[derive(Default)]
+struct DuplicateSummary {
+    groups: u64,
+    /// Files that could be removed (every group member except one).
+    duplicate_files: u64,
+    reclaimable_bytes: u64,
+}
+
+enum DedupeError {
+    Cancelled,
+    IoError(std::io::Error),
+}
+
+struct ProgressEmitter<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    last_emit: Instant,
+}
+
+impl ProgressEmitter<'_> {
+    fn emit(&mut self, phase: &str, files_seen: u64, candidates: u64, hashed: u64, force: bool) {
+        if !force && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:duplicate_scan_progress",
+            DuplicateScanProgress {
+                op_id: self.op_id.to_string(),
+                phase: phase.to_string(),
+                files_seen,
+                candidates,
+                hashed,
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
+
+fn run_duplicate_scan_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    root: &Path,
+    min_size: u64,
+    token: &CancellationToken,
+    summary: &mut DuplicateSummary,
+) -> Result<(), DedupeError> {
+    std::fs::metadata(root).map_err(DedupeError::IoError)?;
+
+    let mut progress = ProgressEmitter {
+        app,
+        op_id,
+        last_emit: Instant::now(),
+    };
+
+    // ==== 1) Collect files grouped by size ====
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut files_seen = 0u64;
+
+    for entry in WalkDir::new(root).into_iter() {
+        if token.is_cancelled() {
+            return Err(DedupeError::Cancelled);
+        }
+
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::warn!("[Dedupe] WalkDir error: {err}");
+                continue;
+            }
+        };
+
+        // Symlinks are not followed and not counted: they share content
+        // with their target and would always show up as duplicates.
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let size = match entry.metadata() {
+            Ok(m) => m.len(),
+            Err(err) => {
+                tracing::warn!("[Dedupe] Metadata error: {err}");
+                continue;
+            }
+        };
+
+        files_seen += 1;
+        if size >= min_size {
+            by_size.entry(size).or_default().push(entry.into_path());
+        }
+        progress.emit("collecting", files_seen, 0, 0, false);
+    }
+
+    let mut size_groups: Vec<(u64, Vec<PathBuf>)> = by_size
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect();
+    // Largest files first: they free the most space, show them early.
+    size_groups.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+
+    let candidates: u64 = size_groups.iter().map(|(_, p)| p.len() as u64).sum();
+    let mut hashed = 0u64;
+    progress.emit("hashing", files_seen, candidates, hashed, true);
+
+    // ==== 2) + 3) Partial hash, then full hash ====
+    for (size, paths) in size_groups {
+        let partial_groups = if size > PARTIAL_HASH_BYTES {
+            group_by_hash(&paths, Some(PARTIAL_HASH_BYTES), token)?
+        } else {
+            vec![paths]
+        };
+
+        for group in partial_groups {
+            for (hash, members) in group_by_hash_keyed(&group, None, token)? {
+                hashed += members.len() as u64;
+                if members.len() < 2 {
+                    continue;
+                }
+
+                let extra = members.len() as u64 - 1;
+                summary.groups += 1;
+                summary.duplicate_files += extra;
+                summary.reclaimable_bytes = summary.reclaimable_bytes.saturating_add(size * extra);
+
+                let _ = app.emit(
+                    "fu:duplicate_group",
+                    DuplicateGroup {
+                        op_id: op_id.to_string(),
+                        size,
+                        hash,
+                        paths: members
+                            .iter()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .collect(),
+                    },
+                );
+            }
+            progress.emit("hashing", files_seen, candidates, hashed, false);
+        }
+    }
+
+    progress.emit("hashing", files_seen, candidates, hashed, true);
+    Ok(())
+}
+
+/// Split `paths` into groups sharing the same hash, dropping singletons.
+fn group_by_hash(
+    paths: &[PathBuf],
+    limit: Option<u64>,
+    token: &CancellationToken,
+) -> Result<Vec<Vec<PathBuf>>, DedupeError> {
+    Ok(group_by_hash_keyed(paths, limit, token)?
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(_, members)| members)
+        .collect())
+}
+
+/// Hash every path (optionally only the first `limit` bytes) and group them.
+/// Unreadable files are skipped rather than failing the whole scan.
+fn group_by_hash_keyed(
+    paths: &[PathBuf],
+    limit: Option<u64>,
+    token: &CancellationToken,
+) -> Result<Vec<(String, Vec<PathBuf>)>, DedupeError> {
+    let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        match hash_file(path, limit, token) {
+            Ok(hash) => groups.entry(hash).or_default().push(path.clone()),
+            Err(DedupeError::Cancelled) => return Err(DedupeError::Cancelled),
+            Err(DedupeError::IoError(err)) => {
+                tracing::warn!("[Dedupe] Hash error for {:?}: {err}", path);
+            }
+        }
+    }
+    Ok(groups.into_iter().collect())
+}
+
+/// BLAKE3 hash of a file, checking the cancel token between 1 MiB reads.
+fn hash_file(
+    path: &Path,
+    limit: Option<u64>,
+    token: &CancellationToken,
+) -> Result<String, DedupeError> {
+    let file = File::open(path).map_err(DedupeError::IoError)?;
+    let mut reader: Box<dyn Read> = match limit {
+        Some(n) => Box::new(file.take(n)),
+        None => Box::new(file),
+    };
+
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        if token.is_cancelled() {
+            return Err(DedupeError::Cancelled);
+        }
+        let n = reader.read(&mut buf).map_err(DedupeError::IoError)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
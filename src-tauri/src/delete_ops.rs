@@ -0,0 +1,196 @@
+// src-tauri/src/delete_ops.rs
+//
+// Safe delete built on the OS recycle bin / trash (via the `trash` crate).
+// Commands:
+//   - delete_entries: move paths to trash (or remove permanently if asked)
+//   - list_trash: enumerate trashed items (Windows + Linux/freedesktop)
+//   - restore_from_trash: put trashed items back at their original paths
+//
+// Listing/restoring is not available on macOS: Finder owns the trash there
+// and offers no public API for it, so those commands return an error.
+//
+// Deletes and restores are recorded in the audit log (audit.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::FileOpFailure;
+use crate::path_guard::PathGuard;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+use tokio::task;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteResult {
+    pub deleted: Vec<String>,
+    pub failed: Vec<FileOpFailure>,
+    pub permanent: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// Opaque id to pass back to restore_from_trash.
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    /// Seconds since UNIX_EPOCH.
+    pub deleted_at: i64,
+}
+
+/// Delete entries, by default moving them to the OS trash.
+///
+/// - `permanent: true` skips the trash (no undo possible).
+/// - Each path is handled separately, so one locked file doesn't stop the rest.
+///
+/// Frontend can call:
+///   invoke<DeleteResult>('delete_entries', { paths, permanent: false })
+#[tauri::command]
+pub async fn delete_entries(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    paths: Vec<String>,
+    permanent: Option<bool>,
+) -> Result<DeleteResult, String> {
+    let targets = guard.check_entries(&paths)?;
+    let permanent = permanent.unwrap_or(false);
+
+    task::spawn_blocking(move || {
+        let mut result = DeleteResult {
+            deleted: Vec::new(),
+            failed: Vec::new(),
+            permanent,
+        };
+        let action = if permanent { AuditAction::Delete } else { AuditAction::Trash };
+        let audit = AuditEntry::new(action).sources(&paths);
+        let total = paths.len();
+
+        for (path, target) in paths.into_iter().zip(targets) {
+            let res = if permanent {
+                remove_permanently(&target).map_err(|e| AppError::io(&e))
+            } else {
+                trash::delete(&target).map_err(|e| AppError::new(ErrorCode::Io, e.to_string()))
+            };
+
+            match res {
+                Ok(()) => result.deleted.push(path),
+                Err(error) => result.failed.push(FileOpFailure::new(path, error)),
+            }
+        }
+
+        audit::record(&app, audit.batch_outcome(total, result.failed.len()));
+        result
+    })
+    .await
+    .map_err(|e| format!("Delete task failed: {}", e))
+}
+
+pub fn remove_permanently(path: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// List everything currently in the OS trash, newest first.
+///
+/// Frontend can call:
+///   invoke<TrashEntry[]>('list_trash')
+#[tauri::command]
+pub async fn list_trash() -> Result<Vec<TrashEntry>, String> {
+    task::spawn_blocking(platform::list_trash)
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?
+}
+
+/// Restore trashed items (ids from list_trash) to their original location.
+/// Returns the restored original paths.
+///
+/// Fails without restoring anything if an original path is occupied again.
+///
+/// Frontend can call:
+///   invoke<string[]>('restore_from_trash', { ids })
+#[tauri::command]
+pub async fn restore_from_trash(app: AppHandle, ids: Vec<String>) -> Result<Vec<String>, String> {
+    let audit = AuditEntry::new(AuditAction::RestoreFromTrash);
+    let ids_for_audit = ids.clone();
+    let res = task::spawn_blocking(move || platform::restore(ids))
+        .await
+        .map_err(|e| format!("Trash task failed: {}", e))?;
+    let audit = match &res {
+        Ok(restored) => audit.sources(restored),
+        Err(_) => audit.sources(&ids_for_audit),
+    };
+    audit::record(&app, audit.outcome(&res));
+    res
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+mod platform {
+    use super::TrashEntry;
+    use trash::os_limited;
+
+    pub fn list_trash() -> Result<Vec<TrashEntry>, String> {
+        let mut items = os_limited::list().map_err(|e| format!("Failed to list trash: {}", e))?;
+        items.sort_by_key(|item| std::cmp::Reverse(item.time_deleted));
+
+        Ok(items
+            .into_iter()
+            .map(|item| TrashEntry {
+                id: item.id.to_string_lossy().to_string(),
+                name: item.name.to_string_lossy().to_string(),
+                original_path: item.original_path().to_string_lossy().to_string(),
+                deleted_at: item.time_deleted,
+            })
+            .collect())
+    }
+
+    pub fn restore(ids: Vec<String>) -> Result<Vec<String>, String> {
+        let items: Vec<_> = os_limited::list()
+            .map_err(|e| format!("Failed to list trash: {}", e))?
+            .into_iter()
+            .filter(|item| ids.iter().any(|id| *id == item.id.to_string_lossy()))
+            .collect();
+
+        if items.len() != ids.len() {
+            return Err("Some items are no longer in the trash".to_string());
+        }
+
+        let restored: Vec<String> = items
+            .iter()
+            .map(|item| item.original_path().to_string_lossy().to_string())
+            .collect();
+
+        os_limited::restore_all(items).map_err(|e| match e {
+            trash::Error::RestoreCollision { path, .. } => {
+                format!("Cannot restore, path already exists: {}", path.to_string_lossy())
+            }
+            other => format!("Failed to restore from trash: {}", other),
+        })?;
+
+        Ok(restored)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+mod platform {
+    use super::TrashEntry;
+
+    pub fn list_trash() -> Result<Vec<TrashEntry>, String> {
+        Err("Listing the trash is not supported on this platform".to_string())
+    }
+
+    pub fn restore(_ids: Vec<String>) -> Result<Vec<String>, String> {
+        Err("Restoring from the trash is not supported on this platform".to_string())
+    }
+}
@@ -0,0 +1,146 @@
+// src-tauri/src/drag_drop.rs
+//
+// Files dropped onto the window from the OS.
+//
+// Tauri reports a drop as bare paths; here each one is checked against the
+// sandbox (path_guard.rs) and stat'ed, and `fu:files_dropped` (FilesDropped)
+// carries full FileEntry metadata, so the frontend can start a copy / move
+// (file_ops.rs) without its own stat round-trips.
+//
+// The drop target is the folder the frontend last reported via
+// set_drop_target (the pane under the cursor / the active pane). The drop
+// position is included too, in CSS pixels, for finer hit-testing.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, State, Window, WindowEvent};
+
+use crate::error::AppError;
+use crate::file_meta::extended_metadata;
+use crate::path_guard::PathGuard;
+use crate::FileEntry;
+
+/// Folder that receives drops (managed state).
+#[derive(Default)]
+pub struct DropTarget(Mutex<Option<String>>);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedEntry {
+    pub path: String,
+    pub entry: FileEntry,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedDrop {
+    pub path: String,
+    pub error: AppError,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FilesDropped {
+    /// From set_drop_target; None when the frontend hasn't set one.
+    pub target_dir: Option<String>,
+    /// CSS pixels relative to the window's content.
+    pub x: f64,
+    pub y: f64,
+    pub entries: Vec<DroppedEntry>,
+    /// Outside the allowed roots, or gone / unreadable.
+    pub rejected: Vec<RejectedDrop>,
+}
+
+/// Command from TS, whenever the pane under the cursor / the active pane
+/// changes:
+/// invoke("set_drop_target", { dir: "C:\\Users\\me\\Downloads" })
+/// invoke("set_drop_target", { dir: null })
+#[tauri::command]
+pub fn set_drop_target(
+    guard: State<'_, PathGuard>,
+    target: State<'_, DropTarget>,
+    dir: Option<String>,
+) -> Result<(), AppError> {
+    let dir = dir.map(|d| guard.check(&d)).transpose()?.map(|d| d.to_string_lossy().to_string());
+    *target.0.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+    Ok(())
+}
+
+/// Registered with Builder::on_window_event in lib.rs.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    let WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }) = event else {
+        return;
+    };
+    let app = window.app_handle().clone();
+    let scale = window.scale_factor().unwrap_or(1.0);
+    let (x, y) = (position.x / scale, position.y / scale);
+    let paths = paths.clone();
+
+    // Stat off the event loop: dropped paths may sit on a slow network share.
+    tauri::async_runtime::spawn_blocking(move || {
+        let dropped = collect(&app, paths, x, y);
+        let _ = app.emit("fu:files_dropped", dropped);
+    });
+}
+
+fn collect(app: &AppHandle, paths: Vec<PathBuf>, x: f64, y: f64) -> FilesDropped {
+    let guard = app.state::<PathGuard>();
+    let mut entries = Vec::new();
+    let mut rejected = Vec::new();
+
+    for path in paths {
+        let res = guard
+            .check(&path.to_string_lossy())
+            .map_err(AppError::from)
+            .and_then(|resolved| dropped_entry(&path, &resolved));
+        match res {
+            Ok(entry) => entries.push(entry),
+            Err(error) => {
+                tracing::warn!("[DragDrop] Rejected {:?}: {}", path, error.message);
+                rejected.push(RejectedDrop {
+                    path: path.to_string_lossy().to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    FilesDropped {
+        target_dir: app.state::<DropTarget>().0.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        x,
+        y,
+        entries,
+        rejected,
+    }
+}
+
+/// `path` as dropped (shortcuts / links stay links), `resolved` only for
+/// the sandbox check.
+fn dropped_entry(path: &Path, resolved: &Path) -> Result<DroppedEntry, AppError> {
+    let meta = std::fs::symlink_metadata(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    let name = path
+        .file_name()
+        .or_else(|| resolved.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(DroppedEntry {
+        path: path.to_string_lossy().to_string(),
+        entry: FileEntry {
+            is_dir: meta.is_dir(),
+            size: if meta.is_dir() { 0 } else { meta.len() },
+            modified: modified.to_string(),
+            extended: Some(extended_metadata(path, &name, &meta)),
+            name,
+        },
+    })
+}
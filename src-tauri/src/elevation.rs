@@ -0,0 +1,872 @@
+// src-tauri/src/elevation.rs
+//
+// Opt-in elevation for protected paths: when a delete / rename / copy hits
+// PERMISSION_DENIED on a system folder, the frontend can offer "Retry as
+// administrator" and call run_elevated with that one operation, instead of
+// the user running the whole app as admin. Off unless settings
+// `system.allow_elevation` is on.
+//
+// Flow:
+//   1) run_elevated checks the op against the sandbox roots and vets it
+//      (absolute paths, no "..", no drive roots, nothing under PROTECTED or
+//      the app's install folder, renames stay in their folder, everything
+//      under copy / move sources readable without elevation) and opens a
+//      one-off local endpoint. Deleted, renamed and moved paths are
+//      checked as entries: a symlink there is acted on itself.
+//      Endpoint:
+//        - unix:    socket helper.sock in a new 0700 folder
+//                   filesup-elevate-<token> in $XDG_RUNTIME_DIR (or the temp dir)
+//        - Windows: named pipe \\.\pipe\com.filesup.asc-elevate-<token>
+//   2) The app's own executable is started elevated with
+//      `--elevated-helper <endpoint> <app pid>` (cli.rs):
+//        - Windows: ShellExecuteEx "runas" (UAC prompt)
+//        - Linux:   pkexec
+//        - macOS:   osascript "with administrator privileges"
+//      Only an executable no unprivileged user can replace is elevated
+//      (trusted_exe): never a copy under the user's versions/ folder.
+//   3) The helper connects, reads the op as one JSON line, vets it again on
+//      the symlink-resolved paths, runs it and writes back the failures as
+//      one JSON line, then exits.
+//
+// Both ends authenticate each other, so another process of the same user
+// can neither feed the helper an op nor pose as the helper:
+//   - the helper checks that the endpoint belongs to <app pid>
+//     (SO_PEERCRED / LOCAL_PEERPID, GetNamedPipeServerProcessId);
+//   - the app checks that the peer runs as root (unix) or is the process it
+//     launched (GetNamedPipeClientProcessId);
+//   - on Linux the request also carries a random secret the helper got on
+//     its stdin through pkexec. UAC and osascript can't hand the helper a
+//     pipe, so there the peer checks stand alone.
+//
+// The helper only knows the ElevatedOp variants below; there is no way to
+// hand it a shell command. Deletes are permanent (the admin's trash is not
+// the user's). Copy / move never overwrite. Results go into the audit log.
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use walkdir::WalkDir;
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::FileOpFailure;
+use crate::path_guard::{self, PathGuard};
+use crate::settings::SettingsState;
+use crate::update;
+
+/// Command-line flag that turns the executable into the helper.
+pub const HELPER_FLAG: &str = "--elevated-helper";
+
+/// Longest JSON line accepted on either side.
+const MAX_MESSAGE: u64 = 16 * 1024 * 1024;
+
+/// Paths per operation.
+const MAX_PATHS: usize = 10_000;
+
+/// How long the helper may take, including the password prompt.
+const HELPER_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// System folders the helper never touches, nor their parents.
+#[cfg(unix)]
+const PROTECTED: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/libx32",
+    "/proc",
+    "/root",
+    "/sbin",
+    "/sys",
+    "/usr/bin",
+    "/usr/sbin",
+    "/usr/lib",
+    "/usr/lib32",
+    "/usr/lib64",
+    "/usr/libexec",
+    "/var/lib",
+    "/System",
+    "/private/etc",
+    "/private/var/db",
+    "/Library/LaunchDaemons",
+    "/Library/LaunchAgents",
+    "/Library/StartupItems",
+    "/Library/Security",
+];
+
+/// One request from the app to the helper.
+#[derive(Serialize, Deserialize)]
+struct Request {
+    /// Matches what the helper got on stdin (Linux).
+    secret: String,
+    op: ElevatedOp,
+}
+
+/// One vetted operation the helper can run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ElevatedOp {
+    /// Permanent delete; folders with their contents.
+    Delete { paths: Vec<String> },
+    CreateDirectory { path: String },
+    CreateFile { path: String },
+    /// `to` must be in the same folder as `from`.
+    Rename { from: String, to: String },
+    /// Into the folder `destination`; existing names fail.
+    Copy { sources: Vec<String>, destination: String },
+    Move { sources: Vec<String>, destination: String },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevatedResult {
+    pub failed: Vec<FileOpFailure>,
+}
+
+impl ElevatedOp {
+    fn action(&self) -> AuditAction {
+        match self {
+            ElevatedOp::Delete { .. } => AuditAction::Delete,
+            ElevatedOp::CreateDirectory { .. } => AuditAction::CreateFolder,
+            ElevatedOp::CreateFile { .. } => AuditAction::CreateFile,
+            ElevatedOp::Rename { .. } => AuditAction::Rename,
+            ElevatedOp::Copy { .. } => AuditAction::Copy,
+            ElevatedOp::Move { .. } => AuditAction::Move,
+        }
+    }
+
+    /// (sources, destination) for the audit log.
+    fn audit_paths(&self) -> (Vec<&str>, Option<&str>) {
+        match self {
+            ElevatedOp::Delete { paths } => (paths.iter().map(String::as_str).collect(), None),
+            ElevatedOp::CreateDirectory { path } | ElevatedOp::CreateFile { path } => (Vec::new(), Some(path.as_str())),
+            ElevatedOp::Rename { from, to } => (vec![from.as_str()], Some(to.as_str())),
+            ElevatedOp::Copy { sources, destination } | ElevatedOp::Move { sources, destination } => {
+                (sources.iter().map(String::as_str).collect(), Some(destination.as_str()))
+            }
+        }
+    }
+
+    fn paths(&self) -> Vec<&str> {
+        let (mut paths, destination) = self.audit_paths();
+        paths.extend(destination);
+        paths
+    }
+
+    /// Paths the op acts on as entries: a symlink there is deleted,
+    /// renamed or moved itself, never what it points to.
+    fn entries(&self) -> Vec<&str> {
+        match self {
+            ElevatedOp::Delete { paths } => paths.iter().map(String::as_str).collect(),
+            ElevatedOp::Rename { from, .. } => vec![from.as_str()],
+            ElevatedOp::Move { sources, .. } => sources.iter().map(String::as_str).collect(),
+            ElevatedOp::CreateDirectory { .. } | ElevatedOp::CreateFile { .. } | ElevatedOp::Copy { .. } => Vec::new(),
+        }
+    }
+
+    /// The same op with every path checked and resolved by the sandbox:
+    /// entries with check_entry, everything else with check.
+    fn resolve(self, guard: &PathGuard) -> Result<ElevatedOp, AppError> {
+        let check = |p: String| -> Result<String, AppError> { Ok(guard.check(&p)?.to_string_lossy().to_string()) };
+        let check_all = |ps: Vec<String>| ps.into_iter().map(check).collect::<Result<Vec<_>, _>>();
+        let entry =
+            |p: String| -> Result<String, AppError> { Ok(guard.check_entry(&p)?.to_string_lossy().to_string()) };
+        let entries = |ps: Vec<String>| ps.into_iter().map(entry).collect::<Result<Vec<_>, _>>();
+        Ok(match self {
+            ElevatedOp::Delete { paths } => ElevatedOp::Delete { paths: entries(paths)? },
+            ElevatedOp::CreateDirectory { path } => ElevatedOp::CreateDirectory { path: check(path)? },
+            ElevatedOp::CreateFile { path } => ElevatedOp::CreateFile { path: check(path)? },
+            ElevatedOp::Rename { from, to } => ElevatedOp::Rename {
+                from: entry(from)?,
+                to: check(to)?,
+            },
+            ElevatedOp::Copy { sources, destination } => ElevatedOp::Copy {
+                sources: check_all(sources)?,
+                destination: check(destination)?,
+            },
+            ElevatedOp::Move { sources, destination } => ElevatedOp::Move {
+                sources: entries(sources)?,
+                destination: check(destination)?,
+            },
+        })
+    }
+
+    /// Checked by the app and again by the helper.
+    fn vet(&self) -> Result<(), AppError> {
+        let paths = self.paths();
+        let protected = protected_roots();
+        if paths.len() > MAX_PATHS {
+            return Err(AppError::invalid_input(format!("At most {} paths can be elevated at once", MAX_PATHS)));
+        }
+        for path in &paths {
+            let p = Path::new(path);
+            if !p.is_absolute() || p.components().any(|c| matches!(c, Component::ParentDir | Component::CurDir)) {
+                return Err(AppError::invalid_input(format!("Not a plain absolute path: {}", path)).with_path(path));
+            }
+            if p.parent().is_none() {
+                return Err(AppError::invalid_input(format!("Refusing to touch a drive root: {}", path)).with_path(path));
+            }
+            // Inside a protected folder, or a parent of one.
+            if let Some(root) = protected.iter().find(|r| p.starts_with(r) || r.starts_with(p)) {
+                return Err(AppError::new(
+                    ErrorCode::PermissionDenied,
+                    format!("Refusing to elevate inside the protected folder {}", root.display()),
+                )
+                .with_path(path));
+            }
+        }
+        match self {
+            ElevatedOp::Rename { from, to } if Path::new(from).parent() != Path::new(to).parent() => {
+                Err(AppError::invalid_input("A rename has to stay in the same folder").with_path(to))
+            }
+            ElevatedOp::Copy { sources, destination } | ElevatedOp::Move { sources, destination } => {
+                match sources.iter().find(|s| Path::new(destination).starts_with(s)) {
+                    Some(s) => Err(AppError::invalid_input("Cannot copy a folder into itself").with_path(s)),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Helper side: every path must still resolve to itself, so nothing on
+    /// the way was swapped for a symlink after the app checked it.
+    fn unchanged(&self) -> Result<(), AppError> {
+        let entries = self.entries();
+        for path in self.paths() {
+            let resolved = if entries.contains(&path) {
+                path_guard::resolve_entry(Path::new(path))
+            } else {
+                path_guard::resolve(Path::new(path))
+            };
+            if resolved.ok().as_deref() != Some(Path::new(path)) {
+                return Err(AppError::invalid_input(format!("Changed since it was checked: {}", path)).with_path(path));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs in the helper; returns what failed.
+    fn execute(&self) -> Vec<FileOpFailure> {
+        let mut failed = Vec::new();
+        let mut run = |path: &str, res: Result<(), AppError>| {
+            if let Err(e) = res {
+                failed.push(FileOpFailure::new(path, e));
+            }
+        };
+        match self {
+            ElevatedOp::Delete { paths } => {
+                for path in paths {
+                    run(path, delete(Path::new(path)));
+                }
+            }
+            ElevatedOp::CreateDirectory { path } => run(path, fs::create_dir(path).map_err(|e| AppError::io(&e))),
+            ElevatedOp::CreateFile { path } => run(
+                path,
+                OpenOptions::new().write(true).create_new(true).open(path).map(|_| ()).map_err(|e| AppError::io(&e)),
+            ),
+            ElevatedOp::Rename { from, to } => run(from, rename_new(Path::new(from), Path::new(to))),
+            ElevatedOp::Copy { sources, destination } => {
+                for source in sources {
+                    let res = target(Path::new(source), Path::new(destination))
+                        .and_then(|target| copy_tree(Path::new(source), &target));
+                    run(source, res);
+                }
+            }
+            ElevatedOp::Move { sources, destination } => {
+                for source in sources {
+                    let res = target(Path::new(source), Path::new(destination))
+                        .and_then(|target| move_path(Path::new(source), &target));
+                    run(source, res);
+                }
+            }
+        }
+        failed
+    }
+}
+
+fn allowed(app: &AppHandle) -> bool {
+    app.try_state::<SettingsState>().is_some_and(|s| {
+        let system = s.system_handle();
+        let allowed = system.read().unwrap_or_else(|e| e.into_inner()).allow_elevation;
+        allowed
+    })
+}
+
+/// PROTECTED (%SystemRoot% on Windows) and the app's install folder, as
+/// given and canonical.
+fn protected_roots() -> Vec<PathBuf> {
+    #[cfg(unix)]
+    let mut roots: Vec<PathBuf> = PROTECTED.iter().map(PathBuf::from).collect();
+    #[cfg(windows)]
+    let mut roots = vec![std::env::var_os("SystemRoot").map_or_else(|| PathBuf::from(r"C:\Windows"), PathBuf::from)];
+    if let Ok(exe) = std::env::current_exe() {
+        // The .app bundle on macOS, the exe's folder elsewhere.
+        let bundle = exe.ancestors().skip(1).find(|a| a.extension().is_some_and(|e| e == "app"));
+        roots.extend(bundle.or(exe.parent()).map(Path::to_path_buf));
+    }
+    let canonical: Vec<PathBuf> = roots.iter().filter_map(|r| fs::canonicalize(r).ok()).collect();
+    roots.extend(canonical);
+    roots
+}
+
+/// The executable to start elevated. Never one an unprivileged process
+/// could swap, such as a downloaded version under versions/.
+fn trusted_exe(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let exe = std::env::current_exe()
+        .and_then(fs::canonicalize)
+        .map_err(|e| AppError::io(&e).context("Cannot locate the app executable"))?;
+    let untrusted = || {
+        AppError::new(
+            ErrorCode::PermissionDenied,
+            "Running as administrator needs the app installed system-wide",
+        )
+        .with_path(&exe)
+    };
+    if let Ok(versions) = update::versions_root(app) {
+        let versions = fs::canonicalize(&versions).unwrap_or(versions);
+        if exe.starts_with(&versions) {
+            return Err(untrusted());
+        }
+    }
+    if !installed_system_wide(&exe) {
+        return Err(untrusted());
+    }
+    Ok(exe)
+}
+
+/// The exe and every folder above it belong to root and only root (or
+/// the admin group on macOS) can write to them.
+#[cfg(unix)]
+fn installed_system_wide(exe: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let trusted_groups: &[u32] = if cfg!(target_os = "macos") { &[0, 80] } else { &[0] };
+    exe.ancestors().all(|p| {
+        fs::metadata(p).is_ok_and(|m| {
+            m.uid() == 0 && m.mode() & 0o002 == 0 && (m.mode() & 0o020 == 0 || trusted_groups.contains(&m.gid()))
+        })
+    })
+}
+
+/// Under Program Files, which only administrators can write to.
+#[cfg(windows)]
+fn installed_system_wide(exe: &Path) -> bool {
+    ["ProgramFiles", "ProgramW6432", "ProgramFiles(x86)"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .filter_map(|dir| fs::canonicalize(dir).ok())
+        .any(|dir| exe.starts_with(dir))
+}
+
+/// Copying (and moving across volumes, which copies) runs as
+/// administrator, so everything under the sources has to be readable
+/// without it; otherwise it would leak files the user can't read.
+/// Symlinks aren't followed, copy_tree recreates them as links.
+fn readable(path: &Path) -> Result<(), AppError> {
+    let unreadable = |e: &io::Error, p: &Path| {
+        AppError::io(e)
+            .context("Copied or moved files must be readable without administrator rights")
+            .with_path(p)
+    };
+    for entry in WalkDir::new(path).follow_root_links(false) {
+        let entry = entry.map_err(|e| {
+            let p = e.path().unwrap_or(path).to_path_buf();
+            unreadable(&io::Error::from(e), &p)
+        })?;
+        let res = if entry.file_type().is_dir() {
+            fs::read_dir(entry.path()).map(|_| ())
+        } else if entry.file_type().is_file() {
+            fs::File::open(entry.path()).map(|_| ())
+        } else {
+            Ok(())
+        };
+        res.map_err(|e| unreadable(&e, entry.path()))?;
+    }
+    Ok(())
+}
+
+/// Run one file operation with administrator rights (UAC / pkexec /
+/// macOS password prompt). Cancelling the prompt gives CANCELLED.
+///
+/// Frontend can call (after a PERMISSION_DENIED):
+///   invoke<{ failed: FileOpFailure[] }>('run_elevated', { op: { kind: 'delete', paths: ['C:\\Program Files\\Old'] } })
+///   kinds: delete { paths }, createDirectory { path }, createFile { path },
+///          rename { from, to }, copy / move { sources, destination }
+#[tauri::command]
+pub async fn run_elevated(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    op: ElevatedOp,
+) -> Result<ElevatedResult, AppError> {
+    if !allowed(&app) {
+        return Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            "Running operations as administrator is turned off in settings",
+        ));
+    }
+    let op = op.resolve(&guard)?;
+    op.vet()?;
+    if let ElevatedOp::Copy { sources, .. } | ElevatedOp::Move { sources, .. } = &op {
+        for source in sources {
+            readable(Path::new(source))?;
+        }
+    }
+
+    let exe = trusted_exe(&app)?;
+    let request = Request {
+        secret: random_token()?,
+        op,
+    };
+    let mut endpoint = Endpoint::create(&random_token()?)
+        .map_err(|e| AppError::io(&e).context("Failed to open the helper endpoint"))?;
+    let name = endpoint.name.clone();
+    let secret = request.secret.clone();
+    let (started, helper_pid) = tokio::sync::oneshot::channel();
+    let mut helper = tauri::async_runtime::spawn_blocking(move || launch(&exe, &name, &secret, started));
+
+    let res = tokio::select! {
+        biased;
+        res = endpoint.exchange(&request, helper_pid) => res,
+        exited = &mut helper => match exited {
+            Ok(Ok(())) => Err(AppError::new(ErrorCode::Internal, "The elevated helper exited without reporting back")),
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(AppError::new(ErrorCode::Internal, format!("Elevation task failed: {}", e))),
+        },
+        _ = tokio::time::sleep(HELPER_TIMEOUT) => {
+            Err(AppError::new(ErrorCode::Internal, "The elevated helper did not answer in time"))
+        }
+    };
+
+    let op = &request.op;
+    let (sources, destination) = op.audit_paths();
+    let mut entry = AuditEntry::new(op.action()).sources(&sources);
+    if let Some(destination) = destination {
+        entry = entry.destination(destination);
+    }
+    entry = match &res {
+        Ok(failed) => entry.batch_outcome(sources.len().max(1), failed.len()),
+        Err(e) => entry.result("error", Some(e.message.clone())),
+    };
+    audit::record(&app, entry);
+
+    res.map(|failed| ElevatedResult { failed })
+}
+
+fn random_token() -> Result<String, AppError> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| AppError::new(ErrorCode::Internal, "System random generator failed"))?;
+    Ok(hex::encode(bytes))
+}
+
+/// App side: send the request, read back the failures.
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &Request,
+) -> Result<Vec<FileOpFailure>, AppError> {
+    let mut line = serde_json::to_vec(request).map_err(|e| AppError::new(ErrorCode::Internal, e.to_string()))?;
+    line.push(b'\n');
+    stream.write_all(&line).await.map_err(|e| AppError::io(&e))?;
+    let mut reply = Vec::new();
+    tokio::io::BufReader::new((&mut stream).take(MAX_MESSAGE))
+        .read_until(b'\n', &mut reply)
+        .await
+        .map_err(|e| AppError::io(&e))?;
+    serde_json::from_slice(&reply).map_err(|e| {
+        AppError::new(ErrorCode::Internal, "The elevated helper sent an invalid reply").with_details(e.to_string())
+    })
+}
+
+#[cfg(unix)]
+struct Endpoint {
+    name: String,
+    dir: PathBuf,
+    listener: tokio::net::UnixListener,
+}
+
+#[cfg(unix)]
+impl Endpoint {
+    fn create(token: &str) -> io::Result<Self> {
+        use std::os::unix::fs::DirBuilderExt;
+
+        let base = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .filter(|d| d.is_dir())
+            .unwrap_or_else(std::env::temp_dir);
+        // A fresh folder only we can enter; fails if the name is taken.
+        let dir = base.join(format!("filesup-elevate-{}", token));
+        fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let path = dir.join("helper.sock");
+        // The socket is created 0600 rather than chmod-ed afterwards.
+        // SAFETY: umask only swaps the process' file creation mask.
+        let umask = unsafe { libc::umask(0o177) };
+        let bound = tokio::net::UnixListener::bind(&path);
+        unsafe { libc::umask(umask) };
+        match bound {
+            Ok(listener) => Ok(Endpoint {
+                name: path.to_string_lossy().to_string(),
+                dir,
+                listener,
+            }),
+            Err(e) => {
+                let _ = fs::remove_dir(&dir);
+                Err(e)
+            }
+        }
+    }
+
+    /// Only a peer running as root gets the request.
+    async fn exchange(
+        &mut self,
+        request: &Request,
+        _helper_pid: tokio::sync::oneshot::Receiver<u32>,
+    ) -> Result<Vec<FileOpFailure>, AppError> {
+        loop {
+            let (stream, _) = self.listener.accept().await.map_err(|e| AppError::io(&e))?;
+            match stream.peer_cred() {
+                Ok(cred) if cred.uid() == 0 => return exchange(stream, request).await,
+                Ok(cred) => tracing::warn!("[Elevation] Ignoring a helper connection from uid {}", cred.uid()),
+                Err(e) => tracing::warn!("[Elevation] Ignoring a helper connection: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.name);
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+#[cfg(windows)]
+struct Endpoint {
+    name: String,
+    server: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+#[cfg(windows)]
+impl Endpoint {
+    fn create(token: &str) -> io::Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let name = format!(r"\\.\pipe\com.filesup.asc-elevate-{}", token);
+        let server = ServerOptions::new()
+            .first_pipe_instance(true)
+            .reject_remote_clients(true)
+            .create(&name)?;
+        Ok(Endpoint { name, server })
+    }
+
+    /// Only the process launch() started gets the request.
+    async fn exchange(
+        &mut self,
+        request: &Request,
+        helper_pid: tokio::sync::oneshot::Receiver<u32>,
+    ) -> Result<Vec<FileOpFailure>, AppError> {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::Pipes::GetNamedPipeClientProcessId;
+
+        // No pid means launch() failed; run_elevated reports that instead.
+        let Ok(helper_pid) = helper_pid.await else {
+            return std::future::pending().await;
+        };
+        loop {
+            self.server.connect().await.map_err(|e| AppError::io(&e))?;
+            let mut client = 0u32;
+            // SAFETY: the handle is our connected pipe server.
+            let ok = unsafe { GetNamedPipeClientProcessId(self.server.as_raw_handle() as _, &mut client) };
+            if ok != 0 && client == helper_pid {
+                return exchange(&mut self.server, request).await;
+            }
+            tracing::warn!("[Elevation] Ignoring a helper connection from process {}", client);
+            self.server.disconnect().map_err(|e| AppError::io(&e))?;
+        }
+    }
+}
+
+/// Start the helper elevated and wait until it exits.
+#[cfg(windows)]
+fn launch(
+    exe: &Path,
+    endpoint: &str,
+    _secret: &str,
+    started: tokio::sync::oneshot::Sender<u32>,
+) -> Result<(), AppError> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_CANCELLED};
+    use windows_sys::Win32::System::Threading::{GetProcessId, WaitForSingleObject, INFINITE};
+    use windows_sys::Win32::UI::Shell::{ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain([0]).collect()
+    }
+
+    let verb = wide("runas");
+    let file: Vec<u16> = exe.as_os_str().encode_wide().chain([0]).collect();
+    let params = wide(&format!("{} \"{}\" {}", HELPER_FLAG, endpoint, std::process::id()));
+    // SAFETY: zeroed is a valid SHELLEXECUTEINFOW; the strings outlive the call.
+    let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+    info.fMask = SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC;
+    info.lpVerb = verb.as_ptr();
+    info.lpFile = file.as_ptr();
+    info.lpParameters = params.as_ptr();
+    info.nShow = SW_HIDE;
+    if unsafe { ShellExecuteExW(&mut info) } == 0 {
+        let e = io::Error::last_os_error();
+        if e.raw_os_error() == Some(ERROR_CANCELLED as i32) {
+            return Err(AppError::cancelled());
+        }
+        return Err(AppError::io(&e).context("Failed to start the elevated helper"));
+    }
+    if !info.hProcess.is_null() {
+        // SAFETY: hProcess is ours to query, wait on and close (SEE_MASK_NOCLOSEPROCESS).
+        unsafe {
+            let _ = started.send(GetProcessId(info.hProcess));
+            WaitForSingleObject(info.hProcess, INFINITE);
+            CloseHandle(info.hProcess);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn launch(
+    exe: &Path,
+    endpoint: &str,
+    secret: &str,
+    started: tokio::sync::oneshot::Sender<u32>,
+) -> Result<(), AppError> {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new("pkexec")
+        .arg(exe)
+        .arg(HELPER_FLAG)
+        .arg(endpoint)
+        .arg(std::process::id().to_string())
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::io(&e).context("Failed to run pkexec"))?;
+    // pkexec execs the helper in place, keeping pid and stdin.
+    let _ = started.send(child.id());
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{}", secret);
+    }
+    let status = child.wait().map_err(|e| AppError::io(&e).context("Failed to run pkexec"))?;
+    match status.code() {
+        // The authentication dialog was dismissed.
+        Some(126) => Err(AppError::cancelled()),
+        Some(127) => Err(AppError::new(ErrorCode::PermissionDenied, "Not authorized to run as administrator")),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn launch(
+    exe: &Path,
+    endpoint: &str,
+    _secret: &str,
+    _started: tokio::sync::oneshot::Sender<u32>,
+) -> Result<(), AppError> {
+    fn sh_quote(s: &str) -> String {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+    let command = format!(
+        "{} {} {} {}",
+        sh_quote(&exe.to_string_lossy()),
+        HELPER_FLAG,
+        sh_quote(endpoint),
+        std::process::id()
+    );
+    let script = format!(
+        "do shell script \"{}\" with administrator privileges",
+        command.replace('\\', r"\\").replace('"', "\\\"")
+    );
+    let output = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .output()
+        .map_err(|e| AppError::io(&e).context("Failed to run osascript"))?;
+    // -128: "User canceled."
+    if !output.status.success() && String::from_utf8_lossy(&output.stderr).contains("-128") {
+        return Err(AppError::cancelled());
+    }
+    Ok(())
+}
+
+/// `--elevated-helper <endpoint> <app pid>` (cli.rs): serve one op;
+/// returns the process exit code.
+pub fn run_helper(endpoint: &str, app_pid: u32) -> i32 {
+    let res = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .and_then(|runtime| runtime.block_on(serve_helper(endpoint, app_pid)));
+    match res {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Elevated helper failed: {}", e);
+            1
+        }
+    }
+}
+
+async fn serve_helper(endpoint: &str, app_pid: u32) -> io::Result<()> {
+    let secret = expected_secret()?;
+    let mut stream = connect(endpoint, app_pid).await?;
+    let mut line = Vec::new();
+    tokio::io::BufReader::new((&mut stream).take(MAX_MESSAGE))
+        .read_until(b'\n', &mut line)
+        .await?;
+    let request: Request = serde_json::from_slice(&line).map_err(io::Error::other)?;
+    if secret.is_some_and(|s| s.is_empty() || !same_secret(&s, &request.secret)) {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "Wrong secret"));
+    }
+    let op = request.op;
+    let failed = match op.vet().and_then(|()| op.unchanged()) {
+        Ok(()) => op.execute(),
+        Err(e) => vec![FileOpFailure::new(e.path.clone().unwrap_or_default(), e)],
+    };
+    let mut reply = serde_json::to_vec(&failed).map_err(io::Error::other)?;
+    reply.push(b'\n');
+    stream.write_all(&reply).await
+}
+
+/// The secret launch() wrote to our stdin (through pkexec).
+#[cfg(all(unix, not(target_os = "macos")))]
+fn expected_secret() -> io::Result<Option<String>> {
+    use std::io::{BufRead, Read};
+
+    let mut line = String::new();
+    io::stdin().lock().take(256).read_line(&mut line)?;
+    Ok(Some(line.trim_end().to_string()))
+}
+
+/// UAC and osascript can't hand us a pipe; connect() checks the peer.
+#[cfg(any(windows, target_os = "macos"))]
+fn expected_secret() -> io::Result<Option<String>> {
+    Ok(None)
+}
+
+/// Constant-time comparison.
+fn same_secret(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Connect, and make sure the endpoint is served by the app that
+/// started us.
+#[cfg(unix)]
+async fn connect(endpoint: &str, app_pid: u32) -> io::Result<tokio::net::UnixStream> {
+    let stream = tokio::net::UnixStream::connect(endpoint).await?;
+    let owner = stream.peer_cred()?.pid();
+    if owner.and_then(|pid| u32::try_from(pid).ok()) != Some(app_pid) {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "The endpoint does not belong to the app"));
+    }
+    Ok(stream)
+}
+
+#[cfg(windows)]
+async fn connect(endpoint: &str, app_pid: u32) -> io::Result<tokio::net::windows::named_pipe::NamedPipeClient> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::System::Pipes::GetNamedPipeServerProcessId;
+
+    let client = tokio::net::windows::named_pipe::ClientOptions::new().open(endpoint)?;
+    let mut owner = 0u32;
+    // SAFETY: the handle is our open pipe client.
+    let ok = unsafe { GetNamedPipeServerProcessId(client.as_raw_handle() as _, &mut owner) };
+    if ok == 0 || owner != app_pid {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "The endpoint does not belong to the app"));
+    }
+    Ok(client)
+}
+
+fn delete(path: &Path) -> Result<(), AppError> {
+    let meta = fs::symlink_metadata(path).map_err(|e| AppError::io(&e))?;
+    let res = if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    res.map_err(|e| AppError::io(&e))
+}
+
+fn rename_new(from: &Path, to: &Path) -> Result<(), AppError> {
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(AppError::new(ErrorCode::AlreadyExists, format!("Already exists: {}", to.display())));
+    }
+    fs::rename(from, to).map_err(|e| AppError::io(&e))
+}
+
+/// `destination/<name of source>`, which must not exist yet.
+fn target(source: &Path, destination: &Path) -> Result<PathBuf, AppError> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| AppError::invalid_input(format!("No file name: {}", source.display())))?;
+    let target = destination.join(name);
+    if fs::symlink_metadata(&target).is_ok() {
+        return Err(AppError::new(ErrorCode::AlreadyExists, format!("Already exists: {}", target.display())));
+    }
+    Ok(target)
+}
+
+/// Recursive copy of folders, regular files and symlinks (as links, so
+/// nothing outside `from` is read). A file swapped for a symlink after
+/// readable() ran is copied as that link, not followed.
+fn copy_tree(from: &Path, to: &Path) -> Result<(), AppError> {
+    for entry in WalkDir::new(from).follow_root_links(false) {
+        let entry = entry.map_err(|e| AppError::new(ErrorCode::Io, e.to_string()))?;
+        let rel = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(rel);
+        let file_type = entry.file_type();
+        let res = if file_type.is_dir() {
+            fs::create_dir_all(&target)
+        } else if file_type.is_file() {
+            fs::copy(entry.path(), &target).map(|_| ())
+        } else if file_type.is_symlink() {
+            copy_link(entry.path(), &target)
+        } else {
+            Ok(())
+        };
+        res.map_err(|e| AppError::io(&e).with_path(entry.path()))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_link(src: &Path, dest: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dest)
+}
+
+#[cfg(windows)]
+fn copy_link(src: &Path, dest: &Path) -> io::Result<()> {
+    use std::os::windows::fs::FileTypeExt;
+
+    let target = fs::read_link(src)?;
+    if fs::symlink_metadata(src)?.file_type().is_symlink_dir() {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
+
+/// Rename, or copy and delete across volumes.
+fn move_path(from: &Path, to: &Path) -> Result<(), AppError> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_tree(from, to)?;
+    delete(from)
+}
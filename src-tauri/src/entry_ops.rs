@@ -0,0 +1,139 @@
+// src-tauri/src/entry_ops.rs
+//
+// Basic write primitives for the file list: "New folder", "New file" and
+// inline rename (F2).
+// Errors are mapped to short, user-facing messages ("Already exists: ...",
+// "Permission denied: ...", "Invalid name: ...") instead of raw OS text,
+// since the UI shows them directly in the rename box / toast.
+// Every attempt is recorded in the audit log (audit.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::catalog::Catalog;
+use crate::path_guard::PathGuard;
+use crate::rename_ops::validate_name;
+use std::fs::{self, OpenOptions};
+use std::io::ErrorKind;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// Create a folder. With `recursive: true` missing parents are created too
+/// (and an existing folder is not an error, like `mkdir -p`).
+///
+/// Frontend can call:
+///   invoke<string>('create_directory', { path, recursive: false })
+#[tauri::command]
+pub fn create_directory(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<String, String> {
+    let res = make_directory(&guard, path.clone(), recursive.unwrap_or(false));
+    audit::record(&app, AuditEntry::new(AuditAction::CreateFolder).destination(path).outcome(&res));
+    res
+}
+
+fn make_directory(guard: &PathGuard, path: String, recursive: bool) -> Result<String, String> {
+    let p = guard.check_entry(&path)?;
+    check_name(&p)?;
+
+    let res = if recursive {
+        fs::create_dir_all(&p)
+    } else {
+        fs::create_dir(&p)
+    };
+    res.map_err(|e| map_io_error(&e, &path))?;
+    Ok(path)
+}
+
+/// Create an empty file; fails if anything already exists at `path`.
+///
+/// Frontend can call:
+///   invoke<string>('create_empty_file', { path })
+#[tauri::command]
+pub fn create_empty_file(app: AppHandle, guard: State<'_, PathGuard>, path: String) -> Result<String, String> {
+    let res = make_empty_file(&guard, path.clone());
+    audit::record(&app, AuditEntry::new(AuditAction::CreateFile).destination(path).outcome(&res));
+    res
+}
+
+fn make_empty_file(guard: &PathGuard, path: String) -> Result<String, String> {
+    let p = guard.check_entry(&path)?;
+    check_name(&p)?;
+
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&p)
+        .map_err(|e| map_io_error(&e, &path))?;
+    Ok(path)
+}
+
+/// Rename (or move within the same volume) a file or folder.
+/// Never overwrites: an existing `to` is an error, except for a case-only
+/// rename of the same entry ("readme.md" -> "README.md").
+///
+/// Frontend can call:
+///   invoke<string>('rename_entry', { from, to })
+#[tauri::command]
+pub fn rename_entry(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    catalog: State<'_, Catalog>,
+    from: String,
+    to: String,
+) -> Result<String, String> {
+    let audit = AuditEntry::new(AuditAction::Rename).sources(&[&from]).destination(to.clone());
+    let res = rename_path(&guard, &catalog, from, to);
+    audit::record(&app, audit.outcome(&res));
+    res
+}
+
+fn rename_path(guard: &PathGuard, catalog: &Catalog, from: String, to: String) -> Result<String, String> {
+    let src = guard.check_entry(&from)?;
+    let dest = guard.check_entry(&to)?;
+    check_name(&dest)?;
+
+    fs::symlink_metadata(&src).map_err(|e| map_io_error(&e, &from))?;
+    if fs::symlink_metadata(&dest).is_ok() && !same_entry(&src, &dest) {
+        return Err(format!("Already exists: {}", to));
+    }
+
+    fs::rename(&src, &dest).map_err(|e| match e.kind() {
+        ErrorKind::CrossesDevices => format!(
+            "Cannot rename across drives: {} -> {} (use move instead)",
+            from, to
+        ),
+        _ => map_io_error(&e, &from),
+    })?;
+    catalog.rekey(&src, &dest);
+    Ok(to)
+}
+
+fn check_name(path: &Path) -> Result<(), String> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    validate_name(&name).map_err(|e| format!("Invalid name: {}", e))
+}
+
+/// Same file reached through a differently-cased path (case-insensitive FS).
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn map_io_error(e: &std::io::Error, path: &str) -> String {
+    match e.kind() {
+        ErrorKind::AlreadyExists => format!("Already exists: {}", path),
+        ErrorKind::NotFound => format!("Not found: {}", path),
+        ErrorKind::PermissionDenied => format!("Permission denied: {}", path),
+        ErrorKind::InvalidInput | ErrorKind::InvalidFilename => format!("Invalid name: {}", path),
+        ErrorKind::ReadOnlyFilesystem => format!("Read-only file system: {}", path),
+        ErrorKind::StorageFull => format!("Disk is full: {}", path),
+        _ => format!("{}: {}", path, e),
+    }
+}
@@ -0,0 +1,223 @@
+// src-tauri/src/error.rs
+//
+// Structured error shared by commands and completion events, so the
+// frontend can branch on `code` instead of parsing message text:
+//
+//   { "code": "NOT_FOUND", "message": "Path does not exist: C:\\x", "path": "C:\\x" }
+//
+// - `message` is user-facing; `details` carries the low-level cause (OS
+//   error text, anyhow chain) for logs and "copy details".
+// - Commands that still return Result<_, String> can use `?` on an
+//   AppError; it turns into its message.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::network_share;
+use crate::path_guard::PathGuardError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotFound,
+    PermissionDenied,
+    /// Outside the allowed roots (path_guard.rs), not an OS permission issue.
+    AccessDenied,
+    AlreadyExists,
+    NotADirectory,
+    InvalidInput,
+    DiskFull,
+    /// Checked before a copy / move / extraction starts; the error carries
+    /// `insufficientSpace { required, available }`.
+    InsufficientSpace,
+    Cancelled,
+    /// Any other I/O failure.
+    Io,
+    /// Update check / download / apply failed (network, signatures, ...).
+    UpdateFailed,
+    /// A remote server's TLS certificate was rejected (self-signed,
+    /// expired, wrong host name).
+    Certificate,
+    /// A network share wants (other) credentials; the error carries
+    /// `share` ("\\server\share") to pass to connect_network_share.
+    NetworkAuthRequired,
+    /// An SSH host isn't in known_hosts yet and nothing was sent to it; the
+    /// error carries `hostKey` ("SHA256:...") for the user to confirm and
+    /// pass back to connect_sftp as acceptedHostKey.
+    HostKeyUnknown,
+    /// Read-only safe mode is on (safe_mode.rs); nothing was changed.
+    SafeModeEnabled,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// Boxed for the same reason as `share`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insufficient_space: Option<Box<InsufficientSpace>>,
+    /// Only with NETWORK_AUTH_REQUIRED, once a path is attached. Boxed so
+    /// Result<_, AppError> stays small.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share: Option<Box<str>>,
+    /// Only with HOST_KEY_UNKNOWN.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_key: Option<Box<str>>,
+}
+
+/// Payload of INSUFFICIENT_SPACE, in bytes.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsufficientSpace {
+    pub required: u64,
+    pub available: u64,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        AppError {
+            code,
+            message: message.into(),
+            path: None,
+            details: None,
+            insufficient_space: None,
+            share: None,
+            host_key: None,
+        }
+    }
+
+    pub fn cancelled() -> Self {
+        AppError::new(ErrorCode::Cancelled, "Operation was cancelled")
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        AppError::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn insufficient_space(required: u64, available: u64) -> Self {
+        let mut err = AppError::new(
+            ErrorCode::InsufficientSpace,
+            format!(
+                "Not enough free space: {} bytes needed, {} bytes available",
+                required, available
+            ),
+        );
+        err.insufficient_space = Some(Box::new(InsufficientSpace { required, available }));
+        err
+    }
+
+    /// First connection to `host`; `fingerprint` awaits confirmation.
+    pub fn host_key_unknown(host: &str, fingerprint: &str) -> Self {
+        let mut err = AppError::new(
+            ErrorCode::HostKeyUnknown,
+            format!("{} is not a known host; confirm its key {} to connect", host, fingerprint),
+        );
+        err.host_key = Some(fingerprint.into());
+        err
+    }
+
+    /// `action` (a command name) was refused because safe mode is on.
+    pub fn safe_mode(action: &str) -> Self {
+        AppError::new(
+            ErrorCode::SafeModeEnabled,
+            format!("Safe mode is on: {} is not allowed", action),
+        )
+    }
+
+    /// Map an io::Error by kind; the OS text becomes the message.
+    pub fn io(e: &io::Error) -> Self {
+        AppError::new(io_code(e), e.to_string())
+    }
+
+    /// Error from the update subsystem; an io::Error anywhere in the chain
+    /// decides the code (e.g. PERMISSION_DENIED on the versions folder).
+    pub fn update(e: anyhow::Error) -> Self {
+        let code = e
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<io::Error>())
+            .map(io_code)
+            .unwrap_or(ErrorCode::UpdateFailed);
+        let chain = format!("{:#}", e);
+        let message = e.to_string();
+        let mut err = AppError::new(code, message.clone());
+        if chain != message {
+            err.details = Some(chain);
+        }
+        err
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        if self.code == ErrorCode::NetworkAuthRequired && self.share.is_none() {
+            self.share = network_share::share_of(path.as_ref()).map(String::into_boxed_str);
+        }
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    /// Prefix the message: "Failed to read directory: <message>".
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+fn io_code(e: &io::Error) -> ErrorCode {
+    if network_share::is_auth_error(e) {
+        return ErrorCode::NetworkAuthRequired;
+    }
+    match e.kind() {
+        io::ErrorKind::NotFound => ErrorCode::NotFound,
+        io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+        io::ErrorKind::AlreadyExists => ErrorCode::AlreadyExists,
+        io::ErrorKind::NotADirectory => ErrorCode::NotADirectory,
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidFilename => ErrorCode::InvalidInput,
+        io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => ErrorCode::DiskFull,
+        _ => ErrorCode::Io,
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message
+    }
+}
+
+impl From<PathGuardError> for AppError {
+    fn from(e: PathGuardError) -> Self {
+        let message = e.to_string();
+        match e {
+            PathGuardError::AccessDenied { path } => {
+                AppError::new(ErrorCode::AccessDenied, message).with_path(path)
+            }
+            PathGuardError::InvalidPath { path, .. } => {
+                AppError::new(ErrorCode::InvalidInput, message).with_path(path)
+            }
+            PathGuardError::NetworkAuthRequired { path, share } => {
+                let mut err = AppError::new(ErrorCode::NetworkAuthRequired, message).with_path(path);
+                err.share = Some(share.into_boxed_str());
+                err
+            }
+        }
+    }
+}
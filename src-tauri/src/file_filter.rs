@@ -0,0 +1,216 @@
+// src-tauri/src/file_filter.rs
+//
+// One filter for every feature that walks or lists files: list_dir /
+// list_dir_stream, content and name search, folder scans and the folder
+// compare (sync preview). The frontend sends the same FileFilterSpec to
+// each of them and gets the same answer for the same entry.
+//
+// Rules (all given criteria must hold):
+// - Size range, modified range, extensions and include globs apply to
+//   files only; folders are never hidden by them, so a walk still reaches
+//   the matching files below.
+// - hidden / readonly: None = any, Some(true) = only such entries,
+//   Some(false) = none of them. Applies to folders as results too; walks
+//   only skip hidden folders (hidden: false), since a plain folder can
+//   hold hidden or read-only files.
+// - Exclude globs drop files and folders; an excluded folder is not
+//   descended into.
+// Globs match the path relative to the walked root ("/"-separated,
+// case-insensitive): "*.jpg" matches at any depth, "**/node_modules"
+// excludes that folder wherever it is.
+
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+use crate::file_meta::is_hidden;
+use crate::vfs::VfsEntry;
+
+/// Serializable filter, as sent by the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileFilterSpec {
+    /// Bytes, inclusive.
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Seconds since UNIX_EPOCH, inclusive.
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+    /// Without the dot, case-insensitive: ["jpg", "png"].
+    pub extensions: Vec<String>,
+    pub hidden: Option<bool>,
+    pub readonly: Option<bool>,
+    /// Files must match one of these (when any are given).
+    pub include_globs: Vec<String>,
+    /// Files and folders matching any of these are skipped.
+    pub exclude_globs: Vec<String>,
+}
+
+impl FileFilterSpec {
+    pub fn is_empty(&self) -> bool {
+        *self == FileFilterSpec::default()
+    }
+}
+
+/// What the filter looks at; built from local metadata or a VfsEntry.
+pub struct FilterEntry<'a> {
+    /// Relative to the walked root (just the name for a listing).
+    pub rel_path: &'a Path,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since UNIX_EPOCH; 0 = unknown.
+    pub modified: u64,
+    pub hidden: bool,
+    /// None when the provider doesn't report it (remote entries); counts
+    /// as writable.
+    pub readonly: Option<bool>,
+}
+
+impl<'a> FilterEntry<'a> {
+    /// `meta` from symlink_metadata or walkdir.
+    pub fn from_metadata(rel_path: &'a Path, meta: &Metadata) -> Self {
+        let name = rel_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        FilterEntry {
+            rel_path,
+            is_dir: meta.is_dir(),
+            size: if meta.is_dir() { 0 } else { meta.len() },
+            modified: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            hidden: is_hidden(&name, meta),
+            readonly: Some(meta.permissions().readonly()),
+        }
+    }
+
+    pub fn from_vfs(rel_path: &'a Path, entry: &VfsEntry) -> Self {
+        FilterEntry {
+            rel_path,
+            is_dir: entry.is_dir,
+            size: entry.size,
+            modified: entry.modified,
+            hidden: entry.hidden,
+            readonly: None,
+        }
+    }
+}
+
+/// Compiled form of a FileFilterSpec.
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    spec: FileFilterSpec,
+    extensions: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl FileFilter {
+    /// Fails with INVALID_INPUT on a bad glob or an empty range.
+    pub fn new(spec: &FileFilterSpec) -> Result<Self, AppError> {
+        if let (Some(min), Some(max)) = (spec.min_size, spec.max_size) {
+            if min > max {
+                return Err(AppError::invalid_input("minSize is larger than maxSize"));
+            }
+        }
+        if let (Some(after), Some(before)) = (spec.modified_after, spec.modified_before) {
+            if after > before {
+                return Err(AppError::invalid_input("modifiedAfter is later than modifiedBefore"));
+            }
+        }
+        Ok(FileFilter {
+            extensions: spec
+                .extensions
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            include: glob_set(&spec.include_globs)?,
+            exclude: glob_set(&spec.exclude_globs)?,
+            spec: spec.clone(),
+        })
+    }
+
+    /// None for an empty spec, so callers can skip the checks entirely.
+    pub fn from_spec(spec: Option<&FileFilterSpec>) -> Result<Option<Self>, AppError> {
+        match spec {
+            Some(spec) if !spec.is_empty() => FileFilter::new(spec).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// True when readonly is filtered on; listings of remote-capable
+    /// providers then have to stat local entries themselves.
+    pub fn needs_readonly(&self) -> bool {
+        self.spec.readonly.is_some()
+    }
+
+    /// Whether a walk should enter this folder.
+    pub fn allows_dir(&self, entry: &FilterEntry) -> bool {
+        let hidden_skipped = self.spec.hidden == Some(false) && entry.hidden;
+        !hidden_skipped && !self.excluded(entry.rel_path)
+    }
+
+    /// Whether the entry is a result (listed, matched, counted).
+    pub fn matches(&self, entry: &FilterEntry) -> bool {
+        if entry.is_dir {
+            return self.attributes_match(entry) && !self.excluded(entry.rel_path);
+        }
+        let spec = &self.spec;
+        if spec.min_size.is_some_and(|min| entry.size < min) || spec.max_size.is_some_and(|max| entry.size > max) {
+            return false;
+        }
+        if spec.modified_after.is_some_and(|t| entry.modified < t)
+            || spec.modified_before.is_some_and(|t| entry.modified > t)
+        {
+            return false;
+        }
+        if !self.extensions.is_empty() {
+            let ext = entry
+                .rel_path
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if !self.extensions.contains(&ext) {
+                return false;
+            }
+        }
+        if self.include.as_ref().is_some_and(|set| !set.is_match(entry.rel_path)) {
+            return false;
+        }
+        self.attributes_match(entry) && !self.excluded(entry.rel_path)
+    }
+
+    fn attributes_match(&self, entry: &FilterEntry) -> bool {
+        self.spec.hidden.is_none_or(|h| h == entry.hidden)
+            && self.spec.readonly.is_none_or(|r| r == entry.readonly.unwrap_or(false))
+    }
+
+    fn excluded(&self, rel_path: &Path) -> bool {
+        self.exclude.as_ref().is_some_and(|set| set.is_match(rel_path))
+    }
+}
+
+/// None when the list is empty.
+fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>, AppError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| AppError::invalid_input(format!("Invalid glob '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| AppError::invalid_input(format!("Invalid glob set: {}", e)))
+}
@@ -0,0 +1,463 @@
+// src-tauri/src/file_meta.rs
+//
+// Extended per-entry metadata for list_dir / list_dir_stream when the
+// frontend passes `extended: true` (details view, properties panel).
+// The default listing stays on the cheap path: one metadata() call and
+// nothing else per entry.
+//
+// Also backs the properties dialog: get_file_attributes / set_file_attributes
+// read and change readonly + hidden (Windows attributes) and mode bits (Unix).
+//
+// Reparse points and sparse files: `reparse` tells symlinks, NTFS
+// junctions and other reparse points (cloud placeholders, dedup) apart,
+// `allocated_size` is what the file really occupies on disk. Alternate data
+// streams (NTFS) are only listed on request (list_alternate_streams), since
+// that costs a call per file.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::path_guard::PathGuard;
+
+/// Extra fields merged into FileEntry (flattened, snake_case like FileEntry).
+/// Timestamps use the same format as `modified`: seconds since UNIX_EPOCH
+/// as a string, "0" if the OS/filesystem doesn't record them.
+#[derive(Serialize, Clone)]
+pub struct ExtendedMetadata {
+    pub created: String,
+    pub accessed: String,
+    /// Lowercase extension without the dot; None for dirs and "Makefile".
+    pub extension: Option<String>,
+    pub readonly: bool,
+    pub hidden: bool,
+    pub is_symlink: bool,
+    pub symlink_target: Option<String>,
+    /// Unix permission bits as octal, e.g. "755" (None on Windows).
+    pub permissions: Option<String>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    /// None for regular entries.
+    pub reparse: Option<ReparseKind>,
+    /// Bytes actually allocated on disk (smaller than `size` for sparse or
+    /// compressed files).
+    pub allocated_size: u64,
+    pub sparse: bool,
+}
+
+/// What kind of link / reparse point an entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReparseKind {
+    Symlink,
+    /// NTFS mount point / directory junction.
+    Junction,
+    /// Any other reparse point (OneDrive placeholder, dedup, WSL, ...).
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Other,
+}
+
+/// One NTFS alternate data stream.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AlternateStream {
+    /// Stream name without the ":$DATA" suffix, e.g. "Zone.Identifier".
+    pub name: String,
+    pub size: u64,
+}
+
+/// Collect extended metadata. `meta` must come from symlink_metadata
+/// (DirEntry::metadata), so symlinks are reported as links.
+pub fn extended_metadata(path: &Path, name: &str, meta: &Metadata) -> ExtendedMetadata {
+    let is_symlink = meta.file_type().is_symlink();
+    let extension = if meta.is_dir() {
+        None
+    } else {
+        Path::new(name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+    };
+
+    let mut ext = ExtendedMetadata {
+        created: epoch_secs(meta.created().ok()),
+        accessed: epoch_secs(meta.accessed().ok()),
+        extension,
+        readonly: meta.permissions().readonly(),
+        hidden: is_hidden(name, meta),
+        is_symlink,
+        symlink_target: is_symlink
+            .then(|| std::fs::read_link(path).ok())
+            .flatten()
+            .map(|t| t.to_string_lossy().to_string()),
+        permissions: None,
+        uid: None,
+        gid: None,
+        owner: None,
+        group: None,
+        reparse: reparse_kind(path, meta),
+        allocated_size: allocated_size(path, meta),
+        sparse: false,
+    };
+    ext.sparse = is_sparse(meta, ext.allocated_size);
+    fill_unix_fields(&mut ext, meta);
+    ext
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileAttributes {
+    pub path: String,
+    pub is_dir: bool,
+    pub readonly: bool,
+    pub hidden: bool,
+    /// Unix permission bits as octal, e.g. "644" (None on Windows).
+    pub permissions: Option<String>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+}
+
+/// Fields left out (None) are not touched.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AttributeChanges {
+    pub readonly: Option<bool>,
+    /// Windows only; on Unix hidden means a dot-name, so rename instead.
+    pub hidden: Option<bool>,
+    /// Unix only; octal string like "755" or "0644".
+    pub mode: Option<String>,
+}
+
+/// Frontend can call:
+///   invoke<FileAttributes>('get_file_attributes', { path })
+#[tauri::command]
+pub fn get_file_attributes(guard: State<'_, PathGuard>, path: String) -> Result<FileAttributes, String> {
+    read_attributes(&guard.check(&path)?)
+}
+
+/// NTFS alternate data streams of a file (empty elsewhere).
+///
+/// Frontend can call:
+///   invoke<AlternateStream[]>('list_alternate_streams', { path })
+#[tauri::command]
+pub fn list_alternate_streams(guard: State<'_, PathGuard>, path: String) -> Result<Vec<AlternateStream>, String> {
+    alternate_streams(&guard.check(&path)?).map_err(|e| format!("Cannot list streams of {:?}: {}", path, e))
+}
+
+/// Apply attribute changes and return the attributes as they are afterwards.
+///
+/// Frontend can call:
+///   invoke<FileAttributes>('set_file_attributes', { path, changes: { readonly: false, mode: '644' } })
+#[tauri::command]
+pub fn set_file_attributes(
+    guard: State<'_, PathGuard>,
+    path: String,
+    changes: AttributeChanges,
+) -> Result<FileAttributes, String> {
+    let checked = guard.check(&path)?;
+    let p = checked.as_path();
+    let meta = fs::metadata(p).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+
+    if let Some(mode) = &changes.mode {
+        let bits = u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+            .ok()
+            .filter(|b| *b <= 0o7777)
+            .ok_or_else(|| format!("Invalid mode '{}' (expected octal like 644)", mode))?;
+        set_mode(p, bits)?;
+    }
+    if let Some(hidden) = changes.hidden {
+        let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if hidden != is_hidden(&name, &meta) {
+            set_hidden(p, hidden)?;
+        }
+    }
+    // Last, so "make writable + chmod" in one call doesn't fight itself.
+    if let Some(readonly) = changes.readonly {
+        let meta = fs::metadata(p).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        if readonly != meta.permissions().readonly() {
+            set_readonly(p, &meta, readonly)?;
+        }
+    }
+
+    read_attributes(p)
+}
+
+fn read_attributes(path: &Path) -> Result<FileAttributes, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Cannot read {:?}: {}", path, e))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = extended_metadata(path, &name, &meta);
+    Ok(FileAttributes {
+        path: path.to_string_lossy().to_string(),
+        is_dir: meta.is_dir(),
+        readonly: ext.readonly,
+        hidden: ext.hidden,
+        permissions: ext.permissions,
+        uid: ext.uid,
+        gid: ext.gid,
+        owner: ext.owner,
+        group: ext.group,
+    })
+}
+
+fn epoch_secs(time: Option<SystemTime>) -> String {
+    time.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs().to_string())
+        .unwrap_or_else(|| "0".to_string())
+}
+
+/// Hidden per platform convention (dot-name on Unix, attribute on Windows).
+#[cfg(windows)]
+pub fn is_hidden(_name: &str, meta: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(windows))]
+pub fn is_hidden(name: &str, _meta: &Metadata) -> bool {
+    name.starts_with('.')
+}
+
+/// Link / reparse point kind; `meta` from symlink_metadata.
+#[cfg(windows)]
+pub fn reparse_kind(path: &Path, meta: &Metadata) -> Option<ReparseKind> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstFileW, FILE_ATTRIBUTE_REPARSE_POINT, WIN32_FIND_DATAW,
+    };
+    const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+    const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+
+    if meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT == 0 {
+        return None;
+    }
+    // The tag is only exposed by FindFirstFile (dwReserved0).
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data: WIN32_FIND_DATAW = unsafe { std::mem::zeroed() };
+    let handle = unsafe { FindFirstFileW(wide.as_ptr(), &mut data) };
+    if handle == windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE {
+        return Some(ReparseKind::Other);
+    }
+    unsafe { FindClose(handle) };
+    Some(match data.dwReserved0 {
+        IO_REPARSE_TAG_SYMLINK => ReparseKind::Symlink,
+        IO_REPARSE_TAG_MOUNT_POINT => ReparseKind::Junction,
+        _ => ReparseKind::Other,
+    })
+}
+
+#[cfg(not(windows))]
+pub fn reparse_kind(_path: &Path, meta: &Metadata) -> Option<ReparseKind> {
+    meta.file_type().is_symlink().then_some(ReparseKind::Symlink)
+}
+
+/// Bytes allocated on disk. Windows only asks the filesystem for sparse /
+/// compressed files; for everything else that is the logical size.
+#[cfg(windows)]
+pub fn allocated_size(path: &Path, meta: &Metadata) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetCompressedFileSizeW, FILE_ATTRIBUTE_COMPRESSED, FILE_ATTRIBUTE_SPARSE_FILE, INVALID_FILE_SIZE,
+    };
+
+    if meta.is_dir() || meta.file_attributes() & (FILE_ATTRIBUTE_SPARSE_FILE | FILE_ATTRIBUTE_COMPRESSED) == 0 {
+        return meta.len();
+    }
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0u32;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && std::io::Error::last_os_error().raw_os_error() != Some(0) {
+        return meta.len();
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(unix)]
+pub fn allocated_size(_path: &Path, meta: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn allocated_size(_path: &Path, meta: &Metadata) -> u64 {
+    meta.len()
+}
+
+#[cfg(windows)]
+pub fn is_sparse(meta: &Metadata, _allocated: u64) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_SPARSE_FILE;
+    meta.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0
+}
+
+/// Unix has no sparse flag: a file with (a block or more) less allocated
+/// than its length has holes. Tiny files stored inline report 0 blocks,
+/// hence the margin.
+#[cfg(not(windows))]
+pub fn is_sparse(meta: &Metadata, allocated: u64) -> bool {
+    meta.is_file() && allocated + 4096 <= meta.len()
+}
+
+#[cfg(windows)]
+fn alternate_streams(path: &Path) -> std::io::Result<Vec<AlternateStream>> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{GetLastError, ERROR_HANDLE_EOF, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstStreamW, FindNextStreamW, FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+    };
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data: WIN32_FIND_STREAM_DATA = unsafe { std::mem::zeroed() };
+    let handle = unsafe {
+        FindFirstStreamW(wide.as_ptr(), FindStreamInfoStandard, &mut data as *mut _ as *mut _, 0)
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        // Folders without streams / non-NTFS volumes.
+        return match unsafe { GetLastError() } {
+            ERROR_HANDLE_EOF => Ok(Vec::new()),
+            _ => Err(std::io::Error::last_os_error()),
+        };
+    }
+
+    let mut streams = Vec::new();
+    loop {
+        let len = data.cStreamName.iter().position(|&c| c == 0).unwrap_or(data.cStreamName.len());
+        let raw = String::from_utf16_lossy(&data.cStreamName[..len]);
+        // "::$DATA" is the main stream; others look like ":name:$DATA".
+        let name = raw.trim_start_matches(':').trim_end_matches(":$DATA");
+        if !name.is_empty() && name != "$DATA" {
+            streams.push(AlternateStream {
+                name: name.to_string(),
+                size: data.StreamSize as u64,
+            });
+        }
+        if unsafe { FindNextStreamW(handle, &mut data as *mut _ as *mut _) } == 0 {
+            break;
+        }
+    }
+    unsafe { FindClose(handle) };
+    Ok(streams)
+}
+
+#[cfg(not(windows))]
+fn alternate_streams(_path: &Path) -> std::io::Result<Vec<AlternateStream>> {
+    Ok(Vec::new())
+}
+
+#[cfg(windows)]
+fn set_hidden(path: &Path, hidden: bool) -> Result<(), String> {
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_HIDDEN;
+    update_windows_attributes(path, FILE_ATTRIBUTE_HIDDEN, hidden)
+}
+
+#[cfg(not(windows))]
+fn set_hidden(_path: &Path, _hidden: bool) -> Result<(), String> {
+    Err("Hidden files are dot-files on this platform; rename the entry instead".to_string())
+}
+
+/// Windows: toggle FILE_ATTRIBUTE_READONLY directly (std's set_readonly
+/// does the same, but keeping both flags on one code path is simpler).
+#[cfg(windows)]
+fn set_readonly(path: &Path, _meta: &Metadata, readonly: bool) -> Result<(), String> {
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_READONLY;
+    update_windows_attributes(path, FILE_ATTRIBUTE_READONLY, readonly)
+}
+
+/// Unix: readonly clears every write bit; writable only restores the
+/// owner's write bit (std's set_readonly(false) would make it world-writable).
+#[cfg(unix)]
+fn set_readonly(path: &Path, meta: &Metadata, readonly: bool) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    let mode = if readonly { mode & !0o222 } else { mode | 0o200 };
+    set_mode(path, mode & 0o7777)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .map_err(|e| format!("Failed to change permissions of {:?}: {}", path, e))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<(), String> {
+    Err("Permission bits are not supported on this platform".to_string())
+}
+
+#[cfg(windows)]
+fn update_windows_attributes(path: &Path, flag: u32, on: bool) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::{SetFileAttributesW, FILE_ATTRIBUTE_NORMAL};
+
+    let current = fs::metadata(path)
+        .map_err(|e| format!("Cannot read {:?}: {}", path, e))?
+        .file_attributes();
+    let mut attrs = if on { current | flag } else { current & !flag };
+    if attrs == 0 {
+        attrs = FILE_ATTRIBUTE_NORMAL;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let ok = unsafe { SetFileAttributesW(wide.as_ptr(), attrs) };
+    if ok == 0 {
+        return Err(format!(
+            "Failed to change attributes of {:?}: {}",
+            path,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn fill_unix_fields(ext: &mut ExtendedMetadata, meta: &Metadata) {
+    use std::os::unix::fs::MetadataExt;
+
+    ext.permissions = Some(format!("{:o}", meta.mode() & 0o7777));
+    ext.uid = Some(meta.uid());
+    ext.gid = Some(meta.gid());
+    ext.owner = user_name(meta.uid());
+    ext.group = group_name(meta.gid());
+}
+
+#[cfg(not(unix))]
+fn fill_unix_fields(_ext: &mut ExtendedMetadata, _meta: &Metadata) {}
+
+/// getpwuid_r: the non-_r variant shares a static buffer between threads.
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(pwd.pw_name) };
+    Some(name.to_string_lossy().to_string())
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    let rc = unsafe { libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(grp.gr_name) };
+    Some(name.to_string_lossy().to_string())
+}
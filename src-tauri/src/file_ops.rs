@@ -0,0 +1,1621 @@
+// src-tauri/src/file_ops.rs
+//
+// Copy / move engine for the file manager.
+// Flow:
+//   1) Plan: walk every source, collect directories to create and files to
+//      copy (with sizes), so progress has a real bytes total.
+//   2) Execute: create directories, copy files in 1 MiB chunks, emitting
+//      throttled `fu:file_op_progress` events and checking the cancel token
+//      (and pause gate) between chunks.
+//   3) Report: one `fu:file_op_completed` event with totals and the list of
+//      per-file failures (a single bad file never aborts the whole job).
+//
+// Move = rename when source and destination share a volume; otherwise
+// copy + delete source files that were copied successfully.
+//
+// Remote sources or destinations (vfs/mod.rs) take the same three steps
+// through the VfsProvider trait: plan by listing, stream each file from
+// the source provider's reader into the destination provider's writer.
+// Symlinks are not carried across providers (reported as failures).
+//
+// FileOpOptions carries per-job knobs set by the transfer queue
+// (transfer_queue.rs) or at job start: a bandwidth cap, checked after every
+// chunk, and a hook run once the job has reported completion. The global
+// IO throttle (io_throttle.rs) applies on top of it.
+//
+// Existing destination files are handled by the job's ConflictPolicy:
+// skip, overwrite, overwrite-if-newer, keep both ("name (1).ext"), or ask.
+// "ask" emits `fu:file_conflict` and blocks the job until the frontend
+// calls resolve_conflict (optionally for all remaining conflicts). Without
+// a policy a conflict is reported as an AlreadyExists failure. Local
+// overwrites go through a temp file + rename; remote ones delete first.
+//
+// CopyOptions (all off by default) carry over times, creation time,
+// permissions and Windows attributes, and can verify every copy by hashing
+// the source while copying and re-reading the destination afterwards. A
+// file whose metadata could not be applied still counts as copied but is
+// listed in the failures; a verification mismatch fails the file. With
+// `retries`, a file whose copy failed mid-way or didn't verify is copied
+// again (after a short, growing pause) before it counts as failed; with
+// `verify` the completed event lists every file's outcome.
+//
+// Local jobs keep a journal while they run (op_journal.rs), so one cut short
+// by a crash can be resumed after a restart from the last finished file.
+//
+// After planning, the bytes the job will add to the destination disk are
+// compared with its free space (files that already exist there only count
+// with keep-both; same-volume moves are renames and need nothing). A job
+// that cannot fit fails before copying anything, with INSUFFICIENT_SPACE.
+// Network shares report their own free space (quotas included) and a share
+// that wants credentials fails with NETWORK_AUTH_REQUIRED (network_share.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::gps_backend::{OperationKind, OperationRegistry, PauseGate};
+use crate::io_throttle::IoThrottle;
+use crate::metrics::{disk_spaces, ensure_free_space};
+use crate::op_journal::{OpJournal, ResumableOp};
+use crate::path_guard::PathGuard;
+use crate::vfs::{Vfs, VfsPath, VfsProvider};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+const COPY_CHUNK_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOpKind {
+    Copy,
+    Move,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FileOpProgress {
+    op_id: String,
+    kind: FileOpKind,
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: u64,
+    files_total: u64,
+    current_file: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOpFailure {
+    pub path: String,
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl FileOpFailure {
+    pub fn new(path: impl Into<String>, error: AppError) -> Self {
+        FileOpFailure {
+            path: path.into(),
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
+/// Outcome of one verified copy (CopyOptions::verify).
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FileVerification {
+    source: String,
+    destination: String,
+    verified: bool,
+    /// 1 + retries used.
+    attempts: u32,
+    /// BLAKE3 of the verified copy.
+    hash: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FileOpCompleted {
+    op_id: String,
+    kind: FileOpKind,
+    status: String, // "ok" | "partial" | "cancelled" | "error"
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: u64,
+    files_total: u64,
+    errors: Vec<FileOpFailure>,
+    /// One entry per copied file when verifying; failed ones are also in
+    /// `errors`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    verification: Vec<FileVerification>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// What to do when a destination file already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    /// Overwrite only when the source was modified later.
+    OverwriteIfNewer,
+    /// Copy as "name (1).ext", "name (2).ext", ...
+    KeepBoth,
+    /// Pause the job and emit `fu:file_conflict`.
+    Ask,
+}
+
+/// Answer to a `fu:file_conflict` event.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictDecision {
+    /// Any policy except `ask`.
+    pub policy: ConflictPolicy,
+    /// Use `policy` for the rest of the job instead of asking again.
+    #[serde(default)]
+    pub apply_to_all: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FileConflict {
+    op_id: String,
+    source: String,
+    destination: String,
+    source_size: u64,
+    /// Seconds since UNIX_EPOCH; 0 when unknown.
+    source_modified: u64,
+    destination_size: u64,
+    destination_modified: u64,
+    destination_is_dir: bool,
+}
+
+/// Size / mtime of one side of a conflict.
+#[derive(Clone, Copy, Default)]
+struct ConflictSide {
+    size: u64,
+    modified: u64,
+    is_dir: bool,
+}
+
+impl ConflictSide {
+    fn local(meta: &fs::Metadata) -> Self {
+        ConflictSide {
+            size: meta.len(),
+            modified: meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            is_dir: meta.is_dir(),
+        }
+    }
+
+    fn vfs(entry: &crate::vfs::VfsEntry) -> Self {
+        ConflictSide {
+            size: entry.size,
+            modified: entry.modified,
+            is_dir: entry.is_dir,
+        }
+    }
+}
+
+/// What to carry over besides file contents, and whether to check the copy.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CopyOptions {
+    /// Modification and access times (also for remote -> local copies).
+    pub preserve_times: bool,
+    /// Creation time; Windows and macOS only.
+    pub preserve_creation_time: bool,
+    /// Unix mode bits (the read-only flag on Windows).
+    pub preserve_permissions: bool,
+    /// Hidden / system / archive / read-only attributes; Windows only.
+    pub preserve_attributes: bool,
+    /// Re-hash the destination and fail files that differ from the source.
+    pub verify: bool,
+    /// Extra attempts for a file whose copy failed or didn't verify
+    /// (flaky USB drives, network shares). Missing sources, permission
+    /// errors and conflicts are not retried.
+    pub retries: u32,
+}
+
+impl CopyOptions {
+    fn preserves_local_metadata(&self) -> bool {
+        self.preserve_times || self.preserve_creation_time || self.preserve_permissions || self.preserve_attributes
+    }
+}
+
+/// Jobs blocked on an "ask" conflict, by op_id.
+static PENDING_CONFLICTS: Mutex<Vec<(String, Sender<ConflictDecision>)>> = Mutex::new(Vec::new());
+
+/// Per-job settings that don't change what gets copied.
+#[derive(Default)]
+pub struct FileOpOptions {
+    /// None = report conflicts as failures.
+    pub conflict_policy: Option<ConflictPolicy>,
+    pub copy_options: CopyOptions,
+    /// Bytes per second, 0 = unlimited. Shared so it can change mid-job.
+    pub bandwidth_limit: Arc<AtomicU64>,
+    /// Runs on the worker thread after `fu:file_op_completed`.
+    pub on_finish: Option<Box<dyn FnOnce() + Send>>,
+    /// Set when resuming an interrupted job (op_journal.rs): source files
+    /// it already finished, skipped this time.
+    pub resume_done: Option<HashSet<PathBuf>>,
+}
+
+/// Command from TS:
+/// invoke("start_copy_operation", { opId, sources, destination, conflictPolicy: "ask",
+///   copyOptions: { preserveTimes: true, verify: true, retries: 2 }, bandwidthLimit: 0 })
+///
+/// `bandwidthLimit`: bytes per second for this job, 0 / omitted = only the
+/// global throttle.
+///
+/// Every source (file or folder) is copied INTO `destination`,
+/// which must be an existing directory. Any of them may be remote.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_copy_operation(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    vfs: State<'_, Vfs>,
+    op_id: String,
+    sources: Vec<String>,
+    destination: String,
+    conflict_policy: Option<ConflictPolicy>,
+    copy_options: Option<CopyOptions>,
+    bandwidth_limit: Option<u64>,
+) -> Result<(), AppError> {
+    let options = FileOpOptions {
+        conflict_policy,
+        copy_options: copy_options.unwrap_or_default(),
+        bandwidth_limit: Arc::new(AtomicU64::new(bandwidth_limit.unwrap_or(0))),
+        ..Default::default()
+    };
+    start_any_file_op(app, &registry, &guard, &vfs, op_id, FileOpKind::Copy, sources, destination, options)
+}
+
+/// Command from TS:
+/// invoke("start_move_operation", { opId, sources, destination, conflictPolicy: "ask",
+///   copyOptions: { preserveTimes: true, verify: true }, bandwidthLimit: 0 })
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn start_move_operation(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    vfs: State<'_, Vfs>,
+    op_id: String,
+    sources: Vec<String>,
+    destination: String,
+    conflict_policy: Option<ConflictPolicy>,
+    copy_options: Option<CopyOptions>,
+    bandwidth_limit: Option<u64>,
+) -> Result<(), AppError> {
+    let options = FileOpOptions {
+        conflict_policy,
+        copy_options: copy_options.unwrap_or_default(),
+        bandwidth_limit: Arc::new(AtomicU64::new(bandwidth_limit.unwrap_or(0))),
+        ..Default::default()
+    };
+    start_any_file_op(app, &registry, &guard, &vfs, op_id, FileOpKind::Move, sources, destination, options)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_any_file_op(
+    app: AppHandle,
+    registry: &OperationRegistry,
+    guard: &PathGuard,
+    vfs: &Vfs,
+    op_id: String,
+    kind: FileOpKind,
+    sources: Vec<String>,
+    destination: String,
+    options: FileOpOptions,
+) -> Result<(), AppError> {
+    let resolved = vfs.resolve_all(guard, &sources)?;
+    let dest = vfs.resolve(guard, &destination)?;
+    start_resolved_file_op(app, registry.clone(), op_id, kind, resolved, dest, options)
+}
+
+/// Start a job on already resolved paths (see Vfs::resolve). Local-only
+/// jobs keep the std::fs engine; anything remote goes through the
+/// providers.
+pub fn start_resolved_file_op(
+    app: AppHandle,
+    registry: OperationRegistry,
+    op_id: String,
+    kind: FileOpKind,
+    sources: Vec<VfsPath>,
+    destination: VfsPath,
+    options: FileOpOptions,
+) -> Result<(), AppError> {
+    if destination.is_local() && sources.iter().all(VfsPath::is_local) {
+        let sources = sources.into_iter().map(|p| p.path).collect();
+        start_file_op(app, registry, op_id, kind, sources, destination.path, options)
+    } else {
+        start_vfs_file_op(app, registry, op_id, kind, sources, destination, options)
+    }
+}
+
+pub fn start_file_op(
+    app: AppHandle,
+    registry: OperationRegistry,
+    op_id: String,
+    kind: FileOpKind,
+    sources: Vec<String>,
+    destination: String,
+    options: FileOpOptions,
+) -> Result<(), AppError> {
+    let dest_dir = PathBuf::from(&destination);
+    if !dest_dir.is_dir() {
+        return Err(AppError::new(
+            ErrorCode::NotADirectory,
+            format!("Destination is not a directory: {}", destination),
+        )
+        .with_path(&destination));
+    }
+    if sources.is_empty() {
+        return Err(AppError::invalid_input("No source paths given"));
+    }
+
+    let audit = AuditEntry::new(audit_action(kind))
+        .op_id(&op_id)
+        .sources(&sources)
+        .destination(destination.clone());
+    let journal_op = ResumableOp::FileOp {
+        kind,
+        sources: sources.clone(),
+        destination,
+        conflict_policy: options.conflict_policy,
+        copy_options: options.copy_options,
+        bandwidth_limit: options.bandwidth_limit.load(Ordering::Relaxed),
+    };
+    let resuming = options.resume_done.is_some();
+    let sources: Vec<PathBuf> = sources.into_iter().map(PathBuf::from).collect();
+    spawn_file_op(app, registry, op_id, kind, options, audit, move |job| {
+        job.journal = OpJournal::begin(job.app, job.op_id, &journal_op, resuming);
+        job.run(&sources, &dest_dir)
+    });
+    Ok(())
+}
+
+/// start_file_op() for sources / destination on any provider. The
+/// destination is checked inside the job (it may be a network call).
+pub fn start_vfs_file_op(
+    app: AppHandle,
+    registry: OperationRegistry,
+    op_id: String,
+    kind: FileOpKind,
+    sources: Vec<VfsPath>,
+    destination: VfsPath,
+    options: FileOpOptions,
+) -> Result<(), AppError> {
+    if sources.is_empty() {
+        return Err(AppError::invalid_input("No source paths given"));
+    }
+    let audit = AuditEntry::new(audit_action(kind))
+        .op_id(&op_id)
+        .sources(&sources.iter().map(|p| p.path.as_str()).collect::<Vec<_>>())
+        .destination(destination.path.clone());
+    spawn_file_op(app, registry, op_id, kind, options, audit, move |job| {
+        job.run_vfs(&sources, &destination)
+    });
+    Ok(())
+}
+
+fn audit_action(kind: FileOpKind) -> AuditAction {
+    match kind {
+        FileOpKind::Copy => AuditAction::Copy,
+        FileOpKind::Move => AuditAction::Move,
+    }
+}
+
+/// Register the op, run `run` on the blocking pool, then emit
+/// `fu:file_op_completed` from the job's totals and record `audit`.
+fn spawn_file_op(
+    app: AppHandle,
+    registry: OperationRegistry,
+    op_id: String,
+    kind: FileOpKind,
+    options: FileOpOptions,
+    audit: AuditEntry,
+    run: impl FnOnce(&mut FileOpJob<'_>) -> Result<(), Cancelled> + Send + 'static,
+) {
+    let op_kind = match kind {
+        FileOpKind::Copy => OperationKind::Copy,
+        FileOpKind::Move => OperationKind::Move,
+    };
+    let (token, pause) = registry.register_pausable(&op_id, op_kind);
+
+    task::spawn_blocking(move || {
+        let mut job = FileOpJob::new(&app, &op_id, kind, &token, &pause);
+        job.bandwidth_limit = options.bandwidth_limit;
+        job.conflict_policy = options.conflict_policy;
+        job.copy_options = options.copy_options;
+        job.io_throttle = app.try_state::<IoThrottle>().map(|t| t.inner().clone());
+        job.resume_done = options.resume_done.unwrap_or_default();
+        let status = match run(&mut job) {
+            Err(Cancelled) => "cancelled",
+            Ok(()) if job.errors.is_empty() => "ok",
+            Ok(()) if job.files_done == 0 => "error",
+            Ok(()) => "partial",
+        };
+        let error = match status {
+            "cancelled" => Some(AppError::cancelled()),
+            "error" => job.fatal.take().or_else(|| {
+                job.errors
+                    .first()
+                    .map(|e| AppError::new(e.code, e.message.clone()).with_path(&e.path))
+            }),
+            _ => None,
+        };
+        let error_message = match status {
+            "error" => error.as_ref().map(|e| e.message.clone()),
+            _ => None,
+        };
+        let journal = job.journal.take();
+        let audit_detail = match status {
+            "partial" => Some(format!("{} of {} files failed", job.errors.len(), job.files_total)),
+            _ => error_message.clone(),
+        };
+
+        let _ = app.emit(
+            "fu:file_op_completed",
+            FileOpCompleted {
+                op_id: op_id.clone(),
+                kind,
+                status: status.to_string(),
+                bytes_done: job.bytes_done,
+                bytes_total: job.bytes_total,
+                files_done: job.files_done,
+                files_total: job.files_total,
+                errors: job.errors,
+                verification: job.verification,
+                error_message,
+                error,
+            },
+        );
+
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+        audit::record(&app, audit.result(status, audit_detail));
+        registry.finish(&op_id, &token);
+        if let Some(on_finish) = options.on_finish {
+            on_finish();
+        }
+    });
+}
+
+/// Answer the `fu:file_conflict` a job is waiting on.
+///
+/// Frontend can call:
+///   invoke('resolve_conflict', { opId, decision: { policy: 'keepBoth', applyToAll: false } })
+#[tauri::command]
+pub fn resolve_conflict(op_id: String, decision: ConflictDecision) -> Result<(), AppError> {
+    if decision.policy == ConflictPolicy::Ask {
+        return Err(AppError::invalid_input("A conflict cannot be answered with \"ask\""));
+    }
+    let mut pending = PENDING_CONFLICTS.lock().unwrap_or_else(|e| e.into_inner());
+    let i = pending
+        .iter()
+        .position(|(id, _)| *id == op_id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No conflict pending for {}", op_id)))?;
+    let (_, tx) = pending.remove(i);
+    let _ = tx.send(decision);
+    Ok(())
+}
+
+/// `fu:file_op_completed` for a job that never started (failed to start,
+/// or cancelled while still queued), so listeners see every op_id end.
+pub fn emit_not_started(app: &AppHandle, op_id: &str, kind: FileOpKind, error: AppError) {
+    let status = if error.code == ErrorCode::Cancelled {
+        "cancelled"
+    } else {
+        "error"
+    };
+    let _ = app.emit(
+        "fu:file_op_completed",
+        FileOpCompleted {
+            op_id: op_id.to_string(),
+            kind,
+            status: status.to_string(),
+            bytes_done: 0,
+            bytes_total: 0,
+            files_done: 0,
+            files_total: 0,
+            errors: Vec::new(),
+            verification: Vec::new(),
+            error_message: Some(error.message.clone()).filter(|_| status == "error"),
+            error: Some(error),
+        },
+    );
+}
+
+/// The job stopped because its cancel token fired.
+struct Cancelled;
+
+enum CopyFileError {
+    Cancelled,
+    Io(std::io::Error),
+    Vfs(AppError),
+}
+
+/// One file to transfer, produced by the planning phase.
+struct PlannedFile {
+    src: PathBuf,
+    dest: PathBuf,
+    size: u64,
+    is_symlink: bool,
+}
+
+/// One top-level source and everything under it.
+struct PlannedRoot {
+    src: PathBuf,
+    dest: PathBuf,
+    dirs: Vec<PathBuf>,
+    files: Vec<PlannedFile>,
+}
+
+/// One file to transfer between providers (provider-side paths).
+struct VfsPlannedFile {
+    src: String,
+    dest: String,
+    size: u64,
+    /// Seconds since UNIX_EPOCH; 0 when unknown.
+    modified: u64,
+}
+
+/// PlannedRoot for run_vfs().
+struct VfsPlannedRoot {
+    src: VfsPath,
+    dest: VfsPath,
+    /// Source directories, parents before children.
+    src_dirs: Vec<String>,
+    /// Directories to create, parents before children.
+    dest_dirs: Vec<String>,
+    files: Vec<VfsPlannedFile>,
+}
+
+struct FileOpJob<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    kind: FileOpKind,
+    token: &'a CancellationToken,
+    pause: &'a PauseGate,
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: u64,
+    files_total: u64,
+    errors: Vec<FileOpFailure>,
+    verification: Vec<FileVerification>,
+    /// Hash of the last copy verify_copy() accepted.
+    verified_hash: Option<blake3::Hash>,
+    /// Failure that stopped the whole job before it started (full error,
+    /// e.g. with the INSUFFICIENT_SPACE payload).
+    fatal: Option<AppError>,
+    last_emit: Instant,
+    conflict_policy: Option<ConflictPolicy>,
+    copy_options: CopyOptions,
+    bandwidth_limit: Arc<AtomicU64>,
+    io_throttle: Option<IoThrottle>,
+    /// Local jobs only; None when it couldn't be written.
+    journal: Option<OpJournal>,
+    /// Sources finished before an interruption.
+    resume_done: HashSet<PathBuf>,
+    /// Start / bytes of the current bandwidth window.
+    throttle_start: Instant,
+    throttle_bytes: u64,
+}
+
+impl<'a> FileOpJob<'a> {
+    fn new(
+        app: &'a AppHandle,
+        op_id: &'a str,
+        kind: FileOpKind,
+        token: &'a CancellationToken,
+        pause: &'a PauseGate,
+    ) -> Self {
+        Self {
+            app,
+            op_id,
+            kind,
+            token,
+            pause,
+            bytes_done: 0,
+            bytes_total: 0,
+            files_done: 0,
+            files_total: 0,
+            errors: Vec::new(),
+            verification: Vec::new(),
+            verified_hash: None,
+            fatal: None,
+            last_emit: Instant::now(),
+            conflict_policy: None,
+            copy_options: CopyOptions::default(),
+            bandwidth_limit: Arc::default(),
+            io_throttle: None,
+            journal: None,
+            resume_done: HashSet::new(),
+            throttle_start: Instant::now(),
+            throttle_bytes: 0,
+        }
+    }
+
+    /// Wait out a pause, then report whether the job was cancelled.
+    fn should_stop(&self) -> bool {
+        self.pause.wait_while_paused(self.token);
+        self.token.is_cancelled()
+    }
+
+    /// Bytes a root adds at the destination: files that already exist there
+    /// are skipped or replaced, so they only count with keep-both.
+    fn bytes_needed(&self, dest_root: &Path, files: impl Iterator<Item = (PathBuf, u64)>) -> u64 {
+        let merging = self.conflict_policy != Some(ConflictPolicy::KeepBoth) && fs::symlink_metadata(dest_root).is_ok();
+        files
+            .filter(|(dest, _)| !merging || fs::symlink_metadata(dest).is_err())
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    /// False (with the job failed) when `required` bytes don't fit on the
+    /// disk of `dest_dir`.
+    fn check_free_space(&mut self, dest_dir: &Path, required: u64) -> bool {
+        match ensure_free_space(dest_dir, required) {
+            Ok(()) => true,
+            Err(e) => {
+                self.fail(dest_dir, e.clone());
+                self.fatal = Some(e);
+                false
+            }
+        }
+    }
+
+    fn fail(&mut self, path: &Path, error: AppError) {
+        self.errors
+            .push(FileOpFailure::new(path.to_string_lossy().to_string(), error));
+    }
+
+    /// Count a skipped file as done so progress still reaches 100%.
+    fn skip(&mut self, size: u64) {
+        self.files_done += 1;
+        self.bytes_done += size;
+    }
+
+    /// Journal `src` as finished, so a resumed job doesn't copy it again.
+    fn journal_done(&mut self, src: &Path) {
+        if let Some(journal) = &mut self.journal {
+            journal.done(src);
+        }
+    }
+
+    /// Turn the job's policy into Skip / Overwrite / KeepBoth for one
+    /// conflict, asking the frontend if needed. None = no policy.
+    fn decide_conflict(
+        &mut self,
+        src: &str,
+        dest: &str,
+        src_side: ConflictSide,
+        dest_side: ConflictSide,
+    ) -> Result<Option<ConflictPolicy>, Cancelled> {
+        let policy = match self.conflict_policy {
+            None => return Ok(None),
+            Some(ConflictPolicy::Ask) => {
+                let decision = self.ask_conflict(src, dest, src_side, dest_side)?;
+                if decision.apply_to_all {
+                    self.conflict_policy = Some(decision.policy);
+                }
+                decision.policy
+            }
+            Some(policy) => policy,
+        };
+        Ok(Some(match policy {
+            ConflictPolicy::OverwriteIfNewer if src_side.modified > dest_side.modified => ConflictPolicy::Overwrite,
+            ConflictPolicy::OverwriteIfNewer => ConflictPolicy::Skip,
+            policy => policy,
+        }))
+    }
+
+    /// Emit `fu:file_conflict` and wait for resolve_conflict (or cancel).
+    fn ask_conflict(
+        &mut self,
+        src: &str,
+        dest: &str,
+        src_side: ConflictSide,
+        dest_side: ConflictSide,
+    ) -> Result<ConflictDecision, Cancelled> {
+        let (tx, rx) = mpsc::channel();
+        PENDING_CONFLICTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((self.op_id.to_string(), tx));
+        let _ = self.app.emit(
+            "fu:file_conflict",
+            FileConflict {
+                op_id: self.op_id.to_string(),
+                source: src.to_string(),
+                destination: dest.to_string(),
+                source_size: src_side.size,
+                source_modified: src_side.modified,
+                destination_size: dest_side.size,
+                destination_modified: dest_side.modified,
+                destination_is_dir: dest_side.is_dir,
+            },
+        );
+
+        let answer = loop {
+            match rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(decision) => break Ok(decision),
+                Err(RecvTimeoutError::Timeout) if !self.token.is_cancelled() => continue,
+                Err(_) => break Err(Cancelled),
+            }
+        };
+        PENDING_CONFLICTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(id, _)| id != self.op_id);
+        // The user took their time: don't count it against the bandwidth cap.
+        self.throttle_start = Instant::now();
+        self.throttle_bytes = 0;
+        answer
+    }
+
+    fn emit_progress(&mut self, current: &Path, force: bool) {
+        if !force && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:file_op_progress",
+            FileOpProgress {
+                op_id: self.op_id.to_string(),
+                kind: self.kind,
+                bytes_done: self.bytes_done,
+                bytes_total: self.bytes_total,
+                files_done: self.files_done,
+                files_total: self.files_total,
+                current_file: current.to_string_lossy().to_string(),
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+
+    fn run(&mut self, sources: &[PathBuf], dest_dir: &Path) -> Result<(), Cancelled> {
+        // ==== 1) Plan ====
+        let mut roots = Vec::new();
+        for src in sources {
+            if self.should_stop() {
+                return Err(Cancelled);
+            }
+            match self.plan_root(src, dest_dir) {
+                Ok(root) => roots.push(root),
+                Err(e) => self.fail(src, e),
+            }
+        }
+        self.files_total = roots.iter().map(|r| r.files.len() as u64).sum();
+        self.bytes_total = roots
+            .iter()
+            .flat_map(|r| r.files.iter())
+            .map(|f| f.size)
+            .sum();
+        self.emit_progress(dest_dir, true);
+
+        // Moves within one volume are renames and need no space.
+        let mut paths = vec![dest_dir];
+        paths.extend(roots.iter().map(|r| r.src.as_path()));
+        let disks = disk_spaces(&paths);
+        let mount = |i: usize| disks[i].as_ref().map(|d| d.mount_point.as_str());
+        let required = roots
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.kind == FileOpKind::Copy || mount(0).is_none() || mount(i + 1) != mount(0))
+            .map(|(_, r)| self.bytes_needed(&r.dest, r.files.iter().map(|f| (f.dest.clone(), f.size))))
+            .sum();
+        if !self.check_free_space(dest_dir, required) {
+            return Ok(());
+        }
+
+        // ==== 2) Execute ====
+        for root in &roots {
+            // Same volume: the whole subtree moves in one syscall. Only when the
+            // destination is free, since rename() may silently replace it.
+            if self.kind == FileOpKind::Move
+                && fs::symlink_metadata(&root.dest).is_err()
+                && fs::rename(&root.src, &root.dest).is_ok()
+            {
+                self.files_done += root.files.len() as u64;
+                self.bytes_done += root.files.iter().map(|f| f.size).sum::<u64>();
+                self.emit_progress(&root.dest, false);
+                continue;
+            }
+            self.transfer_root(root)?;
+        }
+
+        self.emit_progress(dest_dir, true);
+        Ok(())
+    }
+
+    /// Collect directories and files for one source path.
+    fn plan_root(&self, src: &Path, dest_dir: &Path) -> Result<PlannedRoot, AppError> {
+        let meta = fs::symlink_metadata(src)
+            .map_err(|e| AppError::io(&e).context("Cannot read source"))?;
+        let name = src
+            .file_name()
+            .ok_or_else(|| AppError::invalid_input("Source has no file name"))?;
+        let dest = dest_dir.join(name);
+
+        if meta.is_dir() && dest_dir.starts_with(src) {
+            return Err(AppError::invalid_input("Cannot copy a folder into itself"));
+        }
+        if src == dest {
+            return Err(AppError::invalid_input("Source and destination are the same"));
+        }
+
+        let mut root = PlannedRoot {
+            src: src.to_path_buf(),
+            dest: dest.clone(),
+            dirs: Vec::new(),
+            files: Vec::new(),
+        };
+
+        if !meta.is_dir() {
+            root.files.push(PlannedFile {
+                src: src.to_path_buf(),
+                dest,
+                size: meta.len(),
+                is_symlink: meta.file_type().is_symlink(),
+            });
+            return Ok(root);
+        }
+
+        for entry in WalkDir::new(src).into_iter() {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    tracing::warn!("[FileOps] WalkDir error: {err}");
+                    continue;
+                }
+            };
+            let rel = match entry.path().strip_prefix(src) {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let target = dest.join(rel);
+            let file_type = entry.file_type();
+
+            if file_type.is_dir() {
+                root.dirs.push(target);
+            } else {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                root.files.push(PlannedFile {
+                    src: entry.into_path(),
+                    dest: target,
+                    size,
+                    is_symlink: file_type.is_symlink(),
+                });
+            }
+        }
+        Ok(root)
+    }
+
+    fn transfer_root(&mut self, root: &PlannedRoot) -> Result<(), Cancelled> {
+        // WalkDir yields parents before children, so create in order.
+        for dir in &root.dirs {
+            if let Err(e) = fs::create_dir_all(dir) {
+                self.fail(dir, AppError::io(&e).context("Failed to create directory"));
+            }
+        }
+
+        for file in &root.files {
+            if self.should_stop() {
+                return Err(Cancelled);
+            }
+            if self.resume_done.contains(&file.src) {
+                self.skip(file.size);
+                continue;
+            }
+            self.emit_progress(&file.src, false);
+
+            let mut dest = file.dest.clone();
+            let mut replace = false;
+            if let Ok(existing) = fs::symlink_metadata(&dest) {
+                let src_side = fs::symlink_metadata(&file.src)
+                    .map(|m| ConflictSide::local(&m))
+                    .unwrap_or_default();
+                let decision = self.decide_conflict(
+                    &file.src.to_string_lossy(),
+                    &dest.to_string_lossy(),
+                    src_side,
+                    ConflictSide::local(&existing),
+                )?;
+                match decision {
+                    Some(ConflictPolicy::Skip) => {
+                        self.skip(file.size);
+                        self.journal_done(&file.src);
+                        continue;
+                    }
+                    Some(ConflictPolicy::KeepBoth) => dest = unique_path(&dest),
+                    Some(ConflictPolicy::Overwrite) if existing.is_dir() => {
+                        self.fail(
+                            &file.src,
+                            AppError::new(ErrorCode::AlreadyExists, "Cannot replace a folder with a file")
+                                .with_path(&dest),
+                        );
+                        continue;
+                    }
+                    Some(ConflictPolicy::Overwrite) => replace = true,
+                    // No policy: create_new below reports AlreadyExists.
+                    _ => {}
+                }
+            }
+
+            let res = match (file.is_symlink, replace) {
+                (true, true) => fs::remove_file(&dest)
+                    .and_then(|_| copy_symlink(&file.src, &dest))
+                    .map_err(CopyFileError::Io),
+                (true, false) => copy_symlink(&file.src, &dest).map_err(CopyFileError::Io),
+                (false, true) => {
+                    self.copy_with_retries(&file.src, &dest.to_string_lossy(), |job| job.replace_file(&file.src, &dest))
+                }
+                (false, false) => self.copy_with_retries(&file.src, &dest.to_string_lossy(), |job| {
+                    job.copy_file_chunked(&file.src, &dest)
+                }),
+            };
+
+            match res {
+                Ok(()) => {
+                    self.files_done += 1;
+                    if !file.is_symlink {
+                        self.preserve_local(&file.src, &dest);
+                    }
+                    if self.kind == FileOpKind::Move {
+                        if let Err(e) = fs::remove_file(&file.src) {
+                            self.fail(
+                                &file.src,
+                                AppError::io(&e).context("Copied but failed to remove source"),
+                            );
+                        }
+                    }
+                    self.journal_done(&file.src);
+                }
+                Err(CopyFileError::Cancelled) => return Err(Cancelled),
+                Err(CopyFileError::Io(e)) => self.fail(&file.src, AppError::io(&e)),
+                Err(CopyFileError::Vfs(e)) => self.fail(&file.src, e),
+            }
+        }
+
+        // Directories last (adding files changes their times), deepest first.
+        if self.copy_options.preserves_local_metadata() {
+            for dir in root.dirs.iter().rev() {
+                if let Ok(rel) = dir.strip_prefix(&root.dest) {
+                    self.preserve_local(&root.src.join(rel), dir);
+                }
+            }
+        }
+
+        if self.kind == FileOpKind::Move && root.src.is_dir() {
+            // Deepest first; non-empty dirs (failed files) are left in place.
+            let mut src_dirs: Vec<PathBuf> = WalkDir::new(&root.src)
+                .into_iter()
+                .flatten()
+                .filter(|e| e.file_type().is_dir())
+                .map(|e| e.into_path())
+                .collect();
+            src_dirs.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+            for dir in src_dirs {
+                let _ = fs::remove_dir(&dir);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copy one regular file in chunks. Never overwrites an existing file.
+    /// On failure or cancel the partial destination file is removed.
+    fn copy_file_chunked(&mut self, src: &Path, dest: &Path) -> Result<(), CopyFileError> {
+        let mut reader = File::open(src).map_err(CopyFileError::Io)?;
+        let mut writer = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dest)
+            .map_err(CopyFileError::Io)?;
+        if let Some(journal) = &mut self.journal {
+            journal.writing(dest);
+        }
+
+        let mut hasher = self.copy_options.verify.then(blake3::Hasher::new);
+        let mut res = self.pump(&mut reader, &mut writer, src, hasher.as_mut());
+        drop(writer);
+        if let (Ok(()), Some(hasher)) = (&res, hasher) {
+            res = File::open(dest)
+                .map_err(CopyFileError::Io)
+                .and_then(|mut copy| self.verify_copy(&mut copy, hasher.finalize()));
+        }
+        if res.is_err() {
+            let _ = fs::remove_file(dest);
+        }
+        res
+    }
+
+    /// Re-read a finished copy and compare it with the source's hash.
+    fn verify_copy(&mut self, copy: &mut dyn Read, expected: blake3::Hash) -> Result<(), CopyFileError> {
+        let mut hasher = blake3::Hasher::new();
+        let mut buf = vec![0u8; COPY_CHUNK_BYTES];
+        loop {
+            if self.should_stop() {
+                return Err(CopyFileError::Cancelled);
+            }
+            let n = copy.read(&mut buf).map_err(CopyFileError::Io)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        if hasher.finalize() == expected {
+            self.verified_hash = Some(expected);
+            Ok(())
+        } else {
+            Err(CopyFileError::Vfs(AppError::new(
+                ErrorCode::Io,
+                "Verification failed: the copy differs from the source",
+            )))
+        }
+    }
+
+    /// Run `copy` for one file, again after retryable failures (up to
+    /// CopyOptions::retries times), and record the outcome when verifying.
+    /// Progress of a failed attempt is taken back.
+    fn copy_with_retries(
+        &mut self,
+        src: &Path,
+        dest: &str,
+        mut copy: impl FnMut(&mut Self) -> Result<(), CopyFileError>,
+    ) -> Result<(), CopyFileError> {
+        let bytes_before = self.bytes_done;
+        let mut attempts = 0;
+        let res = loop {
+            attempts += 1;
+            self.verified_hash = None;
+            let res = copy(self);
+            match &res {
+                Err(e) if attempts <= self.copy_options.retries && is_retryable(e) => {
+                    tracing::warn!("[FileOps] Copy of {:?} failed (attempt {attempts}), retrying", src);
+                    self.bytes_done = bytes_before;
+                    if !self.wait_before_retry(attempts) {
+                        break Err(CopyFileError::Cancelled);
+                    }
+                }
+                _ => break res,
+            }
+        };
+
+        if self.copy_options.verify && !matches!(res, Err(CopyFileError::Cancelled)) {
+            self.verification.push(FileVerification {
+                source: src.to_string_lossy().to_string(),
+                destination: dest.to_string(),
+                verified: res.is_ok(),
+                attempts,
+                hash: self.verified_hash.take().filter(|_| res.is_ok()).map(|h| h.to_hex().to_string()),
+            });
+        }
+        res
+    }
+
+    /// 1 s, 2 s, 3 s ... (capped at 5 s) before the next attempt, so a
+    /// drive that dropped off has time to come back. False when cancelled.
+    fn wait_before_retry(&mut self, attempt: u32) -> bool {
+        let until = Instant::now() + Duration::from_secs(attempt.min(5) as u64);
+        while Instant::now() < until {
+            if self.should_stop() {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        self.throttle_start = Instant::now();
+        self.throttle_bytes = 0;
+        true
+    }
+
+    /// Apply the CopyOptions metadata of `src` to `dest` (file or folder).
+    fn preserve_local(&mut self, src: &Path, dest: &Path) {
+        if !self.copy_options.preserves_local_metadata() {
+            return;
+        }
+        let res = fs::metadata(src).and_then(|meta| preserve_metadata(&meta, dest, &self.copy_options));
+        if let Err(e) = res {
+            self.fail(dest, AppError::io(&e).context("Copied but failed to preserve metadata"));
+        }
+    }
+
+    /// Overwrite `dest`: copy next to it, then rename over it, so the old
+    /// file survives a failed or cancelled copy.
+    fn replace_file(&mut self, src: &Path, dest: &Path) -> Result<(), CopyFileError> {
+        let name = dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let tmp = unique_path(&dest.with_file_name(format!(".{}.fu-partial", name)));
+        self.copy_file_chunked(src, &tmp)?;
+        fs::rename(&tmp, dest).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            CopyFileError::Io(e)
+        })
+    }
+
+    /// Stream `reader` into `writer`, feeding `hasher` when verifying.
+    fn pump(
+        &mut self,
+        reader: &mut dyn Read,
+        writer: &mut dyn Write,
+        src: &Path,
+        mut hasher: Option<&mut blake3::Hasher>,
+    ) -> Result<(), CopyFileError> {
+        let mut buf = vec![0u8; COPY_CHUNK_BYTES];
+        loop {
+            if self.should_stop() {
+                return Err(CopyFileError::Cancelled);
+            }
+            // Smaller reads under a low cap keep progress smooth.
+            let limit = self.bandwidth_limit.load(Ordering::Relaxed) as usize;
+            let chunk = match limit {
+                0 => COPY_CHUNK_BYTES,
+                limit => (limit / 4).clamp(16 * 1024, COPY_CHUNK_BYTES),
+            };
+            let n = reader.read(&mut buf[..chunk]).map_err(CopyFileError::Io)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n]).map_err(CopyFileError::Io)?;
+            if let Some(hasher) = hasher.as_deref_mut() {
+                hasher.update(&buf[..n]);
+            }
+            self.bytes_done += n as u64;
+            self.emit_progress(src, false);
+            self.throttle(n as u64);
+        }
+        writer.flush().map_err(CopyFileError::Io)
+    }
+
+    /// Sleep as long as needed to stay under the job's bandwidth limit and
+    /// the global IO throttle.
+    fn throttle(&mut self, n: u64) {
+        if let Some(io_throttle) = &self.io_throttle {
+            io_throttle.wait(n, self.token);
+        }
+        let limit = self.bandwidth_limit.load(Ordering::Relaxed);
+        if limit == 0 {
+            return;
+        }
+        // Start a new window after pauses / slow stretches so they are
+        // never paid back with a burst.
+        if self.throttle_start.elapsed() > Duration::from_secs(2) {
+            self.throttle_start = Instant::now();
+            self.throttle_bytes = 0;
+        }
+        self.throttle_bytes += n;
+        let due = Duration::from_secs_f64(self.throttle_bytes as f64 / limit as f64);
+        while !self.token.is_cancelled() {
+            let Some(wait) = due.checked_sub(self.throttle_start.elapsed()) else {
+                break;
+            };
+            std::thread::sleep(wait.min(Duration::from_millis(100)));
+        }
+    }
+
+    /// run() for sources / destination on any provider.
+    fn run_vfs(&mut self, sources: &[VfsPath], dest_dir: &VfsPath) -> Result<(), Cancelled> {
+        let dest_label = PathBuf::from(&dest_dir.path);
+        match dest_dir.provider.stat(&dest_dir.path) {
+            Ok(entry) if entry.is_dir => {}
+            Ok(_) => {
+                let e = AppError::new(
+                    ErrorCode::NotADirectory,
+                    format!("Destination is not a directory: {}", dest_dir.path),
+                );
+                self.fail(&dest_label, e);
+                return Ok(());
+            }
+            Err(e) => {
+                self.fail(&dest_label, e.context("Cannot read destination"));
+                return Ok(());
+            }
+        }
+
+        // ==== 1) Plan ====
+        let mut roots = Vec::new();
+        for src in sources {
+            if self.should_stop() {
+                return Err(Cancelled);
+            }
+            match self.plan_vfs_root(src, dest_dir) {
+                Ok(root) => roots.push(root),
+                Err(e) => self.fail(Path::new(&src.path), e),
+            }
+        }
+        self.files_total = roots.iter().map(|r| r.files.len() as u64).sum();
+        self.bytes_total = roots
+            .iter()
+            .flat_map(|r| r.files.iter())
+            .map(|f| f.size)
+            .sum();
+        self.emit_progress(&dest_label, true);
+
+        // Free space is only known for the local disk.
+        if let Some(local_dest) = dest_dir.provider.local_path(&dest_dir.path) {
+            let provider = &dest_dir.provider;
+            let required = roots
+                .iter()
+                .filter_map(|r| {
+                    let files = r.files.iter().filter_map(|f| Some((provider.local_path(&f.dest)?, f.size)));
+                    Some(self.bytes_needed(&provider.local_path(&r.dest.path)?, files))
+                })
+                .sum();
+            if !self.check_free_space(&local_dest, required) {
+                return Ok(());
+            }
+        }
+
+        // ==== 2) Execute ====
+        for root in &roots {
+            self.transfer_vfs_root(root)?;
+        }
+
+        self.emit_progress(&dest_label, true);
+        Ok(())
+    }
+
+    /// plan_root() through the providers: walk by listing.
+    fn plan_vfs_root(&mut self, src: &VfsPath, dest_dir: &VfsPath) -> Result<VfsPlannedRoot, AppError> {
+        let meta = src.provider.stat(&src.path).map_err(|e| e.context("Cannot read source"))?;
+        if meta.is_symlink {
+            return Err(AppError::invalid_input(
+                "Symbolic links are not copied to or from remote locations",
+            ));
+        }
+        let dest = dest_dir.join(&meta.name);
+
+        if Arc::ptr_eq(&src.provider, &dest_dir.provider) {
+            if meta.is_dir && Path::new(&dest_dir.path).starts_with(&src.path) {
+                return Err(AppError::invalid_input("Cannot copy a folder into itself"));
+            }
+            if src.path == dest.path {
+                return Err(AppError::invalid_input("Source and destination are the same"));
+            }
+        }
+
+        let mut root = VfsPlannedRoot {
+            src: src.clone(),
+            dest: dest.clone(),
+            src_dirs: Vec::new(),
+            dest_dirs: Vec::new(),
+            files: Vec::new(),
+        };
+        if !meta.is_dir {
+            root.files.push(VfsPlannedFile {
+                src: src.path.clone(),
+                dest: dest.path,
+                size: meta.size,
+                modified: meta.modified,
+            });
+            return Ok(root);
+        }
+
+        // Depth-first, so src_dirs / dest_dirs list parents first.
+        let mut stack = vec![(src.path.clone(), dest.path)];
+        while let Some((src_dir, dest_dir)) = stack.pop() {
+            if self.should_stop() {
+                break;
+            }
+            let entries = match src.provider.list(&src_dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    self.fail(Path::new(&src_dir), e.context("Failed to read directory"));
+                    continue;
+                }
+            };
+            root.src_dirs.push(src_dir);
+            root.dest_dirs.push(dest_dir.clone());
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        tracing::warn!("[FileOps] Listing error: {}", e.message);
+                        continue;
+                    }
+                };
+                let target = dest.provider.join(&dest_dir, &entry.name);
+                if entry.is_symlink {
+                    self.fail(
+                        Path::new(&entry.path),
+                        AppError::invalid_input("Symbolic links are not copied to or from remote locations"),
+                    );
+                } else if entry.is_dir {
+                    stack.push((entry.path, target));
+                } else {
+                    root.files.push(VfsPlannedFile {
+                        src: entry.path,
+                        dest: target,
+                        size: entry.size,
+                        modified: entry.modified,
+                    });
+                }
+            }
+        }
+        Ok(root)
+    }
+
+    fn transfer_vfs_root(&mut self, root: &VfsPlannedRoot) -> Result<(), Cancelled> {
+        let (src, dest) = (root.src.provider.as_ref(), root.dest.provider.as_ref());
+        for dir in &root.dest_dirs {
+            if let Err(e) = dest.create_dir(dir) {
+                self.fail(Path::new(dir), e);
+            }
+        }
+
+        for file in &root.files {
+            if self.should_stop() {
+                return Err(Cancelled);
+            }
+            let src_label = PathBuf::from(&file.src);
+            self.emit_progress(&src_label, false);
+
+            let mut dest_path = file.dest.clone();
+            if let Ok(existing) = dest.stat(&dest_path) {
+                let src_side = src.stat(&file.src).map(|e| ConflictSide::vfs(&e)).unwrap_or_default();
+                match self.decide_conflict(&file.src, &dest_path, src_side, ConflictSide::vfs(&existing))? {
+                    Some(ConflictPolicy::Skip) => {
+                        self.skip(file.size);
+                        continue;
+                    }
+                    Some(ConflictPolicy::KeepBoth) => dest_path = unique_vfs_path(dest, &dest_path),
+                    Some(ConflictPolicy::Overwrite) if existing.is_dir => {
+                        self.fail(
+                            &src_label,
+                            AppError::new(ErrorCode::AlreadyExists, "Cannot replace a folder with a file")
+                                .with_path(&dest_path),
+                        );
+                        continue;
+                    }
+                    Some(ConflictPolicy::Overwrite) => {
+                        if let Err(e) = dest.delete(&dest_path) {
+                            self.fail(&src_label, e.context("Cannot replace existing file"));
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let res = self.copy_with_retries(&src_label, &dest_path, |job| {
+                job.copy_vfs_file(src, dest, &file.src, &dest_path, file.size)
+            });
+            match res {
+                Ok(()) => {
+                    self.files_done += 1;
+                    // Remote -> local: the listing's mtime is all we have.
+                    let keep_mtime = self.copy_options.preserve_times && file.modified > 0;
+                    if let Some(local_dest) = dest.local_path(&dest_path).filter(|_| keep_mtime) {
+                        let modified = UNIX_EPOCH + Duration::from_secs(file.modified);
+                        let res = open_for_times(&local_dest)
+                            .and_then(|f| f.set_times(fs::FileTimes::new().set_modified(modified)));
+                        if let Err(e) = res {
+                            self.fail(&src_label, AppError::io(&e).context("Copied but failed to preserve metadata"));
+                        }
+                    }
+                    if self.kind == FileOpKind::Move {
+                        if let Err(e) = src.delete(&file.src) {
+                            self.fail(&src_label, e.context("Copied but failed to remove source"));
+                        }
+                    }
+                }
+                Err(CopyFileError::Cancelled) => return Err(Cancelled),
+                Err(CopyFileError::Io(e)) => self.fail(&src_label, AppError::io(&e)),
+                Err(CopyFileError::Vfs(e)) => self.fail(&src_label, e),
+            }
+        }
+
+        if self.kind == FileOpKind::Move {
+            // Deepest first; non-empty dirs (failed files) are left in place.
+            for dir in root.src_dirs.iter().rev() {
+                let _ = src.delete(dir);
+            }
+        }
+        Ok(())
+    }
+
+    /// copy_file_chunked() between providers. Never overwrites (the
+    /// provider's write() creates a new file); partial files are removed.
+    fn copy_vfs_file(
+        &mut self,
+        src: &dyn VfsProvider,
+        dest: &dyn VfsProvider,
+        src_path: &str,
+        dest_path: &str,
+        size: u64,
+    ) -> Result<(), CopyFileError> {
+        let mut reader = src.read(src_path).map_err(CopyFileError::Vfs)?;
+        let mut writer = dest.write_sized(dest_path, size).map_err(CopyFileError::Vfs)?;
+        let mut hasher = self.copy_options.verify.then(blake3::Hasher::new);
+        let mut res = self.pump(&mut reader, &mut writer, Path::new(src_path), hasher.as_mut());
+        drop(writer);
+        if let (Ok(()), Some(hasher)) = (&res, hasher) {
+            res = dest
+                .read(dest_path)
+                .map_err(CopyFileError::Vfs)
+                .and_then(|mut copy| self.verify_copy(&mut copy, hasher.finalize()));
+        }
+        if res.is_err() {
+            let _ = dest.delete(dest_path);
+        }
+        res
+    }
+}
+
+/// Failures worth another attempt: the transfer itself broke or the copy
+/// didn't verify. Missing files, access and conflicts won't change.
+fn is_retryable(error: &CopyFileError) -> bool {
+    let code = match error {
+        CopyFileError::Cancelled => return false,
+        CopyFileError::Io(e) => AppError::io(e).code,
+        CopyFileError::Vfs(e) => e.code,
+    };
+    !matches!(
+        code,
+        ErrorCode::NotFound
+            | ErrorCode::PermissionDenied
+            | ErrorCode::AccessDenied
+            | ErrorCode::AlreadyExists
+            | ErrorCode::NotADirectory
+            | ErrorCode::InvalidInput
+            | ErrorCode::DiskFull
+            | ErrorCode::InsufficientSpace
+    )
+}
+
+/// Handle that may change the times of a file or folder.
+fn open_for_times(path: &Path) -> std::io::Result<File> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_BACKUP_SEMANTICS, FILE_WRITE_ATTRIBUTES};
+        OpenOptions::new()
+            .access_mode(FILE_WRITE_ATTRIBUTES)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+            .open(path)
+    }
+    #[cfg(not(windows))]
+    {
+        // futimens() works on a read-only handle (and on directories).
+        File::open(path)
+    }
+}
+
+/// Copy times / permissions / attributes of `src` onto `dest` as selected.
+fn preserve_metadata(src: &fs::Metadata, dest: &Path, opts: &CopyOptions) -> std::io::Result<()> {
+    if opts.preserve_times || opts.preserve_creation_time {
+        let mut times = fs::FileTimes::new();
+        if opts.preserve_times {
+            if let Ok(t) = src.modified() {
+                times = times.set_modified(t);
+            }
+            if let Ok(t) = src.accessed() {
+                times = times.set_accessed(t);
+            }
+        }
+        #[cfg(windows)]
+        if opts.preserve_creation_time {
+            use std::os::windows::fs::FileTimesExt;
+            if let Ok(t) = src.created() {
+                times = times.set_created(t);
+            }
+        }
+        #[cfg(target_os = "macos")]
+        if opts.preserve_creation_time {
+            use std::os::macos::fs::FileTimesExt;
+            if let Ok(t) = src.created() {
+                times = times.set_created(t);
+            }
+        }
+        open_for_times(dest)?.set_times(times)?;
+    }
+
+    #[cfg(windows)]
+    if opts.preserve_attributes {
+        use std::os::windows::ffi::OsStrExt;
+        use std::os::windows::fs::MetadataExt;
+        use windows_sys::Win32::Storage::FileSystem::{
+            SetFileAttributesW, FILE_ATTRIBUTE_ARCHIVE, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_NOT_CONTENT_INDEXED,
+            FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_SYSTEM,
+        };
+        let keep = FILE_ATTRIBUTE_READONLY
+            | FILE_ATTRIBUTE_HIDDEN
+            | FILE_ATTRIBUTE_SYSTEM
+            | FILE_ATTRIBUTE_ARCHIVE
+            | FILE_ATTRIBUTE_NOT_CONTENT_INDEXED;
+        let wide: Vec<u16> = dest.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        // 0 would mean "no attributes"; SetFileAttributesW wants NORMAL then.
+        let attrs = match src.file_attributes() & keep {
+            0 => windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_NORMAL,
+            attrs => attrs,
+        };
+        if unsafe { SetFileAttributesW(wide.as_ptr(), attrs) } == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    // Last: a read-only result would block the steps above.
+    if opts.preserve_permissions {
+        fs::set_permissions(dest, src.permissions())?;
+    }
+    Ok(())
+}
+
+/// unique_path() on a provider.
+fn unique_vfs_path(provider: &dyn VfsProvider, path: &str) -> String {
+    let (dir, name) = path.rsplit_once(['/', '\\']).unwrap_or(("", path));
+    let (stem, ext) = match name.rfind('.') {
+        Some(i) if i > 0 => (&name[..i], &name[i..]),
+        _ => (name, ""),
+    };
+    (1u32..)
+        .map(|n| provider.join(dir, &format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| provider.stat(candidate).is_err())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// First free variant of `path`: "name (1).ext", "name (2).ext", ...
+/// Returns `path` unchanged if nothing exists there yet.
+pub fn unique_path(path: &Path) -> PathBuf {
+    if fs::symlink_metadata(path).is_err() {
+        return path.to_path_buf();
+    }
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1u32..)
+        .map(|n| parent.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Recreate a symlink at `dest` pointing to the same target as `src`.
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    let target = fs::read_link(src)?;
+    std::os::unix::fs::symlink(target, dest)
+}
+
+/// On Windows, creating symlinks needs extra privileges; copy the
+/// target's content instead (what Explorer does for file links).
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    if dest.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "Destination already exists",
+        ));
+    }
+    fs::copy(src, dest).map(|_| ())
+}
@@ -0,0 +1,228 @@
+// src-tauri/src/file_type.rs
+//
+// Content-based file type detection for previews.
+// Order of evidence:
+//   1) magic bytes (infer) -> MIME + kind, e.g. a PNG renamed to .txt
+//   2) text sniff of the first 8 KiB (no NUL bytes, valid UTF-8/UTF-16)
+//      -> text, MIME from the extension (text/markdown, ...)
+//   3) otherwise application/octet-stream, "Binary"
+//
+// Statistics (storage advisor, folder scan categories) use the much cheaper
+// `category_of`: extension only, no I/O.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::path_guard::PathGuard;
+
+/// Bytes inspected; enough for every signature infer knows.
+const SNIFF_BYTES: usize = 8 * 1024;
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileTypeInfo {
+    pub mime: String,
+    /// Human-readable kind: "Image", "Video", "Text", "Archive", ...
+    pub kind: String,
+    /// Extension that matches the content (may differ from the file name).
+    pub extension: Option<String>,
+    pub is_text: bool,
+    pub detected_by: String, // "magic" | "content" | "extension" | "none"
+}
+
+/// Frontend can call:
+///   invoke<FileTypeInfo>('detect_file_type', { path })
+#[tauri::command]
+pub async fn detect_file_type(
+    guard: State<'_, PathGuard>,
+    path: String,
+) -> Result<FileTypeInfo, String> {
+    let file = guard.check(&path)?;
+    spawn_blocking(move || {
+        detect_path(&file).map_err(|e| format!("Cannot read {}: {}", path, e))
+    })
+    .await
+    .map_err(|e| format!("File type detection failed: {}", e))?
+}
+
+/// Detect the type of `path` (also used by the preview commands).
+pub fn detect_path(path: &Path) -> std::io::Result<FileTypeInfo> {
+    if path.is_dir() {
+        return Ok(FileTypeInfo {
+            mime: "inode/directory".to_string(),
+            kind: "Folder".to_string(),
+            extension: None,
+            is_text: false,
+            detected_by: "none".to_string(),
+        });
+    }
+
+    let mut head = vec![0u8; SNIFF_BYTES];
+    let n = read_up_to(&mut File::open(path)?, &mut head)?;
+    head.truncate(n);
+    Ok(detect_bytes(path, &head))
+}
+
+fn detect_bytes(path: &Path, head: &[u8]) -> FileTypeInfo {
+    let name_ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+
+    if head.is_empty() {
+        return FileTypeInfo {
+            mime: guess_from_extension(path).unwrap_or_else(|| "application/x-empty".to_string()),
+            kind: "Empty file".to_string(),
+            extension: name_ext,
+            is_text: true,
+            detected_by: "extension".to_string(),
+        };
+    }
+
+    if let Some(t) = infer::get(head) {
+        let is_text = t.matcher_type() == infer::MatcherType::Text;
+        return FileTypeInfo {
+            mime: t.mime_type().to_string(),
+            kind: kind_label(t.matcher_type()).to_string(),
+            extension: Some(t.extension().to_string()),
+            is_text,
+            detected_by: "magic".to_string(),
+        };
+    }
+
+    if looks_like_text(head) {
+        return FileTypeInfo {
+            mime: guess_from_extension(path)
+                .filter(|m| m.starts_with("text/") || is_text_like_mime(m))
+                .unwrap_or_else(|| "text/plain".to_string()),
+            kind: "Text".to_string(),
+            extension: name_ext,
+            is_text: true,
+            detected_by: "content".to_string(),
+        };
+    }
+
+    FileTypeInfo {
+        mime: "application/octet-stream".to_string(),
+        kind: "Binary".to_string(),
+        extension: name_ext,
+        is_text: false,
+        detected_by: "none".to_string(),
+    }
+}
+
+fn kind_label(t: infer::MatcherType) -> &'static str {
+    use infer::MatcherType;
+    match t {
+        MatcherType::App => "Application",
+        MatcherType::Archive => "Archive",
+        MatcherType::Audio => "Audio",
+        MatcherType::Book => "Book",
+        MatcherType::Doc => "Document",
+        MatcherType::Font => "Font",
+        MatcherType::Image => "Image",
+        MatcherType::Text => "Text",
+        MatcherType::Video => "Video",
+        MatcherType::Custom => "Other",
+    }
+}
+
+/// Coarse file group for size statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileCategory {
+    Images,
+    Video,
+    Audio,
+    Documents,
+    Code,
+    Archives,
+    /// Setup programs and disk images (.exe, .msi, .dmg, .iso, ...).
+    Installers,
+    Other,
+}
+
+impl FileCategory {
+    pub const ALL: [FileCategory; 8] = [
+        FileCategory::Images,
+        FileCategory::Video,
+        FileCategory::Audio,
+        FileCategory::Documents,
+        FileCategory::Code,
+        FileCategory::Archives,
+        FileCategory::Installers,
+        FileCategory::Other,
+    ];
+}
+
+/// Category from the file extension alone.
+pub fn category_of(path: &Path) -> FileCategory {
+    let Some(ext) = path.extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+        return FileCategory::Other;
+    };
+    match ext.as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "webp" | "tif" | "tiff" | "heic" | "heif" | "avif" | "svg"
+        | "ico" | "raw" | "cr2" | "cr3" | "nef" | "arw" | "dng" | "orf" | "rw2" | "psd" | "xcf" => {
+            FileCategory::Images
+        }
+        "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" | "m4v" | "mpg" | "mpeg" | "3gp" | "mts"
+        | "m2ts" | "vob" => FileCategory::Video,
+        "mp3" | "wav" | "flac" | "aac" | "ogg" | "oga" | "opus" | "m4a" | "wma" | "aiff" | "aif" | "mid"
+        | "midi" => FileCategory::Audio,
+        "pdf" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "rtf" | "txt" | "md"
+        | "epub" | "mobi" | "csv" | "pages" | "numbers" | "key" | "tex" => FileCategory::Documents,
+        "rs" | "js" | "mjs" | "cjs" | "ts" | "tsx" | "jsx" | "svelte" | "vue" | "py" | "java" | "kt" | "c" | "h"
+        | "cpp" | "hpp" | "cc" | "cs" | "go" | "rb" | "php" | "swift" | "sh" | "ps1" | "bat" | "html" | "css"
+        | "scss" | "json" | "toml" | "yaml" | "yml" | "xml" | "sql" | "lua" | "dart" => FileCategory::Code,
+        "zip" | "7z" | "rar" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "lz4" | "cab" => {
+            FileCategory::Archives
+        }
+        "exe" | "msi" | "msix" | "appx" | "dmg" | "pkg" | "deb" | "rpm" | "appimage" | "flatpak" | "snap"
+        | "iso" | "img" | "vhd" | "vhdx" => FileCategory::Installers,
+        _ => FileCategory::Other,
+    }
+}
+
+/// Same rule as the content search (no NUL bytes), plus valid UTF-8 so
+/// Latin-1 blobs aren't shown as garbage. A multi-byte char cut off at
+/// the end of the sniff window is fine. UTF-16 with BOM counts as text.
+pub fn looks_like_text(head: &[u8]) -> bool {
+    if head.starts_with(&[0xFF, 0xFE]) || head.starts_with(&[0xFE, 0xFF]) {
+        return true;
+    }
+    if head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// application/json, application/xml, application/javascript, ...
+fn is_text_like_mime(mime: &str) -> bool {
+    mime.ends_with("json")
+        || mime.ends_with("xml")
+        || mime.ends_with("javascript")
+        || mime.ends_with("x-sh")
+        || mime.ends_with("toml")
+        || mime.ends_with("yaml")
+}
+
+fn guess_from_extension(path: &Path) -> Option<String> {
+    mime_guess::from_path(path).first().map(|m| m.essence_str().to_string())
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,1258 @@
+// src-tauri/src/folder_scan.rs
+//
+// The full scan runs on a rayon pool (`threads` option, default one per
+// core): every folder is a task, so on SSD/NVMe several directories are
+// read at once. A coordinating thread emits throttled progress meanwhile.
+//
+// Symlinks / junctions: by default they are not followed and are counted
+// as `skippedSymlinks`. With `followSymlinks: true` every directory is
+// identified by (device, inode) — (volume serial, file index) on
+// Windows — and a directory reached a second time (link cycle, or two
+// links to the same folder) is skipped instead of counted twice.
+// NTFS junctions stay skipped even then (counted as `skippedJunctions`)
+// unless `followJunctions: true`: system junctions like "Application Data"
+// point back into the profile and would count it again.
+//
+// Sizes: `totalSize` is the logical size; `allocatedSize` is what the
+// files occupy on disk (less for sparse / compressed files), reported by
+// full local scans only.
+//
+// With `categories: true` files are also bucketed by extension group
+// (file_type::category_of) and the completion event carries count and bytes
+// per group. Incremental rescans can't (reused folders have no file names)
+// and report none.
+//
+// Incremental rescans: a scan with `persistSnapshot: true` stores per-folder
+// results (scan_snapshot.rs); start_incremental_scan then only lists
+// folders whose mtime changed and reuses the stored totals for the rest.
+// It reports through the same fu:folder_scan_* events.
+//
+// `filter` (file_filter.rs) limits which files are counted, e.g. only
+// videos over 1 GB; folders it excludes are not walked.
+//
+// With settings `system.background_priority` the walk and its workers run
+// at background CPU / IO priority (background_priority.rs).
+//
+// Remote folders (vfs/mod.rs) are walked one listing at a time through
+// their provider, with the same events; symlinks are never followed there
+// and snapshots / incremental rescans are local-only.
+
+use crate::background_priority;
+use crate::error::AppError;
+use crate::file_filter::{FileFilter, FileFilterSpec, FilterEntry};
+use crate::file_meta::{allocated_size, is_sparse, reparse_kind, ReparseKind};
+use crate::file_type::{category_of, FileCategory};
+use crate::gps_backend::{OperationKind, OperationRegistry, PauseGate};
+use crate::path_guard::PathGuard;
+use crate::scan_report::{save_report, ScanReport};
+use crate::scan_snapshot::{load_snapshot, mtime_ns, rel_key, save_snapshot, ScanSnapshot};
+use crate::vfs::{Vfs, VfsPath};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FolderScanProgress {
+    op_id: String,
+    folder_count: u64,
+    file_count: u64,
+    total_size: u64,
+    /// Per-root subtotals of a multi-root scan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    roots: Option<Vec<RootSubtotal>>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RootSubtotal {
+    path: String,
+    status: String, // "pending" | "running" | "ok" | "cancelled" | "error"
+    folder_count: u64,
+    file_count: u64,
+    total_size: u64,
+    error_message: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderScanCompleted {
+    op_id: String,
+    status: String, // "ok" | "partial" (multi-root) | "cancelled" | "error"
+    folder_count: u64,
+    file_count: u64,
+    total_size: u64,
+    /// Bytes allocated on disk; None for incremental and remote scans.
+    allocated_size: Option<u64>,
+    sparse_files: u64,
+    skipped_symlinks: u64,
+    skipped_junctions: u64,
+    /// Only with the `categories` option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    categories: Option<Vec<CategoryStat>>,
+    /// Only set by start_incremental_scan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    incremental: Option<IncrementalStats>,
+    /// Only for multi-root scans; the totals above are their sum.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    roots: Option<Vec<RootSubtotal>>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CategoryStat {
+    category: FileCategory,
+    files: u64,
+    bytes: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct IncrementalStats {
+    /// Folders listed again because their mtime changed (or are new).
+    rescanned_dirs: u64,
+    /// Folders whose stored totals were reused.
+    reused_dirs: u64,
+    /// True if no snapshot existed and a full scan was done instead.
+    full_scan: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderScanOptions {
+    /// Descend into symlinked folders / junctions (with cycle detection).
+    pub follow_symlinks: bool,
+    /// Also descend into NTFS junctions when following symlinks.
+    pub follow_junctions: bool,
+    /// Store per-folder results for start_incremental_scan.
+    /// Ignored together with follow_symlinks (snapshots never follow links)
+    /// or a filter (snapshots hold full totals).
+    pub persist_snapshot: bool,
+    /// Keep the per-folder breakdown for export_scan_report (scan_report.rs).
+    /// Ignored together with follow_symlinks or a filter, like persist_snapshot.
+    pub save_report: bool,
+    /// Worker threads for the walk; 0 = one per CPU core.
+    pub threads: usize,
+    /// Count files and bytes per extension group.
+    pub categories: bool,
+    /// Only count files passing the shared filter (file_filter.rs); folders
+    /// it excludes are not walked. Folder counts still include every
+    /// folder walked.
+    pub filter: Option<FileFilterSpec>,
+}
+
+/// One root, or several scanned one after another under the same op id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ScanRoots {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<String> for ScanRoots {
+    fn from(path: String) -> Self {
+        ScanRoots::One(path)
+    }
+}
+
+/// Command from TS:
+/// invoke("start_folder_scan", { opId, path, options: { followSymlinks: true, followJunctions: false, persistSnapshot: false, saveReport: false, threads: 0, categories: true } })
+///
+/// Several local roots (e.g. every drive) in one operation:
+/// invoke("start_folder_scan", { opId, path: ["C:\\", "D:\\"] })
+/// Progress and the completed event then carry `roots` with per-root
+/// subtotals; a root that fails doesn't stop the others, the status is
+/// "partial" when some failed.
+#[tauri::command]
+pub async fn start_folder_scan(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    vfs: State<'_, Vfs>,
+    op_id: String,
+    path: ScanRoots,
+    options: Option<FolderScanOptions>,
+) -> Result<(), AppError> {
+    // Reject a bad filter before anything is registered.
+    if let Some(options) = &options {
+        FileFilter::from_spec(options.filter.as_ref())?;
+    }
+    let path = match path {
+        ScanRoots::One(path) => path,
+        ScanRoots::Many(paths) if paths.len() == 1 => paths.into_iter().next().unwrap_or_default(),
+        ScanRoots::Many(paths) => {
+            let roots = paths
+                .iter()
+                .map(|p| {
+                    let root = vfs.resolve(&guard, p)?;
+                    if !root.is_local() {
+                        return Err(AppError::invalid_input(format!(
+                            "Multi-root scans only support local folders: {}",
+                            p
+                        )));
+                    }
+                    Ok(PathBuf::from(root.path))
+                })
+                .collect::<Result<Vec<_>, AppError>>()?;
+            if roots.is_empty() {
+                return Err(AppError::invalid_input("No paths to scan"));
+            }
+            start_multi_root_scan(app, &registry, op_id, roots, options.unwrap_or_default());
+            return Ok(());
+        }
+    };
+    let root = vfs.resolve(&guard, &path)?;
+    let path = PathBuf::from(&root.path);
+    let options = options.unwrap_or_default();
+
+    // 1) Register operation in global registry, get CancellationToken + PauseGate
+    let (token, pause) = registry.register_pausable(&op_id, OperationKind::FolderScan);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    if !root.is_local() {
+        background_priority::spawn_blocking(low, move || {
+            let filter = FileFilter::from_spec(options.filter.as_ref()).ok().flatten();
+            let res = run_vfs_scan_blocking(&app, &op_id, &root, options.categories, filter.as_ref(), &token, &pause);
+            emit_scan_completed(&app, &op_id, res, None);
+            registry.finish(&op_id, &token);
+        });
+        return Ok(());
+    }
+
+    // 2) Spawn the heavy work in background
+    //    Use spawn_blocking because the walk is synchronous and potentially heavy.
+    background_priority::spawn_blocking(low, move || {
+        let mut snapshot = wants_snapshot(&options).then(|| ScanSnapshot::new(&path));
+        let res = run_folder_scan_blocking(
+            &|stats| emit_scan_progress(&app, &op_id, stats),
+            &path,
+            &options,
+            &token,
+            &pause,
+            snapshot.as_mut(),
+            low,
+        );
+        if let (Ok(_), Some(snapshot)) = (&res, snapshot) {
+            store_scan_results(&app, &op_id, &options, vec![snapshot]);
+        }
+
+        // 3) Emit final "completed" event regardless of outcome
+        emit_scan_completed(&app, &op_id, res, None);
+
+        // Remove from registry AFTER we emitted completed event,
+        // so a late cancel from TS can't race with the final status.
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Command from TS:
+/// invoke("start_incremental_scan", { opId, path })
+///
+/// Rescans `path` using the snapshot stored by an earlier scan with
+/// persistSnapshot (or an earlier incremental scan), then stores the
+/// updated snapshot. Without a snapshot it falls back to a full scan.
+/// Symlinks are never followed here.
+#[tauri::command]
+pub async fn start_incremental_scan(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+) -> Result<(), AppError> {
+    let path = guard.check(&path)?;
+    let (token, pause) = registry.register_pausable(&op_id, OperationKind::FolderScan);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let mut next = ScanSnapshot::new(&path);
+        let mut incremental = IncrementalStats::default();
+
+        let res = match load_snapshot(&app, &path) {
+            Some(previous) => run_incremental_scan_blocking(
+                &app,
+                &op_id,
+                &path,
+                &previous,
+                &mut next,
+                &mut incremental,
+                &token,
+                &pause,
+            ),
+            None => {
+                incremental.full_scan = true;
+                run_folder_scan_blocking(
+                    &|stats| emit_scan_progress(&app, &op_id, stats),
+                    &path,
+                    &FolderScanOptions::default(),
+                    &token,
+                    &pause,
+                    Some(&mut next),
+                    low,
+                )
+            }
+        };
+        if res.is_ok() {
+            if let Err(e) = save_snapshot(&app, &next) {
+                tracing::warn!("[FolderScan] {e}");
+            }
+        }
+
+        emit_scan_completed(&app, &op_id, res, Some(incremental));
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Scan a local folder without an app (cli.rs `--scan`): no sandbox, no
+/// registry, no events. Returns the `fu:folder_scan_completed` payload.
+pub fn scan_folder_headless(root: &Path, options: &FolderScanOptions) -> FolderScanCompleted {
+    let res = run_folder_scan_blocking(
+        &|_| {},
+        &root.to_path_buf(),
+        options,
+        &CancellationToken::new(),
+        &PauseGate::default(),
+        None,
+        false,
+    );
+    scan_completed("cli", res, None)
+}
+
+fn start_multi_root_scan(
+    app: AppHandle,
+    registry: &OperationRegistry,
+    op_id: String,
+    roots: Vec<PathBuf>,
+    options: FolderScanOptions,
+) {
+    let (token, pause) = registry.register_pausable(&op_id, OperationKind::FolderScan);
+    let registry = registry.clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let mut subtotals: Vec<RootSubtotal> = roots
+            .iter()
+            .map(|root| RootSubtotal {
+                path: root.to_string_lossy().to_string(),
+                status: "pending".to_string(),
+                folder_count: 0,
+                file_count: 0,
+                total_size: 0,
+                error_message: None,
+            })
+            .collect();
+        let mut totals = FolderScanStats {
+            allocated: Some(0),
+            categories: options.categories.then(|| vec![(0, 0); FileCategory::ALL.len()]),
+            ..Default::default()
+        };
+        let mut first_error = None;
+        let mut snapshots = Vec::new();
+
+        for (i, root) in roots.iter().enumerate() {
+            subtotals[i].status = "running".to_string();
+            let mut snapshot = wants_snapshot(&options).then(|| ScanSnapshot::new(root));
+            let res = run_folder_scan_blocking(
+                &|stats| {
+                    let mut running = subtotals.clone();
+                    running[i].set_counts(stats);
+                    let mut sum = totals.clone();
+                    sum.add(stats);
+                    emit_progress(&app, &op_id, &sum, Some(running));
+                },
+                root,
+                &options,
+                &token,
+                &pause,
+                snapshot.as_mut(),
+                low,
+            );
+
+            match res {
+                Ok(stats) => {
+                    snapshots.extend(snapshot);
+                    subtotals[i].set_counts(&stats);
+                    subtotals[i].status = "ok".to_string();
+                    totals.add(&stats);
+                }
+                Err(FolderScanError::Cancelled(stats)) => {
+                    subtotals[i].set_counts(&stats);
+                    subtotals[i].status = "cancelled".to_string();
+                    totals.add(&stats);
+                    break;
+                }
+                Err(e) => {
+                    let error = e.to_app_error();
+                    tracing::warn!("[FolderScan] Root {:?} failed: {}", root, error.message);
+                    subtotals[i].status = "error".to_string();
+                    subtotals[i].error_message = Some(error.message);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        let failed = subtotals.iter().filter(|r| r.status == "error").count();
+        let res = if token.is_cancelled() {
+            Err(FolderScanError::Cancelled(totals))
+        } else if failed == roots.len() {
+            Err(first_error.unwrap_or(FolderScanError::Cancelled(totals)))
+        } else {
+            Ok(totals)
+        };
+        if res.is_ok() {
+            store_scan_results(&app, &op_id, &options, snapshots);
+        }
+        let mut completed = scan_completed(&op_id, res, None);
+        if failed > 0 && completed.status == "ok" {
+            completed.status = "partial".to_string();
+        }
+        completed.roots = Some(subtotals);
+        let _ = app.emit("fu:folder_scan_completed", completed);
+
+        registry.finish(&op_id, &token);
+    });
+}
+
+fn wants_snapshot(options: &FolderScanOptions) -> bool {
+    (options.persist_snapshot || options.save_report)
+        && !options.follow_symlinks
+        && options.filter.as_ref().is_none_or(FileFilterSpec::is_empty)
+}
+
+/// Snapshots of the roots that completed; stored before the completed event
+/// so the frontend can export the report right away.
+fn store_scan_results(app: &AppHandle, op_id: &str, options: &FolderScanOptions, snapshots: Vec<ScanSnapshot>) {
+    if options.save_report {
+        if let Err(e) = save_report(app, &ScanReport::from_snapshots(op_id, &snapshots)) {
+            tracing::warn!("[FolderScan] {e}");
+        }
+    }
+    if options.persist_snapshot {
+        for snapshot in &snapshots {
+            if let Err(e) = save_snapshot(app, snapshot) {
+                tracing::warn!("[FolderScan] {e}");
+            }
+        }
+    }
+}
+
+impl RootSubtotal {
+    fn set_counts(&mut self, stats: &FolderScanStats) {
+        self.folder_count = stats.folders;
+        self.file_count = stats.files;
+        self.total_size = stats.size;
+    }
+}
+
+fn emit_scan_completed(
+    app: &AppHandle,
+    op_id: &str,
+    res: Result<FolderScanStats, FolderScanError>,
+    incremental: Option<IncrementalStats>,
+) {
+    let _ = app.emit("fu:folder_scan_completed", scan_completed(op_id, res, incremental));
+}
+
+fn scan_completed(
+    op_id: &str,
+    res: Result<FolderScanStats, FolderScanError>,
+    incremental: Option<IncrementalStats>,
+) -> FolderScanCompleted {
+    let error = res.as_ref().err().map(FolderScanError::to_app_error);
+    let (status, stats, error_message) = match res {
+        Ok(stats) => ("ok".to_string(), stats, None),
+        Err(FolderScanError::Cancelled(stats)) => ("cancelled".to_string(), stats, None),
+        Err(FolderScanError::IoError(e)) => (
+            "error".to_string(),
+            FolderScanStats::default(),
+            Some(format!("I/O error: {}", e)),
+        ),
+        Err(FolderScanError::Provider(e)) => ("error".to_string(), FolderScanStats::default(), Some(e.message)),
+    };
+
+    FolderScanCompleted {
+        op_id: op_id.to_string(),
+        status,
+        folder_count: stats.folders,
+        file_count: stats.files,
+        total_size: stats.size,
+        allocated_size: stats.allocated,
+        sparse_files: stats.sparse_files,
+        skipped_symlinks: stats.skipped_symlinks,
+        skipped_junctions: stats.skipped_junctions,
+        categories: stats.categories.map(|c| category_stats(&c)),
+        incremental,
+        roots: None,
+        error_message,
+        error,
+    }
+}
+
+// Stats container for convenience
+#[derive(Default, Clone)]
+struct FolderScanStats {
+    folders: u64,
+    files: u64,
+    size: u64,
+    allocated: Option<u64>,
+    sparse_files: u64,
+    skipped_symlinks: u64,
+    skipped_junctions: u64,
+    /// (files, bytes) per FileCategory::ALL entry.
+    categories: Option<Vec<(u64, u64)>>,
+}
+
+impl FolderScanStats {
+    /// Sum of two scans (multi-root).
+    fn add(&mut self, other: &FolderScanStats) {
+        self.folders += other.folders;
+        self.files += other.files;
+        self.size += other.size;
+        self.allocated = match (self.allocated, other.allocated) {
+            (Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        };
+        self.sparse_files += other.sparse_files;
+        self.skipped_symlinks += other.skipped_symlinks;
+        self.skipped_junctions += other.skipped_junctions;
+        if let (Some(mine), Some(theirs)) = (&mut self.categories, &other.categories) {
+            for ((files, bytes), (f, b)) in mine.iter_mut().zip(theirs) {
+                *files += f;
+                *bytes += b;
+            }
+        }
+    }
+}
+
+fn category_stats(totals: &[(u64, u64)]) -> Vec<CategoryStat> {
+    FileCategory::ALL
+        .iter()
+        .zip(totals)
+        .map(|(category, (files, bytes))| CategoryStat {
+            category: *category,
+            files: *files,
+            bytes: *bytes,
+        })
+        .collect()
+}
+
+fn category_index(path: &Path) -> usize {
+    let category = category_of(path);
+    FileCategory::ALL.iter().position(|c| *c == category).unwrap_or(FileCategory::ALL.len() - 1)
+}
+
+// Rich error type: either cancelled with partial stats, or IO error
+// (from std::fs, or from a remote provider).
+enum FolderScanError {
+    Cancelled(FolderScanStats),
+    IoError(std::io::Error),
+    Provider(AppError),
+}
+
+impl FolderScanError {
+    fn to_app_error(&self) -> AppError {
+        match self {
+            FolderScanError::Cancelled(_) => AppError::cancelled(),
+            FolderScanError::IoError(e) => AppError::io(e).context("I/O error"),
+            FolderScanError::Provider(e) => e.clone(),
+        }
+    }
+}
+
+fn run_folder_scan_blocking(
+    progress: &dyn Fn(&FolderScanStats),
+    root: &PathBuf,
+    options: &FolderScanOptions,
+    token: &CancellationToken,
+    pause: &PauseGate,
+    snapshot: Option<&mut ScanSnapshot>,
+    low_priority: bool,
+) -> Result<FolderScanStats, FolderScanError> {
+    // Fail fast if the root itself is unreadable (missing, no access).
+    let root_meta = std::fs::metadata(root).map_err(FolderScanError::IoError)?;
+    let filter = FileFilter::from_spec(options.filter.as_ref()).map_err(FolderScanError::Provider)?;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .thread_name(|i| format!("folder-scan-{i}"))
+        .start_handler(move |_| {
+            if low_priority {
+                background_priority::lower_current_thread();
+            }
+        })
+        .build()
+        .map_err(|e| FolderScanError::IoError(std::io::Error::other(e)))?;
+
+    let scan = ParallelScan {
+        root,
+        follow_symlinks: options.follow_symlinks,
+        follow_junctions: options.follow_junctions,
+        filter,
+        token,
+        pause,
+        folders: AtomicU64::new(0),
+        files: AtomicU64::new(0),
+        size: AtomicU64::new(0),
+        allocated: AtomicU64::new(0),
+        sparse_files: AtomicU64::new(0),
+        skipped_symlinks: AtomicU64::new(0),
+        skipped_junctions: AtomicU64::new(0),
+        categories: options.categories.then(Default::default),
+        visited_dirs: Mutex::new(HashSet::new()),
+        snapshot: snapshot.as_ref().map(|_| Mutex::new(ScanSnapshot::new(root))),
+    };
+    if options.follow_symlinks {
+        if let Some(id) = file_id(root, &root_meta) {
+            scan.lock_visited().insert(id);
+        }
+    }
+
+    // Workers walk the tree; this thread only emits throttled progress
+    // (~100ms) until they are done.
+    std::thread::scope(|s| {
+        let workers = s.spawn(|| {
+            pool.install(|| {
+                rayon::scope(|sc| scan.visit_dir(sc, root.clone(), mtime_ns(&root_meta)))
+            })
+        });
+        while !workers.is_finished() {
+            std::thread::sleep(Duration::from_millis(100));
+            progress(&scan.stats());
+        }
+    });
+
+    let stats = scan.stats();
+    if let (Some(target), Some(built)) = (snapshot, scan.snapshot) {
+        *target = built.into_inner().unwrap_or_else(|e| e.into_inner());
+    }
+
+    if token.is_cancelled() {
+        // Return partial stats; TS can show "partial result" message
+        return Err(FolderScanError::Cancelled(stats));
+    }
+
+    // Final progress update
+    progress(&stats);
+
+    Ok(stats)
+}
+
+/// Shared state of one parallel scan. Each directory is one rayon task,
+/// so idle workers steal whole subtrees from busy ones.
+struct ParallelScan<'a> {
+    root: &'a Path,
+    follow_symlinks: bool,
+    follow_junctions: bool,
+    filter: Option<FileFilter>,
+    token: &'a CancellationToken,
+    pause: &'a PauseGate,
+    folders: AtomicU64,
+    files: AtomicU64,
+    size: AtomicU64,
+    allocated: AtomicU64,
+    sparse_files: AtomicU64,
+    skipped_symlinks: AtomicU64,
+    skipped_junctions: AtomicU64,
+    /// (files, bytes) per FileCategory::ALL entry, when requested.
+    categories: Option<[(AtomicU64, AtomicU64); 8]>,
+    /// Directories already counted (only used when following links).
+    visited_dirs: Mutex<HashSet<FileId>>,
+    snapshot: Option<Mutex<ScanSnapshot>>,
+}
+
+impl<'a> ParallelScan<'a> {
+    fn stats(&self) -> FolderScanStats {
+        FolderScanStats {
+            folders: self.folders.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+            size: self.size.load(Ordering::Relaxed),
+            allocated: Some(self.allocated.load(Ordering::Relaxed)),
+            sparse_files: self.sparse_files.load(Ordering::Relaxed),
+            skipped_symlinks: self.skipped_symlinks.load(Ordering::Relaxed),
+            skipped_junctions: self.skipped_junctions.load(Ordering::Relaxed),
+            categories: self.categories.as_ref().map(|c| {
+                c.iter()
+                    .map(|(files, bytes)| (files.load(Ordering::Relaxed), bytes.load(Ordering::Relaxed)))
+                    .collect()
+            }),
+        }
+    }
+
+    fn lock_visited(&self) -> std::sync::MutexGuard<'_, HashSet<FileId>> {
+        self.visited_dirs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn with_snapshot(&self, f: impl FnOnce(&mut ScanSnapshot)) {
+        if let Some(snapshot) = &self.snapshot {
+            f(&mut snapshot.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+
+    /// Count `dir` itself, its files, and spawn one task per subfolder.
+    fn visit_dir<'s>(&'s self, scope: &rayon::Scope<'s>, dir: PathBuf, mtime: u64)
+    where
+        'a: 's,
+    {
+        // Block while paused (counters are kept, so resume continues in place)
+        self.pause.wait_while_paused(self.token);
+        if self.token.is_cancelled() {
+            return;
+        }
+
+        self.folders.fetch_add(1, Ordering::Relaxed);
+        self.with_snapshot(|s| s.add_dir(rel_path(self.root, &dir), mtime));
+
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                // We skip unreadable folders but don't kill the whole scan.
+                tracing::warn!("[FolderScan] read_dir error {:?}: {err}", dir);
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            if self.token.is_cancelled() {
+                return;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            let metadata = if file_type.is_symlink() {
+                // std reports junctions as symlinks too; tell them apart.
+                let junction = entry
+                    .metadata()
+                    .is_ok_and(|m| reparse_kind(&path, &m) == Some(ReparseKind::Junction));
+                if junction && !(self.follow_symlinks && self.follow_junctions) {
+                    self.skipped_junctions.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                if !self.follow_symlinks {
+                    self.skipped_symlinks.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                match std::fs::metadata(&path) {
+                    Ok(m) => m,
+                    // Broken link.
+                    Err(_) => {
+                        self.skipped_symlinks.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+            } else {
+                match entry.metadata() {
+                    Ok(m) => m,
+                    Err(err) => {
+                        tracing::warn!("[FolderScan] Metadata error: {err}");
+                        continue;
+                    }
+                }
+            };
+
+            if let Some(filter) = &self.filter {
+                let candidate = FilterEntry::from_metadata(rel_path(self.root, &path), &metadata);
+                let keep = if metadata.is_dir() {
+                    filter.allows_dir(&candidate)
+                } else {
+                    filter.matches(&candidate)
+                };
+                if !keep {
+                    continue;
+                }
+            }
+
+            if metadata.is_dir() {
+                if self.follow_symlinks {
+                    if let Some(id) = file_id(&path, &metadata) {
+                        if !self.lock_visited().insert(id) {
+                            // Link cycle, or same folder via another link:
+                            // don't count it twice.
+                            self.skipped_symlinks.fetch_add(1, Ordering::Relaxed);
+                            continue;
+                        }
+                    }
+                }
+                let mtime = mtime_ns(&metadata);
+                scope.spawn(move |sc| self.visit_dir(sc, path, mtime));
+            } else if metadata.is_file() {
+                self.files.fetch_add(1, Ordering::Relaxed);
+                self.size.fetch_add(metadata.len(), Ordering::Relaxed);
+                let allocated = allocated_size(&path, &metadata);
+                self.allocated.fetch_add(allocated, Ordering::Relaxed);
+                if is_sparse(&metadata, allocated) {
+                    self.sparse_files.fetch_add(1, Ordering::Relaxed);
+                }
+                if let Some(categories) = &self.categories {
+                    let (files, bytes) = &categories[category_index(&path)];
+                    files.fetch_add(1, Ordering::Relaxed);
+                    bytes.fetch_add(metadata.len(), Ordering::Relaxed);
+                }
+                self.with_snapshot(|s| s.add_file(rel_path(self.root, &path), metadata.len()));
+            }
+        }
+    }
+}
+
+/// Full scan of a folder on a remote provider: one listing per folder,
+/// sequentially (remote connections serialize requests anyway).
+/// Symlinks are counted as skipped, never followed.
+fn run_vfs_scan_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    root: &VfsPath,
+    categories: bool,
+    filter: Option<&FileFilter>,
+    token: &CancellationToken,
+    pause: &PauseGate,
+) -> Result<FolderScanStats, FolderScanError> {
+    let mut stats = FolderScanStats {
+        categories: categories.then(|| vec![(0, 0); FileCategory::ALL.len()]),
+        ..Default::default()
+    };
+    let mut last_emit = Instant::now();
+    let mut stack = vec![root.path.clone()];
+    while let Some(dir) = stack.pop() {
+        pause.wait_while_paused(token);
+        if token.is_cancelled() {
+            return Err(FolderScanError::Cancelled(stats));
+        }
+        stats.folders += 1;
+
+        let entries = match root.provider.list(&dir) {
+            Ok(entries) => entries,
+            // Fail fast if the root itself is unreadable (missing, no access).
+            Err(e) if dir == root.path => return Err(FolderScanError::Provider(e)),
+            Err(e) => {
+                tracing::warn!("[FolderScan] list error {:?}: {}", dir, e.message);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            if let Some(filter) = filter {
+                let rel = entry.path.strip_prefix(&root.path).unwrap_or(&entry.path);
+                let candidate = FilterEntry::from_vfs(Path::new(rel.trim_start_matches('/')), &entry);
+                let keep = if entry.is_dir {
+                    filter.allows_dir(&candidate)
+                } else {
+                    filter.matches(&candidate)
+                };
+                if !keep && !entry.is_symlink {
+                    continue;
+                }
+            }
+            if entry.is_symlink {
+                stats.skipped_symlinks += 1;
+            } else if entry.is_dir {
+                stack.push(entry.path);
+            } else {
+                stats.files += 1;
+                stats.size += entry.size;
+                if let Some(categories) = &mut stats.categories {
+                    let (files, bytes) = &mut categories[category_index(Path::new(&entry.name))];
+                    *files += 1;
+                    *bytes += entry.size;
+                }
+            }
+        }
+
+        if last_emit.elapsed() >= Duration::from_millis(100) {
+            emit_scan_progress(app, op_id, &stats);
+            last_emit = Instant::now();
+        }
+    }
+
+    emit_scan_progress(app, op_id, &stats);
+    Ok(stats)
+}
+
+/// Walk directories only; list a folder's files just when its mtime
+/// differs from the previous snapshot. Fills `next` as it goes.
+#[allow(clippy::too_many_arguments)]
+fn run_incremental_scan_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    root: &Path,
+    previous: &ScanSnapshot,
+    next: &mut ScanSnapshot,
+    incremental: &mut IncrementalStats,
+    token: &CancellationToken,
+    pause: &PauseGate,
+) -> Result<FolderScanStats, FolderScanError> {
+    let mut stats = FolderScanStats::default();
+    let mut last_emit = Instant::now();
+
+    std::fs::metadata(root).map_err(FolderScanError::IoError)?;
+
+    // Depth-first over relative paths; parents are recorded before children.
+    let mut stack: Vec<PathBuf> = vec![PathBuf::new()];
+    while let Some(rel) = stack.pop() {
+        pause.wait_while_paused(token);
+        if token.is_cancelled() {
+            return Err(FolderScanError::Cancelled(stats));
+        }
+
+        let dir = root.join(&rel);
+        let meta = match std::fs::symlink_metadata(&dir) {
+            Ok(m) if m.is_dir() => m,
+            // Vanished since the snapshot, or replaced by a file/link.
+            _ => continue,
+        };
+        let mtime = mtime_ns(&meta);
+        next.add_dir(&rel, mtime);
+        stats.folders += 1;
+
+        let key = rel_key(&rel);
+        match previous.dirs.get(&key).filter(|r| r.mtime_ns == mtime) {
+            Some(record) => {
+                incremental.reused_dirs += 1;
+                stats.files += record.files;
+                stats.size = stats.size.saturating_add(record.bytes);
+                if let Some(current) = next.dirs.get_mut(&key) {
+                    current.files = record.files;
+                    current.bytes = record.bytes;
+                }
+                for name in record.subdirs.iter().rev() {
+                    stack.push(rel.join(name));
+                }
+            }
+            None => {
+                incremental.rescanned_dirs += 1;
+                let entries = match std::fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(err) => {
+                        tracing::warn!("[FolderScan] read_dir error: {err}");
+                        continue;
+                    }
+                };
+                let mut subdirs = Vec::new();
+                for entry in entries.flatten() {
+                    let Ok(file_type) = entry.file_type() else {
+                        continue;
+                    };
+                    if file_type.is_symlink() {
+                        let path = entry.path();
+                        match entry.metadata().ok().and_then(|m| reparse_kind(&path, &m)) {
+                            Some(ReparseKind::Junction) => stats.skipped_junctions += 1,
+                            _ => stats.skipped_symlinks += 1,
+                        }
+                    } else if file_type.is_dir() {
+                        subdirs.push(rel.join(entry.file_name()));
+                    } else if file_type.is_file() {
+                        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                        stats.files += 1;
+                        stats.size = stats.size.saturating_add(size);
+                        next.add_file(&rel.join(entry.file_name()), size);
+                    }
+                }
+                stack.extend(subdirs.into_iter().rev());
+            }
+        }
+
+        if last_emit.elapsed().as_millis() >= 100 {
+            emit_scan_progress(app, op_id, &stats);
+            last_emit = Instant::now();
+        }
+    }
+
+    emit_scan_progress(app, op_id, &stats);
+    Ok(stats)
+}
+
+fn rel_path<'a>(root: &Path, path: &'a Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+fn emit_scan_progress(app: &AppHandle, op_id: &str, stats: &FolderScanStats) {
+    emit_progress(app, op_id, stats, None);
+}
+
+fn emit_progress(app: &AppHandle, op_id: &str, stats: &FolderScanStats, roots: Option<Vec<RootSubtotal>>) {
+    let _ = app.emit(
+        "fu:folder_scan_progress",
+        FolderScanProgress {
+            op_id: op_id.to_string(),
+            folder_count: stats.folders,
+            file_count: stats.files,
+            total_size: stats.size,
+            roots,
+        },
+    );
+}
+
+/// Identity of a file/directory independent of the path used to reach it.
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(_path: &Path, metadata: &std::fs::Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn file_id(path: &Path, _metadata: &std::fs::Metadata) -> Option<FileId> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+    };
+
+    // access_mode(0): query-only handle; BACKUP_SEMANTICS is needed for dirs.
+    let file = std::fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+        .ok()?;
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) } == 0 {
+        return None;
+    }
+    let index = ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64;
+    Some((info.dwVolumeSerialNumber as u64, index))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_id(_path: &Path, _metadata: &std::fs::Metadata) -> Option<FileId> {
+    None
+}
+
+// ======================= Size breakdown (treemap) =======================
+
+/// Aggregated totals for one folder in the breakdown.
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct SizeNode {
+    path: String,
+    /// 0 = scan root, 1 = immediate child, ...
+    depth: u32,
+    size: u64,
+    file_count: u64,
+    folder_count: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SizeBreakdownProgress {
+    op_id: String,
+    /// Only nodes that changed since the previous progress event.
+    nodes: Vec<SizeNode>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SizeBreakdownCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    nodes: Vec<SizeNode>,
+    total_size: u64,
+    error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// Command from TS:
+/// invoke("start_size_breakdown", { opId, path, depth })
+///
+/// Computes size per folder down to `depth` levels below `path`
+/// (default 1 = immediate children only). Loose files of a folder are
+/// `parent.size - sum(children.size)` on the UI side.
+#[tauri::command]
+pub async fn start_size_breakdown(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+    depth: Option<u32>,
+) -> Result<(), AppError> {
+    let root = guard.check(&path)?;
+    let depth = depth.unwrap_or(1).clamp(1, 8);
+
+    let token = registry.register(&op_id, OperationKind::SizeBreakdown);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let mut breakdown = SizeBreakdown::new(&root, depth);
+        let res = breakdown.run(&app, &op_id, &token);
+
+        let error = res.as_ref().err().map(FolderScanError::to_app_error);
+        let (status, error_message) = match res {
+            Ok(()) => ("ok", None),
+            Err(FolderScanError::Cancelled(_)) => ("cancelled", None),
+            Err(FolderScanError::IoError(e)) => ("error", Some(format!("I/O error: {}", e))),
+            Err(FolderScanError::Provider(e)) => ("error", Some(e.message)),
+        };
+        let total_size = breakdown
+            .nodes
+            .get(Path::new(""))
+            .map(|n| n.size)
+            .unwrap_or(0);
+
+        let _ = app.emit(
+            "fu:size_breakdown_completed",
+            SizeBreakdownCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                nodes: breakdown.snapshot_all(),
+                total_size,
+                error_message,
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+struct SizeBreakdown<'a> {
+    root: &'a Path,
+    max_depth: u32,
+    /// Keyed by path relative to root ("" = root itself).
+    nodes: HashMap<PathBuf, SizeNode>,
+    dirty: HashSet<PathBuf>,
+}
+
+impl<'a> SizeBreakdown<'a> {
+    fn new(root: &'a Path, max_depth: u32) -> Self {
+        Self {
+            root,
+            max_depth,
+            nodes: HashMap::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    fn run(
+        &mut self,
+        app: &AppHandle,
+        op_id: &str,
+        token: &CancellationToken,
+    ) -> Result<(), FolderScanError> {
+        std::fs::metadata(self.root).map_err(FolderScanError::IoError)?;
+        let mut last_emit = Instant::now();
+
+        for entry in WalkDir::new(self.root).into_iter() {
+            if token.is_cancelled() {
+                return Err(FolderScanError::Cancelled(FolderScanStats::default()));
+            }
+
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    tracing::warn!("[SizeBreakdown] WalkDir error: {err}");
+                    continue;
+                }
+            };
+            let metadata = match entry.metadata() {
+                Ok(m) => m,
+                Err(err) => {
+                    tracing::warn!("[SizeBreakdown] Metadata error: {err}");
+                    continue;
+                }
+            };
+
+            let rel = entry
+                .path()
+                .strip_prefix(self.root)
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+
+            if metadata.is_dir() {
+                // Make sure empty tracked folders show up too.
+                if (rel.components().count() as u32) <= self.max_depth {
+                    self.node_mut(&rel);
+                }
+                self.add(&rel, 0, false, true);
+            } else if metadata.is_file() {
+                self.add(&rel, metadata.len(), true, false);
+            }
+
+            if last_emit.elapsed().as_millis() >= 250 {
+                self.emit_dirty(app, op_id);
+                last_emit = Instant::now();
+            }
+        }
+
+        self.emit_dirty(app, op_id);
+        Ok(())
+    }
+
+    fn node_mut(&mut self, rel: &Path) -> &mut SizeNode {
+        let root = self.root;
+        self.nodes.entry(rel.to_path_buf()).or_insert_with(|| SizeNode {
+            path: root.join(rel).to_string_lossy().to_string(),
+            depth: rel.components().count() as u32,
+            ..SizeNode::default()
+        })
+    }
+
+    /// Attribute an entry to every tracked ancestor (root + up to max_depth).
+    /// A folder is not counted inside itself, only in its ancestors.
+    fn add(&mut self, rel: &Path, size: u64, is_file: bool, is_dir: bool) {
+        let components: Vec<_> = rel.components().collect();
+        let own_depth = components.len();
+        let tracked = own_depth.min(self.max_depth as usize);
+
+        for level in 0..=tracked {
+            if is_dir && level == own_depth {
+                break;
+            }
+            let key: PathBuf = components[..level].iter().collect();
+            let node = self.node_mut(&key);
+            node.size = node.size.saturating_add(size);
+            if is_file {
+                node.file_count += 1;
+            }
+            if is_dir {
+                node.folder_count += 1;
+            }
+            self.dirty.insert(key);
+        }
+    }
+
+    fn emit_dirty(&mut self, app: &AppHandle, op_id: &str) {
+        if self.dirty.is_empty() {
+            return;
+        }
+        let nodes: Vec<SizeNode> = self
+            .dirty
+            .drain()
+            .filter_map(|k| self.nodes.get(&k).cloned())
+            .collect();
+        let _ = app.emit(
+            "fu:size_breakdown_progress",
+            SizeBreakdownProgress {
+                op_id: op_id.to_string(),
+                nodes,
+            },
+        );
+    }
+
+    /// All nodes, biggest first within each depth.
+    fn snapshot_all(&self) -> Vec<SizeNode> {
+        let mut nodes: Vec<SizeNode> = self.nodes.values().cloned().collect();
+        nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then(b.size.cmp(&a.size)));
+        nodes
+    }
+}
+
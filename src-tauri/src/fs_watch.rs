@@ -0,0 +1,256 @@
+// src-tauri/src/fs_watch.rs
+//
+// Filesystem watcher subsystem (notify crate).
+// - watch_path starts an OS-level watcher for a root folder.
+// - Raw notify events are coalesced per root on a small worker thread and
+//   flushed as ONE `fu:fs_changed` event after the folder went quiet for
+//   DEBOUNCE_MS (or at most MAX_BATCH_MS after the first change), so a
+//   bulk copy of 10k files doesn't send 10k events to the UI.
+// - unwatch_path drops the watcher; the worker thread exits once its
+//   channel closes.
+//
+// State lives in Tauri managed state: .manage(FsWatchState::default())
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::path_guard::PathGuard;
+
+/// Quiet period before a batch is flushed.
+const DEBOUNCE_MS: u64 = 300;
+/// Upper bound for one batch while changes keep coming.
+const MAX_BATCH_MS: u64 = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FsChange {
+    kind: ChangeKind,
+    path: String,
+    /// Old path for renames (when the OS reports both sides).
+    from: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct FsChangedEvent {
+    root: String,
+    changes: Vec<FsChange>,
+}
+
+#[derive(Default)]
+pub struct FsWatchState {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+/// Start watching a folder. Returns the normalized root key to use
+/// with unwatch_path (watching the same root twice is a no-op).
+///
+/// Frontend can call:
+///   invoke<string>('watch_path', { path, recursive: true })
+#[tauri::command]
+pub fn watch_path(
+    app: AppHandle,
+    state: State<'_, FsWatchState>,
+    guard: State<'_, PathGuard>,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<String, String> {
+    let root = guard.check(&path)?;
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+    let key = root.to_string_lossy().to_string();
+
+    let mut watchers = state.watchers.lock().unwrap_or_else(|e| e.into_inner());
+    if watchers.contains_key(&key) {
+        return Ok(key);
+    }
+
+    let (tx, rx) = channel::<notify::Event>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        match res {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(err) => tracing::warn!("[FsWatch] Watch error: {err}"),
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    let mode = if recursive.unwrap_or(true) {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(&root, mode)
+        .map_err(|e| format!("Failed to watch {}: {}", key, e))?;
+
+    let root_key = key.clone();
+    thread::spawn(move || debounce_loop(app, root_key, rx));
+
+    watchers.insert(key.clone(), watcher);
+    Ok(key)
+}
+
+/// Stop watching a folder previously passed to watch_path.
+/// Returns false if it was not being watched.
+///
+/// Frontend can call:
+///   invoke<boolean>('unwatch_path', { path })
+#[tauri::command]
+pub fn unwatch_path(state: State<'_, FsWatchState>, path: String) -> Result<bool, String> {
+    let key = std::fs::canonicalize(&path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(path);
+
+    let mut watchers = state.watchers.lock().unwrap_or_else(|e| e.into_inner());
+    // Dropping the watcher closes the channel, which ends debounce_loop.
+    Ok(watchers.remove(&key).is_some())
+}
+
+/// Collect raw events into batches and emit them.
+/// Exits when the watcher (the sending side) is dropped.
+fn debounce_loop(app: AppHandle, root: String, rx: Receiver<notify::Event>) {
+    let mut pending = PendingChanges::default();
+    let mut batch_started: Option<Instant> = None;
+
+    loop {
+        let res = match batch_started {
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(_) => rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)),
+        };
+
+        match res {
+            Ok(event) => {
+                pending.push(event);
+                let started = *batch_started.get_or_insert_with(Instant::now);
+                if started.elapsed() < Duration::from_millis(MAX_BATCH_MS) {
+                    continue;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        batch_started = None;
+        let changes = pending.drain();
+        if changes.is_empty() {
+            continue;
+        }
+        if app
+            .emit(
+                "fu:fs_changed",
+                FsChangedEvent {
+                    root: root.clone(),
+                    changes,
+                },
+            )
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Per-path coalescing of raw notify events, preserving first-seen order.
+#[derive(Default)]
+struct PendingChanges {
+    order: Vec<PathBuf>,
+    changes: HashMap<PathBuf, (ChangeKind, Option<PathBuf>)>,
+}
+
+impl PendingChanges {
+    fn push(&mut self, event: notify::Event) {
+        match event.kind {
+            EventKind::Create(_) => {
+                for p in event.paths {
+                    self.record(p, ChangeKind::Created, None);
+                }
+            }
+            EventKind::Remove(_) => {
+                for p in event.paths {
+                    self.record(p, ChangeKind::Removed, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let mut paths = event.paths.into_iter();
+                let from = paths.next();
+                if let Some(to) = paths.next() {
+                    self.record(to, ChangeKind::Renamed, from);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for p in event.paths {
+                    self.record(p, ChangeKind::Removed, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                for p in event.paths {
+                    self.record(p, ChangeKind::Renamed, None);
+                }
+            }
+            EventKind::Modify(_) | EventKind::Any | EventKind::Other => {
+                for p in event.paths {
+                    self.record(p, ChangeKind::Modified, None);
+                }
+            }
+            // Reads/opens don't change what the UI shows.
+            EventKind::Access(_) => {}
+        }
+    }
+
+    fn record(&mut self, path: PathBuf, kind: ChangeKind, from: Option<PathBuf>) {
+        match self.changes.get(&path).map(|(k, _)| *k) {
+            None => {
+                self.order.push(path.clone());
+                self.changes.insert(path, (kind, from));
+            }
+            // Still "new" from the UI's point of view.
+            Some(ChangeKind::Created) if kind == ChangeKind::Modified => {}
+            // Appeared and vanished inside one batch: nothing to show.
+            Some(ChangeKind::Created) if kind == ChangeKind::Removed => {
+                self.changes.remove(&path);
+            }
+            Some(_) => {
+                self.changes.insert(path, (kind, from));
+            }
+        }
+    }
+
+    fn drain(&mut self) -> Vec<FsChange> {
+        let mut changes = std::mem::take(&mut self.changes);
+        std::mem::take(&mut self.order)
+            .into_iter()
+            .filter_map(|path| {
+                let (kind, from) = changes.remove(&path)?;
+                Some(FsChange {
+                    kind,
+                    path: path_string(&path),
+                    from: from.as_deref().map(path_string),
+                })
+            })
+            .collect()
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
@@ -0,0 +1,332 @@
+// src-tauri/src/gps_backend.rs
+//
+// Backend half of GPS (Global Processing Service, see src/core/GPS.ts).
+// Every long-running command (folder scan, streamed listing, ...) registers
+// itself here under the op_id chosen by the frontend and receives a
+// CancellationToken that the worker checks between units of work.
+//
+// The registry is stored in Tauri managed state:
+//   .manage(OperationRegistry::default())
+// and cloned into background tasks (it is a cheap Arc handle).
+//
+// Commands for a global task manager UI:
+//   - list_operations: snapshot of everything currently registered
+//   - cancel_operation: fire the cancel token of one op
+//   - pause_operation / resume_operation: hold a pausable op in place
+//
+// finish() only removes the registration it is handed the OpToken of: a
+// worker that was replaced under the same op_id (register cancels it)
+// can finish late without dropping its successor from the registry.
+//
+// Pausing is cooperative: ops registered with `register_pausable` get a
+// PauseGate and call `wait_while_paused` at the same points where they
+// check the cancel token, so partial progress stays in the worker.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+use tokio_util::sync::CancellationToken;
+
+/// Kind of backend operation.
+/// Names mirror `ProcessType` in src/core/GPS.ts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperationKind {
+    FolderLoad,
+    FolderScan,
+    SizeBreakdown,
+    DuplicateScan,
+    Copy,
+    Move,
+    ContentSearch,
+    NameSearch,
+    Compress,
+    Extract,
+    Checksum,
+    Compare,
+    SecureDelete,
+    CleanupScan,
+    StorageAdvisor,
+    OrganizeMedia,
+    ConvertImages,
+    IndexBuild,
+    Manifest,
+}
+
+/// Lifecycle state as seen by the registry.
+/// Finished operations are removed, so there is no "done" state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationState {
+    Running,
+    Paused,
+    /// Cancel requested; the worker hasn't emitted its final event yet.
+    Cancelling,
+}
+
+/// Snapshot of one registered operation for the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationInfo {
+    pub op_id: String,
+    pub kind: OperationKind,
+    /// Milliseconds since UNIX_EPOCH.
+    pub started_at: u64,
+    pub state: OperationState,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct OperationStateChanged {
+    op_id: String,
+    state: OperationState,
+}
+
+/// Shared pause flag between the registry and one worker.
+#[derive(Clone, Default)]
+pub struct PauseGate {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl PauseGate {
+    fn set_paused(&self, paused: bool) {
+        let (lock, cvar) = &*self.inner;
+        *lock.lock().unwrap_or_else(|e| e.into_inner()) = paused;
+        cvar.notify_all();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.inner.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Block the (blocking) worker thread while paused.
+    /// Returns early when `token` is cancelled so cancel always wins.
+    pub fn wait_while_paused(&self, token: &CancellationToken) {
+        let (lock, cvar) = &*self.inner;
+        let mut paused = lock.lock().unwrap_or_else(|e| e.into_inner());
+        while *paused && !token.is_cancelled() {
+            paused = cvar
+                .wait_timeout(paused, Duration::from_millis(200))
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+}
+
+/// Cancellation token of one registration (derefs to the
+/// CancellationToken); pass it back to `OperationRegistry::finish`.
+#[derive(Clone)]
+pub struct OpToken {
+    token: CancellationToken,
+    generation: u64,
+}
+
+impl Deref for OpToken {
+    type Target = CancellationToken;
+
+    fn deref(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+struct OperationEntry {
+    kind: OperationKind,
+    token: CancellationToken,
+    generation: u64,
+    started_at: u64,
+    /// None = the worker never checks for pause.
+    pause: Option<PauseGate>,
+}
+
+impl OperationEntry {
+    fn state(&self) -> OperationState {
+        if self.token.is_cancelled() {
+            OperationState::Cancelling
+        } else if self.pause.as_ref().is_some_and(|p| p.is_paused()) {
+            OperationState::Paused
+        } else {
+            OperationState::Running
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Default)]
+pub struct OperationRegistry {
+    inner: Arc<Mutex<HashMap<String, OperationEntry>>>,
+    next_generation: Arc<AtomicU64>,
+}
+
+impl OperationRegistry {
+    /// Register a new operation and return its cancellation token.
+    ///
+    /// If an operation with the same op_id is still registered, it is
+    /// cancelled first so two workers never share one id.
+    pub fn register(&self, op_id: &str, kind: OperationKind) -> OpToken {
+        self.insert(op_id, kind, None)
+    }
+
+    /// Like `register`, but the operation can also be paused/resumed.
+    /// The worker must call `PauseGate::wait_while_paused` regularly.
+    pub fn register_pausable(
+        &self,
+        op_id: &str,
+        kind: OperationKind,
+    ) -> (OpToken, PauseGate) {
+        let pause = PauseGate::default();
+        let token = self.insert(op_id, kind, Some(pause.clone()));
+        (token, pause)
+    }
+
+    fn insert(&self, op_id: &str, kind: OperationKind, pause: Option<PauseGate>) -> OpToken {
+        let token = OpToken {
+            token: CancellationToken::new(),
+            generation: self.next_generation.fetch_add(1, Ordering::Relaxed),
+        };
+        let mut ops = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(old) = ops.insert(
+            op_id.to_string(),
+            OperationEntry {
+                kind,
+                token: token.token.clone(),
+                generation: token.generation,
+                started_at: now_millis(),
+                pause,
+            },
+        ) {
+            old.token.cancel();
+        }
+        token
+    }
+
+    /// Request cancellation of a running operation.
+    /// Returns false if the op_id is unknown (already finished).
+    pub fn cancel(&self, op_id: &str) -> bool {
+        let ops = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match ops.get(op_id) {
+            Some(entry) => {
+                entry.token.cancel();
+                // Wake a paused worker so it sees the cancel right away.
+                if let Some(pause) = &entry.pause {
+                    pause.set_paused(false);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Pause or resume an operation.
+    /// Errors if the op is unknown or doesn't support pausing.
+    pub fn set_paused(&self, op_id: &str, paused: bool) -> Result<OperationState, String> {
+        let ops = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = ops
+            .get(op_id)
+            .ok_or_else(|| format!("Unknown operation: {}", op_id))?;
+        let pause = entry
+            .pause
+            .as_ref()
+            .ok_or_else(|| format!("Operation {} cannot be paused", op_id))?;
+        if !entry.token.is_cancelled() {
+            pause.set_paused(paused);
+        }
+        Ok(entry.state())
+    }
+
+    /// Snapshot of all registered operations, oldest first.
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let ops = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<OperationInfo> = ops
+            .iter()
+            .map(|(op_id, entry)| OperationInfo {
+                op_id: op_id.clone(),
+                kind: entry.kind,
+                started_at: entry.started_at,
+                state: entry.state(),
+            })
+            .collect();
+        list.sort_by_key(|info| info.started_at);
+        list
+    }
+
+    /// Remove a finished operation from the registry, unless `op_id` has
+    /// been registered again since `token` was handed out.
+    /// Call this AFTER the final "completed" event was emitted.
+    pub fn finish(&self, op_id: &str, token: &OpToken) {
+        let mut ops = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if ops.get(op_id).is_some_and(|entry| entry.generation == token.generation) {
+            ops.remove(op_id);
+        }
+    }
+}
+
+/// `op_id` as a plain file name, for state kept per operation on disk
+/// (op ids come from the frontend).
+pub fn op_file_stem(op_id: &str) -> String {
+    op_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// List all running backend operations (scans, copies, downloads, ...).
+///
+/// Frontend can call:
+///   invoke<OperationInfo[]>('list_operations')
+#[tauri::command]
+pub fn list_operations(registry: State<'_, OperationRegistry>) -> Vec<OperationInfo> {
+    registry.list()
+}
+
+/// Cancel any running operation by op_id.
+/// The worker still emits its own "completed" event with status "cancelled".
+/// Returns false if the operation already finished.
+///
+/// Frontend can call:
+///   invoke<boolean>('cancel_operation', { opId })
+#[tauri::command]
+pub fn cancel_operation(registry: State<'_, OperationRegistry>, op_id: String) -> bool {
+    registry.cancel(&op_id)
+}
+
+/// Pause a running operation (folder scan, copy/move).
+/// Emits `fu:operation_state` with the new state.
+///
+/// Frontend can call:
+///   invoke('pause_operation', { opId })
+#[tauri::command]
+pub fn pause_operation(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    op_id: String,
+) -> Result<OperationState, String> {
+    let state = registry.set_paused(&op_id, true)?;
+    let _ = app.emit("fu:operation_state", OperationStateChanged { op_id, state });
+    Ok(state)
+}
+
+/// Resume an operation paused with pause_operation.
+///
+/// Frontend can call:
+///   invoke('resume_operation', { opId })
+#[tauri::command]
+pub fn resume_operation(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    op_id: String,
+) -> Result<OperationState, String> {
+    let state = registry.set_paused(&op_id, false)?;
+    let _ = app.emit("fu:operation_state", OperationStateChanged { op_id, state });
+    Ok(state)
+}
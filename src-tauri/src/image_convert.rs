@@ -0,0 +1,346 @@
+// src-tauri/src/image_convert.rs
+//
+// convert_images: re-encode a batch of images as PNG, JPEG or WebP, e.g. to
+// prepare an upload, optionally scaled down to a maximum width / height.
+//
+// - Decodes everything the thumbnails do (JPEG, PNG, GIF first frame, WebP,
+//   BMP, TIFF). The EXIF orientation is applied, since the output carries
+//   no EXIF.
+// - quality (1..100) applies to JPEG. WebP output is lossless: the image
+//   crate has no lossy WebP encoder.
+// - JPEG has no alpha; transparent areas are flattened like thumbnails.
+// - Output goes next to each source ("photo.heic" -> "photo.jpg") or into
+//   destDir. An existing output is skipped by default (ifExists "skip"),
+//   or replaced ("overwrite") or kept ("keepBoth" -> "photo (1).jpg").
+//   Files are written to a temp name first, so a failed or cancelled
+//   conversion never leaves a broken image.
+// - Files convert in parallel on a rayon pool (`threads`, 0 = one per
+//   core); the cancel token is checked before each file.
+// Events: `fu:convert_images_progress`, then `fu:convert_images_completed`.
+// Every run is recorded in the audit log (audit.rs).
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::{unique_path, FileOpFailure};
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl TargetFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            TargetFormat::Png => "png",
+            TargetFormat::Jpeg => "jpg",
+            TargetFormat::Webp => "webp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExistsPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    KeepBoth,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConvertOptions {
+    /// JPEG quality 1..100 (default 85).
+    pub quality: Option<u8>,
+    /// Longest side in pixels; larger images are scaled down, smaller ones
+    /// are never enlarged.
+    pub max_dimension: Option<u32>,
+    /// Output folder; default: next to each source.
+    pub dest_dir: Option<String>,
+    pub if_exists: ExistsPolicy,
+    /// Worker threads; 0 = one per CPU core.
+    pub threads: usize,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertedImage {
+    pub source: String,
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ConvertProgress {
+    op_id: String,
+    done: u64,
+    total: u64,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ConvertCompleted {
+    op_id: String,
+    status: String, // "ok" | "partial" | "cancelled" | "error"
+    converted: Vec<ConvertedImage>,
+    /// Sources whose output already existed (ifExists "skip").
+    skipped: Vec<String>,
+    failed: Vec<FileOpFailure>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+enum Outcome {
+    Converted(ConvertedImage),
+    Skipped(String),
+    Failed(FileOpFailure),
+}
+
+/// Command from TS:
+/// invoke("convert_images", { opId, paths, targetFormat: "jpeg", options: { quality: 85, maxDimension: 2048, destDir: null, ifExists: "skip", threads: 0 } })
+#[tauri::command]
+pub async fn convert_images(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    paths: Vec<String>,
+    target_format: TargetFormat,
+    options: Option<ConvertOptions>,
+) -> Result<(), AppError> {
+    let paths: Vec<String> = guard
+        .check_all(&paths)?
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    let mut options = options.unwrap_or_default();
+    if let Some(dir) = &mut options.dest_dir {
+        *dir = guard.check(dir)?.to_string_lossy().to_string();
+    }
+    if options.quality.is_some_and(|q| !(1..=100).contains(&q)) {
+        return Err(AppError::invalid_input("quality must be between 1 and 100"));
+    }
+    if options.max_dimension == Some(0) {
+        return Err(AppError::invalid_input("maxDimension must be greater than 0"));
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .thread_name(|i| format!("convert-images-{i}"))
+        .build()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Failed to start workers: {}", e)))?;
+
+    let token = registry.register(&op_id, OperationKind::ConvertImages);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let done = AtomicU64::new(0);
+        let last_emit = Mutex::new(Instant::now());
+        let total = paths.len() as u64;
+
+        let outcomes: Vec<Option<Outcome>> = pool.install(|| {
+            paths
+                .par_iter()
+                .map(|path| {
+                    if token.is_cancelled() {
+                        return None;
+                    }
+                    let outcome = convert_one(Path::new(path), target_format, &options, &token);
+                    let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    emit_progress(&app, &op_id, &last_emit, done, total, path);
+                    outcome
+                })
+                .collect()
+        });
+
+        let mut converted = Vec::new();
+        let mut skipped = Vec::new();
+        let mut failed = Vec::new();
+        for outcome in outcomes.into_iter().flatten() {
+            match outcome {
+                Outcome::Converted(image) => converted.push(image),
+                Outcome::Skipped(source) => skipped.push(source),
+                Outcome::Failed(failure) => failed.push(failure),
+            }
+        }
+
+        let status = if token.is_cancelled() {
+            "cancelled"
+        } else if failed.is_empty() {
+            "ok"
+        } else if converted.is_empty() && skipped.is_empty() {
+            "error"
+        } else {
+            "partial"
+        };
+        let error = match status {
+            "cancelled" => Some(AppError::cancelled()),
+            "error" => failed.first().map(|f| AppError::new(f.code, f.message.clone()).with_path(&f.path)),
+            _ => None,
+        };
+        let mut entry = AuditEntry::new(AuditAction::ConvertImages)
+            .op_id(&op_id)
+            .sources(&paths)
+            .result(status, error.as_ref().map(|e| e.message.clone()));
+        if let Some(dir) = &options.dest_dir {
+            entry = entry.destination(dir.as_str());
+        }
+        audit::record(&app, entry);
+        let _ = app.emit(
+            "fu:convert_images_completed",
+            ConvertCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                converted,
+                skipped,
+                failed,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+fn emit_progress(app: &AppHandle, op_id: &str, last_emit: &Mutex<Instant>, done: u64, total: u64, current: &str) {
+    // Workers that find the clock taken just skip this update.
+    let Ok(mut last) = last_emit.try_lock() else {
+        return;
+    };
+    if done < total && last.elapsed() < Duration::from_millis(100) {
+        return;
+    }
+    *last = Instant::now();
+    let _ = app.emit(
+        "fu:convert_images_progress",
+        ConvertProgress {
+            op_id: op_id.to_string(),
+            done,
+            total,
+            current_file: current.to_string(),
+        },
+    );
+}
+
+/// None once cancelled (the temp file is removed).
+fn convert_one(
+    src: &Path,
+    format: TargetFormat,
+    options: &ConvertOptions,
+    token: &CancellationToken,
+) -> Option<Outcome> {
+    let fail = |error: AppError| Some(Outcome::Failed(FileOpFailure::new(src.to_string_lossy(), error)));
+
+    let Some(stem) = src.file_stem() else {
+        return fail(AppError::invalid_input("Not a file path"));
+    };
+    let dir = match &options.dest_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => src.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let mut output = dir.join(stem).with_extension(format.extension());
+    if fs::symlink_metadata(&output).is_ok() {
+        match options.if_exists {
+            ExistsPolicy::Skip => return Some(Outcome::Skipped(src.to_string_lossy().to_string())),
+            ExistsPolicy::Overwrite => {}
+            ExistsPolicy::KeepBoth => output = unique_path(&output),
+        }
+    }
+
+    let img = match decode(src) {
+        Ok(img) => img,
+        Err(e) => return fail(e),
+    };
+    let img = match options.max_dimension {
+        Some(max) if img.width() > max || img.height() > max => img.resize(max, max, FilterType::Lanczos3),
+        _ => img,
+    };
+    if token.is_cancelled() {
+        return None;
+    }
+
+    let tmp = output.with_extension(format!("{}.fu-partial", format.extension()));
+    let res = encode(&img, format, options, &tmp).and_then(|_| {
+        fs::rename(&tmp, &output).map_err(|e| AppError::io(&e).with_path(&output))
+    });
+    if let Err(e) = res {
+        let _ = fs::remove_file(&tmp);
+        return fail(e);
+    }
+
+    Some(Outcome::Converted(ConvertedImage {
+        source: src.to_string_lossy().to_string(),
+        bytes: fs::metadata(&output).map(|m| m.len()).unwrap_or(0),
+        output: output.to_string_lossy().to_string(),
+        width: img.width(),
+        height: img.height(),
+    }))
+}
+
+fn decode(src: &Path) -> Result<DynamicImage, AppError> {
+    let reader = ImageReader::open(src)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| AppError::io(&e).with_path(src))?;
+    let not_image = |e: image::ImageError| {
+        AppError::new(ErrorCode::InvalidInput, format!("Cannot decode image: {}", e)).with_path(src)
+    };
+    let mut decoder = reader.into_decoder().map_err(not_image)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder).map_err(not_image)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+fn encode(img: &DynamicImage, format: TargetFormat, options: &ConvertOptions, dest: &Path) -> Result<(), AppError> {
+    let file = File::create(dest).map_err(|e| AppError::io(&e).with_path(dest))?;
+    let mut out = BufWriter::new(file);
+    let res = match format {
+        TargetFormat::Png => img.write_to(&mut out, ImageFormat::Png),
+        TargetFormat::Jpeg => {
+            let quality = options.quality.unwrap_or(DEFAULT_JPEG_QUALITY);
+            DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(JpegEncoder::new_with_quality(&mut out, quality))
+        }
+        // The WebP encoder only takes 8-bit RGB(A).
+        TargetFormat::Webp if img.color().has_alpha() => {
+            DynamicImage::ImageRgba8(img.to_rgba8()).write_with_encoder(WebPEncoder::new_lossless(&mut out))
+        }
+        TargetFormat::Webp => {
+            DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(WebPEncoder::new_lossless(&mut out))
+        }
+    };
+    res.map_err(|e| AppError::new(ErrorCode::Io, format!("Failed to encode image: {}", e)).with_path(dest))?;
+    std::io::Write::flush(&mut out).map_err(|e| AppError::io(&e).with_path(dest))
+}
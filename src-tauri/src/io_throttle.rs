@@ -0,0 +1,83 @@
+// src-tauri/src/io_throttle.rs
+//
+// Global IO throttle: one bytes-per-second budget (settings
+// `system.io_throttle_bytes_per_sec`, 0 = unlimited) shared by every copy /
+// move job, scheduled folder sync and update download, so background
+// transfers together stay under it while the user works.
+//
+// Jobs can also have their own cap (`bandwidthLimit` at job start, see
+// file_ops.rs / transfer_queue.rs); both apply, so the stricter one wins.
+// The budget is a sliding window like the per-job throttle: a window older
+// than 2 s starts over, so idle time is never paid back with a burst.
+// A changed setting applies from the next chunk on.
+//
+// State: app.manage(IoThrottle::new(settings.system_handle())) in setup.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::settings::SharedSystemSettings;
+
+/// Cloneable handle; clones share the budget.
+#[derive(Clone)]
+pub struct IoThrottle {
+    settings: SharedSystemSettings,
+    /// Start and bytes of the current window.
+    window: Arc<Mutex<(Instant, u64)>>,
+}
+
+impl IoThrottle {
+    pub fn new(settings: SharedSystemSettings) -> Self {
+        IoThrottle {
+            settings,
+            window: Arc::new(Mutex::new((Instant::now(), 0))),
+        }
+    }
+
+    fn limit(&self) -> u64 {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .io_throttle_bytes_per_sec
+    }
+
+    /// Count `n` bytes and return how long the caller has to wait before
+    /// its next chunk; None when unlimited or within budget.
+    fn account(&self, n: u64) -> Option<Duration> {
+        let limit = self.limit();
+        if limit == 0 {
+            return None;
+        }
+        let mut window = self.window.lock().unwrap_or_else(|e| e.into_inner());
+        if window.0.elapsed() > Duration::from_secs(2) {
+            *window = (Instant::now(), 0);
+        }
+        window.1 += n;
+        let due = Duration::from_secs_f64(window.1 as f64 / limit as f64);
+        due.checked_sub(window.0.elapsed())
+    }
+
+    /// Blocking: sleep (in 100ms steps, so cancel stays responsive) until
+    /// `n` more bytes fit into the budget.
+    pub fn wait(&self, n: u64, token: &CancellationToken) {
+        let Some(wait) = self.account(n) else {
+            return;
+        };
+        let until = Instant::now() + wait;
+        while !token.is_cancelled() {
+            let Some(left) = until.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            std::thread::sleep(left.min(Duration::from_millis(100)));
+        }
+    }
+
+    /// Async variant for the update downloader.
+    pub async fn wait_async(&self, n: u64) {
+        if let Some(wait) = self.account(n) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
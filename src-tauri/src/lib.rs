@@ -4,28 +4,370 @@
 // You will add the actual implementation in src-tauri/src/update/*.rs
 mod update;
 mod ai_bundle;
+mod gps_backend;
+mod folder_scan;
+mod dedupe;
+mod file_ops;
+mod delete_ops;
+mod search;
+mod fs_watch;
+mod archive;
+mod checksum;
+mod settings;
+mod metrics;
+mod power;
+mod volumes;
+mod file_meta;
+mod scan_snapshot;
+mod scan_report;
+mod listing_export;
+mod media_meta;
+mod media_organize;
+mod image_convert;
+mod search_index;
+mod file_type;
+mod file_filter;
+mod thumbnails;
+mod text_preview;
+mod rename_ops;
+mod entry_ops;
+mod path_guard;
+mod error;
+mod logging;
+mod catalog;
+mod bookmarks;
+mod compare;
+mod manifest;
+mod shred;
+mod clipboard;
+mod shell;
+mod vfs;
+mod secrets;
+mod transfer_queue;
+mod io_throttle;
+mod background_priority;
+mod op_journal;
+mod audit;
+mod safe_mode;
+mod elevation;
+mod network_share;
+mod cleanup;
+mod storage_advisor;
+mod scheduler;
+mod single_instance;
+mod cli;
+mod drag_drop;
+mod shortcuts;
+mod tray;
+mod notifications;
 
-use crate::update::{ApplyResult, DownloadResult, UpdateCheckResult};
-use crate::ai_bundle::{write_latest_bundle, write_debug_bundle};
+use serde::Serialize;
+use tauri::{Emitter, Manager, State};
+
+use crate::update::{
+  start_update_scheduler, ApplyResult, CleanupResult, DownloadResult, EndpointTestResult,
+  NetworkSettings, UpdateChannel, UpdateCheckResult, UpdateScheduleInfo, UpdateScheduler,
+  TrustInfo, UpdateHistoryEntry, VerifyReport,
+};
+use crate::ai_bundle::{
+  bundle_paths, get_bundle_dir, list_bundles, read_bundle, write_debug_bundle, write_latest_bundle,
+  write_latest_bundle_json,
+};
+use crate::folder_scan::{start_folder_scan, start_incremental_scan, start_size_breakdown};
+use crate::scan_report::export_scan_report;
+use crate::listing_export::export_dir_listing;
+use crate::media_meta::read_media_metadata;
+use crate::media_organize::{organize_media, undo_organize_media};
+use crate::image_convert::convert_images;
+use crate::file_filter::{FileFilter, FileFilterSpec, FilterEntry};
+use crate::search_index::{
+  add_index_root, index_status, rebuild_index, remove_index_root, search_index, start_search_index,
+};
+use crate::dedupe::start_duplicate_scan;
+use crate::file_ops::{resolve_conflict, start_copy_operation, start_move_operation};
+use crate::gps_backend::{
+  cancel_operation, list_operations, pause_operation, resume_operation, OperationKind,
+  OperationRegistry,
+};
+use crate::delete_ops::{delete_entries, list_trash, restore_from_trash};
+use crate::search::{start_content_search, start_name_search};
+use crate::fs_watch::{unwatch_path, watch_path, FsWatchState};
+use crate::archive::{create_archive, extract_archive};
+use crate::checksum::compute_checksum;
+use crate::settings::{
+  get_settings, load_settings, reset_settings, save_settings, update_settings, SettingsState,
+};
+use crate::metrics::{
+  get_disk_free_space, pause_metrics, resume_metrics, start_metrics_loop, stop_metrics,
+  MetricsControl,
+};
+use crate::power::get_power_state;
+use crate::volumes::{list_volumes, start_volume_watcher};
+use crate::file_meta::{
+  extended_metadata, get_file_attributes, list_alternate_streams, set_file_attributes,
+  ExtendedMetadata,
+};
+use crate::file_type::detect_file_type;
+use crate::thumbnails::get_thumbnail;
+use crate::text_preview::read_text_preview;
+use crate::rename_ops::batch_rename;
+use crate::entry_ops::{create_directory, create_empty_file, rename_entry};
+use crate::path_guard::{get_allowed_roots, set_allowed_roots, PathGuard};
+use crate::error::{AppError, ErrorCode};
+use crate::logging::{get_recent_logs, init_logging};
+use crate::catalog::{get_tags, query_by_tag, set_tags, Catalog};
+use crate::bookmarks::{
+  add_bookmark, list_bookmarks, remove_bookmark, reorder_bookmarks, Bookmarks,
+};
+use crate::compare::compare_directories;
+use crate::manifest::{create_manifest, verify_manifest};
+use crate::shred::secure_delete;
+use crate::clipboard::{
+  clipboard_copy_paths, clipboard_cut_paths, clipboard_paste_into, clipboard_read_paths,
+};
+use crate::shell::{
+  get_shell_context_actions, invoke_shell_action, open_path, open_with, reveal_in_file_manager,
+};
+use crate::vfs::{
+  cancel_remote, connect_mtp, connect_s3, connect_sftp, connect_webdav, disconnect_remote, list_mtp_devices, Vfs,
+  VfsEntry, VfsPath,
+};
+use crate::secrets::{delete_secret, get_secret, store_secret};
+use crate::transfer_queue::{
+  cancel_transfer, enqueue_transfer, get_transfer_queue, move_transfer, pause_transfer, resume_transfer,
+  set_transfer_bandwidth, TransferQueue,
+};
+use crate::io_throttle::IoThrottle;
+use crate::audit::{export_audit_log, get_audit_log};
+use crate::elevation::run_elevated;
+use crate::network_share::connect_network_share;
+use crate::op_journal::{
+  discard_interrupted_operations, list_interrupted_operations, resume_interrupted_operations,
+};
+use crate::cleanup::{apply_cleanup, start_cleanup_scan};
+use crate::storage_advisor::start_storage_advisor;
+use crate::scheduler::{
+  create_schedule, delete_schedule, get_schedule_history, list_schedules, start_scheduler,
+};
+use crate::single_instance::Acquired;
+use crate::cli::{get_launch_path, Cli};
+use crate::drag_drop::{set_drop_target, DropTarget};
+use crate::shortcuts::{register_shortcut, start_shortcuts, unregister_shortcut};
 
 /// Entry point for the Tauri application.
 /// - Registers all Tauri commands (hello, debug bundle, folder listing, TUF updates).
+/// - Rejects file-changing commands while safe mode is on (safe_mode.rs).
+/// - Manages the backend OperationRegistry used by long-running commands.
+/// - Loads persisted settings and starts the status bar metrics loop.
+/// - Handles command-line arguments; --scan / --check-updates run without UI (cli.rs).
+/// - Forwards to an already running instance and exits (single_instance.rs).
+/// - Adds the tray icon; in background mode the process outlives its window (tray.rs).
 /// - For mobile builds, uses the mobile entry point attribute.
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+  let context = tauri::generate_context!();
+  let args: Vec<String> = std::env::args().skip(1).collect();
+  let launch = match cli::parse(&args) {
+    Ok(Cli::Gui(launch)) => launch,
+    Ok(command) => std::process::exit(cli::run_headless(command, context)),
+    Err(message) => {
+      eprintln!("{}\n\n{}", message, cli::USAGE);
+      std::process::exit(2);
+    }
+  };
+
+  let instance = single_instance::acquire();
+  if matches!(instance, Ok(Acquired::Forwarded)) {
+    return;
+  }
+
   tauri::Builder::default()
-    .invoke_handler(tauri::generate_handler![
+    .manage(OperationRegistry::default())
+    .manage(FsWatchState::default())
+    .manage(Vfs::default())
+    .manage(launch)
+    .manage(DropTarget::default())
+    .on_window_event(|window, event| {
+      drag_drop::on_window_event(window, event);
+      tray::on_window_event(window, event);
+    })
+    .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+    .plugin(tauri_plugin_notification::init())
+    .setup(|app| {
+      init_logging(app.handle());
+      match instance {
+        Ok(Acquired::Primary(listener)) => single_instance::start_listener(app.handle().clone(), listener),
+        Ok(Acquired::Forwarded) => {}
+        Err(e) => tracing::warn!("[SingleInstance] Running without single-instance support: {e}"),
+      }
+      // Settings are loaded once; commands keep the in-memory copy in sync.
+      let settings = SettingsState::new(load_settings(app.handle()));
+      let metrics = MetricsControl::default();
+      start_metrics_loop(app.handle().clone(), settings.system_handle(), metrics.clone());
+      app.manage(TransferQueue::new(settings.system_handle()));
+      app.manage(IoThrottle::new(settings.system_handle()));
+      app.manage(settings);
+      start_shortcuts(app.handle());
+      notifications::start_notifications(app.handle());
+      app.manage(metrics);
+      app.manage(PathGuard::load(app.handle()));
+      app.manage(Catalog::load(app.handle()));
+      start_search_index(app.handle());
+      app.manage(Bookmarks::load(app.handle()));
+      app.manage(start_update_scheduler(app.handle().clone()));
+      app.manage(start_scheduler(app.handle().clone()));
+      update::complete_handoff(app.handle());
+      update::start_integrity_check(app.handle().clone());
+      start_volume_watcher(app.handle().clone());
+      if let Err(e) = tray::create_tray(app.handle()) {
+        tracing::warn!("[Tray] Failed to create tray icon: {e}");
+      }
+      Ok(())
+    })
+    .invoke_handler(safe_mode::middleware(tauri::generate_handler![
       hello,
       read_debug_bundle,
       list_dir,
+      list_dir_stream,
+      list_operations,
+      cancel_operation,
+      pause_operation,
+      resume_operation,
+      start_folder_scan,
+      start_incremental_scan,
+      start_size_breakdown,
+      export_scan_report,
+      export_dir_listing,
+      read_media_metadata,
+      organize_media,
+      undo_organize_media,
+      convert_images,
+      add_index_root,
+      remove_index_root,
+      rebuild_index,
+      index_status,
+      search_index,
+      start_duplicate_scan,
+      start_copy_operation,
+      start_move_operation,
+      list_interrupted_operations,
+      resume_interrupted_operations,
+      discard_interrupted_operations,
+      get_audit_log,
+      export_audit_log,
+      run_elevated,
+      connect_network_share,
+      resolve_conflict,
       tuf_check_for_updates,
       tuf_download_update,
       tuf_apply_update,
+      tuf_rollback_update,
+      cleanup_old_versions,
+      get_update_channel,
+      set_update_channel,
       write_latest_bundle,
-      write_debug_bundle
-    ])
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+      write_debug_bundle,
+      write_latest_bundle_json,
+      list_bundles,
+      read_bundle,
+      get_bundle_dir,
+      delete_entries,
+      list_trash,
+      restore_from_trash,
+      start_content_search,
+      start_name_search,
+      watch_path,
+      unwatch_path,
+      create_archive,
+      extract_archive,
+      compute_checksum,
+      get_settings,
+      save_settings,
+      update_settings,
+      reset_settings,
+      get_disk_free_space,
+      pause_metrics,
+      resume_metrics,
+      stop_metrics,
+      get_power_state,
+      list_volumes,
+      detect_file_type,
+      get_thumbnail,
+      read_text_preview,
+      batch_rename,
+      get_file_attributes,
+      set_file_attributes,
+      list_alternate_streams,
+      create_directory,
+      create_empty_file,
+      rename_entry,
+      get_allowed_roots,
+      set_allowed_roots,
+      get_update_schedule,
+      set_update_schedule,
+      trigger_update_check,
+      get_update_network,
+      set_update_network,
+      test_update_endpoints,
+      verify_version,
+      get_update_history,
+      set_update_repository,
+      reset_update_repository,
+      get_trust_info,
+      restart_into_version,
+      get_recent_logs,
+      set_tags,
+      get_tags,
+      query_by_tag,
+      list_bookmarks,
+      add_bookmark,
+      remove_bookmark,
+      reorder_bookmarks,
+      compare_directories,
+      create_manifest,
+      verify_manifest,
+      secure_delete,
+      clipboard_copy_paths,
+      clipboard_cut_paths,
+      clipboard_read_paths,
+      clipboard_paste_into,
+      open_path,
+      open_with,
+      reveal_in_file_manager,
+      get_shell_context_actions,
+      invoke_shell_action,
+      connect_sftp,
+      cancel_remote,
+      disconnect_remote,
+      connect_webdav,
+      connect_s3,
+      list_mtp_devices,
+      connect_mtp,
+      store_secret,
+      get_secret,
+      delete_secret,
+      enqueue_transfer,
+      get_transfer_queue,
+      move_transfer,
+      pause_transfer,
+      resume_transfer,
+      cancel_transfer,
+      set_transfer_bandwidth,
+      start_cleanup_scan,
+      apply_cleanup,
+      start_storage_advisor,
+      create_schedule,
+      list_schedules,
+      delete_schedule,
+      get_schedule_history,
+      get_launch_path,
+      set_drop_target,
+      register_shortcut,
+      unregister_shortcut
+    ]))
+    .build(context)
+    .expect("error while running tauri application")
+    .run(tray::on_run_event);
 }
 
 /// Simple test command to verify that the backend is alive.
@@ -38,15 +380,16 @@ fn hello(name: &str) -> String {
 
 /// Read the latest debug bundle that ASC wrote.
 ///
-/// The bundle path is fixed:
-///   .ai/bundles/latest.bundle.md
+/// The bundle path comes from ai_bundle::bundle_paths():
+///   dev:     <repo>/.ai/bundles/latest.bundle.md
+///   release: <app data dir>/ai/bundles/latest.bundle.md
 ///
 /// This is the single canonical source of truth for the AI agent.
 /// Frontend can call:
 ///   invoke<string>('read_debug_bundle')
 #[tauri::command]
-fn read_debug_bundle() -> Result<String, String> {
-  let bundle_path = std::path::Path::new(".ai/bundles/latest.bundle.md");
+fn read_debug_bundle(app: tauri::AppHandle) -> Result<String, String> {
+  let bundle_path = bundle_paths(&app)?.latest_md;
   std::fs::read_to_string(bundle_path)
     .map_err(|e| format!("Failed to read bundle: {}", e))
 }
@@ -56,90 +399,377 @@ fn read_debug_bundle() -> Result<String, String> {
 /// - `is_dir`: true if this entry is a directory
 /// - `size`: file size in bytes (0 for directories)
 /// - `modified`: last modified timestamp (seconds since UNIX_EPOCH as string)
-#[derive(serde::Serialize)]
+/// - extended fields (created, accessed, flags, owner, ...) only when the
+///   command was called with `extended: true`, see file_meta.rs
+#[derive(Serialize, Clone)]
 pub struct FileEntry {
   name: String,
   is_dir: bool,
   size: u64,
   modified: String,
+  #[serde(flatten, skip_serializing_if = "Option::is_none")]
+  extended: Option<ExtendedMetadata>,
 }
 
-/// List directory contents for a given filesystem path.
+/// List directory contents for a given filesystem path, local or remote
+/// ("sftp://<id>/dir", see vfs/mod.rs).
 ///
 /// Safety rules:
 /// - Validates that path exists and is a directory.
 /// - Returns a simple, serializable structure (FileEntry).
 /// - Sorts directories first, then files, both alphabetically by name.
+/// - A bare UNC server ("\\nas") lists its shares (network_share.rs).
+///
+/// Optional, applied here so huge folders aren't re-sorted in JS:
+/// - `sort_by`: "name" (default) | "size" | "modified" | "type"
+/// - `sort_order`: "asc" (default) | "desc"; directories stay first
+/// - `name_filter`: case-insensitive glob on the name, e.g. "*.jpg"
+/// - `show_hidden`: false drops dot-files / Windows hidden entries (default true)
+/// - `filter`: size / date / extension / attribute / glob filter shared with
+///   search, scans and compare (file_filter.rs)
 ///
 /// Frontend can call:
 ///   invoke<FileEntry[]>('list_dir', { path: 'C:\\' })
+///   invoke<FileEntry[]>('list_dir', { path, extended: true })
+///   invoke<FileEntry[]>('list_dir', { path, sortBy: 'size', sortOrder: 'desc', nameFilter: '*.png', showHidden: false })
+///   invoke<FileEntry[]>('list_dir', { path, filter: { minSize: 1048576, extensions: ['mp4', 'mkv'] } })
 #[tauri::command]
-fn list_dir(path: String) -> Result<Vec<FileEntry>, String> {
-  let dir_path = std::path::Path::new(&path);
-  
-  if !dir_path.exists() {
-    return Err(format!("Path does not exist: {}", path));
-  }
-  
-  if !dir_path.is_dir() {
-    return Err(format!("Path is not a directory: {}", path));
+#[allow(clippy::too_many_arguments)]
+async fn list_dir(
+  guard: State<'_, PathGuard>,
+  vfs: State<'_, Vfs>,
+  path: String,
+  extended: Option<bool>,
+  sort_by: Option<String>,
+  sort_order: Option<String>,
+  name_filter: Option<String>,
+  show_hidden: Option<bool>,
+  filter: Option<FileFilterSpec>,
+) -> Result<Vec<FileEntry>, AppError> {
+  // A bare "\\server" lists the server's shares.
+  if let Some(server) = network_share::unc_server(&path) {
+    guard.check_server(server)?;
+    let server = server.to_string();
+    let shares = tokio::task::spawn_blocking(move || network_share::list_shares(&server))
+      .await
+      .map_err(|e| AppError::new(ErrorCode::Internal, format!("Listing task failed: {}", e)))??;
+    return Ok(
+      shares
+        .into_iter()
+        .map(|name| FileEntry { name, is_dir: true, size: 0, modified: "0".to_string(), extended: None })
+        .collect(),
+    );
   }
 
-  let mut entries = Vec::new();
-
-  match std::fs::read_dir(dir_path) {
-    Ok(entries_iter) => {
-      for entry in entries_iter {
-        if let Ok(entry) = entry {
-          let metadata = entry.metadata();
-          if let Ok(meta) = metadata {
-            let name = entry
-              .file_name()
-              .into_string()
-              .unwrap_or_else(|_| "?".to_string());
-            
-            let modified = meta
-              .modified()
-              .ok()
-              .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-              .map(|d| d.as_secs().to_string())
-              .unwrap_or_else(|| "0".to_string());
-
-            entries.push(FileEntry {
-              name,
-              is_dir: meta.is_dir(),
-              size: meta.len(),
-              modified,
-            });
-          }
-        }
-      }
-    }
-    Err(e) => {
-      return Err(format!("Failed to read directory: {}", e));
-    }
-  }
+  let dir = vfs.resolve(&guard, &path)?;
+  let filter = FileFilter::from_spec(filter.as_ref())?;
+
+  let sort_key = match sort_by.as_deref().unwrap_or("name") {
+    "name" => SortKey::Name,
+    "size" => SortKey::Size,
+    "modified" => SortKey::Modified,
+    "type" => SortKey::Type,
+    other => return Err(AppError::invalid_input(format!("Unknown sort key: {}", other))),
+  };
+  let descending = match sort_order.as_deref().unwrap_or("asc") {
+    "asc" => false,
+    "desc" => true,
+    other => return Err(AppError::invalid_input(format!("Unknown sort order: {}", other))),
+  };
+  let name_matcher = match name_filter.as_deref().map(str::trim) {
+    Some(pattern) if !pattern.is_empty() => Some(
+      globset::GlobBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| AppError::invalid_input(format!("Invalid name filter '{}': {}", pattern, e)))?
+        .compile_matcher(),
+    ),
+    _ => None,
+  };
+  let show_hidden = show_hidden.unwrap_or(true);
+
+  let extended = extended.unwrap_or(false);
+  // Remote listings are network round-trips: keep them off the async runtime.
+  let mut entries: Vec<FileEntry> = tokio::task::spawn_blocking(move || {
+    check_listable(&dir)?;
+    Ok::<_, AppError>(
+      dir
+        .provider
+        .list(&dir.path)?
+        .flatten()
+        .filter(|entry| show_hidden || !entry.hidden)
+        .filter(|entry| name_matcher.as_ref().is_none_or(|m| m.is_match(&entry.name)))
+        .filter(|entry| filter.as_ref().is_none_or(|f| entry_passes(&dir, entry, f)))
+        .map(|entry| file_entry_from(&dir, entry, extended))
+        .collect(),
+    )
+  })
+  .await
+  .map_err(|e| AppError::new(ErrorCode::Internal, format!("Listing task failed: {}", e)))??;
 
-  // Directories first, then files, within each group sort by name.
+  // Directories first, then files; within each group by the chosen key.
   entries.sort_by(|a, b| {
     match (a.is_dir, b.is_dir) {
       (true, false) => std::cmp::Ordering::Less,
       (false, true) => std::cmp::Ordering::Greater,
-      _ => a.name.cmp(&b.name),
+      _ => {
+        let ord = compare_entries(a, b, sort_key);
+        if descending { ord.reverse() } else { ord }
+      }
     }
   });
 
   Ok(entries)
 }
 
+#[derive(Clone, Copy)]
+enum SortKey {
+  Name,
+  Size,
+  Modified,
+  Type,
+}
+
+/// Compare two entries of the same kind (both dirs or both files).
+/// Ties fall back to the name so the order is stable across calls.
+fn compare_entries(a: &FileEntry, b: &FileEntry, key: SortKey) -> std::cmp::Ordering {
+  let by_key = match key {
+    SortKey::Name => std::cmp::Ordering::Equal,
+    SortKey::Size => a.size.cmp(&b.size),
+    // `modified` is epoch seconds as a string; compare numerically.
+    SortKey::Modified => {
+      let secs = |e: &FileEntry| e.modified.parse::<u64>().unwrap_or(0);
+      secs(a).cmp(&secs(b))
+    }
+    SortKey::Type => {
+      let ext = |e: &FileEntry| {
+        std::path::Path::new(&e.name)
+          .extension()
+          .map(|x| x.to_string_lossy().to_lowercase())
+          .unwrap_or_default()
+      };
+      ext(a).cmp(&ext(b))
+    }
+  };
+  by_key.then_with(|| a.name.cmp(&b.name))
+}
+
+/// The path must exist and be a directory (NOT_FOUND / NOT_A_DIRECTORY).
+fn check_listable(dir: &VfsPath) -> Result<(), AppError> {
+  let stat = dir.provider.stat(&dir.path).map_err(|e| {
+    if e.code == ErrorCode::NotFound {
+      AppError::new(ErrorCode::NotFound, format!("Path does not exist: {}", dir.path)).with_path(&dir.path)
+    } else {
+      e
+    }
+  })?;
+  // A symlink to a folder is listable too.
+  let is_dir = stat.is_dir
+    || (stat.is_symlink && dir.provider.local_path(&dir.path).is_some_and(|p| p.is_dir()));
+  if !is_dir {
+    return Err(
+      AppError::new(ErrorCode::NotADirectory, format!("Path is not a directory: {}", dir.path))
+        .with_path(&dir.path),
+    );
+  }
+  Ok(())
+}
+
+/// Listing side of the shared filter. Provider entries carry no readonly
+/// flag, so local ones are stat'ed when the filter asks for it.
+fn entry_passes(dir: &VfsPath, entry: &VfsEntry, filter: &FileFilter) -> bool {
+  let rel = std::path::Path::new(&entry.name);
+  let mut candidate = FilterEntry::from_vfs(rel, entry);
+  if filter.needs_readonly() {
+    candidate.readonly = dir
+      .provider
+      .local_path(&entry.path)
+      .and_then(|p| std::fs::symlink_metadata(p).ok())
+      .map(|m| m.permissions().readonly());
+  }
+  filter.matches(&candidate)
+}
+
+/// Build a FileEntry from a provider entry. Extended metadata is only
+/// available for local entries.
+fn file_entry_from(dir: &VfsPath, entry: VfsEntry, extended: bool) -> FileEntry {
+  let extended = extended
+    .then(|| dir.provider.local_path(&entry.path))
+    .flatten()
+    .and_then(|path| {
+      let meta = std::fs::symlink_metadata(&path).ok()?;
+      Some(extended_metadata(&path, &entry.name, &meta))
+    });
+
+  FileEntry {
+    name: entry.name,
+    is_dir: entry.is_dir,
+    size: entry.size,
+    modified: entry.modified.to_string(),
+    extended,
+  }
+}
+
+/// Default number of entries per `fu:dir_chunk` event.
+const DIR_CHUNK_SIZE: usize = 500;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DirChunk {
+  op_id: String,
+  chunk_index: u64,
+  entries: Vec<FileEntry>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DirListingCompleted {
+  op_id: String,
+  status: String, // "ok" | "cancelled" | "error"
+  total_entries: u64,
+  skipped_entries: u64,
+  error_message: Option<String>,
+  /// Structured form of the failure (also set for "cancelled").
+  #[serde(skip_serializing_if = "Option::is_none")]
+  error: Option<AppError>,
+}
+
+/// Streaming variant of `list_dir` for huge folders (100k+ entries).
+///
+/// - Registers `op_id` in OperationRegistry (kind: folder-load), so the
+///   listing can be cancelled like any other GPS operation.
+/// - Emits `fu:dir_chunk` events with up to `chunk_size` entries (default 500).
+/// - Emits a final `fu:dir_completed` event with totals and status.
+/// - Entries arrive in OS order; sorting is up to the frontend, since the
+///   whole point is to not hold the full listing before showing anything.
+///
+/// Frontend can call:
+///   invoke('list_dir_stream', { opId, path, chunkSize: 500, extended: false, filter: null })
+///
+/// Entries dropped by `filter` are not counted in `skippedEntries`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn list_dir_stream(
+  app: tauri::AppHandle,
+  registry: State<'_, OperationRegistry>,
+  guard: State<'_, PathGuard>,
+  vfs: State<'_, Vfs>,
+  op_id: String,
+  path: String,
+  chunk_size: Option<usize>,
+  extended: Option<bool>,
+  filter: Option<FileFilterSpec>,
+) -> Result<(), AppError> {
+  let dir = vfs.resolve(&guard, &path)?;
+  let filter = FileFilter::from_spec(filter.as_ref())?;
+  {
+    let dir = dir.clone();
+    tokio::task::spawn_blocking(move || check_listable(&dir))
+      .await
+      .map_err(|e| AppError::new(ErrorCode::Internal, format!("Listing task failed: {}", e)))??;
+  }
+
+  let chunk_size = chunk_size.unwrap_or(DIR_CHUNK_SIZE).max(1);
+  let extended = extended.unwrap_or(false);
+  let token = registry.register(&op_id, OperationKind::FolderLoad);
+  let registry = registry.inner().clone();
+
+  tokio::task::spawn_blocking(move || {
+    let mut total: u64 = 0;
+    let mut skipped: u64 = 0;
+    let mut chunk_index: u64 = 0;
+    let mut batch: Vec<FileEntry> = Vec::with_capacity(chunk_size);
+
+    let (status, error) = match dir.provider.list(&dir.path) {
+      Ok(entries_iter) => {
+        let mut status = "ok";
+        for entry in entries_iter {
+          if token.is_cancelled() {
+            status = "cancelled";
+            break;
+          }
+
+          let entry = match entry {
+            Ok(e) if filter.as_ref().is_some_and(|f| !entry_passes(&dir, &e, f)) => continue,
+            other => other,
+          };
+          match entry.ok().map(|e| file_entry_from(&dir, e, extended)) {
+            Some(file_entry) => {
+              batch.push(file_entry);
+              total += 1;
+            }
+            None => skipped += 1,
+          }
+
+          if batch.len() >= chunk_size {
+            let _ = app.emit(
+              "fu:dir_chunk",
+              DirChunk {
+                op_id: op_id.clone(),
+                chunk_index,
+                entries: std::mem::take(&mut batch),
+              },
+            );
+            chunk_index += 1;
+          }
+        }
+        let error = (status == "cancelled").then(AppError::cancelled);
+        (status, error)
+      }
+      Err(e) => ("error", Some(e)),
+    };
+
+    // Flush the tail (also on cancel: the UI keeps what it already got).
+    if !batch.is_empty() {
+      let _ = app.emit(
+        "fu:dir_chunk",
+        DirChunk {
+          op_id: op_id.clone(),
+          chunk_index,
+          entries: batch,
+        },
+      );
+    }
+
+    let _ = app.emit(
+      "fu:dir_completed",
+      DirListingCompleted {
+        op_id: op_id.clone(),
+        status: status.to_string(),
+        total_entries: total,
+        skipped_entries: skipped,
+        error_message: error
+          .as_ref()
+          .filter(|e| e.code != ErrorCode::Cancelled)
+          .map(|e| e.message.clone()),
+        error,
+      },
+    );
+
+    registry.finish(&op_id, &token);
+  });
+
+  Ok(())
+}
+
+/// Parse an optional channel argument coming from the frontend.
+fn parse_channel(channel: Option<String>) -> Result<Option<UpdateChannel>, AppError> {
+  channel
+    .map(|c| c.parse::<UpdateChannel>())
+    .transpose()
+    .map_err(|e| AppError::invalid_input(e.to_string()))
+}
+
 /// TUF: check if a newer signed update is available.
 ///
 /// - `current_version`: the version currently running (e.g. "0.0.1").
 /// - `platform_id`: platform string used in TUF targets
 ///   (e.g. "desktop-windows-x86_64", "desktop-macos-aarch64").
+/// - `channel`: optional "stable" | "beta" | "nightly" override;
+///   defaults to the persisted channel (see set_update_channel).
 ///
 /// Returns:
-///   { current_version, latest_version, update_available }
+///   { current_version, latest_version, update_available, channel }
 ///
 /// Frontend ASC flow should call this via a Task and Contract.
 #[tauri::command]
@@ -147,10 +777,12 @@ async fn tuf_check_for_updates(
   app: tauri::AppHandle,
   current_version: String,
   platform_id: String,
-) -> Result<UpdateCheckResult, String> {
-  update::check_for_updates(&app, current_version, platform_id)
+  channel: Option<String>,
+) -> Result<UpdateCheckResult, AppError> {
+  let channel = parse_channel(channel)?;
+  update::check_for_updates(&app, current_version, platform_id, channel)
     .await
-    .map_err(|e| e.to_string())
+    .map_err(AppError::update)
 }
 
 /// TUF: download and verify the latest signed update bundle.
@@ -159,6 +791,8 @@ async fn tuf_check_for_updates(
 /// - Verifies signatures, metadata freshness, and target hashes.
 /// - Saves the ZIP bundle into the local targets cache.
 ///
+/// - `channel`: optional override, same as in tuf_check_for_updates.
+///
 /// Returns:
 ///   { version, bundle_path }
 ///
@@ -167,10 +801,14 @@ async fn tuf_check_for_updates(
 async fn tuf_download_update(
   app: tauri::AppHandle,
   platform_id: String,
-) -> Result<DownloadResult, String> {
-  update::download_update_bundle(&app, platform_id)
+  channel: Option<String>,
+) -> Result<DownloadResult, AppError> {
+  let channel = parse_channel(channel)?;
+  let result = update::download_update_bundle(&app, platform_id, channel)
     .await
-    .map_err(|e| e.to_string())
+    .map_err(AppError::update)?;
+  notifications::notify_update_downloaded(&app, &result.version);
+  Ok(result)
 }
 
 /// Apply a previously downloaded update bundle.
@@ -182,13 +820,274 @@ async fn tuf_download_update(
 /// Frontend ASC flow can:
 ///   1) call tuf_download_update()
 ///   2) call tuf_apply_update()
-///   3) optionally ask user to restart or let a launcher handle it.
+///   3) ask the user, then call restart_into_version(version).
 #[tauri::command]
 fn tuf_apply_update(
   app: tauri::AppHandle,
   bundle_path: String,
   new_version: String,
-) -> Result<ApplyResult, String> {
+) -> Result<ApplyResult, AppError> {
   update::apply_staged_update(&app, bundle_path, new_version)
-    .map_err(|e| e.to_string())
+    .map_err(AppError::update)
+}
+
+/// Roll back to the previously applied version.
+///
+/// - Validates that versions/<previous>/ still exists.
+/// - Swaps current/previous in version_state.json.
+/// - Does NOT restart the app (same as tuf_apply_update).
+///
+/// Returns:
+///   { from_version, to_version }
+///
+/// Frontend (or a launcher) can call:
+///   invoke<ApplyResult>('tuf_rollback_update')
+#[tauri::command]
+fn tuf_rollback_update(app: tauri::AppHandle) -> Result<ApplyResult, AppError> {
+  update::rollback_update(&app).map_err(AppError::update)
+}
+
+/// Past update checks / downloads / applies / rollbacks, newest first,
+/// with outcome and error text (for support diagnostics).
+///
+/// Returns:
+///   [{ timestamp, kind, outcome, from_version?, to_version?, channel?, error? }]
+///
+/// Frontend can call:
+///   invoke<UpdateHistoryEntry[]>('get_update_history', { limit: 50 })
+#[tauri::command]
+fn get_update_history(
+  app: tauri::AppHandle,
+  limit: Option<usize>,
+) -> Result<Vec<UpdateHistoryEntry>, AppError> {
+  update::load_update_history(&app, limit).map_err(AppError::update)
+}
+
+/// Switch to an installed version now: starts versions/<version>/ (making
+/// it current), waits for it to confirm startup, then exits this process.
+/// Typical flow: tuf_download_update -> tuf_apply_update -> restart_into_version.
+/// If the new version fails to start, this app keeps running and the
+/// error is returned.
+///
+/// Frontend can call:
+///   await invoke('restart_into_version', { version: '0.2.3' })
+#[tauri::command]
+async fn restart_into_version(app: tauri::AppHandle, version: String) -> Result<(), AppError> {
+  update::spawn_version(&app, &version)
+    .await
+    .map_err(AppError::update)?;
+  app.exit(0);
+  Ok(())
+}
+
+/// Re-check an installed version folder against its manifest.json
+/// (sizes + BLAKE3 hashes), e.g. before rolling back to it or when a
+/// version crashes on start.
+///
+/// Returns:
+///   { version, manifest_found, ok, checked_files, missing, corrupted, unexpected }
+///
+/// Frontend can call:
+///   invoke<VerifyReport>('verify_version', { version: '0.2.3' })
+#[tauri::command]
+async fn verify_version(
+  app: tauri::AppHandle,
+  version: String,
+) -> Result<VerifyReport, AppError> {
+  tauri::async_runtime::spawn_blocking(move || update::verify_installed_version(&app, &version))
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Verify task failed: {}", e)))?
+    .map_err(AppError::update)
+}
+
+/// Free disk space used by old versions and cached update bundles.
+///
+/// Keeps current + previous + `keep_newest` newest installed versions
+/// (default 2) and removes everything else, including cached ZIPs.
+///
+/// Returns:
+///   { kept_versions, removed_versions, removed_bundles, bytes_freed }
+///
+/// Frontend can call:
+///   invoke<CleanupResult>('cleanup_old_versions', { keepNewest: 2 })
+#[tauri::command]
+async fn cleanup_old_versions(
+  app: tauri::AppHandle,
+  keep_newest: Option<usize>,
+) -> Result<CleanupResult, AppError> {
+  let keep_newest = keep_newest.unwrap_or(update::DEFAULT_KEEP_NEWEST);
+  tokio::task::spawn_blocking(move || update::cleanup_old_versions(&app, keep_newest))
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Cleanup task failed: {}", e)))?
+    .map_err(AppError::update)
+}
+
+/// Read the persisted update channel ("stable" | "beta" | "nightly").
+///
+/// Frontend can call:
+///   invoke<string>('get_update_channel')
+#[tauri::command]
+fn get_update_channel(app: tauri::AppHandle) -> Result<UpdateChannel, AppError> {
+  update::get_update_channel(&app).map_err(AppError::update)
+}
+
+/// Persist the update channel used by future checks and downloads.
+/// Lets testers opt into beta/nightly builds without reinstalling.
+///
+/// Frontend can call:
+///   invoke('set_update_channel', { channel: 'beta' })
+#[tauri::command]
+fn set_update_channel(app: tauri::AppHandle, channel: String) -> Result<UpdateChannel, AppError> {
+  let channel = channel
+    .parse::<UpdateChannel>()
+    .map_err(|e| AppError::invalid_input(e.to_string()))?;
+  update::set_update_channel(&app, channel).map_err(AppError::update)?;
+  Ok(channel)
+}
+
+/// Proxy / custom CA / timeout settings for update downloads.
+///
+/// Frontend can call:
+///   invoke<NetworkSettings>('get_update_network')
+#[tauri::command]
+fn get_update_network(app: tauri::AppHandle) -> Result<NetworkSettings, AppError> {
+  update::get_network_settings(&app).map_err(AppError::update)
+}
+
+/// Persist proxy / custom CA / timeout settings for update downloads
+/// (for users behind corporate proxies or TLS inspection).
+/// Empty fields fall back to env proxies and built-in defaults.
+///
+/// Frontend can call:
+///   invoke('set_update_network', { network: {
+///     proxy_url: 'http://proxy.corp.local:3128',
+///     ca_bundle_path: 'C:\\corp\\root-ca.pem',
+///     connect_timeout_secs: 15,
+///     read_timeout_secs: 60,
+///   } })
+#[tauri::command]
+fn set_update_network(
+  app: tauri::AppHandle,
+  network: NetworkSettings,
+) -> Result<NetworkSettings, AppError> {
+  network
+    .validate()
+    .map_err(|e| AppError::invalid_input(format!("{:#}", e)))?;
+  update::set_network_settings(&app, network.clone()).map_err(AppError::update)?;
+  Ok(network)
+}
+
+/// Probe every update mirror (in fallback order) with the current network
+/// settings, to diagnose "update check failed" reports.
+///
+/// Returns: [{ metadata_url, targets_url, ok, latency_ms, status, error }]
+///
+/// Frontend can call:
+///   invoke<EndpointTestResult[]>('test_update_endpoints')
+#[tauri::command]
+async fn test_update_endpoints(
+  app: tauri::AppHandle,
+) -> Result<Vec<EndpointTestResult>, AppError> {
+  let cfg = update::TufConfig::default_tuf_config(&app).map_err(AppError::update)?;
+  update::test_endpoints(&cfg).await.map_err(AppError::update)
+}
+
+/// QA: point this install at a staging TUF repository and trust the given
+/// root.json (base64), without recompiling. Only available in debug builds
+/// or with `allow_repository_override: true` in update_settings.json.
+///
+/// Frontend can call:
+///   invoke('set_update_repository', {
+///     metadataUrl: 'https://staging.filesup.app/metadata/',
+///     targetsUrl: 'https://staging.filesup.app/targets/',
+///     rootJsonB64: btoa(rootJsonText),
+///   })
+#[tauri::command]
+fn set_update_repository(
+  app: tauri::AppHandle,
+  metadata_url: String,
+  targets_url: String,
+  root_json_b64: String,
+) -> Result<(), AppError> {
+  use base64::Engine;
+
+  if !update::repository_override_enabled(&app) {
+    return Err(AppError::new(
+      ErrorCode::AccessDenied,
+      "Update repository override is disabled in this build",
+    ));
+  }
+  let root_json = base64::engine::general_purpose::STANDARD
+    .decode(root_json_b64.trim())
+    .map_err(|e| AppError::invalid_input(format!("root_json_b64 is not valid base64: {}", e)))?;
+  update::set_repository_override(&app, &metadata_url, &targets_url, &root_json)
+    .map_err(|e| AppError::invalid_input(format!("{:#}", e)))
+}
+
+/// Trusted TUF root: version and expiry. Show a warning when
+/// `expiring_soon` (< 30 days) or `expired` — after expiry no update can
+/// be verified until a newer root is installed.
+///
+/// Returns:
+///   { root_version, expires, expires_in_days, expired, expiring_soon, verified, root_path }
+///
+/// Frontend can call:
+///   invoke<TrustInfo>('get_trust_info')
+#[tauri::command]
+fn get_trust_info(app: tauri::AppHandle) -> Result<TrustInfo, AppError> {
+  update::trust_info(&app).map_err(AppError::update)
+}
+
+/// Switch back to the production update repository.
+///
+/// Frontend can call:
+///   invoke('reset_update_repository')
+#[tauri::command]
+fn reset_update_repository(app: tauri::AppHandle) -> Result<(), AppError> {
+  update::clear_repository_override(&app).map_err(AppError::update)
+}
+
+/// Background update checks: settings + last result.
+///
+/// Returns:
+///   { auto_check, check_interval_hours, auto_download,
+///     last_check, last_error, latest_version, next_check_in_sec }
+///
+/// Frontend can call:
+///   invoke<UpdateScheduleInfo>('get_update_schedule')
+#[tauri::command]
+fn get_update_schedule(
+  app: tauri::AppHandle,
+  scheduler: State<'_, UpdateScheduler>,
+) -> Result<UpdateScheduleInfo, AppError> {
+  scheduler.info(&app).map_err(AppError::update)
+}
+
+/// Enable/disable background checks, optionally changing the interval
+/// (hours, default 6) and pre-download on unmetered connections.
+/// New versions are announced via `fu:update_available`.
+///
+/// Frontend can call:
+///   invoke('set_update_schedule', { enabled: true, intervalHours: 12, autoDownload: false })
+#[tauri::command]
+fn set_update_schedule(
+  app: tauri::AppHandle,
+  scheduler: State<'_, UpdateScheduler>,
+  enabled: bool,
+  interval_hours: Option<u64>,
+  auto_download: Option<bool>,
+) -> Result<UpdateScheduleInfo, AppError> {
+  scheduler
+    .configure(&app, enabled, interval_hours, auto_download)
+    .map_err(AppError::update)
+}
+
+/// Run a background check now (also when automatic checks are off).
+/// The result arrives as `fu:update_available` if there is an update.
+///
+/// Frontend can call:
+///   invoke('trigger_update_check')
+#[tauri::command]
+fn trigger_update_check(scheduler: State<'_, UpdateScheduler>) {
+  scheduler.trigger();
 }
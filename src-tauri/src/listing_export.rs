@@ -0,0 +1,189 @@
+// src-tauri/src/listing_export.rs
+//
+// export_dir_listing: a folder's contents (optionally its whole tree) as a
+// spreadsheet-friendly CSV or a JSON array, e.g. to catalog a media drive.
+//
+// One row per entry: path, name, kind (file / dir / symlink), size, dates,
+// extension and the readonly / hidden attributes. Dates are RFC 3339 in
+// local time (spreadsheets parse them, unlike the epoch strings list_dir
+// returns). Rows are written as the walk goes, so huge trees don't have to
+// fit in memory; symlinks are listed but never followed. They go to a
+// hidden ".<name>.fu-partial" next to `dest` that replaces it once the walk
+// is done; a failed export removes only that file.
+
+use std::fs::{self, File, Metadata};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::Serialize;
+use tauri::State;
+use tokio::task;
+use walkdir::WalkDir;
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_meta::is_hidden;
+use crate::file_ops::unique_path;
+use crate::path_guard::PathGuard;
+use crate::scan_report::{csv_field, ReportFormat};
+
+const CSV_HEADER: &str = "path,name,kind,size,modified,created,extension,readonly,hidden";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListingRow {
+    path: String,
+    name: String,
+    kind: &'static str,
+    /// 0 for folders.
+    size: u64,
+    modified: Option<String>,
+    created: Option<String>,
+    extension: Option<String>,
+    readonly: bool,
+    hidden: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ListingExportSummary {
+    /// Rows written.
+    pub entries: u64,
+    /// Entries that couldn't be read (permission denied, vanished).
+    pub skipped: u64,
+}
+
+/// Command from TS:
+/// invoke("export_dir_listing", { path: "E:\\Photos", recursive: true, format: "csv", dest: "C:\\Users\\me\\photos.csv" })
+#[tauri::command]
+pub async fn export_dir_listing(
+    guard: State<'_, PathGuard>,
+    path: String,
+    recursive: bool,
+    format: ReportFormat,
+    dest: String,
+) -> Result<ListingExportSummary, AppError> {
+    let root = guard.check(&path)?;
+    let dest = guard.check(&dest)?;
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Not a directory: {}", path)));
+    }
+
+    task::spawn_blocking(move || {
+        let name = dest.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let partial = unique_path(&dest.with_file_name(format!(".{}.fu-partial", name)));
+        write_listing(&root, recursive, format, &partial)
+            .and_then(|summary| fs::rename(&partial, &dest).map(|()| summary))
+            .map_err(|e| {
+                let _ = fs::remove_file(&partial);
+                AppError::io(&e).with_path(&dest)
+            })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Listing export task failed: {}", e)))?
+}
+
+fn write_listing(
+    root: &Path,
+    recursive: bool,
+    format: ReportFormat,
+    dest: &Path,
+) -> std::io::Result<ListingExportSummary> {
+    let mut out = BufWriter::new(File::create(dest)?);
+    let mut summary = ListingExportSummary { entries: 0, skipped: 0 };
+    match format {
+        ReportFormat::Csv => writeln!(out, "{}", CSV_HEADER)?,
+        ReportFormat::Json => write!(out, "[")?,
+    }
+
+    let walk = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .sort_by_file_name();
+    for entry in walk {
+        // Our own output, when exporting into the listed folder.
+        if entry.as_ref().is_ok_and(|e| e.path() == dest) {
+            continue;
+        }
+        let row = entry
+            .map_err(|e| e.to_string())
+            .and_then(|e| e.metadata().map(|m| listing_row(e.path(), &m)).map_err(|err| err.to_string()));
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                tracing::warn!("[ListingExport] Skipping entry: {e}");
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        match format {
+            ReportFormat::Csv => write_csv_row(&mut out, &row)?,
+            ReportFormat::Json => {
+                if summary.entries > 0 {
+                    write!(out, ",")?;
+                }
+                write!(out, "\n  ")?;
+                serde_json::to_writer(&mut out, &row)?;
+            }
+        }
+        summary.entries += 1;
+    }
+
+    if let ReportFormat::Json = format {
+        writeln!(out, "\n]")?;
+    }
+    out.flush()?;
+    Ok(summary)
+}
+
+/// `meta` is from symlink_metadata (walkdir doesn't follow links).
+fn listing_row(path: &Path, meta: &Metadata) -> ListingRow {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let kind = if meta.file_type().is_symlink() {
+        "symlink"
+    } else if meta.is_dir() {
+        "dir"
+    } else {
+        "file"
+    };
+    let extension = if meta.is_file() {
+        path.extension().map(|e| e.to_string_lossy().to_lowercase())
+    } else {
+        None
+    };
+    ListingRow {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        size: if meta.is_dir() { 0 } else { meta.len() },
+        modified: meta.modified().ok().map(rfc3339),
+        created: meta.created().ok().map(rfc3339),
+        extension,
+        readonly: meta.permissions().readonly(),
+        hidden: is_hidden(&name, meta),
+        name,
+    }
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Local>::from(time).to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+fn write_csv_row(out: &mut impl Write, row: &ListingRow) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{},{},{},{},{},{},{},{},{}",
+        csv_field(&row.path),
+        csv_field(&row.name),
+        row.kind,
+        row.size,
+        row.modified.as_deref().unwrap_or(""),
+        row.created.as_deref().unwrap_or(""),
+        csv_field(row.extension.as_deref().unwrap_or("")),
+        row.readonly,
+        row.hidden
+    )
+}
@@ -0,0 +1,120 @@
+// src-tauri/src/logging.rs
+//
+// Backend logging via `tracing`.
+// Every event goes to stderr (dev console) and to daily-rotated files:
+//   <app data dir>/logs/filesup.YYYY-MM-DD.log
+// keeping the newest MAX_LOG_FILES files.
+//
+// Modules log with `tracing::warn!("[Module] ...")` etc.; the prefix keeps
+// lines greppable in both the console and the files.
+//
+// Commands:
+//   - get_recent_logs(lines): tail across the newest log files
+// ai_bundle.rs also appends the tail to every debug bundle.
+
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+const LOG_PREFIX: &str = "filesup";
+const LOG_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7;
+
+/// Default for get_recent_logs when `lines` is omitted.
+const DEFAULT_TAIL_LINES: usize = 200;
+
+fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data dir error: {}", e))?;
+    Ok(dir.join("logs"))
+}
+
+/// Install the global subscriber. Called first thing in setup.
+/// If the log dir can't be created we still log to stderr.
+pub fn init_logging(app: &AppHandle) {
+    let file_layer = log_dir(app)
+        .and_then(|dir| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_PREFIX)
+                .filename_suffix(LOG_SUFFIX)
+                .max_log_files(MAX_LOG_FILES)
+                .build(&dir)
+                .map_err(|e| format!("Failed to open log dir {:?}: {}", dir, e))
+        })
+        .map(|appender| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(appender)
+        });
+
+    let (file_layer, file_error) = match file_layer {
+        Ok(layer) => (Some(layer), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let res = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(file_layer)
+        .try_init();
+
+    if let Err(e) = res {
+        eprintln!("[Logging] Failed to install subscriber: {e}");
+    }
+    if let Some(e) = file_error {
+        tracing::warn!("[Logging] File logging disabled: {e}");
+    }
+}
+
+/// Last `lines` lines across the newest log files, oldest line first.
+pub fn recent_log_lines(app: &AppHandle, lines: usize) -> Result<Vec<String>, String> {
+    let dir = log_dir(app)?;
+    let mut files: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(rd) => rd
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(LOG_PREFIX) && n.ends_with(LOG_SUFFIX))
+            })
+            .collect(),
+        Err(_) => return Ok(Vec::new()),
+    };
+    // Date in the file name: newest sorts last.
+    files.sort();
+
+    let mut out: Vec<String> = Vec::new();
+    for path in files.iter().rev() {
+        if out.len() >= lines {
+            break;
+        }
+        let Ok(text) = fs::read_to_string(path) else {
+            continue;
+        };
+        let needed = lines - out.len();
+        let file_lines: Vec<&str> = text.lines().collect();
+        let start = file_lines.len().saturating_sub(needed);
+        // Older file goes in front of what we already have.
+        let mut chunk: Vec<String> = file_lines[start..].iter().map(|l| l.to_string()).collect();
+        chunk.append(&mut out);
+        out = chunk;
+    }
+    Ok(out)
+}
+
+/// Tail of the backend log files.
+/// Frontend can call:
+///   invoke<string[]>('get_recent_logs', { lines: 200 })
+#[tauri::command]
+pub fn get_recent_logs(app: AppHandle, lines: Option<usize>) -> Result<Vec<String>, String> {
+    recent_log_lines(&app, lines.unwrap_or(DEFAULT_TAIL_LINES))
+}
@@ -0,0 +1,479 @@
+// src-tauri/src/manifest.rs
+//
+// Integrity manifests ("verify my backup"):
+// - create_manifest hashes every file below a folder (BLAKE3) and writes a
+//   JSON manifest: relative path, size and hash per file.
+// - verify_manifest re-reads the folder, e.g. on the backup drive, and
+//   reports files that are missing, changed (size or content) or extra.
+//
+// The manifest carries a `digest`: BLAKE3 of its file list. It is not a
+// signature (there is no key), but a damaged or hand-edited manifest is
+// rejected instead of producing a bogus report.
+//
+// Paths in the manifest are "/"-separated and relative to the root, so it
+// verifies the same copy on another drive letter or OS. Symlinks are not
+// followed; a manifest written inside the root is not listed in itself.
+// Events: `fu:manifest_progress`, then `fu:create_manifest_completed` or
+// `fu:verify_manifest_completed`.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use crate::background_priority;
+use crate::compare::relative_key;
+use crate::error::{AppError, ErrorCode};
+use crate::file_filter::{FileFilter, FileFilterSpec, FilterEntry};
+use crate::file_ops::FileOpFailure;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+
+const MANIFEST_VERSION: u32 = 1;
+const READ_CHUNK_BYTES: usize = 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    version: u32,
+    /// Always "blake3" for now.
+    algorithm: String,
+    created_at: String,
+    /// Where it was created; informational only.
+    root: String,
+    files: Vec<ManifestEntry>,
+    /// BLAKE3 of `files` serialized as compact JSON.
+    digest: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ManifestEntry {
+    path: String,
+    size: u64,
+    hash: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ManifestProgress {
+    op_id: String,
+    phase: String, // "listing" | "hashing"
+    files_done: u64,
+    files_total: u64,
+    bytes_done: u64,
+    bytes_total: u64,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CreateManifestCompleted {
+    op_id: String,
+    status: String, // "ok" | "partial" | "cancelled" | "error"
+    manifest_path: String,
+    files: u64,
+    total_bytes: u64,
+    /// Files that couldn't be read and are not in the manifest.
+    failed: Vec<FileOpFailure>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ChangedFile {
+    path: String,
+    reason: &'static str, // "size" | "content"
+    expected_size: u64,
+    actual_size: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct VerifyReport {
+    /// True when every listed file is present and unchanged (extra files
+    /// don't count against it).
+    intact: bool,
+    verified: u64,
+    missing: Vec<String>,
+    changed: Vec<ChangedFile>,
+    /// On disk but not in the manifest.
+    extra: Vec<String>,
+    /// Listed files that exist but couldn't be read.
+    unreadable: Vec<FileOpFailure>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VerifyManifestCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    #[serde(flatten)]
+    report: VerifyReport,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// Command from TS:
+/// invoke("create_manifest", { opId, root: "D:\\Photos", dest: "D:\\Photos\\manifest.json", filter: null })
+#[tauri::command]
+pub async fn create_manifest(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    root: String,
+    dest: String,
+    filter: Option<FileFilterSpec>,
+) -> Result<(), AppError> {
+    let root = guard.check(&root)?;
+    let dest = guard.check(&dest)?;
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Not a directory: {}", root.display())));
+    }
+    let filter = FileFilter::from_spec(filter.as_ref())?;
+
+    let token = registry.register(&op_id, OperationKind::Manifest);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let mut failed = Vec::new();
+        let res = build_manifest(&app, &op_id, &root, &dest, filter.as_ref(), &token, &mut failed)
+            .and_then(|manifest| {
+                write_manifest(&manifest, &dest)?;
+                Ok(manifest)
+            });
+
+        let (status, files, total_bytes, error) = match res {
+            Ok(manifest) => {
+                let status = if failed.is_empty() { "ok" } else { "partial" };
+                let total_bytes = manifest.files.iter().map(|f| f.size).sum();
+                (status, manifest.files.len() as u64, total_bytes, None)
+            }
+            Err(e) if e.code == ErrorCode::Cancelled => ("cancelled", 0, 0, Some(e)),
+            Err(e) => ("error", 0, 0, Some(e)),
+        };
+        let _ = app.emit(
+            "fu:create_manifest_completed",
+            CreateManifestCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                manifest_path: dest.to_string_lossy().to_string(),
+                files,
+                total_bytes,
+                failed,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Command from TS:
+/// invoke("verify_manifest", { opId, root: "E:\\Backup\\Photos", manifest: "E:\\Backup\\Photos\\manifest.json" })
+///
+/// A manifest that can't be read or fails its digest check is rejected
+/// up front, before any hashing starts.
+#[tauri::command]
+pub async fn verify_manifest(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    root: String,
+    manifest: String,
+) -> Result<(), AppError> {
+    let root = guard.check(&root)?;
+    let manifest_path = guard.check(&manifest)?;
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Not a directory: {}", root.display())));
+    }
+    let manifest = {
+        let path = manifest_path.clone();
+        task::spawn_blocking(move || read_manifest(&path))
+            .await
+            .map_err(|e| AppError::new(ErrorCode::Internal, format!("Manifest task failed: {}", e)))??
+    };
+
+    let token = registry.register(&op_id, OperationKind::Manifest);
+    let registry = registry.inner().clone();
+    let low = background_priority::enabled(&app);
+
+    background_priority::spawn_blocking(low, move || {
+        let res = verify(&app, &op_id, &root, &manifest_path, &manifest, &token);
+        let (status, report, error) = match res {
+            Ok(report) => ("ok", report, None),
+            Err(e) if e.code == ErrorCode::Cancelled => ("cancelled", VerifyReport::default(), Some(e)),
+            Err(e) => ("error", VerifyReport::default(), Some(e)),
+        };
+        let _ = app.emit(
+            "fu:verify_manifest_completed",
+            VerifyManifestCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                report,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Files below `root` as (relative key, absolute path, size), sorted.
+/// `skip` (the manifest itself) is left out.
+fn list_files(
+    root: &Path,
+    skip: &Path,
+    filter: Option<&FileFilter>,
+    token: &CancellationToken,
+) -> Result<Vec<(String, PathBuf, u64)>, AppError> {
+    let walker = WalkDir::new(root).min_depth(1).sort_by_file_name().into_iter().filter_entry(|e| {
+        let (Some(filter), true) = (filter, e.file_type().is_dir()) else {
+            return true;
+        };
+        let rel = e.path().strip_prefix(root).unwrap_or(e.path());
+        e.metadata().is_ok_and(|m| filter.allows_dir(&FilterEntry::from_metadata(rel, &m)))
+    });
+
+    let mut files = Vec::new();
+    for entry in walker {
+        if token.is_cancelled() {
+            return Err(AppError::cancelled());
+        }
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::warn!("[Manifest] WalkDir error: {err}");
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() || entry.path() == skip {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if filter.is_some_and(|f| !f.matches(&FilterEntry::from_metadata(rel, &meta))) {
+            continue;
+        }
+        if let Some(key) = relative_key(root, entry.path()) {
+            files.push((key, entry.into_path(), meta.len()));
+        }
+    }
+    Ok(files)
+}
+
+fn build_manifest(
+    app: &AppHandle,
+    op_id: &str,
+    root: &Path,
+    dest: &Path,
+    filter: Option<&FileFilter>,
+    token: &CancellationToken,
+    failed: &mut Vec<FileOpFailure>,
+) -> Result<Manifest, AppError> {
+    let mut progress = ManifestProgress::new(op_id);
+    let _ = app.emit("fu:manifest_progress", progress.clone());
+    let files = list_files(root, dest, filter, token)?;
+    progress.start_hashing(&files);
+
+    let mut entries = Vec::with_capacity(files.len());
+    let mut last_emit = Instant::now();
+    for (key, path, size) in files {
+        progress.current_file = key.clone();
+        match hash_file(app, &path, token, &mut progress, &mut last_emit) {
+            Ok(hash) => entries.push(ManifestEntry { path: key, size, hash }),
+            Err(e) if e.code == ErrorCode::Cancelled => return Err(e),
+            Err(e) => failed.push(FileOpFailure::new(path.to_string_lossy(), e)),
+        }
+        progress.files_done += 1;
+    }
+    let _ = app.emit("fu:manifest_progress", progress.clone());
+
+    Ok(Manifest {
+        version: MANIFEST_VERSION,
+        algorithm: "blake3".to_string(),
+        created_at: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+        root: root.to_string_lossy().to_string(),
+        digest: digest(&entries),
+        files: entries,
+    })
+}
+
+fn verify(
+    app: &AppHandle,
+    op_id: &str,
+    root: &Path,
+    manifest_path: &Path,
+    manifest: &Manifest,
+    token: &CancellationToken,
+) -> Result<VerifyReport, AppError> {
+    let mut progress = ManifestProgress::new(op_id);
+    let _ = app.emit("fu:manifest_progress", progress.clone());
+    let on_disk = list_files(root, manifest_path, None, token)?;
+    let listed: HashSet<&str> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+
+    let mut report = VerifyReport {
+        extra: on_disk
+            .iter()
+            .filter(|(key, _, _)| !listed.contains(key.as_str()))
+            .map(|(key, _, _)| key.clone())
+            .collect(),
+        ..Default::default()
+    };
+    progress.files_total = manifest.files.len() as u64;
+    progress.bytes_total = manifest.files.iter().map(|f| f.size).sum();
+    progress.phase = "hashing".to_string();
+
+    let mut last_emit = Instant::now();
+    for entry in &manifest.files {
+        progress.current_file = entry.path.clone();
+        let path = root.join(&entry.path);
+        match fs::metadata(&path) {
+            Ok(meta) if meta.is_file() && meta.len() != entry.size => {
+                report.changed.push(ChangedFile {
+                    path: entry.path.clone(),
+                    reason: "size",
+                    expected_size: entry.size,
+                    actual_size: meta.len(),
+                });
+                progress.bytes_done += entry.size;
+            }
+            Ok(meta) if meta.is_file() => match hash_file(app, &path, token, &mut progress, &mut last_emit) {
+                Ok(hash) if hash == entry.hash => report.verified += 1,
+                Ok(_) => report.changed.push(ChangedFile {
+                    path: entry.path.clone(),
+                    reason: "content",
+                    expected_size: entry.size,
+                    actual_size: meta.len(),
+                }),
+                Err(e) if e.code == ErrorCode::Cancelled => return Err(e),
+                Err(e) => report.unreadable.push(FileOpFailure::new(entry.path.clone(), e)),
+            },
+            // Gone, or replaced by a folder.
+            _ => {
+                report.missing.push(entry.path.clone());
+                progress.bytes_done += entry.size;
+            }
+        }
+        progress.files_done += 1;
+    }
+    let _ = app.emit("fu:manifest_progress", progress.clone());
+
+    report.intact = report.missing.is_empty() && report.changed.is_empty() && report.unreadable.is_empty();
+    Ok(report)
+}
+
+impl ManifestProgress {
+    fn new(op_id: &str) -> Self {
+        ManifestProgress {
+            op_id: op_id.to_string(),
+            phase: "listing".to_string(),
+            files_done: 0,
+            files_total: 0,
+            bytes_done: 0,
+            bytes_total: 0,
+            current_file: String::new(),
+        }
+    }
+
+    fn start_hashing(&mut self, files: &[(String, PathBuf, u64)]) {
+        self.phase = "hashing".to_string();
+        self.files_total = files.len() as u64;
+        self.bytes_total = files.iter().map(|(_, _, size)| size).sum();
+    }
+}
+
+/// BLAKE3 of one file; adds the bytes read to `progress`.
+fn hash_file(
+    app: &AppHandle,
+    path: &Path,
+    token: &CancellationToken,
+    progress: &mut ManifestProgress,
+    last_emit: &mut Instant,
+) -> Result<String, AppError> {
+    let mut file = File::open(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buf = vec![0u8; READ_CHUNK_BYTES];
+    loop {
+        if token.is_cancelled() {
+            return Err(AppError::cancelled());
+        }
+        let n = file.read(&mut buf).map_err(|e| AppError::io(&e).with_path(path))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        progress.bytes_done += n as u64;
+        if last_emit.elapsed() >= Duration::from_millis(100) {
+            let _ = app.emit("fu:manifest_progress", progress.clone());
+            *last_emit = Instant::now();
+        }
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+fn digest(files: &[ManifestEntry]) -> String {
+    let json = serde_json::to_vec(files).unwrap_or_default();
+    blake3::hash(&json).to_hex().to_string()
+}
+
+/// Written to a temp file and renamed, so an interrupted write never
+/// leaves a truncated manifest behind.
+fn write_manifest(manifest: &Manifest, dest: &Path) -> Result<(), AppError> {
+    let tmp = dest.with_extension("fu-partial");
+    let res = File::create(&tmp)
+        .map_err(|e| AppError::io(&e).with_path(&tmp))
+        .and_then(|file| {
+            let mut out = BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut out, manifest)
+                .map_err(|e| AppError::new(ErrorCode::Io, format!("Failed to write manifest: {}", e)))?;
+            out.flush().map_err(|e| AppError::io(&e).with_path(&tmp))
+        })
+        .and_then(|_| fs::rename(&tmp, dest).map_err(|e| AppError::io(&e).with_path(dest)));
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    res
+}
+
+fn read_manifest(path: &Path) -> Result<Manifest, AppError> {
+    let file = File::open(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    let manifest: Manifest = serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+        AppError::invalid_input(format!("Not a manifest file: {}", e)).with_path(path)
+    })?;
+    if manifest.version > MANIFEST_VERSION || manifest.algorithm != "blake3" {
+        return Err(AppError::invalid_input(format!(
+            "Unsupported manifest (version {}, {})",
+            manifest.version, manifest.algorithm
+        ))
+        .with_path(path));
+    }
+    if digest(&manifest.files) != manifest.digest {
+        return Err(AppError::invalid_input("Manifest is damaged or was edited (digest mismatch)").with_path(path));
+    }
+    Ok(manifest)
+}
@@ -0,0 +1,307 @@
+// src-tauri/src/media_meta.rs
+//
+// Photo / audio / video metadata for the properties panel.
+//   - images: EXIF via kamadak-exif (JPEG, TIFF and TIFF-based RAW, HEIF /
+//     HEIC / AVIF, PNG, WebP): camera, lens, exposure, date taken, GPS
+//   - audio / video: container tags and duration via symphonia (MP3, FLAC,
+//     Ogg, WAV, MP4 / M4A / MOV, MKV / WebM). Only audio tracks are decoded
+//     by symphonia, so a video's duration comes from its audio track.
+// The kind is picked by extension (file_type::category_of).
+//
+// date_taken is also the grouping key for organize_media
+// (media_organize.rs), so it is exposed on its own.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use exif::{Exif, In, Reader, Tag, Value};
+use serde::Serialize;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+use tauri::State;
+use tokio::task;
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_type::{category_of, FileCategory};
+use crate::path_guard::PathGuard;
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaMetadata {
+    /// "image" | "audio" | "video"
+    pub kind: String,
+    /// Pixel size from the image header (None for formats the image crate
+    /// can't read, e.g. HEIC and RAW).
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// None if the image carries no EXIF block.
+    pub exif: Option<ExifData>,
+    /// Audio / video only.
+    pub av: Option<AvData>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ExifData {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens: Option<String>,
+    /// "2024-05-01T14:03:22", with the offset ("+02:00") when recorded.
+    pub date_taken: Option<String>,
+    /// Rendered the way cameras show them: "1/250 s", "f/2.8", "35 mm".
+    pub exposure_time: Option<String>,
+    pub f_number: Option<String>,
+    pub iso: Option<u32>,
+    pub focal_length: Option<String>,
+    /// EXIF orientation 1..8 (1 = upright).
+    pub orientation: Option<u32>,
+    pub gps: Option<GpsPosition>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GpsPosition {
+    /// Decimal degrees, negative for south / west.
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters, negative below sea level.
+    pub altitude: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AvData {
+    pub duration_secs: Option<f64>,
+    pub codec: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u32>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub genre: Option<String>,
+    pub date: Option<String>,
+    pub track_number: Option<String>,
+}
+
+/// Frontend can call:
+///   invoke<MediaMetadata>('read_media_metadata', { path })
+#[tauri::command]
+pub async fn read_media_metadata(
+    guard: State<'_, PathGuard>,
+    path: String,
+) -> Result<MediaMetadata, AppError> {
+    let path = guard.check(&path)?;
+    task::spawn_blocking(move || read_metadata(&path))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Metadata task failed: {}", e)))?
+}
+
+pub fn read_metadata(path: &Path) -> Result<MediaMetadata, AppError> {
+    match category_of(path) {
+        FileCategory::Images => {
+            let (width, height) = image::image_dimensions(path)
+                .map(|(w, h)| (Some(w), Some(h)))
+                .unwrap_or((None, None));
+            Ok(MediaMetadata {
+                kind: "image".to_string(),
+                width,
+                height,
+                exif: read_exif(path)?.map(|exif| exif_data(&exif)),
+                av: None,
+            })
+        }
+        category @ (FileCategory::Audio | FileCategory::Video) => Ok(MediaMetadata {
+            kind: if category == FileCategory::Audio { "audio" } else { "video" }.to_string(),
+            av: Some(av_data(path)?),
+            ..Default::default()
+        }),
+        _ => Err(AppError::invalid_input("Not an image, audio or video file").with_path(path)),
+    }
+}
+
+/// EXIF DateTimeOriginal (falling back to DateTime), camera local time.
+/// None for non-images and photos without a date.
+pub fn date_taken(path: &Path) -> Option<NaiveDateTime> {
+    if category_of(path) != FileCategory::Images {
+        return None;
+    }
+    let exif = read_exif(path).ok()??;
+    exif_date(&exif)
+}
+
+fn exif_date(exif: &Exif) -> Option<NaiveDateTime> {
+    [Tag::DateTimeOriginal, Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| parse_exif_date(&ascii(exif, tag)?))
+}
+
+/// Ok(None) when the file has no EXIF block.
+fn read_exif(path: &Path) -> Result<Option<Exif>, AppError> {
+    let file = File::open(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    match Reader::new().read_from_container(&mut BufReader::new(file)) {
+        Ok(exif) => Ok(Some(exif)),
+        Err(exif::Error::Io(e)) => Err(AppError::io(&e).with_path(path)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn exif_data(exif: &Exif) -> ExifData {
+    let date_taken = exif_date(exif).map(|date| {
+        let date = date.format("%Y-%m-%dT%H:%M:%S").to_string();
+        match ascii(exif, Tag::OffsetTimeOriginal) {
+            Some(offset) => date + &offset,
+            None => date,
+        }
+    });
+
+    ExifData {
+        camera_make: ascii(exif, Tag::Make),
+        camera_model: ascii(exif, Tag::Model),
+        lens: ascii(exif, Tag::LensModel),
+        date_taken,
+        exposure_time: rational(exif, Tag::ExposureTime).map(|(num, denom)| {
+            if num == 0 || denom == 0 {
+                "0 s".to_string()
+            } else if num < denom {
+                format!("1/{} s", (denom as f64 / num as f64).round())
+            } else {
+                format!("{} s", num as f64 / denom as f64)
+            }
+        }),
+        f_number: rational_f64(exif, Tag::FNumber).map(|f| format!("f/{:.1}", f)),
+        iso: uint(exif, Tag::PhotographicSensitivity),
+        focal_length: rational_f64(exif, Tag::FocalLength).map(|f| format!("{} mm", f.round())),
+        orientation: uint(exif, Tag::Orientation),
+        gps: gps_position(exif),
+    }
+}
+
+fn gps_position(exif: &Exif) -> Option<GpsPosition> {
+    let coordinate = |tag: Tag, ref_tag: Tag, negative: &str| {
+        let field = exif.get_field(tag, In::PRIMARY)?;
+        let Value::Rational(parts) = &field.value else {
+            return None;
+        };
+        let degrees = parts
+            .iter()
+            .zip([1.0, 60.0, 3600.0])
+            .map(|(part, div)| part.to_f64() / div)
+            .sum::<f64>();
+        let sign = if ascii(exif, ref_tag).as_deref() == Some(negative) { -1.0 } else { 1.0 };
+        Some(sign * degrees).filter(|d| d.is_finite())
+    };
+    let latitude = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let longitude = coordinate(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+    let altitude = rational_f64(exif, Tag::GPSAltitude).map(|alt| {
+        let below = exif
+            .get_field(Tag::GPSAltitudeRef, In::PRIMARY)
+            .and_then(|f| f.value.get_uint(0))
+            == Some(1);
+        if below {
+            -alt
+        } else {
+            alt
+        }
+    });
+    Some(GpsPosition { latitude, longitude, altitude })
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let text = String::from_utf8_lossy(values.first()?);
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn rational(exif: &Exif, tag: Tag) -> Option<(u32, u32)> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(v) => v.first().map(|r| (r.num, r.denom)),
+        _ => None,
+    }
+}
+
+fn rational_f64(exif: &Exif, tag: Tag) -> Option<f64> {
+    rational(exif, tag)
+        .filter(|(_, denom)| *denom != 0)
+        .map(|(num, denom)| num as f64 / denom as f64)
+}
+
+fn uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+/// "2024:05:01 14:03:22"; some cameras write "0000:00:00 00:00:00".
+fn parse_exif_date(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, "%Y:%m:%d %H:%M:%S").ok()
+}
+
+fn av_data(path: &Path) -> Result<AvData, AppError> {
+    let file = File::open(path).map_err(|e| AppError::io(&e).with_path(path))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension() {
+        hint.with_extension(&ext.to_string_lossy());
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| {
+            AppError::new(ErrorCode::InvalidInput, format!("Unsupported media file: {}", e)).with_path(path)
+        })?;
+
+    let mut av = AvData::default();
+    // Tags can sit before the container (ID3v2 in front of MP3) or inside it.
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        apply_tags(&mut av, revision);
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        apply_tags(&mut av, revision);
+    }
+
+    if let Some(track) = probed.format.default_track() {
+        let params = &track.codec_params;
+        av.codec = symphonia::default::get_codecs()
+            .get_codec(params.codec)
+            .map(|c| c.short_name.to_string());
+        av.sample_rate = params.sample_rate;
+        av.channels = params.channels.map(|c| c.count() as u32);
+        av.duration_secs = match (params.time_base, params.n_frames) {
+            (Some(base), Some(frames)) => {
+                let time = base.calc_time(frames);
+                Some(time.seconds as f64 + time.frac)
+            }
+            (None, Some(frames)) => params.sample_rate.map(|rate| frames as f64 / rate as f64),
+            _ => None,
+        };
+    }
+    Ok(av)
+}
+
+/// Fills the fields still empty.
+fn apply_tags(av: &mut AvData, revision: &MetadataRevision) {
+    for tag in revision.tags() {
+        let slot = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => &mut av.title,
+            Some(StandardTagKey::Artist) => &mut av.artist,
+            Some(StandardTagKey::Album) => &mut av.album,
+            Some(StandardTagKey::AlbumArtist) => &mut av.album_artist,
+            Some(StandardTagKey::Genre) => &mut av.genre,
+            Some(StandardTagKey::Date) => &mut av.date,
+            Some(StandardTagKey::TrackNumber) => &mut av.track_number,
+            _ => continue,
+        };
+        if slot.is_none() {
+            let value = tag.value.to_string();
+            if !value.trim().is_empty() {
+                *slot = Some(value.trim().to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,514 @@
+// src-tauri/src/media_organize.rs
+//
+// organize_media: move the photos and videos of a folder into date folders
+// below it, "2024/05/IMG_0001.jpg" with the default pattern "{YYYY}/{MM}".
+//
+// Flow (one registered operation, cancellable between files):
+//   1) Plan: collect images / videos (file_type::category_of), date each by
+//      EXIF date taken (media_meta::date_taken) or else its modified time,
+//      and work out the target. Files already in place are left out.
+//      Name clashes (on disk, or two files planned to the same name) are
+//      resolved by the duplicate policy: keep both ("name (1).jpg") or skip.
+//   2) Move (not with dryRun): same-volume renames, so nothing is copied.
+//      Every finished move is appended to an undo journal right away:
+//        <app data dir>/organize-journals/<op id>.jsonl
+//   3) `fu:organize_media_completed` with the plan and the failures.
+// Progress: `fu:organize_media_progress` (phase "planning" / "moving").
+// Runs that move files (and their undos) go into the audit log (audit.rs).
+//
+// undo_organize_media(opId) replays a journal backwards: each file goes
+// back to where it was and date folders left empty are removed.
+//
+// Pattern tokens: {YYYY}, {YY}, {MM}, {DD}; "/" separates folders.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use crate::catalog::Catalog;
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::FileOpFailure;
+use crate::file_type::{category_of, FileCategory};
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::gps_backend::{op_file_stem, OperationKind, OperationRegistry};
+use crate::media_meta::date_taken;
+use crate::path_guard::{resolve, PathGuard};
+use crate::rename_ops::validate_name;
+
+const DEFAULT_PATTERN: &str = "{YYYY}/{MM}";
+const JOURNAL_DIR: &str = "organize-journals";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicatePolicy {
+    /// Move under the next free name, "name (1).ext".
+    #[default]
+    KeepBoth,
+    /// Leave the file where it is.
+    Skip,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OrganizeOptions {
+    pub dry_run: bool,
+    /// Also pick up media in subfolders (files already in the right date
+    /// folder stay put).
+    pub recursive: bool,
+    pub on_duplicate: DuplicatePolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    Exif,
+    Mtime,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaMove {
+    pub from: String,
+    pub to: String,
+    pub date_source: DateSource,
+    /// Why the file stays (name taken with onDuplicate "skip").
+    pub skipped: Option<String>,
+}
+
+/// One line of the undo journal.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct OrganizeProgress {
+    op_id: String,
+    phase: &'static str, // "planning" | "moving"
+    done: u64,
+    total: u64,
+    current_file: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct OrganizeCompleted {
+    op_id: String,
+    status: String, // "ok" | "partial" | "cancelled" | "error"
+    dry_run: bool,
+    moves: Vec<MediaMove>,
+    moved: u64,
+    failed: Vec<FileOpFailure>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoOrganizeResult {
+    pub restored: u64,
+    pub failed: Vec<FileOpFailure>,
+}
+
+/// Command from TS:
+/// invoke("organize_media", { opId, source: "D:\\Camera", pattern: "{YYYY}/{MM}", options: { dryRun: true, recursive: false, onDuplicate: "keepBoth" } })
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn organize_media(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    catalog: State<'_, Catalog>,
+    op_id: String,
+    source: String,
+    pattern: Option<String>,
+    options: Option<OrganizeOptions>,
+) -> Result<(), AppError> {
+    let root = guard.check(&source)?;
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Not a directory: {}", source)));
+    }
+    let pattern = pattern.unwrap_or_else(|| DEFAULT_PATTERN.to_string());
+    // Any date shows a bad token or folder name up front.
+    expand_pattern(&pattern, &NaiveDateTime::default()).map_err(AppError::invalid_input)?;
+    let options = options.unwrap_or_default();
+    let journal = journal_path(&app, &op_id).map_err(|e| AppError::new(ErrorCode::Internal, e))?;
+
+    let token = registry.register(&op_id, OperationKind::OrganizeMedia);
+    let registry = registry.inner().clone();
+    let catalog = catalog.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut job = OrganizeJob {
+            app: &app,
+            op_id: &op_id,
+            token: &token,
+            last_emit: Instant::now(),
+        };
+        let mut failed = Vec::new();
+        let mut moved = 0;
+        let res = job.plan(&root, &pattern, &options).and_then(|moves| {
+            if !options.dry_run {
+                let done = job.apply(&moves, &journal, &mut failed);
+                moved = done.len() as u64;
+                // Keep tags/notes attached to the moved files.
+                catalog.rekey_all(&resolved_pairs(&done));
+                if token.is_cancelled() {
+                    return Err(AppError::cancelled());
+                }
+            }
+            Ok(moves)
+        });
+
+        let (status, moves, error) = match res {
+            Ok(moves) if failed.is_empty() => ("ok", moves, None),
+            Ok(moves) => ("partial", moves, None),
+            Err(e) if e.code == ErrorCode::Cancelled => ("cancelled", Vec::new(), Some(e)),
+            Err(e) => ("error", Vec::new(), Some(e)),
+        };
+        if !options.dry_run {
+            let detail = match &error {
+                Some(e) if status == "error" => e.message.clone(),
+                _ => format!("{} moved, {} failed", moved, failed.len()),
+            };
+            audit::record(
+                &app,
+                AuditEntry::new(AuditAction::OrganizeMedia)
+                    .op_id(&op_id)
+                    .sources(&[root.to_string_lossy()])
+                    .destination(root.to_string_lossy())
+                    .result(status, Some(detail)),
+            );
+        }
+        let _ = app.emit(
+            "fu:organize_media_completed",
+            OrganizeCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                dry_run: options.dry_run,
+                moves,
+                moved,
+                failed,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Put the files of an earlier organize_media back. The journal is removed
+/// once everything is restored; on failures it is kept so undo can be
+/// retried.
+///
+/// Frontend can call:
+///   invoke<UndoOrganizeResult>('undo_organize_media', { opId })
+#[tauri::command]
+pub async fn undo_organize_media(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    catalog: State<'_, Catalog>,
+    op_id: String,
+) -> Result<UndoOrganizeResult, AppError> {
+    let journal = journal_path(&app, &op_id).map_err(|e| AppError::new(ErrorCode::Internal, e))?;
+    let mut entries = read_journal(&journal).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AppError::new(ErrorCode::NotFound, format!("Nothing to undo for {:?}", op_id))
+        } else {
+            AppError::io(&e).with_path(&journal)
+        }
+    })?;
+    for entry in &mut entries {
+        entry.from = guard.check_entry(&entry.from)?.to_string_lossy().to_string();
+        entry.to = guard.check_entry(&entry.to)?.to_string_lossy().to_string();
+    }
+    let catalog = catalog.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut result = UndoOrganizeResult {
+            restored: 0,
+            failed: Vec::new(),
+        };
+        let mut done = Vec::new();
+        for entry in entries.iter().rev() {
+            match undo_move(entry) {
+                Ok(()) => {
+                    result.restored += 1;
+                    done.push((PathBuf::from(&entry.to), PathBuf::from(&entry.from)));
+                }
+                Err(error) => result.failed.push(FileOpFailure::new(entry.to.as_str(), error)),
+            }
+        }
+        catalog.rekey_all(&resolved_pairs(&done));
+        audit::record(
+            &app,
+            AuditEntry::new(AuditAction::UndoOrganizeMedia)
+                .op_id(&op_id)
+                .batch_outcome(entries.len(), result.failed.len()),
+        );
+        if result.failed.is_empty() {
+            if let Err(e) = fs::remove_file(&journal) {
+                tracing::warn!("[OrganizeMedia] Failed to remove journal {:?}: {e}", journal);
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Undo task failed: {}", e)))
+}
+
+struct OrganizeJob<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    token: &'a CancellationToken,
+    last_emit: Instant,
+}
+
+impl OrganizeJob<'_> {
+    fn plan(&mut self, root: &Path, pattern: &str, options: &OrganizeOptions) -> Result<Vec<MediaMove>, AppError> {
+        let files: Vec<PathBuf> = WalkDir::new(root)
+            .min_depth(1)
+            .max_depth(if options.recursive { usize::MAX } else { 1 })
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| matches!(category_of(p), FileCategory::Images | FileCategory::Video))
+            .collect();
+
+        let mut moves = Vec::new();
+        // Targets handed out so far, so two files never get the same name.
+        let mut taken: HashSet<PathBuf> = HashSet::new();
+        for (i, from) in files.iter().enumerate() {
+            if self.token.is_cancelled() {
+                return Err(AppError::cancelled());
+            }
+            self.progress("planning", i as u64, files.len() as u64, from);
+
+            let (date, date_source) = match date_taken(from) {
+                Some(date) => (date, DateSource::Exif),
+                None => match fs::metadata(from).and_then(|m| m.modified()) {
+                    Ok(mtime) => (DateTime::<Local>::from(mtime).naive_local(), DateSource::Mtime),
+                    Err(e) => {
+                        tracing::warn!("[OrganizeMedia] Skipping {:?}: {e}", from);
+                        continue;
+                    }
+                },
+            };
+            let Some(name) = from.file_name() else {
+                continue;
+            };
+            let dir = root.join(expand_pattern(pattern, &date).map_err(AppError::invalid_input)?);
+            let mut to = dir.join(name);
+            if to == *from {
+                continue;
+            }
+
+            let mut skipped = None;
+            if taken.contains(&to) || fs::symlink_metadata(&to).is_ok() {
+                match options.on_duplicate {
+                    DuplicatePolicy::KeepBoth => to = free_name(&to, &taken),
+                    DuplicatePolicy::Skip => skipped = Some("Name already taken".to_string()),
+                }
+            }
+            if skipped.is_none() {
+                taken.insert(to.clone());
+            }
+            moves.push(MediaMove {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+                date_source,
+                skipped,
+            });
+        }
+        Ok(moves)
+    }
+
+    /// Returns the (from, to) pairs that were moved.
+    fn apply(&mut self, moves: &[MediaMove], journal: &Path, failed: &mut Vec<FileOpFailure>) -> Vec<(PathBuf, PathBuf)> {
+        let mut done = Vec::new();
+        let mut journal = match open_journal(journal) {
+            Ok(file) => file,
+            Err(e) => {
+                // Without a journal the moves couldn't be undone.
+                failed.push(FileOpFailure::new(journal.to_string_lossy(), AppError::io(&e)));
+                return done;
+            }
+        };
+
+        let todo: Vec<&MediaMove> = moves.iter().filter(|m| m.skipped.is_none()).collect();
+        for (i, item) in todo.iter().enumerate() {
+            if self.token.is_cancelled() {
+                break;
+            }
+            let (from, to) = (Path::new(&item.from), Path::new(&item.to));
+            self.progress("moving", i as u64, todo.len() as u64, from);
+
+            match move_file(from, to) {
+                Ok(()) => {
+                    let line = serde_json::to_string(&JournalEntry {
+                        from: item.from.clone(),
+                        to: item.to.clone(),
+                    })
+                    .unwrap_or_default();
+                    if let Err(e) = writeln!(journal, "{}", line).and_then(|_| journal.flush()) {
+                        tracing::warn!("[OrganizeMedia] Failed to write undo journal: {e}");
+                    }
+                    done.push((from.to_path_buf(), to.to_path_buf()));
+                }
+                Err(error) => failed.push(FileOpFailure::new(item.from.as_str(), error)),
+            }
+        }
+        done
+    }
+
+    fn progress(&mut self, phase: &'static str, done: u64, total: u64, current: &Path) {
+        if self.last_emit.elapsed() < Duration::from_millis(100) {
+            return;
+        }
+        self.last_emit = Instant::now();
+        let _ = self.app.emit(
+            "fu:organize_media_progress",
+            OrganizeProgress {
+                op_id: self.op_id.to_string(),
+                phase,
+                done,
+                total,
+                current_file: current.to_string_lossy().to_string(),
+            },
+        );
+    }
+}
+
+/// "{YYYY}/{MM}" -> "2024/05". Errors name the bad token or folder name.
+fn expand_pattern(pattern: &str, date: &NaiveDateTime) -> Result<PathBuf, String> {
+    let mut text = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed token in pattern {:?}", pattern))?;
+        let value = match &rest[start + 1..start + end] {
+            "YYYY" => format!("{:04}", date.year()),
+            "YY" => format!("{:02}", date.year() % 100),
+            "MM" => format!("{:02}", date.month()),
+            "DD" => format!("{:02}", date.day()),
+            other => return Err(format!("Unknown token {{{}}} in pattern", other)),
+        };
+        text.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let mut path = PathBuf::new();
+    for part in text.split(['/', '\\']).filter(|p| !p.is_empty()) {
+        if part == "." || part == ".." {
+            return Err(format!("Pattern may not contain {:?}", part));
+        }
+        validate_name(part)?;
+        path.push(part);
+    }
+    if path.as_os_str().is_empty() {
+        return Err("Pattern is empty".to_string());
+    }
+    Ok(path)
+}
+
+/// Like file_ops::unique_path, but also skipping names already planned.
+fn free_name(path: &Path, taken: &HashSet<PathBuf>) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1u32..)
+        .map(|n| parent.join(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| !taken.contains(candidate) && fs::symlink_metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Rename without replacing: a file that appeared at `to` since planning
+/// is reported, not overwritten.
+fn move_file(from: &Path, to: &Path) -> Result<(), AppError> {
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(AppError::new(ErrorCode::AlreadyExists, "Target appeared since planning").with_path(to));
+    }
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e).with_path(parent))?;
+    }
+    fs::rename(from, to).map_err(|e| AppError::io(&e).with_path(from))
+}
+
+fn undo_move(entry: &JournalEntry) -> Result<(), AppError> {
+    let (from, to) = (Path::new(&entry.from), Path::new(&entry.to));
+    if fs::symlink_metadata(from).is_ok() {
+        return Err(AppError::new(ErrorCode::AlreadyExists, "Original location is taken again").with_path(from));
+    }
+    move_file(to, from)?;
+    // Remove date folders left empty, up to the first one that also holds
+    // the original location.
+    for dir in to.ancestors().skip(1) {
+        if from.starts_with(dir) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn journal_path(app: &AppHandle, op_id: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data dir error: {}", e))?;
+    Ok(dir.join(JOURNAL_DIR).join(format!("{}.jsonl", op_file_stem(op_id))))
+}
+
+/// Truncates: an op id reused for a new run starts a new journal.
+fn open_journal(path: &Path) -> std::io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).write(true).truncate(true).open(path)
+}
+
+/// A torn last line (crash mid-write) is ignored.
+fn read_journal(path: &Path) -> std::io::Result<Vec<JournalEntry>> {
+    let file = File::open(path)?;
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        match serde_json::from_str(&line?) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => tracing::warn!("[OrganizeMedia] Bad journal line in {:?}: {e}", path),
+        }
+    }
+    Ok(entries)
+}
+
+fn resolved_pairs(moves: &[(PathBuf, PathBuf)]) -> Vec<(PathBuf, PathBuf)> {
+    moves
+        .iter()
+        .filter_map(|(from, to)| Some((resolve(from).ok()?, resolve(to).ok()?)))
+        .collect()
+}
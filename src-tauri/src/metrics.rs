@@ -1,9 +1,26 @@
 use serde::Serialize;
-use std::{cmp::max, thread, time::Duration};
-use tauri::{AppHandle, Emitter};
-use sysinfo::{CpuExt, DiskExt, System, SystemExt};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tauri::{AppHandle, Emitter, State};
+use sysinfo::{
+    ComponentExt, CpuExt, DiskExt, DiskKind, NetworkExt, NetworksExt, ProcessExt,
+    ProcessRefreshKind, System, SystemExt,
+};
 
-use crate::settings::SystemSettings;
+use crate::error::AppError;
+use crate::network_share;
+use crate::path_guard::PathGuard;
+use crate::power::{read_power_state, PowerState, POWER_INTERVAL};
+use crate::settings::{SettingsState, SharedSystemSettings, SystemSettings};
+
+/// Longest single sleep; keeps interval changes from settings responsive.
+const MAX_SLEEP_SLICE_MS: u64 = 250;
 
 #[derive(Serialize, Clone)]
 pub struct DiskUsage {
@@ -17,22 +34,141 @@ pub struct SystemMetrics {
     pub mem_used: u64,    // bytes 
     pub mem_total: u64,   // bytes
     // max % may be  None, if no disk
+    // (kept for older frontends; `disks` has the full picture)
     pub disk_max: Option<DiskUsage>,
+    // refreshed every disk_check_interval_sec, cached in between
+    pub disks: Vec<DiskInfo>,
+    // None until two samples exist
+    pub disk_io: Option<DiskIoRates>,
+    // None when disabled in settings (system.network_metrics_enabled)
+    pub network: Option<NetworkMetrics>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub kind: String, // "ssd" | "hdd" | "unknown"
+    pub is_removable: bool,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub used_percent: f32,
+}
+
+// System-wide read/write rates (sum over all processes), averaged over
+// the last disk check interval. Not split per disk.
+#[derive(Serialize, Clone)]
+pub struct DiskIoRates {
+    pub read_bytes_per_sec: u64,
+    pub write_bytes_per_sec: u64,
+}
+
+// One hardware sensor, payload item of `system://temperatures`.
+#[derive(Serialize, Clone)]
+pub struct TemperatureReading {
+    pub label: String,
+    pub kind: String, // "cpu" | "disk" | "other"
+    pub celsius: f32,
+    pub max_celsius: f32,
+    pub critical_celsius: Option<f32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct InterfaceThroughput {
+    pub name: String,
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct NetworkMetrics {
+    // sum over all interfaces
+    pub rx_bytes_per_sec: u64,
+    pub tx_bytes_per_sec: u64,
+    pub interfaces: Vec<InterfaceThroughput>,
+}
+
+/// Run state of the metrics thread, shared with the pause/resume/stop
+/// commands. Managed in lib.rs: app.manage(MetricsControl::default())
+///
+/// Each loop instance remembers the generation it was started with and
+/// exits as soon as the generation moves on (stop, or stop + resume
+/// before the old thread noticed), so at most one loop ever samples.
+#[derive(Clone, Default)]
+pub struct MetricsControl {
+    paused: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    generation: Arc<AtomicU64>,
+    // last emitted sample, for consumers that can't wait for an event
+    latest: Arc<Mutex<Option<SystemMetrics>>>,
+}
+
+impl MetricsControl {
+    fn status(&self) -> &'static str {
+        if self.stopped.load(Ordering::SeqCst) {
+            "stopped"
+        } else if self.paused.load(Ordering::SeqCst) {
+            "paused"
+        } else {
+            "running"
+        }
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Most recent `system://metrics` payload (None before the first tick).
+    pub fn latest(&self) -> Option<SystemMetrics> {
+        self.latest.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
 }
 
-pub fn start_metrics_loop(app: AppHandle, settings: SystemSettings) {
+/// Intervals are re-read from `settings` on every tick, so changes saved
+/// through the settings commands apply without restarting the loop.
+pub fn start_metrics_loop(app: AppHandle, settings: SharedSystemSettings, control: MetricsControl) {
+    let generation = control.generation.load(Ordering::SeqCst);
     thread::spawn(move || {
         let mut sys = System::new_all();
 
-        let mut ticks: u64 = 0;
-        let step_ms = settings.cpu_mem_interval_ms.max(250);
-        let disk_interval_ms = settings.disk_check_interval_sec.max(1) * 1_000;
-        let disk_interval_ticks = max(1, disk_interval_ms / step_ms);
-
      // cache the last value across disks to avoid tugging disks every tick  
         let mut last_disk_max: Option<DiskUsage> = None;
+        let mut last_disks: Vec<DiskInfo> = Vec::new();
+        let mut last_disk_io: Option<DiskIoRates> = None;
+        let mut last_disk_check: Option<Instant> = None;
+        // None = network sampling not primed (first tick or just re-enabled)
+        let mut last_net_refresh: Option<Instant> = None;
+        let mut last_power: Option<PowerState> = None;
+        let mut last_power_check: Option<Instant> = None;
+        // None = sensors not listed yet (first use or just re-enabled)
+        let mut last_temperature_check: Option<Instant> = None;
 
         loop {
+            if !control.is_current(generation) {
+                break;
+            }
+            if control.paused.load(Ordering::SeqCst) {
+                // Re-prime rates after resume instead of averaging over the pause.
+                last_net_refresh = None;
+                thread::sleep(Duration::from_millis(MAX_SLEEP_SLICE_MS));
+                continue;
+            }
+
+            let (step_ms, disk_interval) = intervals(&settings);
+            let (network_enabled, temperatures) = {
+                let s = settings.read().unwrap_or_else(|e| e.into_inner());
+                (
+                    s.network_metrics_enabled,
+                    s.temperature_metrics_enabled
+                        .then(|| Duration::from_secs(s.temperature_interval_sec.max(1))),
+                )
+            };
+
             // ==== CPU + RAM ====
             sys.refresh_cpu();
             sys.refresh_memory();
@@ -44,59 +180,256 @@ pub fn start_metrics_loop(app: AppHandle, settings: SystemSettings) {
             let mem_used = mem_used_kib * 1024;
             let mem_total = mem_total_kib * 1024;
 
-       // ==== Disk (every N ticks) ====
-            if ticks % disk_interval_ticks == 0 {
+       // ==== Disk (every disk_check_interval_sec) ====
+            if last_disk_check.is_none_or(|t| t.elapsed() >= disk_interval) {
+                let previous_check = last_disk_check.replace(Instant::now());
                 sys.refresh_disks_list();
                 sys.refresh_disks();
 
-                let mut best: Option<DiskUsage> = None;
-
-                for disk in sys.disks() {
-                    let total = disk.total_space() as f32;
-                    let avail = disk.available_space() as f32;
-                    if total <= 0.0 {
-                        continue;
-                    }
-                    let used = total - avail;
-                    let used_percent = (used / total) * 100.0;
-
-                    let mp = disk
-                        .mount_point()
-                        .to_string_lossy()
-                        .to_string();
-
-                    match &best {
-                        Some(current) if current.used_percent >= used_percent => {}
-                        _ => {
-                            best = Some(DiskUsage {
-                                mount_point: mp,
-                                used_percent,
-                            });
-                        }
-                    }
-                }
-
-                last_disk_max = best;
+                last_disks = collect_disks(&sys);
+                last_disk_max = last_disks
+                    .iter()
+                    .max_by(|a, b| a.used_percent.total_cmp(&b.used_percent))
+                    .map(|d| DiskUsage {
+                        mount_point: d.mount_point.clone(),
+                        used_percent: d.used_percent,
+                    });
+                last_disk_io = sample_disk_io(&mut sys, previous_check);
             }
 
+            // ==== Network ====
+            let network = if network_enabled {
+                sample_network(&mut sys, &mut last_net_refresh)
+            } else {
+                last_net_refresh = None;
+                None
+            };
+
             let metrics = SystemMetrics {
                 cpu_total,
                 mem_used,
                 mem_total,
                 disk_max: last_disk_max.clone(),
+                disks: last_disks.clone(),
+                disk_io: last_disk_io.clone(),
+                network,
             };
 
+            *control.latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(metrics.clone());
+
             if app.emit("system://metrics", &metrics).is_err() {
                 // If the window layer is gone, exit the loop gracefully.
+                if control.is_current(generation) {
+                    control.stopped.store(true, Ordering::SeqCst);
+                }
                 break;
             }
 
-            ticks = ticks.wrapping_add(1);
-            thread::sleep(Duration::from_millis(step_ms));
+            // ==== Power (own event, only when it changed) ====
+            if last_power_check.is_none_or(|t| t.elapsed() >= POWER_INTERVAL) {
+                last_power_check = Some(Instant::now());
+                let power = read_power_state();
+                if last_power.as_ref() != Some(&power) {
+                    let _ = app.emit("system://power", &power);
+                    last_power = Some(power);
+                }
+            }
+
+            // ==== Temperatures (opt-in, own event and interval) ====
+            match temperatures {
+                Some(interval) => {
+                    if last_temperature_check.is_none_or(|t| t.elapsed() >= interval) {
+                        if last_temperature_check.is_none() {
+                            sys.refresh_components_list();
+                        } else {
+                            sys.refresh_components();
+                        }
+                        last_temperature_check = Some(Instant::now());
+                        let _ = app.emit("system://temperatures", collect_temperatures(&sys));
+                    }
+                }
+                None => last_temperature_check = None,
+            }
+
+            sleep_until_next_tick(&settings, step_ms);
         }
     });
 }
 
+fn collect_disks(sys: &System) -> Vec<DiskInfo> {
+    sys.disks()
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .map(|disk| {
+            let total = disk.total_space();
+            let free = disk.available_space();
+            DiskInfo {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+                kind: match disk.kind() {
+                    DiskKind::SSD => "ssd",
+                    DiskKind::HDD => "hdd",
+                    _ => "unknown",
+                }
+                .to_string(),
+                is_removable: disk.is_removable(),
+                total_bytes: total,
+                free_bytes: free,
+                used_percent: (total - free.min(total)) as f32 / total as f32 * 100.0,
+            }
+        })
+        .collect()
+}
+
+fn collect_temperatures(sys: &System) -> Vec<TemperatureReading> {
+    sys.components()
+        .iter()
+        .filter(|c| c.temperature().is_finite())
+        .map(|c| {
+            let label = c.label().to_string();
+            TemperatureReading {
+                kind: sensor_kind(&label).to_string(),
+                label,
+                celsius: c.temperature(),
+                max_celsius: c.max(),
+                critical_celsius: c.critical(),
+            }
+        })
+        .collect()
+}
+
+/// Best-effort grouping by sensor label; names differ per OS and driver
+/// (coretemp/k10temp "Package id 0", "Tctl", "nvme Composite", ...).
+fn sensor_kind(label: &str) -> &'static str {
+    let l = label.to_ascii_lowercase();
+    if ["cpu", "core", "package", "tctl", "tdie", "k10temp", "coretemp"]
+        .iter()
+        .any(|k| l.contains(k))
+    {
+        "cpu"
+    } else if ["nvme", "ssd", "hdd", "disk", "drive", "sata"]
+        .iter()
+        .any(|k| l.contains(k))
+    {
+        "disk"
+    } else {
+        "other"
+    }
+}
+
+/// Sum of per-process disk I/O since the previous disk check.
+/// Returns None for the first sample (counters cover the whole uptime).
+fn sample_disk_io(sys: &mut System, previous_check: Option<Instant>) -> Option<DiskIoRates> {
+    sys.refresh_processes_specifics(ProcessRefreshKind::new().with_disk_usage());
+    let previous = previous_check?;
+
+    let secs = previous.elapsed().as_secs_f64().max(0.001);
+    let (read, written) = sys
+        .processes()
+        .values()
+        .map(|p| p.disk_usage())
+        .fold((0u64, 0u64), |(r, w), u| (r + u.read_bytes, w + u.written_bytes));
+
+    Some(DiskIoRates {
+        read_bytes_per_sec: (read as f64 / secs) as u64,
+        write_bytes_per_sec: (written as f64 / secs) as u64,
+    })
+}
+
+/// Rates since the previous refresh. The first call after (re-)enabling
+/// only primes the counters and returns None.
+fn sample_network(sys: &mut System, last_refresh: &mut Option<Instant>) -> Option<NetworkMetrics> {
+    let previous = last_refresh.replace(Instant::now());
+    let Some(previous) = previous else {
+        sys.refresh_networks_list();
+        return None;
+    };
+    sys.refresh_networks();
+
+    let secs = previous.elapsed().as_secs_f64().max(0.001);
+    let per_sec = |bytes: u64| (bytes as f64 / secs) as u64;
+
+    let mut interfaces: Vec<InterfaceThroughput> = sys
+        .networks()
+        .iter()
+        .map(|(name, data)| InterfaceThroughput {
+            name: name.clone(),
+            rx_bytes_per_sec: per_sec(data.received()),
+            tx_bytes_per_sec: per_sec(data.transmitted()),
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Some(NetworkMetrics {
+        rx_bytes_per_sec: interfaces.iter().map(|i| i.rx_bytes_per_sec).sum(),
+        tx_bytes_per_sec: interfaces.iter().map(|i| i.tx_bytes_per_sec).sum(),
+        interfaces,
+    })
+}
+
+/// Current (tick, disk check) intervals with the same lower bounds as before.
+fn intervals(settings: &SharedSystemSettings) -> (u64, Duration) {
+    let s: SystemSettings = settings.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let step_ms = s.cpu_mem_interval_ms.max(250);
+    let disk_interval = Duration::from_secs(s.disk_check_interval_sec.max(1));
+    (step_ms, disk_interval)
+}
+
+/// Sleep `step_ms` in short slices; returns early if the interval was
+/// shortened meanwhile so e.g. 60s -> 1s takes effect right away.
+fn sleep_until_next_tick(settings: &SharedSystemSettings, step_ms: u64) {
+    let started = Instant::now();
+    loop {
+        let (current_ms, _) = intervals(settings);
+        let target = Duration::from_millis(current_ms.min(step_ms));
+        let elapsed = started.elapsed();
+        if elapsed >= target {
+            return;
+        }
+        let remaining = target - elapsed;
+        thread::sleep(remaining.min(Duration::from_millis(MAX_SLEEP_SLICE_MS)));
+    }
+}
+
+/// Stop sampling but keep the thread (cheap to resume).
+///
+/// Frontend can call:
+///   invoke<string>('pause_metrics')   // -> "paused" | "stopped"
+#[tauri::command]
+pub fn pause_metrics(control: State<'_, MetricsControl>) -> String {
+    control.paused.store(true, Ordering::SeqCst);
+    control.status().to_string()
+}
+
+/// Resume after pause_metrics, or start a fresh loop after stop_metrics.
+///
+/// Frontend can call:
+///   invoke<string>('resume_metrics')  // -> "running"
+#[tauri::command]
+pub fn resume_metrics(
+    app: AppHandle,
+    control: State<'_, MetricsControl>,
+    settings: State<'_, SettingsState>,
+) -> String {
+    control.paused.store(false, Ordering::SeqCst);
+    if control.stopped.swap(false, Ordering::SeqCst) {
+        start_metrics_loop(app, settings.system_handle(), control.inner().clone());
+    }
+    control.status().to_string()
+}
+
+/// End the metrics thread entirely (e.g. dashboard closed).
+///
+/// Frontend can call:
+///   invoke<string>('stop_metrics')    // -> "stopped"
+#[tauri::command]
+pub fn stop_metrics(control: State<'_, MetricsControl>) -> String {
+    control.stopped.store(true, Ordering::SeqCst);
+    control.generation.fetch_add(1, Ordering::SeqCst);
+    control.status().to_string()
+}
+
 /// Get free space on the disk containing the given path
 #[derive(Serialize, Clone)]
 pub struct DiskSpaceInfo {
@@ -105,26 +438,40 @@ pub struct DiskSpaceInfo {
     pub mount_point: String,
 }
 
-#[tauri::command]
-pub fn get_disk_free_space(path: String) -> Result<DiskSpaceInfo, String> {
-    use std::path::Path;
-    
-    let target_path = Path::new(&path);
-    
-    // Normalize to get the drive/mount point
-    let target_str = target_path.to_string_lossy().to_uppercase();
-    
+/// Space of the disk whose mount point is the longest prefix of `path`
+/// (the path itself need not exist yet). None if no disk matches.
+pub fn disk_space(path: &std::path::Path) -> Option<DiskSpaceInfo> {
+    disk_spaces(&[path]).pop().flatten()
+}
+
+/// disk_space() for several paths with a single disk refresh.
+pub fn disk_spaces(paths: &[&std::path::Path]) -> Vec<Option<DiskSpaceInfo>> {
     let mut sys = System::new();
     sys.refresh_disks_list();
     sys.refresh_disks();
-    
+    paths.iter().map(|path| disk_for(&sys, path)).collect()
+}
+
+fn disk_for(sys: &System, path: &std::path::Path) -> Option<DiskSpaceInfo> {
+    // Shares aren't in sysinfo's disk list; ask the share itself.
+    if let Some(share) = network_share::space(path) {
+        return Some(DiskSpaceInfo {
+            free_bytes: share.free_bytes,
+            total_bytes: share.total_bytes,
+            mount_point: share.mount_point,
+        });
+    }
+
+    // Normalize to get the drive/mount point
+    let target_str = path.to_string_lossy().to_uppercase();
+
     // Find the disk that contains this path
     let mut best_match: Option<DiskSpaceInfo> = None;
     let mut best_match_len = 0;
-    
+
     for disk in sys.disks() {
         let mount = disk.mount_point().to_string_lossy().to_uppercase();
-        
+
         // Check if target path starts with this mount point
         if target_str.starts_with(&mount) && mount.len() > best_match_len {
             best_match_len = mount.len();
@@ -135,6 +482,25 @@ pub fn get_disk_free_space(path: String) -> Result<DiskSpaceInfo, String> {
             });
         }
     }
-    
-    best_match.ok_or_else(|| format!("Could not find disk for path: {}", path))
+    best_match
+}
+
+/// Fail fast with INSUFFICIENT_SPACE when writing `required` bytes under
+/// `destination` cannot fit. Unknown disks pass (the write reports DISK_FULL
+/// if it comes to that).
+pub fn ensure_free_space(destination: &std::path::Path, required: u64) -> Result<(), AppError> {
+    match disk_space(destination) {
+        Some(space) if space.free_bytes < required => {
+            Err(AppError::insufficient_space(required, space.free_bytes).with_path(destination))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_disk_free_space(
+    guard: State<'_, PathGuard>,
+    path: String,
+) -> Result<DiskSpaceInfo, String> {
+    disk_space(&guard.check(&path)?).ok_or_else(|| format!("Could not find disk for path: {}", path))
 }
@@ -0,0 +1,469 @@
+// src-tauri/src/network_share.rs
+//
+// Network share (SMB / UNC) awareness:
+//   - unc_share / unc_server: "\\server\share\..." (also "//server/share"
+//     and the canonical "\\?\UNC\server\share") and bare "\\server".
+//   - is_auth_error: OS errors that mean "sign in to the share first"; they
+//     become NETWORK_AUTH_REQUIRED (error.rs) with `share` set, so the UI can
+//     ask for credentials and call connect_network_share.
+//   - space: free / total space of a share. sysinfo only knows local disks,
+//     so get_disk_free_space and the copy engine's free-space check ask the
+//     share itself (GetDiskFreeSpaceEx honours quotas; statvfs elsewhere).
+//     On Windows mapped drive letters count as shares too.
+//   - list_shares: list_dir on a bare "\\server" lists its disk shares
+//     (Windows only).
+//
+// connect_network_share(unc, credentials):
+//   - Windows: WNetAddConnection2 (session only, no drive letter); the share
+//     is browsed by its UNC path.
+//   - Linux:   `gio mount smb://server/share` (credentials on stdin); the
+//     share is browsed under $XDG_RUNTIME_DIR/gvfs/.
+//   - macOS:   AppleScript `mount volume` (script on stdin); the share is
+//     browsed under /Volumes.
+// Passwords never end up on a command line.
+
+use std::io;
+use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode};
+
+/// Free / total bytes of a share, and what to show as its mount point.
+pub struct ShareSpace {
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub mount_point: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareCredentials {
+    pub username: String,
+    pub password: String,
+    /// Windows domain / SMB workgroup.
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedShare {
+    /// "\\server\share"
+    pub unc: String,
+    /// Where to browse the share: the UNC path on Windows, the mount
+    /// elsewhere.
+    pub path: String,
+}
+
+/// Server and share of a UNC path; None for anything else.
+fn unc_parts(path: &str) -> Option<(&str, &str)> {
+    let rest = path
+        .strip_prefix(r"\\?\UNC\")
+        .or_else(|| path.strip_prefix(r"\\"))
+        .or_else(|| path.strip_prefix("//"))?;
+    if rest.starts_with(['?', '.']) {
+        // \\?\C:\ and \\.\device paths are local.
+        return None;
+    }
+    let mut parts = rest.split(['\\', '/']);
+    let server = parts.next().filter(|s| !s.is_empty())?;
+    let share = parts.next().unwrap_or("");
+    Some((server, share))
+}
+
+/// "\\server\share" for a path on a UNC share.
+pub fn unc_share(path: &str) -> Option<String> {
+    match unc_parts(path)? {
+        (_, "") => None,
+        (server, share) => Some(format!(r"\\{}\{}", server, share)),
+    }
+}
+
+/// `server` when `path` names just a server ("\\server" or "\\server\").
+pub fn unc_server(path: &str) -> Option<&str> {
+    match unc_parts(path)? {
+        (server, "") => Some(server),
+        _ => None,
+    }
+}
+
+/// The share a path lives on: its UNC share or, on Windows, the share
+/// behind a mapped drive letter.
+pub fn share_of(path: &Path) -> Option<String> {
+    let text = path.to_string_lossy();
+    unc_share(&text).or_else(|| mapped_drive(&text).map(|(_, share)| share))
+}
+
+/// OS errors that mean the share wants (other) credentials.
+pub fn is_auth_error(e: &io::Error) -> bool {
+    #[cfg(windows)]
+    const AUTH_ERRORS: &[i32] = &[
+        86,   // ERROR_INVALID_PASSWORD
+        1219, // ERROR_SESSION_CREDENTIAL_CONFLICT
+        1244, // ERROR_NOT_AUTHENTICATED
+        1326, // ERROR_LOGON_FAILURE
+        1327, // ERROR_ACCOUNT_RESTRICTION
+        1330, // ERROR_PASSWORD_EXPIRED
+        1331, // ERROR_ACCOUNT_DISABLED
+        1385, // ERROR_LOGON_TYPE_NOT_GRANTED
+        1907, // ERROR_PASSWORD_MUST_CHANGE
+        1909, // ERROR_ACCOUNT_LOCKED_OUT
+    ];
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const AUTH_ERRORS: &[i32] = &[libc::ENOKEY, libc::EKEYEXPIRED, libc::EKEYREJECTED];
+    #[cfg(target_os = "macos")]
+    const AUTH_ERRORS: &[i32] = &[libc::EAUTH, libc::ENEEDAUTH];
+    #[cfg(not(any(windows, target_os = "linux", target_os = "android", target_os = "macos")))]
+    const AUTH_ERRORS: &[i32] = &[];
+
+    e.raw_os_error().is_some_and(|code| AUTH_ERRORS.contains(&code))
+}
+
+/// For a path the sandbox couldn't resolve: the share, when that is
+/// because it wants credentials.
+pub fn auth_required(path: &Path) -> Option<String> {
+    let share = share_of(path)?;
+    match std::fs::metadata(&share) {
+        Err(e) if is_auth_error(&e) => Some(share),
+        _ => None,
+    }
+}
+
+/// Nearest existing folder at or above `path` (copy destinations may not
+/// exist yet).
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.is_dir())
+}
+
+/// Space of the share holding `path`; None for local paths.
+#[cfg(windows)]
+pub fn space(path: &Path) -> Option<ShareSpace> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let text = path.to_string_lossy();
+    let mount_point = match mapped_drive(&text) {
+        Some((drive, _)) => drive,
+        None => unc_share(&text)?,
+    };
+    let dir = existing_ancestor(path)?;
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain([0]).collect();
+    let (mut free, mut total) = (0u64, 0u64);
+    // SAFETY: valid NUL-terminated path; the out pointers are live locals.
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free, &mut total, std::ptr::null_mut()) };
+    (ok != 0).then_some(ShareSpace {
+        free_bytes: free,
+        total_bytes: total,
+        mount_point,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn space(path: &Path) -> Option<ShareSpace> {
+    use std::os::unix::fs::MetadataExt;
+
+    const SMB_MAGIC: u32 = 0x517B;
+    const CIFS_MAGIC: u32 = 0xFF53_4D42;
+    const SMB2_MAGIC: u32 = 0xFE53_4D42;
+    const NFS_MAGIC: u32 = 0x6969;
+    const FUSE_MAGIC: u32 = 0x6573_5546;
+
+    let dir = existing_ancestor(path)?;
+    let c_path = std::ffi::CString::new(dir.as_os_str().as_encoded_bytes()).ok()?;
+    // SAFETY: valid C string; the out struct is a live local.
+    let mut fs: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut fs) } != 0 {
+        return None;
+    }
+    let magic = fs.f_type as u32;
+    let gvfs_smb = magic == FUSE_MAGIC && dir.to_string_lossy().contains("/gvfs/smb-share:");
+    if !gvfs_smb && ![SMB_MAGIC, CIFS_MAGIC, SMB2_MAGIC, NFS_MAGIC].contains(&magic) {
+        return None;
+    }
+    let (free_bytes, total_bytes) = statvfs_space(&c_path)?;
+    // The mount point is the topmost folder still on the same device.
+    let dev = std::fs::metadata(dir).ok()?.dev();
+    let mount: PathBuf = dir
+        .ancestors()
+        .take_while(|p| std::fs::metadata(p).is_ok_and(|m| m.dev() == dev))
+        .last()
+        .unwrap_or(dir)
+        .to_path_buf();
+    Some(ShareSpace {
+        free_bytes,
+        total_bytes,
+        mount_point: mount.to_string_lossy().to_string(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+pub fn space(path: &Path) -> Option<ShareSpace> {
+    use std::ffi::CStr;
+
+    let dir = existing_ancestor(path)?;
+    let c_path = std::ffi::CString::new(dir.as_os_str().as_encoded_bytes()).ok()?;
+    // SAFETY: valid C string; the out struct is a live local.
+    let mut fs: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut fs) } != 0 {
+        return None;
+    }
+    // SAFETY: both names are NUL-terminated by the kernel.
+    let fs_type = unsafe { CStr::from_ptr(fs.f_fstypename.as_ptr()) }.to_string_lossy();
+    if !["smbfs", "nfs", "afpfs", "webdav"].contains(&fs_type.as_ref()) {
+        return None;
+    }
+    let mount = unsafe { CStr::from_ptr(fs.f_mntonname.as_ptr()) }.to_string_lossy().to_string();
+    let (free_bytes, total_bytes) = statvfs_space(&c_path)?;
+    Some(ShareSpace {
+        free_bytes,
+        total_bytes,
+        mount_point: mount,
+    })
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "android", target_os = "macos")))]
+pub fn space(_path: &Path) -> Option<ShareSpace> {
+    None
+}
+
+/// (available to us, total) bytes.
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)] // the field types differ per platform
+fn statvfs_space(c_path: &std::ffi::CStr) -> Option<(u64, u64)> {
+    // SAFETY: valid C string; the out struct is a live local.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let block = u64::from(stat.f_frsize);
+    Some((u64::from(stat.f_bavail) * block, u64::from(stat.f_blocks) * block))
+}
+
+/// ("Z:\", "\\server\share") when `path` is on a mapped network drive.
+#[cfg(windows)]
+fn mapped_drive(path: &str) -> Option<(String, String)> {
+    use windows_sys::Win32::NetworkManagement::WNet::WNetGetConnectionW;
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    /// GetDriveTypeW result for network drives.
+    const DRIVE_REMOTE: u32 = 4;
+
+    let path = path.strip_prefix(r"\\?\").unwrap_or(path);
+    let letter = path.chars().next().filter(|c| c.is_ascii_alphabetic())?;
+    if path.get(1..2) != Some(":") {
+        return None;
+    }
+    let root: Vec<u16> = format!("{}:\\", letter).encode_utf16().chain([0]).collect();
+    // SAFETY: valid NUL-terminated string.
+    if unsafe { GetDriveTypeW(root.as_ptr()) } != DRIVE_REMOTE {
+        return None;
+    }
+    let local: Vec<u16> = format!("{}:", letter).encode_utf16().chain([0]).collect();
+    let mut buf = vec![0u16; 1024];
+    let mut len = buf.len() as u32;
+    // SAFETY: buf holds len UTF-16 units.
+    if unsafe { WNetGetConnectionW(local.as_ptr(), buf.as_mut_ptr(), &mut len) } != 0 {
+        return None;
+    }
+    let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    Some((format!("{}:\\", letter), String::from_utf16_lossy(&buf[..end])))
+}
+
+#[cfg(not(windows))]
+fn mapped_drive(_path: &str) -> Option<(String, String)> {
+    None
+}
+
+/// Disk shares of `server`, without the hidden / admin ones (C$, IPC$).
+#[cfg(windows)]
+pub fn list_shares(server: &str) -> Result<Vec<String>, AppError> {
+    use windows_sys::Win32::NetworkManagement::NetManagement::{NetApiBufferFree, MAX_PREFERRED_LENGTH};
+    use windows_sys::Win32::Storage::FileSystem::{NetShareEnum, SHARE_INFO_1, STYPE_DISKTREE, STYPE_SPECIAL};
+
+    let name: Vec<u16> = format!(r"\\{}", server).encode_utf16().chain([0]).collect();
+    let mut buf: *mut u8 = std::ptr::null_mut();
+    let (mut read, mut total) = (0u32, 0u32);
+    // SAFETY: out pointers are live locals; buf is freed below.
+    let rc = unsafe {
+        NetShareEnum(name.as_ptr(), 1, &mut buf, MAX_PREFERRED_LENGTH, &mut read, &mut total, std::ptr::null_mut())
+    };
+    if rc != 0 {
+        let e = io::Error::from_raw_os_error(rc as i32);
+        return Err(AppError::io(&e).context(&format!("Cannot list shares of {}", server)).with_path(format!(r"\\{}", server)));
+    }
+    let mut shares = Vec::new();
+    if !buf.is_null() {
+        // SAFETY: NetShareEnum returned `read` SHARE_INFO_1 records in buf.
+        let infos = unsafe { std::slice::from_raw_parts(buf as *const SHARE_INFO_1, read as usize) };
+        for info in infos {
+            if (info.shi1_type & STYPE_SPECIAL) != 0 || (info.shi1_type & 0xFF) != STYPE_DISKTREE {
+                continue;
+            }
+            // SAFETY: shi1_netname is a NUL-terminated string inside buf.
+            let len = (0..).take_while(|&i| unsafe { *info.shi1_netname.add(i) } != 0).count();
+            let netname = unsafe { std::slice::from_raw_parts(info.shi1_netname, len) };
+            shares.push(String::from_utf16_lossy(netname));
+        }
+        // SAFETY: allocated by NetShareEnum.
+        unsafe { NetApiBufferFree(buf as *const _) };
+    }
+    shares.sort_by_key(|s| s.to_lowercase());
+    Ok(shares)
+}
+
+#[cfg(not(windows))]
+pub fn list_shares(server: &str) -> Result<Vec<String>, AppError> {
+    Err(AppError::invalid_input(format!(
+        "Listing the shares of {} is only supported on Windows; connect a share with connect_network_share",
+        server
+    )))
+}
+
+/// Connect to an SMB share, with credentials or as the current user.
+/// A failed sign-in gives NETWORK_AUTH_REQUIRED.
+///
+/// Frontend can call (e.g. after NETWORK_AUTH_REQUIRED, with err.share):
+///   invoke<{ unc: string, path: string }>('connect_network_share', {
+///     unc: '\\\\nas\\photos',
+///     credentials: { username: 'me', password: '…', domain: 'WORKGROUP' },
+///   })
+/// Browse `path` afterwards; outside the allowed roots it is rejected like
+/// any other folder.
+#[tauri::command]
+pub async fn connect_network_share(
+    unc: String,
+    credentials: Option<ShareCredentials>,
+) -> Result<ConnectedShare, AppError> {
+    let share = unc_share(&unc).ok_or_else(|| AppError::invalid_input(format!("Not a share path (\\\\server\\share): {}", unc)))?;
+    tauri::async_runtime::spawn_blocking(move || connect(&share, credentials.as_ref()))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Connect task failed: {}", e)))?
+}
+
+#[cfg(windows)]
+fn connect(share: &str, credentials: Option<&ShareCredentials>) -> Result<ConnectedShare, AppError> {
+    use windows_sys::Win32::NetworkManagement::WNet::{
+        WNetAddConnection2W, CONNECT_TEMPORARY, NETRESOURCEW, RESOURCETYPE_DISK,
+    };
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain([0]).collect()
+    }
+
+    let mut remote = wide(share);
+    // SAFETY: zeroed is a valid NETRESOURCEW (all null / 0).
+    let mut resource: NETRESOURCEW = unsafe { std::mem::zeroed() };
+    resource.dwType = RESOURCETYPE_DISK;
+    resource.lpRemoteName = remote.as_mut_ptr();
+    let user = credentials.map(|c| match &c.domain {
+        Some(domain) if !domain.is_empty() => wide(&format!(r"{}\{}", domain, c.username)),
+        _ => wide(&c.username),
+    });
+    let password = credentials.map(|c| wide(&c.password));
+    let ptr = |s: &Option<Vec<u16>>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+    // SAFETY: every string is NUL-terminated and outlives the call.
+    let rc = unsafe { WNetAddConnection2W(&resource, ptr(&password), ptr(&user), CONNECT_TEMPORARY) };
+    if rc != 0 {
+        let e = io::Error::from_raw_os_error(rc as i32);
+        return Err(AppError::io(&e).context(&format!("Cannot connect to {}", share)).with_path(share));
+    }
+    Ok(ConnectedShare {
+        unc: share.to_string(),
+        path: share.to_string(),
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn connect(share: &str, credentials: Option<&ShareCredentials>) -> Result<ConnectedShare, AppError> {
+    let (server, name) = unc_parts(share).unwrap_or_default();
+    // gio asks for user, domain and password on stdin when it needs them.
+    let input = credentials.map(|c| {
+        format!("{}\n{}\n{}\n", c.username, c.domain.as_deref().unwrap_or(""), c.password)
+    });
+    run_with_stdin("gio", &["mount", &format!("smb://{}/{}", server, name)], input.as_deref().unwrap_or(""), share)?;
+
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).unwrap_or_else(|| {
+        // SAFETY: getuid has no preconditions.
+        PathBuf::from(format!("/run/user/{}", unsafe { libc::getuid() }))
+    });
+    let path = runtime.join("gvfs").join(format!(
+        "smb-share:server={},share={}",
+        server.to_lowercase(),
+        name.to_lowercase()
+    ));
+    Ok(ConnectedShare {
+        unc: share.to_string(),
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn connect(share: &str, credentials: Option<&ShareCredentials>) -> Result<ConnectedShare, AppError> {
+    fn quote(s: &str) -> String {
+        format!("\"{}\"", s.replace('\\', r"\\").replace('"', "\\\""))
+    }
+    let (server, name) = unc_parts(share).unwrap_or_default();
+    let url = quote(&format!("smb://{}/{}", server, name));
+    let script = match credentials {
+        Some(c) => {
+            let user = match &c.domain {
+                Some(domain) if !domain.is_empty() => format!("{};{}", domain, c.username),
+                _ => c.username.clone(),
+            };
+            format!("mount volume {} as user name {} with password {}\n", url, quote(&user), quote(&c.password))
+        }
+        None => format!("mount volume {}\n", url),
+    };
+    run_with_stdin("osascript", &[], &script, share)?;
+    Ok(ConnectedShare {
+        unc: share.to_string(),
+        path: Path::new("/Volumes").join(name).to_string_lossy().to_string(),
+    })
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "android", target_os = "macos")))]
+fn connect(share: &str, _credentials: Option<&ShareCredentials>) -> Result<ConnectedShare, AppError> {
+    Err(AppError::invalid_input(format!("Connecting network shares is not supported here: {}", share)))
+}
+
+/// Run a mount helper, feeding `input` on stdin. A failure that mentions
+/// the password / permissions gives NETWORK_AUTH_REQUIRED.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn run_with_stdin(program: &str, args: &[&str], input: &str, share: &str) -> Result<(), AppError> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::io(&e).context(&format!("Failed to run {}", program)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // It may exit without reading (already mounted); that's fine.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| AppError::io(&e).context(&format!("Failed to run {}", program)))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let lower = stderr.to_lowercase();
+    let code = if ["password", "permission denied", "authentication", "-5000"]
+        .iter()
+        .any(|m| lower.contains(m))
+    {
+        ErrorCode::NetworkAuthRequired
+    } else {
+        ErrorCode::Io
+    };
+    Err(AppError::new(code, format!("Cannot connect to {}", share))
+        .with_details(stderr)
+        .with_path(share))
+}
@@ -0,0 +1,125 @@
+// src-tauri/src/notifications.rs
+//
+// OS notifications (tauri-plugin-notification) when a long operation
+// finishes while nobody is looking: the window is unfocused, hidden in the
+// tray, or closed in background mode (tray.rs).
+//
+// Sources, all existing completion events:
+//   - folder_scan:     fu:folder_scan_completed
+//   - duplicate_scan:  fu:duplicate_scan_completed
+//   - sync:            fu:file_op_completed of a scheduled folder sync
+//                      (op id "schedule-...", scheduler.rs)
+//   - copy_move:       any other fu:file_op_completed
+//   - update_download: fu:update_downloaded (update scheduler), and manual
+//                      downloads via notify_update_downloaded
+// settings.system.notify_on lists the kinds that notify; cancelled
+// operations never do.
+
+use serde::Deserialize;
+use tauri::{AppHandle, Listener, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::settings::{NotifyKind, SettingsState};
+
+/// Fields shared by the *_completed payloads (camelCase).
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Completed {
+    op_id: String,
+    status: String,
+    kind: Option<String>,
+    file_count: u64,
+    files_done: u64,
+    group_count: u64,
+    error_message: Option<String>,
+}
+
+/// Subscribe to the completion events. Called once from setup.
+pub fn start_notifications(app: &AppHandle) {
+    listen(app, "fu:folder_scan_completed", |c| {
+        let body = format!("{} files", c.file_count);
+        (NotifyKind::FolderScan, "Folder scan finished", body)
+    });
+    listen(app, "fu:duplicate_scan_completed", |c| {
+        let body = format!("{} duplicate groups", c.group_count);
+        (NotifyKind::DuplicateScan, "Duplicate scan finished", body)
+    });
+    listen(app, "fu:file_op_completed", |c| {
+        let body = format!("{} files", c.files_done);
+        if c.op_id.starts_with("schedule-") {
+            (NotifyKind::Sync, "Folder sync finished", body)
+        } else if c.kind.as_deref() == Some("move") {
+            (NotifyKind::CopyMove, "Move finished", body)
+        } else {
+            (NotifyKind::CopyMove, "Copy finished", body)
+        }
+    });
+
+    let handle = app.clone();
+    app.listen_any("fu:update_downloaded", move |event| {
+        let version = serde_json::from_str::<serde_json::Value>(event.payload())
+            .ok()
+            .and_then(|v| v["version"].as_str().map(str::to_string))
+            .unwrap_or_default();
+        notify_update_downloaded(&handle, &version);
+    });
+}
+
+fn listen(
+    app: &AppHandle,
+    event: &'static str,
+    describe: impl Fn(&Completed) -> (NotifyKind, &'static str, String) + Send + 'static,
+) {
+    let handle = app.clone();
+    app.listen_any(event, move |e| {
+        let completed: Completed = match serde_json::from_str(e.payload()) {
+            Ok(c) => c,
+            Err(err) => {
+                tracing::warn!("[Notifications] Bad {event} payload: {err}");
+                return;
+            }
+        };
+        if completed.status == "cancelled" {
+            return;
+        }
+        let (kind, title, body) = describe(&completed);
+        let body = match (completed.status.as_str(), &completed.error_message) {
+            ("error", Some(message)) => format!("Failed: {}", message),
+            ("error", None) => "Failed".to_string(),
+            ("partial", _) => format!("{} (with errors)", body),
+            _ => body,
+        };
+        notify(&handle, kind, title, &body);
+    });
+}
+
+/// For downloads that didn't come from the update scheduler.
+pub fn notify_update_downloaded(app: &AppHandle, version: &str) {
+    notify(
+        app,
+        NotifyKind::UpdateDownload,
+        "Update downloaded",
+        &format!("Version {} is ready to install", version),
+    );
+}
+
+fn notify(app: &AppHandle, kind: NotifyKind, title: &str, body: &str) {
+    let enabled = app.try_state::<SettingsState>().is_some_and(|s| {
+        let system = s.system_handle();
+        let enabled = system.read().unwrap_or_else(|e| e.into_inner()).notify_on.contains(&kind);
+        enabled
+    });
+    if !enabled || user_is_watching(app) {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        tracing::warn!("[Notifications] Failed to show notification: {e}");
+    }
+}
+
+/// A visible, focused window; false when closed to the tray.
+fn user_is_watching(app: &AppHandle) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+}
@@ -0,0 +1,403 @@
+// src-tauri/src/op_journal.rs
+//
+// Crash-safe resume: every local copy / move job (including transfer queue
+// jobs and scheduled folder syncs) and every update download writes a
+// journal while it runs:
+//   <app data dir>/op-journals/<op id>.jsonl
+//
+//   - first line: what was started (sources, destination, conflict policy,
+//     copy options ... or the download's platform / channel)
+//   - `writing`: a file the job created and is filling right now
+//   - `done`: a source file that is fully copied (and verified, with
+//     `verify`), or was skipped as a conflict
+//
+// A job that ends in any way (ok, partial, error, cancelled) removes its
+// journal. One that is still there at startup was cut short by a crash or
+// a forced quit: list_interrupted_operations shows it,
+// resume_interrupted_operations starts it again under the same op id and
+// skips the files already done. The file that was being written is
+// removed first, so it is copied again from the start. For moves, sources
+// that are gone were moved already.
+//
+// Remote (vfs) jobs aren't journaled: their connections don't survive a
+// restart. Downloads resume by downloading again; the update client keeps
+// the partial file and continues it with a range request
+// (update/tuf_client.rs).
+//
+// Journal writes that fail are logged; they never fail the job itself.
+
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::{emit_not_started, start_file_op, ConflictPolicy, CopyOptions, FileOpKind, FileOpOptions};
+use crate::gps_backend::{op_file_stem, OperationRegistry};
+use crate::notifications;
+use crate::path_guard::PathGuard;
+use crate::update::{self, UpdateChannel};
+
+const JOURNAL_DIR: &str = "op-journals";
+
+/// Op id of the update download (there is only ever one).
+pub const UPDATE_DOWNLOAD_OP: &str = "update-download";
+
+/// Journals of operations running in this process; never "interrupted".
+static LIVE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// What is needed to start an operation again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ResumableOp {
+    #[serde(rename_all = "camelCase")]
+    FileOp {
+        kind: FileOpKind,
+        sources: Vec<String>,
+        destination: String,
+        conflict_policy: Option<ConflictPolicy>,
+        copy_options: CopyOptions,
+        /// Bytes per second, 0 = unlimited.
+        bandwidth_limit: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Download {
+        platform_id: String,
+        channel: Option<UpdateChannel>,
+    },
+}
+
+/// An operation that didn't finish in an earlier run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterruptedOperation {
+    pub op_id: String,
+    /// Milliseconds since UNIX_EPOCH.
+    pub started_at: u64,
+    #[serde(flatten)]
+    pub op: ResumableOp,
+    /// Files finished before the interruption; skipped on resume.
+    pub files_done: u64,
+}
+
+/// One line of a journal.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "entry", rename_all = "camelCase")]
+enum JournalEntry {
+    #[serde(rename_all = "camelCase")]
+    Start {
+        op_id: String,
+        started_at: u64,
+        op: ResumableOp,
+    },
+    Writing { path: String },
+    Done { source: String },
+}
+
+/// A journal as read back from disk.
+struct ReadJournal {
+    op_id: String,
+    started_at: u64,
+    op: ResumableOp,
+    done: HashSet<String>,
+    /// Last `writing` file not followed by a `done`.
+    partial: Option<String>,
+}
+
+/// Open journal of a running operation.
+pub struct OpJournal {
+    op_id: String,
+    path: PathBuf,
+    file: File,
+}
+
+impl OpJournal {
+    /// Start journaling `op_id`. With `resume` an existing journal is
+    /// continued (its done files stay done); otherwise it starts over.
+    /// None (logged) when the journal can't be written.
+    pub fn begin(app: &AppHandle, op_id: &str, op: &ResumableOp, resume: bool) -> Option<Self> {
+        let mut continued = false;
+        let res = journal_path(app, op_id).and_then(|path| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| AppError::io(&e).with_path(parent))?;
+            }
+            continued = resume && path.exists();
+            let file = if continued {
+                OpenOptions::new().append(true).open(&path)
+            } else {
+                File::create(&path)
+            };
+            let file = file.map_err(|e| AppError::io(&e).with_path(&path))?;
+            Ok((path, file))
+        });
+        let (path, file) = match res {
+            Ok(opened) => opened,
+            Err(e) => {
+                tracing::warn!("[OpJournal] Cannot journal {op_id}: {}", e.message);
+                return None;
+            }
+        };
+        let mut journal = OpJournal {
+            op_id: op_id.to_string(),
+            path,
+            file,
+        };
+        if !continued {
+            journal.append(&JournalEntry::Start {
+                op_id: op_id.to_string(),
+                started_at: now_millis(),
+                op: op.clone(),
+            });
+        }
+        LIVE.lock().unwrap_or_else(|e| e.into_inner()).push(op_id.to_string());
+        Some(journal)
+    }
+
+    /// `path` was just created by the job and is being written.
+    pub fn writing(&mut self, path: &Path) {
+        self.append(&JournalEntry::Writing {
+            path: path.to_string_lossy().to_string(),
+        });
+    }
+
+    /// `source` is finished and doesn't need copying again.
+    pub fn done(&mut self, source: &Path) {
+        self.append(&JournalEntry::Done {
+            source: source.to_string_lossy().to_string(),
+        });
+    }
+
+    /// The operation ended: nothing left to resume.
+    pub fn finish(self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            tracing::warn!("[OpJournal] Failed to remove journal {:?}: {e}", self.path);
+        }
+        LIVE.lock().unwrap_or_else(|e| e.into_inner()).retain(|id| *id != self.op_id);
+    }
+
+    fn append(&mut self, entry: &JournalEntry) {
+        let res = serde_json::to_string(entry)
+            .map_err(std::io::Error::other)
+            .and_then(|line| writeln!(self.file, "{}", line));
+        if let Err(e) = res {
+            tracing::warn!("[OpJournal] Failed to write journal {:?}: {e}", self.path);
+        }
+    }
+}
+
+/// Frontend can call (e.g. at startup, to offer a resume):
+///   invoke<InterruptedOperation[]>('list_interrupted_operations')
+#[tauri::command]
+pub fn list_interrupted_operations(app: AppHandle) -> Result<Vec<InterruptedOperation>, AppError> {
+    Ok(interrupted(&app)?
+        .into_iter()
+        .map(|(_, journal)| InterruptedOperation {
+            files_done: journal.done.len() as u64,
+            op_id: journal.op_id,
+            started_at: journal.started_at,
+            op: journal.op,
+        })
+        .collect())
+}
+
+/// Frontend can call:
+///   invoke<string[]>('resume_interrupted_operations', { opIds: null })
+///
+/// Starts the given interrupted operations again (all of them without
+/// `opIds`) and returns the op ids started. Copies and moves report through
+/// the usual `fu:file_op_*` events; one that can't start (e.g. the
+/// destination is gone) gets its `fu:file_op_completed` with the error and
+/// is forgotten. A download emits `fu:update_downloaded` when done.
+#[tauri::command]
+pub async fn resume_interrupted_operations(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_ids: Option<Vec<String>>,
+) -> Result<Vec<String>, AppError> {
+    let mut resumed = Vec::new();
+    for (path, journal) in selected(&app, op_ids.as_deref())? {
+        remove_partial(&journal);
+        match journal.op {
+            ResumableOp::FileOp {
+                kind,
+                sources,
+                destination,
+                conflict_policy,
+                copy_options,
+                bandwidth_limit,
+            } => {
+                // Moved sources are gone from where they were.
+                let sources: Vec<String> = sources
+                    .into_iter()
+                    .filter(|s| kind == FileOpKind::Copy || fs::symlink_metadata(s).is_ok())
+                    .collect();
+                if sources.is_empty() {
+                    let _ = fs::remove_file(&path);
+                    continue;
+                }
+                let options = FileOpOptions {
+                    conflict_policy,
+                    copy_options,
+                    bandwidth_limit: Arc::new(AtomicU64::new(bandwidth_limit)),
+                    resume_done: Some(journal.done.iter().map(PathBuf::from).collect()),
+                    ..Default::default()
+                };
+                let res = guard
+                    .check_entries(&sources)
+                    .and_then(|sources| Ok((sources, guard.check(&destination)?)))
+                    .map_err(AppError::from)
+                    .and_then(|(sources, destination)| {
+                        start_file_op(
+                            app.clone(),
+                            registry.inner().clone(),
+                            journal.op_id.clone(),
+                            kind,
+                            sources.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+                            destination.to_string_lossy().to_string(),
+                            options,
+                        )
+                    });
+                match res {
+                    Ok(()) => resumed.push(journal.op_id),
+                    Err(e) => {
+                        let _ = fs::remove_file(&path);
+                        emit_not_started(&app, &journal.op_id, kind, e);
+                    }
+                }
+            }
+            ResumableOp::Download { platform_id, channel } => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    match update::download_update_bundle(&app, platform_id, channel).await {
+                        Ok(download) => {
+                            notifications::notify_update_downloaded(&app, &download.version);
+                            let _ = app.emit("fu:update_downloaded", download);
+                        }
+                        Err(e) => tracing::warn!("[OpJournal] Resumed update download failed: {e:#}"),
+                    }
+                });
+                resumed.push(journal.op_id);
+            }
+        }
+    }
+    Ok(resumed)
+}
+
+/// Frontend can call:
+///   invoke('discard_interrupted_operations', { opIds: null })
+///
+/// Forgets interrupted operations (all without `opIds`); a half-written
+/// file they left behind is removed.
+#[tauri::command]
+pub fn discard_interrupted_operations(app: AppHandle, op_ids: Option<Vec<String>>) -> Result<(), AppError> {
+    for (path, journal) in selected(&app, op_ids.as_deref())? {
+        remove_partial(&journal);
+        fs::remove_file(&path).map_err(|e| AppError::io(&e).with_path(&path))?;
+    }
+    Ok(())
+}
+
+fn selected(app: &AppHandle, op_ids: Option<&[String]>) -> Result<Vec<(PathBuf, ReadJournal)>, AppError> {
+    let mut journals = interrupted(app)?;
+    if let Some(op_ids) = op_ids {
+        journals.retain(|(_, j)| op_ids.contains(&j.op_id));
+    }
+    Ok(journals)
+}
+
+/// Journals left by earlier runs, oldest first.
+fn interrupted(app: &AppHandle) -> Result<Vec<(PathBuf, ReadJournal)>, AppError> {
+    let dir = journal_dir(app)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::io(&e).with_path(&dir)),
+    };
+    let live = LIVE.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut journals: Vec<(PathBuf, ReadJournal)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .filter_map(|path| match read_journal(&path) {
+            Ok(journal) => Some((path, journal?)),
+            Err(e) => {
+                tracing::warn!("[OpJournal] Cannot read journal {:?}: {e}", path);
+                None
+            }
+        })
+        .filter(|(_, journal)| !live.contains(&journal.op_id))
+        .collect();
+    journals.sort_by_key(|(_, journal)| journal.started_at);
+    Ok(journals)
+}
+
+/// None without a start line. A torn last line (crash mid-write) is
+/// ignored.
+fn read_journal(path: &Path) -> std::io::Result<Option<ReadJournal>> {
+    let file = File::open(path)?;
+    let mut journal: Option<ReadJournal> = None;
+    for line in BufReader::new(file).lines() {
+        let entry = match serde_json::from_str(&line?) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!("[OpJournal] Bad journal line in {:?}: {e}", path);
+                continue;
+            }
+        };
+        match (entry, journal.as_mut()) {
+            (JournalEntry::Start { op_id, started_at, op }, _) => {
+                journal = Some(ReadJournal {
+                    op_id,
+                    started_at,
+                    op,
+                    done: HashSet::new(),
+                    partial: None,
+                });
+            }
+            (JournalEntry::Writing { path }, Some(journal)) => journal.partial = Some(path),
+            (JournalEntry::Done { source }, Some(journal)) => {
+                journal.partial = None;
+                journal.done.insert(source);
+            }
+            _ => {}
+        }
+    }
+    Ok(journal)
+}
+
+fn remove_partial(journal: &ReadJournal) {
+    let Some(partial) = &journal.partial else {
+        return;
+    };
+    match fs::remove_file(partial) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("[OpJournal] Failed to remove partial file {partial}: {e}"),
+    }
+}
+
+fn journal_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("App data dir error: {}", e)))?;
+    Ok(dir.join(JOURNAL_DIR))
+}
+
+fn journal_path(app: &AppHandle, op_id: &str) -> Result<PathBuf, AppError> {
+    Ok(journal_dir(app)?.join(format!("{}.jsonl", op_file_stem(op_id))))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,455 @@
+// src-tauri/src/path_guard.rs
+//
+// Central allowlist for filesystem access from the frontend.
+// - Every command that takes a path runs it through PathGuard::check first
+//   and then works on the path check returns, never on the string it was
+//   given: a symlink swapped in between can't redirect the operation.
+// - check canonicalizes the path (symlinks and ".." resolved, so
+//   "/allowed/../etc" or a link pointing outside can't slip through) and
+//   requires it to be inside one of the allowed roots. check_entry does
+//   the same but keeps a symlink at the path itself, for operations on
+//   the entry (delete, rename, attributes) rather than what it points to.
+// - On Windows canonical paths come back in their plain form (C:\...,
+//   \\srv\share) where that means the same file, so they can be shown and
+//   compared like what the user typed.
+// - Paths that don't exist yet (new folder, copy destination) are checked
+//   via their nearest existing ancestor.
+// - A network share we aren't signed in to can't be resolved; that comes
+//   back as NetworkAuthRequired (network_share.rs) instead of InvalidPath.
+// - An empty list means "no restriction", which is the default so existing
+//   installs keep working; the settings page fills it in. A roots file
+//   that exists but can't be read or parsed allows nothing at all until
+//   set_allowed_roots replaces it, so corrupting it never lifts the
+//   restriction. Roots missing at startup (an unplugged drive) stay in
+//   the list as written.
+// - The app's own config and data dirs (allowed_roots.json, settings,
+//   versions/, ...) are never allowed, whatever the roots say.
+// - The webview may narrow the list on its own. Anything that widens it
+//   (a root outside the current ones, or lifting the restriction) needs
+//   the user's yes in a native dialog the webview can't answer; without a
+//   dialog tool (Linux without zenity / kdialog) only editing
+//   allowed_roots.json by hand and restarting widens it.
+//
+// Persisted at <app config dir>/allowed_roots.json as a plain JSON array.
+// State: .manage(PathGuard::load(app.handle()))
+
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::network_share;
+
+const ROOTS_FILE: &str = "allowed_roots.json";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PathGuardError {
+    /// Path resolves outside every allowed root.
+    AccessDenied { path: String },
+    /// Path can't be resolved (no existing ancestor, ".." after a missing part).
+    InvalidPath { path: String, message: String },
+    /// Path is on a network share that wants credentials first.
+    NetworkAuthRequired { path: String, share: String },
+}
+
+impl fmt::Display for PathGuardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathGuardError::AccessDenied { path } => {
+                write!(f, "Access denied: {} is outside the allowed folders", path)
+            }
+            PathGuardError::InvalidPath { path, message } => {
+                write!(f, "Invalid path {}: {}", path, message)
+            }
+            PathGuardError::NetworkAuthRequired { share, .. } => {
+                write!(f, "Sign in to {} to open this folder", share)
+            }
+        }
+    }
+}
+
+impl From<PathGuardError> for String {
+    fn from(e: PathGuardError) -> Self {
+        e.to_string()
+    }
+}
+
+#[derive(Default)]
+pub struct PathGuard {
+    /// Canonical roots; empty = unrestricted.
+    roots: RwLock<Vec<PathBuf>>,
+    /// The roots file couldn't be read: nothing is allowed until
+    /// set_allowed_roots writes a new one.
+    deny_all: AtomicBool,
+    /// The app's config and data dirs; never allowed.
+    private: Vec<PathBuf>,
+}
+
+impl PathGuard {
+    /// Load the persisted list. A missing file means unrestricted, an
+    /// unreadable one means nothing is allowed.
+    pub fn load(app: &AppHandle) -> Self {
+        let mut deny_all = false;
+        let roots = match roots_path(app) {
+            Ok(p) if p.exists() => {
+                let list = fs::read_to_string(&p)
+                    .map_err(|e| e.to_string())
+                    .and_then(|data| serde_json::from_str::<Vec<String>>(&data).map_err(|e| e.to_string()));
+                list.unwrap_or_else(|e| {
+                    tracing::error!("[PathGuard] Invalid {:?} ({e}), allowing nothing until it is replaced", p);
+                    deny_all = true;
+                    Vec::new()
+                })
+            }
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                tracing::error!("[PathGuard] {e}, allowing nothing");
+                deny_all = true;
+                Vec::new()
+            }
+        };
+        // A root that is missing right now (unplugged drive) is kept as
+        // written rather than dropped, which could empty the list.
+        let roots = roots
+            .iter()
+            .map(|r| canonical(Path::new(r)).unwrap_or_else(|_| PathBuf::from(r)))
+            .collect();
+
+        let paths = app.path();
+        let private = [paths.app_config_dir(), paths.app_data_dir(), paths.app_local_data_dir()]
+            .into_iter()
+            .flatten()
+            .flat_map(|dir| [resolve(&dir).ok(), Some(dir)])
+            .flatten()
+            .collect();
+
+        PathGuard {
+            roots: RwLock::new(roots),
+            deny_all: AtomicBool::new(deny_all),
+            private,
+        }
+    }
+
+    /// Canonicalize `path` and make sure it is inside an allowed root.
+    /// Returns the canonical path; use it rather than `path` from here on.
+    pub fn check(&self, path: &str) -> Result<PathBuf, PathGuardError> {
+        let resolved = resolve_for(Path::new(path), path)?;
+        self.admit(resolved, path)
+    }
+
+    /// check() for the entry at `path` itself: its folder is resolved, a
+    /// symlink at `path` is kept rather than followed.
+    pub fn check_entry(&self, path: &str) -> Result<PathBuf, PathGuardError> {
+        let p = Path::new(path);
+        let resolved = match (p.parent(), p.file_name()) {
+            (Some(parent), Some(name)) => resolve_for(parent, path)?.join(name),
+            _ => resolve_for(p, path)?,
+        };
+        self.admit(resolved, path)
+    }
+
+    /// check_entry() for a list of paths; fails on the first rejected one.
+    pub fn check_entries(&self, paths: &[String]) -> Result<Vec<PathBuf>, PathGuardError> {
+        paths.iter().map(|p| self.check_entry(p)).collect()
+    }
+
+    /// `resolved` if it is inside an allowed root.
+    fn admit(&self, resolved: PathBuf, path: &str) -> Result<PathBuf, PathGuardError> {
+        let roots = self.roots.read().unwrap_or_else(|e| e.into_inner());
+        let private = self.private.iter().any(|dir| resolved.starts_with(dir));
+        let allowed = roots.is_empty() || roots.iter().any(|root| resolved.starts_with(root));
+        if allowed && !private && !self.deny_all.load(Ordering::Relaxed) {
+            Ok(resolved)
+        } else {
+            Err(PathGuardError::AccessDenied {
+                path: path.to_string(),
+            })
+        }
+    }
+
+    /// A bare "\\server" (list_dir shows its shares): allowed when
+    /// unrestricted or when one of the roots is on that server.
+    pub fn check_server(&self, server: &str) -> Result<(), PathGuardError> {
+        let prefix = format!(r"\\{}\", server).to_lowercase();
+        let roots = self.roots.read().unwrap_or_else(|e| e.into_inner());
+        let on_server = |root: &PathBuf| {
+            network_share::unc_share(&root.to_string_lossy()).is_some_and(|s| s.to_lowercase().starts_with(&prefix))
+        };
+        if (roots.is_empty() || roots.iter().any(on_server)) && !self.deny_all.load(Ordering::Relaxed) {
+            Ok(())
+        } else {
+            Err(PathGuardError::AccessDenied {
+                path: format!(r"\\{}", server),
+            })
+        }
+    }
+
+    /// check() for a list of paths; fails on the first rejected one.
+    pub fn check_all(&self, paths: &[String]) -> Result<Vec<PathBuf>, PathGuardError> {
+        paths.iter().map(|p| self.check(p)).collect()
+    }
+
+    fn roots(&self) -> Vec<String> {
+        self.roots
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|r| r.to_string_lossy().to_string())
+            .collect()
+    }
+}
+
+/// Canonical form of `path`, also for paths that don't exist yet:
+/// the longest existing prefix is canonicalized and the rest appended.
+pub fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if !path.is_absolute() {
+        return Err("path must be absolute".to_string());
+    }
+
+    let mut existing = path;
+    let mut tail = Vec::new();
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(base) => {
+                let mut resolved = simplified(base);
+                for part in tail.iter().rev() {
+                    resolved.push(part);
+                }
+                return Ok(resolved);
+            }
+            Err(_) => {
+                let name = match existing.components().next_back() {
+                    Some(Component::Normal(name)) => name,
+                    // ".." past a missing folder can't be resolved safely.
+                    Some(Component::ParentDir) => return Err("'..' after a missing folder".to_string()),
+                    _ => return Err("no existing parent folder".to_string()),
+                };
+                tail.push(name.to_os_string());
+                existing = existing
+                    .parent()
+                    .ok_or_else(|| "no existing parent folder".to_string())?;
+            }
+        }
+    }
+}
+
+/// resolve() for the entry at `path` itself (see check_entry): its folder
+/// is resolved, the final component is kept as it is.
+pub fn resolve_entry(path: &Path) -> Result<PathBuf, String> {
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => Ok(resolve(parent)?.join(name)),
+        _ => resolve(path),
+    }
+}
+
+/// resolve() with errors for `path` (the string the caller was given).
+fn resolve_for(p: &Path, path: &str) -> Result<PathBuf, PathGuardError> {
+    resolve(p).map_err(|message| {
+        // A share we aren't signed in to has no existing ancestor either.
+        match network_share::auth_required(Path::new(path)) {
+            Some(share) => PathGuardError::NetworkAuthRequired {
+                path: path.to_string(),
+                share,
+            },
+            None => PathGuardError::InvalidPath {
+                path: path.to_string(),
+                message,
+            },
+        }
+    })
+}
+
+/// fs::canonicalize in the plain form (see simplified).
+fn canonical(path: &Path) -> std::io::Result<PathBuf> {
+    fs::canonicalize(path).map(simplified)
+}
+
+/// \\?\C:\x -> C:\x and \\?\UNC\srv\share -> \\srv\share, unless the plain form
+/// would be parsed into something else (reserved device names, trailing
+/// dots or spaces, too long for the legacy limit).
+#[cfg(windows)]
+pub fn simplified(path: PathBuf) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path;
+    };
+    let plain = if let Some(rest) = s.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = s.strip_prefix(r"\\?\").filter(|r| r.as_bytes().get(1) == Some(&b':')) {
+        rest.to_string()
+    } else {
+        return path;
+    };
+    let reserved = |name: &str| {
+        let stem = name.split('.').next().unwrap_or("").trim_end().to_ascii_uppercase();
+        matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL" | "CONIN$" | "CONOUT$")
+            || (stem.len() == 4
+                && (stem.starts_with("COM") || stem.starts_with("LPT"))
+                && stem.as_bytes()[3].is_ascii_digit())
+    };
+    let ambiguous = plain
+        .split('\\')
+        .skip(1)
+        .any(|name| name.ends_with('.') || name.ends_with(' ') || reserved(name));
+    if ambiguous || plain.len() >= 260 {
+        path
+    } else {
+        PathBuf::from(plain)
+    }
+}
+
+#[cfg(not(windows))]
+pub fn simplified(path: PathBuf) -> PathBuf {
+    path
+}
+
+fn roots_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("App config dir error: {}", e))?;
+    Ok(dir.join(ROOTS_FILE))
+}
+
+/// Current allowlist (canonical paths). Empty = unrestricted.
+///
+/// Frontend can call:
+///   invoke<string[]>('get_allowed_roots')
+#[tauri::command]
+pub fn get_allowed_roots(guard: State<'_, PathGuard>) -> Vec<String> {
+    guard.roots()
+}
+
+/// Replace the allowlist. Every root must be an existing folder; pass an
+/// empty array to lift the restriction. Narrowing applies directly;
+/// widening first asks the user in a native dialog and fails with
+/// "Not confirmed" if they decline. Emits `fu:allowed_roots_changed`.
+///
+/// Frontend can call:
+///   invoke<string[]>('set_allowed_roots', { roots: ['C:\\Users\\me', 'D:\\'] })
+#[tauri::command]
+pub async fn set_allowed_roots(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    roots: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut resolved: Vec<PathBuf> = Vec::with_capacity(roots.len());
+    for root in &roots {
+        let p = canonical(Path::new(root)).map_err(|e| format!("Cannot use {} as a root: {}", root, e))?;
+        if !p.is_dir() {
+            return Err(format!("Not a folder: {}", root));
+        }
+        if !resolved.contains(&p) {
+            resolved.push(p);
+        }
+    }
+
+    let widens = {
+        let current = guard.roots.read().unwrap_or_else(|e| e.into_inner());
+        guard.deny_all.load(Ordering::Relaxed)
+            || (!current.is_empty()
+                && (resolved.is_empty() || resolved.iter().any(|new| !current.iter().any(|root| new.starts_with(root)))))
+    };
+    if widens {
+        let message = if resolved.is_empty() {
+            "FilesUP was asked to lift its folder restriction and access every folder.\n\nAllow this?".to_string()
+        } else {
+            let list: Vec<String> = resolved.iter().map(|p| format!("  {}", p.display())).collect();
+            format!(
+                "FilesUP was asked to widen the folders it may access to:\n\n{}\n\nAllow this?",
+                list.join("\n")
+            )
+        };
+        let confirmed = tauri::async_runtime::spawn_blocking(move || confirm_natively("FilesUP allowed folders", &message))
+            .await
+            .map_err(|e| format!("Confirmation dialog failed: {}", e))??;
+        if !confirmed {
+            return Err("Not confirmed: the allowed folders were left unchanged".to_string());
+        }
+        tracing::info!("[PathGuard] Widening the allowed roots was confirmed");
+    }
+
+    // Store what the user typed (not \\?\ resolved forms) so the file
+    // stays readable; it is canonicalized again on load.
+    let path = roots_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config dir {:?}: {}", parent, e))?;
+    }
+    let data = serde_json::to_string_pretty(&roots)
+        .map_err(|e| format!("Failed to serialize allowed roots: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write allowed roots: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace allowed roots file: {}", e))?;
+
+    *guard.roots.write().unwrap_or_else(|e| e.into_inner()) = resolved;
+    guard.deny_all.store(false, Ordering::Relaxed);
+    let current = guard.roots();
+    let _ = app.emit("fu:allowed_roots_changed", current.clone());
+    Ok(current)
+}
+
+/// Yes / no question in a native dialog, outside the webview's reach.
+#[cfg(windows)]
+fn confirm_natively(title: &str, message: &str) -> Result<bool, String> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        MessageBoxW, IDYES, MB_DEFBUTTON2, MB_ICONWARNING, MB_SETFOREGROUND, MB_TOPMOST, MB_YESNO,
+    };
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain([0]).collect()
+    }
+    let (title, text) = (wide(title), wide(message));
+    // SAFETY: both strings are NUL-terminated and outlive the call.
+    let answer = unsafe {
+        MessageBoxW(
+            std::ptr::null_mut(),
+            text.as_ptr(),
+            title.as_ptr(),
+            MB_YESNO | MB_ICONWARNING | MB_DEFBUTTON2 | MB_TOPMOST | MB_SETFOREGROUND,
+        )
+    };
+    Ok(answer == IDYES)
+}
+
+#[cfg(target_os = "macos")]
+fn confirm_natively(title: &str, message: &str) -> Result<bool, String> {
+    // Texts go in as arguments, so nothing needs AppleScript quoting.
+    let output = std::process::Command::new("osascript")
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display dialog (item 2 of argv) with title (item 1 of argv) buttons {\"Cancel\", \"Allow\"} \
+             default button \"Cancel\" cancel button \"Cancel\" with icon caution",
+            "-e",
+            "end run",
+            title,
+            message,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    Ok(output.status.success())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn confirm_natively(title: &str, message: &str) -> Result<bool, String> {
+    let dialogs: [(&str, Vec<&str>); 2] = [
+        (
+            "zenity",
+            vec!["--question", "--default-cancel", "--no-markup", "--title", title, "--text", message],
+        ),
+        ("kdialog", vec!["--title", title, "--warningyesno", message]),
+    ];
+    for (program, args) in dialogs {
+        match std::process::Command::new(program).args(&args).status() {
+            Ok(status) => return Ok(status.success()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("Failed to run {}: {}", program, e)),
+        }
+    }
+    Err("No dialog tool (zenity or kdialog) to confirm with; edit allowed_roots.json and restart instead".to_string())
+}
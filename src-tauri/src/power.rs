@@ -0,0 +1,164 @@
+// src-tauri/src/power.rs
+//
+// Battery / power source reporting for the metrics subsystem.
+// The metrics loop samples this every POWER_INTERVAL and emits
+// `system://power` when something changed, so the frontend can throttle
+// background scans while running on battery.
+//
+// No extra crate: each OS exposes this cheaply on its own.
+//   - Linux:   /sys/class/power_supply/*
+//   - Windows: GetSystemPowerStatus
+//   - macOS:   `pmset -g batt`
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// How often the metrics loop re-reads the power state.
+pub const POWER_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PowerState {
+    pub has_battery: bool,
+    // 0..100, None if there is no battery or it can't be read
+    pub battery_percent: Option<f32>,
+    // None if unknown
+    pub charging: Option<bool>,
+    pub power_source: String, // "ac" | "battery" | "unknown"
+}
+
+impl PowerState {
+    fn unknown() -> Self {
+        Self {
+            has_battery: false,
+            battery_percent: None,
+            charging: None,
+            power_source: "unknown".to_string(),
+        }
+    }
+}
+
+/// Frontend can call:
+///   invoke('get_power_state')
+#[tauri::command]
+pub fn get_power_state() -> PowerState {
+    read_power_state()
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_power_state() -> PowerState {
+    use std::fs;
+
+    let read = |dir: &std::path::Path, name: &str| {
+        fs::read_to_string(dir.join(name))
+            .map(|s| s.trim().to_string())
+            .ok()
+    };
+
+    let mut state = PowerState::unknown();
+    let mut on_mains: Option<bool> = None;
+
+    let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+        return state;
+    };
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                let online = read(&dir, "online").as_deref() == Some("1");
+                on_mains = Some(on_mains.unwrap_or(false) || online);
+            }
+            // Peripheral batteries (mice, ...) report scope "Device".
+            Some("Battery") if read(&dir, "scope").as_deref() != Some("Device") => {
+                state.has_battery = true;
+                state.battery_percent = read(&dir, "capacity").and_then(|c| c.parse().ok());
+                state.charging = match read(&dir, "status").as_deref() {
+                    Some("Charging") => Some(true),
+                    Some("Discharging") | Some("Not charging") | Some("Full") => Some(false),
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    state.power_source = match (on_mains, state.has_battery, state.charging) {
+        (Some(true), _, _) => "ac",
+        (Some(false), true, _) => "battery",
+        // No mains entry exposed: infer from the battery itself.
+        (None, true, Some(true)) => "ac",
+        (None, true, Some(false)) => "battery",
+        (None, false, _) => "ac", // desktop without battery or mains entry
+        _ => "unknown",
+    }
+    .to_string();
+    state
+}
+
+#[cfg(windows)]
+pub fn read_power_state() -> PowerState {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // Plain C struct, all-zero is a valid value.
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerState::unknown();
+    }
+
+    const NO_BATTERY: u8 = 128;
+    const CHARGING: u8 = 8;
+    const UNKNOWN: u8 = 255;
+
+    let has_battery = status.BatteryFlag != UNKNOWN && status.BatteryFlag & NO_BATTERY == 0;
+    PowerState {
+        has_battery,
+        battery_percent: (has_battery && status.BatteryLifePercent <= 100)
+            .then_some(status.BatteryLifePercent as f32),
+        charging: has_battery.then_some(status.BatteryFlag & CHARGING != 0),
+        power_source: match status.ACLineStatus {
+            0 => "battery",
+            1 => "ac",
+            _ => "unknown",
+        }
+        .to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn read_power_state() -> PowerState {
+    // Now drawing from 'Battery Power'
+    //  -InternalBattery-0 (id=1234)	85%; discharging; 4:10 remaining present: true
+    let Ok(out) = std::process::Command::new("pmset").args(["-g", "batt"]).output() else {
+        return PowerState::unknown();
+    };
+    let text = String::from_utf8_lossy(&out.stdout);
+
+    let mut state = PowerState::unknown();
+    if text.contains("'AC Power'") {
+        state.power_source = "ac".to_string();
+    } else if text.contains("'Battery Power'") {
+        state.power_source = "battery".to_string();
+    }
+
+    if let Some(line) = text.lines().find(|l| l.contains("InternalBattery")) {
+        state.has_battery = true;
+        let fields: Vec<&str> = line
+            .split(['\t', ';'])
+            .map(|f| f.trim())
+            .collect();
+        state.battery_percent = fields
+            .iter()
+            .find_map(|f| f.strip_suffix('%'))
+            .and_then(|p| p.parse().ok());
+        state.charging = fields.iter().find_map(|f| match *f {
+            "charging" | "finishing charge" => Some(true),
+            "discharging" | "charged" => Some(false),
+            _ => None,
+        });
+    }
+    state
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn read_power_state() -> PowerState {
+    PowerState::unknown()
+}
@@ -0,0 +1,425 @@
+// src-tauri/src/rename_ops.rs
+//
+// Batch rename engine.
+// - A list of paths goes through a chain of rules (find/replace, regex with
+//   capture groups, numbering, case change), applied in order to each name.
+// - By default rules only see the stem; the extension is kept as-is
+//   (includeExtension: true to rename "photo.JPG" -> "photo.jpg").
+// - dryRun: true returns the proposed names and any conflicts without
+//   touching the disk, so the dialog can show a live preview.
+// - Applying is all-or-nothing: every file is first moved to a temporary
+//   name in its folder, then to its final name. That makes swaps
+//   (a -> b, b -> a) and case-only renames work, and if any step fails the
+//   files already moved are put back and every failure is reported.
+// - Applied batches are recorded in the audit log (audit.rs), one entry per
+//   renamed file, all with the batch's result.
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::catalog::Catalog;
+use crate::error::AppError;
+use crate::file_ops::FileOpFailure;
+use crate::path_guard::{resolve, PathGuard};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+use tokio::task;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RenameRule {
+    /// Plain text replacement.
+    FindReplace {
+        find: String,
+        #[serde(default)]
+        replace: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    /// Regex replacement; `replace` may use $1 / ${name} capture groups.
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        replace: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    /// Template with {name} (current name) and {n} (counter),
+    /// e.g. "Holiday {n}" or "{n} - {name}".
+    Numbering {
+        template: String,
+        #[serde(default = "default_start")]
+        start: i64,
+        #[serde(default = "default_step")]
+        step: i64,
+        /// Minimum digits for {n}, zero-padded.
+        #[serde(default)]
+        padding: usize,
+    },
+    Case { mode: CaseMode },
+}
+
+fn default_start() -> i64 {
+    1
+}
+
+fn default_step() -> i64 {
+    1
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMode {
+    Lower,
+    Upper,
+    /// First letter of every word upper-case, the rest lower-case.
+    Title,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BatchRenameOptions {
+    pub dry_run: bool,
+    /// Apply rules to the full file name instead of just the stem.
+    pub include_extension: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameItem {
+    pub from: String,
+    pub to: String,
+    pub new_name: String,
+    /// False when the rules left the name untouched (nothing to do).
+    pub changed: bool,
+    /// Why this item can't be renamed (invalid name, duplicate, target exists).
+    pub conflict: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRenameResult {
+    pub items: Vec<RenameItem>,
+    pub dry_run: bool,
+    /// True once every changed item has its new name on disk.
+    pub applied: bool,
+    pub failed: Vec<FileOpFailure>,
+}
+
+/// Preview or apply a batch rename.
+///
+/// Nothing is renamed if any item has a conflict; fix the rules and retry.
+///
+/// Frontend can call:
+///   invoke<BatchRenameResult>('batch_rename', {
+///     paths,
+///     rules: [{ type: 'regex', pattern: '^IMG_(\\d+)$', replace: 'Photo $1' }],
+///     options: { dryRun: true },
+///   })
+#[tauri::command]
+pub async fn batch_rename(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    catalog: State<'_, Catalog>,
+    paths: Vec<String>,
+    rules: Vec<RenameRule>,
+    options: Option<BatchRenameOptions>,
+) -> Result<BatchRenameResult, String> {
+    let paths: Vec<String> = guard
+        .check_entries(&paths)?
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    let options = options.unwrap_or_default();
+    let catalog = catalog.inner().clone();
+
+    task::spawn_blocking(move || {
+        let items = plan_renames(&paths, &rules, options.include_extension)?;
+        let mut result = BatchRenameResult {
+            items,
+            dry_run: options.dry_run,
+            applied: false,
+            failed: Vec::new(),
+        };
+        if options.dry_run || result.items.iter().any(|i| i.conflict.is_some()) {
+            return Ok(result);
+        }
+
+        result.failed = apply_renames(&result.items);
+        result.applied = result.failed.is_empty();
+        let detail = result.failed.first().map(|f| format!("{}: {}", f.path, f.message));
+        for item in result.items.iter().filter(|i| i.changed) {
+            let entry = AuditEntry::new(AuditAction::BatchRename)
+                .sources(&[&item.from])
+                .destination(item.to.clone());
+            let entry = match &detail {
+                None => entry,
+                Some(detail) => entry.result("error", Some(detail.clone())),
+            };
+            audit::record(&app, entry);
+        }
+        if result.applied {
+            // Keep tags/notes attached to the renamed files.
+            let moves: Vec<(PathBuf, PathBuf)> = result
+                .items
+                .iter()
+                .filter(|i| i.changed)
+                .filter_map(|i| Some((resolve(Path::new(&i.from)).ok()?, resolve(Path::new(&i.to)).ok()?)))
+                .collect();
+            catalog.rekey_all(&moves);
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Rename task failed: {}", e))?
+}
+
+// ==== Planning ====
+
+fn plan_renames(
+    paths: &[String],
+    rules: &[RenameRule],
+    include_extension: bool,
+) -> Result<Vec<RenameItem>, String> {
+    let rules = CompiledRules::new(rules)?;
+
+    let sources: HashSet<String> = paths.iter().map(|p| path_key(Path::new(p))).collect();
+    let mut targets: HashMap<String, usize> = HashMap::new();
+    let mut items = Vec::with_capacity(paths.len());
+
+    for (index, from) in paths.iter().enumerate() {
+        let from_path = Path::new(from);
+        let old_name = match from_path.file_name() {
+            Some(n) => n.to_string_lossy().to_string(),
+            None => return Err(format!("Not a file or folder path: {}", from)),
+        };
+
+        let (base, ext) = if include_extension || from_path.is_dir() {
+            (old_name.clone(), String::new())
+        } else {
+            split_extension(&old_name)
+        };
+        let new_name = format!("{}{}", rules.apply(&base, index), ext);
+        let to_path = from_path.with_file_name(&new_name);
+        let changed = new_name != old_name;
+
+        let mut conflict = validate_name(&new_name).err();
+        if conflict.is_none() && changed {
+            let key = path_key(&to_path);
+            if let Some(other) = targets.insert(key.clone(), index) {
+                conflict = Some(format!("Same new name as {}", paths[other]));
+            } else if fs::symlink_metadata(&to_path).is_ok() && !sources.contains(&key) {
+                conflict = Some("Target already exists".to_string());
+            }
+        }
+
+        items.push(RenameItem {
+            from: from.clone(),
+            to: to_path.to_string_lossy().to_string(),
+            new_name,
+            changed,
+            conflict,
+        });
+    }
+
+    Ok(items)
+}
+
+enum CompiledRule {
+    FindReplace(regex::Regex, String),
+    Regex(regex::Regex, String),
+    Numbering { template: String, start: i64, step: i64, padding: usize },
+    Case(CaseMode),
+}
+
+struct CompiledRules(Vec<CompiledRule>);
+
+impl CompiledRules {
+    fn new(rules: &[RenameRule]) -> Result<Self, String> {
+        let compile = |pattern: &str, case_sensitive: bool| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))
+        };
+
+        rules
+            .iter()
+            .map(|rule| {
+                Ok(match rule {
+                    RenameRule::FindReplace { find, replace, case_sensitive } => {
+                        if find.is_empty() {
+                            return Err("Find text must not be empty".to_string());
+                        }
+                        // Escaped so it behaves like plain text; `$` in the
+                        // replacement is escaped for the same reason.
+                        let re = compile(&regex::escape(find), *case_sensitive)?;
+                        CompiledRule::FindReplace(re, replace.replace('$', "$$"))
+                    }
+                    RenameRule::Regex { pattern, replace, case_sensitive } => {
+                        CompiledRule::Regex(compile(pattern, *case_sensitive)?, replace.clone())
+                    }
+                    RenameRule::Numbering { template, start, step, padding } => {
+                        if !template.contains("{n}") {
+                            return Err("Numbering template must contain {n}".to_string());
+                        }
+                        CompiledRule::Numbering {
+                            template: template.clone(),
+                            start: *start,
+                            step: *step,
+                            padding: *padding,
+                        }
+                    }
+                    RenameRule::Case { mode } => CompiledRule::Case(*mode),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(CompiledRules)
+    }
+
+    fn apply(&self, name: &str, index: usize) -> String {
+        self.0.iter().fold(name.to_string(), |name, rule| match rule {
+            CompiledRule::FindReplace(re, replace) | CompiledRule::Regex(re, replace) => {
+                re.replace_all(&name, replace.as_str()).into_owned()
+            }
+            CompiledRule::Numbering { template, start, step, padding } => {
+                let n = start.saturating_add(step.saturating_mul(index as i64));
+                let n = format!("{:0width$}", n, width = *padding);
+                template.replace("{name}", &name).replace("{n}", &n)
+            }
+            CompiledRule::Case(mode) => change_case(&name, *mode),
+        })
+    }
+}
+
+fn change_case(name: &str, mode: CaseMode) -> String {
+    match mode {
+        CaseMode::Lower => name.to_lowercase(),
+        CaseMode::Upper => name.to_uppercase(),
+        CaseMode::Title => {
+            let mut out = String::with_capacity(name.len());
+            let mut word_start = true;
+            for c in name.chars() {
+                if c.is_alphanumeric() {
+                    if word_start {
+                        out.extend(c.to_uppercase());
+                    } else {
+                        out.extend(c.to_lowercase());
+                    }
+                    word_start = false;
+                } else {
+                    out.push(c);
+                    word_start = true;
+                }
+            }
+            out
+        }
+    }
+}
+
+/// "archive.tar.gz" -> ("archive.tar", ".gz"); dotfiles keep their name.
+fn split_extension(name: &str) -> (String, String) {
+    match name.rfind('.') {
+        Some(pos) if pos > 0 => (name[..pos].to_string(), name[pos..].to_string()),
+        _ => (name.to_string(), String::new()),
+    }
+}
+
+/// Check a single file name (no path) for characters the OS rejects.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err("Name must not be empty".to_string());
+    }
+    if name.contains('/') || (cfg!(windows) && name.contains('\\')) {
+        return Err("Name must not contain path separators".to_string());
+    }
+    if cfg!(windows) {
+        if let Some(c) = name.chars().find(|c| "<>:\"|?*".contains(*c) || c.is_control()) {
+            return Err(format!("Name must not contain '{}'", c.escape_default()));
+        }
+        if name.ends_with(' ') || name.ends_with('.') {
+            return Err("Name must not end with a space or dot".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Key for "is this the same path": case-insensitive where the usual
+/// filesystem is (Windows, macOS).
+fn path_key(path: &Path) -> String {
+    let s = path.to_string_lossy().to_string();
+    if cfg!(any(windows, target_os = "macos")) {
+        s.to_lowercase()
+    } else {
+        s
+    }
+}
+
+// ==== Applying ====
+
+/// Two-phase rename with rollback. Returns the failures (empty on success).
+fn apply_renames(items: &[RenameItem]) -> Vec<FileOpFailure> {
+    let stamp = std::process::id();
+    let todo: Vec<(&RenameItem, PathBuf)> = items
+        .iter()
+        .filter(|i| i.changed)
+        .enumerate()
+        .map(|(n, item)| {
+            let tmp = Path::new(&item.from).with_file_name(format!(".fu-rename-{}-{}.tmp", stamp, n));
+            (item, tmp)
+        })
+        .collect();
+    let mut failed = Vec::new();
+
+    // Phase 1: from -> temp name in the same folder.
+    let mut staged = 0;
+    for (item, tmp) in &todo {
+        if let Err(e) = fs::rename(&item.from, tmp) {
+            failed.push(FileOpFailure::new(item.from.as_str(), AppError::io(&e)));
+            break;
+        }
+        staged += 1;
+    }
+
+    // Phase 2: temp -> final name.
+    let mut done = 0;
+    if failed.is_empty() {
+        for (item, tmp) in &todo {
+            if let Err(e) = fs::rename(tmp, &item.to) {
+                failed.push(FileOpFailure::new(item.from.as_str(), AppError::io(&e)));
+                break;
+            }
+            done += 1;
+        }
+    }
+
+    if failed.is_empty() {
+        return failed;
+    }
+
+    // Rollback, again in two steps so a finished swap can't overwrite
+    // anything: final names -> temp, then temp -> original names.
+    for (item, tmp) in &todo[..done] {
+        if let Err(e) = fs::rename(&item.to, tmp) {
+            tracing::warn!("[Rename] Failed to roll back {:?}: {e}", item.to);
+            failed.push(FileOpFailure::new(
+                item.to.as_str(),
+                AppError::io(&e).context(&format!("Could not restore original name {}", item.from)),
+            ));
+        }
+    }
+    for (item, tmp) in &todo[..staged] {
+        if tmp.exists() {
+            if let Err(e) = fs::rename(tmp, &item.from) {
+                tracing::warn!("[Rename] Failed to roll back {:?}: {e}", tmp);
+                failed.push(FileOpFailure::new(
+                    tmp.to_string_lossy(),
+                    AppError::io(&e).context(&format!("Could not restore original name {}", item.from)),
+                ));
+            }
+        }
+    }
+    failed
+}
@@ -0,0 +1,166 @@
+// src-tauri/src/safe_mode.rs
+//
+// Read-only "safe mode": with settings `system.safe_mode` on, every command
+// that changes files or trust settings (delete, move, copy, rename, archive,
+// organize, manifests, apply update, allowed roots, ...) is rejected with
+// SAFE_MODE_ENABLED before it runs. Browsing, scans and previews keep
+// working.
+//
+// Enforced in one place: lib.rs wraps the generated invoke handler with
+// `middleware`, which rejects every command that is not in
+// READ_ONLY_COMMANDS. The list is deny-by-default on purpose: a new
+// command stays blocked in safe mode until someone decides it is safe and
+// adds it here. Scheduled folder syncs don't go through IPC and call
+// `check` themselves (scheduler.rs).
+//
+// "Read-only" means it changes no user files and no trust / access
+// settings (allowed roots, update repository, ...). Exports to a user
+// chosen file can overwrite one, so they are blocked; so is opening files
+// (open_path / open_with), which may launch programs or scripts. Debug
+// bundles and update downloads into the app's own dirs are allowed, and
+// so are the settings commands, which are how safe mode is switched off.
+//
+// Jobs already running (or queued in the transfer queue) when safe mode is
+// switched on are not stopped; cancel them if needed.
+
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::AppError;
+use crate::settings::SettingsState;
+
+/// The only commands that run while safe mode is on.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    // browsing and previews
+    "hello",
+    "list_dir",
+    "list_dir_stream",
+    "watch_path",
+    "unwatch_path",
+    "get_launch_path",
+    "set_drop_target",
+    "list_volumes",
+    "get_disk_free_space",
+    "get_power_state",
+    "detect_file_type",
+    "get_thumbnail",
+    "read_text_preview",
+    "read_media_metadata",
+    "get_file_attributes",
+    "list_alternate_streams",
+    "compute_checksum",
+    "list_trash",
+    "get_tags",
+    "query_by_tag",
+    "list_bookmarks",
+    "get_allowed_roots",
+    "clipboard_copy_paths",
+    "clipboard_cut_paths",
+    "clipboard_read_paths",
+    "reveal_in_file_manager",
+    "get_shell_context_actions",
+    "register_shortcut",
+    "unregister_shortcut",
+    // remote locations (mounting only; writes go through file commands)
+    "connect_network_share",
+    "connect_sftp",
+    "connect_webdav",
+    "connect_s3",
+    "list_mtp_devices",
+    "connect_mtp",
+    "cancel_remote",
+    "disconnect_remote",
+    "get_secret",
+    // scans, searches and reports
+    "start_folder_scan",
+    "start_incremental_scan",
+    "start_size_breakdown",
+    "start_duplicate_scan",
+    "start_content_search",
+    "start_name_search",
+    "start_cleanup_scan",
+    "start_storage_advisor",
+    "compare_directories",
+    "verify_manifest",
+    "index_status",
+    "search_index",
+    "rebuild_index",
+    // jobs: watch, pause, stop; answer prompts of jobs already running
+    "list_operations",
+    "cancel_operation",
+    "pause_operation",
+    "resolve_conflict",
+    "set_transfer_bandwidth",
+    "list_interrupted_operations",
+    "get_transfer_queue",
+    "pause_transfer",
+    "cancel_transfer",
+    "list_schedules",
+    "get_schedule_history",
+    // logs and diagnostics
+    "get_audit_log",
+    "get_recent_logs",
+    "pause_metrics",
+    "resume_metrics",
+    "stop_metrics",
+    "read_debug_bundle",
+    "write_latest_bundle",
+    "write_debug_bundle",
+    "write_latest_bundle_json",
+    "list_bundles",
+    "read_bundle",
+    "get_bundle_dir",
+    // settings (safe mode itself is switched here)
+    "get_settings",
+    "save_settings",
+    "update_settings",
+    "reset_settings",
+    // updates: check and download, never apply
+    "tuf_check_for_updates",
+    "tuf_download_update",
+    "trigger_update_check",
+    "get_update_channel",
+    "get_update_schedule",
+    "get_update_network",
+    "test_update_endpoints",
+    "verify_version",
+    "get_update_history",
+    "get_trust_info",
+];
+
+/// Whether safe mode is on.
+pub fn enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<SettingsState>().is_some_and(|s| {
+        let system = s.system_handle();
+        let enabled = system.read().unwrap_or_else(|e| e.into_inner()).safe_mode;
+        enabled
+    })
+}
+
+/// For mutating work that doesn't come in through a command.
+pub fn check<R: Runtime>(app: &AppHandle<R>, action: &str) -> Result<(), AppError> {
+    if enabled(app) {
+        Err(AppError::safe_mode(action))
+    } else {
+        Ok(())
+    }
+}
+
+/// Wrap the generated invoke handler:
+///   .invoke_handler(safe_mode::middleware(tauri::generate_handler![...]))
+pub fn middleware<R, H>(handler: H) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static
+where
+    R: Runtime,
+    H: Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+{
+    move |invoke| {
+        let command = invoke.message.command();
+        if !READ_ONLY_COMMANDS.contains(&command) && enabled(invoke.message.webview().app_handle()) {
+            tracing::info!("[SafeMode] Rejected {}", command);
+            let error = AppError::safe_mode(command);
+            invoke.resolver.reject(error);
+            return true;
+        }
+        handler(invoke)
+    }
+}
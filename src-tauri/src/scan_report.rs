@@ -0,0 +1,255 @@
+// src-tauri/src/scan_report.rs
+//
+// Disk usage reports: the per-folder breakdown of a completed folder scan
+// (start_folder_scan with saveReport), kept under the app data dir so it can
+// be exported later, e.g. to send to IT:
+//   <app data dir>/scan-reports/<op id>.json
+//
+// The breakdown is built from the same per-directory records as the
+// incremental scan snapshots (scan_snapshot.rs): direct files per folder,
+// summed up the tree here. Only the newest MAX_REPORTS are kept.
+//
+// export_scan_report writes a stored report as CSV (one row per folder,
+// for spreadsheets) or JSON (the report as stored).
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tokio::task;
+
+use crate::error::{AppError, ErrorCode};
+use crate::gps_backend::op_file_stem;
+use crate::path_guard::PathGuard;
+use crate::scan_snapshot::ScanSnapshot;
+
+const REPORT_DIR: &str = "scan-reports";
+const MAX_REPORTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanReport {
+    pub op_id: String,
+    /// Seconds since UNIX_EPOCH.
+    pub created_at: u64,
+    pub roots: Vec<String>,
+    /// Roots first, then their subfolders in path order.
+    pub dirs: Vec<DirUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirUsage {
+    pub path: String,
+    /// 0 for a scan root.
+    pub depth: u32,
+    /// Totals including all subfolders.
+    pub size: u64,
+    pub file_count: u64,
+    pub folder_count: u64,
+    /// Files directly in this folder.
+    pub own_size: u64,
+    pub own_file_count: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+impl ScanReport {
+    pub fn from_snapshots(op_id: &str, snapshots: &[ScanSnapshot]) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            op_id: op_id.to_string(),
+            created_at,
+            roots: snapshots.iter().map(|s| s.root.clone()).collect(),
+            dirs: snapshots.iter().flat_map(dir_usage).collect(),
+        }
+    }
+}
+
+fn dir_usage(snapshot: &ScanSnapshot) -> Vec<DirUsage> {
+    // Deepest folders first, so each one is complete before it is added to
+    // its parent.
+    let mut keys: Vec<&String> = snapshot.dirs.keys().collect();
+    keys.sort_by_key(|k| std::cmp::Reverse(depth(k)));
+
+    // key -> (size, files, folders), recursive
+    let mut totals: HashMap<&str, (u64, u64, u64)> = HashMap::new();
+    for key in &keys {
+        let record = &snapshot.dirs[*key];
+        let below = totals.get(key.as_str()).copied().unwrap_or_default();
+        let total = (below.0.saturating_add(record.bytes), below.1 + record.files, below.2);
+        totals.insert(key.as_str(), total);
+        if !key.is_empty() {
+            let parent = key.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
+            let entry = totals.entry(parent).or_default();
+            entry.0 = entry.0.saturating_add(total.0);
+            entry.1 += total.1;
+            entry.2 += total.2 + 1;
+        }
+    }
+
+    let root = Path::new(&snapshot.root);
+    let mut dirs: Vec<DirUsage> = keys
+        .into_iter()
+        .map(|key| {
+            let record = &snapshot.dirs[key];
+            let (size, file_count, folder_count) = totals[key.as_str()];
+            let path = key.split('/').filter(|s| !s.is_empty()).fold(root.to_path_buf(), |p, s| p.join(s));
+            DirUsage {
+                path: path.to_string_lossy().to_string(),
+                depth: depth(key),
+                size,
+                file_count,
+                folder_count,
+                own_size: record.bytes,
+                own_file_count: record.files,
+            }
+        })
+        .collect();
+    dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    dirs
+}
+
+fn depth(key: &str) -> u32 {
+    if key.is_empty() {
+        0
+    } else {
+        key.split('/').count() as u32
+    }
+}
+
+fn report_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("App data dir error: {}", e))?;
+    Ok(dir.join(REPORT_DIR))
+}
+
+fn report_path(app: &AppHandle, op_id: &str) -> Result<PathBuf, String> {
+    Ok(report_dir(app)?.join(format!("{}.json", op_file_stem(op_id))))
+}
+
+pub fn save_report(app: &AppHandle, report: &ScanReport) -> Result<(), String> {
+    let path = report_path(app, &report.op_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create report dir {:?}: {}", parent, e))?;
+    }
+    let data = serde_json::to_string(report)
+        .map_err(|e| format!("Failed to serialize scan report: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write scan report: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace scan report: {}", e))?;
+    prune_reports(app);
+    Ok(())
+}
+
+pub fn load_report(app: &AppHandle, op_id: &str) -> Result<ScanReport, AppError> {
+    let path = report_path(app, op_id).map_err(|e| AppError::new(ErrorCode::Internal, e))?;
+    let data = fs::read_to_string(&path).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AppError::new(ErrorCode::NotFound, format!("No saved report for scan {:?}", op_id))
+        } else {
+            AppError::io(&e).with_path(&path)
+        }
+    })?;
+    serde_json::from_str(&data).map_err(|e| {
+        AppError::new(ErrorCode::Internal, format!("Corrupt scan report {:?}: {}", path, e))
+    })
+}
+
+/// Delete the oldest reports beyond MAX_REPORTS.
+fn prune_reports(app: &AppHandle) {
+    let Ok(dir) = report_dir(app) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let mut reports: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| e.path().extension().is_some_and(|x| x == "json"))
+        .filter_map(|e| Some((e.metadata().ok()?.modified().ok()?, e.path())))
+        .collect();
+    if reports.len() <= MAX_REPORTS {
+        return;
+    }
+    reports.sort_by_key(|r| std::cmp::Reverse(r.0));
+    for (_, path) in reports.into_iter().skip(MAX_REPORTS) {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!("[ScanReport] Failed to remove {:?}: {e}", path);
+        }
+    }
+}
+
+/// One CSV field, quoted when needed (RFC 4180).
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_report(report: &ScanReport, format: ReportFormat, dest: &Path) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(dest)?);
+    match format {
+        ReportFormat::Json => serde_json::to_writer_pretty(&mut out, report)?,
+        ReportFormat::Csv => {
+            writeln!(out, "path,depth,size,file_count,folder_count,own_size,own_file_count")?;
+            for d in &report.dirs {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{}",
+                    csv_field(&d.path),
+                    d.depth,
+                    d.size,
+                    d.file_count,
+                    d.folder_count,
+                    d.own_size,
+                    d.own_file_count
+                )?;
+            }
+        }
+    }
+    out.flush()
+}
+
+/// Write the report of an earlier scan (started with saveReport) to `dest`.
+/// Sizes are in bytes.
+///
+/// Command from TS:
+/// invoke("export_scan_report", { opId, format: "csv", dest: "C:\\Users\\me\\Desktop\\usage.csv" })
+#[tauri::command]
+pub async fn export_scan_report(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    format: ReportFormat,
+    dest: String,
+) -> Result<(), AppError> {
+    let dest = guard.check(&dest)?;
+    task::spawn_blocking(move || {
+        let report = load_report(&app, &op_id)?;
+        write_report(&report, format, &dest).map_err(|e| {
+            // Never leave a half-written report behind.
+            let _ = fs::remove_file(&dest);
+            AppError::io(&e).with_path(&dest)
+        })
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Report export task failed: {}", e)))?
+}
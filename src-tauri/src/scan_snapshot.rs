@@ -0,0 +1,129 @@
+// src-tauri/src/scan_snapshot.rs
+//
+// Persisted folder scan results for incremental rescans.
+// One JSON file per scan root under the app cache dir:
+//   <app cache dir>/scan-snapshots/<blake3(root)[..16]>.json
+//
+// Per directory we keep its mtime, the files directly inside it (count +
+// bytes) and the names of its subdirectories. A directory's mtime changes
+// when entries are added, removed or renamed in it, so an unchanged mtime
+// means its direct file list can be reused without listing it again.
+//
+// Known limit (same as other mtime-based scanners): a file rewritten in
+// place doesn't bump its folder's mtime, so its new size is picked up on
+// the next full scan or once something else changes in that folder.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirRecord {
+    /// Directory mtime in nanoseconds since UNIX_EPOCH.
+    pub mtime_ns: u64,
+    /// Regular files directly in this directory.
+    pub files: u64,
+    pub bytes: u64,
+    /// Names of direct subdirectories (symlinks excluded).
+    pub subdirs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSnapshot {
+    pub root: String,
+    /// Keyed by path relative to root ("" = root itself, "/" separated).
+    pub dirs: HashMap<String, DirRecord>,
+}
+
+impl ScanSnapshot {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_string_lossy().to_string(),
+            dirs: HashMap::new(),
+        }
+    }
+
+    /// Record a directory (WalkDir yields parents first, so the parent
+    /// record already exists when a child is added).
+    pub fn add_dir(&mut self, rel: &Path, mtime_ns: u64) {
+        let key = rel_key(rel);
+        if let Some(name) = rel.file_name() {
+            let parent = rel_key(rel.parent().unwrap_or(Path::new("")));
+            if let Some(parent) = self.dirs.get_mut(&parent) {
+                parent.subdirs.push(name.to_string_lossy().to_string());
+            }
+        }
+        self.dirs.insert(
+            key,
+            DirRecord {
+                mtime_ns,
+                files: 0,
+                bytes: 0,
+                subdirs: Vec::new(),
+            },
+        );
+    }
+
+    pub fn add_file(&mut self, rel: &Path, size: u64) {
+        let parent = rel_key(rel.parent().unwrap_or(Path::new("")));
+        if let Some(record) = self.dirs.get_mut(&parent) {
+            record.files += 1;
+            record.bytes = record.bytes.saturating_add(size);
+        }
+    }
+}
+
+/// "" for the root, "a/b" otherwise (same on every OS).
+pub fn rel_key(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn mtime_ns(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn snapshot_path(app: &AppHandle, root: &Path) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("App cache dir error: {}", e))?;
+    let hash = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    Ok(dir.join("scan-snapshots").join(format!("{}.json", &hash[..16])))
+}
+
+/// None if there is no snapshot for this root (or it is unreadable).
+pub fn load_snapshot(app: &AppHandle, root: &Path) -> Option<ScanSnapshot> {
+    let path = snapshot_path(app, root).ok()?;
+    let data = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str::<ScanSnapshot>(&data) {
+        Ok(snapshot) if snapshot.root == root.to_string_lossy() => Some(snapshot),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("[ScanSnapshot] Ignoring corrupt snapshot {:?}: {e}", path);
+            None
+        }
+    }
+}
+
+pub fn save_snapshot(app: &AppHandle, snapshot: &ScanSnapshot) -> Result<(), String> {
+    let path = snapshot_path(app, Path::new(&snapshot.root))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create snapshot dir {:?}: {}", parent, e))?;
+    }
+    let data = serde_json::to_string(snapshot)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write snapshot: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace snapshot: {}", e))
+}
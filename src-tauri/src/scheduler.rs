@@ -0,0 +1,534 @@
+// src-tauri/src/scheduler.rs
+//
+// Scheduled tasks: nightly scan of a drive, weekly duplicate check,
+// periodic one-way folder sync, ...
+//
+// Persisted at <app config dir>/schedules.json:
+//
+//   {
+//     "schedules": [
+//       { "id": "…", "name": "Nightly D:", "enabled": true,
+//         "task": { "kind": "folderScan", "path": "D:\\" },
+//         "trigger": { "type": "cron", "expression": "0 3 * * *" }, ... }
+//     ],
+//     "history": { "<schedule id>": [ { "opId": "…", "status": "ok", ... } ] }
+//   }
+//
+// Triggers are an interval in minutes or a 5-field cron expression in local
+// time (croner; "0 3 * * MON-FRI", "*/30 * * * *", ...). A run that was due
+// while the app was closed happens once shortly after startup.
+//
+// Runs go through the same commands the frontend uses (start_folder_scan,
+// start_duplicate_scan, start_copy_operation), so they show up in GPS with
+// their usual events; op ids are "schedule-<id>-<unix secs>". Their
+// completion events are watched to fill the run history (last 20 runs per
+// schedule). A schedule whose previous run is still going is not started
+// again. Every change emits `fu:schedules_changed` with the list.
+// Folder syncs bypass the IPC layer, so they check safe mode (safe_mode.rs)
+// themselves and end as an error run while it is on.
+//
+// State: .manage(start_scheduler(app.handle().clone()))
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{Local, TimeZone};
+use croner::Cron;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use tokio::sync::Notify;
+
+use crate::dedupe::start_duplicate_scan;
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::{start_copy_operation, ConflictPolicy, CopyOptions};
+use crate::folder_scan::{start_folder_scan, FolderScanOptions};
+use crate::path_guard::PathGuard;
+use crate::safe_mode;
+
+const SCHEDULES_FILE: &str = "schedules.json";
+/// Runs kept per schedule.
+const HISTORY_LIMIT: usize = 20;
+/// Let the app finish starting before catching up on missed runs.
+const STARTUP_DELAY: Duration = Duration::from_secs(30);
+/// Longest single sleep, so clock changes (sleep / DST) are noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+/// Completion events of the operations schedules can start.
+const COMPLETION_EVENTS: [&str; 3] = [
+    "fu:folder_scan_completed",
+    "fu:duplicate_scan_completed",
+    "fu:file_op_completed",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ScheduleTrigger {
+    Interval { minutes: u64 },
+    /// Minute, hour, day of month, month, day of week; local time.
+    Cron { expression: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ScheduledTask {
+    FolderScan {
+        path: String,
+        #[serde(default)]
+        options: Option<FolderScanOptions>,
+    },
+    DuplicateScan {
+        path: String,
+        #[serde(default)]
+        min_size: Option<u64>,
+    },
+    /// One-way: copies what is new or newer in `source` into `destination`
+    /// (times preserved); nothing is deleted. `bandwidthLimit` caps the
+    /// sync in bytes per second (0 = only the global IO throttle).
+    FolderSync {
+        source: String,
+        destination: String,
+        #[serde(default)]
+        bandwidth_limit: u64,
+    },
+}
+
+impl ScheduledTask {
+    fn paths(&self) -> Vec<&str> {
+        match self {
+            ScheduledTask::FolderScan { path, .. } | ScheduledTask::DuplicateScan { path, .. } => vec![path],
+            ScheduledTask::FolderSync { source, destination, .. } => vec![source, destination],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub id: String,
+    pub name: String,
+    pub task: ScheduledTask,
+    pub trigger: ScheduleTrigger,
+    pub enabled: bool,
+    /// Seconds since UNIX_EPOCH.
+    pub created_at: u64,
+    #[serde(default)]
+    pub last_run: Option<u64>,
+    /// None when disabled (or the cron expression never matches again).
+    #[serde(default)]
+    pub next_run: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub op_id: String,
+    pub started_at: u64,
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    /// "running" | "ok" | "partial" | "cancelled" | "error"
+    pub status: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SchedulerFile {
+    schedules: Vec<Schedule>,
+    history: HashMap<String, Vec<ScheduleRun>>,
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    state: Arc<Mutex<SchedulerFile>>,
+    wake: Arc<Notify>,
+}
+
+impl Scheduler {
+    fn load(app: &AppHandle) -> Self {
+        let mut file: SchedulerFile = schedules_path(app)
+            .ok()
+            .filter(|p| p.exists())
+            .and_then(|p| {
+                let data = fs::read_to_string(&p).ok()?;
+                match serde_json::from_str(&data) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        tracing::warn!("[Scheduler] Invalid {:?} ({e}), ignoring", p);
+                        None
+                    }
+                }
+            })
+            .unwrap_or_default();
+        // Runs cut short by the last shutdown will never report back.
+        for run in file.history.values_mut().flatten() {
+            if run.status == "running" {
+                run.status = "error".to_string();
+                run.error = Some("App closed while running".to_string());
+            }
+        }
+        Scheduler {
+            state: Arc::new(Mutex::new(file)),
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SchedulerFile> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn schedules(&self) -> Vec<Schedule> {
+        self.lock().schedules.clone()
+    }
+
+    /// Apply `change`, persist, emit `fu:schedules_changed` and re-plan.
+    fn update<T>(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut SchedulerFile) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let (value, schedules) = {
+            let mut state = self.lock();
+            let mut next = state.clone();
+            let value = change(&mut next)?;
+            save(app, &next)?;
+            *state = next;
+            (value, state.schedules.clone())
+        };
+        let _ = app.emit("fu:schedules_changed", &schedules);
+        self.wake.notify_one();
+        Ok(value)
+    }
+
+    /// Schedules due at `now`: marked as run and re-planned.
+    fn take_due(&self, app: &AppHandle, now: u64) -> Vec<Schedule> {
+        let due = |s: &Schedule| s.enabled && s.next_run.is_some_and(|t| t <= now);
+        if !self.lock().schedules.iter().any(due) {
+            return Vec::new();
+        }
+        self.update(app, |file| {
+            let mut taken = Vec::new();
+            for schedule in file.schedules.iter_mut().filter(|s| due(s)) {
+                schedule.last_run = Some(now);
+                schedule.next_run = next_run(&schedule.trigger, now);
+                taken.push(schedule.clone());
+            }
+            Ok(taken)
+        })
+        .unwrap_or_else(|e| {
+            tracing::warn!("[Scheduler] Failed to save schedules: {}", e.message);
+            Vec::new()
+        })
+    }
+
+    /// Time until the next due schedule, at most MAX_SLEEP.
+    fn sleep_time(&self, now: u64) -> Duration {
+        self.lock()
+            .schedules
+            .iter()
+            .filter(|s| s.enabled)
+            .filter_map(|s| s.next_run)
+            .min()
+            .map(|t| Duration::from_secs(t.saturating_sub(now)))
+            .unwrap_or(MAX_SLEEP)
+            .min(MAX_SLEEP)
+    }
+
+    /// Record the end of a run from its completion event.
+    fn finish_run(&self, app: &AppHandle, op_id: &str, status: &str, error: Option<String>) {
+        let known = self
+            .lock()
+            .history
+            .values()
+            .flatten()
+            .any(|r| r.op_id == op_id && r.status == "running");
+        if !known {
+            return;
+        }
+        let res = self.update(app, |file| {
+            if let Some(run) = file.history.values_mut().flatten().find(|r| r.op_id == op_id) {
+                run.finished_at = Some(now_secs());
+                run.status = status.to_string();
+                run.error = error;
+            }
+            Ok(())
+        });
+        if let Err(e) = res {
+            tracing::warn!("[Scheduler] Failed to save run history: {}", e.message);
+        }
+    }
+
+    fn record_run(&self, app: &AppHandle, schedule_id: &str, run: ScheduleRun) {
+        let res = self.update(app, |file| {
+            let runs = file.history.entry(schedule_id.to_string()).or_default();
+            runs.push(run);
+            if runs.len() > HISTORY_LIMIT {
+                runs.remove(0);
+            }
+            Ok(())
+        });
+        if let Err(e) = res {
+            tracing::warn!("[Scheduler] Failed to save run history: {}", e.message);
+        }
+    }
+
+    fn is_running(&self, schedule_id: &str) -> bool {
+        self.lock()
+            .history
+            .get(schedule_id)
+            .is_some_and(|runs| runs.iter().any(|r| r.status == "running"))
+    }
+}
+
+/// Start the scheduler loop. Keep the returned handle in managed state.
+pub fn start_scheduler(app: AppHandle) -> Scheduler {
+    let scheduler = Scheduler::load(&app);
+
+    for event in COMPLETION_EVENTS {
+        let handle = scheduler.clone();
+        let app_handle = app.clone();
+        app.listen_any(event, move |event| {
+            let Ok(payload) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+                return;
+            };
+            let (Some(op_id), Some(status)) = (payload["opId"].as_str(), payload["status"].as_str()) else {
+                return;
+            };
+            if op_id.starts_with("schedule-") {
+                let error = payload["errorMessage"].as_str().map(str::to_string);
+                handle.finish_run(&app_handle, op_id, status, error);
+            }
+        });
+    }
+
+    let handle = scheduler.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let now = now_secs();
+            for schedule in handle.take_due(&app, now) {
+                run_schedule(&app, &handle, &schedule, now).await;
+            }
+            let sleep = handle.sleep_time(now_secs());
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = handle.wake.notified() => {}
+            }
+        }
+    });
+
+    scheduler
+}
+
+async fn run_schedule(app: &AppHandle, scheduler: &Scheduler, schedule: &Schedule, now: u64) {
+    if scheduler.is_running(&schedule.id) {
+        tracing::info!("[Scheduler] {:?} still running, skipping this run", schedule.name);
+        return;
+    }
+    let op_id = format!("schedule-{}-{}", schedule.id, now);
+    scheduler.record_run(
+        app,
+        &schedule.id,
+        ScheduleRun {
+            op_id: op_id.clone(),
+            started_at: now,
+            finished_at: None,
+            status: "running".to_string(),
+            error: None,
+        },
+    );
+
+    if let Err(e) = start_task(app, &op_id, &schedule.task).await {
+        tracing::warn!("[Scheduler] {:?} failed to start: {}", schedule.name, e.message);
+        scheduler.finish_run(app, &op_id, "error", Some(e.message));
+    }
+}
+
+async fn start_task(app: &AppHandle, op_id: &str, task: &ScheduledTask) -> Result<(), AppError> {
+    let op_id = op_id.to_string();
+    match task.clone() {
+        ScheduledTask::FolderScan { path, options } => {
+            start_folder_scan(app.clone(), app.state(), app.state(), app.state(), op_id, path.into(), options).await
+        }
+        ScheduledTask::DuplicateScan { path, min_size } => {
+            start_duplicate_scan(app.clone(), app.state(), app.state(), op_id, path, min_size)
+                .await
+                .map_err(|e| AppError::new(ErrorCode::Io, e))
+        }
+        ScheduledTask::FolderSync {
+            source,
+            destination,
+            bandwidth_limit,
+        } => {
+            safe_mode::check(app, "folder sync")?;
+            // Checked again now: the folders may have changed since the
+            // schedule was saved.
+            let guard = app.state::<PathGuard>();
+            let source = guard.check(&source)?;
+            let destination = guard.check(&destination)?.to_string_lossy().to_string();
+            let sources = fs::read_dir(&source)
+                .map_err(|e| AppError::io(&e).with_path(&source))?
+                .flatten()
+                .map(|entry| entry.path().to_string_lossy().to_string())
+                .collect();
+            fs::create_dir_all(&destination).map_err(|e| AppError::io(&e).with_path(&destination))?;
+            let copy_options = CopyOptions {
+                preserve_times: true,
+                ..Default::default()
+            };
+            start_copy_operation(
+                app.clone(),
+                app.state(),
+                app.state(),
+                app.state(),
+                op_id,
+                sources,
+                destination,
+                Some(ConflictPolicy::OverwriteIfNewer),
+                Some(copy_options),
+                Some(bandwidth_limit),
+            )
+            .await
+        }
+    }
+}
+
+/// First run strictly after `after` (seconds since UNIX_EPOCH).
+fn next_run(trigger: &ScheduleTrigger, after: u64) -> Option<u64> {
+    match trigger {
+        ScheduleTrigger::Interval { minutes } => Some(after + minutes.max(&1) * 60),
+        ScheduleTrigger::Cron { expression } => {
+            let cron = Cron::from_str(expression).ok()?;
+            let start = Local.timestamp_opt(after as i64, 0).single()?;
+            let next = cron.find_next_occurrence(&start, false).ok()?;
+            u64::try_from(next.timestamp()).ok()
+        }
+    }
+}
+
+fn validate_trigger(trigger: &ScheduleTrigger) -> Result<(), AppError> {
+    match trigger {
+        ScheduleTrigger::Interval { minutes: 0 } => Err(AppError::invalid_input("Interval must be at least 1 minute")),
+        ScheduleTrigger::Interval { .. } => Ok(()),
+        ScheduleTrigger::Cron { expression } => Cron::from_str(expression)
+            .map(|_| ())
+            .map_err(|e| AppError::invalid_input(format!("Invalid cron expression {:?}: {}", expression, e))),
+    }
+}
+
+fn schedules_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("App config dir error: {}", e)))?;
+    Ok(dir.join(SCHEDULES_FILE))
+}
+
+fn save(app: &AppHandle, file: &SchedulerFile) -> Result<(), AppError> {
+    let path = schedules_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(&e).context("Failed to create config dir"))?;
+    }
+    let data = serde_json::to_string_pretty(file)
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Failed to serialize schedules: {}", e)))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| AppError::io(&e).context("Failed to write schedules"))?;
+    fs::rename(&tmp, &path).map_err(|e| AppError::io(&e).context("Failed to replace schedules file"))?;
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn new_id() -> Result<String, AppError> {
+    let mut bytes = [0u8; 8];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| AppError::new(ErrorCode::Internal, "System random generator failed"))?;
+    Ok(hex::encode(bytes))
+}
+
+/// Frontend can call:
+///   invoke<Schedule>('create_schedule', {
+///     name: 'Weekly duplicates',
+///     task: { kind: 'duplicateScan', path: 'D:\\Photos' },
+///     trigger: { type: 'cron', expression: '0 4 * * SUN' },
+///   })
+#[tauri::command]
+pub fn create_schedule(
+    app: AppHandle,
+    scheduler: State<'_, Scheduler>,
+    guard: State<'_, PathGuard>,
+    name: String,
+    task: ScheduledTask,
+    trigger: ScheduleTrigger,
+    enabled: Option<bool>,
+) -> Result<Schedule, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid_input("Schedule name must not be empty"));
+    }
+    // Rejected up front; start_task checks again when the schedule runs.
+    for path in task.paths() {
+        guard.check(path)?;
+    }
+    validate_trigger(&trigger)?;
+
+    let now = now_secs();
+    let enabled = enabled.unwrap_or(true);
+    let schedule = Schedule {
+        id: new_id()?,
+        name: name.trim().to_string(),
+        next_run: enabled.then(|| next_run(&trigger, now)).flatten(),
+        task,
+        trigger,
+        enabled,
+        created_at: now,
+        last_run: None,
+    };
+    scheduler.update(&app, |file| {
+        file.schedules.push(schedule.clone());
+        Ok(())
+    })?;
+    Ok(schedule)
+}
+
+/// Frontend can call:
+///   invoke<Schedule[]>('list_schedules')
+#[tauri::command]
+pub fn list_schedules(scheduler: State<'_, Scheduler>) -> Vec<Schedule> {
+    scheduler.schedules()
+}
+
+/// Remove a schedule and its history; a run in progress keeps going.
+///
+/// Frontend can call:
+///   invoke('delete_schedule', { id })
+#[tauri::command]
+pub fn delete_schedule(app: AppHandle, scheduler: State<'_, Scheduler>, id: String) -> Result<(), AppError> {
+    scheduler.update(&app, |file| {
+        let before = file.schedules.len();
+        file.schedules.retain(|s| s.id != id);
+        if file.schedules.len() == before {
+            return Err(AppError::new(ErrorCode::NotFound, format!("No schedule {}", id)));
+        }
+        file.history.remove(&id);
+        Ok(())
+    })
+}
+
+/// Past runs of a schedule, oldest first.
+///
+/// Frontend can call:
+///   invoke<ScheduleRun[]>('get_schedule_history', { id })
+#[tauri::command]
+pub fn get_schedule_history(scheduler: State<'_, Scheduler>, id: String) -> Vec<ScheduleRun> {
+    scheduler.lock().history.get(&id).cloned().unwrap_or_default()
+}
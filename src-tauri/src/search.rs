@@ -0,0 +1,658 @@
+// src-tauri/src/search.rs
+//
+// Search subsystem with streaming results. Two independent commands:
+//
+// start_content_search ("grep"):
+// - Walks a root (like folder_scan), filters files by include/exclude
+//   globs and max size, skips binary files (NUL byte in the first 8 KiB).
+// - Matches line by line with a single regex (plain queries are escaped),
+//   emitting one `fu:search_match` event per hit.
+// - Cancellable through OperationRegistry; stops early at `max_matches`.
+//
+// start_name_search:
+// - Matches entry NAMES only (substring / glob / regex / fuzzy).
+// - Buffers hits and emits them in `fu:name_search_batch` events,
+//   each batch sorted by score, so the UI can merge ranked chunks.
+//
+// Both take the shared FileFilter (file_filter.rs) as `options.filter`.
+
+use crate::file_filter::{FileFilter, FileFilterSpec, FilterEntry};
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use globset::{Glob, GlobMatcher, GlobSet, GlobSetBuilder};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+/// Bytes inspected to decide whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+/// Longest snippet sent to the frontend (characters).
+const MAX_SNIPPET_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentSearchOptions {
+    /// Treat `query` as a regular expression instead of plain text.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Only search files matching one of these globs (relative to root).
+    pub include_globs: Vec<String>,
+    /// Skip files/folders matching any of these globs.
+    pub exclude_globs: Vec<String>,
+    /// Skip files larger than this many bytes.
+    pub max_file_size: u64,
+    /// Stop after this many matches in total.
+    pub max_matches: u64,
+    /// Shared size / date / attribute filter (file_filter.rs), on top of
+    /// the globs above.
+    pub filter: FileFilterSpec,
+}
+
+impl Default for ContentSearchOptions {
+    fn default() -> Self {
+        Self {
+            regex: false,
+            case_sensitive: false,
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            max_file_size: 20 * 1024 * 1024,
+            max_matches: 10_000,
+            filter: FileFilterSpec::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SearchMatch {
+    op_id: String,
+    path: String,
+    line_number: u64,
+    snippet: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SearchProgress {
+    op_id: String,
+    files_scanned: u64,
+    match_count: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SearchCompleted {
+    op_id: String,
+    status: String, // "ok" | "limit" | "cancelled" | "error"
+    files_scanned: u64,
+    match_count: u64,
+    error_message: Option<String>,
+}
+
+/// Command from TS:
+/// invoke("start_content_search", { opId, path, query, options })
+///
+/// Invalid regex / glob patterns are rejected up front with an error,
+/// before any background work is started.
+#[tauri::command]
+pub async fn start_content_search(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+    query: String,
+    options: Option<ContentSearchOptions>,
+) -> Result<(), String> {
+    let root = guard.check(&path)?;
+    let options = options.unwrap_or_default();
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+
+    let pattern = if options.regex {
+        query.clone()
+    } else {
+        regex::escape(&query)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))?;
+    let include = build_globset(&options.include_globs)?;
+    let exclude = build_globset(&options.exclude_globs)?;
+    let filter = FileFilter::from_spec(Some(&options.filter))?;
+
+    let token = registry.register(&op_id, OperationKind::ContentSearch);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut search = ContentSearch {
+            app: &app,
+            op_id: &op_id,
+            token: &token,
+            matcher,
+            include,
+            exclude,
+            filter,
+            options,
+            files_scanned: 0,
+            match_count: 0,
+            last_emit: Instant::now(),
+        };
+
+        let (status, error_message) = match search.run(&root) {
+            Ok(SearchEnd::Done) => ("ok", None),
+            Ok(SearchEnd::LimitReached) => ("limit", None),
+            Ok(SearchEnd::Cancelled) => ("cancelled", None),
+            Err(e) => ("error", Some(format!("I/O error: {}", e))),
+        };
+
+        let _ = app.emit(
+            "fu:search_completed",
+            SearchCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                files_scanned: search.files_scanned,
+                match_count: search.match_count,
+                error_message,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Walk side of the shared filter: folders it excludes are not entered.
+fn filter_allows_dir(filter: Option<&FileFilter>, rel: &Path, entry: &walkdir::DirEntry) -> bool {
+    let Some(filter) = filter else {
+        return true;
+    };
+    if !entry.file_type().is_dir() {
+        return true;
+    }
+    entry
+        .metadata()
+        .map(|m| filter.allows_dir(&FilterEntry::from_metadata(rel, &m)))
+        .unwrap_or(true)
+}
+
+/// Compile a list of glob strings; None when the list is empty.
+pub fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for p in patterns {
+        let glob = Glob::new(p).map_err(|e| format!("Invalid glob '{}': {}", p, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Invalid glob set: {}", e))
+}
+
+enum SearchEnd {
+    Done,
+    LimitReached,
+    Cancelled,
+}
+
+struct ContentSearch<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    token: &'a CancellationToken,
+    matcher: Regex,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    filter: Option<FileFilter>,
+    options: ContentSearchOptions,
+    files_scanned: u64,
+    match_count: u64,
+    last_emit: Instant,
+}
+
+impl ContentSearch<'_> {
+    fn run(&mut self, root: &Path) -> std::io::Result<SearchEnd> {
+        std::fs::metadata(root)?;
+
+        let exclude = self.exclude.clone();
+        let filter = self.filter.clone();
+        let walker = WalkDir::new(root).into_iter().filter_entry(|e| {
+            // Prune excluded folders instead of walking into them.
+            let rel = e.path().strip_prefix(root).unwrap_or(e.path());
+            e.depth() == 0
+                || (!exclude.as_ref().is_some_and(|set| set.is_match(rel)) && filter_allows_dir(filter.as_ref(), rel, e))
+        });
+
+        for entry in walker {
+            if self.token.is_cancelled() {
+                return Ok(SearchEnd::Cancelled);
+            }
+
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    tracing::warn!("[Search] WalkDir error: {err}");
+                    continue;
+                }
+            };
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            if let Some(include) = &self.include {
+                if !include.is_match(rel) {
+                    continue;
+                }
+            }
+            match entry.metadata() {
+                Ok(m) if m.len() <= self.options.max_file_size => {
+                    if self.filter.as_ref().is_some_and(|f| !f.matches(&FilterEntry::from_metadata(rel, &m))) {
+                        continue;
+                    }
+                }
+                _ => continue,
+            }
+
+            self.files_scanned += 1;
+            match self.search_file(entry.path()) {
+                Ok(Some(end)) => return Ok(end),
+                Ok(None) => {}
+                Err(err) => tracing::warn!("[Search] Read error for {:?}: {err}", entry.path()),
+            }
+
+            if self.last_emit.elapsed().as_millis() >= 100 {
+                self.emit_progress();
+            }
+        }
+
+        self.emit_progress();
+        Ok(SearchEnd::Done)
+    }
+
+    /// Search one file. Returns Some(..) when the whole search must stop.
+    fn search_file(&mut self, path: &Path) -> std::io::Result<Option<SearchEnd>> {
+        let mut file = File::open(path)?;
+        let mut head = vec![0u8; BINARY_SNIFF_BYTES];
+        let n = read_up_to(&mut file, &mut head)?;
+        if head[..n].contains(&0) {
+            return Ok(None);
+        }
+        head.truncate(n);
+
+        let mut reader = BufReader::new(std::io::Cursor::new(head).chain(file));
+        let mut line = Vec::new();
+        let mut line_number = 0u64;
+
+        loop {
+            line.clear();
+            if reader.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
+            line_number += 1;
+            if line_number.is_multiple_of(1024) && self.token.is_cancelled() {
+                return Ok(Some(SearchEnd::Cancelled));
+            }
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            if let Some(m) = self.matcher.find(text) {
+                self.match_count += 1;
+                let _ = self.app.emit(
+                    "fu:search_match",
+                    SearchMatch {
+                        op_id: self.op_id.to_string(),
+                        path: path.to_string_lossy().to_string(),
+                        line_number,
+                        snippet: make_snippet(text, m.start()),
+                    },
+                );
+                if self.match_count >= self.options.max_matches {
+                    return Ok(Some(SearchEnd::LimitReached));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn emit_progress(&mut self) {
+        let _ = self.app.emit(
+            "fu:search_progress",
+            SearchProgress {
+                op_id: self.op_id.to_string(),
+                files_scanned: self.files_scanned,
+                match_count: self.match_count,
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
+
+/// Fill `buf` as far as possible (read() may return short counts).
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = file.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Cut a long line down to a window around the match start.
+fn make_snippet(line: &str, match_start: usize) -> String {
+    let trimmed = line.trim_start();
+    let offset = line.len() - trimmed.len();
+    let start_char = trimmed[..match_start.saturating_sub(offset).min(trimmed.len())]
+        .chars()
+        .count();
+
+    let skip = start_char.saturating_sub(MAX_SNIPPET_CHARS / 4);
+    let mut snippet: String = trimmed.chars().skip(skip).take(MAX_SNIPPET_CHARS).collect();
+    if skip > 0 {
+        snippet.insert(0, '…');
+    }
+    snippet
+}
+
+// ======================= Filename search =======================
+
+/// Flush a name-search batch at this many hits (or every 150ms).
+const NAME_BATCH_SIZE: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameMatchMode {
+    #[default]
+    Substring,
+    Glob,
+    Regex,
+    Fuzzy,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NameSearchOptions {
+    pub mode: NameMatchMode,
+    pub case_sensitive: bool,
+    /// Also report matching folders, not only files.
+    pub include_dirs: bool,
+    /// Stop after this many hits in total.
+    pub max_results: u64,
+    /// Shared size / date / attribute filter (file_filter.rs).
+    pub filter: FileFilterSpec,
+}
+
+impl Default for NameSearchOptions {
+    fn default() -> Self {
+        Self {
+            mode: NameMatchMode::Substring,
+            case_sensitive: false,
+            include_dirs: true,
+            max_results: 5_000,
+            filter: FileFilterSpec::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NameMatch {
+    path: String,
+    name: String,
+    is_dir: bool,
+    /// Higher is better. Only meaningful within one search.
+    score: i64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NameSearchBatch {
+    op_id: String,
+    batch_index: u64,
+    results: Vec<NameMatch>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NameSearchCompleted {
+    op_id: String,
+    status: String, // "ok" | "limit" | "cancelled" | "error"
+    entries_scanned: u64,
+    match_count: u64,
+    error_message: Option<String>,
+}
+
+/// Compiled name matcher for one search.
+enum NameMatcher {
+    Substring { needle: String, case_sensitive: bool },
+    Glob(GlobMatcher),
+    Regex(Regex),
+    Fuzzy { matcher: Box<SkimMatcherV2>, pattern: String },
+}
+
+impl NameMatcher {
+    fn new(pattern: &str, options: &NameSearchOptions) -> Result<Self, String> {
+        let cs = options.case_sensitive;
+        Ok(match options.mode {
+            NameMatchMode::Substring => NameMatcher::Substring {
+                needle: if cs { pattern.to_string() } else { pattern.to_lowercase() },
+                case_sensitive: cs,
+            },
+            NameMatchMode::Glob => NameMatcher::Glob(
+                globset::GlobBuilder::new(pattern)
+                    .case_insensitive(!cs)
+                    .build()
+                    .map_err(|e| format!("Invalid glob '{}': {}", pattern, e))?
+                    .compile_matcher(),
+            ),
+            NameMatchMode::Regex => NameMatcher::Regex(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(!cs)
+                    .build()
+                    .map_err(|e| format!("Invalid search pattern: {}", e))?,
+            ),
+            NameMatchMode::Fuzzy => {
+                let matcher = SkimMatcherV2::default();
+                let matcher = if cs {
+                    matcher.respect_case()
+                } else {
+                    matcher.ignore_case()
+                };
+                NameMatcher::Fuzzy {
+                    matcher: Box::new(matcher),
+                    pattern: pattern.to_string(),
+                }
+            }
+        })
+    }
+
+    /// Score a name; None = no match.
+    fn score(&self, name: &str) -> Option<i64> {
+        match self {
+            NameMatcher::Substring {
+                needle,
+                case_sensitive,
+            } => {
+                let hay = if *case_sensitive {
+                    name.to_string()
+                } else {
+                    name.to_lowercase()
+                };
+                let pos = hay.find(needle.as_str())?;
+                // Exact name > prefix > anywhere; shorter names first.
+                let base = if hay.len() == needle.len() {
+                    300
+                } else if pos == 0 {
+                    200
+                } else {
+                    100
+                };
+                Some(base - (hay.len() as i64).min(99))
+            }
+            NameMatcher::Glob(glob) => glob.is_match(name).then_some(0),
+            NameMatcher::Regex(re) => re.is_match(name).then_some(0),
+            NameMatcher::Fuzzy { matcher, pattern } => matcher.fuzzy_match(name, pattern),
+        }
+    }
+}
+
+/// Command from TS:
+/// invoke("start_name_search", { opId, path, pattern, options })
+///
+/// options.mode: "substring" | "glob" | "regex" | "fuzzy"
+#[tauri::command]
+pub async fn start_name_search(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+    pattern: String,
+    options: Option<NameSearchOptions>,
+) -> Result<(), String> {
+    let root = guard.check(&path)?;
+    let options = options.unwrap_or_default();
+    if pattern.is_empty() {
+        return Err("Search pattern is empty".to_string());
+    }
+    let matcher = NameMatcher::new(&pattern, &options)?;
+    let filter = FileFilter::from_spec(Some(&options.filter))?;
+
+    let token = registry.register(&op_id, OperationKind::NameSearch);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let res = run_name_search_blocking(&app, &op_id, &root, &matcher, filter.as_ref(), &options, &token);
+
+        let (status, scanned, matched, error_message) = match res {
+            Ok((SearchEnd::Done, scanned, matched)) => ("ok", scanned, matched, None),
+            Ok((SearchEnd::LimitReached, scanned, matched)) => ("limit", scanned, matched, None),
+            Ok((SearchEnd::Cancelled, scanned, matched)) => ("cancelled", scanned, matched, None),
+            Err(e) => ("error", 0, 0, Some(format!("I/O error: {}", e))),
+        };
+
+        let _ = app.emit(
+            "fu:name_search_completed",
+            NameSearchCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                entries_scanned: scanned,
+                match_count: matched,
+                error_message,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+/// Returns (how it ended, entries scanned, matches found).
+fn run_name_search_blocking(
+    app: &AppHandle,
+    op_id: &str,
+    root: &Path,
+    matcher: &NameMatcher,
+    filter: Option<&FileFilter>,
+    options: &NameSearchOptions,
+    token: &CancellationToken,
+) -> std::io::Result<(SearchEnd, u64, u64)> {
+    std::fs::metadata(root)?;
+
+    let mut scanned = 0u64;
+    let mut matched = 0u64;
+    let mut batch: Vec<NameMatch> = Vec::with_capacity(NAME_BATCH_SIZE);
+    let mut batch_index = 0u64;
+    let mut last_emit = Instant::now();
+
+    let flush = |batch: &mut Vec<NameMatch>, batch_index: &mut u64| {
+        if batch.is_empty() {
+            return;
+        }
+        batch.sort_by_key(|m| std::cmp::Reverse(m.score));
+        let _ = app.emit(
+            "fu:name_search_batch",
+            NameSearchBatch {
+                op_id: op_id.to_string(),
+                batch_index: *batch_index,
+                results: std::mem::take(batch),
+            },
+        );
+        *batch_index += 1;
+    };
+
+    let mut end = SearchEnd::Done;
+    // min_depth(1): the root itself is never a result.
+    let walker = WalkDir::new(root).min_depth(1).into_iter().filter_entry(|e| {
+        filter_allows_dir(filter, e.path().strip_prefix(root).unwrap_or(e.path()), e)
+    });
+    for entry in walker {
+        if token.is_cancelled() {
+            end = SearchEnd::Cancelled;
+            break;
+        }
+
+        let entry = match entry {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::warn!("[NameSearch] WalkDir error: {err}");
+                continue;
+            }
+        };
+        scanned += 1;
+
+        let is_dir = entry.file_type().is_dir();
+        if is_dir && !options.include_dirs {
+            continue;
+        }
+        if let Some(filter) = filter {
+            let rel = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            match entry.metadata() {
+                Ok(m) if filter.matches(&FilterEntry::from_metadata(rel, &m)) => {}
+                _ => continue,
+            }
+        }
+
+        let name = entry.file_name().to_string_lossy();
+        if let Some(score) = matcher.score(&name) {
+            batch.push(NameMatch {
+                path: entry.path().to_string_lossy().to_string(),
+                name: name.to_string(),
+                is_dir,
+                score,
+            });
+            matched += 1;
+            if matched >= options.max_results {
+                end = SearchEnd::LimitReached;
+                break;
+            }
+        }
+
+        if batch.len() >= NAME_BATCH_SIZE || last_emit.elapsed().as_millis() >= 150 {
+            flush(&mut batch, &mut batch_index);
+            last_emit = Instant::now();
+        }
+    }
+
+    flush(&mut batch, &mut batch_index);
+    Ok((end, scanned, matched))
+}
+
@@ -0,0 +1,735 @@
+// src-tauri/src/search_index.rs
+//
+// Persistent search index, so a name search on a big drive doesn't walk
+// the whole tree (search.rs does that for unindexed folders).
+//
+// The index is an SQLite database under the app cache dir (it can always
+// be rebuilt from disk):
+//   <app cache dir>/search-index.sqlite3
+// - files:    one row per entry below an indexed root
+// - file_fts: FTS5 table with the trigram tokenizer over the name (and, for
+//             roots added with withContent, the text of small text files),
+//             so substring queries are answered from the index
+//
+// Keeping it current:
+// - rebuild_index walks a root and upserts every entry with a new
+//   generation number, then deletes the rows the walk didn't see. Searches
+//   keep working on the old rows meanwhile. Runs as a registered operation
+//   ("index-build-<root>"), so it shows up in list_operations and can be
+//   cancelled.
+// - Each root has its own recursive notify watcher. Changed paths are
+//   collected for a second and then re-stat'ed: present -> upsert (a folder
+//   that is new to the index is indexed with everything below it), gone ->
+//   the row and everything below it is dropped.
+// - On startup every root is rebuilt once in the background, to pick up
+//   changes made while the app wasn't running.
+// `fu:index_status` (IndexRootStatus) reports building / ready / error.
+//
+// Roots may not overlap (a path belongs to exactly one root).
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, Metadata};
+use std::io::Read;
+use std::path::{Path, PathBuf, MAIN_SEPARATOR};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+use crate::background_priority;
+use crate::error::{AppError, ErrorCode};
+use crate::file_type::looks_like_text;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+
+const INDEX_FILE: &str = "search-index.sqlite3";
+const SCHEMA_VERSION: i32 = 1;
+/// Entries per write transaction while building.
+const BATCH_SIZE: usize = 1000;
+/// Text files larger than this are indexed by name only.
+const MAX_CONTENT_BYTES: u64 = 1024 * 1024;
+/// Quiet period before watched changes are applied.
+const UPDATE_DEBOUNCE_MS: u64 = 1_000;
+/// Upper bound for one batch of watched changes.
+const UPDATE_MAX_BATCH_MS: u64 = 5_000;
+const DEFAULT_LIMIT: u32 = 200;
+const MAX_LIMIT: u32 = 5_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS roots (
+    path         TEXT PRIMARY KEY,
+    with_content INTEGER NOT NULL,
+    generation   INTEGER NOT NULL DEFAULT 0,
+    indexed_at   INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS files (
+    id         INTEGER PRIMARY KEY,
+    path       TEXT NOT NULL UNIQUE,
+    root       TEXT NOT NULL,
+    name       TEXT NOT NULL,
+    is_dir     INTEGER NOT NULL,
+    size       INTEGER NOT NULL,
+    modified   INTEGER NOT NULL,
+    generation INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS files_by_root ON files(root, generation);
+CREATE VIRTUAL TABLE IF NOT EXISTS file_fts USING fts5(name, body, tokenize = 'trigram');
+CREATE TRIGGER IF NOT EXISTS files_ai AFTER INSERT ON files BEGIN
+    INSERT INTO file_fts(rowid, name, body) VALUES (new.id, new.name, '');
+END;
+CREATE TRIGGER IF NOT EXISTS files_ad AFTER DELETE ON files BEGIN
+    DELETE FROM file_fts WHERE rowid = old.id;
+END;
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexState {
+    Building,
+    Ready,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRootStatus {
+    pub path: String,
+    pub with_content: bool,
+    pub state: IndexState,
+    pub file_count: u64,
+    /// Milliseconds since UNIX_EPOCH of the last finished build; 0 = never.
+    pub indexed_at: u64,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IndexSearchOptions {
+    /// Only hits below this indexed root.
+    pub root: Option<String>,
+    /// Also match the text of files in roots indexed withContent.
+    pub content: bool,
+    /// Default 200, at most 5000.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexHit {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Seconds since UNIX_EPOCH, like FileEntry.modified.
+    pub modified: u64,
+}
+
+/// State plus error message.
+type RootState = (IndexState, Option<String>);
+
+/// Index handle in Tauri managed state (cheap Arc clone for workers).
+#[derive(Clone)]
+pub struct SearchIndex {
+    conn: Arc<Mutex<Connection>>,
+    /// Per-root state that isn't stored: building / last error.
+    states: Arc<Mutex<HashMap<String, RootState>>>,
+    watchers: Arc<Mutex<HashMap<String, RecommendedWatcher>>>,
+}
+
+/// Open the index, watch its roots and refresh them in the background.
+/// Called once from setup, after OperationRegistry is managed.
+pub fn start_search_index(app: &AppHandle) {
+    let conn = index_path(app)
+        .and_then(|path| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            Connection::open(&path).map_err(|e| e.to_string())
+        })
+        .and_then(|conn| init_schema(&conn).map(|_| conn))
+        .unwrap_or_else(|e| {
+            tracing::error!("[SearchIndex] Failed to open index, using in-memory: {e}");
+            let conn = Connection::open_in_memory().expect("in-memory SQLite");
+            let _ = init_schema(&conn);
+            conn
+        });
+    let index = SearchIndex {
+        conn: Arc::new(Mutex::new(conn)),
+        states: Arc::default(),
+        watchers: Arc::default(),
+    };
+    app.manage(index.clone());
+
+    let roots = index.roots().unwrap_or_else(|e| {
+        tracing::warn!("[SearchIndex] {}", e.message);
+        Vec::new()
+    });
+    for (root, _) in roots {
+        index.watch(&root);
+        index.spawn_build(app, root);
+    }
+}
+
+impl SearchIndex {
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn roots(&self) -> Result<Vec<(String, bool)>, AppError> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT path, with_content FROM roots ORDER BY path").map_err(db_error)?;
+        let roots = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+            .map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(db_error)?;
+        Ok(roots)
+    }
+
+    fn set_state(&self, app: &AppHandle, root: &str, state: IndexState, error: Option<String>) {
+        self.states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(root.to_string(), (state, error));
+        match self.status(root) {
+            Ok(Some(status)) => {
+                let _ = app.emit("fu:index_status", status);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("[SearchIndex] {}", e.message),
+        }
+    }
+
+    fn status(&self, root: &str) -> Result<Option<IndexRootStatus>, AppError> {
+        let row: Option<(bool, i64)> = self
+            .conn()
+            .query_row(
+                "SELECT with_content, indexed_at FROM roots WHERE path = ?1",
+                params![root],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()
+            .map_err(db_error)?;
+        let Some((with_content, indexed_at)) = row else {
+            return Ok(None);
+        };
+        let file_count: i64 = self
+            .conn()
+            .query_row("SELECT COUNT(*) FROM files WHERE root = ?1", params![root], |r| r.get(0))
+            .map_err(db_error)?;
+        let (state, error_message) = self
+            .states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(root)
+            .cloned()
+            .unwrap_or((IndexState::Ready, None));
+        Ok(Some(IndexRootStatus {
+            path: root.to_string(),
+            with_content,
+            state,
+            file_count: file_count as u64,
+            indexed_at: indexed_at as u64,
+            error_message,
+        }))
+    }
+
+    fn watch(&self, root: &str) {
+        let (tx, rx) = channel::<notify::Event>();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) => {
+                let _ = tx.send(event);
+            }
+            Err(err) => tracing::warn!("[SearchIndex] Watch error: {err}"),
+        });
+        let mut watcher = match watcher {
+            Ok(w) => w,
+            Err(e) => {
+                tracing::warn!("[SearchIndex] Failed to create watcher for {root}: {e}");
+                return;
+            }
+        };
+        if let Err(e) = watcher.watch(Path::new(root), RecursiveMode::Recursive) {
+            tracing::warn!("[SearchIndex] Failed to watch {root}: {e}");
+            return;
+        }
+        let index = self.clone();
+        let root_key = root.to_string();
+        thread::spawn(move || index.update_loop(&root_key, rx));
+        self.watchers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(root.to_string(), watcher);
+    }
+
+    /// Exits when the root's watcher is dropped (remove_index_root).
+    fn update_loop(&self, root: &str, rx: Receiver<notify::Event>) {
+        let mut pending: HashSet<PathBuf> = HashSet::new();
+        let mut batch_started: Option<Instant> = None;
+        loop {
+            let res = match batch_started {
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                Some(_) => rx.recv_timeout(Duration::from_millis(UPDATE_DEBOUNCE_MS)),
+            };
+            match res {
+                Ok(event) => {
+                    if !matches!(event.kind, notify::EventKind::Access(_)) {
+                        pending.extend(event.paths);
+                    }
+                    let started = *batch_started.get_or_insert_with(Instant::now);
+                    if started.elapsed() < Duration::from_millis(UPDATE_MAX_BATCH_MS) {
+                        continue;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            batch_started = None;
+            let paths: Vec<PathBuf> = pending.drain().collect();
+            if let Err(e) = self.apply_changes(root, &paths) {
+                tracing::warn!("[SearchIndex] Failed to update {root}: {}", e.message);
+            }
+        }
+    }
+
+    fn apply_changes(&self, root: &str, paths: &[PathBuf]) -> Result<(), AppError> {
+        let Some((with_content, generation)) = self.root_info(root)? else {
+            return Ok(());
+        };
+        let mut present = Vec::new();
+        {
+            let mut conn = self.conn();
+            let tx = conn.transaction().map_err(db_error)?;
+            for path in paths {
+                match fs::symlink_metadata(path) {
+                    Ok(meta) => present.push((path.clone(), meta)),
+                    Err(_) => {
+                        delete_prefix(&tx, &path.to_string_lossy())?;
+                    }
+                }
+            }
+            tx.commit().map_err(db_error)?;
+        }
+
+        let mut entries = Vec::new();
+        for (path, meta) in present {
+            let known = self.is_indexed(&path)?;
+            let is_dir = meta.is_dir();
+            entries.push((path.clone(), meta));
+            // A folder moved in from outside: index everything below it.
+            if is_dir && !known {
+                entries.extend(walk_entries(&path, None));
+            }
+        }
+        for chunk in entries.chunks(BATCH_SIZE) {
+            self.write_batch(root, generation, with_content, chunk)?;
+        }
+        Ok(())
+    }
+
+    fn root_info(&self, root: &str) -> Result<Option<(bool, i64)>, AppError> {
+        self.conn()
+            .query_row(
+                "SELECT with_content, generation FROM roots WHERE path = ?1",
+                params![root],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()
+            .map_err(db_error)
+    }
+
+    fn is_indexed(&self, path: &Path) -> Result<bool, AppError> {
+        self.conn()
+            .query_row(
+                "SELECT 1 FROM files WHERE path = ?1",
+                params![path.to_string_lossy()],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|r| r.is_some())
+            .map_err(db_error)
+    }
+
+    /// Upsert metadata in one transaction, then read the text of changed
+    /// files without holding the connection, then store it.
+    fn write_batch(&self, root: &str, generation: i64, with_content: bool, batch: &[(PathBuf, Metadata)]) -> Result<(), AppError> {
+        let mut changed = Vec::new();
+        {
+            let mut conn = self.conn();
+            let tx = conn.transaction().map_err(db_error)?;
+            for (path, meta) in batch {
+                if let Some(id) = upsert(&tx, root, generation, path, meta).map_err(db_error)? {
+                    if with_content && meta.is_file() && meta.len() <= MAX_CONTENT_BYTES {
+                        changed.push((id, path));
+                    }
+                }
+            }
+            tx.commit().map_err(db_error)?;
+        }
+        if changed.is_empty() {
+            return Ok(());
+        }
+
+        let bodies: Vec<(i64, String)> = changed
+            .into_iter()
+            .filter_map(|(id, path)| Some((id, read_text(path)?)))
+            .collect();
+        let mut conn = self.conn();
+        let tx = conn.transaction().map_err(db_error)?;
+        for (id, body) in bodies {
+            tx.execute("UPDATE file_fts SET body = ?2 WHERE rowid = ?1", params![id, body])
+                .map_err(db_error)?;
+        }
+        tx.commit().map_err(db_error)
+    }
+
+    fn spawn_build(&self, app: &AppHandle, root: String) {
+        let op_id = format!("index-build-{}", root);
+        let registry = app.state::<OperationRegistry>().inner().clone();
+        let token = registry.register(&op_id, OperationKind::IndexBuild);
+        let index = self.clone();
+        let app = app.clone();
+        self.set_state(&app, &root, IndexState::Building, None);
+
+        background_priority::spawn_blocking(background_priority::enabled(&app), move || {
+            match index.build(&root, &token) {
+                Ok(()) => index.set_state(&app, &root, IndexState::Ready, None),
+                Err(e) if e.code == ErrorCode::Cancelled => index.set_state(&app, &root, IndexState::Ready, None),
+                Err(e) => {
+                    tracing::warn!("[SearchIndex] Build of {root} failed: {}", e.message);
+                    index.set_state(&app, &root, IndexState::Error, Some(e.message));
+                }
+            }
+            registry.finish(&op_id, &token);
+        });
+    }
+
+    fn build(&self, root: &str, token: &CancellationToken) -> Result<(), AppError> {
+        let Some((with_content, generation)) = self.root_info(root)? else {
+            return Ok(());
+        };
+        let generation = generation + 1;
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for entry in walk_entries(Path::new(root), Some(token)) {
+            batch.push(entry);
+            if batch.len() >= BATCH_SIZE {
+                self.write_batch(root, generation, with_content, &batch)?;
+                batch.clear();
+            }
+        }
+        if token.is_cancelled() {
+            return Err(AppError::cancelled());
+        }
+        self.write_batch(root, generation, with_content, &batch)?;
+
+        let conn = self.conn();
+        conn.execute(
+            "DELETE FROM files WHERE root = ?1 AND generation < ?2",
+            params![root, generation],
+        )
+        .map_err(db_error)?;
+        conn.execute(
+            "UPDATE roots SET generation = ?2, indexed_at = ?3 WHERE path = ?1",
+            params![root, generation, now_millis()],
+        )
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+/// Every entry below `dir` (symlinks not followed); unreadable ones are
+/// skipped. Stops early once `token` is cancelled.
+fn walk_entries<'a>(
+    dir: &Path,
+    token: Option<&'a CancellationToken>,
+) -> impl Iterator<Item = (PathBuf, Metadata)> + 'a {
+    WalkDir::new(dir)
+        .min_depth(1)
+        .into_iter()
+        .take_while(move |_| !token.is_some_and(|t| t.is_cancelled()))
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((e.into_path(), meta))
+        })
+}
+
+/// Insert or refresh one row. Returns its id when it is new or changed
+/// (its text needs reading), None when only the generation moved on.
+fn upsert(conn: &Connection, root: &str, generation: i64, path: &Path, meta: &Metadata) -> rusqlite::Result<Option<i64>> {
+    let key = path.to_string_lossy();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| key.to_string());
+    let size = if meta.is_dir() { 0 } else { meta.len() as i64 };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let existing: Option<(i64, i64, i64)> = conn
+        .prepare_cached("SELECT id, size, modified FROM files WHERE path = ?1")?
+        .query_row(params![key], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+        .optional()?;
+    match existing {
+        Some((id, old_size, old_modified)) if old_size == size && old_modified == modified => {
+            conn.prepare_cached("UPDATE files SET generation = ?2, root = ?3 WHERE id = ?1")?
+                .execute(params![id, generation, root])?;
+            Ok(None)
+        }
+        Some((id, _, _)) => {
+            conn.prepare_cached(
+                "UPDATE files SET size = ?2, modified = ?3, is_dir = ?4, generation = ?5, root = ?6 WHERE id = ?1",
+            )?
+            .execute(params![id, size, modified, meta.is_dir(), generation, root])?;
+            Ok(Some(id))
+        }
+        None => {
+            conn.prepare_cached(
+                "INSERT INTO files (path, root, name, is_dir, size, modified, generation)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?
+            .execute(params![key, root, name, meta.is_dir(), size, modified, generation])?;
+            Ok(Some(conn.last_insert_rowid()))
+        }
+    }
+}
+
+/// `path` itself and everything below it.
+fn delete_prefix(conn: &Connection, path: &str) -> Result<usize, AppError> {
+    let prefix = format!("{}{}", path.trim_end_matches(MAIN_SEPARATOR), MAIN_SEPARATOR);
+    conn.execute(
+        "DELETE FROM files WHERE path = ?1 OR substr(path, 1, length(?2)) = ?2",
+        params![path, prefix],
+    )
+    .map_err(db_error)
+}
+
+/// Text of a small file, None for binary content.
+fn read_text(path: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    File::open(path).ok()?.take(MAX_CONTENT_BYTES).read_to_end(&mut bytes).ok()?;
+    looks_like_text(&bytes).then(|| String::from_utf8_lossy(&bytes).to_string())
+}
+
+fn index_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("App cache dir error: {}", e))?;
+    Ok(dir.join(INDEX_FILE))
+}
+
+fn init_schema(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")
+        .map_err(|e| format!("Search index error: {}", e))?;
+    conn.execute_batch(SCHEMA).map_err(|e| format!("Search index error: {}", e))?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(|e| format!("Search index error: {}", e))?;
+    Ok(())
+}
+
+fn db_error(e: rusqlite::Error) -> AppError {
+    AppError::new(ErrorCode::Internal, format!("Search index error: {}", e))
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// FTS5 string literal: the whole query is one phrase, no operators.
+fn fts_phrase(query: &str) -> String {
+    format!("\"{}\"", query.replace('"', "\"\""))
+}
+
+/// Start indexing a folder (and keep it indexed across restarts).
+/// `withContent` also indexes the text of files up to 1 MiB.
+///
+/// Frontend can call:
+///   invoke<IndexRootStatus>('add_index_root', { path: 'D:\\', withContent: false })
+#[tauri::command]
+pub fn add_index_root(
+    app: AppHandle,
+    guard: State<'_, PathGuard>,
+    index: State<'_, SearchIndex>,
+    path: String,
+    with_content: Option<bool>,
+) -> Result<IndexRootStatus, AppError> {
+    let root = guard.check(&path)?;
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::NotADirectory, format!("Not a directory: {}", path)));
+    }
+    let key = root.to_string_lossy().to_string();
+    for (other, _) in index.roots()? {
+        if other != key && (root.starts_with(&other) || Path::new(&other).starts_with(&root)) {
+            return Err(AppError::invalid_input(format!("Overlaps the indexed folder {}", other)));
+        }
+    }
+
+    index
+        .conn()
+        .execute(
+            "INSERT INTO roots (path, with_content) VALUES (?1, ?2)
+             ON CONFLICT(path) DO UPDATE SET with_content = excluded.with_content",
+            params![key, with_content.unwrap_or(false)],
+        )
+        .map_err(db_error)?;
+    let watched = index.watchers.lock().unwrap_or_else(|e| e.into_inner()).contains_key(&key);
+    if !watched {
+        index.watch(&key);
+    }
+    index.spawn_build(&app, key.clone());
+    index
+        .status(&key)?
+        .ok_or_else(|| AppError::new(ErrorCode::Internal, "Index root vanished"))
+}
+
+/// Stop indexing a folder and drop its entries.
+/// Returns false if it wasn't indexed.
+///
+/// Frontend can call:
+///   invoke<boolean>('remove_index_root', { path })
+#[tauri::command]
+pub fn remove_index_root(
+    app: AppHandle,
+    index: State<'_, SearchIndex>,
+    path: String,
+) -> Result<bool, AppError> {
+    let key = fs::canonicalize(&path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or(path);
+    let registry = app.state::<OperationRegistry>();
+    registry.cancel(&format!("index-build-{}", key));
+    // Dropping the watcher ends its update thread.
+    index.watchers.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+    index.states.lock().unwrap_or_else(|e| e.into_inner()).remove(&key);
+
+    let mut conn = index.conn();
+    let tx = conn.transaction().map_err(db_error)?;
+    let removed = tx.execute("DELETE FROM roots WHERE path = ?1", params![key]).map_err(db_error)? > 0;
+    tx.execute("DELETE FROM files WHERE root = ?1", params![key]).map_err(db_error)?;
+    tx.commit().map_err(db_error)?;
+    Ok(removed)
+}
+
+/// Re-walk one indexed root, or all of them. Progress arrives as
+/// `fu:index_status`.
+///
+/// Frontend can call:
+///   invoke('rebuild_index', { path: null })
+#[tauri::command]
+pub fn rebuild_index(
+    app: AppHandle,
+    index: State<'_, SearchIndex>,
+    path: Option<String>,
+) -> Result<(), AppError> {
+    let roots = index.roots()?;
+    let wanted = match path {
+        Some(path) => {
+            let key = fs::canonicalize(&path)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or(path);
+            if !roots.iter().any(|(r, _)| *r == key) {
+                return Err(AppError::new(ErrorCode::NotFound, format!("Not an indexed folder: {}", key)));
+            }
+            vec![key]
+        }
+        None => roots.into_iter().map(|(r, _)| r).collect(),
+    };
+    for root in wanted {
+        index.spawn_build(&app, root);
+    }
+    Ok(())
+}
+
+/// Frontend can call:
+///   invoke<IndexRootStatus[]>('index_status')
+#[tauri::command]
+pub fn index_status(index: State<'_, SearchIndex>) -> Result<Vec<IndexRootStatus>, AppError> {
+    let mut out = Vec::new();
+    for (root, _) in index.roots()? {
+        out.extend(index.status(&root)?);
+    }
+    Ok(out)
+}
+
+/// Case-insensitive substring search over indexed names (and text, with
+/// `content`). Best matches first.
+///
+/// Frontend can call:
+///   invoke<IndexHit[]>('search_index', { query: 'invoice', options: { root: null, content: false, limit: 200 } })
+#[tauri::command]
+pub async fn search_index(
+    index: State<'_, SearchIndex>,
+    query: String,
+    options: Option<IndexSearchOptions>,
+) -> Result<Vec<IndexHit>, AppError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err(AppError::invalid_input("Search query is empty"));
+    }
+    let options = options.unwrap_or_default();
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let root = options.root.map(|r| {
+        fs::canonicalize(&r)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or(r)
+    });
+    let index = index.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let conn = index.conn();
+        let map_row = |r: &rusqlite::Row| {
+            Ok(IndexHit {
+                path: r.get(0)?,
+                name: r.get(1)?,
+                is_dir: r.get(2)?,
+                size: r.get::<_, i64>(3)? as u64,
+                modified: r.get::<_, i64>(4)? as u64,
+            })
+        };
+
+        // The trigram tokenizer needs at least three characters; shorter
+        // queries fall back to a scan of the names.
+        let hits = if query.chars().count() >= 3 {
+            let column = if options.content { "{name body}" } else { "name" };
+            let mut stmt = conn
+                .prepare(
+                    "SELECT f.path, f.name, f.is_dir, f.size, f.modified
+                     FROM file_fts JOIN files f ON f.id = file_fts.rowid
+                     WHERE file_fts MATCH ?1 AND (?2 IS NULL OR f.root = ?2)
+                     ORDER BY rank LIMIT ?3",
+                )
+                .map_err(db_error)?;
+            let expr = format!("{} : {}", column, fts_phrase(&query));
+            let rows = stmt.query_map(params![expr, root, limit], map_row).map_err(db_error)?;
+            rows.collect::<Result<Vec<_>, _>>()
+        } else {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT path, name, is_dir, size, modified FROM files
+                     WHERE instr(lower(name), lower(?1)) > 0 AND (?2 IS NULL OR root = ?2)
+                     ORDER BY length(name) LIMIT ?3",
+                )
+                .map_err(db_error)?;
+            let rows = stmt.query_map(params![query, root, limit], map_row).map_err(db_error)?;
+            rows.collect::<Result<Vec<_>, _>>()
+        };
+        hits.map_err(db_error)
+    })
+    .await
+    .map_err(|e| AppError::new(ErrorCode::Internal, format!("Index search task failed: {}", e)))?
+}
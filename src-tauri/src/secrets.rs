@@ -0,0 +1,101 @@
+// src-tauri/src/secrets.rs
+//
+// Secrets (remote passwords, access keys, ...) in the OS keychain:
+//   - Windows: Credential Manager
+//   - macOS:   login Keychain
+//   - Linux:   Secret Service (GNOME Keyring / KWallet via libsecret's
+//              D-Bus API)
+//
+// Settings only ever hold the name of a profile; the secret lives in the
+// keychain under service "com.filesup.asc" and account
+// "<provider>:<key>", e.g. "webdav:Nextcloud" or "s3:minio", so providers
+// cannot read each other's entries by accident.
+//
+// All keychain calls are blocking (and the Secret Service backend runs its
+// own executor), so commands go through spawn_blocking.
+
+use crate::error::{AppError, ErrorCode};
+use keyring::Entry;
+use tokio::task;
+
+/// Keychain service name, the same as the app identifier.
+const SERVICE: &str = "com.filesup.asc";
+
+fn entry(provider: &str, key: &str) -> Result<Entry, AppError> {
+    let valid_provider = !provider.is_empty()
+        && provider
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_provider {
+        return Err(AppError::invalid_input(format!(
+            "Invalid secret namespace {:?} (use lowercase letters, digits, '-' or '_')",
+            provider
+        )));
+    }
+    if key.trim().is_empty() {
+        return Err(AppError::invalid_input("Secret key must not be empty"));
+    }
+    Entry::new(SERVICE, &format!("{}:{}", provider, key)).map_err(keyring_error)
+}
+
+fn keyring_error(e: keyring::Error) -> AppError {
+    let code = match &e {
+        keyring::Error::NoEntry => ErrorCode::NotFound,
+        keyring::Error::NoStorageAccess(_) => ErrorCode::PermissionDenied,
+        keyring::Error::TooLong(..) | keyring::Error::Invalid(..) | keyring::Error::BadEncoding(_) => {
+            ErrorCode::InvalidInput
+        }
+        _ => ErrorCode::Internal,
+    };
+    AppError::new(code, format!("Keychain error: {}", e))
+}
+
+/// Store (or replace) a secret.
+pub fn store(provider: &str, key: &str, secret: &str) -> Result<(), AppError> {
+    entry(provider, key)?.set_password(secret).map_err(keyring_error)
+}
+
+/// A stored secret; None when there is none.
+pub fn load(provider: &str, key: &str) -> Result<Option<String>, AppError> {
+    match entry(provider, key)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+/// Remove a secret; returns false when there was none.
+pub fn delete(provider: &str, key: &str) -> Result<bool, AppError> {
+    match entry(provider, key)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(keyring_error(e)),
+    }
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, AppError> + Send + 'static) -> Result<T, AppError> {
+    task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Keychain task failed: {}", e)))?
+}
+
+/// Frontend can call:
+///   invoke('store_secret', { provider: 'webdav', key: 'Nextcloud', secret })
+#[tauri::command]
+pub async fn store_secret(provider: String, key: String, secret: String) -> Result<(), AppError> {
+    blocking(move || store(&provider, &key, &secret)).await
+}
+
+/// Frontend can call:
+///   invoke<string | null>('get_secret', { provider: 'webdav', key: 'Nextcloud' })
+#[tauri::command]
+pub async fn get_secret(provider: String, key: String) -> Result<Option<String>, AppError> {
+    blocking(move || load(&provider, &key)).await
+}
+
+/// Frontend can call:
+///   invoke<boolean>('delete_secret', { provider: 'webdav', key: 'Nextcloud' })
+#[tauri::command]
+pub async fn delete_secret(provider: String, key: String) -> Result<bool, AppError> {
+    blocking(move || delete(&provider, &key)).await
+}
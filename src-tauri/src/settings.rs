@@ -0,0 +1,640 @@
+// src-tauri/src/settings.rs
+//
+// Persistent application settings.
+// Stored as JSON under the app config dir:
+//   <app config dir>/settings.json
+//
+// The schema mirrors `Settings` in src/sideBar/settings/settings.ts
+// (snake_case here, camelCase on the TS side via rustToJs/jsToRust).
+// Missing fields fall back to defaults, unknown fields are ignored, so
+// older and newer frontends can share one file.
+//
+// Every successful write emits `fu:settings_changed` with the full
+// settings object, so backend loops (metrics, ...) and other windows can
+// pick up new values without polling.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Status bar metrics (see metrics.rs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemSettings {
+    pub show_status_bar_metrics: bool,
+    pub cpu_mem_interval_ms: u64,
+    pub disk_check_interval_sec: u64,
+    pub disk_warn_threshold_percent: u8,
+    pub cpu_warn_threshold_percent: u8,
+    pub ram_warn_threshold_percent: u8,
+    /// Sample network interfaces (rx/tx rates) in the metrics loop.
+    pub network_metrics_enabled: bool,
+    /// Opt-in: emit `system://temperatures` from hardware sensors.
+    pub temperature_metrics_enabled: bool,
+    pub temperature_interval_sec: u64,
+    /// Archived copies kept in .ai/bundles/history/ (0 = no history).
+    pub ai_bundle_history_keep: u32,
+    /// Strip home paths, usernames, emails and API keys from AI bundles.
+    /// Turn off only for local-only debugging.
+    pub ai_bundle_redact: bool,
+    /// Extra regexes redacted from AI bundles (in addition to the built-ins).
+    pub ai_bundle_redact_patterns: Vec<String>,
+    /// Transfer queue: jobs running at the same time (transfer_queue.rs).
+    pub transfer_max_concurrent: u32,
+    /// Bytes per second shared by all copies, syncs and update downloads
+    /// (io_throttle.rs); 0 = unlimited.
+    pub io_throttle_bytes_per_sec: u64,
+    /// Run folder scans and hashing at background CPU / IO priority
+    /// (background_priority.rs).
+    pub background_priority: bool,
+    /// Read-only mode: commands that change files are rejected (safe_mode.rs).
+    pub safe_mode: bool,
+    /// Offer "Retry as administrator" for protected paths (elevation.rs).
+    pub allow_elevation: bool,
+    /// Saved WebDAV / Nextcloud servers for connect_webdav.
+    pub webdav_profiles: Vec<WebDavProfile>,
+    /// Saved S3 / MinIO accounts for connect_s3.
+    pub s3_profiles: Vec<S3Profile>,
+    /// System-wide hotkeys (shortcuts.rs).
+    pub global_shortcuts: Vec<GlobalShortcut>,
+    /// Hide the window in the tray when minimized (tray.rs).
+    pub minimize_to_tray: bool,
+    /// Background mode: closing the window keeps the app running in the
+    /// tray, with schedules and watchers active (tray.rs).
+    pub close_to_tray: bool,
+    /// Operations that show an OS notification when they finish while the
+    /// window is unfocused or in the tray (notifications.rs).
+    pub notify_on: Vec<NotifyKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyKind {
+    FolderScan,
+    DuplicateScan,
+    /// Scheduled folder syncs (scheduler.rs).
+    Sync,
+    CopyMove,
+    UpdateDownload,
+}
+
+/// System-wide hotkey, e.g. "CmdOrCtrl+Shift+F" -> show_window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalShortcut {
+    pub accelerator: String,
+    pub action: ShortcutAction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// Bring the window to the front.
+    ShowWindow,
+    /// Folder scan of `path`; the Downloads folder when None.
+    QuickScan {
+        #[serde(default)]
+        path: Option<String>,
+    },
+}
+
+/// Saved WebDAV server (vfs/webdav.rs). Never holds the password: that is
+/// passed to connect_webdav or kept in the keychain (secrets.rs).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebDavProfile {
+    /// Unique, shown in the UI and used to connect.
+    pub name: String,
+    /// Collection that becomes "/", e.g.
+    /// "https://cloud.example.com/remote.php/dav/files/me/" for Nextcloud.
+    pub url: String,
+    pub username: String,
+    /// Accept self-signed / mismatched TLS certificates for this server.
+    pub accept_invalid_certs: bool,
+}
+
+/// Saved S3-compatible account (vfs/s3.rs). The secret access key is
+/// passed to connect_s3 or kept in the keychain (secrets.rs), never here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct S3Profile {
+    /// Unique, shown in the UI and used to connect.
+    pub name: String,
+    /// Custom endpoint for MinIO & co ("http://nas:9000"); empty = AWS.
+    pub endpoint: String,
+    /// Signing region; empty = "us-east-1".
+    pub region: String,
+    /// Only show this bucket (for keys without ListAllMyBuckets); empty = all.
+    pub bucket: String,
+    pub access_key_id: String,
+    /// Path-style URLs ("endpoint/bucket/key"), needed by most MinIO setups.
+    pub path_style: bool,
+}
+
+impl Default for SystemSettings {
+    fn default() -> Self {
+        Self {
+            show_status_bar_metrics: true,
+            cpu_mem_interval_ms: 1000,
+            disk_check_interval_sec: 60,
+            disk_warn_threshold_percent: 95,
+            cpu_warn_threshold_percent: 95,
+            ram_warn_threshold_percent: 95,
+            network_metrics_enabled: true,
+            temperature_metrics_enabled: false,
+            temperature_interval_sec: 10,
+            ai_bundle_history_keep: 20,
+            ai_bundle_redact: true,
+            ai_bundle_redact_patterns: Vec::new(),
+            transfer_max_concurrent: 2,
+            io_throttle_bytes_per_sec: 0,
+            background_priority: false,
+            safe_mode: false,
+            allow_elevation: false,
+            webdav_profiles: Vec::new(),
+            s3_profiles: Vec::new(),
+            global_shortcuts: Vec::new(),
+            minimize_to_tray: false,
+            close_to_tray: false,
+            notify_on: vec![NotifyKind::FolderScan, NotifyKind::Sync, NotifyKind::UpdateDownload],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub confirm_delete_files: bool,
+    pub show_hidden_files: bool,
+    pub show_file_extensions: bool,
+    pub show_alternate_data_streams: bool,
+    pub show_hidden_folders: bool,
+    pub show_system_files: bool,
+    pub active_tab_id: String,
+    /// Folder the user last browsed (set by the frontend); "Open last
+    /// folder" in the tray menu (tray.rs). Empty = none.
+    pub last_folder: String,
+    pub theme: String,
+    pub font_family: String,
+    pub language: String,
+    pub auto_save: bool,
+    pub sidebar_width: u32,
+    pub panel_layout: String,
+    pub toolbar_position: String,
+    pub sidebar_position: String,
+    pub show_status_bar: bool,
+    pub show_tab_bar_when_only_one_tab: bool,
+    pub remember_panel_sizes_per_tab: bool,
+    pub show_bookmarks_bar: bool,
+    pub sort_by: String,
+    pub sort_order: String,
+    pub font_size: String,
+    pub icon_size: String,
+    pub icon_style: String,
+    pub folder_icon_color: String,
+    pub file_icon_color: String,
+    pub row_style: String,
+    pub show_icons: bool,
+    pub confirm_copy: bool,
+    pub confirm_move: bool,
+    pub confirm_rename: bool,
+    pub double_click_empty_goes_parent: bool,
+    pub show_moved_notification: bool,
+    pub show_copied_notification: bool,
+    pub play_sound_on_error: bool,
+    pub auto_sanitize_names: bool,
+    pub auto_refresh: bool,
+    pub check_updates: bool,
+    pub show_delete_snackbar: bool,
+    pub show_copy_paste_snackbar: bool,
+    pub show_cut_paste_snackbar: bool,
+    pub snackbar_duration: u32,
+    pub toast_position: String,
+    pub toast_duration_seconds: u32,
+    pub toast_default_style: String,
+    pub toast_color_mode: String,
+    pub toast_show_progress_bar: bool,
+    pub toast_show_delete_forever: bool,
+    pub toast_show_undo_button: bool,
+    pub show_row_inline_actions: bool,
+    pub smart_primary_row_action: bool,
+    pub show_paste_rename_dialog: bool,
+    pub paste_rename_pattern: String,
+    pub show_protected_system_folders: bool,
+    pub show_dot_folders: bool,
+    pub open_folders_with: String,
+    pub open_folders_in_new_tab: bool,
+    pub enable_custom_icons: bool,
+    pub prefer_thumbnails: bool,
+    pub show_thumbnails_list_view: bool,
+    pub show_thumbnails_details_view: bool,
+    pub hide_backup_files: bool,
+    pub hide_temporary_files: bool,
+    pub hide_os_metadata_files: bool,
+    pub hide_version_control_folders: bool,
+    pub use_os_icons: bool,
+    pub telemetry_enabled: bool,
+    pub system_debug_level: u8,
+    pub date_format: String,
+    pub start_behavior: String,
+    pub launch_at_startup: bool,
+    pub new_tab_behavior: String,
+    pub crash_report_level: String,
+    pub system: SystemSettings,
+}
+
+impl Default for AppSettings {
+    /// Same values as `defaultSettings` in settings.ts.
+    fn default() -> Self {
+        Self {
+            confirm_delete_files: true,
+            show_hidden_files: false,
+            show_file_extensions: true,
+            show_alternate_data_streams: false,
+            show_hidden_folders: false,
+            show_system_files: false,
+            active_tab_id: "1".into(),
+            last_folder: String::new(),
+            theme: "light".into(),
+            font_family: "system-ui".into(),
+            language: "en".into(),
+            auto_save: true,
+            sidebar_width: 250,
+            panel_layout: "treeLeftPreviewRight".into(),
+            toolbar_position: "left".into(),
+            sidebar_position: "left".into(),
+            show_status_bar: true,
+            show_tab_bar_when_only_one_tab: false,
+            remember_panel_sizes_per_tab: true,
+            show_bookmarks_bar: true,
+            sort_by: "name".into(),
+            sort_order: "asc".into(),
+            font_size: "medium".into(),
+            icon_size: "medium".into(),
+            icon_style: "filled".into(),
+            folder_icon_color: String::new(),
+            file_icon_color: String::new(),
+            row_style: "zebra".into(),
+            show_icons: true,
+            confirm_copy: false,
+            confirm_move: true,
+            confirm_rename: false,
+            double_click_empty_goes_parent: true,
+            show_moved_notification: true,
+            show_copied_notification: true,
+            play_sound_on_error: false,
+            auto_sanitize_names: true,
+            auto_refresh: true,
+            check_updates: true,
+            show_delete_snackbar: true,
+            show_copy_paste_snackbar: true,
+            show_cut_paste_snackbar: true,
+            snackbar_duration: 3,
+            toast_position: "center".into(),
+            toast_duration_seconds: 4,
+            toast_default_style: "warning".into(),
+            toast_color_mode: "manual".into(),
+            toast_show_progress_bar: true,
+            toast_show_delete_forever: true,
+            toast_show_undo_button: true,
+            show_row_inline_actions: true,
+            smart_primary_row_action: true,
+            show_paste_rename_dialog: true,
+            paste_rename_pattern: "os-default".into(),
+            show_protected_system_folders: false,
+            show_dot_folders: false,
+            open_folders_with: "double-click".into(),
+            open_folders_in_new_tab: false,
+            enable_custom_icons: true,
+            prefer_thumbnails: true,
+            show_thumbnails_list_view: true,
+            show_thumbnails_details_view: true,
+            hide_backup_files: true,
+            hide_temporary_files: true,
+            hide_os_metadata_files: true,
+            hide_version_control_folders: true,
+            use_os_icons: false,
+            telemetry_enabled: true,
+            system_debug_level: 3,
+            date_format: "YYYY-MM-DD".into(),
+            start_behavior: "lastSession".into(),
+            launch_at_startup: false,
+            new_tab_behavior: "home".into(),
+            crash_report_level: "crashOnly".into(),
+            system: SystemSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Reject values the backend (or UI) can't work with.
+    /// All problems are reported at once, separated by "; ".
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors: Vec<String> = Vec::new();
+        let mut check = |ok: bool, msg: &str| {
+            if !ok {
+                errors.push(msg.to_string());
+            }
+        };
+
+        let sys = &self.system;
+        check(
+            (250..=60_000).contains(&sys.cpu_mem_interval_ms),
+            "system.cpu_mem_interval_ms must be between 250 and 60000",
+        );
+        check(
+            (1..=86_400).contains(&sys.disk_check_interval_sec),
+            "system.disk_check_interval_sec must be between 1 and 86400",
+        );
+        check(
+            (1..=3_600).contains(&sys.temperature_interval_sec),
+            "system.temperature_interval_sec must be between 1 and 3600",
+        );
+        check(
+            sys.ai_bundle_history_keep <= 1_000,
+            "system.ai_bundle_history_keep must be at most 1000",
+        );
+        for pattern in &sys.ai_bundle_redact_patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                check(
+                    false,
+                    &format!("system.ai_bundle_redact_patterns: invalid regex {:?} ({})", pattern, e),
+                );
+            }
+        }
+        check(
+            (1..=16).contains(&sys.transfer_max_concurrent),
+            "system.transfer_max_concurrent must be between 1 and 16",
+        );
+        for (i, profile) in sys.webdav_profiles.iter().enumerate() {
+            check(
+                !profile.name.trim().is_empty(),
+                &format!("system.webdav_profiles[{}]: name must not be empty", i),
+            );
+            check(
+                sys.webdav_profiles[..i].iter().all(|p| p.name != profile.name),
+                &format!("system.webdav_profiles: duplicate name {:?}", profile.name),
+            );
+            check(
+                url::Url::parse(&profile.url).is_ok_and(|u| matches!(u.scheme(), "http" | "https")),
+                &format!("system.webdav_profiles[{}]: url must be an http(s) URL", i),
+            );
+        }
+        for (i, profile) in sys.s3_profiles.iter().enumerate() {
+            check(
+                !profile.name.trim().is_empty(),
+                &format!("system.s3_profiles[{}]: name must not be empty", i),
+            );
+            check(
+                sys.s3_profiles[..i].iter().all(|p| p.name != profile.name),
+                &format!("system.s3_profiles: duplicate name {:?}", profile.name),
+            );
+            check(
+                profile.endpoint.trim().is_empty()
+                    || url::Url::parse(profile.endpoint.trim()).is_ok_and(|u| matches!(u.scheme(), "http" | "https")),
+                &format!("system.s3_profiles[{}]: endpoint must be empty or an http(s) URL", i),
+            );
+            check(
+                !profile.access_key_id.trim().is_empty(),
+                &format!("system.s3_profiles[{}]: access_key_id must not be empty", i),
+            );
+        }
+        for (i, shortcut) in sys.global_shortcuts.iter().enumerate() {
+            match crate::shortcuts::parse_accelerator(&shortcut.accelerator) {
+                Ok(parsed) => check(
+                    sys.global_shortcuts[..i]
+                        .iter()
+                        .all(|s| crate::shortcuts::parse_accelerator(&s.accelerator).ok() != Some(parsed)),
+                    &format!("system.global_shortcuts: duplicate accelerator {:?}", shortcut.accelerator),
+                ),
+                Err(e) => check(false, &format!("system.global_shortcuts[{}]: {}", i, e)),
+            }
+        }
+        for (value, name) in [
+            (sys.disk_warn_threshold_percent, "disk"),
+            (sys.cpu_warn_threshold_percent, "cpu"),
+            (sys.ram_warn_threshold_percent, "ram"),
+        ] {
+            check(
+                (1..=100).contains(&value),
+                &format!("system.{}_warn_threshold_percent must be between 1 and 100", name),
+            );
+        }
+
+        check(
+            (100..=2_000).contains(&self.sidebar_width),
+            "sidebar_width must be between 100 and 2000",
+        );
+        check(
+            self.snackbar_duration <= 60,
+            "snackbar_duration must be at most 60 seconds",
+        );
+        check(
+            (1..=60).contains(&self.toast_duration_seconds),
+            "toast_duration_seconds must be between 1 and 60",
+        );
+        check(self.system_debug_level <= 7, "system_debug_level must be 0..7");
+        check(
+            matches!(self.sort_order.as_str(), "asc" | "desc"),
+            "sort_order must be 'asc' or 'desc'",
+        );
+        check(
+            matches!(self.open_folders_with.as_str(), "single-click" | "double-click"),
+            "open_folders_with must be 'single-click' or 'double-click'",
+        );
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Shared, live view of the `system` section.
+/// Background loops read it every tick instead of capturing a copy.
+pub type SharedSystemSettings = Arc<RwLock<SystemSettings>>;
+
+/// In-memory copy of the settings file, shared by all commands.
+/// Managed in lib.rs setup: app.manage(SettingsState::new(load_settings(..)))
+pub struct SettingsState {
+    current: RwLock<AppSettings>,
+    system: SharedSystemSettings,
+}
+
+impl SettingsState {
+    pub fn new(settings: AppSettings) -> Self {
+        Self {
+            system: Arc::new(RwLock::new(settings.system.clone())),
+            current: RwLock::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.current.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Handle for the metrics loop; updated in place on every save.
+    pub fn system_handle(&self) -> SharedSystemSettings {
+        self.system.clone()
+    }
+
+    fn set(&self, settings: AppSettings) {
+        *self.system.write().unwrap_or_else(|e| e.into_inner()) = settings.system.clone();
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("App config dir error: {}", e))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+/// Load settings from disk. Never fails: a missing file gives defaults,
+/// and an unreadable/invalid file is kept as settings.json.bak and
+/// replaced by defaults so the app can still start.
+pub fn load_settings(app: &AppHandle) -> AppSettings {
+    let path = match settings_path(app) {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::warn!("[Settings] {e}");
+            return AppSettings::default();
+        }
+    };
+    if !path.exists() {
+        return AppSettings::default();
+    }
+
+    let parsed = fs::read_to_string(&path)
+        .map_err(|e| format!("read failed: {}", e))
+        .and_then(|data| {
+            serde_json::from_str::<AppSettings>(&data).map_err(|e| format!("parse failed: {}", e))
+        })
+        .and_then(|settings| settings.validate().map(|()| settings));
+
+    match parsed {
+        Ok(settings) => settings,
+        Err(e) => {
+            tracing::warn!("[Settings] Invalid settings file {:?} ({e}), using defaults", path);
+            let _ = fs::rename(&path, path.with_extension("json.bak"));
+            AppSettings::default()
+        }
+    }
+}
+
+/// Write settings atomically (temp file + rename).
+fn write_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings dir {:?}: {}", parent, e))?;
+    }
+    let data = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| format!("Failed to write settings: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace settings file: {}", e))
+}
+
+/// Validate, persist, update the in-memory copy and notify listeners.
+fn commit_settings(
+    app: &AppHandle,
+    state: &SettingsState,
+    settings: AppSettings,
+) -> Result<AppSettings, String> {
+    settings.validate()?;
+    write_settings(app, &settings)?;
+    state.set(settings.clone());
+    let _ = app.emit("fu:settings_changed", settings.clone());
+    Ok(settings)
+}
+
+/// Change settings from the backend (same validation, file write and
+/// `fu:settings_changed` as the commands).
+pub fn modify_settings(
+    app: &AppHandle,
+    state: &SettingsState,
+    change: impl FnOnce(&mut AppSettings),
+) -> Result<AppSettings, String> {
+    let mut settings = state.get();
+    change(&mut settings);
+    commit_settings(app, state, settings)
+}
+
+/// Frontend can call:
+///   invoke('get_settings')
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> AppSettings {
+    state.get()
+}
+
+/// Replace all settings.
+///
+/// Frontend can call:
+///   invoke('save_settings', { newSettings })
+#[tauri::command]
+pub fn save_settings(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+    new_settings: AppSettings,
+) -> Result<AppSettings, String> {
+    commit_settings(&app, &state, new_settings)
+}
+
+/// Merge a partial object into the current settings; nested objects
+/// (e.g. `system`) are merged key by key.
+///
+/// Frontend can call:
+///   invoke('update_settings', { patch: { system: { cpu_mem_interval_ms: 2000 } } })
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+    patch: Value,
+) -> Result<AppSettings, String> {
+    if !patch.is_object() {
+        return Err("Settings patch must be a JSON object".to_string());
+    }
+    let mut merged = serde_json::to_value(state.get())
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    merge_json(&mut merged, patch);
+    let settings: AppSettings =
+        serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))?;
+    commit_settings(&app, &state, settings)
+}
+
+/// Restore defaults (and persist them).
+///
+/// Frontend can call:
+///   invoke('reset_settings')
+#[tauri::command]
+pub fn reset_settings(
+    app: AppHandle,
+    state: State<'_, SettingsState>,
+) -> Result<AppSettings, String> {
+    commit_settings(&app, &state, AppSettings::default())
+}
+
+fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
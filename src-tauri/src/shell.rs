@@ -0,0 +1,473 @@
+// src-tauri/src/shell.rs
+//
+// Hand files and folders to the OS shell:
+//   - open_path(path)                default application (double-click)
+//   - open_with(path, app_hint)      a chosen application
+//   - reveal_in_file_manager(path)   select the item in Explorer / Finder /
+//                                    the desktop file manager
+//   - get_shell_context_actions(path) / invoke_shell_action(path, verb)
+//                                    registered shell verbs ("Edit",
+//                                    "Edit with Notepad++", ...) for the
+//                                    app's context menu (Windows-first)
+//
+// Launch failures (no associated app, unknown app, launcher missing) come
+// back as AppError instead of being dropped. Everything runs on the
+// blocking pool: ShellExecute / xdg-open can take a moment.
+
+use crate::error::{AppError, ErrorCode};
+use crate::path_guard::PathGuard;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::State;
+use tokio::task;
+
+/// The path must exist: "open" on a missing file fails late and vaguely.
+/// Returns the path as given: shells don't all accept Windows' verbatim
+/// (\\?\) canonical form.
+fn existing(guard: &PathGuard, path: &str) -> Result<PathBuf, AppError> {
+    let resolved = guard.check(path)?;
+    std::fs::symlink_metadata(&resolved).map_err(|e| AppError::io(&e).with_path(path))?;
+    Ok(PathBuf::from(path))
+}
+
+async fn run_blocking(job: impl FnOnce() -> Result<(), AppError> + Send + 'static) -> Result<(), AppError> {
+    task::spawn_blocking(job)
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Shell task failed: {}", e)))?
+}
+
+/// Open a file or folder with its default application.
+///
+/// Frontend can call:
+///   invoke('open_path', { path })
+#[tauri::command]
+pub async fn open_path(guard: State<'_, PathGuard>, path: String) -> Result<(), AppError> {
+    let resolved = existing(&guard, &path)?;
+    run_blocking(move || {
+        open::that(&resolved).map_err(|e| {
+            AppError::io(&e)
+                .context("Failed to open with the default application")
+                .with_path(&resolved)
+        })
+    })
+    .await
+}
+
+/// Open a file or folder with a chosen application. `app_hint` is what the
+/// OS understands as an application: an executable name or full path
+/// ("code", "C:\\Program Files\\Notepad++\\notepad++.exe") or, on macOS,
+/// an application name ("TextEdit").
+///
+/// Frontend can call:
+///   invoke('open_with', { path, appHint: 'code' })
+#[tauri::command]
+pub async fn open_with(guard: State<'_, PathGuard>, path: String, app_hint: String) -> Result<(), AppError> {
+    let resolved = existing(&guard, &path)?;
+    let app_hint = app_hint.trim().to_string();
+    if app_hint.is_empty() {
+        return Err(AppError::invalid_input("No application given"));
+    }
+    run_blocking(move || {
+        // On Linux the app itself is started, and `open::with` would wait
+        // until it exits; detached still reports a failed spawn.
+        let res = if cfg!(target_os = "linux") {
+            open::with_detached(&resolved, app_hint.clone())
+        } else {
+            open::with(&resolved, app_hint.clone())
+        };
+        res.map_err(|e| {
+            AppError::io(&e)
+                .context(&format!("Failed to open with {}", app_hint))
+                .with_path(&resolved)
+        })
+    })
+    .await
+}
+
+/// Show `path` selected in the system file manager.
+///
+/// Frontend can call:
+///   invoke('reveal_in_file_manager', { path })
+#[tauri::command]
+pub async fn reveal_in_file_manager(guard: State<'_, PathGuard>, path: String) -> Result<(), AppError> {
+    let resolved = existing(&guard, &path)?;
+    run_blocking(move || reveal(&resolved).map_err(|e| e.with_path(&resolved))).await
+}
+
+#[cfg(any(windows, target_os = "macos"))]
+fn launch_error(tool: &str, e: std::io::Error) -> AppError {
+    AppError::io(&e).context(&format!("Failed to run {}", tool))
+}
+
+#[cfg(windows)]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    use std::os::windows::process::CommandExt;
+
+    // explorer parses its own command line: the path must be quoted inside
+    // the /select, argument, and it exits with 1 even on success.
+    Command::new("explorer")
+        .raw_arg(format!("/select,\"{}\"", path.display()))
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| launch_error("explorer", e))
+}
+
+#[cfg(target_os = "macos")]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    let out = Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .output()
+        .map_err(|e| launch_error("open", e))?;
+    if !out.status.success() {
+        return Err(AppError::new(
+            ErrorCode::Io,
+            format!("Finder refused to reveal: {}", String::from_utf8_lossy(&out.stderr).trim()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn reveal(path: &Path) -> Result<(), AppError> {
+    // org.freedesktop.FileManager1 (Nautilus, Dolphin, Nemo, Thunar, ...)
+    // selects the item; without one, open the parent folder instead.
+    if let Ok(uri) = url::Url::from_file_path(path) {
+        let shown = Command::new("busctl")
+            .args([
+                "--user",
+                "call",
+                "org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1",
+                "ShowItems",
+                "ass",
+                "1",
+                uri.as_str(),
+                "",
+            ])
+            .output()
+            .is_ok_and(|out| out.status.success());
+        if shown {
+            return Ok(());
+        }
+    }
+    let folder = path.parent().unwrap_or(path);
+    open::that(folder).map_err(|e| {
+        AppError::io(&e)
+            .context("No file manager could show the item")
+            .with_details(format!("Tried to open {}", folder.display()))
+    })
+}
+
+/// One entry of the OS "right-click" menu for a path.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellAction {
+    /// Canonical verb name, passed back to invoke_shell_action.
+    pub verb: String,
+    /// Menu text, accelerators (&) removed.
+    pub label: String,
+    /// Icon source as registered ("C:\\...\\app.exe,0"), if any.
+    pub icon: Option<String>,
+    /// The double-click action.
+    pub is_default: bool,
+}
+
+/// Shell verbs available for `path`, in menu order.
+///
+/// Windows: the static verbs registered for the file type, its perceived
+/// type and all files / folders (what Explorer shows without Shift).
+/// Verbs that only come from COM context-menu handlers (shellex) and
+/// cascading submenus are not listed.
+/// Elsewhere: "open" and "reveal" only.
+///
+/// Frontend can call:
+///   invoke<ShellAction[]>('get_shell_context_actions', { path })
+#[tauri::command]
+pub async fn get_shell_context_actions(
+    guard: State<'_, PathGuard>,
+    path: String,
+) -> Result<Vec<ShellAction>, AppError> {
+    let path = existing(&guard, &path)?;
+    task::spawn_blocking(move || verbs::list(&path))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("Shell task failed: {}", e)))
+}
+
+/// Run a verb from get_shell_context_actions on `path`.
+///
+/// Frontend can call:
+///   invoke('invoke_shell_action', { path, verb: 'edit' })
+#[tauri::command]
+pub async fn invoke_shell_action(guard: State<'_, PathGuard>, path: String, verb: String) -> Result<(), AppError> {
+    let path = existing(&guard, &path)?;
+    run_blocking(move || {
+        // Only verbs we offered: no "runas" or other surprises from the UI.
+        if !verbs::list(&path).iter().any(|a| a.verb.eq_ignore_ascii_case(&verb)) {
+            return Err(AppError::invalid_input(format!("Unknown shell action: {}", verb)).with_path(&path));
+        }
+        verbs::invoke(&path, &verb).map_err(|e| e.with_path(&path))
+    })
+    .await
+}
+
+#[cfg(windows)]
+mod verbs {
+    use super::ShellAction;
+    use crate::error::AppError;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::{mem, ptr};
+    use windows_sys::Win32::Foundation::{ERROR_SUCCESS, FILETIME};
+    use windows_sys::Win32::System::Com::{
+        CoInitializeEx, CoUninitialize, COINIT_APARTMENTTHREADED, COINIT_DISABLE_OLE1DDE,
+    };
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY, HKEY_CLASSES_ROOT, KEY_READ,
+        RRF_RT_ANY, RRF_RT_REG_SZ,
+    };
+    use windows_sys::Win32::UI::Shell::{
+        SHLoadIndirectString, ShellExecuteExW, SEE_MASK_FLAG_NO_UI, SEE_MASK_INVOKEIDLIST,
+        SEE_MASK_NOASYNC, SHELLEXECUTEINFOW,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain([0]).collect()
+    }
+
+    fn from_wide(buf: &[u16]) -> String {
+        let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..end])
+    }
+
+    /// Open HKCR subkey, closed on drop.
+    struct Key(HKEY);
+
+    impl Key {
+        fn open(sub: &str) -> Option<Key> {
+            let mut hkey: HKEY = ptr::null_mut();
+            let rc = unsafe { RegOpenKeyExW(HKEY_CLASSES_ROOT, wide(sub).as_ptr(), 0, KEY_READ, &mut hkey) };
+            (rc == ERROR_SUCCESS).then_some(Key(hkey))
+        }
+
+        /// REG_SZ / REG_EXPAND_SZ value; None = the key's default value.
+        fn string(&self, name: Option<&str>) -> Option<String> {
+            let name = name.map(wide);
+            let name_ptr = name.as_ref().map_or(ptr::null(), |n| n.as_ptr());
+            let mut bytes = 0u32;
+            let rc = unsafe {
+                RegGetValueW(self.0, ptr::null(), name_ptr, RRF_RT_REG_SZ, ptr::null_mut(), ptr::null_mut(), &mut bytes)
+            };
+            if rc != ERROR_SUCCESS || bytes < 2 {
+                return None;
+            }
+            let mut buf = vec![0u16; bytes as usize / 2 + 1];
+            let mut size = (buf.len() * 2) as u32;
+            let rc = unsafe {
+                RegGetValueW(
+                    self.0,
+                    ptr::null(),
+                    name_ptr,
+                    RRF_RT_REG_SZ,
+                    ptr::null_mut(),
+                    buf.as_mut_ptr().cast(),
+                    &mut size,
+                )
+            };
+            let s = from_wide(&buf);
+            (rc == ERROR_SUCCESS && !s.is_empty()).then_some(s)
+        }
+
+        fn has_value(&self, name: &str) -> bool {
+            let rc = unsafe {
+                RegGetValueW(
+                    self.0,
+                    ptr::null(),
+                    wide(name).as_ptr(),
+                    RRF_RT_ANY,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            rc == ERROR_SUCCESS
+        }
+
+        fn subkeys(&self) -> Vec<String> {
+            let mut out = Vec::new();
+            let mut buf = [0u16; 256];
+            for i in 0.. {
+                let mut len = buf.len() as u32;
+                let mut written: FILETIME = unsafe { mem::zeroed() };
+                let rc = unsafe {
+                    RegEnumKeyExW(
+                        self.0,
+                        i,
+                        buf.as_mut_ptr(),
+                        &mut len,
+                        ptr::null(),
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        &mut written,
+                    )
+                };
+                if rc != ERROR_SUCCESS {
+                    break;
+                }
+                out.push(String::from_utf16_lossy(&buf[..len as usize]));
+            }
+            out
+        }
+    }
+
+    impl Drop for Key {
+        fn drop(&mut self) {
+            unsafe {
+                RegCloseKey(self.0);
+            }
+        }
+    }
+
+    /// "@shell32.dll,-8506" style resource references.
+    fn resolve_label(raw: String) -> String {
+        if !raw.starts_with('@') {
+            return raw;
+        }
+        let mut buf = [0u16; 512];
+        let hr = unsafe { SHLoadIndirectString(wide(&raw).as_ptr(), buf.as_mut_ptr(), buf.len() as u32, ptr::null()) };
+        if hr >= 0 {
+            from_wide(&buf)
+        } else {
+            raw
+        }
+    }
+
+    /// HKCR class keys whose `shell` subkey applies to `path`, most specific first.
+    fn classes(path: &Path) -> Vec<String> {
+        if path.is_dir() {
+            return vec!["Directory".into(), "Folder".into(), "AllFilesystemObjects".into()];
+        }
+        let mut out = Vec::new();
+        if let Some(ext) = path.extension().map(|e| format!(".{}", e.to_string_lossy())) {
+            if let Some(key) = Key::open(&ext) {
+                if let Some(prog_id) = key.string(None) {
+                    out.push(prog_id);
+                }
+                out.push(format!("SystemFileAssociations\\{}", ext));
+                if let Some(perceived) = key.string(Some("PerceivedType")) {
+                    out.push(format!("SystemFileAssociations\\{}", perceived));
+                }
+            }
+            out.push(ext);
+        }
+        out.push("*".into());
+        out.push("AllFilesystemObjects".into());
+        out
+    }
+
+    pub fn list(path: &Path) -> Vec<ShellAction> {
+        let mut actions: Vec<ShellAction> = Vec::new();
+        let mut default_verb: Option<String> = None;
+
+        for class in classes(path) {
+            let Some(shell) = Key::open(&format!("{}\\shell", class)) else {
+                continue;
+            };
+            if default_verb.is_none() {
+                default_verb = shell.string(None);
+            }
+            for verb in shell.subkeys() {
+                if actions.iter().any(|a| a.verb.eq_ignore_ascii_case(&verb)) {
+                    continue;
+                }
+                let Some(key) = Key::open(&format!("{}\\shell\\{}", class, verb)) else {
+                    continue;
+                };
+                // Shift-only, hidden, or cascading (no command of its own).
+                if ["Extended", "ProgrammaticAccessOnly", "LegacyDisable", "SubCommands", "ExtendedSubCommandsKey"]
+                    .iter()
+                    .any(|v| key.has_value(v))
+                {
+                    continue;
+                }
+                let label = key
+                    .string(Some("MUIVerb"))
+                    .or_else(|| key.string(None))
+                    .map(resolve_label)
+                    .unwrap_or_else(|| verb.clone())
+                    .replace('&', "");
+                actions.push(ShellAction {
+                    label,
+                    icon: key.string(Some("Icon")),
+                    verb,
+                    is_default: false,
+                });
+            }
+        }
+
+        let default_verb = default_verb.unwrap_or_else(|| "open".to_string());
+        if let Some(a) = actions.iter_mut().find(|a| a.verb.eq_ignore_ascii_case(&default_verb)) {
+            a.is_default = true;
+        }
+        actions
+    }
+
+    pub fn invoke(path: &Path, verb: &str) -> Result<(), AppError> {
+        let file: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+        let verb_w = wide(verb);
+        let mut info: SHELLEXECUTEINFOW = unsafe { mem::zeroed() };
+        info.cbSize = mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_INVOKEIDLIST | SEE_MASK_NOASYNC | SEE_MASK_FLAG_NO_UI;
+        info.lpVerb = verb_w.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.nShow = SW_SHOWNORMAL;
+
+        // Verbs backed by COM handlers need an STA on this (pool) thread.
+        let com = unsafe {
+            CoInitializeEx(ptr::null(), (COINIT_APARTMENTTHREADED | COINIT_DISABLE_OLE1DDE) as u32)
+        };
+        let ok = unsafe { ShellExecuteExW(&mut info) } != 0;
+        let err = std::io::Error::last_os_error();
+        if com >= 0 {
+            unsafe { CoUninitialize() };
+        }
+        if !ok {
+            return Err(AppError::io(&err).context(&format!("Shell action \"{}\" failed", verb)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+mod verbs {
+    use super::{reveal, ShellAction};
+    use crate::error::AppError;
+    use std::path::Path;
+
+    pub fn list(_path: &Path) -> Vec<ShellAction> {
+        vec![
+            ShellAction {
+                verb: "open".into(),
+                label: "Open".into(),
+                icon: None,
+                is_default: true,
+            },
+            ShellAction {
+                verb: "reveal".into(),
+                label: "Show in file manager".into(),
+                icon: None,
+                is_default: false,
+            },
+        ]
+    }
+
+    pub fn invoke(path: &Path, verb: &str) -> Result<(), AppError> {
+        match verb {
+            "reveal" => reveal(path),
+            _ => open::that(path).map_err(|e| AppError::io(&e).context("Failed to open with the default application")),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+// src-tauri/src/shortcuts.rs
+//
+// System-wide keyboard shortcuts (tauri-plugin-global-shortcut).
+//
+// Bindings live in settings.system.global_shortcuts (settings.rs), so they
+// survive restarts and can also be edited through save_settings /
+// update_settings: every `fu:settings_changed` re-syncs what is registered
+// with the OS. Actions:
+//   - show_window: bring the window to the front
+//   - quick_scan:  folder scan of a path (default: Downloads) with op id
+//                  "shortcut-scan-<ms>"; progress / result arrive as the
+//                  usual fu:folder_scan_* events
+// Each press also emits `fu:shortcut_triggered` (ShortcutTriggered).
+//
+// Accelerators use the plugin's syntax: "CmdOrCtrl+Shift+F", "Alt+F9", ...
+
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Listener, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::error::{AppError, ErrorCode};
+use crate::folder_scan::start_folder_scan;
+use crate::settings::{modify_settings, AppSettings, GlobalShortcut, SettingsState, ShortcutAction};
+use crate::single_instance::focus_main_window;
+
+/// What is currently registered with the OS (managed state).
+#[derive(Default)]
+pub struct Shortcuts {
+    registered: Mutex<Vec<GlobalShortcut>>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ShortcutTriggered {
+    accelerator: String,
+    action: ShortcutAction,
+    /// Set for quick_scan.
+    op_id: Option<String>,
+}
+
+pub fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator.trim())
+        .map_err(|e| format!("Invalid accelerator {:?}: {}", accelerator, e))
+}
+
+/// Register the saved bindings and follow later settings changes.
+/// Called once from setup, after SettingsState is managed.
+pub fn start_shortcuts(app: &AppHandle) {
+    app.manage(Shortcuts::default());
+    sync(app, &app.state::<SettingsState>().get().system.global_shortcuts);
+
+    let handle = app.clone();
+    app.listen_any("fu:settings_changed", move |event| {
+        match serde_json::from_str::<AppSettings>(event.payload()) {
+            Ok(settings) => sync(&handle, &settings.system.global_shortcuts),
+            Err(e) => tracing::warn!("[Shortcuts] Bad settings payload: {e}"),
+        }
+    });
+}
+
+/// Make the OS registrations match `wanted`. Failures (accelerator taken
+/// by another app) are logged and the binding stays saved.
+fn sync(app: &AppHandle, wanted: &[GlobalShortcut]) {
+    let state = app.state::<Shortcuts>();
+    let mut registered = state.registered.lock().unwrap_or_else(|e| e.into_inner());
+    if registered.as_slice() == wanted {
+        return;
+    }
+
+    let manager = app.global_shortcut();
+    if let Err(e) = manager.unregister_all() {
+        tracing::warn!("[Shortcuts] Failed to unregister: {e}");
+    }
+    for binding in wanted {
+        if let Err(e) = register(app, binding) {
+            tracing::warn!("[Shortcuts] {}", e.message);
+        }
+    }
+    *registered = wanted.to_vec();
+}
+
+fn register(app: &AppHandle, binding: &GlobalShortcut) -> Result<(), AppError> {
+    let shortcut = parse_accelerator(&binding.accelerator).map_err(AppError::invalid_input)?;
+    let accelerator = binding.accelerator.clone();
+    let action = binding.action.clone();
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app, _, event| {
+            if event.state() == ShortcutState::Pressed {
+                trigger(app, &accelerator, &action);
+            }
+        })
+        .map_err(|e| {
+            AppError::new(
+                ErrorCode::Internal,
+                format!("Failed to register {:?}: {}", binding.accelerator, e),
+            )
+        })
+}
+
+fn trigger(app: &AppHandle, accelerator: &str, action: &ShortcutAction) {
+    focus_main_window(app);
+    let op_id = match action {
+        ShortcutAction::ShowWindow => None,
+        ShortcutAction::QuickScan { path } => quick_scan(app, path.clone()),
+    };
+    let _ = app.emit(
+        "fu:shortcut_triggered",
+        ShortcutTriggered {
+            accelerator: accelerator.to_string(),
+            action: action.clone(),
+            op_id,
+        },
+    );
+}
+
+fn quick_scan(app: &AppHandle, path: Option<String>) -> Option<String> {
+    let path = match path {
+        Some(p) => p,
+        None => match app.path().download_dir() {
+            Ok(dir) => dir.to_string_lossy().to_string(),
+            Err(e) => {
+                tracing::warn!("[Shortcuts] No Downloads folder: {e}");
+                return None;
+            }
+        },
+    };
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let op_id = format!("shortcut-scan-{}", millis);
+
+    let app = app.clone();
+    let scan_id = op_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let res = start_folder_scan(app.clone(), app.state(), app.state(), app.state(), scan_id, path.into(), None).await;
+        if let Err(e) = res {
+            tracing::warn!("[Shortcuts] Quick scan failed: {}", e.message);
+        }
+    });
+    Some(op_id)
+}
+
+/// Bind `accelerator` (replacing its previous action) and save it.
+///
+/// Command from TS:
+/// invoke("register_shortcut", { accelerator: "CmdOrCtrl+Shift+F", action: { type: "show_window" } })
+/// invoke("register_shortcut", { accelerator: "Alt+F9", action: { type: "quick_scan", path: null } })
+#[tauri::command]
+pub fn register_shortcut(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    accelerator: String,
+    action: ShortcutAction,
+) -> Result<Vec<GlobalShortcut>, AppError> {
+    let parsed = parse_accelerator(&accelerator).map_err(AppError::invalid_input)?;
+    let binding = GlobalShortcut { accelerator, action };
+
+    // Try the OS first, so a taken accelerator is reported, not just saved.
+    let manager = app.global_shortcut();
+    if manager.is_registered(parsed) {
+        let _ = manager.unregister(parsed);
+    }
+    register(&app, &binding)?;
+
+    let saved = modify_settings(&app, &settings, |s| {
+        let list = &mut s.system.global_shortcuts;
+        list.retain(|b| parse_accelerator(&b.accelerator).ok() != Some(parsed));
+        list.push(binding);
+    })
+    .map_err(|e| {
+        let _ = manager.unregister(parsed);
+        AppError::invalid_input(e)
+    })?;
+    Ok(saved.system.global_shortcuts)
+}
+
+/// Command from TS:
+/// invoke("unregister_shortcut", { accelerator: "CmdOrCtrl+Shift+F" })
+#[tauri::command]
+pub fn unregister_shortcut(
+    app: AppHandle,
+    settings: State<'_, SettingsState>,
+    accelerator: String,
+) -> Result<Vec<GlobalShortcut>, AppError> {
+    let parsed = parse_accelerator(&accelerator).map_err(AppError::invalid_input)?;
+    let saved = modify_settings(&app, &settings, |s| {
+        s.system.global_shortcuts.retain(|b| parse_accelerator(&b.accelerator).ok() != Some(parsed));
+    })
+    .map_err(AppError::invalid_input)?;
+    Ok(saved.system.global_shortcuts)
+}
@@ -0,0 +1,270 @@
+// src-tauri/src/shred.rs
+//
+// Secure delete ("shred"): overwrite file contents with random data
+// (`passes` times, fsync after each pass), rename to a random name so the
+// original name doesn't linger in the directory, then unlink.
+//
+// - Folders are shredded file by file, then removed.
+// - Symlinks are unlinked, never followed.
+// - SSDs remap writes (wear levelling), so overwriting gives no guarantee
+//   there; paths on an SSD are refused unless `force: true`.
+// - Registered in GPS (kind: secure-delete); cancelling stops between
+//   1 MiB writes and the result says exactly what state things are in.
+//
+// Events: `fu:secure_delete_progress` (throttled, per file + pass).
+// Every run is recorded in the audit log (audit.rs).
+
+use crate::audit::{self, AuditAction, AuditEntry};
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::FileOpFailure;
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use crate::volumes::disk_kind_for_path;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sysinfo::DiskKind;
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+const CHUNK: usize = 1024 * 1024;
+const DEFAULT_PASSES: u32 = 1;
+const MAX_PASSES: u32 = 35;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SecureDeleteProgress {
+    op_id: String,
+    path: String,
+    pass: u32,
+    passes: u32,
+    bytes_done: u64,
+    bytes_total: u64,
+    files_done: u64,
+}
+
+/// File that was being overwritten when the op was cancelled: still on
+/// disk under its original name, partly overwritten.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialShred {
+    pub path: String,
+    pub pass: u32,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SecureDeleteResult {
+    /// Fully overwritten and removed.
+    pub shredded: Vec<String>,
+    pub failed: Vec<FileOpFailure>,
+    pub cancelled: bool,
+    pub partial: Option<PartialShred>,
+    /// Requested paths never touched (after a cancel).
+    pub untouched: Vec<String>,
+}
+
+enum ShredError {
+    Cancelled(PartialShred),
+    Io(AppError),
+}
+
+/// Overwrite and delete `paths`.
+///
+/// - `passes`: random overwrites per file (default 1, max 35).
+/// - `force`: also shred on SSDs (see header).
+///
+/// Frontend can call:
+///   invoke<SecureDeleteResult>('secure_delete', { opId, paths, passes: 3, force: false })
+#[tauri::command]
+pub async fn secure_delete(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    paths: Vec<String>,
+    passes: Option<u32>,
+    force: Option<bool>,
+) -> Result<SecureDeleteResult, String> {
+    let resolved = guard.check_entries(&paths)?;
+    let passes = passes.unwrap_or(DEFAULT_PASSES).clamp(1, MAX_PASSES);
+
+    if !force.unwrap_or(false) {
+        for (path, canonical) in paths.iter().zip(&resolved) {
+            if matches!(disk_kind_for_path(canonical), Some(DiskKind::SSD)) {
+                return Err(format!(
+                    "{} is on an SSD: overwriting can't reach remapped blocks, so shredding gives no guarantee. Use force to shred anyway.",
+                    path
+                ));
+            }
+        }
+    }
+
+    let token = registry.register(&op_id, OperationKind::SecureDelete);
+    let registry = registry.inner().clone();
+
+    let res = task::spawn_blocking(move || {
+        let mut shredder = Shredder {
+            app: &app,
+            op_id: &op_id,
+            token: &token,
+            passes,
+            rng: SystemRandom::new(),
+            files_done: 0,
+            last_emit: Instant::now(),
+        };
+        let mut result = SecureDeleteResult::default();
+
+        for (i, (path, target)) in paths.iter().zip(&resolved).enumerate() {
+            if token.is_cancelled() {
+                result.cancelled = true;
+                result.untouched = paths[i..].to_vec();
+                break;
+            }
+            match shredder.shred_path(target) {
+                Ok(()) => result.shredded.push(path.clone()),
+                Err(ShredError::Io(e)) => result.failed.push(FileOpFailure::new(path.as_str(), e)),
+                Err(ShredError::Cancelled(partial)) => {
+                    result.cancelled = true;
+                    result.partial = Some(partial);
+                    result.untouched = paths[i + 1..].to_vec();
+                    break;
+                }
+            }
+        }
+
+        let audit = AuditEntry::new(AuditAction::SecureDelete).op_id(&op_id).sources(&paths);
+        let audit = if result.cancelled {
+            audit.result("cancelled", None)
+        } else {
+            audit.batch_outcome(paths.len(), result.failed.len())
+        };
+        audit::record(&app, audit);
+
+        registry.finish(&op_id, &token);
+        result
+    })
+    .await
+    .map_err(|e| format!("Secure delete task failed: {}", e))?;
+
+    Ok(res)
+}
+
+struct Shredder<'a> {
+    app: &'a AppHandle,
+    op_id: &'a str,
+    token: &'a CancellationToken,
+    passes: u32,
+    rng: SystemRandom,
+    files_done: u64,
+    last_emit: Instant,
+}
+
+impl Shredder<'_> {
+    fn shred_path(&mut self, path: &Path) -> Result<(), ShredError> {
+        let meta = fs::symlink_metadata(path).map_err(|e| ShredError::Io(AppError::io(&e)))?;
+        if !meta.is_dir() {
+            return self.shred_file(path, &meta);
+        }
+
+        // Files first (contents_first = children before their folder).
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for entry in WalkDir::new(path).contents_first(true) {
+            let entry = entry.map_err(|e| {
+                ShredError::Io(AppError::new(ErrorCode::Io, e.to_string()))
+            })?;
+            if entry.file_type().is_dir() {
+                dirs.push(entry.into_path());
+                continue;
+            }
+            let meta = entry
+                .path()
+                .symlink_metadata()
+                .map_err(|e| ShredError::Io(AppError::io(&e).with_path(entry.path())))?;
+            self.shred_file(entry.path(), &meta)?;
+        }
+        for dir in dirs {
+            fs::remove_dir(&dir).map_err(|e| ShredError::Io(AppError::io(&e).with_path(&dir)))?;
+        }
+        Ok(())
+    }
+
+    fn shred_file(&mut self, path: &Path, meta: &fs::Metadata) -> Result<(), ShredError> {
+        let io_err = |e: std::io::Error| ShredError::Io(AppError::io(&e).with_path(path));
+
+        if meta.is_file() && meta.len() > 0 {
+            let total = meta.len();
+            let mut file = OpenOptions::new().write(true).open(path).map_err(io_err)?;
+            let mut buf = vec![0u8; CHUNK];
+
+            for pass in 1..=self.passes {
+                file.seek(SeekFrom::Start(0)).map_err(io_err)?;
+                let mut done = 0u64;
+                while done < total {
+                    if self.token.is_cancelled() {
+                        return Err(ShredError::Cancelled(PartialShred {
+                            path: path.to_string_lossy().to_string(),
+                            pass,
+                            bytes_done: done,
+                            bytes_total: total,
+                        }));
+                    }
+                    let n = (total - done).min(CHUNK as u64) as usize;
+                    self.rng.fill(&mut buf[..n]).map_err(|_| {
+                        ShredError::Io(AppError::new(
+                            ErrorCode::Internal,
+                            "System random generator failed",
+                        ))
+                    })?;
+                    file.write_all(&buf[..n]).map_err(io_err)?;
+                    done += n as u64;
+                    self.progress(path, pass, done, total, false);
+                }
+                // Each pass must hit the disk, not just the page cache.
+                file.sync_all().map_err(io_err)?;
+            }
+            self.progress(path, self.passes, total, total, true);
+        }
+
+        // Hide the original name before unlinking.
+        let target = match self.random_name(path) {
+            Some(hidden) if fs::rename(path, &hidden).is_ok() => hidden,
+            _ => path.to_path_buf(),
+        };
+        fs::remove_file(&target).map_err(io_err)?;
+        self.files_done += 1;
+        Ok(())
+    }
+
+    fn random_name(&self, path: &Path) -> Option<PathBuf> {
+        let mut bytes = [0u8; 12];
+        self.rng.fill(&mut bytes).ok()?;
+        Some(path.with_file_name(hex::encode(bytes)))
+    }
+
+    fn progress(&mut self, path: &Path, pass: u32, bytes_done: u64, bytes_total: u64, force: bool) {
+        if !force && self.last_emit.elapsed().as_millis() < 100 {
+            return;
+        }
+        let _ = self.app.emit(
+            "fu:secure_delete_progress",
+            SecureDeleteProgress {
+                op_id: self.op_id.to_string(),
+                path: path.to_string_lossy().to_string(),
+                pass,
+                passes: self.passes,
+                bytes_done,
+                bytes_total,
+                files_done: self.files_done,
+            },
+        );
+        self.last_emit = Instant::now();
+    }
+}
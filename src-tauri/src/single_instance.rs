@@ -0,0 +1,277 @@
+// src-tauri/src/single_instance.rs
+//
+// Single-instance enforcement with path hand-off.
+//
+// The first process listens on a per-user local endpoint:
+//   - unix:    socket file com.filesup.asc-<uid>.sock in $XDG_RUNTIME_DIR
+//              (falls back to the temp dir)
+//   - Windows: named pipe \\.\pipe\com.filesup.asc-<user>
+// A second launch connects, sends its arguments and working directory as
+// one JSON line and exits, so "Open with FilesUP" from the OS reuses the
+// running window. The running instance brings its window to the front and,
+// when a path came along, emits `fu:open_path_request` (OpenPathRequest).
+//
+// An update handoff (update/launcher.rs) starts the new version while the
+// old one is still running; HANDOFF_ENV tells the new process to take over
+// the endpoint instead of forwarding to the old one.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::path_guard::PathGuard;
+
+/// Set by update/launcher.rs on the process it spawns.
+pub const HANDOFF_ENV: &str = "FILESUP_HANDOFF";
+
+const NAME: &str = "com.filesup.asc";
+
+/// Longest message accepted from a second instance.
+const MAX_MESSAGE: u64 = 64 * 1024;
+
+/// What a second instance sends.
+#[derive(Debug, Serialize, Deserialize)]
+struct Forwarded {
+    /// Arguments without the executable.
+    args: Vec<String>,
+    cwd: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenPathRequest {
+    pub path: String,
+    pub is_dir: bool,
+}
+
+pub enum Acquired {
+    /// First instance: hand to `start_listener` once the app is set up.
+    Primary(Listener),
+    /// The running instance took our arguments; this process should exit.
+    Forwarded,
+}
+
+#[cfg(unix)]
+pub struct Listener(std::os::unix::net::UnixListener);
+
+/// The pipe server is created in `start_listener` (it needs the async runtime).
+#[cfg(windows)]
+pub struct Listener;
+
+/// Called before the app is built. An error leaves this process running
+/// without single-instance support.
+pub fn acquire() -> io::Result<Acquired> {
+    let handoff = std::env::var_os(HANDOFF_ENV).is_some();
+    let message = serde_json::to_vec(&Forwarded {
+        args: std::env::args().skip(1).collect(),
+        cwd: std::env::current_dir().ok().map(|d| d.to_string_lossy().to_string()),
+    })
+    .map_err(io::Error::other)?;
+    acquire_endpoint(&message, handoff)
+}
+
+/// First argument that isn't a flag, made absolute against `cwd`.
+pub fn launch_path(args: &[String], cwd: Option<&Path>) -> Option<PathBuf> {
+    let arg = args.iter().find(|a| !a.starts_with('-'))?;
+    let path = PathBuf::from(arg);
+    if path.is_absolute() {
+        Some(path)
+    } else {
+        cwd.map(|cwd| cwd.join(path))
+    }
+}
+
+/// Unminimize, show and focus the window; re-create it when it was
+/// closed in background mode (tray.rs).
+pub fn focus_main_window(app: &AppHandle) {
+    let existing = app.webview_windows().into_values().next();
+    let window = match existing {
+        Some(w) => w,
+        None => {
+            let Some(config) = app.config().app.windows.first() else {
+                return;
+            };
+            match tauri::WebviewWindowBuilder::from_config(app, config).and_then(|b| b.build()) {
+                Ok(w) => w,
+                Err(e) => {
+                    tracing::warn!("[SingleInstance] Failed to re-create window: {e}");
+                    return;
+                }
+            }
+        }
+    };
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+}
+
+fn dispatch(app: &AppHandle, data: &[u8]) {
+    let forwarded: Forwarded = match serde_json::from_slice(data) {
+        Ok(f) => f,
+        Err(e) => {
+            tracing::warn!("[SingleInstance] Bad message: {e}");
+            return;
+        }
+    };
+    focus_main_window(app);
+
+    let cwd = forwarded.cwd.as_deref().map(Path::new);
+    let Some(path) = launch_path(&forwarded.args, cwd) else {
+        return;
+    };
+    match open_request(app, &path) {
+        Ok(request) => {
+            let _ = app.emit("fu:open_path_request", request);
+        }
+        Err(e) => tracing::warn!("[SingleInstance] Cannot open {:?}: {}", path, e.message),
+    }
+}
+
+/// Check a launch path against the sandbox and stat it.
+pub fn open_request(app: &AppHandle, path: &Path) -> Result<OpenPathRequest, AppError> {
+    let path = app.state::<PathGuard>().check(&path.to_string_lossy())?;
+    let meta = std::fs::metadata(&path).map_err(|e| AppError::io(&e).with_path(&path))?;
+    Ok(OpenPathRequest {
+        path: path.to_string_lossy().to_string(),
+        is_dir: meta.is_dir(),
+    })
+}
+
+#[cfg(unix)]
+fn socket_path() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|d| d.is_dir())
+        .unwrap_or_else(std::env::temp_dir);
+    // SAFETY: getuid has no preconditions.
+    let uid = unsafe { libc::getuid() };
+    dir.join(format!("{}-{}.sock", NAME, uid))
+}
+
+#[cfg(unix)]
+fn acquire_endpoint(message: &[u8], handoff: bool) -> io::Result<Acquired> {
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = socket_path();
+    if !handoff {
+        if let Ok(mut stream) = UnixStream::connect(&path) {
+            if stream.write_all(message).and_then(|_| stream.write_all(b"\n")).is_ok() {
+                return Ok(Acquired::Forwarded);
+            }
+        }
+    }
+
+    // Nobody answered: a stale file from a crash (or, during a handoff, the
+    // old version's socket).
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(Acquired::Primary(Listener(listener)))
+}
+
+/// Serve second instances for the lifetime of the app.
+#[cfg(unix)]
+pub fn start_listener(app: AppHandle, listener: Listener) {
+    use std::io::{BufRead, BufReader, Read};
+    use std::time::Duration;
+
+    std::thread::spawn(move || {
+        for stream in listener.0.incoming() {
+            let stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("[SingleInstance] Accept failed: {e}");
+                    continue;
+                }
+            };
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+            let mut line = Vec::new();
+            match BufReader::new(stream.take(MAX_MESSAGE)).read_until(b'\n', &mut line) {
+                Ok(_) => dispatch(&app, &line),
+                Err(e) => tracing::warn!("[SingleInstance] Read failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(windows)]
+fn pipe_name() -> String {
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!(r"\\.\pipe\{}-{}", NAME, user)
+}
+
+#[cfg(windows)]
+fn acquire_endpoint(message: &[u8], handoff: bool) -> io::Result<Acquired> {
+    use std::time::Duration;
+
+    /// All pipe instances are busy.
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    if !handoff {
+        for _ in 0..10 {
+            match std::fs::OpenOptions::new().write(true).open(pipe_name()) {
+                Ok(mut pipe) => {
+                    pipe.write_all(message)?;
+                    pipe.write_all(b"\n")?;
+                    return Ok(Acquired::Forwarded);
+                }
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(_) => break,
+            }
+        }
+    }
+    Ok(Acquired::Primary(Listener))
+}
+
+/// Serve second instances for the lifetime of the app.
+#[cfg(windows)]
+pub fn start_listener(app: AppHandle, _listener: Listener) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    // During a handoff the old version still owns the first instance.
+    let first = std::env::var_os(HANDOFF_ENV).is_none();
+    tauri::async_runtime::spawn(async move {
+        let name = pipe_name();
+        let mut server = match ServerOptions::new()
+            .first_pipe_instance(first)
+            .reject_remote_clients(true)
+            .create(&name)
+        {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("[SingleInstance] Failed to create pipe: {e}");
+                return;
+            }
+        };
+        loop {
+            if let Err(e) = server.connect().await {
+                tracing::warn!("[SingleInstance] Connect failed: {e}");
+                continue;
+            }
+            let connected = server;
+            server = match ServerOptions::new().reject_remote_clients(true).create(&name) {
+                Ok(s) => s,
+                Err(e) => {
+                    tracing::warn!("[SingleInstance] Failed to create pipe: {e}");
+                    return;
+                }
+            };
+
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let mut line = Vec::new();
+                match BufReader::new(connected.take(MAX_MESSAGE)).read_until(b'\n', &mut line).await {
+                    Ok(_) => dispatch(&app, &line),
+                    Err(e) => tracing::warn!("[SingleInstance] Read failed: {e}"),
+                }
+            });
+        }
+    });
+}
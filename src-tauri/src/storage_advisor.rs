@@ -0,0 +1,366 @@
+// src-tauri/src/storage_advisor.rs
+//
+// Storage advisor: one walk over a root that answers "where can I reclaim
+// space?". Every regular file is counted by
+//   - age:      time since last modification (under 1 year, 1-2, 2-5, 5+);
+//               access times are not used, most volumes don't keep them
+//   - size:     under 1 MiB, 1-100 MiB, 100 MiB-1 GiB, 1 GiB+
+//   - category: file_type::category_of (video, archives, installers, ...)
+// and aggregated per category and per top-level folder of the root, plus
+// the largest files that haven't been touched for a year.
+//
+// Symlinks are not followed. Progress streams as `fu:storage_advisor_progress`,
+// the report arrives with `fu:storage_advisor_completed` (partial on cancel).
+
+use crate::error::AppError;
+use crate::file_type::{category_of, FileCategory};
+use crate::gps_backend::{OperationKind, OperationRegistry};
+use crate::path_guard::PathGuard;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+const YEAR_SECS: u64 = 365 * 24 * 3600;
+const MIB: u64 = 1024 * 1024;
+
+/// Upper bounds (exclusive) of the age buckets, in years; the last bucket
+/// is open-ended.
+const AGE_BUCKETS: [(&str, u64); 4] = [("under1y", 1), ("1to2y", 2), ("2to5y", 5), ("over5y", u64::MAX)];
+
+/// Upper bounds (exclusive) of the size buckets, in bytes.
+const SIZE_BUCKETS: [(&str, u64); 4] = [
+    ("under1MiB", MIB),
+    ("1to100MiB", 100 * MIB),
+    ("100MiBto1GiB", 1024 * MIB),
+    ("over1GiB", u64::MAX),
+];
+
+/// Length of the largest-stale-files list.
+const STALE_FILES_LIMIT: usize = 50;
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub files: u64,
+    pub bytes: u64,
+}
+
+impl Totals {
+    fn add(&mut self, size: u64) {
+        self.files += 1;
+        self.bytes = self.bytes.saturating_add(size);
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Bucket {
+    /// Stable key, e.g. "2to5y" or "over1GiB".
+    pub key: &'static str,
+    #[serde(flatten)]
+    pub totals: Totals,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryReport {
+    pub category: FileCategory,
+    #[serde(flatten)]
+    pub totals: Totals,
+    /// Files not modified for a year or more.
+    pub stale: Totals,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderReport {
+    pub name: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub totals: Totals,
+    pub stale: Totals,
+    /// The category taking the most bytes in this folder.
+    pub top_category: Option<FileCategory>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleFile {
+    pub path: String,
+    pub size: u64,
+    /// Seconds since UNIX_EPOCH.
+    pub modified: u64,
+    pub category: FileCategory,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    pub root: String,
+    pub totals: Totals,
+    pub by_age: Vec<Bucket>,
+    pub by_size: Vec<Bucket>,
+    /// Largest first; empty categories left out.
+    pub by_category: Vec<CategoryReport>,
+    /// Top-level folders of the root, largest first.
+    pub by_folder: Vec<FolderReport>,
+    /// Files directly in the root.
+    pub root_files: Totals,
+    /// Largest files not modified for a year or more, largest first.
+    pub largest_stale_files: Vec<StaleFile>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StorageAdvisorProgress {
+    op_id: String,
+    files_seen: u64,
+    bytes_seen: u64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StorageAdvisorCompleted {
+    op_id: String,
+    status: String, // "ok" | "cancelled" | "error"
+    /// Set for "ok" and "cancelled" (what was counted so far).
+    report: Option<StorageReport>,
+    error_message: Option<String>,
+    /// Structured form of the failure (also set for "cancelled").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// Command from TS:
+/// invoke("start_storage_advisor", { opId, path })
+#[tauri::command]
+pub async fn start_storage_advisor(
+    app: AppHandle,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    op_id: String,
+    path: String,
+) -> Result<(), AppError> {
+    let root = guard.check(&path)?;
+
+    let token = registry.register(&op_id, OperationKind::StorageAdvisor);
+    let registry = registry.inner().clone();
+
+    task::spawn_blocking(move || {
+        let mut advisor = Advisor::new(SystemTime::now());
+        let res = advisor.run(&app, &op_id, &root, &token);
+
+        let (status, report, error) = match res {
+            Ok(()) => ("ok", Some(advisor.report(&root)), None),
+            Err(None) => ("cancelled", Some(advisor.report(&root)), Some(AppError::cancelled())),
+            Err(Some(e)) => ("error", None, Some(e)),
+        };
+        let _ = app.emit(
+            "fu:storage_advisor_completed",
+            StorageAdvisorCompleted {
+                op_id: op_id.clone(),
+                status: status.to_string(),
+                report,
+                error_message: error.as_ref().filter(|_| status == "error").map(|e| e.message.clone()),
+                error,
+            },
+        );
+
+        registry.finish(&op_id, &token);
+    });
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct FolderAcc {
+    totals: Totals,
+    stale: Totals,
+    by_category: HashMap<FileCategory, u64>,
+}
+
+struct Advisor {
+    now: SystemTime,
+    totals: Totals,
+    by_age: [Totals; 4],
+    by_size: [Totals; 4],
+    by_category: HashMap<FileCategory, (Totals, Totals)>,
+    folders: HashMap<OsString, FolderAcc>,
+    root_files: Totals,
+    /// Min-heap on size, so the smallest of the kept files drops out.
+    stale_files: BinaryHeap<Reverse<(u64, u64, PathBuf)>>,
+}
+
+impl Advisor {
+    fn new(now: SystemTime) -> Self {
+        Advisor {
+            now,
+            totals: Totals::default(),
+            by_age: Default::default(),
+            by_size: Default::default(),
+            by_category: HashMap::new(),
+            folders: HashMap::new(),
+            root_files: Totals::default(),
+            stale_files: BinaryHeap::new(),
+        }
+    }
+
+    /// Err(None) = cancelled.
+    fn run(
+        &mut self,
+        app: &AppHandle,
+        op_id: &str,
+        root: &Path,
+        token: &CancellationToken,
+    ) -> Result<(), Option<AppError>> {
+        std::fs::metadata(root).map_err(|e| Some(AppError::io(&e).with_path(root)))?;
+        let mut last_emit = Instant::now();
+
+        for entry in WalkDir::new(root) {
+            if token.is_cancelled() {
+                return Err(None);
+            }
+            let entry = match entry {
+                Ok(e) => e,
+                Err(err) => {
+                    tracing::warn!("[StorageAdvisor] WalkDir error: {err}");
+                    continue;
+                }
+            };
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let meta = match entry.metadata() {
+                Ok(m) => m,
+                Err(err) => {
+                    tracing::warn!("[StorageAdvisor] Metadata error: {err}");
+                    continue;
+                }
+            };
+            let modified = meta.modified().unwrap_or(self.now);
+            self.add(root, entry.into_path(), meta.len(), modified);
+
+            if last_emit.elapsed() >= Duration::from_millis(100) {
+                self.emit_progress(app, op_id);
+                last_emit = Instant::now();
+            }
+        }
+        self.emit_progress(app, op_id);
+        Ok(())
+    }
+
+    fn add(&mut self, root: &Path, path: PathBuf, size: u64, modified: SystemTime) {
+        let age_years = self.now.duration_since(modified).unwrap_or_default().as_secs() / YEAR_SECS;
+        let stale = age_years >= 1;
+        let category = category_of(&path);
+
+        self.totals.add(size);
+        if let Some(i) = AGE_BUCKETS.iter().position(|(_, max)| age_years < *max) {
+            self.by_age[i].add(size);
+        }
+        if let Some(i) = SIZE_BUCKETS.iter().position(|(_, max)| size < *max) {
+            self.by_size[i].add(size);
+        }
+        let (cat_totals, cat_stale) = self.by_category.entry(category).or_default();
+        cat_totals.add(size);
+        if stale {
+            cat_stale.add(size);
+        }
+
+        // First component below the root; files directly in it have none
+        // besides their own name.
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let mut components = rel.components();
+        match (components.next(), components.next()) {
+            (Some(top), Some(_)) => {
+                let folder = self.folders.entry(top.as_os_str().to_os_string()).or_default();
+                folder.totals.add(size);
+                if stale {
+                    folder.stale.add(size);
+                }
+                *folder.by_category.entry(category).or_default() += size;
+            }
+            _ => self.root_files.add(size),
+        }
+
+        if stale {
+            let secs = modified.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            self.stale_files.push(Reverse((size, secs, path)));
+            if self.stale_files.len() > STALE_FILES_LIMIT {
+                self.stale_files.pop();
+            }
+        }
+    }
+
+    fn emit_progress(&self, app: &AppHandle, op_id: &str) {
+        let _ = app.emit(
+            "fu:storage_advisor_progress",
+            StorageAdvisorProgress {
+                op_id: op_id.to_string(),
+                files_seen: self.totals.files,
+                bytes_seen: self.totals.bytes,
+            },
+        );
+    }
+
+    fn report(&mut self, root: &Path) -> StorageReport {
+        let buckets = |keys: &[(&'static str, u64); 4], totals: &[Totals; 4]| {
+            keys.iter()
+                .zip(totals)
+                .map(|((key, _), totals)| Bucket {
+                    key,
+                    totals: totals.clone(),
+                })
+                .collect()
+        };
+
+        let mut by_category: Vec<CategoryReport> = self
+            .by_category
+            .drain()
+            .map(|(category, (totals, stale))| CategoryReport { category, totals, stale })
+            .collect();
+        by_category.sort_by_key(|c| Reverse(c.totals.bytes));
+
+        let mut by_folder: Vec<FolderReport> = self
+            .folders
+            .drain()
+            .map(|(name, acc)| FolderReport {
+                name: name.to_string_lossy().to_string(),
+                path: root.join(&name).to_string_lossy().to_string(),
+                top_category: acc.by_category.iter().max_by_key(|(_, bytes)| **bytes).map(|(c, _)| *c),
+                totals: acc.totals,
+                stale: acc.stale,
+            })
+            .collect();
+        by_folder.sort_by_key(|f| Reverse(f.totals.bytes));
+
+        let largest_stale_files = std::mem::take(&mut self.stale_files)
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((size, modified, path))| StaleFile {
+                category: category_of(&path),
+                path: path.to_string_lossy().to_string(),
+                size,
+                modified,
+            })
+            .collect();
+
+        StorageReport {
+            root: root.to_string_lossy().to_string(),
+            totals: self.totals.clone(),
+            by_age: buckets(&AGE_BUCKETS, &self.by_age),
+            by_size: buckets(&SIZE_BUCKETS, &self.by_size),
+            by_category,
+            by_folder,
+            root_files: self.root_files.clone(),
+            largest_stale_files,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// src-tauri/src/text_preview.rs
+//
+// Quick preview of text files (logs, configs, source) for the preview pane.
+// - Reads at most max_bytes from the start of the file, never the whole file.
+// - Encoding: BOM first (UTF-8 / UTF-16LE / UTF-16BE), then BOM-less UTF-16
+//   (NUL in every other byte), then strict UTF-8, then chardetng's guess among legacy encodings (windows-1252, Shift_JIS,
+//   GBK, KOI8-R, ...).
+// - Binary files (NUL bytes without a UTF-16 BOM) come back with empty text
+//   and isBinary = true, so the UI can show "binary file" instead of garbage.
+// - A multi-byte character cut at the max_bytes boundary is dropped rather
+//   than turned into a replacement character.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tauri::async_runtime::spawn_blocking;
+use tauri::State;
+
+use crate::file_type::looks_like_text;
+use crate::path_guard::PathGuard;
+
+const DEFAULT_MAX_BYTES: usize = 64 * 1024;
+const MAX_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TextPreview {
+    /// Decoded text (UTF-8). Empty for binary files.
+    pub text: String,
+    /// WHATWG encoding name, e.g. "UTF-8", "UTF-16LE", "windows-1252".
+    pub encoding: String,
+    pub had_bom: bool,
+    /// Some bytes could not be decoded and were replaced with U+FFFD.
+    pub had_errors: bool,
+    pub is_binary: bool,
+    /// File is larger than what was read.
+    pub truncated: bool,
+    pub bytes_read: u64,
+    pub file_size: u64,
+}
+
+/// Command from TS:
+/// invoke<TextPreview>("read_text_preview", { path, maxBytes: 65536 })
+#[tauri::command]
+pub async fn read_text_preview(
+    guard: State<'_, PathGuard>,
+    path: String,
+    max_bytes: Option<usize>,
+) -> Result<TextPreview, String> {
+    let file = guard.check(&path)?;
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_MAX_BYTES).clamp(1, MAX_MAX_BYTES);
+    spawn_blocking(move || {
+        preview_path(&file, max_bytes).map_err(|e| format!("Cannot read {}: {}", path, e))
+    })
+    .await
+    .map_err(|e| format!("Preview worker failed: {}", e))?
+}
+
+fn preview_path(path: &Path, max_bytes: usize) -> std::io::Result<TextPreview> {
+    let file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+
+    let mut head = Vec::with_capacity(max_bytes.min(file_size as usize));
+    file.take(max_bytes as u64).read_to_end(&mut head)?;
+    let truncated = file_size > head.len() as u64;
+
+    let bom = Encoding::for_bom(&head).or_else(|| sniff_utf16(&head).map(|enc| (enc, 0)));
+    let is_binary = bom.is_none() && !looks_like_text(&head);
+
+    let mut preview = TextPreview {
+        text: String::new(),
+        encoding: String::new(),
+        had_bom: bom.is_some_and(|(_, len)| len > 0),
+        had_errors: false,
+        is_binary,
+        truncated,
+        bytes_read: head.len() as u64,
+        file_size,
+    };
+    if is_binary {
+        return Ok(preview);
+    }
+
+    let (encoding, bom_len) = match bom {
+        Some((enc, len)) => (enc, len),
+        None => (detect_encoding(&head, truncated), 0),
+    };
+    let (text, had_errors) = decode(encoding, &head[bom_len..], truncated);
+
+    preview.encoding = encoding.name().to_string();
+    preview.text = text;
+    preview.had_errors = had_errors;
+    Ok(preview)
+}
+
+/// BOM-less UTF-16 (common for Windows exports): mostly-ASCII text has a
+/// NUL in nearly every high byte and almost none in the low bytes.
+fn sniff_utf16(head: &[u8]) -> Option<&'static Encoding> {
+    let pairs = head.len() / 2;
+    if pairs < 8 {
+        return None;
+    }
+    let (mut even, mut odd) = (0usize, 0usize);
+    for pair in head.chunks_exact(2) {
+        even += (pair[0] == 0) as usize;
+        odd += (pair[1] == 0) as usize;
+    }
+    if odd * 10 >= pairs * 9 && even * 10 <= pairs {
+        Some(UTF_16LE)
+    } else if even * 10 >= pairs * 9 && odd * 10 <= pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Strict UTF-8 wins (ASCII included); otherwise ask chardetng.
+fn detect_encoding(head: &[u8], truncated: bool) -> &'static Encoding {
+    match std::str::from_utf8(head) {
+        Ok(_) => return UTF_8,
+        // Only the tail is incomplete: still UTF-8, just cut mid-character.
+        Err(e) if e.error_len().is_none() && truncated => return UTF_8,
+        Err(_) => {}
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(head, !truncated);
+    detector.guess(None, true)
+}
+
+/// Decode without BOM sniffing. When the input was cut short, `last = false`
+/// keeps a trailing partial character buffered instead of emitting U+FFFD.
+fn decode(encoding: &'static Encoding, bytes: &[u8], truncated: bool) -> (String, bool) {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder
+        .max_utf8_buffer_length(bytes.len())
+        .unwrap_or(bytes.len() * 3);
+    let mut out = String::with_capacity(capacity);
+    let (_, _, had_errors) = decoder.decode_to_string(bytes, &mut out, !truncated);
+    (out, had_errors)
+}
@@ -0,0 +1,191 @@
+// src-tauri/src/thumbnails.rs
+//
+// Image thumbnails for grid / list views.
+// - Decodes JPEG, PNG, GIF (first frame), WebP, BMP and TIFF.
+// - Downscales to fit max_dim x max_dim (aspect ratio kept).
+// - Caches the result under the app cache dir:
+//     <app cache dir>/thumbnails/<blake3(path, mtime, size, max_dim, format)>.<ext>
+//   so an edited file (new mtime/size) gets a fresh thumbnail, and old
+//   entries are just never hit again.
+// - Returns the cache path (use convertFileSrc in the UI) and, on request,
+//   the bytes as base64 for places that need a data: URL.
+
+use base64::Engine;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime::spawn_blocking;
+use tauri::{AppHandle, Manager, State};
+
+use crate::path_guard::PathGuard;
+
+const DEFAULT_MAX_DIM: u32 = 256;
+const MAX_MAX_DIM: u32 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFormat {
+    /// Small, no alpha (transparent areas are flattened).
+    #[default]
+    Jpeg,
+    /// Keeps transparency (icons, screenshots).
+    Png,
+}
+
+impl ThumbnailFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpg",
+            ThumbnailFormat::Png => "png",
+        }
+    }
+
+    fn mime(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "image/jpeg",
+            ThumbnailFormat::Png => "image/png",
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Thumbnail {
+    pub cache_path: String,
+    pub mime: String,
+    pub width: u32,
+    pub height: u32,
+    /// Only set when called with base64: true.
+    pub base64: Option<String>,
+    /// True if served from the cache without decoding.
+    pub cached: bool,
+}
+
+/// Command from TS:
+/// invoke<Thumbnail>("get_thumbnail", { path, maxDim: 256, format: "jpeg", base64: false })
+#[tauri::command]
+pub async fn get_thumbnail(
+    guard: State<'_, PathGuard>,
+    app: AppHandle,
+    path: String,
+    max_dim: Option<u32>,
+    format: Option<ThumbnailFormat>,
+    base64: Option<bool>,
+) -> Result<Thumbnail, String> {
+    let file = guard.check(&path)?;
+    let max_dim = max_dim.unwrap_or(DEFAULT_MAX_DIM).clamp(16, MAX_MAX_DIM);
+    let format = format.unwrap_or_default();
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("App cache dir error: {}", e))?
+        .join("thumbnails");
+
+    spawn_blocking(move || {
+        make_thumbnail(&file, &cache_dir, max_dim, format, base64.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Thumbnail worker failed: {}", e))?
+}
+
+fn make_thumbnail(
+    src: &Path,
+    cache_dir: &Path,
+    max_dim: u32,
+    format: ThumbnailFormat,
+    want_base64: bool,
+) -> Result<Thumbnail, String> {
+    let meta = fs::metadata(src).map_err(|e| format!("Cannot read {:?}: {}", src, e))?;
+    if !meta.is_file() {
+        return Err(format!("Not a file: {:?}", src));
+    }
+
+    let cache_path = cache_path(cache_dir, src, &meta, max_dim, format);
+
+    // ==== Cache hit ====
+    if let Ok(bytes) = fs::read(&cache_path) {
+        if let Ok((width, height)) = image::image_dimensions(&cache_path) {
+            return Ok(build_result(&cache_path, format, width, height, want_base64.then_some(bytes), true));
+        }
+    }
+
+    // ==== Decode + downscale ====
+    let img = ImageReader::open(src)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| format!("Cannot open image {:?}: {}", src, e))?
+        .decode()
+        .map_err(|e| format!("Cannot decode image {:?}: {}", src, e))?;
+    let thumb = img.thumbnail(max_dim, max_dim);
+    let (width, height) = (thumb.width(), thumb.height());
+
+    let (thumb, image_format) = match format {
+        // JPEG has no alpha channel; the encoder rejects RGBA input.
+        ThumbnailFormat::Jpeg => (DynamicImage::ImageRgb8(thumb.to_rgb8()), ImageFormat::Jpeg),
+        ThumbnailFormat::Png => (thumb, ImageFormat::Png),
+    };
+    let mut bytes = Vec::new();
+    thumb
+        .write_to(&mut Cursor::new(&mut bytes), image_format)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+
+    // ==== Store (best-effort: a read-only cache still returns bytes) ====
+    if let Err(e) = write_atomic(&cache_path, &bytes) {
+        tracing::warn!("[Thumbnails] Failed to cache {:?}: {e}", cache_path);
+    }
+
+    Ok(build_result(&cache_path, format, width, height, want_base64.then_some(bytes), false))
+}
+
+fn build_result(
+    cache_path: &Path,
+    format: ThumbnailFormat,
+    width: u32,
+    height: u32,
+    bytes: Option<Vec<u8>>,
+    cached: bool,
+) -> Thumbnail {
+    Thumbnail {
+        cache_path: cache_path.to_string_lossy().to_string(),
+        mime: format.mime().to_string(),
+        width,
+        height,
+        base64: bytes.map(|b| base64::engine::general_purpose::STANDARD.encode(b)),
+        cached,
+    }
+}
+
+fn cache_path(
+    cache_dir: &Path,
+    src: &Path,
+    meta: &fs::Metadata,
+    max_dim: u32,
+    format: ThumbnailFormat,
+) -> PathBuf {
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(src.to_string_lossy().as_bytes());
+    hasher.update(&mtime.to_le_bytes());
+    hasher.update(&meta.len().to_le_bytes());
+    hasher.update(&max_dim.to_le_bytes());
+    hasher.update(format.extension().as_bytes());
+    let hash = hasher.finalize().to_hex();
+
+    cache_dir.join(format!("{}.{}", &hash[..32], format.extension()))
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(&tmp, path)
+}
@@ -0,0 +1,444 @@
+// src-tauri/src/transfer_queue.rs
+//
+// Queue in front of the copy / move engine (file_ops.rs) for big
+// copy / upload / download jobs: at most `transfer_max_concurrent`
+// (settings) run at once, the rest wait in order. A changed limit applies
+// from the next queue change on.
+//
+//   - enqueue_transfer: paths are resolved and checked right away; the
+//     job is inserted after every queued job of equal or higher priority.
+//   - move_transfer: put a queued job at another position (its priority
+//     is kept, it only affects later inserts).
+//   - pause_transfer / resume_transfer: a queued job is held back (other
+//     jobs pass it); a running one is paused through the GPS registry.
+//   - cancel_transfer: a queued job is dropped, a running one cancelled.
+//   - set_transfer_bandwidth: bytes/s cap, also for running jobs.
+//
+// Running jobs are regular GPS ops (same op_id, `fu:file_op_progress` /
+// `fu:file_op_completed`). A job that is cancelled while queued, or fails
+// to start, still gets its `fu:file_op_completed`.
+//
+// Every change emits `fu:transfer_queue` with the full queue snapshot.
+//
+// State: app.manage(TransferQueue::new(settings.system_handle())) in setup.
+
+use crate::error::{AppError, ErrorCode};
+use crate::file_ops::{
+    emit_not_started, start_resolved_file_op, ConflictPolicy, CopyOptions, FileOpKind, FileOpOptions,
+};
+use crate::gps_backend::OperationRegistry;
+use crate::path_guard::PathGuard;
+use crate::settings::SharedSystemSettings;
+use crate::vfs::{Vfs, VfsPath};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferState {
+    Queued,
+    /// Queued, but skipped until resumed.
+    Held,
+    Running,
+}
+
+/// One job as shown in the transfer panel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferInfo {
+    pub op_id: String,
+    pub kind: FileOpKind,
+    pub state: TransferState,
+    pub priority: i32,
+    /// Bytes per second; 0 = unlimited.
+    pub bandwidth_limit: u64,
+    pub sources: Vec<String>,
+    pub destination: String,
+    /// Milliseconds since UNIX_EPOCH.
+    pub enqueued_at: u64,
+}
+
+/// Payload of `fu:transfer_queue`: running jobs first, then the queue in
+/// start order.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferQueueSnapshot {
+    pub max_concurrent: u32,
+    pub jobs: Vec<TransferInfo>,
+}
+
+struct QueuedJob {
+    info: TransferInfo,
+    resolved_sources: Vec<VfsPath>,
+    resolved_destination: VfsPath,
+    bandwidth_limit: Arc<AtomicU64>,
+    conflict_policy: Option<ConflictPolicy>,
+    copy_options: CopyOptions,
+}
+
+#[derive(Default)]
+struct QueueInner {
+    /// Waiting jobs in start order.
+    pending: Vec<QueuedJob>,
+    /// Started jobs; removed by the job's on_finish hook.
+    running: Vec<(TransferInfo, Arc<AtomicU64>)>,
+}
+
+#[derive(Clone)]
+pub struct TransferQueue {
+    inner: Arc<Mutex<QueueInner>>,
+    settings: SharedSystemSettings,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn unknown(op_id: &str) -> AppError {
+    AppError::new(ErrorCode::NotFound, format!("No queued transfer {}", op_id))
+}
+
+impl TransferQueue {
+    pub fn new(settings: SharedSystemSettings) -> Self {
+        TransferQueue {
+            inner: Arc::default(),
+            settings,
+        }
+    }
+
+    fn max_concurrent(&self) -> u32 {
+        self.settings
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .transfer_max_concurrent
+            .max(1)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn snapshot(&self) -> TransferQueueSnapshot {
+        let inner = self.lock();
+        TransferQueueSnapshot {
+            max_concurrent: self.max_concurrent(),
+            jobs: inner
+                .running
+                .iter()
+                .map(|(info, _)| info.clone())
+                .chain(inner.pending.iter().map(|job| job.info.clone()))
+                .collect(),
+        }
+    }
+
+    fn emit(&self, app: &AppHandle) {
+        let _ = app.emit("fu:transfer_queue", self.snapshot());
+    }
+
+    fn enqueue(&self, job: QueuedJob) -> Result<(), AppError> {
+        let mut inner = self.lock();
+        let op_id = &job.info.op_id;
+        let taken = inner.running.iter().any(|(info, _)| &info.op_id == op_id)
+            || inner.pending.iter().any(|j| &j.info.op_id == op_id);
+        if taken {
+            return Err(AppError::new(
+                ErrorCode::AlreadyExists,
+                format!("Transfer {} is already queued", op_id),
+            ));
+        }
+        let at = inner
+            .pending
+            .iter()
+            .position(|j| j.info.priority < job.info.priority)
+            .unwrap_or(inner.pending.len());
+        inner.pending.insert(at, job);
+        Ok(())
+    }
+
+    /// Start queued jobs while there is room, then report the new state.
+    fn pump(&self, app: &AppHandle, registry: &OperationRegistry) {
+        let mut to_start = Vec::new();
+        {
+            let mut inner = self.lock();
+            let max = self.max_concurrent() as usize;
+            while inner.running.len() < max {
+                let Some(i) = inner
+                    .pending
+                    .iter()
+                    .position(|j| j.info.state == TransferState::Queued)
+                else {
+                    break;
+                };
+                let mut job = inner.pending.remove(i);
+                job.info.state = TransferState::Running;
+                inner
+                    .running
+                    .push((job.info.clone(), job.bandwidth_limit.clone()));
+                to_start.push(job);
+            }
+        }
+
+        // Started outside the lock: on_finish locks the queue again.
+        let mut failed = false;
+        for job in to_start {
+            let op_id = job.info.op_id.clone();
+            let queue = self.clone();
+            let hook_app = app.clone();
+            let hook_registry = registry.clone();
+            let hook_op = op_id.clone();
+            let options = FileOpOptions {
+                conflict_policy: job.conflict_policy,
+                copy_options: job.copy_options,
+                bandwidth_limit: job.bandwidth_limit,
+                on_finish: Some(Box::new(move || {
+                    queue.remove_running(&hook_op);
+                    queue.pump(&hook_app, &hook_registry);
+                })),
+                resume_done: None,
+            };
+            let started = start_resolved_file_op(
+                app.clone(),
+                registry.clone(),
+                op_id.clone(),
+                job.info.kind,
+                job.resolved_sources,
+                job.resolved_destination,
+                options,
+            );
+            if let Err(e) = started {
+                tracing::warn!("[TransferQueue] Failed to start {}: {}", op_id, e.message);
+                self.remove_running(&op_id);
+                emit_not_started(app, &op_id, job.info.kind, e);
+                failed = true;
+            }
+        }
+        if failed {
+            // Their slots are free again.
+            self.pump(app, registry);
+        } else {
+            self.emit(app);
+        }
+    }
+
+    fn remove_running(&self, op_id: &str) {
+        self.lock().running.retain(|(info, _)| info.op_id != op_id);
+    }
+
+    fn is_running(&self, op_id: &str) -> bool {
+        self.lock().running.iter().any(|(info, _)| info.op_id == op_id)
+    }
+}
+
+/// Queue a copy / move job. It starts right away when fewer than
+/// `transfer_max_concurrent` jobs are running.
+///
+/// Frontend can call:
+///   invoke('enqueue_transfer', { opId, kind: 'copy', sources, destination, priority: 0,
+///     bandwidthLimit: 0, conflictPolicy: 'ask', copyOptions: { preserveTimes: true } })
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn enqueue_transfer(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    registry: State<'_, OperationRegistry>,
+    guard: State<'_, PathGuard>,
+    vfs: State<'_, Vfs>,
+    op_id: String,
+    kind: FileOpKind,
+    sources: Vec<String>,
+    destination: String,
+    priority: Option<i32>,
+    bandwidth_limit: Option<u64>,
+    conflict_policy: Option<ConflictPolicy>,
+    copy_options: Option<CopyOptions>,
+) -> Result<(), AppError> {
+    if sources.is_empty() {
+        return Err(AppError::invalid_input("No source paths given"));
+    }
+    let resolved_sources = vfs.resolve_all(&guard, &sources)?;
+    let resolved_destination = vfs.resolve(&guard, &destination)?;
+    let bandwidth_limit = bandwidth_limit.unwrap_or(0);
+
+    queue.enqueue(QueuedJob {
+        info: TransferInfo {
+            op_id,
+            kind,
+            state: TransferState::Queued,
+            priority: priority.unwrap_or(0),
+            bandwidth_limit,
+            sources,
+            destination,
+            enqueued_at: now_millis(),
+        },
+        resolved_sources,
+        resolved_destination,
+        bandwidth_limit: Arc::new(AtomicU64::new(bandwidth_limit)),
+        conflict_policy,
+        copy_options: copy_options.unwrap_or_default(),
+    })?;
+    queue.pump(&app, &registry);
+    Ok(())
+}
+
+/// Frontend can call:
+///   invoke<TransferQueueSnapshot>('get_transfer_queue')
+#[tauri::command]
+pub fn get_transfer_queue(queue: State<'_, TransferQueue>) -> TransferQueueSnapshot {
+    queue.snapshot()
+}
+
+/// Move a queued job to `position` (0 = next to start) among the queued
+/// jobs.
+///
+/// Frontend can call:
+///   invoke('move_transfer', { opId, position: 0 })
+#[tauri::command]
+pub fn move_transfer(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    op_id: String,
+    position: usize,
+) -> Result<(), AppError> {
+    {
+        let mut inner = queue.lock();
+        let from = inner
+            .pending
+            .iter()
+            .position(|j| j.info.op_id == op_id)
+            .ok_or_else(|| unknown(&op_id))?;
+        let job = inner.pending.remove(from);
+        let to = position.min(inner.pending.len());
+        inner.pending.insert(to, job);
+    }
+    queue.emit(&app);
+    Ok(())
+}
+
+/// Hold a queued job back, or pause a running one.
+///
+/// Frontend can call:
+///   invoke('pause_transfer', { opId })
+#[tauri::command]
+pub fn pause_transfer(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    registry: State<'_, OperationRegistry>,
+    op_id: String,
+) -> Result<(), AppError> {
+    set_held(&app, &queue, &registry, &op_id, true)
+}
+
+/// Release a held job, or resume a paused running one.
+///
+/// Frontend can call:
+///   invoke('resume_transfer', { opId })
+#[tauri::command]
+pub fn resume_transfer(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    registry: State<'_, OperationRegistry>,
+    op_id: String,
+) -> Result<(), AppError> {
+    set_held(&app, &queue, &registry, &op_id, false)
+}
+
+fn set_held(
+    app: &AppHandle,
+    queue: &TransferQueue,
+    registry: &OperationRegistry,
+    op_id: &str,
+    held: bool,
+) -> Result<(), AppError> {
+    if queue.is_running(op_id) {
+        registry
+            .set_paused(op_id, held)
+            .map_err(|e| AppError::new(ErrorCode::InvalidInput, e))?;
+        queue.emit(app);
+        return Ok(());
+    }
+    {
+        let mut inner = queue.lock();
+        let job = inner
+            .pending
+            .iter_mut()
+            .find(|j| j.info.op_id == op_id)
+            .ok_or_else(|| unknown(op_id))?;
+        job.info.state = if held {
+            TransferState::Held
+        } else {
+            TransferState::Queued
+        };
+    }
+    queue.pump(app, registry);
+    Ok(())
+}
+
+/// Drop a queued job or cancel a running one.
+///
+/// Frontend can call:
+///   invoke('cancel_transfer', { opId })
+#[tauri::command]
+pub fn cancel_transfer(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    registry: State<'_, OperationRegistry>,
+    op_id: String,
+) -> Result<(), AppError> {
+    if queue.is_running(&op_id) {
+        // The job's on_finish hook removes it and starts the next one.
+        registry.cancel(&op_id);
+        return Ok(());
+    }
+    let job = {
+        let mut inner = queue.lock();
+        let i = inner
+            .pending
+            .iter()
+            .position(|j| j.info.op_id == op_id)
+            .ok_or_else(|| unknown(&op_id))?;
+        inner.pending.remove(i)
+    };
+    emit_not_started(&app, &op_id, job.info.kind, AppError::cancelled());
+    queue.emit(&app);
+    Ok(())
+}
+
+/// Change a job's bandwidth cap (bytes/s, 0 = unlimited); running jobs
+/// pick it up at their next chunk.
+///
+/// Frontend can call:
+///   invoke('set_transfer_bandwidth', { opId, bandwidthLimit: 5 * 1024 * 1024 })
+#[tauri::command]
+pub fn set_transfer_bandwidth(
+    app: AppHandle,
+    queue: State<'_, TransferQueue>,
+    op_id: String,
+    bandwidth_limit: u64,
+) -> Result<(), AppError> {
+    {
+        let mut inner = queue.lock();
+        let inner = &mut *inner;
+        let (info, limit) = inner
+            .running
+            .iter_mut()
+            .map(|(info, limit)| (info, limit.clone()))
+            .chain(
+                inner
+                    .pending
+                    .iter_mut()
+                    .map(|j| (&mut j.info, j.bandwidth_limit.clone())),
+            )
+            .find(|(info, _)| info.op_id == op_id)
+            .ok_or_else(|| unknown(&op_id))?;
+        info.bandwidth_limit = bandwidth_limit;
+        limit.store(bandwidth_limit, Ordering::Relaxed);
+    }
+    queue.emit(&app);
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// src-tauri/src/tray.rs
+//
+// System tray icon and background mode.
+//
+// Tray menu: Show FilesUP, Check for updates (runs the update scheduler's
+// check now; a result arrives as `fu:update_available`), Pause metrics,
+// Open last folder (settings.last_folder, via `fu:open_path_request` like a
+// second launch), Quit. A left click on the icon shows the window.
+//
+// Behaviour, from settings.system:
+//   - minimize_to_tray: a minimized window is hidden instead
+//   - close_to_tray:    background mode; closing the window destroys it
+//                       (frees the webview) but the process keeps running,
+//                       so schedules (scheduler.rs), update checks and
+//                       watchers (fs_watch.rs) go on. The window is
+//                       re-created from the tray or by a second launch.
+// Quit from the tray (or app.exit) always ends the process.
+
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, Window, WindowEvent, Wry};
+
+use crate::metrics::{pause_metrics, resume_metrics, MetricsControl};
+use crate::settings::{SettingsState, SystemSettings};
+use crate::single_instance::{focus_main_window, open_request};
+use crate::update::UpdateScheduler;
+
+const MENU_SHOW: &str = "tray-show";
+const MENU_CHECK_UPDATES: &str = "tray-check-updates";
+const MENU_PAUSE_METRICS: &str = "tray-pause-metrics";
+const MENU_OPEN_LAST: &str = "tray-open-last";
+const MENU_QUIT: &str = "tray-quit";
+
+/// Called once from setup, after settings and metrics are managed.
+pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
+    let pause = CheckMenuItem::with_id(app, MENU_PAUSE_METRICS, "Pause metrics", true, false, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &MenuItem::with_id(app, MENU_SHOW, "Show FilesUP", true, None::<&str>)?,
+            &MenuItem::with_id(app, MENU_OPEN_LAST, "Open last folder", true, None::<&str>)?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, MENU_CHECK_UPDATES, "Check for updates", true, None::<&str>)?,
+            &pause,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?,
+        ],
+    )?;
+
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip(app.package_info().name.clone())
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(move |app, event| on_menu_event(app, &event, &pause))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                focus_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+fn on_menu_event(app: &AppHandle, event: &MenuEvent, pause: &CheckMenuItem<Wry>) {
+    match event.id.as_ref() {
+        MENU_SHOW => focus_main_window(app),
+        MENU_CHECK_UPDATES => app.state::<UpdateScheduler>().trigger(),
+        MENU_PAUSE_METRICS => {
+            let status = if app.state::<MetricsControl>().is_paused() {
+                resume_metrics(app.clone(), app.state(), app.state())
+            } else {
+                pause_metrics(app.state())
+            };
+            let _ = pause.set_checked(status == "paused");
+        }
+        MENU_OPEN_LAST => open_last_folder(app),
+        MENU_QUIT => app.exit(0),
+        _ => {}
+    }
+}
+
+fn open_last_folder(app: &AppHandle) {
+    focus_main_window(app);
+    let last = app.state::<SettingsState>().get().last_folder;
+    if last.is_empty() {
+        return;
+    }
+    match open_request(app, std::path::Path::new(&last)) {
+        Ok(request) => {
+            let _ = app.emit("fu:open_path_request", request);
+        }
+        Err(e) => tracing::warn!("[Tray] Cannot open last folder {:?}: {}", last, e.message),
+    }
+}
+
+fn system_flag(app: &AppHandle, flag: impl Fn(&SystemSettings) -> bool) -> bool {
+    // Window events can arrive before setup managed the settings.
+    let Some(state) = app.try_state::<SettingsState>() else {
+        return false;
+    };
+    let system = state.system_handle();
+    let system = system.read().unwrap_or_else(|e| e.into_inner());
+    flag(&system)
+}
+
+/// Registered with Builder::on_window_event in lib.rs.
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+    if let WindowEvent::Resized(_) = event {
+        let to_tray = system_flag(window.app_handle(), |s| s.minimize_to_tray);
+        if to_tray && window.is_minimized().unwrap_or(false) {
+            let _ = window.hide();
+        }
+    }
+}
+
+/// Passed to App::run in lib.rs: keeps the process alive in background
+/// mode once the last window is closed.
+pub fn on_run_event(app: &AppHandle, event: RunEvent) {
+    if let RunEvent::ExitRequested { code: None, api, .. } = event {
+        if system_flag(app, |s| s.close_to_tray) {
+            api.prevent_exit();
+        }
+    }
+}
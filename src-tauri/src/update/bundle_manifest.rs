@@ -0,0 +1,199 @@
+// src-tauri/src/update/bundle_manifest.rs
+//
+// manifest.json at the root of every update bundle (and therefore of every
+// versions/<v>/ folder after applying):
+//
+//   {
+//     "version": "0.2.3",
+//     "min_launcher_version": "0.2.0",
+//     "files": [
+//       { "path": "filesup-asc.exe", "size": 8123456, "blake3": "<hex>" },
+//       { "path": "resources/app.js", "size": 40211, "blake3": "<hex>" }
+//     ]
+//   }
+//
+// - apply_staged_update verifies the extracted folder against it before the
+//   folder becomes current, so a broken bundle never gets switched to.
+// - verify_installed_version re-runs the same check later to detect
+//   corruption (disk errors, AV quarantine, manual edits).
+// - Delta bundles ship the new manifest.json as a regular "replace" entry.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use super::delta::safe_relative;
+use super::version_fs::version_dir;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BundleManifest {
+    pub version: String,
+    /// Oldest launcher able to start this version; None = any.
+    pub min_launcher_version: Option<String>,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestFile {
+    /// Relative, '/'-separated.
+    pub path: String,
+    pub size: u64,
+    pub blake3: String,
+}
+
+/// Outcome of checking a version folder against its manifest.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    pub version: String,
+    pub manifest_found: bool,
+    pub ok: bool,
+    pub checked_files: usize,
+    pub missing: Vec<String>,
+    /// Size or hash differs from the manifest.
+    pub corrupted: Vec<String>,
+    /// Present on disk but not listed (reported only, not an error).
+    pub unexpected: Vec<String>,
+}
+
+/// Read manifest.json from a version folder; Ok(None) if there is none.
+pub fn read_bundle_manifest(dir: &Path) -> Result<Option<BundleManifest>> {
+    let path = dir.join(MANIFEST_FILE);
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    };
+    let manifest = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse {:?}", path))?;
+    Ok(Some(manifest))
+}
+
+/// Hash every listed file under `dir` and compare.
+pub fn verify_dir(dir: &Path, manifest: &BundleManifest) -> Result<VerifyReport> {
+    let mut report = VerifyReport {
+        version: manifest.version.clone(),
+        manifest_found: true,
+        ..Default::default()
+    };
+    let mut listed = HashSet::new();
+
+    for entry in &manifest.files {
+        let rel = safe_relative(&entry.path)?;
+        let path = dir.join(&rel);
+        listed.insert(rel);
+        report.checked_files += 1;
+
+        let meta = match fs::metadata(&path) {
+            Ok(m) if m.is_file() => m,
+            _ => {
+                report.missing.push(entry.path.clone());
+                continue;
+            }
+        };
+        if meta.len() != entry.size {
+            report.corrupted.push(entry.path.clone());
+            continue;
+        }
+        let actual = hash_file(&path)?;
+        if !actual.eq_ignore_ascii_case(&entry.blake3) {
+            report.corrupted.push(entry.path.clone());
+        }
+    }
+
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        if rel != Path::new(MANIFEST_FILE) && !listed.contains(rel) {
+            report
+                .unexpected
+                .push(rel.to_string_lossy().replace('\\', "/"));
+        }
+    }
+
+    report.ok = report.missing.is_empty() && report.corrupted.is_empty();
+    Ok(report)
+}
+
+/// Check a freshly extracted folder before it becomes current.
+/// Fails on a missing manifest, a version mismatch, a launcher that is too
+/// old, or any missing / corrupted file.
+pub fn verify_extracted(dir: &Path, expected: &Version, launcher: &Version) -> Result<()> {
+    let manifest = read_bundle_manifest(dir)?
+        .ok_or_else(|| anyhow!("Update bundle has no {}", MANIFEST_FILE))?;
+
+    let version = Version::parse(&manifest.version)
+        .context("Failed to parse manifest version as semver")?;
+    if version != *expected {
+        return Err(anyhow!(
+            "Bundle manifest is for version {}, expected {}",
+            manifest.version,
+            expected
+        ));
+    }
+
+    if let Some(min) = &manifest.min_launcher_version {
+        let min = Version::parse(min)
+            .context("Failed to parse min_launcher_version as semver")?;
+        if *launcher < min {
+            return Err(anyhow!(
+                "Version {} needs launcher {} or newer (installed: {}); reinstall FilesUP",
+                version,
+                min,
+                launcher
+            ));
+        }
+    }
+
+    let report = verify_dir(dir, &manifest)?;
+    if !report.ok {
+        return Err(anyhow!(
+            "Bundle verification failed: {} missing, {} corrupted (first: {})",
+            report.missing.len(),
+            report.corrupted.len(),
+            report
+                .missing
+                .iter()
+                .chain(report.corrupted.iter())
+                .next()
+                .map(String::as_str)
+                .unwrap_or("-")
+        ));
+    }
+    Ok(())
+}
+
+/// Re-check an installed versions/<version>/ folder.
+pub fn verify_installed_version(app: &AppHandle, version: &str) -> Result<VerifyReport> {
+    let ver = Version::parse(version).context("Failed to parse version as semver")?;
+    let dir = version_dir(app, &ver)?;
+    if !dir.is_dir() {
+        return Err(anyhow!("Version {} is not installed ({:?})", ver, dir));
+    }
+
+    match read_bundle_manifest(&dir)? {
+        Some(manifest) => verify_dir(&dir, &manifest),
+        None => Ok(VerifyReport {
+            version: ver.to_string(),
+            manifest_found: false,
+            ..Default::default()
+        }),
+    }
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut file, &mut hasher).with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
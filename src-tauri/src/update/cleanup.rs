@@ -0,0 +1,156 @@
+// src-tauri/src/update/cleanup.rs
+//
+// Garbage collection for the update subsystem.
+// Side-by-side versions and the TUF targets cache only ever grow, so this
+// removes what is no longer needed:
+//   - versions/<v>/ folders outside the retention set
+//   - leftover versions/.<v>_tmp/ folders from interrupted applies
+//   - cached bundles (targets-cache/**/app-<v>.zip and delta bundles
+//     app-<v>.from-<base>.delta.zip) outside the retention set
+//
+// Retention set = current + previous + the N newest installed versions,
+// plus anything newer than current: a downloaded bundle waiting to be
+// applied, or a version a pending handoff (launcher.rs) switches to.
+// Runs under the same lock as apply / rollback, so a .<v>_tmp/ folder
+// found here is never one being extracted right now.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use super::delta::parse_delta_name;
+use super::update_manager::VERSIONS_LOCK;
+use super::version_fs::{list_installed_versions, load_version_state, versions_root};
+use super::TufConfig;
+
+/// Default number of newest versions kept in addition to current/previous.
+pub const DEFAULT_KEEP_NEWEST: usize = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupResult {
+    pub kept_versions: Vec<String>,
+    pub removed_versions: Vec<String>,
+    pub removed_bundles: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+/// Delete stale version folders and cached bundles.
+///
+/// Failures on individual folders are logged and skipped, so one locked
+/// file doesn't prevent the rest of the cleanup.
+pub fn cleanup_old_versions(app: &AppHandle, keep_newest: usize) -> Result<CleanupResult> {
+    let _lock = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let state = load_version_state(app)?;
+    let installed = list_installed_versions(app)?;
+    // An unreadable current version keeps everything rather than guessing.
+    let current = Version::parse(&state.current).ok();
+    let staged = |v: &Version| current.as_ref().is_none_or(|c| v > c);
+
+    let mut keep: HashSet<String> = HashSet::new();
+    keep.insert(state.current.clone());
+    if let Some(prev) = &state.previous {
+        keep.insert(prev.clone());
+    }
+    // `installed` is sorted ascending; take from the end.
+    for (v, _) in installed.iter().rev().take(keep_newest) {
+        keep.insert(v.to_string());
+    }
+
+    let mut result = CleanupResult {
+        kept_versions: Vec::new(),
+        removed_versions: Vec::new(),
+        removed_bundles: Vec::new(),
+        bytes_freed: 0,
+    };
+
+    // ==== versions/<v>/ ====
+    for (v, path) in &installed {
+        let name = v.to_string();
+        if keep.contains(&name) || staged(v) {
+            result.kept_versions.push(name);
+            continue;
+        }
+        let size = dir_size(path);
+        match fs::remove_dir_all(path) {
+            Ok(()) => {
+                result.bytes_freed += size;
+                result.removed_versions.push(name);
+            }
+            Err(e) => tracing::warn!("[UpdateCleanup] Failed to remove {:?}: {e}", path),
+        }
+    }
+
+    // ==== versions/.<v>_tmp/ leftovers ====
+    let root = versions_root(app)?;
+    if let Ok(entries) = fs::read_dir(&root) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_tmp = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with('.') && n.ends_with("_tmp"))
+                .unwrap_or(false);
+            if is_tmp && path.is_dir() {
+                let size = dir_size(&path);
+                if fs::remove_dir_all(&path).is_ok() {
+                    result.bytes_freed += size;
+                }
+            }
+        }
+    }
+
+    // ==== targets-cache/**/app-<v>.zip ====
+    let cfg = TufConfig::default_tuf_config(app)?;
+    for entry in WalkDir::new(&cfg.targets_cache_dir).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let version = match bundle_version(entry.path()) {
+            Some(v) => v,
+            None => continue,
+        };
+        if keep.contains(&version.to_string()) || staged(&version) {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        match fs::remove_file(entry.path()) {
+            Ok(()) => {
+                result.bytes_freed += size;
+                result
+                    .removed_bundles
+                    .push(entry.path().to_string_lossy().to_string());
+            }
+            Err(e) => tracing::warn!("[UpdateCleanup] Failed to remove {:?}: {e}", entry.path()),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parse `<version>` out of a cached bundle name `app-<version>.zip`
+/// or `app-<version>.from-<base>.delta.zip`.
+fn bundle_version(path: &Path) -> Option<Version> {
+    let name = path.file_name()?.to_str()?;
+    if let Some((version, _base)) = parse_delta_name(name) {
+        return Some(version);
+    }
+    let ver = name.strip_prefix("app-")?.strip_suffix(".zip")?;
+    Version::parse(ver).ok()
+}
+
+/// Total size of regular files under `path` (best-effort).
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
@@ -0,0 +1,283 @@
+// src-tauri/src/update/delta.rs
+//
+// Delta (patch) bundles: only the files that changed between two versions.
+//
+// Target name, next to the full bundle:
+//   filesup/{channel}/{platform_id}/app-{version}.from-{base}.delta.zip
+//
+// ZIP layout:
+//   delta.json            manifest (see DeltaManifest)
+//   files/<path>          new content for every "replace" entry
+//
+//   {
+//     "base_version": "0.2.2",
+//     "target_version": "0.2.3",
+//     "base_files": [
+//       { "path": "filesup-asc.exe", "blake3": "<hex>" },
+//       { "path": "resources/app.js", "blake3": "<hex>" }
+//     ],
+//     "files": [
+//       { "action": "replace", "path": "resources/app.js", "blake3": "<hex>" },
+//       { "action": "delete",  "path": "resources/old.js" }
+//     ]
+//   }
+//
+// Applying = copy the base_files out of versions/<base>/ into the staging
+// folder, then run "files" over it. Each base file is hashed while it is
+// copied and must match base_files, so a base that was modified or
+// corrupted on disk fails before anything is patched; files in the base
+// folder that aren't listed are left behind. Every replaced file is hashed
+// after writing as well. Unix mode bits carry over: from the base file, or
+// from the ZIP entry for replaced files, as for a full bundle.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use semver::Version;
+use serde::Deserialize;
+use zip::read::ZipArchive;
+
+use super::UpdateChannel;
+
+const MANIFEST_NAME: &str = "delta.json";
+const FILES_PREFIX: &str = "files/";
+const DELTA_SUFFIX: &str = ".delta.zip";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeltaManifest {
+    pub base_version: String,
+    pub target_version: String,
+    /// Every file of the base version the delta was built from.
+    #[serde(default)]
+    pub base_files: Vec<BaseFile>,
+    pub files: Vec<DeltaEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BaseFile {
+    /// Relative, '/'-separated.
+    pub path: String,
+    pub blake3: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum DeltaEntry {
+    /// New or changed file; content lives at files/<path> in the ZIP.
+    Replace { path: String, blake3: String },
+    /// File that no longer exists in the target version.
+    Delete { path: String },
+}
+
+/// Target name of the delta from `base` to `version`.
+pub fn delta_target_name(
+    channel: UpdateChannel,
+    platform_id: &str,
+    base: &Version,
+    version: &Version,
+) -> String {
+    format!(
+        "filesup/{}/{}/app-{}.from-{}{}",
+        channel, platform_id, version, base, DELTA_SUFFIX
+    )
+}
+
+/// True for bundles named like delta_target_name().
+pub fn is_delta_bundle(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.ends_with(DELTA_SUFFIX))
+        .unwrap_or(false)
+}
+
+/// `(version, base)` out of "app-<version>.from-<base>.delta.zip".
+pub fn parse_delta_name(name: &str) -> Option<(Version, Version)> {
+    let rest = name.strip_prefix("app-")?.strip_suffix(DELTA_SUFFIX)?;
+    let (version, base) = rest.split_once(".from-")?;
+    Some((Version::parse(version).ok()?, Version::parse(base).ok()?))
+}
+
+/// Read delta.json from a delta bundle.
+pub fn read_manifest(bundle: &Path) -> Result<DeltaManifest> {
+    let file = File::open(bundle)
+        .with_context(|| format!("Failed to open delta bundle at {:?}", bundle))?;
+    let mut archive = ZipArchive::new(file).context("Failed to open delta ZIP archive")?;
+    let mut entry = archive
+        .by_name(MANIFEST_NAME)
+        .context("Delta bundle has no delta.json")?;
+    let mut data = String::new();
+    entry
+        .read_to_string(&mut data)
+        .context("Failed to read delta.json")?;
+    serde_json::from_str(&data).context("Failed to parse delta.json")
+}
+
+/// Build `out_dir` = the base files + changes from the delta bundle.
+/// `out_dir` must exist and be empty.
+pub fn apply_delta(
+    base_dir: &Path,
+    bundle: &Path,
+    out_dir: &Path,
+    manifest: &DeltaManifest,
+) -> Result<()> {
+    if manifest.base_files.is_empty() {
+        return Err(anyhow!(
+            "Delta bundle lists no base files to check against; download the full bundle"
+        ));
+    }
+    copy_base(base_dir, out_dir, &manifest.base_files)?;
+
+    let file = File::open(bundle)
+        .with_context(|| format!("Failed to open delta bundle at {:?}", bundle))?;
+    let mut archive = ZipArchive::new(file).context("Failed to open delta ZIP archive")?;
+
+    for entry in &manifest.files {
+        match entry {
+            DeltaEntry::Delete { path } => {
+                let target = out_dir.join(safe_relative(path)?);
+                match fs::remove_file(&target) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        return Err(e).with_context(|| format!("Failed to delete {:?}", target))
+                    }
+                }
+            }
+            DeltaEntry::Replace { path, blake3 } => {
+                let target = out_dir.join(safe_relative(path)?);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create dir {:?}", parent))?;
+                }
+
+                let mut src = archive
+                    .by_name(&format!("{}{}", FILES_PREFIX, path))
+                    .with_context(|| format!("Delta bundle is missing files/{}", path))?;
+                // A read-only base file can't be opened for writing; write
+                // a new one and give it the base file's mode.
+                let base_permissions = fs::metadata(&target).ok().map(|m| m.permissions());
+                if base_permissions.is_some() {
+                    fs::remove_file(&target)
+                        .with_context(|| format!("Failed to replace {:?}", target))?;
+                }
+                let mut out = File::create(&target)
+                    .with_context(|| format!("Failed to create {:?}", target))?;
+                let mut hasher = HashingWriter::new(&mut out);
+                io::copy(&mut src, &mut hasher)
+                    .with_context(|| format!("Failed to write {:?}", target))?;
+                // Mode from the ZIP entry, else the base file's.
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let permissions = src
+                        .unix_mode()
+                        .map(|mode| fs::Permissions::from_mode(mode & 0o777))
+                        .or(base_permissions);
+                    if let Some(permissions) = permissions {
+                        fs::set_permissions(&target, permissions)
+                            .with_context(|| format!("Failed to set permissions of {:?}", target))?;
+                    }
+                }
+                #[cfg(not(unix))]
+                let _ = base_permissions;
+
+                let actual = hasher.finalize();
+                if !actual.eq_ignore_ascii_case(blake3) {
+                    return Err(anyhow!(
+                        "Hash mismatch for {} after patching (expected {}, got {})",
+                        path,
+                        blake3,
+                        actual
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy the listed base files from `from` to `to`, checking each one's
+/// hash on the way.
+fn copy_base(from: &Path, to: &Path, files: &[BaseFile]) -> Result<()> {
+    for file in files {
+        let rel = safe_relative(&file.path)?;
+        let source = from.join(&rel);
+        let target = to.join(&rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create dir {:?}", parent))?;
+        }
+
+        let mut src = File::open(&source)
+            .with_context(|| format!("Base version is missing {}", file.path))?;
+        let mut out = File::create(&target)
+            .with_context(|| format!("Failed to create {:?}", target))?;
+        let mut hasher = HashingWriter::new(&mut out);
+        io::copy(&mut src, &mut hasher)
+            .with_context(|| format!("Failed to copy {:?} -> {:?}", source, target))?;
+        // Keep the mode bits (executables, scripts) like a full bundle does.
+        #[cfg(unix)]
+        {
+            let permissions = src
+                .metadata()
+                .with_context(|| format!("Failed to read {:?}", source))?
+                .permissions();
+            fs::set_permissions(&target, permissions)
+                .with_context(|| format!("Failed to set permissions of {:?}", target))?;
+        }
+
+        let actual = hasher.finalize();
+        if !actual.eq_ignore_ascii_case(&file.blake3) {
+            return Err(anyhow!(
+                "Base file {} differs from the one the delta was built against (expected {}, got {}); download the full bundle",
+                file.path,
+                file.blake3,
+                actual
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Manifest paths are relative, '/'-separated and may not leave the folder.
+pub fn safe_relative(path: &str) -> Result<PathBuf> {
+    let p = Path::new(path);
+    if path.is_empty() || p.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(anyhow!("Unsafe path in delta manifest: {}", path));
+    }
+    Ok(p.to_path_buf())
+}
+
+/// Writer that hashes everything passing through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: blake3::Hasher,
+}
+
+impl<W: io::Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: blake3::Hasher::new(),
+        }
+    }
+
+    fn finalize(&self) -> String {
+        self.hasher.finalize().to_hex().to_string()
+    }
+}
+
+impl<W: io::Write> io::Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
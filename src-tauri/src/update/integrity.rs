@@ -0,0 +1,138 @@
+// src-tauri/src/update/integrity.rs
+//
+// Startup self-check of the running version.
+//
+// A power loss while an update was being applied (or a disk error, an AV
+// quarantine) can leave versions/<current>/ half-written. Shortly after
+// launch the folder is re-verified against its manifest.json
+// (bundle_manifest.rs); on mismatch:
+//   - `fu:integrity_warning` (IntegrityWarning) is emitted
+//   - with `fallback_on_corruption` in update_settings.json, and a
+//     `previous` version that verifies cleanly, the app restarts into
+//     `previous` (launcher.rs) and records a "fallback" history entry
+//
+// Only runs when the process was started from versions/<current>/; a dev
+// build or the base install has no manifest to check against.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use semver::Version;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use super::bundle_manifest::{verify_installed_version, VerifyReport};
+use super::launcher::spawn_version;
+use super::update_history::{record_update_event, UpdateEventKind, UpdateHistoryEntry};
+use super::update_settings::load_update_settings;
+use super::version_fs::{load_version_state, version_dir};
+
+/// Let the window subscribe to events before a warning can be emitted.
+const CHECK_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityWarning {
+    pub version: String,
+    pub missing: Vec<String>,
+    pub corrupted: Vec<String>,
+    /// Version being restarted into; None when staying on this one.
+    pub fallback_version: Option<String>,
+    /// Why no fallback happened, when it was enabled.
+    pub fallback_error: Option<String>,
+}
+
+/// Verify the running version in the background (called once from setup).
+pub fn start_integrity_check(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(CHECK_DELAY).await;
+
+        let check_app = app.clone();
+        let report = match tauri::async_runtime::spawn_blocking(move || check_running(&check_app)).await {
+            Ok(Ok(Some(report))) if !report.ok => report,
+            Ok(Ok(_)) => return,
+            Ok(Err(e)) => {
+                tracing::warn!("[Integrity] Check failed: {e:#}");
+                return;
+            }
+            Err(e) => {
+                tracing::warn!("[Integrity] Check task failed: {e}");
+                return;
+            }
+        };
+        tracing::error!(
+            "[Integrity] Version {} is damaged: {} missing, {} corrupted",
+            report.version,
+            report.missing.len(),
+            report.corrupted.len()
+        );
+
+        let mut warning = IntegrityWarning {
+            version: report.version.clone(),
+            missing: report.missing,
+            corrupted: report.corrupted,
+            fallback_version: None,
+            fallback_error: None,
+        };
+        let fallback = load_update_settings(&app).map(|s| s.fallback_on_corruption).unwrap_or(false);
+        if !fallback {
+            let _ = app.emit("fu:integrity_warning", &warning);
+            return;
+        }
+
+        let res = fall_back(&app, &mut warning).await;
+        record_update_event(
+            &app,
+            UpdateHistoryEntry::from_result(UpdateEventKind::Fallback, &res)
+                .versions(Some(warning.version.clone()), warning.fallback_version.clone()),
+        );
+        match res {
+            Ok(()) => app.exit(0),
+            Err(e) => {
+                tracing::error!("[Integrity] Fallback failed: {e:#}");
+                warning.fallback_version = None;
+                warning.fallback_error = Some(format!("{e:#}"));
+                let _ = app.emit("fu:integrity_warning", &warning);
+            }
+        }
+    });
+}
+
+/// Report for versions/<current>/, or None when this process doesn't run
+/// from there or the folder has no manifest.
+fn check_running(app: &AppHandle) -> Result<Option<VerifyReport>> {
+    let state = load_version_state(app)?;
+    let Ok(current) = Version::parse(&state.current) else {
+        return Ok(None);
+    };
+    let dir = version_dir(app, &current)?;
+    let exe = std::env::current_exe()?;
+    if !exe.starts_with(&dir) {
+        return Ok(None);
+    }
+    let report = verify_installed_version(app, &state.current)?;
+    Ok(report.manifest_found.then_some(report))
+}
+
+/// Restart into `previous` if it is intact. The warning is emitted first,
+/// so the user learns why the app restarted.
+async fn fall_back(app: &AppHandle, warning: &mut IntegrityWarning) -> Result<()> {
+    let previous = load_version_state(app)?
+        .previous
+        .ok_or_else(|| anyhow!("No previous version to fall back to"))?;
+
+    let check_app = app.clone();
+    let check_version = previous.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || verify_installed_version(&check_app, &check_version))
+        .await
+        .map_err(|e| anyhow!("Verify task failed: {}", e))??;
+    if !report.manifest_found {
+        return Err(anyhow!("Previous version {} has no manifest to verify", previous));
+    }
+    if !report.ok {
+        return Err(anyhow!("Previous version {} is damaged too", previous));
+    }
+
+    warning.fallback_version = Some(previous.clone());
+    let _ = app.emit("fu:integrity_warning", &*warning);
+    spawn_version(app, &previous).await
+}
@@ -0,0 +1,235 @@
+// src-tauri/src/update/launcher.rs
+//
+// Restart into another installed version (versions/<version>/).
+//
+// Handshake through versions/handoff.json:
+//   1) old process writes { "state": "pending", "to_version": "0.2.3", ... }
+//      and spawns versions/0.2.3/<same exe name as ours> with
+//      single_instance::HANDOFF_ENV set, so it takes over the
+//      single-instance endpoint instead of forwarding to us
+//   2) new process calls complete_handoff() during setup and rewrites the
+//      file with "state": "ready" + its pid
+//   3) old process sees "ready" and exits; if the new one never reports
+//      (crash on start, missing libs), the old one kills it, keeps running
+//      and the command returns an error instead of leaving the user with
+//      no app (or two of them).
+//
+// version_state.json is changed under update_manager::VERSIONS_LOCK, like
+// apply / rollback, so cleanup never removes the version being switched to.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Context, Result};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use super::update_manager::VERSIONS_LOCK;
+use super::version_fs::{load_version_state, save_version_state, version_dir, versions_root};
+
+/// How long the new process gets to come up and acknowledge.
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(30);
+const HANDOFF_POLL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandoffState {
+    Pending,
+    Ready,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Handoff {
+    pub state: HandoffState,
+    pub from_version: String,
+    pub to_version: String,
+    pub from_pid: u32,
+    /// Set by the new process when it acknowledges.
+    pub to_pid: Option<u32>,
+    /// Seconds since UNIX_EPOCH when the handoff started.
+    pub created_at: u64,
+}
+
+fn handoff_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(versions_root(app)?.join("handoff.json"))
+}
+
+fn read_handoff(path: &Path) -> Option<Handoff> {
+    let data = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn write_handoff(path: &Path, handoff: &Handoff) -> Result<()> {
+    let data = serde_json::to_string_pretty(handoff).context("Failed to serialize handoff")?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, path).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(())
+}
+
+/// Start versions/<version>/ and wait until it acknowledges.
+/// Makes `version` current in version_state.json first, so the next cold
+/// start lands there too. Returns once the caller may exit.
+pub async fn spawn_version(app: &AppHandle, version: &str) -> Result<()> {
+    let ver = Version::parse(version).context("Failed to parse version as semver")?;
+    let dir = version_dir(app, &ver)?;
+    let (exe, original, switched) = {
+        let _lock = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        if !dir.is_dir() {
+            return Err(anyhow!("Version {} is not installed ({:?})", ver, dir));
+        }
+        let exe = find_executable(&dir)?;
+        ensure_executable(&exe)?;
+
+        let original = load_version_state(app)?;
+        let switched = (original.current != ver.to_string()).then(|| {
+            let mut state = original.clone();
+            state.previous = Some(original.current.clone());
+            state.current = ver.to_string();
+            state
+        });
+        if let Some(state) = &switched {
+            save_version_state(app, state)?;
+        }
+        (exe, original, switched)
+    };
+
+    let res = handoff(app, &ver, &dir, &exe, &original.current).await;
+    if let (Err(_), Some(switched)) = (&res, switched) {
+        // Still running the old version: keep it current, unless an apply
+        // or rollback changed the state meanwhile.
+        let _lock = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let unchanged = load_version_state(app)
+            .is_ok_and(|s| s.current == switched.current && s.previous == switched.previous);
+        if unchanged {
+            if let Err(e) = save_version_state(app, &original) {
+                tracing::error!("[Launcher] Failed to restore version state: {e:#}");
+            }
+        }
+    }
+    res
+}
+
+async fn handoff(
+    app: &AppHandle,
+    ver: &Version,
+    dir: &Path,
+    exe: &Path,
+    from_version: &str,
+) -> Result<()> {
+    let path = handoff_path(app)?;
+    write_handoff(
+        &path,
+        &Handoff {
+            state: HandoffState::Pending,
+            from_version: from_version.to_string(),
+            to_version: ver.to_string(),
+            from_pid: std::process::id(),
+            to_pid: None,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        },
+    )?;
+
+    let mut child = std::process::Command::new(exe)
+        .current_dir(dir)
+        .env(crate::single_instance::HANDOFF_ENV, "1")
+        .spawn()
+        .with_context(|| format!("Failed to start {:?}", exe))?;
+
+    let started = Instant::now();
+    while started.elapsed() < HANDOFF_TIMEOUT {
+        if let Some(h) = read_handoff(&path) {
+            if h.state == HandoffState::Ready {
+                return Ok(());
+            }
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            let _ = fs::remove_file(&path);
+            return Err(anyhow!("Version {} exited during startup ({})", ver, status));
+        }
+        tokio::time::sleep(HANDOFF_POLL).await;
+    }
+
+    // Don't leave it running next to us: it already owns the
+    // single-instance endpoint or is about to take it over.
+    if let Err(e) = child.kill() {
+        tracing::warn!("[Launcher] Failed to stop version {}: {e}", ver);
+    }
+    let _ = child.wait();
+    let _ = fs::remove_file(&path);
+    Err(anyhow!(
+        "Version {} did not confirm startup within {}s",
+        ver,
+        HANDOFF_TIMEOUT.as_secs()
+    ))
+}
+
+/// Called once during setup: acknowledge a pending handoff aimed at us.
+pub fn complete_handoff(app: &AppHandle) {
+    let Ok(path) = handoff_path(app) else {
+        return;
+    };
+    let Some(mut handoff) = read_handoff(&path) else {
+        return;
+    };
+    if handoff.state != HandoffState::Pending || handoff.from_pid == std::process::id() {
+        return;
+    }
+
+    let own = app.package_info().version.to_string();
+    if handoff.to_version != own {
+        tracing::warn!(
+            "[Launcher] Handoff expected version {}, this is {}",
+            handoff.to_version, own
+        );
+    }
+    handoff.state = HandoffState::Ready;
+    handoff.to_pid = Some(std::process::id());
+    if let Err(e) = write_handoff(&path, &handoff) {
+        tracing::warn!("[Launcher] Failed to acknowledge handoff: {e:#}");
+    }
+}
+
+/// The executable inside a version folder: same file name as the running
+/// one (also inside a macOS .app bundle).
+fn find_executable(dir: &Path) -> Result<PathBuf> {
+    let current = std::env::current_exe().context("Failed to locate current executable")?;
+    let name = current
+        .file_name()
+        .ok_or_else(|| anyhow!("Current executable has no file name"))?;
+
+    WalkDir::new(dir)
+        .max_depth(4)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_type().is_file() && e.file_name() == name)
+        .map(|e| e.into_path())
+        .ok_or_else(|| anyhow!("No {:?} found in {:?}", name, dir))
+}
+
+/// ZIPs built on Windows may carry no mode bits.
+#[cfg(unix)]
+fn ensure_executable(exe: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perms = fs::metadata(exe)
+        .with_context(|| format!("Failed to stat {:?}", exe))?
+        .permissions();
+    if perms.mode() & 0o111 == 0 {
+        perms.set_mode(perms.mode() | 0o755);
+        fs::set_permissions(exe, perms)
+            .with_context(|| format!("Failed to make {:?} executable", exe))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn ensure_executable(_exe: &Path) -> Result<()> {
+    Ok(())
+}
@@ -1,19 +1,48 @@
 // src-tauri/src/update/mod.rs
 //
 // High-level update module for FilesUP / EmptyBox.
-// Uses The Update Framework (TUF) via the `tough` crate to securely
-// discover and download signed update bundles.
+// Uses The Update Framework (TUF) to securely discover and download
+// signed update bundles (client in tuf_client.rs, verification in
+// tuf_metadata.rs; modelled on the `tough` crate).
 
 mod tuf_config;
 mod tuf_client;
+mod tuf_metadata;
+mod tuf_root;
 mod version_fs;
 mod update_manager;
+mod update_settings;
+mod cleanup;
+mod delta;
+mod bundle_manifest;
+mod update_history;
+mod launcher;
+mod update_scheduler;
+mod integrity;
 
-pub use tuf_config::TufConfig;
+pub use tuf_config::{
+    clear_repository_override, repository_override_enabled, set_repository_override, TufConfig,
+};
+pub use launcher::{complete_handoff, spawn_version};
+pub use integrity::start_integrity_check;
+pub use version_fs::{load_version_state, versions_root};
+pub use update_history::{load_update_history, UpdateHistoryEntry};
+pub use bundle_manifest::{verify_installed_version, VerifyReport};
+pub use tuf_client::{test_endpoints, EndpointTestResult};
+pub use tuf_root::{trust_info, TrustInfo};
+pub use cleanup::{cleanup_old_versions, CleanupResult, DEFAULT_KEEP_NEWEST};
+pub use update_settings::{
+    get_network_settings, get_update_channel, set_network_settings, set_update_channel,
+    NetworkSettings, UpdateChannel,
+};
+pub use update_scheduler::{
+    current_version, platform_id, start_update_scheduler, UpdateScheduleInfo, UpdateScheduler,
+};
 pub use update_manager::{
     check_for_updates,
     download_update_bundle,
     apply_staged_update,
+    rollback_update,
     UpdateCheckResult,
     DownloadResult,
     ApplyResult,
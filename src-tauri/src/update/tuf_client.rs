@@ -1,18 +1,66 @@
 // src-tauri/src/update/tuf_client.rs
 //
-// Thin wrapper around the `tough` TUF client. :contentReference[oaicite:3]{index=3}
+// TUF client. Same shape as the `tough` API it replaces (tough needs CMake
+// to build); signature / expiry / hash checks live in tuf_metadata.rs.
 // Responsibilities:
 //   - Load repository using trusted root.json + remote metadata URLs
 //   - Find latest update for a given platform
+//   - Find a delta bundle from the installed version (see delta.rs)
 //   - Save a signed target (ZIP bundle) into local cache
+//
+// All HTTP goes through one reqwest client built from TufConfig
+// (proxy, extra CA certificates, connect/read timeouts), so corporate
+// networks only need to be configured once in update_settings.json.
+//
+// Mirrors: load_repository walks cfg.mirrors in order and sticks with the
+// first one whose metadata verifies; later calls on the same Repository
+// (target downloads) use that mirror.
+//
+// Update workflow (TUF spec 5.x, without delegations):
+//   trusted root.json (cfg.root_path)
+//   -> N+1.root.json    rotation: signed by old AND new root keys, persisted
+//   -> timestamp.json   signed by timestamp keys, not older than cached
+//   -> snapshot.json    version/hashes from timestamp, signed by snapshot keys
+//   -> targets.json     version/hashes from snapshot, signed by targets keys
+//   -> target files     length + sha256/sha512 from targets.json
+// Verified metadata is cached in cfg.datastore_path; the cached versions
+// are the floor for the next update (rollback protection).
+//
+// The tests at the bottom run this against signed fixture repositories
+// (thresholds, expiry, rollback, hash / length, rotation, consistent
+// snapshots), since tough's own test suite no longer covers us.
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use semver::Version;
-// use tough::{Prefix, Repository, RepositoryLoader, TargetName};
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha512};
+use url::Url;
+
+use crate::io_throttle::IoThrottle;
 
-use super::TufConfig;
+use super::delta::{delta_target_name, is_delta_bundle};
+use super::tuf_config::TufMirror;
+use super::tuf_metadata::{
+    check_expiry, check_hashes, check_meta_file, decode, parse_signed, verify_metadata,
+    verify_root_update, verify_signatures, MetaFile, Role, Root, Snapshot, TargetFile, Targets,
+    Timestamp,
+};
+use super::tuf_root::persist_root;
+use super::{TufConfig, UpdateChannel};
+
+/// Upper bounds for metadata downloads (endless-data attacks).
+const MAX_TIMESTAMP_SIZE: u64 = 16 * 1024;
+const MAX_METADATA_SIZE: u64 = 10 * 1024 * 1024;
+const MAX_ROOT_SIZE: u64 = 512 * 1024;
+/// Stop rotating after this many new roots in one session (spec default).
+const MAX_ROOT_ROTATIONS: u64 = 32;
+
+/// Top-level TUF metadata that every mirror must serve; cheap to fetch.
+const PROBE_FILE: &str = "timestamp.json";
 
 /// Data about the latest update found in the TUF repo.
 #[derive(Debug, Clone)]
@@ -20,53 +68,1090 @@ pub struct UpdateDescriptor {
     /// Parsed semantic version from the target name.
     pub version: Version,
     /// Target name in the TUF repository, e.g.
-    /// "filesup/desktop-windows-x86_64/app-0.2.3.zip"
+    /// "filesup/stable/desktop-windows-x86_64/app-0.2.3.zip"
     pub target_name: String,
     /// Expected length from TUF metadata.
     #[allow(dead_code)]
     pub length: u64,
+    /// Set for delta bundles: the version the patch applies on top of.
+    pub base_version: Option<Version>,
 }
 
-// TODO: Implement with actual tough library when available
-pub type Repository = ();
+pub struct Repository {
+    /// Transport for metadata and targets.
+    client: reqwest::Client,
+    /// Mirror that answered during load_repository.
+    mirror: TufMirror,
+    /// Trusted root the metadata below was verified with.
+    root: Root,
+    /// Verified top-level targets (name -> length + hashes).
+    targets: HashMap<String, TargetFile>,
+}
+
+pub async fn load_repository(cfg: &TufConfig) -> Result<Repository> {
+    let client = build_http_client(cfg)?;
+    let trusted = load_trusted_root(&cfg.root_path)?;
+
+    let mut failures = Vec::new();
+    for mirror in &cfg.mirrors {
+        let res = match update_root(&client, cfg, mirror, trusted.clone()).await {
+            Ok(root) => update_metadata(&client, cfg, mirror, &root)
+                .await
+                .map(|targets| (root, targets)),
+            Err(e) => Err(e),
+        };
+        match res {
+            Ok((root, targets)) => {
+                return Ok(Repository {
+                    client,
+                    mirror: mirror.clone(),
+                    root,
+                    targets: targets.targets,
+                });
+            }
+            Err(e) => {
+                tracing::warn!("[Update] Mirror {} unavailable: {e:#}", mirror.metadata_base_url);
+                failures.push(format!("{}: {:#}", mirror.metadata_base_url, e));
+            }
+        }
+    }
 
-pub async fn load_repository(_cfg: &TufConfig) -> Result<Repository> {
-    // Stub implementation
+    Err(anyhow!(
+        "No update server with valid metadata ({})",
+        if failures.is_empty() {
+            "no mirrors configured".to_string()
+        } else {
+            failures.join("; ")
+        }
+    ))
+}
+
+/// Read root.json and check it is signed by its own root keys.
+/// Expiry is checked after rotation, since a newer root may fix it.
+fn load_trusted_root(path: &Path) -> Result<Root> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Trusted TUF root not found at {:?}", path))?;
+    let raw = parse_signed(&bytes)?;
+    let root: Root = decode(&raw)?;
+    verify_signatures(&raw, &root, Root::TYPE).context("Trusted root.json failed verification")?;
+    Ok(root)
+}
+
+/// Follow root rotations: fetch N+1.root.json until the mirror has none,
+/// persisting every accepted root. If timestamp/snapshot keys changed,
+/// cached timestamp/snapshot are dropped (their rollback floor was
+/// signed by keys that are no longer trusted).
+async fn update_root(
+    client: &reqwest::Client,
+    cfg: &TufConfig,
+    mirror: &TufMirror,
+    mut root: Root,
+) -> Result<Root> {
+    let original = root.clone();
+
+    for _ in 0..MAX_ROOT_ROTATIONS {
+        let name = format!("{}.root.json", root.version + 1);
+        let url = mirror.metadata_base_url.join(&name)?;
+        let Some(bytes) = fetch_optional(client, &url, MAX_ROOT_SIZE).await? else {
+            break;
+        };
+        root = verify_root_update(&parse_signed(&bytes)?, &root)?;
+        persist_root(&cfg.root_path, &bytes)?;
+        tracing::info!("[TUF] Rotated trusted root to version {}", root.version);
+    }
+
+    check_expiry(Root::TYPE, &root.expires)?;
+
+    if root.version != original.version {
+        for (role, file) in [("timestamp", "timestamp.json"), ("snapshot", "snapshot.json")] {
+            if role_keyids(&original, role) != role_keyids(&root, role) {
+                let _ = std::fs::remove_file(cfg.datastore_path.join(file));
+            }
+        }
+    }
+    Ok(root)
+}
+
+fn role_keyids(root: &Root, role: &str) -> Vec<String> {
+    let mut ids = root
+        .roles
+        .get(role)
+        .map(|r| r.keyids.clone())
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
+/// timestamp -> snapshot -> targets from one mirror, verified against
+/// `root` and the cached versions; caches the new metadata on success.
+async fn update_metadata(
+    client: &reqwest::Client,
+    cfg: &TufConfig,
+    mirror: &TufMirror,
+    root: &Root,
+) -> Result<Targets> {
+    let base = &mirror.metadata_base_url;
+
+    let ts_bytes = fetch(client, &base.join("timestamp.json")?, MAX_TIMESTAMP_SIZE).await?;
+    let timestamp: Timestamp = verify_metadata(&parse_signed(&ts_bytes)?, root)?;
+    check_not_older(cfg, "timestamp.json", &timestamp)?;
+
+    let snap_meta = meta_entry(&timestamp.meta, "snapshot.json")?;
+    let snap_bytes = fetch_meta(client, base, root, "snapshot.json", snap_meta).await?;
+    let snapshot: Snapshot = verify_metadata(&parse_signed(&snap_bytes)?, root)?;
+    check_version("snapshot.json", snapshot.version, snap_meta)?;
+    check_not_older(cfg, "snapshot.json", &snapshot)?;
+
+    let targets_meta = meta_entry(&snapshot.meta, "targets.json")?;
+    let targets_bytes = fetch_meta(client, base, root, "targets.json", targets_meta).await?;
+    let targets: Targets = verify_metadata(&parse_signed(&targets_bytes)?, root)?;
+    check_version("targets.json", targets.version, targets_meta)?;
+    check_not_older(cfg, "targets.json", &targets)?;
+
+    for (name, bytes) in [
+        ("timestamp.json", &ts_bytes),
+        ("snapshot.json", &snap_bytes),
+        ("targets.json", &targets_bytes),
+    ] {
+        write_cached(&cfg.datastore_path, name, bytes)?;
+    }
+    Ok(targets)
+}
+
+fn meta_entry<'a>(meta: &'a HashMap<String, MetaFile>, name: &str) -> Result<&'a MetaFile> {
+    meta.get(name)
+        .ok_or_else(|| anyhow!("TUF metadata does not list {}", name))
+}
+
+/// Download snapshot/targets metadata ("<version>.<name>" with consistent
+/// snapshots) and check it against the length/hashes listed for it.
+async fn fetch_meta(
+    client: &reqwest::Client,
+    base: &Url,
+    root: &Root,
+    name: &str,
+    expected: &MetaFile,
+) -> Result<Vec<u8>> {
+    let file = if root.consistent_snapshot {
+        format!("{}.{}", expected.version, name)
+    } else {
+        name.to_string()
+    };
+    let limit = expected.length.unwrap_or(MAX_METADATA_SIZE);
+    let bytes = fetch(client, &base.join(&file)?, limit).await?;
+    check_meta_file(name, &bytes, expected)?;
+    Ok(bytes)
+}
+
+fn check_version(name: &str, actual: u64, expected: &MetaFile) -> Result<()> {
+    if actual != expected.version {
+        return Err(anyhow!(
+            "{} has version {} (expected {})",
+            name,
+            actual,
+            expected.version
+        ));
+    }
     Ok(())
 }
 
-/// Find the latest update target for a given platform.
+/// Rollback protection: never accept metadata older than what we had.
+fn check_not_older<T: Role>(cfg: &TufConfig, name: &str, new: &T) -> Result<()> {
+    let path = cfg.datastore_path.join(name);
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Ok(());
+    };
+    let cached = match parse_signed(&bytes).and_then(|raw| decode::<T>(&raw)) {
+        Ok(cached) => cached,
+        Err(e) => {
+            tracing::warn!("[TUF] Ignoring unreadable cached {}: {e:#}", name);
+            return Ok(());
+        }
+    };
+    if new.version() < cached.version() {
+        return Err(anyhow!(
+            "{} version {} is older than trusted version {} (rollback attack?)",
+            name,
+            new.version(),
+            cached.version()
+        ));
+    }
+    Ok(())
+}
+
+fn write_cached(dir: &Path, name: &str, bytes: &[u8]) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let path = dir.join(name);
+    let tmp = dir.join(format!("{}.tmp", name));
+    std::fs::write(&tmp, bytes).with_context(|| format!("Failed to write {:?}", tmp))?;
+    std::fs::rename(&tmp, &path).with_context(|| format!("Failed to write {:?}", path))?;
+    Ok(())
+}
+
+/// Like fetch(), but Ok(None) when the file does not exist
+/// (404, or 403 as S3-style hosts answer for missing keys).
+async fn fetch_optional(client: &reqwest::Client, url: &Url, limit: u64) -> Result<Option<Vec<u8>>> {
+    match fetch(client, url, limit).await {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) => {
+            let status = e
+                .chain()
+                .find_map(|c| c.downcast_ref::<reqwest::Error>())
+                .and_then(|e| e.status());
+            match status {
+                Some(reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::FORBIDDEN) => Ok(None),
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+/// GET `url` into memory, failing once more than `limit` bytes arrive.
+async fn fetch(client: &reqwest::Client, url: &Url, limit: u64) -> Result<Vec<u8>> {
+    let mut resp = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to download {}", url))?;
+    let mut out = Vec::new();
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .with_context(|| format!("Download of {} interrupted", url))?
+    {
+        if (out.len() + chunk.len()) as u64 > limit {
+            return Err(anyhow!("{} is larger than {} bytes", url, limit));
+        }
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
+
+/// Result of probing one mirror, for the connectivity diagnostics page.
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointTestResult {
+    pub metadata_url: String,
+    pub targets_url: String,
+    pub ok: bool,
+    /// Time until the probe response was fully read.
+    pub latency_ms: Option<u64>,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// Probe every configured mirror (in order) and report latency / errors.
+pub async fn test_endpoints(cfg: &TufConfig) -> Result<Vec<EndpointTestResult>> {
+    let client = build_http_client(cfg)?;
+    let mut results = Vec::with_capacity(cfg.mirrors.len());
+
+    for mirror in &cfg.mirrors {
+        let started = Instant::now();
+        let res = probe(&client, mirror).await;
+        let elapsed = started.elapsed().as_millis() as u64;
+
+        let mut result = EndpointTestResult {
+            metadata_url: mirror.metadata_base_url.to_string(),
+            targets_url: mirror.targets_base_url.to_string(),
+            ok: false,
+            latency_ms: None,
+            status: None,
+            error: None,
+        };
+        match res {
+            Ok(status) => {
+                result.ok = true;
+                result.latency_ms = Some(elapsed);
+                result.status = Some(status);
+            }
+            Err(e) => {
+                result.status = e
+                    .downcast_ref::<reqwest::Error>()
+                    .and_then(|e| e.status())
+                    .map(|s| s.as_u16());
+                result.error = Some(format!("{:#}", e));
+            }
+        }
+        results.push(result);
+    }
+
+    Ok(results)
+}
+
+/// GET <metadata>/timestamp.json; Ok(status) on a 2xx response.
+async fn probe(client: &reqwest::Client, mirror: &TufMirror) -> Result<u16> {
+    let url = mirror
+        .metadata_base_url
+        .join(PROBE_FILE)
+        .context("Invalid metadata URL")?;
+    let resp = client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(anyhow::Error::from)?;
+    let status = resp.status().as_u16();
+    resp.bytes()
+        .await
+        .with_context(|| format!("Failed to read {}", url))?;
+    Ok(status)
+}
+
+/// HTTP client honoring the proxy / CA / timeout settings of `cfg`.
+pub fn build_http_client(cfg: &TufConfig) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(concat!("FilesUP-updater/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(cfg.connect_timeout)
+        .read_timeout(cfg.read_timeout);
+
+    if let Some(proxy) = &cfg.proxy_url {
+        let proxy = reqwest::Proxy::all(proxy.as_str())
+            .with_context(|| format!("Invalid proxy URL {}", proxy))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_path) = &cfg.ca_bundle_path {
+        let pem = std::fs::read(ca_path)
+            .with_context(|| format!("Failed to read CA bundle {:?}", ca_path))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Failed to parse CA bundle {:?}", ca_path))?;
+        if certs.is_empty() {
+            return Err(anyhow!("CA bundle {:?} contains no certificates", ca_path));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder.build().context("Failed to build HTTP client for updates")
+}
+
+/// Find the latest update target for a given channel + platform.
 ///
 /// Convention:
-///   target name = "filesup/{platform_id}/app-{version}.zip"
+///   target name = "filesup/{channel}/{platform_id}/app-{version}.zip"
 ///   where {version} is a semver string like "0.2.3".
 ///
+/// Example channel: "stable", "beta", "nightly"
 /// Example platform_id:
 ///   - "desktop-windows-x86_64"
 ///   - "desktop-macos-aarch64"
+///
+/// Delta bundles and names that don't parse as semver are ignored.
 pub fn find_latest_update_for_platform(
-    _repo: &Repository,
-    _platform_id: &str,
+    repo: &Repository,
+    channel: UpdateChannel,
+    platform_id: &str,
+) -> Result<Option<UpdateDescriptor>> {
+    let prefix = format!("filesup/{}/{}/app-", channel, platform_id);
+
+    let latest = repo
+        .targets
+        .iter()
+        .filter(|(name, _)| !is_delta_bundle(Path::new(name.as_str())))
+        .filter_map(|(name, target)| {
+            let version = name.strip_prefix(&prefix)?.strip_suffix(".zip")?;
+            let version = Version::parse(version).ok()?;
+            Some((version, name, target))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(latest.map(|(version, name, target)| UpdateDescriptor {
+        version,
+        target_name: name.clone(),
+        length: target.length,
+        base_version: None,
+    }))
+}
+
+/// Find a delta bundle that turns `base` into `version`.
+///
+/// Convention (see delta.rs):
+///   target name = "filesup/{channel}/{platform_id}/app-{version}.from-{base}.delta.zip"
+///
+/// Returns None if the repository has no such target; callers then
+/// download the full bundle instead.
+pub fn find_delta_for_platform(
+    repo: &Repository,
+    channel: UpdateChannel,
+    platform_id: &str,
+    base: &Version,
+    version: &Version,
 ) -> Result<Option<UpdateDescriptor>> {
-    // Stub: return None (no update available)
-    Ok(None)
+    let target_name = delta_target_name(channel, platform_id, base, version);
+    Ok(repo.targets.get(&target_name).map(|target| UpdateDescriptor {
+        version: version.clone(),
+        length: target.length,
+        target_name,
+        base_version: Some(base.clone()),
+    }))
 }
 
 /// Save a target (update ZIP) into local cache directory.
 ///
 /// Returns the full path to the downloaded bundle.
 /// TUF verifies length and hashes for you before writing.
+/// `throttle`: the global IO throttle, paced per received chunk.
 pub async fn save_target_to_cache(
-    _repo: &Repository,
+    repo: &Repository,
     cfg: &TufConfig,
     descriptor: &UpdateDescriptor,
+    throttle: Option<&IoThrottle>,
 ) -> Result<PathBuf> {
     use tokio::fs;
+    use tokio::io::AsyncWriteExt;
+
+    let name = &descriptor.target_name;
+    let target = repo
+        .targets
+        .get(name)
+        .ok_or_else(|| anyhow!("Target {} is not listed in targets.json", name))?;
+
+    let bundle_path = cfg.targets_cache_dir.join(name);
+    if let Some(parent) = bundle_path.parent() {
+        fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("Failed to create targets cache dir {:?}", parent))?;
+    }
+
+    // Already downloaded (e.g. by the background pre-download)?
+    if cached_target_matches(&bundle_path, target).await {
+        return Ok(bundle_path);
+    }
+
+    // Write next to the final path and rename, so an interrupted download
+    // never leaves a truncated bundle behind. A part left by an earlier
+    // attempt (lost connection, crash) is continued with a range request
+    // when the server supports it.
+    let tmp_path = bundle_path.with_extension("part");
+    let partial = match fs::metadata(&tmp_path).await {
+        Ok(meta) if meta.len() > 0 && meta.len() < target.length => meta.len(),
+        _ => 0,
+    };
 
-    fs::create_dir_all(&cfg.targets_cache_dir)
+    let url = target_url(repo, name, target)?;
+    let mut request = repo.client.get(url.clone());
+    if partial > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", partial));
+    }
+    let mut resp = request
+        .send()
         .await
-        .with_context(|| format!("Failed to create targets cache dir {:?}", cfg.targets_cache_dir))?;
+        .and_then(|r| r.error_for_status())
+        .with_context(|| format!("Failed to download {}", url))?;
 
-    let bundle_path = cfg.targets_cache_dir.join(&descriptor.target_name);
+    let continues = partial > 0
+        && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT
+        && resp
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|range| range.starts_with(&format!("bytes {}-", partial)));
+    let existing = if continues { hash_file(&tmp_path).await } else { None };
+    let (mut out, mut hasher) = match existing {
+        Some(hasher) => {
+            let out = fs::OpenOptions::new()
+                .append(true)
+                .open(&tmp_path)
+                .await
+                .with_context(|| format!("Failed to open {:?}", tmp_path))?;
+            (out, hasher)
+        }
+        None if partial > 0 && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT => {
+            // A range we can't use; the next attempt starts over.
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(anyhow!("Server sent an unexpected range for {}", name));
+        }
+        None => {
+            let out = fs::File::create(&tmp_path)
+                .await
+                .with_context(|| format!("Failed to create {:?}", tmp_path))?;
+            (out, TargetHasher::default())
+        }
+    };
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .with_context(|| format!("Download of {} interrupted", url))?
+    {
+        hasher.update(&chunk);
+        if hasher.len > target.length {
+            let _ = fs::remove_file(&tmp_path).await;
+            return Err(anyhow!("Target {} is larger than expected", name));
+        }
+        out.write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write {:?}", tmp_path))?;
+        if let Some(throttle) = throttle {
+            throttle.wait_async(chunk.len() as u64).await;
+        }
+    }
+    out.flush().await.ok();
+    drop(out);
+
+    if let Err(e) = hasher.verify(name, target) {
+        let _ = fs::remove_file(&tmp_path).await;
+        return Err(e);
+    }
+
+    fs::rename(&tmp_path, &bundle_path)
+        .await
+        .with_context(|| format!("Failed to move download to {:?}", bundle_path))?;
     Ok(bundle_path)
 }
+
+/// With consistent snapshots, targets are served as
+/// "<dir>/<sha256>.<file name>" so a file never changes under its URL.
+fn target_url(repo: &Repository, name: &str, target: &TargetFile) -> Result<Url> {
+    let path = match target.hashes.get("sha256").filter(|_| repo.root.consistent_snapshot) {
+        Some(hash) => match name.rsplit_once('/') {
+            Some((dir, file)) => format!("{}/{}.{}", dir, hash, file),
+            None => format!("{}.{}", hash, name),
+        },
+        None => name.to_string(),
+    };
+    repo.mirror
+        .targets_base_url
+        .join(&path)
+        .with_context(|| format!("Invalid target name {}", name))
+}
+
+async fn cached_target_matches(path: &Path, target: &TargetFile) -> bool {
+    hash_file(path)
+        .await
+        .is_some_and(|hasher| hasher.verify("cached target", target).is_ok())
+}
+
+/// TargetHasher fed with a whole file; None if it can't be read.
+async fn hash_file(path: &Path) -> Option<TargetHasher> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = tokio::fs::File::open(path).await.ok()?;
+    let mut hasher = TargetHasher::default();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        match file.read(&mut buf).await {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(_) => return None,
+        }
+    }
+    Some(hasher)
+}
+
+/// Length + sha256/sha512 of a target while it streams in.
+#[derive(Default)]
+struct TargetHasher {
+    len: u64,
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+impl TargetHasher {
+    fn update(&mut self, chunk: &[u8]) {
+        self.len += chunk.len() as u64;
+        self.sha256.update(chunk);
+        self.sha512.update(chunk);
+    }
+
+    fn verify(self, name: &str, target: &TargetFile) -> Result<()> {
+        if self.len != target.length {
+            return Err(anyhow!(
+                "Target {} has length {} (expected {})",
+                name,
+                self.len,
+                target.length
+            ));
+        }
+        let sha256 = hex::encode(self.sha256.finalize());
+        let sha512 = hex::encode(self.sha512.finalize());
+        check_hashes(name, &target.hashes, |algo| match algo {
+            "sha256" => Some(sha256.clone()),
+            "sha512" => Some(sha512.clone()),
+            _ => None,
+        })
+    }
+}
+
+// Fixture repositories: keys are generated per test, metadata is signed
+// here and served from a temp folder by a minimal HTTP server on loopback.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::update::UpdateChannel;
+    use chrono::{SecondsFormat, Utc};
+    use olpc_cjson::CanonicalFormatter;
+    use ring::rand::{SecureRandom, SystemRandom};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const PLATFORM: &str = "desktop-linux-x86_64";
+
+    struct TestKey {
+        pkcs8: Vec<u8>,
+        pair: Ed25519KeyPair,
+        id: String,
+    }
+
+    impl TestKey {
+        fn new() -> Self {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap().as_ref().to_vec();
+            let pair = Ed25519KeyPair::from_pkcs8(&pkcs8).unwrap();
+            let id = hex::encode(Sha256::digest(pair.public_key().as_ref()));
+            TestKey { pkcs8, pair, id }
+        }
+
+        /// The same key under another keyid.
+        fn alias(&self) -> Self {
+            TestKey {
+                pkcs8: self.pkcs8.clone(),
+                pair: Ed25519KeyPair::from_pkcs8(&self.pkcs8).unwrap(),
+                id: format!("{}-alias", self.id),
+            }
+        }
+
+        fn json(&self) -> Value {
+            json!({
+                "keytype": "ed25519",
+                "scheme": "ed25519",
+                "keyval": { "public": hex::encode(self.pair.public_key().as_ref()) }
+            })
+        }
+    }
+
+    /// Signing keys of one root version.
+    struct Keys {
+        root: TestKey,
+        timestamp: TestKey,
+        snapshot: TestKey,
+        targets: Vec<TestKey>,
+        targets_threshold: u64,
+    }
+
+    impl Keys {
+        fn new() -> Self {
+            Keys {
+                root: TestKey::new(),
+                timestamp: TestKey::new(),
+                snapshot: TestKey::new(),
+                targets: vec![TestKey::new()],
+                targets_threshold: 1,
+            }
+        }
+
+        fn root(&self, version: u64, consistent_snapshot: bool, expires: &str) -> Value {
+            let mut keys = serde_json::Map::new();
+            for key in [&self.root, &self.timestamp, &self.snapshot].into_iter().chain(&self.targets) {
+                keys.insert(key.id.clone(), key.json());
+            }
+            let role = |ids: Vec<&String>, threshold: u64| json!({ "keyids": ids, "threshold": threshold });
+            json!({
+                "_type": "root",
+                "spec_version": "1.0.0",
+                "version": version,
+                "expires": expires,
+                "consistent_snapshot": consistent_snapshot,
+                "keys": keys,
+                "roles": {
+                    "root": role(vec![&self.root.id], 1),
+                    "timestamp": role(vec![&self.timestamp.id], 1),
+                    "snapshot": role(vec![&self.snapshot.id], 1),
+                    "targets": role(self.targets.iter().map(|k| &k.id).collect(), self.targets_threshold),
+                }
+            })
+        }
+    }
+
+    fn sign(signed: &Value, keys: &[&TestKey]) -> Vec<u8> {
+        let mut message = Vec::new();
+        signed
+            .serialize(&mut serde_json::Serializer::with_formatter(&mut message, CanonicalFormatter::new()))
+            .unwrap();
+        let signatures: Vec<Value> = keys
+            .iter()
+            .map(|k| json!({ "keyid": k.id, "sig": hex::encode(k.pair.sign(&message).as_ref()) }))
+            .collect();
+        serde_json::to_vec(&json!({ "signed": signed, "signatures": signatures })).unwrap()
+    }
+
+    fn expires_in(days: i64) -> String {
+        (Utc::now() + chrono::Duration::days(days)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    fn meta_entry(version: u64, bytes: &[u8]) -> Value {
+        json!({
+            "version": version,
+            "length": bytes.len(),
+            "hashes": { "sha256": hex::encode(Sha256::digest(bytes)) }
+        })
+    }
+
+    fn target_name(version: &str) -> String {
+        format!("filesup/stable/{}/app-{}.zip", PLATFORM, version)
+    }
+
+    struct Fixture {
+        dir: PathBuf,
+        cfg: TufConfig,
+        keys: Keys,
+        consistent: bool,
+    }
+
+    impl Fixture {
+        /// Client trusting root v1 of a fresh repository.
+        async fn new(keys: Keys, consistent: bool) -> Self {
+            let mut token = [0u8; 8];
+            SystemRandom::new().fill(&mut token).unwrap();
+            let dir = std::env::temp_dir().join(format!("filesup-tuf-test-{}", hex::encode(token)));
+            std::fs::create_dir_all(dir.join("repo/metadata")).unwrap();
+            std::fs::create_dir_all(dir.join("repo/targets")).unwrap();
+            let base = format!("http://127.0.0.1:{}/", serve(dir.join("repo")).await);
+            let cfg = TufConfig {
+                mirrors: vec![TufMirror {
+                    metadata_base_url: Url::parse(&format!("{}metadata/", base)).unwrap(),
+                    targets_base_url: Url::parse(&format!("{}targets/", base)).unwrap(),
+                }],
+                root_path: dir.join("client/root.json"),
+                datastore_path: dir.join("client/datastore"),
+                targets_cache_dir: dir.join("client/targets"),
+                channel: UpdateChannel::Stable,
+                proxy_url: None,
+                ca_bundle_path: None,
+                connect_timeout: Duration::from_secs(5),
+                read_timeout: Duration::from_secs(5),
+            };
+            let fixture = Fixture {
+                dir,
+                cfg,
+                keys,
+                consistent,
+            };
+            let root = sign(&fixture.keys.root(1, consistent, &expires_in(365)), &[&fixture.keys.root]);
+            persist_root(&fixture.cfg.root_path, &root).unwrap();
+            fixture.write_meta("1.root.json", &root);
+            fixture
+        }
+
+        fn write_meta(&self, name: &str, bytes: &[u8]) {
+            std::fs::write(self.dir.join("repo/metadata").join(name), bytes).unwrap();
+        }
+
+        /// Path of `name` under repo/targets, as the client will ask for it.
+        fn target_path(&self, name: &str, bytes: &[u8]) -> PathBuf {
+            let name = if self.consistent {
+                let (dir, file) = name.rsplit_once('/').unwrap();
+                format!("{}/{}.{}", dir, hex::encode(Sha256::digest(bytes)), file)
+            } else {
+                name.to_string()
+            };
+            self.dir.join("repo/targets").join(name)
+        }
+
+        /// The file name snapshot / targets metadata is served under.
+        fn meta_name(&self, version: u64, name: &str) -> String {
+            if self.consistent {
+                format!("{}.{}", version, name)
+            } else {
+                name.to_string()
+            }
+        }
+
+        fn publish_targets(&self, version: u64, files: &[(&str, &[u8])], signers: &[&TestKey]) -> Vec<u8> {
+            let mut targets = serde_json::Map::new();
+            for (name, bytes) in files {
+                let path = self.target_path(name, bytes);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, bytes).unwrap();
+                targets.insert(
+                    name.to_string(),
+                    json!({ "length": bytes.len(), "hashes": { "sha256": hex::encode(Sha256::digest(bytes)) } }),
+                );
+            }
+            let signed = json!({
+                "_type": "targets",
+                "spec_version": "1.0.0",
+                "version": version,
+                "expires": expires_in(30),
+                "targets": targets,
+            });
+            let bytes = sign(&signed, signers);
+            self.write_meta(&self.meta_name(version, "targets.json"), &bytes);
+            bytes
+        }
+
+        fn publish_snapshot(&self, version: u64, targets_version: u64, targets: &[u8]) -> Vec<u8> {
+            let signed = json!({
+                "_type": "snapshot",
+                "spec_version": "1.0.0",
+                "version": version,
+                "expires": expires_in(30),
+                "meta": { "targets.json": meta_entry(targets_version, targets) },
+            });
+            let bytes = sign(&signed, &[&self.keys.snapshot]);
+            self.write_meta(&self.meta_name(version, "snapshot.json"), &bytes);
+            bytes
+        }
+
+        fn publish_timestamp(&self, version: u64, snapshot_version: u64, snapshot: &[u8], expires: &str) {
+            let signed = json!({
+                "_type": "timestamp",
+                "spec_version": "1.0.0",
+                "version": version,
+                "expires": expires,
+                "meta": { "snapshot.json": meta_entry(snapshot_version, snapshot) },
+            });
+            self.write_meta("timestamp.json", &sign(&signed, &[&self.keys.timestamp]));
+        }
+
+        /// targets, snapshot and timestamp, all at `version`, signed by
+        /// the current keys.
+        fn publish(&self, version: u64, files: &[(&str, &[u8])]) {
+            let signers: Vec<&TestKey> = self.keys.targets.iter().collect();
+            let targets = self.publish_targets(version, files, &signers);
+            let snapshot = self.publish_snapshot(version, version, &targets);
+            self.publish_timestamp(version, version, &snapshot, &expires_in(1));
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    /// GET-only HTTP/1.1 server for `dir`; returns the port.
+    async fn serve(dir: PathBuf) -> u16 {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let dir = dir.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match stream.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => request.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let request = String::from_utf8_lossy(&request);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/").trim_start_matches('/');
+                    let response = match std::fs::read(dir.join(path)) {
+                        Ok(body) => {
+                            let mut response =
+                                format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                                    .into_bytes();
+                            response.extend_from_slice(&body);
+                            response
+                        }
+                        Err(_) => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                    };
+                    let _ = stream.write_all(&response).await;
+                });
+            }
+        });
+        port
+    }
+
+    async fn load_error(cfg: &TufConfig) -> String {
+        match load_repository(cfg).await {
+            Ok(_) => panic!("repository loaded"),
+            Err(e) => format!("{:#}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_repository_and_downloads_target() {
+        let fixture = Fixture::new(Keys::new(), false).await;
+        let name = target_name("1.2.0");
+        fixture.publish(1, &[(&name, b"new app"), (&target_name("1.1.0"), b"old app")]);
+
+        let repo = load_repository(&fixture.cfg).await.unwrap();
+        let latest = find_latest_update_for_platform(&repo, UpdateChannel::Stable, PLATFORM)
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.target_name, name);
+        let path = save_target_to_cache(&repo, &fixture.cfg, &latest, None).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"new app");
+    }
+
+    #[tokio::test]
+    async fn targets_need_threshold_of_distinct_keys() {
+        let mut keys = Keys::new();
+        keys.targets.push(TestKey::new());
+        keys.targets_threshold = 2;
+        let fixture = Fixture::new(keys, false).await;
+        let files: &[(&str, &[u8])] = &[(&target_name("1.0.0"), b"app")];
+        let (first, second) = (&fixture.keys.targets[0], &fixture.keys.targets[1]);
+
+        let publish = |version: u64, signers: &[&TestKey]| {
+            let targets = fixture.publish_targets(version, files, signers);
+            let snapshot = fixture.publish_snapshot(version, version, &targets);
+            fixture.publish_timestamp(version, version, &snapshot, &expires_in(1));
+        };
+
+        publish(1, &[first]);
+        assert!(load_error(&fixture.cfg).await.contains("1 valid signature(s), 2 required"));
+
+        // The same key twice still counts once.
+        publish(1, &[first, first]);
+        assert!(load_error(&fixture.cfg).await.contains("1 valid signature(s), 2 required"));
+
+        // A key the root doesn't list for targets doesn't count either.
+        publish(1, &[first, &fixture.keys.snapshot]);
+        assert!(load_error(&fixture.cfg).await.contains("1 valid signature(s), 2 required"));
+
+        publish(1, &[first, second]);
+        load_repository(&fixture.cfg).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn one_key_under_two_keyids_counts_once() {
+        let mut keys = Keys::new();
+        let alias = keys.targets[0].alias();
+        keys.targets.push(alias);
+        keys.targets_threshold = 2;
+        let fixture = Fixture::new(keys, false).await;
+
+        fixture.publish(1, &[(&target_name("1.0.0"), b"app")]);
+        assert!(load_error(&fixture.cfg).await.contains("1 valid signature(s), 2 required"));
+    }
+
+    #[tokio::test]
+    async fn expired_metadata_is_rejected() {
+        let fixture = Fixture::new(Keys::new(), false).await;
+        let targets = fixture.publish_targets(1, &[], &[&fixture.keys.targets[0]]);
+        let snapshot = fixture.publish_snapshot(1, 1, &targets);
+        fixture.publish_timestamp(1, 1, &snapshot, &expires_in(-1));
+        assert!(load_error(&fixture.cfg).await.contains("timestamp metadata expired"));
+
+        // An expired trusted root fails even with fresh metadata.
+        fixture.publish(1, &[]);
+        let root = sign(&fixture.keys.root(1, false, &expires_in(-1)), &[&fixture.keys.root]);
+        persist_root(&fixture.cfg.root_path, &root).unwrap();
+        assert!(load_error(&fixture.cfg).await.contains("root metadata expired"));
+    }
+
+    #[tokio::test]
+    async fn older_metadata_than_cached_is_rejected() {
+        let fixture = Fixture::new(Keys::new(), false).await;
+        fixture.publish(2, &[]);
+        load_repository(&fixture.cfg).await.unwrap();
+
+        fixture.publish(1, &[]);
+        assert!(load_error(&fixture.cfg).await.contains("rollback"));
+
+        // Same versions again are fine.
+        fixture.publish(2, &[]);
+        load_repository(&fixture.cfg).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn snapshot_version_must_match_timestamp() {
+        let fixture = Fixture::new(Keys::new(), false).await;
+        let targets = fixture.publish_targets(1, &[], &[&fixture.keys.targets[0]]);
+        let snapshot = fixture.publish_snapshot(1, 1, &targets);
+        fixture.publish_timestamp(1, 2, &snapshot, &expires_in(1));
+        assert!(load_error(&fixture.cfg).await.contains("snapshot.json has version 1 (expected 2)"));
+    }
+
+    #[tokio::test]
+    async fn hash_and_length_mismatches_are_rejected() {
+        let fixture = Fixture::new(Keys::new(), false).await;
+        let targets = fixture.publish_targets(1, &[], &[&fixture.keys.targets[0]]);
+        let snapshot = fixture.publish_snapshot(1, 1, &targets);
+        fixture.publish_timestamp(1, 1, &snapshot, &expires_in(1));
+
+        // Same length, other content.
+        let mut tampered = snapshot.clone();
+        let last = tampered.len() - 2;
+        tampered[last] = b' ';
+        fixture.write_meta("snapshot.json", &tampered);
+        assert!(load_error(&fixture.cfg).await.contains("snapshot.json failed sha256 verification"));
+
+        let mut longer = snapshot.clone();
+        longer.push(b' ');
+        fixture.write_meta("snapshot.json", &longer);
+        assert!(load_error(&fixture.cfg).await.contains("larger than"));
+
+        // A target that changed after signing never reaches the cache.
+        let name = target_name("1.0.0");
+        fixture.publish(2, &[(&name, b"signed app")]);
+        let repo = load_repository(&fixture.cfg).await.unwrap();
+        let latest = find_latest_update_for_platform(&repo, UpdateChannel::Stable, PLATFORM)
+            .unwrap()
+            .unwrap();
+        std::fs::write(fixture.target_path(&name, b"signed app"), b"evil app!!").unwrap();
+        let err = save_target_to_cache(&repo, &fixture.cfg, &latest, None).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("failed sha256 verification"));
+        std::fs::write(fixture.target_path(&name, b"signed app"), b"signed app, longer").unwrap();
+        let err = save_target_to_cache(&repo, &fixture.cfg, &latest, None).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("larger than expected"));
+        assert!(!fixture.cfg.targets_cache_dir.join(&name).exists());
+    }
+
+    #[tokio::test]
+    async fn root_rotation_needs_old_and_new_keys() {
+        let mut fixture = Fixture::new(Keys::new(), false).await;
+        let old = std::mem::replace(&mut fixture.keys, Keys::new());
+        let new_root = fixture.keys.root(2, false, &expires_in(365));
+        fixture.publish(1, &[]);
+
+        // Only the new keys: not trusted.
+        fixture.write_meta("2.root.json", &sign(&new_root, &[&fixture.keys.root]));
+        assert!(load_error(&fixture.cfg).await.contains("not signed by the current root keys"));
+
+        // Only the old keys: the new root doesn't vouch for itself.
+        fixture.write_meta("2.root.json", &sign(&new_root, &[&old.root]));
+        assert!(load_error(&fixture.cfg).await.contains("not signed by its own root keys"));
+
+        // Skipping a version is refused.
+        let skipped = fixture.keys.root(3, false, &expires_in(365));
+        fixture.write_meta("2.root.json", &sign(&skipped, &[&old.root, &fixture.keys.root]));
+        assert!(load_error(&fixture.cfg).await.contains("expected 2"));
+
+        fixture.write_meta("2.root.json", &sign(&new_root, &[&old.root, &fixture.keys.root]));
+        let repo = load_repository(&fixture.cfg).await.unwrap();
+        assert_eq!(repo.root.version, 2);
+        // Persisted: the next run starts from version 2.
+        assert_eq!(load_trusted_root(&fixture.cfg.root_path).unwrap().version, 2);
+
+        // Metadata signed by the replaced timestamp key is refused now.
+        let targets = fixture.publish_targets(3, &[], &[&fixture.keys.targets[0]]);
+        let snapshot = fixture.publish_snapshot(3, 3, &targets);
+        let signed = json!({
+            "_type": "timestamp",
+            "spec_version": "1.0.0",
+            "version": 3,
+            "expires": expires_in(1),
+            "meta": { "snapshot.json": meta_entry(3, &snapshot) },
+        });
+        fixture.write_meta("timestamp.json", &sign(&signed, &[&old.timestamp]));
+        assert!(load_error(&fixture.cfg).await.contains("timestamp metadata has 0 valid signature(s)"));
+    }
+
+    #[tokio::test]
+    async fn consistent_snapshots_use_versioned_and_hashed_names() {
+        let fixture = Fixture::new(Keys::new(), true).await;
+        let name = target_name("2.0.0");
+        fixture.publish(4, &[(&name, b"consistent app")]);
+        let metadata = fixture.dir.join("repo/metadata");
+        assert!(metadata.join("4.snapshot.json").exists());
+        assert!(!metadata.join("snapshot.json").exists());
+
+        let repo = load_repository(&fixture.cfg).await.unwrap();
+        let latest = find_latest_update_for_platform(&repo, UpdateChannel::Stable, PLATFORM)
+            .unwrap()
+            .unwrap();
+        let path = save_target_to_cache(&repo, &fixture.cfg, &latest, None).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"consistent app");
+
+        // The plain names are not consulted.
+        std::fs::remove_file(metadata.join("4.targets.json")).unwrap();
+        std::fs::copy(metadata.join("4.snapshot.json"), metadata.join("snapshot.json")).unwrap();
+        assert!(load_error(&fixture.cfg).await.contains("4.targets.json"));
+    }
+}
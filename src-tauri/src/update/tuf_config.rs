@@ -9,25 +9,51 @@
 //   https://updates.filesup.app/metadata/
 //   https://updates.filesup.app/targets/
 //
+// Extra mirrors (same signed repo, other hosts) can be listed in
+// update_settings.json; they are tried after the primary endpoint.
+//
 // The repo itself is created & signed outside of the app
 // using `tuftool` (or any other TUF tooling). :contentReference[oaicite:2]{index=2}
 
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use tauri::AppHandle;
 use url::Url;
 
+use super::tuf_root::bootstrap_root;
+use super::update_settings::{
+    load_update_settings, save_update_settings, RepositoryOverride, UpdateChannel, UpdateSettings,
+};
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// One metadata/targets endpoint pair. All mirrors serve the same
+/// signed repository, so any of them can be used interchangeably.
 #[derive(Debug, Clone)]
-pub struct TufConfig {
-    #[allow(dead_code)]
+pub struct TufMirror {
     pub metadata_base_url: Url,
-    #[allow(dead_code)]
     pub targets_base_url: Url,
+}
+
+#[derive(Debug, Clone)]
+pub struct TufConfig {
+    /// Tried in order; the first reachable one is used (see load_repository).
+    pub mirrors: Vec<TufMirror>,
     pub root_path: PathBuf,
     pub datastore_path: PathBuf,
     pub targets_cache_dir: PathBuf,
+    /// Release channel used to resolve target names.
+    pub channel: UpdateChannel,
+    /// Explicit proxy; None = system env vars (HTTPS_PROXY, ...).
+    pub proxy_url: Option<Url>,
+    /// Extra CA certificates (PEM), added on top of the built-in roots.
+    pub ca_bundle_path: Option<PathBuf>,
+    pub connect_timeout: Duration,
+    pub read_timeout: Duration,
 }
 
 impl TufConfig {
@@ -45,6 +71,16 @@ impl TufConfig {
     }
 }
 
+/// Root of all TUF state: `<app config dir>/tuf/`.
+pub fn tuf_dir(app: &AppHandle) -> Result<PathBuf> {
+    use tauri::Manager;
+    let app_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| anyhow!("App config dir error: {}", e))?;
+    Ok(app_dir.join("tuf"))
+}
+
 /// Build a default TUF configuration for this app.
 ///
 /// This uses the OS-specific app config directory, for example:
@@ -54,17 +90,25 @@ impl TufConfig {
 ///
 /// Layout inside config dir:
 ///   tuf/
-///     root.json          (initial trusted root, updated via TUF root rotation)
+///     root.json          (initial trusted root embedded in the binary,
+///                         updated via TUF root rotation; see tuf_root.rs)
 ///     metadata-cache/    (cached metadata: timestamp, snapshot, targets)
 ///     targets-cache/     (downloaded target files, e.g. update bundles)
+///     update_settings.json (user-selected channel, see update_settings.rs)
+///     override/          (root + caches of a QA repository, see with_custom_urls)
+///
+/// A repository override from update_settings.json replaces the production
+/// endpoints when allowed (debug build or `allow_repository_override`).
 pub fn default_tuf_config(app: &AppHandle) -> Result<TufConfig> {
-    use tauri::Manager;
-    let app_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| anyhow!("App config dir error: {}", e))?;
+    let settings = load_update_settings(app)?;
+    if let Some(ov) = active_override(&settings) {
+        return with_custom_urls(app, &ov.metadata_url, &ov.targets_url);
+    }
+    production_config(app, settings)
+}
 
-    let tuf_dir = app_dir.join("tuf");
+fn production_config(app: &AppHandle, settings: UpdateSettings) -> Result<TufConfig> {
+    let tuf_dir = tuf_dir(app)?;
     let root_path = tuf_dir.join("root.json");
     let datastore_path = tuf_dir.join("metadata-cache");
     let targets_cache_dir = tuf_dir.join("targets-cache");
@@ -76,29 +120,148 @@ pub fn default_tuf_config(app: &AppHandle) -> Result<TufConfig> {
     let targets_base_url = Url::parse("https://updates.filesup.app/targets/")
         .context("Failed to parse TUF targets base URL")?;
 
-    let cfg = TufConfig {
+    let mut mirrors = vec![TufMirror {
         metadata_base_url,
         targets_base_url,
+    }];
+    for m in &settings.mirrors {
+        match parse_mirror(&m.metadata_url, &m.targets_url) {
+            Ok(mirror) => mirrors.push(mirror),
+            Err(e) => tracing::warn!("[Update] Ignoring invalid mirror in settings: {e:#}"),
+        }
+    }
+
+    let network = settings.network;
+    let proxy_url = match network.proxy_url.as_deref() {
+        Some(p) => Some(Url::parse(p).with_context(|| format!("Invalid proxy URL '{}'", p))?),
+        None => None,
+    };
+
+    let cfg = TufConfig {
+        mirrors,
         root_path,
         datastore_path,
         targets_cache_dir,
+        channel: settings.channel,
+        proxy_url,
+        ca_bundle_path: network.ca_bundle_path,
+        connect_timeout: network
+            .connect_timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+        read_timeout: network
+            .read_timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_READ_TIMEOUT),
     };
 
     cfg.ensure_dirs()?;
+    bootstrap_root(&cfg.root_path)?;
     Ok(cfg)
 }
 
-/// Helper used in tests or dev builds to override URLs.
-#[allow(dead_code)]
+/// Config for a QA/staging repository: its own URLs, and its own trusted
+/// root + caches under tuf/override/ so nothing mixes with production state.
 pub fn with_custom_urls(
     app: &AppHandle,
     metadata_url: &str,
     targets_url: &str,
 ) -> Result<TufConfig> {
-    let mut cfg = default_tuf_config(app)?;
-    cfg.metadata_base_url = Url::parse(metadata_url)
-        .context("Failed to parse custom metadata URL")?;
-    cfg.targets_base_url = Url::parse(targets_url)
-        .context("Failed to parse custom targets URL")?;
+    let mut cfg = production_config(app, load_update_settings(app)?)?;
+    let dir = override_dir(app)?;
+    cfg.mirrors = vec![parse_mirror(metadata_url, targets_url)?];
+    cfg.root_path = dir.join("root.json");
+    cfg.datastore_path = dir.join("metadata-cache");
+    cfg.targets_cache_dir = dir.join("targets-cache");
+    cfg.ensure_dirs()?;
     Ok(cfg)
 }
+
+fn override_dir(app: &AppHandle) -> Result<PathBuf> {
+    Ok(tuf_dir(app)?.join("override"))
+}
+
+/// Overrides are honored in debug builds, or when the settings file opts in.
+fn repository_override_allowed(settings: &UpdateSettings) -> bool {
+    cfg!(debug_assertions) || settings.allow_repository_override
+}
+
+pub fn repository_override_enabled(app: &AppHandle) -> bool {
+    load_update_settings(app)
+        .map(|s| repository_override_allowed(&s))
+        .unwrap_or(cfg!(debug_assertions))
+}
+
+fn active_override(settings: &UpdateSettings) -> Option<&RepositoryOverride> {
+    settings
+        .repository_override
+        .as_ref()
+        .filter(|_| repository_override_allowed(settings))
+}
+
+/// Point the updater at another TUF repository, trusting `root_json`.
+/// Cached metadata of a previous override is dropped, since it was signed
+/// under a different root.
+pub fn set_repository_override(
+    app: &AppHandle,
+    metadata_url: &str,
+    targets_url: &str,
+    root_json: &[u8],
+) -> Result<()> {
+    let mut settings = load_update_settings(app)?;
+    if !repository_override_allowed(&settings) {
+        return Err(anyhow!(
+            "Repository override is disabled (set allow_repository_override in update_settings.json)"
+        ));
+    }
+
+    parse_mirror(metadata_url, targets_url)?;
+    let root: serde_json::Value =
+        serde_json::from_slice(root_json).context("Trusted root is not valid JSON")?;
+    if root.pointer("/signed/_type").and_then(|t| t.as_str()) != Some("root") {
+        return Err(anyhow!("Trusted root is not a TUF root.json (signed._type != \"root\")"));
+    }
+
+    let dir = override_dir(app)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to clear old override state {:?}", dir))?;
+    }
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    let root_path = dir.join("root.json");
+    let tmp = dir.join("root.json.tmp");
+    fs::write(&tmp, root_json).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, &root_path).with_context(|| format!("Failed to write {:?}", root_path))?;
+
+    settings.repository_override = Some(RepositoryOverride {
+        metadata_url: metadata_url.to_string(),
+        targets_url: targets_url.to_string(),
+    });
+    save_update_settings(app, &settings)
+}
+
+/// Back to the production repository; override state is deleted.
+pub fn clear_repository_override(app: &AppHandle) -> Result<()> {
+    let mut settings = load_update_settings(app)?;
+    settings.repository_override = None;
+    save_update_settings(app, &settings)?;
+
+    let dir = override_dir(app)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove override state {:?}", dir))?;
+    }
+    Ok(())
+}
+
+/// Base URLs must end with '/', otherwise Url::join drops the last segment.
+fn parse_mirror(metadata_url: &str, targets_url: &str) -> Result<TufMirror> {
+    let base = |s: &str, what: &str| -> Result<Url> {
+        let s = if s.ends_with('/') { s.to_string() } else { format!("{}/", s) };
+        Url::parse(&s).with_context(|| format!("Failed to parse {} URL '{}'", what, s))
+    };
+    Ok(TufMirror {
+        metadata_base_url: base(metadata_url, "metadata")?,
+        targets_base_url: base(targets_url, "targets")?,
+    })
+}
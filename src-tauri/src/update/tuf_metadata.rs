@@ -0,0 +1,373 @@
+// src-tauri/src/update/tuf_metadata.rs
+//
+// TUF metadata types + verification (the part of `tough` we need).
+// Spec: https://theupdateframework.github.io/specification/latest/
+//
+// Every metadata file looks like:
+//   { "signed": { "_type": "timestamp", "version": 7, "expires": "...", ... },
+//     "signatures": [ { "keyid": "<hex>", "sig": "<hex>" } ] }
+//
+// - Signatures cover the canonical JSON (OLPC) of "signed", so we keep the
+//   raw JSON value around and only deserialize after verifying.
+// - A role is trusted when at least `threshold` distinct keys listed for it
+//   in root.json produced a valid signature. Keys count as distinct by
+//   their decoded key material, not their keyid, so one key listed under
+//   two keyids can't meet a threshold of 2 on its own.
+// - Supported key types (same as tuftool): ed25519, ecdsa-sha2-nistp256,
+//   rsa with rsassa-pss-sha256.
+// - Delegated targets roles are not supported; only top-level targets.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use olpc_cjson::CanonicalFormatter;
+use ring::signature::{self, UnparsedPublicKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+/// A metadata file before its signatures are checked.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawSigned {
+    pub signed: serde_json::Value,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Signature {
+    pub keyid: String,
+    pub sig: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Key {
+    pub keytype: String,
+    pub scheme: String,
+    pub keyval: KeyVal,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyVal {
+    pub public: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoleKeys {
+    pub keyids: Vec<String>,
+    pub threshold: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Root {
+    pub version: u64,
+    pub expires: String,
+    #[serde(default)]
+    pub consistent_snapshot: bool,
+    pub keys: HashMap<String, Key>,
+    pub roles: HashMap<String, RoleKeys>,
+}
+
+/// Entry of timestamp.meta / snapshot.meta.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetaFile {
+    pub version: u64,
+    pub length: Option<u64>,
+    pub hashes: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Timestamp {
+    pub version: u64,
+    pub expires: String,
+    pub meta: HashMap<String, MetaFile>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Snapshot {
+    pub version: u64,
+    pub expires: String,
+    pub meta: HashMap<String, MetaFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetFile {
+    pub length: u64,
+    pub hashes: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Targets {
+    pub version: u64,
+    pub expires: String,
+    pub targets: HashMap<String, TargetFile>,
+}
+
+/// Common accessors so verify_metadata can treat all roles alike.
+pub trait Role: DeserializeOwned {
+    const TYPE: &'static str;
+    fn version(&self) -> u64;
+    fn expires(&self) -> &str;
+}
+
+macro_rules! impl_role {
+    ($ty:ty, $name:literal) => {
+        impl Role for $ty {
+            const TYPE: &'static str = $name;
+            fn version(&self) -> u64 {
+                self.version
+            }
+            fn expires(&self) -> &str {
+                &self.expires
+            }
+        }
+    };
+}
+
+impl_role!(Root, "root");
+impl_role!(Timestamp, "timestamp");
+impl_role!(Snapshot, "snapshot");
+impl_role!(Targets, "targets");
+
+pub fn parse_signed(bytes: &[u8]) -> Result<RawSigned> {
+    serde_json::from_slice(bytes).context("Failed to parse TUF metadata JSON")
+}
+
+/// Check `raw` against the keys `root` trusts for role `T`, then
+/// deserialize it and check its type and expiry.
+pub fn verify_metadata<T: Role>(raw: &RawSigned, root: &Root) -> Result<T> {
+    verify_signatures(raw, root, T::TYPE)?;
+    let meta = decode::<T>(raw)?;
+    check_expiry(T::TYPE, meta.expires())?;
+    Ok(meta)
+}
+
+/// Root rotation step: `raw` (version N+1) must be signed by a threshold of
+/// the current root keys AND by a threshold of its own root keys.
+/// Expiry is not checked here; only the final root after rotation must be
+/// unexpired.
+pub fn verify_root_update(raw: &RawSigned, current: &Root) -> Result<Root> {
+    verify_signatures(raw, current, Root::TYPE)
+        .context("New root is not signed by the current root keys")?;
+    let new: Root = decode(raw)?;
+    verify_signatures(raw, &new, Root::TYPE)
+        .context("New root is not signed by its own root keys")?;
+    if new.version != current.version + 1 {
+        return Err(anyhow!(
+            "New root has version {} (expected {})",
+            new.version,
+            current.version + 1
+        ));
+    }
+    Ok(new)
+}
+
+/// Deserialize without verifying (only for metadata we verified earlier,
+/// e.g. cached copies used for rollback checks).
+pub fn decode<T: Role>(raw: &RawSigned) -> Result<T> {
+    let ty = raw.signed.get("_type").and_then(|t| t.as_str());
+    if ty != Some(T::TYPE) {
+        return Err(anyhow!(
+            "Expected {} metadata, got {}",
+            T::TYPE,
+            ty.unwrap_or("<none>")
+        ));
+    }
+    serde_json::from_value(raw.signed.clone())
+        .with_context(|| format!("Invalid {} metadata", T::TYPE))
+}
+
+/// At least `threshold` distinct trusted keys of `role` must have signed
+/// (distinct by key material, see the header).
+pub fn verify_signatures(raw: &RawSigned, root: &Root, role: &str) -> Result<()> {
+    let role_keys = root
+        .roles
+        .get(role)
+        .ok_or_else(|| anyhow!("root.json defines no '{}' role", role))?;
+    if role_keys.threshold == 0 {
+        return Err(anyhow!("Role '{}' has threshold 0", role));
+    }
+
+    let message = canonical_json(&raw.signed)?;
+    let mut valid: HashSet<Vec<u8>> = HashSet::new();
+    for sig in &raw.signatures {
+        if !role_keys.keyids.iter().any(|id| id == &sig.keyid) {
+            continue;
+        }
+        let Some(key) = root.keys.get(&sig.keyid) else {
+            continue;
+        };
+        match verify_signature(key, &message, &sig.sig) {
+            Ok(public_key) => {
+                valid.insert(public_key);
+            }
+            Err(e) => tracing::warn!("[TUF] Bad {} signature from key {}: {e:#}", role, sig.keyid),
+        }
+    }
+
+    if (valid.len() as u64) < role_keys.threshold {
+        return Err(anyhow!(
+            "{} metadata has {} valid signature(s), {} required",
+            role,
+            valid.len(),
+            role_keys.threshold
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_expiry(role: &str, expires: &str) -> Result<()> {
+    let expires = parse_expires(expires)?;
+    if expires <= Utc::now() {
+        return Err(anyhow!("{} metadata expired at {}", role, expires));
+    }
+    Ok(())
+}
+
+pub fn parse_expires(expires: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(expires)
+        .map(|d| d.with_timezone(&Utc))
+        .with_context(|| format!("Invalid expiry timestamp '{}'", expires))
+}
+
+/// Length + every known hash of a downloaded metadata file.
+pub fn check_meta_file(name: &str, bytes: &[u8], expected: &MetaFile) -> Result<()> {
+    if let Some(len) = expected.length {
+        if bytes.len() as u64 != len {
+            return Err(anyhow!(
+                "{} has length {} (expected {})",
+                name,
+                bytes.len(),
+                len
+            ));
+        }
+    }
+    if let Some(hashes) = &expected.hashes {
+        check_hashes(name, hashes, |algo| hash_bytes(algo, bytes))?;
+    }
+    Ok(())
+}
+
+/// Compare every supported hash in `expected` (sha256 / sha512) with
+/// `actual(algo)`; at least one supported hash must be present.
+pub fn check_hashes(
+    name: &str,
+    expected: &HashMap<String, String>,
+    actual: impl Fn(&str) -> Option<String>,
+) -> Result<()> {
+    let mut checked = 0;
+    for (algo, want) in expected {
+        if let Some(got) = actual(algo) {
+            if !got.eq_ignore_ascii_case(want) {
+                return Err(anyhow!("{} failed {} verification", name, algo));
+            }
+            checked += 1;
+        }
+    }
+    if checked == 0 {
+        return Err(anyhow!("{} has no sha256/sha512 hash in metadata", name));
+    }
+    Ok(())
+}
+
+fn hash_bytes(algo: &str, bytes: &[u8]) -> Option<String> {
+    match algo {
+        "sha256" => Some(hex::encode(Sha256::digest(bytes))),
+        "sha512" => Some(hex::encode(Sha512::digest(bytes))),
+        _ => None,
+    }
+}
+
+fn canonical_json(value: &serde_json::Value) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, CanonicalFormatter::new());
+    value
+        .serialize(&mut ser)
+        .context("Failed to canonicalize TUF metadata")?;
+    Ok(buf)
+}
+
+/// Returns the decoded public key that made the signature.
+fn verify_signature(key: &Key, message: &[u8], sig_hex: &str) -> Result<Vec<u8>> {
+    let sig = hex::decode(sig_hex).context("Signature is not hex")?;
+    let public = &key.keyval.public;
+
+    let (algorithm, public_key): (&dyn signature::VerificationAlgorithm, Vec<u8>) =
+        match (key.keytype.as_str(), key.scheme.as_str()) {
+            ("ed25519", "ed25519") => (
+                &signature::ED25519,
+                hex::decode(public).context("ed25519 public key is not hex")?,
+            ),
+            ("ecdsa" | "ecdsa-sha2-nistp256", "ecdsa-sha2-nistp256") => (
+                &signature::ECDSA_P256_SHA256_ASN1,
+                if public.trim_start().starts_with("-----BEGIN") {
+                    spki_key_bits(&pem_to_der(public)?)?
+                } else {
+                    hex::decode(public).context("ECDSA public key is not hex or PEM")?
+                },
+            ),
+            ("rsa", "rsassa-pss-sha256") => (
+                &signature::RSA_PSS_2048_8192_SHA256,
+                spki_key_bits(&pem_to_der(public)?)?,
+            ),
+            (keytype, scheme) => {
+                return Err(anyhow!("Unsupported key type {} / scheme {}", keytype, scheme))
+            }
+        };
+
+    UnparsedPublicKey::new(algorithm, &public_key)
+        .verify(message, &sig)
+        .map_err(|_| anyhow!("signature does not match"))?;
+    Ok(public_key)
+}
+
+fn pem_to_der(pem: &str) -> Result<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .filter(|l| !l.starts_with("-----"))
+        .map(str::trim)
+        .collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(body)
+        .context("Invalid PEM public key")
+}
+
+/// subjectPublicKey BIT STRING out of a DER SubjectPublicKeyInfo:
+///   SEQUENCE { SEQUENCE { algorithm ... }, BIT STRING { key } }
+fn spki_key_bits(der: &[u8]) -> Result<Vec<u8>> {
+    let (tag, spki, _) = der_read(der)?;
+    if tag != 0x30 {
+        return Err(anyhow!("Public key is not a SubjectPublicKeyInfo"));
+    }
+    let (tag, _, rest) = der_read(spki)?;
+    if tag != 0x30 {
+        return Err(anyhow!("Public key has no AlgorithmIdentifier"));
+    }
+    let (tag, bits, _) = der_read(rest)?;
+    if tag != 0x03 || bits.first() != Some(&0) {
+        return Err(anyhow!("Public key has no BIT STRING"));
+    }
+    Ok(bits[1..].to_vec())
+}
+
+/// One DER TLV: (tag, contents, remaining input).
+fn der_read(input: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let truncated = || anyhow!("Truncated DER in public key");
+    let (&tag, rest) = input.split_first().ok_or_else(truncated)?;
+    let (&first, rest) = rest.split_first().ok_or_else(truncated)?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let n = (first & 0x7f) as usize;
+        if n == 0 || n > 4 || rest.len() < n {
+            return Err(truncated());
+        }
+        let len = rest[..n].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        (len, &rest[n..])
+    };
+    if rest.len() < len {
+        return Err(truncated());
+    }
+    Ok((tag, &rest[..len], &rest[len..]))
+}
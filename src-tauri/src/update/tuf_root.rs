@@ -0,0 +1,97 @@
+// src-tauri/src/update/tuf_root.rs
+//
+// Trusted root.json lifecycle:
+// - The initial root ships inside the binary (src-tauri/tuf/root.json) and
+//   is written to tuf/root.json on first run.
+// - A build carrying a newer embedded root replaces an older one on disk;
+//   an older embedded root never downgrades a rotated one.
+// - Root rotation itself (fetching N+1.root.json) happens in tuf_client.rs
+//   on every update check and persists each accepted root here.
+// - trust_info() reports version + expiry so the UI can warn in time.
+//
+// The checked-in src-tauri/tuf/root.json is a key-less placeholder that
+// never verifies (fail closed); release builds replace it with the real
+// root produced by `tuftool root ...`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use tauri::AppHandle;
+
+use super::tuf_metadata::{decode, parse_expires, parse_signed, verify_signatures, Root};
+use super::TufConfig;
+
+const EMBEDDED_ROOT: &[u8] = include_bytes!("../../tuf/root.json");
+
+/// Warn the user this many days before the trusted root expires.
+const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrustInfo {
+    pub root_version: u64,
+    /// RFC 3339, e.g. "2027-01-01T00:00:00Z".
+    pub expires: String,
+    /// Negative once expired.
+    pub expires_in_days: i64,
+    pub expired: bool,
+    /// True when fewer than EXPIRY_WARNING_DAYS remain.
+    pub expiring_soon: bool,
+    /// Root is signed by its own root keys (false for the placeholder).
+    pub verified: bool,
+    pub root_path: String,
+}
+
+/// Write the embedded root if there is none yet, or if the embedded one is
+/// newer than what is on disk.
+pub fn bootstrap_root(root_path: &Path) -> Result<()> {
+    let embedded = root_version(EMBEDDED_ROOT).context("Embedded root.json is invalid")?;
+    let on_disk = fs::read(root_path).ok().and_then(|b| root_version(&b).ok());
+
+    match on_disk {
+        Some(v) if v >= embedded => Ok(()),
+        _ => persist_root(root_path, EMBEDDED_ROOT),
+    }
+}
+
+/// Atomically replace the trusted root (initial write or rotation).
+pub fn persist_root(root_path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = root_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create TUF root dir {:?}", parent))?;
+    }
+    let tmp = root_path.with_extension("json.tmp");
+    fs::write(&tmp, bytes).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, root_path).with_context(|| format!("Failed to write {:?}", root_path))?;
+    Ok(())
+}
+
+fn root_version(bytes: &[u8]) -> Result<u64> {
+    Ok(decode::<Root>(&parse_signed(bytes)?)?.version)
+}
+
+/// Version / expiry of the root the next update check will start from.
+pub fn trust_info(app: &AppHandle) -> Result<TrustInfo> {
+    let cfg = TufConfig::default_tuf_config(app)?;
+    let bytes = fs::read(&cfg.root_path)
+        .with_context(|| format!("Failed to read trusted root {:?}", cfg.root_path))?;
+    let raw = parse_signed(&bytes)?;
+    let root: Root = decode(&raw)?;
+
+    let expires = parse_expires(&root.expires)?;
+    let remaining = expires - Utc::now();
+    let expires_in_days = remaining.num_days();
+    let expired = remaining.num_seconds() <= 0;
+
+    Ok(TrustInfo {
+        root_version: root.version,
+        expires: root.expires.clone(),
+        expires_in_days,
+        expired,
+        expiring_soon: !expired && expires_in_days < EXPIRY_WARNING_DAYS,
+        verified: verify_signatures(&raw, &root, "root").is_ok(),
+        root_path: cfg.root_path.to_string_lossy().to_string(),
+    })
+}
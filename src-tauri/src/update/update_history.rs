@@ -0,0 +1,158 @@
+// src-tauri/src/update/update_history.rs
+//
+// Append-only log of update activity, for support:
+//   <app config dir>/tuf/update_history.json
+//
+//   [
+//     { "timestamp": 1760000000, "kind": "check", "outcome": "success",
+//       "from_version": "0.2.2", "to_version": "0.2.3", "channel": "stable" },
+//     { "timestamp": 1760000100, "kind": "apply", "outcome": "failure",
+//       "to_version": "0.2.3", "error": "Bundle verification failed: ..." }
+//   ]
+//
+// Oldest entries are dropped past MAX_ENTRIES. Recording is best-effort:
+// a failure to write history never fails the update operation itself.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::tuf_config::tuf_dir;
+use super::UpdateChannel;
+
+const MAX_ENTRIES: usize = 500;
+
+/// Serializes read-modify-write of the history file (scheduler + commands).
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateEventKind {
+    Check,
+    Download,
+    Apply,
+    Rollback,
+    /// Automatic switch to `previous` after a failed integrity check.
+    Fallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateOutcome {
+    Success,
+    Failure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateHistoryEntry {
+    /// Seconds since UNIX_EPOCH.
+    pub timestamp: u64,
+    pub kind: UpdateEventKind,
+    pub outcome: UpdateOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<UpdateChannel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl UpdateHistoryEntry {
+    /// Entry stamped with the current time; outcome/error from `res`.
+    pub fn from_result<T>(kind: UpdateEventKind, res: &Result<T>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (outcome, error) = match res {
+            Ok(_) => (UpdateOutcome::Success, None),
+            Err(e) => (UpdateOutcome::Failure, Some(format!("{:#}", e))),
+        };
+        UpdateHistoryEntry {
+            timestamp,
+            kind,
+            outcome,
+            from_version: None,
+            to_version: None,
+            channel: None,
+            error,
+        }
+    }
+
+    pub fn versions(mut self, from: Option<String>, to: Option<String>) -> Self {
+        self.from_version = from;
+        self.to_version = to;
+        self
+    }
+
+    pub fn channel(mut self, channel: Option<UpdateChannel>) -> Self {
+        self.channel = channel;
+        self
+    }
+}
+
+fn history_path(app: &AppHandle) -> Result<PathBuf> {
+    Ok(tuf_dir(app)?.join("update_history.json"))
+}
+
+fn read_entries(path: &PathBuf) -> Result<Vec<UpdateHistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read update history at {:?}", path))?;
+    serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse update history at {:?}", path))
+}
+
+/// Append one entry (best-effort; errors are only logged).
+pub fn record_update_event(app: &AppHandle, entry: UpdateHistoryEntry) {
+    if let Err(e) = append(app, entry) {
+        tracing::warn!("[UpdateHistory] Failed to record event: {e:#}");
+    }
+}
+
+fn append(app: &AppHandle, entry: UpdateHistoryEntry) -> Result<()> {
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = history_path(app)?;
+
+    // A corrupt file shouldn't block logging forever: start over.
+    let mut entries = read_entries(&path).unwrap_or_else(|e| {
+        tracing::warn!("[UpdateHistory] Resetting unreadable history: {e:#}");
+        Vec::new()
+    });
+    entries.push(entry);
+    if entries.len() > MAX_ENTRIES {
+        let excess = entries.len() - MAX_ENTRIES;
+        entries.drain(..excess);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create update history dir {:?}", parent))?;
+    }
+    let data = serde_json::to_string_pretty(&entries)
+        .context("Failed to serialize update history to JSON")?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).with_context(|| format!("Failed to write {:?}", tmp))?;
+    fs::rename(&tmp, &path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
+/// Newest first, at most `limit` entries (None = all).
+pub fn load_update_history(app: &AppHandle, limit: Option<usize>) -> Result<Vec<UpdateHistoryEntry>> {
+    let _lock = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries = read_entries(&history_path(app)?)?;
+    entries.reverse();
+    if let Some(limit) = limit {
+        entries.truncate(limit);
+    }
+    Ok(entries)
+}
@@ -2,37 +2,60 @@
 //
 // High-level operations:
 //   - check_for_updates: ask TUF repo if newer version exists
-//   - download_update_bundle: download & verify signed ZIP
-//   - apply_staged_update: extract ZIP into versions/<version>/ and update state
+//   - download_update_bundle: download & verify signed ZIP (a delta from the
+//     current version when the repo has one, else the full bundle)
+//   - apply_staged_update: extract ZIP (or patch the current version with a
+//     delta) into versions/<version>/ and update state
+//   - rollback_update: switch back to the previous version folder
 //
 // All TUF correctness (signatures, hashes, rollback protection, expiration)
-// is handled by the `tough` library. :contentReference[oaicite:5]{index=5}
+// is handled by tuf_client.rs / tuf_metadata.rs.
 //
 // This module is intentionally "dumb": it only glues TUF + ZIP + FS layout.
+// Every public operation is recorded in update_history.json.
 
 use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
 
 use anyhow::{anyhow, Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use zip::read::ZipArchive;
 
-use super::tuf_client::{find_latest_update_for_platform, load_repository, save_target_to_cache};
+use crate::io_throttle::IoThrottle;
+use crate::op_journal::{OpJournal, ResumableOp, UPDATE_DOWNLOAD_OP};
+
+use super::bundle_manifest::verify_extracted;
+use super::delta::{apply_delta, is_delta_bundle, read_manifest};
+use super::update_settings::get_update_channel;
+use super::update_history::{record_update_event, UpdateEventKind, UpdateHistoryEntry};
+use super::tuf_client::{
+    find_delta_for_platform, find_latest_update_for_platform, load_repository, save_target_to_cache,
+};
 use super::version_fs::{load_version_state, save_version_state, version_dir};
-use super::TufConfig;
+use super::{TufConfig, UpdateChannel};
+
+/// Held while versions/ is being changed, so apply, rollback and
+/// cleanup_old_versions never run over each other.
+pub(super) static VERSIONS_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateCheckResult {
     pub current_version: String,
     pub latest_version: Option<String>,
     pub update_available: bool,
+    pub channel: UpdateChannel,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadResult {
     pub version: String,
     pub bundle_path: String,
+    /// Base version when `bundle_path` is a delta bundle (None = full bundle).
+    #[serde(default)]
+    pub delta_from: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,18 +65,42 @@ pub struct ApplyResult {
 }
 
 /// Determine whether a newer version exists in TUF repository.
+///
+/// `channel` overrides the persisted channel for this call only
+/// (None = use the channel from update_settings.json).
 pub async fn check_for_updates(
     app: &AppHandle,
     current_version: String,
     platform_id: String,
+    channel: Option<UpdateChannel>,
 ) -> Result<UpdateCheckResult> {
-    let cfg = TufConfig::default_tuf_config(app)?; // we'll add impl below
+    let res = check_inner(app, current_version.clone(), platform_id, channel).await;
+    let (latest, channel) = match &res {
+        Ok(r) => (r.latest_version.clone(), Some(r.channel)),
+        Err(_) => (None, resolved_channel(app, channel)),
+    };
+    record_update_event(
+        app,
+        UpdateHistoryEntry::from_result(UpdateEventKind::Check, &res)
+            .versions(Some(current_version), latest)
+            .channel(channel),
+    );
+    res
+}
+
+async fn check_inner(
+    app: &AppHandle,
+    current_version: String,
+    platform_id: String,
+    channel: Option<UpdateChannel>,
+) -> Result<UpdateCheckResult> {
+    let cfg = TufConfig::for_channel(app, channel)?;
     let repo = load_repository(&cfg).await?;
 
     let current = Version::parse(&current_version)
         .context("Failed to parse current version as semver")?;
 
-    let maybe_latest = find_latest_update_for_platform(&repo, &platform_id)?;
+    let maybe_latest = find_latest_update_for_platform(&repo, cfg.channel, &platform_id)?;
 
     let (latest_version, update_available) = if let Some(desc) = maybe_latest {
         let newer = desc.version > current;
@@ -66,6 +113,7 @@ pub async fn check_for_updates(
         current_version,
         latest_version,
         update_available,
+        channel: cfg.channel,
     })
 }
 
@@ -74,25 +122,102 @@ pub async fn check_for_updates(
 pub async fn download_update_bundle(
     app: &AppHandle,
     platform_id: String,
+    channel: Option<UpdateChannel>,
+) -> Result<DownloadResult> {
+    // Journaled so a download cut short by a crash is offered for resume
+    // (op_journal.rs).
+    let journal = OpJournal::begin(
+        app,
+        UPDATE_DOWNLOAD_OP,
+        &ResumableOp::Download {
+            platform_id: platform_id.clone(),
+            channel,
+        },
+        false,
+    );
+    let res = download_inner(app, platform_id, channel).await;
+    if let Some(journal) = journal {
+        journal.finish();
+    }
+    let (from, to) = match &res {
+        Ok(r) => (r.delta_from.clone(), Some(r.version.clone())),
+        Err(_) => (None, None),
+    };
+    record_update_event(
+        app,
+        UpdateHistoryEntry::from_result(UpdateEventKind::Download, &res)
+            .versions(from, to)
+            .channel(resolved_channel(app, channel)),
+    );
+    res
+}
+
+async fn download_inner(
+    app: &AppHandle,
+    platform_id: String,
+    channel: Option<UpdateChannel>,
 ) -> Result<DownloadResult> {
-    let cfg = TufConfig::default_tuf_config(app)?;
+    let cfg = TufConfig::for_channel(app, channel)?;
     let repo = load_repository(&cfg).await?;
+    let throttle = app.try_state::<IoThrottle>();
+    let throttle = throttle.as_deref();
 
-    let desc = find_latest_update_for_platform(&repo, &platform_id)?
-        .ok_or_else(|| anyhow!("No update available for platform {}", platform_id))?;
+    let desc = find_latest_update_for_platform(&repo, cfg.channel, &platform_id)?
+        .ok_or_else(|| {
+            anyhow!(
+                "No update available for platform {} on channel {}",
+                platform_id,
+                cfg.channel
+            )
+        })?;
 
-    let bundle_path = save_target_to_cache(&repo, &cfg, &desc).await?;
+    // Prefer a delta from the installed version; any problem with it
+    // (none published, download/verify failure) falls back to the full ZIP.
+    if let Some(base) = delta_base(app, &desc.version) {
+        match find_delta_for_platform(&repo, cfg.channel, &platform_id, &base, &desc.version) {
+            Ok(Some(delta)) => match save_target_to_cache(&repo, &cfg, &delta, throttle).await {
+                Ok(bundle_path) => {
+                    return Ok(DownloadResult {
+                        version: desc.version.to_string(),
+                        bundle_path: bundle_path.to_string_lossy().to_string(),
+                        delta_from: Some(delta.base_version.as_ref().unwrap_or(&base).to_string()),
+                    });
+                }
+                Err(e) => tracing::warn!("[Update] Delta download failed, using full bundle: {e:#}"),
+            },
+            Ok(None) => {}
+            Err(e) => tracing::warn!("[Update] Delta lookup failed, using full bundle: {e:#}"),
+        }
+    }
+
+    let bundle_path = save_target_to_cache(&repo, &cfg, &desc, throttle).await?;
 
     Ok(DownloadResult {
         version: desc.version.to_string(),
         bundle_path: bundle_path.to_string_lossy().to_string(),
+        delta_from: None,
     })
 }
 
+/// Channel an operation actually used: the override or the persisted one.
+fn resolved_channel(app: &AppHandle, channel: Option<UpdateChannel>) -> Option<UpdateChannel> {
+    channel.or_else(|| get_update_channel(app).ok())
+}
+
+/// The installed version a delta to `target` could be applied to:
+/// version_state.current, if it is older and its folder is still on disk.
+fn delta_base(app: &AppHandle, target: &Version) -> Option<Version> {
+    let state = load_version_state(app).ok()?;
+    let base = Version::parse(&state.current).ok()?;
+    let installed = version_dir(app, &base).ok()?.is_dir();
+    (installed && base < *target).then_some(base)
+}
+
 /// Apply a previously downloaded bundle:
 ///   - Extract ZIP into versions/<version>/
+///   - Verify the files against the bundle's manifest.json
 ///   - Update version_state.json (current/previous)
-///   - Does NOT restart the app; the launcher or user
+///   - Does NOT restart the app (see launcher.rs); the user
 ///     decides when to switch.
 ///
 /// This function is intentionally synchronous (blocking IO) because
@@ -103,13 +228,25 @@ pub fn apply_staged_update(
     bundle_path: String,
     new_version: String,
 ) -> Result<ApplyResult> {
+    let _lock = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let from = load_version_state(app).ok().map(|s| s.current);
+    let res = apply_inner(app, bundle_path, new_version.clone());
+    record_update_event(
+        app,
+        UpdateHistoryEntry::from_result(UpdateEventKind::Apply, &res)
+            .versions(from, Some(new_version)),
+    );
+    res
+}
+
+fn apply_inner(app: &AppHandle, bundle_path: String, new_version: String) -> Result<ApplyResult> {
     let new_ver = Version::parse(&new_version)
         .context("Failed to parse new version as semver")?;
     let target_dir = version_dir(app, &new_ver)?;
 
     // 1) Extract bundle into a temp folder first (best-effort atomicity).
     let temp_dir = target_dir
-        .with_file_name(format!(".{}_tmp", new_ver.to_string()));
+        .with_file_name(format!(".{}_tmp", new_ver));
     if temp_dir.exists() {
         std::fs::remove_dir_all(&temp_dir)
             .with_context(|| format!("Failed to clean old temp dir {:?}", temp_dir))?;
@@ -117,17 +254,32 @@ pub fn apply_staged_update(
     std::fs::create_dir_all(&temp_dir)
         .with_context(|| format!("Failed to create temp dir {:?}", temp_dir))?;
 
-    // 2) Extract ZIP.
-    let file = File::open(&bundle_path)
-        .with_context(|| format!("Failed to open bundle at {}", bundle_path))?;
-    let mut archive = ZipArchive::new(file)
-        .context("Failed to open ZIP archive from bundle")?;
+    // 2) Extract ZIP, or rebuild from the current version + delta.
+    if is_delta_bundle(Path::new(&bundle_path)) {
+        let res = apply_delta_bundle(app, &bundle_path, &new_ver, &temp_dir);
+        if res.is_err() {
+            let _ = std::fs::remove_dir_all(&temp_dir);
+        }
+        res?;
+    } else {
+        let file = File::open(&bundle_path)
+            .with_context(|| format!("Failed to open bundle at {}", bundle_path))?;
+        let mut archive = ZipArchive::new(file)
+            .context("Failed to open ZIP archive from bundle")?;
+
+        // Safe extraction: ZipArchive::extract() uses enclosed_name() internally,
+        // which prevents path traversal and absolute paths. :contentReference[oaicite:6]{index=6}
+        archive
+            .extract(&temp_dir)
+            .with_context(|| format!("Failed to extract ZIP into {:?}", temp_dir))?;
+    }
 
-    // Safe extraction: ZipArchive::extract() uses enclosed_name() internally,
-    // which prevents path traversal and absolute paths. :contentReference[oaicite:6]{index=6}
-    archive
-        .extract(&temp_dir)
-        .with_context(|| format!("Failed to extract ZIP into {:?}", temp_dir))?;
+    // 2b) Check the result against its manifest.json before switching.
+    let launcher = app.package_info().version.clone();
+    if let Err(e) = verify_extracted(&temp_dir, &new_ver, &launcher) {
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        return Err(e);
+    }
 
     // 3) Move temp dir into final location.
     if target_dir.exists() {
@@ -151,9 +303,103 @@ pub fn apply_staged_update(
     })
 }
 
+/// Patch versions/<current>/ into `out_dir` using a delta bundle.
+/// The delta must be built from the version that is current right now.
+fn apply_delta_bundle(
+    app: &AppHandle,
+    bundle_path: &str,
+    new_ver: &Version,
+    out_dir: &Path,
+) -> Result<()> {
+    let manifest = read_manifest(Path::new(bundle_path))?;
+    let target = Version::parse(&manifest.target_version)
+        .context("Failed to parse delta target version as semver")?;
+    if target != *new_ver {
+        return Err(anyhow!(
+            "Delta bundle is for version {}, not {}",
+            manifest.target_version,
+            new_ver
+        ));
+    }
+
+    let state = load_version_state(app)?;
+    if manifest.base_version != state.current {
+        return Err(anyhow!(
+            "Delta bundle needs base version {}, but {} is installed; download the full bundle",
+            manifest.base_version,
+            state.current
+        ));
+    }
+    let base = Version::parse(&manifest.base_version)
+        .context("Failed to parse delta base version as semver")?;
+    let base_dir = version_dir(app, &base)?;
+    if !base_dir.is_dir() {
+        return Err(anyhow!("Base version directory is missing: {:?}", base_dir));
+    }
+
+    apply_delta(&base_dir, Path::new(bundle_path), out_dir, &manifest)
+}
+
+/// Revert to the previously applied version:
+///   - Requires `previous` in version_state.json
+///   - Requires versions/<previous>/ to still exist on disk
+///   - Swaps current/previous, so a second rollback re-applies the update
+///
+/// Like apply_staged_update, this does NOT restart the app.
+pub fn rollback_update(app: &AppHandle) -> Result<ApplyResult> {
+    let _lock = VERSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let state = load_version_state(app).ok();
+    let res = rollback_inner(app);
+    record_update_event(
+        app,
+        UpdateHistoryEntry::from_result(UpdateEventKind::Rollback, &res).versions(
+            state.as_ref().map(|s| s.current.clone()),
+            state.and_then(|s| s.previous),
+        ),
+    );
+    res
+}
+
+fn rollback_inner(app: &AppHandle) -> Result<ApplyResult> {
+    let mut state = load_version_state(app)?;
+    let prev = state
+        .previous
+        .clone()
+        .ok_or_else(|| anyhow!("No previous version recorded; nothing to roll back to"))?;
+
+    let prev_ver = Version::parse(&prev)
+        .with_context(|| format!("Failed to parse previous version '{}' as semver", prev))?;
+    let prev_dir = version_dir(app, &prev_ver)?;
+    if !prev_dir.is_dir() {
+        return Err(anyhow!(
+            "Previous version directory is missing: {:?}",
+            prev_dir
+        ));
+    }
+
+    let from = state.current.clone();
+    state.current = prev.clone();
+    state.previous = Some(from.clone());
+    save_version_state(app, &state)?;
+
+    Ok(ApplyResult {
+        from_version: from,
+        to_version: prev,
+    })
+}
+
 // Small helper so default_tuf_config can be used via `TufConfig::default_tuf_config(app)`
 impl TufConfig {
     pub fn default_tuf_config(app: &AppHandle) -> Result<TufConfig> {
         super::tuf_config::default_tuf_config(app)
     }
+
+    /// Default config, optionally forcing a channel other than the persisted one.
+    pub fn for_channel(app: &AppHandle, channel: Option<UpdateChannel>) -> Result<TufConfig> {
+        let mut cfg = Self::default_tuf_config(app)?;
+        if let Some(channel) = channel {
+            cfg.channel = channel;
+        }
+        Ok(cfg)
+    }
 }
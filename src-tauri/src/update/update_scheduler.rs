@@ -0,0 +1,243 @@
+// src-tauri/src/update/update_scheduler.rs
+//
+// Background update checks.
+// - Runs on the async runtime for the whole app lifetime.
+// - Every `check_interval_hours` (default 6h, from update_settings.json)
+//   calls check_for_updates for this platform and emits
+//   `fu:update_available` (UpdateCheckResult) once per new version.
+// - With `auto_download` on, the bundle is fetched right away, but only
+//   when the OS reports an unmetered connection; then `fu:update_downloaded`
+//   (DownloadResult) is emitted. Applying stays a user decision.
+// - trigger() wakes the loop for an immediate check ("Check now").
+//
+// Settings are re-read on every round, so toggling auto_check or the
+// interval takes effect without a restart.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use semver::Version;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use super::update_manager::{check_for_updates, download_update_bundle};
+use super::update_settings::{load_update_settings, save_update_settings, UpdateSettings};
+use super::version_fs::load_version_state;
+
+/// Give startup some room before the first network call.
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+/// Retry sooner than the full interval after a failed check (offline, ...).
+const RETRY_DELAY: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchedulerStatus {
+    /// Seconds since UNIX_EPOCH of the last finished check (0 = never).
+    pub last_check: u64,
+    pub last_error: Option<String>,
+    pub latest_version: Option<String>,
+    pub next_check_in_sec: u64,
+}
+
+/// Schedule settings + live status, for the settings page.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateScheduleInfo {
+    pub auto_check: bool,
+    pub check_interval_hours: u64,
+    pub auto_download: bool,
+    #[serde(flatten)]
+    pub status: SchedulerStatus,
+}
+
+#[derive(Clone, Default)]
+pub struct UpdateScheduler {
+    wake: Arc<Notify>,
+    status: Arc<Mutex<SchedulerStatus>>,
+}
+
+impl UpdateScheduler {
+    /// Run a check now; if one is in progress, another follows right after.
+    pub fn trigger(&self) {
+        self.wake.notify_one();
+    }
+
+    pub fn status(&self) -> SchedulerStatus {
+        self.status.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn info(&self, app: &AppHandle) -> anyhow::Result<UpdateScheduleInfo> {
+        let settings = load_update_settings(app)?;
+        Ok(UpdateScheduleInfo {
+            auto_check: settings.auto_check,
+            check_interval_hours: settings.check_interval_hours,
+            auto_download: settings.auto_download,
+            status: self.status(),
+        })
+    }
+
+    /// Persist schedule settings and re-plan right away (a shorter
+    /// interval shouldn't wait for the old, longer sleep to end).
+    pub fn configure(
+        &self,
+        app: &AppHandle,
+        auto_check: bool,
+        interval_hours: Option<u64>,
+        auto_download: Option<bool>,
+    ) -> anyhow::Result<UpdateScheduleInfo> {
+        let mut settings: UpdateSettings = load_update_settings(app)?;
+        settings.auto_check = auto_check;
+        if let Some(hours) = interval_hours {
+            if hours == 0 {
+                anyhow::bail!("Update check interval must be at least 1 hour");
+            }
+            settings.check_interval_hours = hours;
+        }
+        if let Some(auto_download) = auto_download {
+            settings.auto_download = auto_download;
+        }
+        save_update_settings(app, &settings)?;
+        if auto_check {
+            self.trigger();
+        }
+        self.info(app)
+    }
+}
+
+/// Start the scheduler loop. Keep the returned handle in managed state.
+pub fn start_update_scheduler(app: AppHandle) -> UpdateScheduler {
+    let scheduler = UpdateScheduler::default();
+    let handle = scheduler.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut notified_version: Option<Version> = None;
+        let mut forced = wait(&handle, FIRST_CHECK_DELAY).await;
+
+        loop {
+            let settings = load_update_settings(&app).unwrap_or_default();
+            let interval = Duration::from_secs(settings.check_interval_hours.max(1) * 3600);
+
+            if !settings.auto_check && !forced {
+                set_next(&handle, interval);
+                forced = wait(&handle, interval).await;
+                continue;
+            }
+
+            let res = run_check(&app, &handle, settings.auto_download, &mut notified_version).await;
+            let delay = match res {
+                Ok(()) => interval,
+                Err(e) => {
+                    tracing::warn!("[UpdateScheduler] Check failed: {e:#}");
+                    let mut status = handle.status.lock().unwrap_or_else(|e| e.into_inner());
+                    status.last_check = now_secs();
+                    status.last_error = Some(format!("{e:#}"));
+                    RETRY_DELAY.min(interval)
+                }
+            };
+            set_next(&handle, delay);
+            forced = wait(&handle, delay).await;
+        }
+    });
+
+    scheduler
+}
+
+async fn run_check(
+    app: &AppHandle,
+    handle: &UpdateScheduler,
+    auto_download: bool,
+    notified_version: &mut Option<Version>,
+) -> anyhow::Result<()> {
+    let result = check_for_updates(app, current_version(app), platform_id(), None).await?;
+    let latest = result
+        .latest_version
+        .as_deref()
+        .and_then(|v| Version::parse(v).ok());
+
+    {
+        let mut status = handle.status.lock().unwrap_or_else(|e| e.into_inner());
+        status.last_check = now_secs();
+        status.last_error = None;
+        status.latest_version = result.latest_version.clone();
+    }
+
+    if !result.update_available || latest.is_none() || *notified_version == latest {
+        return Ok(());
+    }
+    *notified_version = latest;
+    let _ = app.emit("fu:update_available", result.clone());
+
+    if auto_download {
+        if is_metered_connection() == Some(false) {
+            let download = download_update_bundle(app, platform_id(), None).await?;
+            let _ = app.emit("fu:update_downloaded", download);
+        } else {
+            tracing::info!("[UpdateScheduler] Skipping pre-download: connection may be metered");
+        }
+    }
+    Ok(())
+}
+
+/// Sleep for `delay` or until trigger(). Returns true if triggered.
+async fn wait(handle: &UpdateScheduler, delay: Duration) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => false,
+        _ = handle.wake.notified() => true,
+    }
+}
+
+fn set_next(handle: &UpdateScheduler, delay: Duration) {
+    handle.status.lock().unwrap_or_else(|e| e.into_inner()).next_check_in_sec = delay.as_secs();
+}
+
+/// Version actually running: the applied side-by-side version if newer
+/// than the launcher binary itself.
+pub fn current_version(app: &AppHandle) -> String {
+    let package = app.package_info().version.clone();
+    let applied = load_version_state(app)
+        .ok()
+        .and_then(|s| Version::parse(&s.current).ok());
+    match applied {
+        Some(v) if v > package => v.to_string(),
+        _ => package.to_string(),
+    }
+}
+
+/// Platform id used in target names, e.g. "desktop-windows-x86_64".
+pub fn platform_id() -> String {
+    format!("desktop-{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Some(true) = metered, Some(false) = unmetered, None = unknown.
+/// Unknown is treated as metered: no surprise downloads on a phone hotspot.
+#[cfg(target_os = "linux")]
+fn is_metered_connection() -> Option<bool> {
+    // NetworkManager's global NMMetered value, printed as "u <n>":
+    // 0 unknown, 1 yes, 2 no, 3 guessed yes, 4 guessed no.
+    let out = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    match String::from_utf8_lossy(&out.stdout).trim() {
+        "u 1" | "u 3" => Some(true),
+        "u 2" | "u 4" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_metered_connection() -> Option<bool> {
+    None
+}
@@ -15,16 +15,22 @@
 //     "previous": "0.2.2"
 //   }
 //
+// Writes go to version_state.json.tmp (fsynced) and are renamed over the
+// old file, which is first copied to version_state.json.bak. If the state
+// still fails to parse, loading falls back to the backup and emits
+// `fu:version_state_recovered`.
+//
 // This module doesn't know HOW the launcher starts different versions,
 // it only manages folders + state.
 
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionState {
@@ -32,7 +38,7 @@ pub struct VersionState {
     pub previous: Option<String>,
 }
 
-fn versions_root(app: &AppHandle) -> Result<PathBuf> {
+pub fn versions_root(app: &AppHandle) -> Result<PathBuf> {
     use tauri::Manager;
     let app_dir = app
         .path()
@@ -45,6 +51,41 @@ fn state_path(app: &AppHandle) -> Result<PathBuf> {
     Ok(versions_root(app)?.join("version_state.json"))
 }
 
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+/// Payload of `fu:version_state_recovered`.
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionStateRecovered {
+    pub path: String,
+    /// Why the state file was rejected.
+    pub error: String,
+    /// The state now in effect (read from the backup).
+    pub state: VersionState,
+}
+
+fn read_state(path: &Path) -> Result<VersionState> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read version state at {:?}", path))?;
+    serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse version state at {:?}", path))
+}
+
+/// Write to a temp file, fsync it and rename it over `path`.
+fn write_state(path: &Path, state: &VersionState) -> Result<()> {
+    let data = serde_json::to_string_pretty(state)
+        .context("Failed to serialize version state to JSON")?;
+    let tmp = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp).with_context(|| format!("Failed to create {:?}", tmp))?;
+    file.write_all(data.as_bytes())
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Failed to write {:?}", tmp))?;
+    drop(file);
+    fs::rename(&tmp, path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
 pub fn load_version_state(app: &AppHandle) -> Result<VersionState> {
     let path = state_path(app)?;
     if !path.exists() {
@@ -55,10 +96,31 @@ pub fn load_version_state(app: &AppHandle) -> Result<VersionState> {
         });
     }
 
-    let data = fs::read_to_string(&path)
-        .with_context(|| format!("Failed to read version state at {:?}", path))?;
-    let state: VersionState = serde_json::from_str(&data)
-        .with_context(|| format!("Failed to parse version state at {:?}", path))?;
+    let err = match read_state(&path) {
+        Ok(state) => return Ok(state),
+        Err(e) => e,
+    };
+
+    // Unreadable or half-written: use the copy from before the last save.
+    let backup = backup_path(&path);
+    let Ok(state) = read_state(&backup) else {
+        return Err(err.context("No usable version state backup"));
+    };
+    let err = format!("{:#}", err);
+    tracing::warn!("[Update] Version state recovered from {:?}: {}", backup, err);
+
+    // Put the good copy back so the warning isn't repeated on every load.
+    if let Err(e) = write_state(&path, &state) {
+        tracing::warn!("[Update] Failed to restore version state: {e:#}");
+    }
+    let _ = app.emit(
+        "fu:version_state_recovered",
+        VersionStateRecovered {
+            path: path.to_string_lossy().to_string(),
+            error: err,
+            state: state.clone(),
+        },
+    );
     Ok(state)
 }
 
@@ -68,14 +130,45 @@ pub fn save_version_state(app: &AppHandle, state: &VersionState) -> Result<()> {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create version state dir {:?}", parent))?;
     }
-    let data = serde_json::to_string_pretty(state)
-        .context("Failed to serialize version state to JSON")?;
-    fs::write(&path, data)
-        .with_context(|| format!("Failed to write version state to {:?}", path))?;
-    Ok(())
+    // Only a state that still parses is worth keeping as the backup.
+    if read_state(&path).is_ok() {
+        let backup = backup_path(&path);
+        fs::copy(&path, &backup)
+            .with_context(|| format!("Failed to back up version state to {:?}", backup))?;
+    }
+    write_state(&path, state)
 }
 
 /// Returns the directory where a given version should live.
 pub fn version_dir(app: &AppHandle, version: &Version) -> Result<PathBuf> {
     Ok(versions_root(app)?.join(version.to_string()))
 }
+
+/// List every installed version folder (`versions/<semver>/`).
+/// Folders whose name is not a valid semver (temp dirs etc.) are ignored.
+pub fn list_installed_versions(app: &AppHandle) -> Result<Vec<(Version, PathBuf)>> {
+    let root = versions_root(app)?;
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut versions = Vec::new();
+    for entry in fs::read_dir(&root)
+        .with_context(|| format!("Failed to read versions dir {:?}", root))?
+        .flatten()
+    {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if let Some(v) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| Version::parse(n).ok())
+        {
+            versions.push((v, path));
+        }
+    }
+    versions.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(versions)
+}
@@ -0,0 +1,100 @@
+// src-tauri/src/vfs/local.rs
+//
+// The local disk as a VfsProvider (std::fs). Paths are OS paths as given
+// by the frontend.
+
+use super::{VfsDirIter, VfsEntry, VfsProvider};
+use crate::error::AppError;
+use crate::file_meta::is_hidden;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub struct LocalProvider;
+
+/// `meta` from symlink_metadata / DirEntry::metadata (links not followed).
+fn entry_from(path: &Path, name: String, meta: &Metadata) -> VfsEntry {
+    VfsEntry {
+        hidden: is_hidden(&name, meta),
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_dir: meta.is_dir(),
+        is_symlink: meta.file_type().is_symlink(),
+        size: if meta.is_dir() { 0 } else { meta.len() },
+        modified: meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    }
+}
+
+impl VfsProvider for LocalProvider {
+    fn scheme(&self) -> &'static str {
+        "file"
+    }
+
+    fn list(&self, path: &str) -> Result<VfsDirIter, AppError> {
+        let iter = fs::read_dir(path)
+            .map_err(|e| AppError::io(&e).context("Failed to read directory").with_path(path))?;
+        Ok(Box::new(iter.map(|entry| {
+            let entry = entry.map_err(|e| AppError::io(&e))?;
+            let meta = entry
+                .metadata()
+                .map_err(|e| AppError::io(&e).with_path(entry.path()))?;
+            let name = entry
+                .file_name()
+                .into_string()
+                .unwrap_or_else(|_| "?".to_string());
+            Ok(entry_from(&entry.path(), name, &meta))
+        })))
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsEntry, AppError> {
+        let meta = fs::symlink_metadata(path).map_err(|e| AppError::io(&e).with_path(path))?;
+        let name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        Ok(entry_from(Path::new(path), name, &meta))
+    }
+
+    fn read(&self, path: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        let file = File::open(path).map_err(|e| AppError::io(&e).with_path(path))?;
+        Ok(Box::new(file))
+    }
+
+    fn write(&self, path: &str) -> Result<Box<dyn Write + Send>, AppError> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| AppError::io(&e).with_path(path))?;
+        Ok(Box::new(file))
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError> {
+        fs::create_dir_all(path)
+            .map_err(|e| AppError::io(&e).context("Failed to create directory").with_path(path))
+    }
+
+    fn delete(&self, path: &str) -> Result<(), AppError> {
+        let meta = fs::symlink_metadata(path).map_err(|e| AppError::io(&e).with_path(path))?;
+        let res = if meta.is_dir() {
+            fs::remove_dir(path)
+        } else {
+            fs::remove_file(path)
+        };
+        res.map_err(|e| AppError::io(&e).with_path(path))
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        Path::new(dir).join(name).to_string_lossy().to_string()
+    }
+
+    fn local_path(&self, path: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(path))
+    }
+}
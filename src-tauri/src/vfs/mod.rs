@@ -0,0 +1,281 @@
+// src-tauri/src/vfs/mod.rs
+//
+// Virtual filesystem layer. Commands resolve a path to a provider and a
+// provider-side path, then only talk to the `VfsProvider` trait, so the
+// local disk is just one provider and remote backends plug in without
+// duplicating every command.
+//
+// Path forms:
+//   - local:  anything else ("C:\\Users", "/home/me"), checked by PathGuard
+//   - remote: "<scheme>://<connection id>/<path>", e.g. "sftp://3f9a.../home/me",
+//     "s3://3f9a.../bucket/photos" or "mtp://3f9a.../Internal shared storage/DCIM"
+//
+// The local disk keeps its fast paths where they matter (parallel folder
+// scan, same-volume rename, symlink copies); everything that crosses a
+// provider boundary goes through the trait.
+//
+// Remote providers are mounted under a random connection id by their
+// connect_* command (sftp.rs, webdav.rs, s3.rs, mtp.rs) and dropped again
+// by disconnect_remote.
+//
+// State: .manage(Vfs::default())
+
+mod local;
+mod mtp;
+mod s3;
+mod sftp;
+mod webdav;
+
+pub use local::LocalProvider;
+pub use mtp::{connect_mtp, list_mtp_devices};
+pub use s3::connect_s3;
+pub use sftp::connect_sftp;
+pub use webdav::connect_webdav;
+
+use crate::error::{AppError, ErrorCode};
+use crate::path_guard::{PathGuard, PathGuardError};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+/// One directory entry, the same shape for every provider.
+#[derive(Debug, Clone)]
+pub struct VfsEntry {
+    pub name: String,
+    /// Provider-side path of the entry.
+    pub path: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Bytes; 0 for directories.
+    pub size: u64,
+    /// Seconds since UNIX_EPOCH; 0 when unknown.
+    pub modified: u64,
+    pub hidden: bool,
+}
+
+pub type VfsDirIter = Box<dyn Iterator<Item = Result<VfsEntry, AppError>> + Send>;
+
+/// A storage backend. Paths are provider-side paths (no scheme / id).
+/// Implementations are blocking: call them from spawn_blocking.
+pub trait VfsProvider: Send + Sync {
+    /// URI scheme of remote paths ("sftp", ...); "file" for the local disk.
+    fn scheme(&self) -> &'static str;
+
+    /// Entries of a directory, lazily where the backend allows it.
+    fn list(&self, path: &str) -> Result<VfsDirIter, AppError>;
+
+    /// Metadata of one entry, without following a final symlink.
+    fn stat(&self, path: &str) -> Result<VfsEntry, AppError>;
+
+    fn read(&self, path: &str) -> Result<Box<dyn Read + Send>, AppError>;
+
+    /// Create a new file for writing; fails if something exists at `path`.
+    fn write(&self, path: &str) -> Result<Box<dyn Write + Send>, AppError>;
+
+    /// write() when the final size is known up front (the copy engine),
+    /// for backends that must announce it before the data (MTP).
+    fn write_sized(&self, path: &str, _size: u64) -> Result<Box<dyn Write + Send>, AppError> {
+        self.write(path)
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError>;
+
+    /// Remove a file or an empty directory.
+    fn delete(&self, path: &str) -> Result<(), AppError>;
+
+    /// Child path of `dir` in this provider's syntax.
+    fn join(&self, dir: &str, name: &str) -> String;
+
+    /// The same path on the local disk, for local-only features
+    /// (extended metadata, OS shell). None for remote providers.
+    fn local_path(&self, _path: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Abort in-flight listings / transfers on this connection; the
+    /// connection itself stays usable.
+    fn cancel(&self) {}
+
+    /// Called once when the connection is dropped from the registry.
+    fn close(&self) {}
+}
+
+/// Reader / writer that stops at a connection's cancel token
+/// (VfsProvider::cancel).
+pub struct Cancellable<T> {
+    pub inner: T,
+    pub token: CancellationToken,
+}
+
+pub fn cancelled_io() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "Cancelled")
+}
+
+impl<T: Read> Read for Cancellable<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.token.is_cancelled() {
+            return Err(cancelled_io());
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl<T: Write> Write for Cancellable<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.token.is_cancelled() {
+            return Err(cancelled_io());
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A path resolved to its provider.
+#[derive(Clone)]
+pub struct VfsPath {
+    pub provider: Arc<dyn VfsProvider>,
+    pub path: String,
+}
+
+impl VfsPath {
+    pub fn is_local(&self) -> bool {
+        self.provider.local_path(&self.path).is_some()
+    }
+
+    pub fn join(&self, name: &str) -> VfsPath {
+        VfsPath {
+            provider: self.provider.clone(),
+            path: self.provider.join(&self.path, name),
+        }
+    }
+}
+
+/// Provider registry in Tauri managed state.
+pub struct Vfs {
+    local: Arc<LocalProvider>,
+    /// Remote connections by connection id.
+    remotes: RwLock<HashMap<String, Arc<dyn VfsProvider>>>,
+}
+
+impl Default for Vfs {
+    fn default() -> Self {
+        Vfs {
+            local: Arc::new(LocalProvider),
+            remotes: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl Vfs {
+    /// Map a frontend path to its provider. Local paths must pass `guard`
+    /// and come back in their checked, canonical form.
+    pub fn resolve(&self, guard: &PathGuard, path: &str) -> Result<VfsPath, AppError> {
+        self.resolve_with(path, |p| guard.check(p))
+    }
+
+    /// resolve() for transfer sources: a local symlink is copied or moved
+    /// as the link itself (PathGuard::check_entry). What it points to must
+    /// pass too, since uploads read through it.
+    pub fn resolve_all(&self, guard: &PathGuard, paths: &[String]) -> Result<Vec<VfsPath>, AppError> {
+        paths
+            .iter()
+            .map(|p| self.resolve_with(p, |p| guard.check(p).and_then(|_| guard.check_entry(p))))
+            .collect()
+    }
+
+    fn resolve_with(
+        &self,
+        path: &str,
+        check_local: impl FnOnce(&str) -> Result<PathBuf, PathGuardError>,
+    ) -> Result<VfsPath, AppError> {
+        let Some((scheme, rest)) = path.split_once("://") else {
+            let checked = check_local(path)?;
+            return Ok(VfsPath {
+                provider: self.local.clone(),
+                path: checked.to_string_lossy().to_string(),
+            });
+        };
+        let (id, remote_path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let provider = self
+            .remotes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .filter(|p| p.scheme() == scheme)
+            .cloned()
+            .ok_or_else(|| {
+                AppError::new(ErrorCode::NotFound, format!("No open connection for {}", path))
+                    .with_path(path)
+            })?;
+        Ok(VfsPath {
+            provider,
+            path: remote_path.to_string(),
+        })
+    }
+
+    /// Register a remote connection; returns its new connection id.
+    pub fn mount(&self, provider: Arc<dyn VfsProvider>) -> Result<String, AppError> {
+        let mut bytes = [0u8; 8];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| AppError::new(ErrorCode::Internal, "System random generator failed"))?;
+        let id = hex::encode(bytes);
+        self.remotes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), provider);
+        Ok(id)
+    }
+
+    fn remote(&self, id: &str) -> Result<Arc<dyn VfsProvider>, AppError> {
+        self.remotes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No open connection {}", id)))
+    }
+}
+
+/// Frontend path of `path` on connection `id`, e.g. "sftp://<id>/home/me".
+pub fn remote_uri(scheme: &str, id: &str, path: &str) -> String {
+    format!("{}://{}{}", scheme, id, path)
+}
+
+/// Abort everything running on a connection (listings, transfers) without
+/// closing it. GPS ops using it finish with errors / "cancelled".
+///
+/// Frontend can call:
+///   invoke('cancel_remote', { connectionId })
+#[tauri::command]
+pub fn cancel_remote(vfs: State<'_, Vfs>, connection_id: String) -> Result<(), AppError> {
+    vfs.remote(&connection_id)?.cancel();
+    Ok(())
+}
+
+/// Close a remote connection; its paths stop resolving.
+///
+/// Frontend can call:
+///   invoke('disconnect_remote', { connectionId })
+#[tauri::command]
+pub fn disconnect_remote(vfs: State<'_, Vfs>, connection_id: String) -> Result<(), AppError> {
+    let provider = vfs
+        .remotes
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&connection_id)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No open connection {}", connection_id)))?;
+    provider.cancel();
+    provider.close();
+    Ok(())
+}
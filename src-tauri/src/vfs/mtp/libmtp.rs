@@ -0,0 +1,641 @@
+// src-tauri/src/vfs/mtp/libmtp.rs
+//
+// MtpBackend on libmtp (Linux, macOS). The library is loaded at runtime
+// so the app starts and runs without it; only the MTP commands need it
+// (apt install libmtp9 / dnf install libmtp / brew install libmtp).
+//
+// Object ids are "<storage id>:<item id>", with ROOT_ITEM for the top
+// folder of a storage. libmtp is not thread-safe per device: every call
+// takes the device lock, and transfers keep it until they finish, running
+// on their own thread and streaming through a bounded channel.
+//
+// Only the leading fields of libmtp's public structs are declared; they
+// have been stable since libmtp 1.0.
+
+use super::{MtpBackend, MtpDeviceInfo, MtpObject, ROOT_ID};
+use crate::error::{AppError, ErrorCode};
+use libloading::Library;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::io::{self, Read, Write};
+use std::ptr;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: &[&str] = &[
+    "libmtp.9.dylib",
+    "/opt/homebrew/lib/libmtp.9.dylib",
+    "/usr/local/lib/libmtp.9.dylib",
+];
+#[cfg(not(target_os = "macos"))]
+const LIBRARY_NAMES: &[&str] = &["libmtp.so.9", "libmtp.so"];
+
+/// LIBMTP_FILES_AND_FOLDERS_ROOT: the top folder of a storage.
+const ROOT_ITEM: u32 = 0xFFFF_FFFF;
+/// Chunks buffered between a transfer thread and the copy engine.
+const TRANSFER_QUEUE: usize = 4;
+
+// LIBMTP_error_number_t
+const ERROR_NONE: c_int = 0;
+const ERROR_NO_DEVICE_ATTACHED: c_int = 5;
+// LIBMTP_filetype_t
+const FILETYPE_FOLDER: c_int = 0;
+const FILETYPE_UNKNOWN: c_int = 44;
+// LIBMTP_HANDLER_RETURN_*
+const HANDLER_OK: u16 = 0;
+const HANDLER_ERROR: u16 = 1;
+const HANDLER_CANCEL: u16 = 2;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct DeviceEntry {
+    vendor: *const c_char,
+    vendor_id: u16,
+    product: *const c_char,
+    product_id: u16,
+    device_flags: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct RawDevice {
+    device_entry: DeviceEntry,
+    bus_location: u32,
+    devnum: u8,
+}
+
+/// LIBMTP_mtpdevice_t, up to the storage list.
+#[repr(C)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct MtpDevice {
+    object_bitsize: u8,
+    params: *mut c_void,
+    usbinfo: *mut c_void,
+    storage: *mut Storage,
+}
+
+#[repr(C)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct Storage {
+    id: u32,
+    storage_type: u16,
+    filesystem_type: u16,
+    access_capability: u16,
+    max_capacity: u64,
+    free_space_in_bytes: u64,
+    free_space_in_objects: u64,
+    storage_description: *mut c_char,
+    volume_identifier: *mut c_char,
+    next: *mut Storage,
+    prev: *mut Storage,
+}
+
+#[repr(C)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct FileT {
+    item_id: u32,
+    parent_id: u32,
+    storage_id: u32,
+    filename: *mut c_char,
+    filesize: u64,
+    modificationdate: libc::time_t,
+    filetype: c_int,
+    next: *mut FileT,
+}
+
+#[repr(C)]
+#[allow(dead_code)] // mirrors libmtp.h
+struct ErrorT {
+    errornumber: c_int,
+    error_text: *mut c_char,
+    next: *mut ErrorT,
+}
+
+type PutFn = unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut u8, *mut u32) -> u16;
+type GetFn = unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut u8, *mut u32) -> u16;
+
+struct Api {
+    init: unsafe extern "C" fn(),
+    detect_raw_devices: unsafe extern "C" fn(*mut *mut RawDevice, *mut c_int) -> c_int,
+    open_raw_device_uncached: unsafe extern "C" fn(*mut RawDevice) -> *mut MtpDevice,
+    release_device: unsafe extern "C" fn(*mut MtpDevice),
+    get_friendlyname: unsafe extern "C" fn(*mut MtpDevice) -> *mut c_char,
+    get_storage: unsafe extern "C" fn(*mut MtpDevice, c_int) -> c_int,
+    get_files_and_folders: unsafe extern "C" fn(*mut MtpDevice, u32, u32) -> *mut FileT,
+    new_file_t: unsafe extern "C" fn() -> *mut FileT,
+    destroy_file_t: unsafe extern "C" fn(*mut FileT),
+    get_file_to_handler:
+        unsafe extern "C" fn(*mut MtpDevice, u32, PutFn, *mut c_void, *const c_void, *const c_void) -> c_int,
+    send_file_from_handler:
+        unsafe extern "C" fn(*mut MtpDevice, GetFn, *mut c_void, *mut FileT, *const c_void, *const c_void) -> c_int,
+    create_folder: unsafe extern "C" fn(*mut MtpDevice, *mut c_char, u32, u32) -> u32,
+    delete_object: unsafe extern "C" fn(*mut MtpDevice, u32) -> c_int,
+    get_errorstack: unsafe extern "C" fn(*mut MtpDevice) -> *mut ErrorT,
+    clear_errorstack: unsafe extern "C" fn(*mut MtpDevice),
+    _lib: Library,
+}
+
+impl Api {
+    fn load() -> Result<Api, String> {
+        let lib = LIBRARY_NAMES
+            .iter()
+            // SAFETY: libmtp has no load-time side effects beyond libusb's.
+            .find_map(|name| unsafe { Library::new(name) }.ok())
+            .ok_or_else(|| format!("none of {} could be loaded", LIBRARY_NAMES.join(", ")))?;
+
+        macro_rules! sym {
+            ($name:literal) => {
+                // SAFETY: the field types match libmtp.h.
+                *unsafe { lib.get(concat!($name, "\0").as_bytes()) }.map_err(|e| e.to_string())?
+            };
+        }
+        let api = Api {
+            init: sym!("LIBMTP_Init"),
+            detect_raw_devices: sym!("LIBMTP_Detect_Raw_Devices"),
+            open_raw_device_uncached: sym!("LIBMTP_Open_Raw_Device_Uncached"),
+            release_device: sym!("LIBMTP_Release_Device"),
+            get_friendlyname: sym!("LIBMTP_Get_Friendlyname"),
+            get_storage: sym!("LIBMTP_Get_Storage"),
+            get_files_and_folders: sym!("LIBMTP_Get_Files_And_Folders"),
+            new_file_t: sym!("LIBMTP_new_file_t"),
+            destroy_file_t: sym!("LIBMTP_destroy_file_t"),
+            get_file_to_handler: sym!("LIBMTP_Get_File_To_Handler"),
+            send_file_from_handler: sym!("LIBMTP_Send_File_From_Handler"),
+            create_folder: sym!("LIBMTP_Create_Folder"),
+            delete_object: sym!("LIBMTP_Delete_Object"),
+            get_errorstack: sym!("LIBMTP_Get_Errorstack"),
+            clear_errorstack: sym!("LIBMTP_Clear_Errorstack"),
+            _lib: lib,
+        };
+        unsafe { (api.init)() };
+        Ok(api)
+    }
+}
+
+fn api() -> Result<&'static Api, AppError> {
+    static API: OnceLock<Result<Api, String>> = OnceLock::new();
+    API.get_or_init(Api::load).as_ref().map_err(|e| {
+        AppError::new(
+            ErrorCode::NotFound,
+            "Portable devices need libmtp, which is not installed",
+        )
+        .with_details(e.clone())
+    })
+}
+
+/// Copy of a C string owned by libmtp; None for NULL / empty.
+fn c_string(p: *const c_char) -> Option<String> {
+    if p.is_null() {
+        return None;
+    }
+    let s = unsafe { CStr::from_ptr(p) }.to_string_lossy().trim().to_string();
+    (!s.is_empty()).then_some(s)
+}
+
+fn parse_id(id: &str) -> Result<(u32, u32), AppError> {
+    id.split_once(':')
+        .and_then(|(storage, item)| Some((storage.parse().ok()?, item.parse().ok()?)))
+        .ok_or_else(|| AppError::new(ErrorCode::Internal, format!("Invalid MTP object id {:?}", id)))
+}
+
+fn device_id(raw: &RawDevice) -> String {
+    format!("{}:{}", raw.bus_location, raw.devnum)
+}
+
+fn detect(api: &Api) -> Result<Vec<RawDevice>, AppError> {
+    let mut raw: *mut RawDevice = ptr::null_mut();
+    let mut count: c_int = 0;
+    let rc = unsafe { (api.detect_raw_devices)(&mut raw, &mut count) };
+    let devices = if raw.is_null() || count <= 0 {
+        Vec::new()
+    } else {
+        unsafe { std::slice::from_raw_parts(raw, count as usize) }.to_vec()
+    };
+    if !raw.is_null() {
+        unsafe { libc::free(raw.cast()) };
+    }
+    match rc {
+        ERROR_NONE | ERROR_NO_DEVICE_ATTACHED => Ok(devices),
+        rc => Err(AppError::new(
+            ErrorCode::Io,
+            format!("Cannot look for portable devices (libmtp error {})", rc),
+        )),
+    }
+}
+
+pub fn devices() -> Result<Vec<MtpDeviceInfo>, AppError> {
+    let api = api()?;
+    Ok(detect(api)?
+        .iter()
+        .map(|raw| {
+            let entry = raw.device_entry;
+            let manufacturer = c_string(entry.vendor).unwrap_or_default();
+            let model = c_string(entry.product).unwrap_or_default();
+            let name = match (manufacturer.is_empty(), model.is_empty()) {
+                (_, false) if model.starts_with(&manufacturer) => model.clone(),
+                (false, false) => format!("{} {}", manufacturer, model),
+                _ => format!("MTP device {:04x}:{:04x}", entry.vendor_id, entry.product_id),
+            };
+            MtpDeviceInfo {
+                id: device_id(raw),
+                name,
+                manufacturer,
+                model,
+            }
+        })
+        .collect())
+}
+
+pub fn open(id: &str) -> Result<(Arc<dyn MtpBackend>, String), AppError> {
+    let api = api()?;
+    let mut raw = Box::new(
+        detect(api)?
+            .into_iter()
+            .find(|raw| device_id(raw) == id)
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "The device is no longer connected"))?,
+    );
+    let ptr = unsafe { (api.open_raw_device_uncached)(&mut *raw) };
+    if ptr.is_null() {
+        return Err(AppError::new(
+            ErrorCode::Io,
+            "Cannot open the device. Unlock it, choose \"File transfer\" and close other programs using it",
+        ));
+    }
+    let device = Device { ptr, api, raw };
+    let name = unsafe {
+        let p = (api.get_friendlyname)(ptr);
+        let name = c_string(p);
+        if !p.is_null() {
+            libc::free(p.cast());
+        }
+        name
+    }
+    .or_else(|| c_string(device.raw.device_entry.product))
+    .unwrap_or_else(|| "MTP device".to_string());
+
+    let backend = LibMtpDevice {
+        device: Arc::new(Mutex::new(device)),
+    };
+    Ok((Arc::new(backend), name))
+}
+
+/// An open libmtp device; released when the last user drops it.
+struct Device {
+    ptr: *mut MtpDevice,
+    api: &'static Api,
+    /// Kept alive for the device's lifetime.
+    raw: Box<RawDevice>,
+}
+
+// SAFETY: only used behind LibMtpDevice's mutex.
+unsafe impl Send for Device {}
+
+impl Device {
+    /// The device's error stack as an AppError (and cleared).
+    fn error(&self, what: &str) -> AppError {
+        let mut texts = Vec::new();
+        unsafe {
+            let mut e = (self.api.get_errorstack)(self.ptr);
+            while !e.is_null() {
+                texts.extend(c_string((*e).error_text));
+                e = (*e).next;
+            }
+            (self.api.clear_errorstack)(self.ptr);
+        }
+        let error = AppError::new(ErrorCode::Io, what.to_string());
+        if texts.is_empty() {
+            error
+        } else {
+            error.with_details(texts.join("\n"))
+        }
+    }
+
+    fn has_error(&self) -> bool {
+        !unsafe { (self.api.get_errorstack)(self.ptr) }.is_null()
+    }
+
+    fn clear_error(&self) {
+        unsafe { (self.api.clear_errorstack)(self.ptr) };
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { (self.api.release_device)(self.ptr) };
+    }
+}
+
+struct LibMtpDevice {
+    device: Arc<Mutex<Device>>,
+}
+
+impl LibMtpDevice {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Device> {
+        self.device.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn storages(&self) -> Result<Vec<MtpObject>, AppError> {
+        let device = self.lock();
+        device.clear_error();
+        if unsafe { (device.api.get_storage)(device.ptr, 0) } != 0 {
+            return Err(device.error("Cannot read the device's storages (is it unlocked?)"));
+        }
+        let mut storages = Vec::new();
+        let mut s = unsafe { (*device.ptr).storage };
+        while !s.is_null() {
+            let storage = unsafe { &*s };
+            storages.push(MtpObject {
+                id: format!("{}:{}", storage.id, ROOT_ITEM),
+                name: c_string(storage.storage_description)
+                    .or_else(|| c_string(storage.volume_identifier))
+                    .unwrap_or_else(|| format!("Storage {:08x}", storage.id))
+                    .replace('/', "_"),
+                is_dir: true,
+                size: 0,
+                modified: 0,
+            });
+            s = storage.next;
+        }
+        Ok(storages)
+    }
+}
+
+impl MtpBackend for LibMtpDevice {
+    fn children(&self, parent: &str) -> Result<Vec<MtpObject>, AppError> {
+        if parent == ROOT_ID {
+            return self.storages();
+        }
+        let (storage, item) = parse_id(parent)?;
+        let device = self.lock();
+        device.clear_error();
+        let mut file = unsafe { (device.api.get_files_and_folders)(device.ptr, storage, item) };
+        if file.is_null() && device.has_error() {
+            return Err(device.error("Failed to read directory"));
+        }
+        let mut objects = Vec::new();
+        while !file.is_null() {
+            let f = unsafe { &*file };
+            if let Some(name) = c_string(f.filename) {
+                let is_dir = f.filetype == FILETYPE_FOLDER;
+                objects.push(MtpObject {
+                    id: format!("{}:{}", f.storage_id, f.item_id),
+                    name,
+                    is_dir,
+                    size: if is_dir { 0 } else { f.filesize },
+                    modified: f.modificationdate.max(0) as u64,
+                });
+            }
+            let next = f.next;
+            unsafe { (device.api.destroy_file_t)(file) };
+            file = next;
+        }
+        Ok(objects)
+    }
+
+    fn read(&self, id: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        let (_, item) = parse_id(id)?;
+        if item == ROOT_ITEM {
+            return Err(AppError::new(ErrorCode::NotADirectory, "Not a file"));
+        }
+        let (tx, rx) = mpsc::sync_channel(TRANSFER_QUEUE);
+        let device = self.device.clone();
+        std::thread::spawn(move || {
+            let device = device.lock().unwrap_or_else(|e| e.into_inner());
+            device.clear_error();
+            let rc = unsafe {
+                (device.api.get_file_to_handler)(
+                    device.ptr,
+                    item,
+                    put_data,
+                    (&tx as *const SyncSender<io::Result<Vec<u8>>>).cast_mut().cast(),
+                    ptr::null(),
+                    ptr::null(),
+                )
+            };
+            if rc != 0 {
+                let e = device.error("Download from the device failed");
+                let _ = tx.send(Err(io::Error::other(e.details.unwrap_or(e.message))));
+            }
+        });
+        Ok(Box::new(DownloadReader {
+            rx,
+            buf: Vec::new(),
+            pos: 0,
+        }))
+    }
+
+    fn upload(&self, parent: &str, name: &str, size: u64) -> Result<Box<dyn Write + Send>, AppError> {
+        let (storage, item) = parse_id(parent)?;
+        let name = CString::new(name).map_err(|_| AppError::invalid_input("Invalid file name"))?;
+        let (tx, rx) = mpsc::sync_channel(TRANSFER_QUEUE);
+        let device = self.device.clone();
+        let upload = std::thread::spawn(move || {
+            let device = device.lock().unwrap_or_else(|e| e.into_inner());
+            device.clear_error();
+            let mut source = UploadSource {
+                rx,
+                buf: Vec::new(),
+                pos: 0,
+                done: false,
+            };
+            let rc = unsafe {
+                let file = (device.api.new_file_t)();
+                (*file).filename = libc::strdup(name.as_ptr());
+                (*file).filesize = size;
+                (*file).storage_id = storage;
+                // 0 is the storage's top folder here.
+                (*file).parent_id = if item == ROOT_ITEM { 0 } else { item };
+                (*file).filetype = FILETYPE_UNKNOWN;
+                let rc = (device.api.send_file_from_handler)(
+                    device.ptr,
+                    get_data,
+                    (&mut source as *mut UploadSource).cast(),
+                    file,
+                    ptr::null(),
+                    ptr::null(),
+                );
+                (device.api.destroy_file_t)(file);
+                rc
+            };
+            if rc != 0 {
+                return Err(device.error("Upload to the device failed"));
+            }
+            Ok(())
+        });
+        Ok(Box::new(UploadWriter {
+            tx: Some(tx),
+            upload: Some(upload),
+        }))
+    }
+
+    fn create_dir(&self, parent: &str, name: &str) -> Result<String, AppError> {
+        let (storage, item) = parse_id(parent)?;
+        let name = CString::new(name).map_err(|_| AppError::invalid_input("Invalid folder name"))?;
+        let device = self.lock();
+        device.clear_error();
+        let parent_item = if item == ROOT_ITEM { 0 } else { item };
+        let id = unsafe { (device.api.create_folder)(device.ptr, name.as_ptr().cast_mut(), parent_item, storage) };
+        if id == 0 {
+            return Err(device.error("The device refused to create the folder"));
+        }
+        Ok(format!("{}:{}", storage, id))
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        let (_, item) = parse_id(id)?;
+        if item == ROOT_ITEM {
+            return Err(AppError::invalid_input("A storage cannot be deleted"));
+        }
+        let device = self.lock();
+        device.clear_error();
+        if unsafe { (device.api.delete_object)(device.ptr, item) } != 0 {
+            return Err(device.error("The device refused to delete the object"));
+        }
+        Ok(())
+    }
+}
+
+/// MTPDataPutFunc: hands each downloaded chunk to DownloadReader. A
+/// dropped reader (cancel / error in the copy engine) ends the transfer.
+unsafe extern "C" fn put_data(
+    _params: *mut c_void,
+    private: *mut c_void,
+    sendlen: u32,
+    data: *mut u8,
+    putlen: *mut u32,
+) -> u16 {
+    let tx = unsafe { &*(private as *const SyncSender<io::Result<Vec<u8>>>) };
+    let chunk = unsafe { std::slice::from_raw_parts(data, sendlen as usize) }.to_vec();
+    if tx.send(Ok(chunk)).is_err() {
+        return HANDLER_CANCEL;
+    }
+    unsafe { *putlen = sendlen };
+    HANDLER_OK
+}
+
+/// MTPDataGetFunc: feeds the upload from UploadWriter's chunks.
+unsafe extern "C" fn get_data(
+    _params: *mut c_void,
+    private: *mut c_void,
+    wantlen: u32,
+    data: *mut u8,
+    gotlen: *mut u32,
+) -> u16 {
+    let source = unsafe { &mut *(private as *mut UploadSource) };
+    let out = unsafe { std::slice::from_raw_parts_mut(data, wantlen as usize) };
+    match source.read(out) {
+        Ok(n) if n > 0 || wantlen == 0 => {
+            unsafe { *gotlen = n as u32 };
+            HANDLER_OK
+        }
+        // Fewer bytes than announced.
+        Ok(_) => HANDLER_ERROR,
+        Err(_) => HANDLER_CANCEL,
+    }
+}
+
+struct DownloadReader {
+    rx: Receiver<io::Result<Vec<u8>>>,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for DownloadReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.buf.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.buf = chunk?;
+                    self.pos = 0;
+                }
+                // Transfer thread done.
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+enum Chunk {
+    Data(Vec<u8>),
+    End,
+}
+
+/// Upload data as libmtp pulls it. The writer going away without `End`
+/// aborts the transfer, so the device never keeps a truncated file.
+struct UploadSource {
+    rx: Receiver<Chunk>,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl Read for UploadSource {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let n = out.len().min(self.buf.len() - self.pos);
+                out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            match self.rx.recv() {
+                Ok(Chunk::Data(data)) => {
+                    self.buf = data;
+                    self.pos = 0;
+                }
+                Ok(Chunk::End) => self.done = true,
+                Err(_) => return Err(io::Error::new(io::ErrorKind::Interrupted, "Upload aborted")),
+            }
+        }
+    }
+}
+
+/// Write side of an upload; flush() completes it and reports the result.
+struct UploadWriter {
+    tx: Option<SyncSender<Chunk>>,
+    upload: Option<JoinHandle<Result<(), AppError>>>,
+}
+
+impl UploadWriter {
+    fn finish(&mut self) -> io::Result<()> {
+        let Some(tx) = self.tx.take() else {
+            return Ok(());
+        };
+        let _ = tx.send(Chunk::End);
+        drop(tx);
+        match self.upload.take().map(|h| h.join()) {
+            Some(Ok(Ok(()))) | None => Ok(()),
+            Some(Ok(Err(e))) => Err(io::Error::other(e.details.unwrap_or(e.message))),
+            Some(Err(_)) => Err(io::Error::other("Upload thread panicked")),
+        }
+    }
+}
+
+impl Write for UploadWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let sent = self
+            .tx
+            .as_ref()
+            .is_some_and(|tx| tx.send(Chunk::Data(buf.to_vec())).is_ok());
+        if !sent {
+            // The transfer already ended: report why.
+            self.finish()?;
+            return Err(io::Error::other("Upload already finished"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish()
+    }
+}
@@ -0,0 +1,421 @@
+// src-tauri/src/vfs/mtp/wpd.rs
+//
+// MtpBackend on Windows Portable Devices (WPD), the COM API Explorer uses
+// for phones and cameras. Object ids are WPD object ids; ROOT_ID maps to
+// the "DEVICE" object, whose storage functional objects become the top
+// level folders.
+//
+// The device is created through PortableDeviceFTM (free-threaded
+// marshaler), so it and everything it hands out may be called from any
+// thread; each thread joins the multithreaded apartment on first use.
+
+use super::{MtpBackend, MtpDeviceInfo, MtpObject, ROOT_ID};
+use crate::error::{AppError, ErrorCode};
+use std::cell::Cell;
+use std::io::{self, Read, Write};
+use std::ptr;
+use std::sync::Arc;
+use windows::core::{Interface, GUID, HSTRING, PWSTR};
+use windows::Win32::Devices::PortableDevices::{
+    IPortableDevice, IPortableDeviceContent, IPortableDeviceKeyCollection, IPortableDeviceManager,
+    IPortableDevicePropVariantCollection, IPortableDeviceProperties, IPortableDeviceResources,
+    IPortableDeviceValues, PortableDeviceFTM, PortableDeviceKeyCollection, PortableDeviceManager,
+    PortableDevicePropVariantCollection, PortableDeviceValues, PORTABLE_DEVICE_DELETE_NO_RECURSION, WPD_CLIENT_NAME,
+    WPD_CONTENT_TYPE_FOLDER, WPD_CONTENT_TYPE_FUNCTIONAL_OBJECT, WPD_CONTENT_TYPE_GENERIC_FILE,
+    WPD_FUNCTIONAL_CATEGORY_STORAGE, WPD_FUNCTIONAL_OBJECT_CATEGORY, WPD_OBJECT_CONTENT_TYPE,
+    WPD_OBJECT_DATE_MODIFIED, WPD_OBJECT_FORMAT, WPD_OBJECT_FORMAT_PROPERTIES_ONLY, WPD_OBJECT_FORMAT_UNSPECIFIED,
+    WPD_OBJECT_NAME, WPD_OBJECT_ORIGINAL_FILE_NAME, WPD_OBJECT_PARENT_ID, WPD_OBJECT_SIZE, WPD_RESOURCE_DEFAULT,
+};
+use windows::Win32::Foundation::PROPERTYKEY;
+use windows::Win32::System::Com::StructuredStorage::{PropVariantClear, PropVariantToFileTime, PROPVARIANT};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, IStream, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+    STGC_DEFAULT, STGM_READ,
+};
+use windows::Win32::System::Variant::{PSTF_UTC, VT_LPWSTR};
+
+/// WPD_DEVICE_OBJECT_ID
+const DEVICE_OBJECT_ID: &str = "DEVICE";
+const CLIENT_NAME: &str = "filesUP-ASC";
+/// Object ids fetched per IEnumPortableDeviceObjectIDs::Next call.
+const ENUM_BATCH: usize = 64;
+/// FILETIME ticks (100 ns) between 1601-01-01 and 1970-01-01.
+const UNIX_EPOCH_TICKS: u64 = 116_444_736_000_000_000;
+
+/// Join the multithreaded apartment once per thread. A thread that is
+/// already in an STA keeps it; the FTM objects work there too.
+fn com_init() {
+    thread_local!(static JOINED: Cell<bool> = const { Cell::new(false) });
+    JOINED.with(|joined| {
+        if !joined.replace(true) {
+            let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+        }
+    });
+}
+
+fn create<T: Interface>(clsid: &GUID) -> windows::core::Result<T> {
+    unsafe { CoCreateInstance(clsid, None, CLSCTX_INPROC_SERVER) }
+}
+
+/// Win32 failures keep their usual codes (access denied, disk full, ...).
+fn com_error(e: windows::core::Error, what: &str) -> AppError {
+    let hr = e.code().0 as u32;
+    let error = if hr >> 16 == 0x8007 {
+        AppError::io(&io::Error::from_raw_os_error((hr & 0xFFFF) as i32))
+    } else {
+        AppError::new(ErrorCode::Io, e.message())
+    };
+    error.context(what)
+}
+
+/// Copy and free a string allocated by WPD.
+fn take_string(p: PWSTR) -> String {
+    if p.is_null() {
+        return String::new();
+    }
+    let s = unsafe { p.to_string() }.unwrap_or_default();
+    unsafe { CoTaskMemFree(Some(p.0 as *const _)) };
+    s
+}
+
+/// IPortableDeviceManager's size-then-fill string getters.
+fn manager_string(get: impl Fn(PWSTR, *mut u32) -> windows::core::Result<()>) -> String {
+    let mut len = 0u32;
+    if get(PWSTR::null(), &mut len).is_err() || len == 0 {
+        return String::new();
+    }
+    let mut buf = vec![0u16; len as usize];
+    if get(PWSTR(buf.as_mut_ptr()), &mut len).is_err() {
+        return String::new();
+    }
+    let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16_lossy(&buf[..end]).trim().to_string()
+}
+
+fn device_names(manager: &IPortableDeviceManager, id: &str) -> (String, String, String) {
+    let pnp_id = HSTRING::from(id);
+    let name = manager_string(|buf, len| unsafe { manager.GetDeviceFriendlyName(&pnp_id, buf, len) });
+    let manufacturer = manager_string(|buf, len| unsafe { manager.GetDeviceManufacturer(&pnp_id, buf, len) });
+    let model = manager_string(|buf, len| unsafe { manager.GetDeviceDescription(&pnp_id, buf, len) });
+    (name, manufacturer, model)
+}
+
+pub fn devices() -> Result<Vec<MtpDeviceInfo>, AppError> {
+    com_init();
+    let list = || -> windows::core::Result<Vec<MtpDeviceInfo>> {
+        let manager: IPortableDeviceManager = create(&PortableDeviceManager)?;
+        let mut count = 0u32;
+        unsafe {
+            let _ = manager.RefreshDeviceList();
+            manager.GetDevices(ptr::null_mut(), &mut count)?;
+        }
+        let mut ids = vec![PWSTR::null(); count as usize];
+        if count > 0 {
+            unsafe { manager.GetDevices(ids.as_mut_ptr(), &mut count)? };
+        }
+        ids.truncate(count as usize);
+        Ok(ids
+            .into_iter()
+            .map(|p| {
+                let id = take_string(p);
+                let (name, manufacturer, model) = device_names(&manager, &id);
+                MtpDeviceInfo {
+                    name: if name.is_empty() { model.clone() } else { name },
+                    id,
+                    manufacturer,
+                    model,
+                }
+            })
+            .collect())
+    };
+    list().map_err(|e| com_error(e, "Cannot list portable devices"))
+}
+
+pub fn open(id: &str) -> Result<(Arc<dyn MtpBackend>, String), AppError> {
+    com_init();
+    let device: IPortableDevice = create(&PortableDeviceFTM).map_err(|e| com_error(e, "Cannot open the device"))?;
+    let client: IPortableDeviceValues =
+        create(&PortableDeviceValues).map_err(|e| com_error(e, "Cannot open the device"))?;
+    unsafe {
+        client
+            .SetStringValue(&WPD_CLIENT_NAME, &HSTRING::from(CLIENT_NAME))
+            .and_then(|_| device.Open(&HSTRING::from(id), &client))
+    }
+    .map_err(|e| com_error(e, "Cannot open the device. Unlock it and choose \"File transfer\""))?;
+
+    let parts = || -> windows::core::Result<WpdDevice> {
+        let content = unsafe { device.Content()? };
+        let keys: IPortableDeviceKeyCollection = create(&PortableDeviceKeyCollection)?;
+        for key in [
+            &WPD_OBJECT_NAME,
+            &WPD_OBJECT_ORIGINAL_FILE_NAME,
+            &WPD_OBJECT_CONTENT_TYPE,
+            &WPD_OBJECT_SIZE,
+            &WPD_OBJECT_DATE_MODIFIED,
+            &WPD_FUNCTIONAL_OBJECT_CATEGORY,
+        ] {
+            unsafe { keys.Add(key)? };
+        }
+        Ok(WpdDevice {
+            properties: unsafe { content.Properties()? },
+            resources: unsafe { content.Transfer()? },
+            content,
+            keys,
+            device: device.clone(),
+        })
+    };
+    let wpd = parts().map_err(|e| {
+        let _ = unsafe { device.Close() };
+        com_error(e, "Cannot read the device")
+    })?;
+
+    let name = create::<IPortableDeviceManager>(&PortableDeviceManager)
+        .ok()
+        .map(|manager| device_names(&manager, id).0)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Portable device".to_string());
+    Ok((Arc::new(wpd), name))
+}
+
+struct WpdDevice {
+    device: IPortableDevice,
+    content: IPortableDeviceContent,
+    properties: IPortableDeviceProperties,
+    resources: IPortableDeviceResources,
+    /// Properties read for every listed object.
+    keys: IPortableDeviceKeyCollection,
+}
+
+// SAFETY: created through PortableDeviceFTM, whose objects are
+// free-threaded (see header).
+unsafe impl Send for WpdDevice {}
+unsafe impl Sync for WpdDevice {}
+
+impl Drop for WpdDevice {
+    fn drop(&mut self) {
+        com_init();
+        let _ = unsafe { self.device.Close() };
+    }
+}
+
+impl WpdDevice {
+    fn object_ids(&self, parent: &str) -> windows::core::Result<Vec<String>> {
+        let objects = unsafe { self.content.EnumObjects(0, &HSTRING::from(parent), None)? };
+        let mut ids = Vec::new();
+        let mut batch = [PWSTR::null(); ENUM_BATCH];
+        loop {
+            let mut fetched = 0u32;
+            unsafe { objects.Next(&mut batch, &mut fetched).ok()? };
+            ids.extend(batch[..fetched as usize].iter().map(|p| take_string(*p)));
+            if (fetched as usize) < ENUM_BATCH {
+                return Ok(ids);
+            }
+        }
+    }
+
+    /// None for objects that are not storages / folders / files (at the
+    /// top level only storages are kept).
+    fn object(&self, id: String, top_level: bool) -> windows::core::Result<Option<MtpObject>> {
+        let values = unsafe { self.properties.GetValues(&HSTRING::from(&id), &self.keys)? };
+        let string = |key: &PROPERTYKEY| unsafe { values.GetStringValue(key) }.map(take_string).ok();
+        let content_type = unsafe { values.GetGuidValue(&WPD_OBJECT_CONTENT_TYPE) }.ok();
+        let is_storage = content_type == Some(WPD_CONTENT_TYPE_FUNCTIONAL_OBJECT)
+            && unsafe { values.GetGuidValue(&WPD_FUNCTIONAL_OBJECT_CATEGORY) }.ok()
+                == Some(WPD_FUNCTIONAL_CATEGORY_STORAGE);
+        if top_level != is_storage {
+            return Ok(None);
+        }
+        let is_dir = is_storage || content_type == Some(WPD_CONTENT_TYPE_FOLDER);
+        let name = string(&WPD_OBJECT_ORIGINAL_FILE_NAME)
+            .filter(|n| !n.is_empty() && !is_storage)
+            .or_else(|| string(&WPD_OBJECT_NAME))
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| id.clone())
+            .replace('/', "_");
+        let size = if is_dir {
+            0
+        } else {
+            unsafe { values.GetUnsignedLargeIntegerValue(&WPD_OBJECT_SIZE) }.unwrap_or(0)
+        };
+        Ok(Some(MtpObject {
+            id,
+            name,
+            is_dir,
+            size,
+            modified: modified(&values),
+        }))
+    }
+
+    fn new_object(&self, parent: &str, name: &str) -> windows::core::Result<IPortableDeviceValues> {
+        let values: IPortableDeviceValues = create(&PortableDeviceValues)?;
+        let name = HSTRING::from(name);
+        unsafe {
+            values.SetStringValue(&WPD_OBJECT_PARENT_ID, &HSTRING::from(parent))?;
+            values.SetStringValue(&WPD_OBJECT_NAME, &name)?;
+            values.SetStringValue(&WPD_OBJECT_ORIGINAL_FILE_NAME, &name)?;
+        }
+        Ok(values)
+    }
+}
+
+/// WPD_OBJECT_DATE_MODIFIED as seconds since UNIX_EPOCH; 0 when missing.
+fn modified(values: &IPortableDeviceValues) -> u64 {
+    let Ok(mut value) = (unsafe { values.GetValue(&WPD_OBJECT_DATE_MODIFIED) }) else {
+        return 0;
+    };
+    let time = unsafe { PropVariantToFileTime(&value, PSTF_UTC) };
+    let _ = unsafe { PropVariantClear(&mut value) };
+    time.map(|t| {
+        let ticks = (u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime);
+        ticks.saturating_sub(UNIX_EPOCH_TICKS) / 10_000_000
+    })
+    .unwrap_or(0)
+}
+
+impl MtpBackend for WpdDevice {
+    fn children(&self, parent: &str) -> Result<Vec<MtpObject>, AppError> {
+        com_init();
+        let top_level = parent == ROOT_ID;
+        let parent = if top_level { DEVICE_OBJECT_ID } else { parent };
+        let list = || -> windows::core::Result<Vec<MtpObject>> {
+            let mut objects = Vec::new();
+            for id in self.object_ids(parent)? {
+                objects.extend(self.object(id, top_level)?);
+            }
+            Ok(objects)
+        };
+        list().map_err(|e| com_error(e, "Failed to read directory"))
+    }
+
+    fn read(&self, id: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        com_init();
+        let mut stream: Option<IStream> = None;
+        let mut optimal = 0u32;
+        unsafe {
+            self.resources
+                .GetStream(&HSTRING::from(id), &WPD_RESOURCE_DEFAULT, STGM_READ.0, &mut optimal, &mut stream)
+        }
+        .map_err(|e| com_error(e, "Download from the device failed"))?;
+        let stream = stream.ok_or_else(|| AppError::new(ErrorCode::Io, "The device returned no data"))?;
+        Ok(Box::new(StreamReader(stream)))
+    }
+
+    fn upload(&self, parent: &str, name: &str, size: u64) -> Result<Box<dyn Write + Send>, AppError> {
+        com_init();
+        let start = || -> windows::core::Result<Option<IStream>> {
+            let values = self.new_object(parent, name)?;
+            let mut stream: Option<IStream> = None;
+            let mut optimal = 0u32;
+            unsafe {
+                values.SetUnsignedLargeIntegerValue(&WPD_OBJECT_SIZE, size)?;
+                values.SetGuidValue(&WPD_OBJECT_CONTENT_TYPE, &WPD_CONTENT_TYPE_GENERIC_FILE)?;
+                values.SetGuidValue(&WPD_OBJECT_FORMAT, &WPD_OBJECT_FORMAT_UNSPECIFIED)?;
+                self.content
+                    .CreateObjectWithPropertiesAndData(&values, &mut stream, &mut optimal, ptr::null_mut())?;
+            }
+            Ok(stream)
+        };
+        let stream = start()
+            .map_err(|e| com_error(e, "Upload to the device failed"))?
+            .ok_or_else(|| AppError::new(ErrorCode::Io, "The device accepted no data"))?;
+        Ok(Box::new(StreamWriter {
+            stream,
+            committed: false,
+        }))
+    }
+
+    fn create_dir(&self, parent: &str, name: &str) -> Result<String, AppError> {
+        com_init();
+        let create = || -> windows::core::Result<String> {
+            let values = self.new_object(parent, name)?;
+            let mut id = PWSTR::null();
+            unsafe {
+                values.SetGuidValue(&WPD_OBJECT_CONTENT_TYPE, &WPD_CONTENT_TYPE_FOLDER)?;
+                values.SetGuidValue(&WPD_OBJECT_FORMAT, &WPD_OBJECT_FORMAT_PROPERTIES_ONLY)?;
+                self.content.CreateObjectWithPropertiesOnly(&values, &mut id)?;
+            }
+            Ok(take_string(id))
+        };
+        create().map_err(|e| com_error(e, "The device refused to create the folder"))
+    }
+
+    fn delete(&self, id: &str) -> Result<(), AppError> {
+        com_init();
+        let delete = || -> windows::core::Result<()> {
+            let ids: IPortableDevicePropVariantCollection = create(&PortableDevicePropVariantCollection)?;
+            let mut wide: Vec<u16> = id.encode_utf16().chain(Some(0)).collect();
+            let mut value = PROPVARIANT::default();
+            unsafe {
+                // Add() copies the value; `wide` outlives the call.
+                let inner = &mut *value.Anonymous.Anonymous;
+                inner.vt = VT_LPWSTR;
+                inner.Anonymous.pwszVal = PWSTR(wide.as_mut_ptr());
+                ids.Add(&value)?;
+                self.content
+                    .Delete(PORTABLE_DEVICE_DELETE_NO_RECURSION.0 as u32, &ids, ptr::null_mut())
+            }
+        };
+        delete().map_err(|e| com_error(e, "The device refused to delete the object"))
+    }
+}
+
+struct StreamReader(IStream);
+
+// SAFETY: see WpdDevice.
+unsafe impl Send for StreamReader {}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        com_init();
+        let mut read = 0u32;
+        let len = buf.len().min(u32::MAX as usize) as u32;
+        unsafe { self.0.Read(buf.as_mut_ptr().cast(), len, Some(&mut read)) }
+            .ok()
+            .map_err(|e| io::Error::other(e.message()))?;
+        Ok(read as usize)
+    }
+}
+
+/// Upload stream; flush() commits the object, dropping it uncommitted
+/// discards the partial file.
+struct StreamWriter {
+    stream: IStream,
+    committed: bool,
+}
+
+// SAFETY: see WpdDevice.
+unsafe impl Send for StreamWriter {}
+
+impl Write for StreamWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.committed {
+            return Err(io::Error::other("Upload already finished"));
+        }
+        com_init();
+        let mut written = 0u32;
+        let len = buf.len().min(u32::MAX as usize) as u32;
+        unsafe { self.stream.Write(buf.as_ptr().cast(), len, Some(&mut written)) }
+            .ok()
+            .map_err(|e| io::Error::other(e.message()))?;
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.committed {
+            return Ok(());
+        }
+        com_init();
+        unsafe { self.stream.Commit(STGC_DEFAULT) }.map_err(|e| io::Error::other(e.message()))?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for StreamWriter {
+    fn drop(&mut self) {
+        if !self.committed {
+            com_init();
+            let _ = unsafe { self.stream.Revert() };
+        }
+    }
+}
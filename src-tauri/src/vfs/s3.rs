@@ -0,0 +1,856 @@
+// src-tauri/src/vfs/s3.rs
+//
+// Amazon S3 and S3-compatible stores (MinIO, Wasabi, R2, ...) as a
+// VfsProvider. connect_s3 takes a saved profile
+// (settings.system.s3_profiles) plus the secret access key (given, or from
+// the keychain) and mounts it
+// as "s3://<connection id>/".
+//
+// Paths are "/<bucket>/<key>". The root lists buckets (or holds just the
+// profile's bucket); inside a bucket "/" in keys is treated as a folder
+// separator:
+//   list    ListObjectsV2 with delimiter "/" (CommonPrefixes = folders),
+//           paged lazily
+//   stat    HEAD object, else "any key under prefix/" = folder
+//   read    GET, streamed
+//   write   single PUT below PART_SIZE, multipart upload above it
+//           (aborted when the copy fails or is cancelled)
+//   mkdir   zero-byte "prefix/" marker object, as the AWS console does
+//   delete  object, or folder marker when nothing else is under it
+//
+// Upload / download progress comes from the copy engine: downloading to a
+// local folder is start_copy_operation with s3:// sources and a local
+// destination, uploading is the reverse.
+//
+// Requests are signed with AWS Signature V4 (ring for HMAC / SHA-256) and
+// sent with the blocking reqwest client; payloads are sent as
+// UNSIGNED-PAYLOAD so bodies can be streamed.
+
+use super::{remote_uri, Cancellable, Vfs, VfsDirIter, VfsEntry, VfsProvider};
+use crate::error::{AppError, ErrorCode};
+use crate::secrets;
+use crate::settings::{S3Profile, SettingsState};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Body, Client, Response};
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+use ring::{digest, hmac};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::State;
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Listings, HEAD, DELETE, multipart bookkeeping.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Multipart part size (S3 minimum is 5 MiB, maximum 10 000 parts, so
+/// objects up to ~160 GB).
+const PART_SIZE: usize = 16 * 1024 * 1024;
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// RFC 3986 unreserved characters stay as they are; everything else is
+/// percent-encoded, as SigV4 canonicalisation requires.
+const URI_ENCODE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3ConnectionInfo {
+    pub connection_id: String,
+    /// "s3://<id>/", or "s3://<id>/<bucket>/" for single-bucket profiles.
+    pub root_uri: String,
+}
+
+/// Signing client; cheap to clone (paged listings keep their own copy).
+#[derive(Clone)]
+struct S3Client {
+    http: Client,
+    /// Endpoint without bucket, e.g. "https://s3.eu-west-1.amazonaws.com".
+    endpoint: Url,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    /// "endpoint/bucket/key" instead of "bucket.endpoint/key" (MinIO).
+    path_style: bool,
+}
+
+fn uri_encode(s: &str) -> String {
+    utf8_percent_encode(s, URI_ENCODE).to_string()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest::digest(&digest::SHA256, data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+        .as_ref()
+        .to_vec()
+}
+
+impl S3Client {
+    /// URL plus canonical URI of an object (or bucket when `key` is empty).
+    fn url(&self, bucket: &str, key: &str, query: &[(&str, &str)]) -> (Url, String) {
+        let key_path: String = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        let mut url = self.endpoint.clone();
+        let path = if bucket.is_empty() {
+            "/".to_string()
+        } else if self.path_style {
+            format!("/{}/{}", uri_encode(bucket), key_path)
+        } else {
+            let host = format!("{}.{}", bucket, self.endpoint.host_str().unwrap_or(""));
+            let _ = url.set_host(Some(&host));
+            format!("/{}", key_path)
+        };
+
+        let mut pairs: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k), uri_encode(v))).collect();
+        pairs.sort();
+        let query = pairs
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        // Set the already-encoded path / query verbatim so the request line
+        // is exactly what was signed.
+        url.set_path(&path);
+        url.set_query(if query.is_empty() { None } else { Some(&query) });
+        (url, path)
+    }
+
+    /// Build and send a signed request.
+    fn send(
+        &self,
+        method: Method,
+        bucket: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Option<Body>,
+        timeout: Option<Duration>,
+    ) -> Result<Response, AppError> {
+        let (url, canonical_uri) = self.url(bucket, key, query);
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or(""), port),
+            None => url.host_str().unwrap_or("").to_string(),
+        };
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            canonical_uri,
+            url.query().unwrap_or(""),
+            host,
+            UNSIGNED_PAYLOAD,
+            amz_date,
+            signed_headers,
+            UNSIGNED_PAYLOAD
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let key_date = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), &date);
+        let key_region = hmac_sha256(&key_date, &self.region);
+        let key_service = hmac_sha256(&key_region, "s3");
+        let key_signing = hmac_sha256(&key_service, "aws4_request");
+        let signature = hex::encode(hmac_sha256(&key_signing, &string_to_sign));
+
+        let mut req = self
+            .http
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", UNSIGNED_PAYLOAD)
+            .header(
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            );
+        if let Some(body) = body {
+            req = req.body(body);
+        }
+        if let Some(timeout) = timeout {
+            req = req.timeout(timeout);
+        }
+
+        let what = format!("/{}/{}", bucket, key);
+        let res = req.send().map_err(|e| transport_error(e, &self.endpoint))?;
+        if res.status().is_success() {
+            Ok(res)
+        } else {
+            Err(status_error(res, &what))
+        }
+    }
+
+    /// Signed request whose XML answer is returned as text.
+    fn send_xml(
+        &self,
+        method: Method,
+        bucket: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Option<Body>,
+    ) -> Result<String, AppError> {
+        self.send(method, bucket, key, query, body, Some(REQUEST_TIMEOUT))?
+            .text()
+            .map_err(|e| transport_error(e, &self.endpoint))
+    }
+
+    /// One ListObjectsV2 page.
+    fn list_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        token: Option<&str>,
+        max_keys: &str,
+    ) -> Result<ListPage, AppError> {
+        let mut query = vec![
+            ("list-type", "2"),
+            ("delimiter", "/"),
+            ("prefix", prefix),
+            ("max-keys", max_keys),
+        ];
+        if let Some(token) = token {
+            query.push(("continuation-token", token));
+        }
+        let xml = self.send_xml(Method::GET, bucket, "", &query, None)?;
+        let doc = parse_xml(&xml)?;
+        let root = doc.root_element();
+
+        let folders = children(root, "CommonPrefixes")
+            .filter_map(|p| child_text(p, "Prefix"))
+            .collect();
+        let files = children(root, "Contents")
+            .filter_map(|c| {
+                let key = child_text(c, "Key")?;
+                // The folder's own marker object.
+                if key == prefix {
+                    return None;
+                }
+                let name = key.rsplit('/').next().unwrap_or("").to_string();
+                Some(VfsEntry {
+                    hidden: name.starts_with('.'),
+                    name,
+                    path: format!("/{}/{}", bucket, key),
+                    is_dir: false,
+                    is_symlink: false,
+                    size: child_text(c, "Size").and_then(|s| s.parse().ok()).unwrap_or(0),
+                    modified: child_text(c, "LastModified")
+                        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                        .map(|t| t.timestamp().max(0) as u64)
+                        .unwrap_or(0),
+                })
+            })
+            .collect();
+        let next = match child_text(root, "IsTruncated").as_deref() {
+            Some("true") => child_text(root, "NextContinuationToken"),
+            _ => None,
+        };
+        Ok(ListPage { folders, files, next })
+    }
+}
+
+struct ListPage {
+    /// Common prefixes ("a/b/"), i.e. sub-folders.
+    folders: Vec<String>,
+    files: Vec<VfsEntry>,
+    /// Continuation token when the listing is truncated.
+    next: Option<String>,
+}
+
+fn parse_xml(xml: &str) -> Result<roxmltree::Document<'_>, AppError> {
+    roxmltree::Document::parse(xml)
+        .map_err(|e| AppError::new(ErrorCode::Io, format!("Invalid S3 response: {}", e)))
+}
+
+/// Direct children named `name` (S3 XML uses a default namespace, so match
+/// on the local name only).
+fn children<'a, 'i>(node: roxmltree::Node<'a, 'i>, name: &'static str) -> impl Iterator<Item = roxmltree::Node<'a, 'i>> {
+    node.children().filter(move |n| n.tag_name().name() == name)
+}
+
+fn child_text(node: roxmltree::Node, name: &'static str) -> Option<String> {
+    children(node, name).next().and_then(|n| n.text()).map(str::to_string)
+}
+
+fn folder_entry(bucket: &str, prefix: &str) -> VfsEntry {
+    let key = prefix.trim_end_matches('/');
+    let name = key.rsplit('/').next().unwrap_or(bucket).to_string();
+    VfsEntry {
+        hidden: name.starts_with('.'),
+        name,
+        path: if key.is_empty() {
+            format!("/{}", bucket)
+        } else {
+            format!("/{}/{}", bucket, key)
+        },
+        is_dir: true,
+        is_symlink: false,
+        size: 0,
+        modified: 0,
+    }
+}
+
+/// Split "/bucket/a/b" into ("bucket", "a/b").
+fn split_path(path: &str) -> (&str, &str) {
+    let trimmed = path.trim_matches('/');
+    trimmed.split_once('/').unwrap_or((trimmed, ""))
+}
+
+fn status_error(res: Response, path: &str) -> AppError {
+    let status = res.status();
+    let body = res.text().unwrap_or_default();
+    let (s3_code, s3_message) = roxmltree::Document::parse(&body)
+        .ok()
+        .map(|doc| {
+            let root = doc.root_element();
+            (child_text(root, "Code"), child_text(root, "Message"))
+        })
+        .unwrap_or((None, None));
+
+    let code = match (status, s3_code.as_deref()) {
+        (_, Some("NoSuchKey" | "NoSuchBucket" | "NoSuchUpload")) => ErrorCode::NotFound,
+        (StatusCode::NOT_FOUND, _) => ErrorCode::NotFound,
+        (StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN, _) => ErrorCode::PermissionDenied,
+        (_, Some("BucketAlreadyExists" | "BucketAlreadyOwnedByYou")) => ErrorCode::AlreadyExists,
+        (StatusCode::PRECONDITION_FAILED, _) => ErrorCode::AlreadyExists,
+        (_, Some("EntityTooLarge" | "QuotaExceeded")) => ErrorCode::DiskFull,
+        _ => ErrorCode::Io,
+    };
+    let message = match (s3_code, s3_message) {
+        (Some(c), Some(m)) => format!("S3: {} ({})", m, c),
+        (Some(c), None) => format!("S3: {}", c),
+        _ => format!("S3: server answered {}", status),
+    };
+    AppError::new(code, message).with_path(path)
+}
+
+fn transport_error(e: reqwest::Error, endpoint: &Url) -> AppError {
+    let mut chain = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(s) = source {
+        chain.push_str(": ");
+        chain.push_str(&s.to_string());
+        source = s.source();
+    }
+    let host = endpoint.host_str().unwrap_or("endpoint");
+    if chain.to_lowercase().contains("certificate") {
+        return AppError::new(
+            ErrorCode::Certificate,
+            format!("The TLS certificate of {} was rejected", host),
+        )
+        .with_details(chain);
+    }
+    AppError::new(ErrorCode::Io, format!("Cannot reach {}", host)).with_details(chain)
+}
+
+/// Lazily paged folder listing.
+struct ListPages {
+    client: S3Client,
+    bucket: String,
+    prefix: String,
+    next: Option<String>,
+    started: bool,
+    buffer: VecDeque<VfsEntry>,
+    token: CancellationToken,
+}
+
+impl Iterator for ListPages {
+    type Item = Result<VfsEntry, AppError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.token.is_cancelled() {
+                return Some(Err(AppError::cancelled()));
+            }
+            if let Some(entry) = self.buffer.pop_front() {
+                return Some(Ok(entry));
+            }
+            if self.started && self.next.is_none() {
+                return None;
+            }
+            self.started = true;
+            let page = self
+                .client
+                .list_page(&self.bucket, &self.prefix, self.next.as_deref(), "1000");
+            match page {
+                Ok(page) => {
+                    self.next = page.next;
+                    self.buffer.extend(page.folders.iter().map(|p| folder_entry(&self.bucket, p)));
+                    self.buffer.extend(page.files);
+                }
+                Err(e) => {
+                    self.next = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Upload sink: buffers one part, switches to a multipart upload once the
+/// object outgrows it. Nothing becomes visible until flush(); dropping the
+/// writer earlier aborts the multipart upload.
+struct ObjectWriter {
+    client: S3Client,
+    bucket: String,
+    key: String,
+    buf: Vec<u8>,
+    upload_id: Option<String>,
+    /// (part number, ETag) of uploaded parts.
+    parts: Vec<(u32, String)>,
+    done: bool,
+}
+
+impl ObjectWriter {
+    fn upload_part(&mut self) -> Result<(), AppError> {
+        if self.upload_id.is_none() {
+            let xml = self
+                .client
+                .send_xml(Method::POST, &self.bucket, &self.key, &[("uploads", "")], None)?;
+            let doc = parse_xml(&xml)?;
+            let id = child_text(doc.root_element(), "UploadId")
+                .ok_or_else(|| AppError::new(ErrorCode::Io, "S3: no UploadId in CreateMultipartUpload response"))?;
+            self.upload_id = Some(id);
+        }
+        let upload_id = self.upload_id.clone().unwrap_or_default();
+        let number = (self.parts.len() + 1).to_string();
+        let data = std::mem::take(&mut self.buf);
+        let res = self.client.send(
+            Method::PUT,
+            &self.bucket,
+            &self.key,
+            &[("partNumber", &number), ("uploadId", &upload_id)],
+            Some(Body::from(data)),
+            None,
+        )?;
+        let etag = header(res.headers(), "etag")
+            .ok_or_else(|| AppError::new(ErrorCode::Io, "S3: no ETag for uploaded part"))?;
+        self.parts.push((self.parts.len() as u32 + 1, etag));
+        Ok(())
+    }
+
+    fn complete(&mut self) -> Result<(), AppError> {
+        let Some(upload_id) = self.upload_id.clone() else {
+            let data = std::mem::take(&mut self.buf);
+            self.client
+                .send(Method::PUT, &self.bucket, &self.key, &[], Some(Body::from(data)), None)?;
+            return Ok(());
+        };
+        if !self.buf.is_empty() {
+            self.upload_part()?;
+        }
+        let mut xml = String::from("<CompleteMultipartUpload>");
+        for (number, etag) in &self.parts {
+            xml.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                number,
+                etag.replace('&', "&amp;").replace('<', "&lt;")
+            ));
+        }
+        xml.push_str("</CompleteMultipartUpload>");
+        let answer = self.client.send_xml(
+            Method::POST,
+            &self.bucket,
+            &self.key,
+            &[("uploadId", &upload_id)],
+            Some(Body::from(xml)),
+        )?;
+        // CompleteMultipartUpload can fail after a 200 status.
+        let doc = parse_xml(&answer)?;
+        if doc.root_element().tag_name().name() == "Error" {
+            let message = child_text(doc.root_element(), "Message").unwrap_or_else(|| "upload failed".into());
+            return Err(AppError::new(ErrorCode::Io, format!("S3: {}", message)));
+        }
+        Ok(())
+    }
+
+    fn abort(&mut self) {
+        if let Some(upload_id) = self.upload_id.take() {
+            let res = self.client.send(
+                Method::DELETE,
+                &self.bucket,
+                &self.key,
+                &[("uploadId", &upload_id)],
+                None,
+                Some(REQUEST_TIMEOUT),
+            );
+            if let Err(e) = res {
+                tracing::warn!("[S3] Failed to abort multipart upload of {}: {}", self.key, e.message);
+            }
+        }
+    }
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn to_io(e: AppError) -> io::Error {
+    io::Error::other(e.message)
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.done {
+            return Err(io::Error::other("Upload already finished"));
+        }
+        let n = data.len().min(PART_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == PART_SIZE {
+            self.upload_part().map_err(to_io)?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        self.complete().map_err(to_io)?;
+        self.upload_id = None;
+        Ok(())
+    }
+}
+
+impl Drop for ObjectWriter {
+    fn drop(&mut self) {
+        // Unfinished or failed multipart upload: free the stored parts.
+        self.abort();
+    }
+}
+
+pub struct S3Connection {
+    client: S3Client,
+    /// Profile restricted to one bucket: the root shows only that bucket.
+    bucket: Option<String>,
+    /// Replaced by a fresh token on cancel().
+    token: Mutex<CancellationToken>,
+}
+
+impl S3Connection {
+    fn token(&self) -> CancellationToken {
+        self.token.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn list_buckets(&self) -> Result<Vec<VfsEntry>, AppError> {
+        if let Some(bucket) = &self.bucket {
+            return Ok(vec![folder_entry(bucket, "")]);
+        }
+        let xml = self.client.send_xml(Method::GET, "", "", &[], None)?;
+        let doc = parse_xml(&xml)?;
+        let buckets = children(doc.root_element(), "Buckets").flat_map(|b| children(b, "Bucket"));
+        Ok(buckets
+            .filter_map(|b| {
+                let name = child_text(b, "Name")?;
+                let mut entry = folder_entry(&name, "");
+                entry.modified = child_text(b, "CreationDate")
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|t| t.timestamp().max(0) as u64)
+                    .unwrap_or(0);
+                Some(entry)
+            })
+            .collect())
+    }
+
+    /// Whether anything exists under "prefix/" (a folder in S3 terms).
+    fn folder_exists(&self, bucket: &str, key: &str) -> Result<bool, AppError> {
+        let prefix = if key.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", key)
+        };
+        let page = self.client.list_page(bucket, &prefix, None, "2")?;
+        if !page.folders.is_empty() || !page.files.is_empty() {
+            return Ok(true);
+        }
+        // A lone marker object is filtered out of `files` but still counts.
+        self.object_exists(bucket, &prefix)
+    }
+
+    fn object_exists(&self, bucket: &str, key: &str) -> Result<bool, AppError> {
+        match self
+            .client
+            .send(Method::HEAD, bucket, key, &[], None, Some(REQUEST_TIMEOUT))
+        {
+            Ok(_) => Ok(true),
+            Err(e) if e.code == ErrorCode::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl VfsProvider for S3Connection {
+    fn scheme(&self) -> &'static str {
+        "s3"
+    }
+
+    fn list(&self, path: &str) -> Result<VfsDirIter, AppError> {
+        let (bucket, key) = split_path(path);
+        if bucket.is_empty() {
+            let buckets = self.list_buckets().map_err(|e| e.context("Failed to list buckets"))?;
+            return Ok(Box::new(buckets.into_iter().map(Ok)));
+        }
+        let prefix = if key.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", key)
+        };
+        let mut pages = ListPages {
+            client: self.client.clone(),
+            bucket: bucket.to_string(),
+            prefix,
+            next: None,
+            started: false,
+            buffer: VecDeque::new(),
+            token: self.token(),
+        };
+        // Fetch the first page now so a bad bucket / permissions fail here.
+        match pages.next() {
+            Some(Err(e)) => Err(e.context("Failed to read directory")),
+            first => Ok(Box::new(first.into_iter().chain(pages))),
+        }
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsEntry, AppError> {
+        let (bucket, key) = split_path(path);
+        if bucket.is_empty() {
+            return Ok(VfsEntry {
+                name: String::new(),
+                path: "/".to_string(),
+                is_dir: true,
+                is_symlink: false,
+                size: 0,
+                modified: 0,
+                hidden: false,
+            });
+        }
+        if key.is_empty() {
+            self.client
+                .send(Method::HEAD, bucket, "", &[], None, Some(REQUEST_TIMEOUT))?;
+            return Ok(folder_entry(bucket, ""));
+        }
+        match self
+            .client
+            .send(Method::HEAD, bucket, key, &[], None, Some(REQUEST_TIMEOUT))
+        {
+            Ok(res) => {
+                let headers = res.headers();
+                let name = key.rsplit('/').next().unwrap_or("").to_string();
+                Ok(VfsEntry {
+                    hidden: name.starts_with('.'),
+                    name,
+                    path: format!("/{}/{}", bucket, key),
+                    is_dir: false,
+                    is_symlink: false,
+                    size: header(headers, "content-length")
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(0),
+                    modified: header(headers, "last-modified")
+                        .and_then(|s| chrono::DateTime::parse_from_rfc2822(&s).ok())
+                        .map(|t| t.timestamp().max(0) as u64)
+                        .unwrap_or(0),
+                })
+            }
+            Err(e) if e.code == ErrorCode::NotFound => {
+                if self.folder_exists(bucket, key)? {
+                    Ok(folder_entry(bucket, key))
+                } else {
+                    Err(e)
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read(&self, path: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        let (bucket, key) = split_path(path);
+        let res = self.client.send(Method::GET, bucket, key, &[], None, None)?;
+        Ok(Box::new(Cancellable {
+            inner: res,
+            token: self.token(),
+        }))
+    }
+
+    fn write(&self, path: &str) -> Result<Box<dyn Write + Send>, AppError> {
+        let (bucket, key) = split_path(path);
+        if key.is_empty() {
+            return Err(AppError::invalid_input("Files can only be created inside a bucket").with_path(path));
+        }
+        // PUT silently overwrites: keep the create-new contract.
+        if self.object_exists(bucket, key)? {
+            return Err(AppError::new(ErrorCode::AlreadyExists, format!("Already exists: {}", path)).with_path(path));
+        }
+        Ok(Box::new(Cancellable {
+            inner: ObjectWriter {
+                client: self.client.clone(),
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                buf: Vec::new(),
+                upload_id: None,
+                parts: Vec::new(),
+                done: false,
+            },
+            token: self.token(),
+        }))
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError> {
+        let (bucket, key) = split_path(path);
+        if bucket.is_empty() {
+            return Ok(());
+        }
+        if key.is_empty() {
+            // Buckets are managed outside the file manager.
+            return self.stat(path).map(|_| ()).map_err(|e| e.context("Bucket does not exist"));
+        }
+        if self.object_exists(bucket, key)? {
+            return Err(AppError::new(ErrorCode::AlreadyExists, format!("Not a directory: {}", path)).with_path(path));
+        }
+        if self.folder_exists(bucket, key)? {
+            return Ok(());
+        }
+        self.client
+            .send(
+                Method::PUT,
+                bucket,
+                &format!("{}/", key),
+                &[],
+                Some(Body::from(Vec::new())),
+                Some(REQUEST_TIMEOUT),
+            )
+            .map(|_| ())
+            .map_err(|e| e.context("Failed to create directory"))
+    }
+
+    fn delete(&self, path: &str) -> Result<(), AppError> {
+        let (bucket, key) = split_path(path);
+        if key.is_empty() {
+            return Err(AppError::invalid_input("Buckets cannot be deleted from here").with_path(path));
+        }
+        if self.object_exists(bucket, key)? {
+            return self
+                .client
+                .send(Method::DELETE, bucket, key, &[], None, Some(REQUEST_TIMEOUT))
+                .map(|_| ());
+        }
+        let prefix = format!("{}/", key);
+        let page = self.client.list_page(bucket, &prefix, None, "2")?;
+        if !page.folders.is_empty() || !page.files.is_empty() {
+            return Err(AppError::new(ErrorCode::Io, format!("Directory not empty: {}", path)).with_path(path));
+        }
+        // Removing the marker (if any) is all it takes; a folder without
+        // one disappears with its last object.
+        self.client
+            .send(Method::DELETE, bucket, &prefix, &[], None, Some(REQUEST_TIMEOUT))
+            .map(|_| ())
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+
+    fn cancel(&self) {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        token.cancel();
+        *token = CancellationToken::new();
+    }
+}
+
+/// Connect to a saved S3 profile and mount it. Without `secret_access_key`
+/// the one stored in the keychain (secrets.rs, "s3:<profile>") is used.
+///
+/// Frontend can call:
+///   invoke<S3ConnectionInfo>('connect_s3', { profile: 'minio', secretAccessKey })
+///   invoke<S3ConnectionInfo>('connect_s3', { profile: 'minio' })
+#[tauri::command]
+pub async fn connect_s3(
+    settings: State<'_, SettingsState>,
+    vfs: State<'_, Vfs>,
+    profile: String,
+    secret_access_key: Option<String>,
+) -> Result<S3ConnectionInfo, AppError> {
+    let profile = settings
+        .get()
+        .system
+        .s3_profiles
+        .into_iter()
+        .find(|p| p.name == profile)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No S3 profile named {:?}", profile)))?;
+
+    let root = match profile.bucket.trim() {
+        "" => "/".to_string(),
+        bucket => format!("/{}/", bucket),
+    };
+    let conn = task::spawn_blocking(move || {
+        let secret_access_key = match secret_access_key {
+            Some(key) => key,
+            None => secrets::load("s3", &profile.name)?.ok_or_else(|| {
+                AppError::new(ErrorCode::NotFound, format!("No saved secret key for {:?}", profile.name))
+            })?,
+        };
+        open(&profile, secret_access_key)
+    })
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("S3 task failed: {}", e)))??;
+
+    let id = vfs.mount(Arc::new(conn))?;
+    Ok(S3ConnectionInfo {
+        root_uri: remote_uri("s3", &id, &root),
+        connection_id: id,
+    })
+}
+
+fn open(profile: &S3Profile, secret_access_key: String) -> Result<S3Connection, AppError> {
+    let region = match profile.region.trim() {
+        "" => "us-east-1".to_string(),
+        region => region.to_string(),
+    };
+    let endpoint = match profile.endpoint.trim() {
+        "" => format!("https://s3.{}.amazonaws.com", region),
+        endpoint => endpoint.to_string(),
+    };
+    let endpoint = Url::parse(&endpoint)
+        .map_err(|e| AppError::invalid_input(format!("Invalid S3 endpoint {:?}: {}", endpoint, e)))?;
+
+    let http = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(None)
+        .build()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("HTTP client error: {}", e)))?;
+
+    let bucket = Some(profile.bucket.trim().to_string()).filter(|b| !b.is_empty());
+    let conn = S3Connection {
+        client: S3Client {
+            http,
+            endpoint,
+            region,
+            access_key_id: profile.access_key_id.trim().to_string(),
+            secret_access_key,
+            path_style: profile.path_style,
+        },
+        bucket,
+        token: Mutex::new(CancellationToken::new()),
+    };
+
+    // Checks endpoint, TLS and credentials in one go.
+    match &conn.bucket {
+        Some(bucket) => conn.client.list_page(bucket, "", None, "1").map(|_| ())?,
+        None => conn.list_buckets().map(|_| ())?,
+    }
+    tracing::info!("[S3] Connected to {}", conn.client.endpoint.host_str().unwrap_or(""));
+    Ok(conn)
+}
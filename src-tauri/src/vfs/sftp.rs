@@ -0,0 +1,450 @@
+// src-tauri/src/vfs/sftp.rs
+//
+// SFTP as a VfsProvider (ssh2 / libssh2). connect_sftp opens one SSH
+// session with one SFTP channel and mounts it in the Vfs registry; its
+// paths are "sftp://<connection id>/<absolute remote path>".
+//
+// - Auth: password, private key file (optional passphrase) or ssh-agent.
+// - Host keys are checked against ~/.ssh/known_hosts before any
+//   credentials are sent, in two phases:
+//     1) connect_sftp to an unknown host stops after the handshake with
+//        HOST_KEY_UNKNOWN, carrying the SHA256 fingerprint as `hostKey`;
+//     2) once the user confirmed it, connect_sftp again with
+//        acceptedHostKey = that fingerprint: the key is appended to
+//        known_hosts and only then does authentication start.
+//   A changed key, or a known_hosts that can't be read or checked, is
+//   refused.
+// - Keep-alive: a background thread sends SSH keep-alives every
+//   KEEPALIVE_SECS until the connection is closed.
+// - Cancellation: cancel_remote aborts in-flight reads / writes /
+//   listings on this connection (checked per chunk / entry) and leaves
+//   the session open; disconnect_remote also closes it.
+//
+// libssh2 serializes calls on one session, so transfers on the same
+// connection take turns; open a second connection for parallel work.
+
+use super::{remote_uri, Cancellable, Vfs, VfsDirIter, VfsEntry, VfsProvider};
+use crate::error::{AppError, ErrorCode};
+use serde::{Deserialize, Serialize};
+use ssh2::{CheckResult, FileStat, HashType, KnownHostFileKind, OpenFlags, OpenType, Session, Sftp};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_PORT: u16 = 22;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Upper bound for one blocking libssh2 call (a dead link fails, not hangs).
+const SESSION_TIMEOUT_MS: u32 = 60_000;
+const KEEPALIVE_SECS: u32 = 30;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SftpAuth {
+    #[serde(rename_all = "camelCase")]
+    Password { username: String, password: String },
+    #[serde(rename_all = "camelCase")]
+    Key {
+        username: String,
+        private_key_path: String,
+        passphrase: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Agent { username: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpConnectionInfo {
+    pub connection_id: String,
+    /// Frontend path of the login directory, e.g. "sftp://<id>/home/me".
+    pub home_uri: String,
+    /// "SHA256:..." as printed by ssh-keygen -l.
+    pub host_key_fingerprint: String,
+    /// False when the key was just added to known_hosts (first connection).
+    pub host_key_known: bool,
+}
+
+pub struct SftpConnection {
+    session: Session,
+    sftp: Sftp,
+    label: String,
+    /// Replaced by a fresh token on cancel(); cancelled for good on close().
+    token: Mutex<CancellationToken>,
+    closed: CancellationToken,
+}
+
+impl SftpConnection {
+    fn token(&self) -> CancellationToken {
+        self.token.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+fn sftp_error(e: ssh2::Error, path: &str) -> AppError {
+    // SSH_FX_* status codes from the SFTP protocol.
+    let code = match e.code() {
+        ssh2::ErrorCode::SFTP(2) | ssh2::ErrorCode::SFTP(10) => ErrorCode::NotFound,
+        ssh2::ErrorCode::SFTP(3) => ErrorCode::PermissionDenied,
+        ssh2::ErrorCode::SFTP(11) => ErrorCode::AlreadyExists,
+        ssh2::ErrorCode::SFTP(14) => ErrorCode::DiskFull,
+        ssh2::ErrorCode::SFTP(18) => ErrorCode::NotADirectory,
+        _ => ErrorCode::Io,
+    };
+    AppError::new(code, format!("SFTP: {}", e.message())).with_path(path)
+}
+
+fn entry_from(path: String, name: String, stat: &FileStat) -> VfsEntry {
+    let file_type = stat.file_type();
+    VfsEntry {
+        hidden: name.starts_with('.'),
+        name,
+        path,
+        is_dir: file_type.is_dir(),
+        is_symlink: file_type.is_symlink(),
+        size: if file_type.is_dir() { 0 } else { stat.size.unwrap_or(0) },
+        modified: stat.mtime.unwrap_or(0),
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+impl VfsProvider for SftpConnection {
+    fn scheme(&self) -> &'static str {
+        "sftp"
+    }
+
+    fn list(&self, path: &str) -> Result<VfsDirIter, AppError> {
+        let token = self.token();
+        let entries = self
+            .sftp
+            .readdir(Path::new(path))
+            .map_err(|e| sftp_error(e, path).context("Failed to read directory"))?;
+        Ok(Box::new(entries.into_iter().map(move |(child, stat)| {
+            if token.is_cancelled() {
+                return Err(AppError::cancelled());
+            }
+            let child = child.to_string_lossy().to_string();
+            Ok(entry_from(child.clone(), file_name(&child), &stat))
+        })))
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsEntry, AppError> {
+        let stat = self
+            .sftp
+            .lstat(Path::new(path))
+            .map_err(|e| sftp_error(e, path))?;
+        Ok(entry_from(path.to_string(), file_name(path), &stat))
+    }
+
+    fn read(&self, path: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        let file = self.sftp.open(Path::new(path)).map_err(|e| sftp_error(e, path))?;
+        Ok(Box::new(Cancellable {
+            inner: file,
+            token: self.token(),
+        }))
+    }
+
+    fn write(&self, path: &str) -> Result<Box<dyn Write + Send>, AppError> {
+        let file = self
+            .sftp
+            .open_mode(
+                Path::new(path),
+                OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+                0o644,
+                OpenType::File,
+            )
+            .map_err(|e| sftp_error(e, path))?;
+        Ok(Box::new(Cancellable {
+            inner: file,
+            token: self.token(),
+        }))
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError> {
+        // create_dir_all semantics, like the local provider.
+        let mut missing: Vec<&Path> = Vec::new();
+        let mut current = Some(Path::new(path));
+        while let Some(dir) = current {
+            match self.sftp.lstat(dir) {
+                Ok(stat) if stat.is_dir() => break,
+                Ok(_) => {
+                    return Err(AppError::new(
+                        ErrorCode::AlreadyExists,
+                        format!("Not a directory: {}", dir.display()),
+                    )
+                    .with_path(dir))
+                }
+                Err(_) => missing.push(dir),
+            }
+            current = dir.parent();
+        }
+        for dir in missing.into_iter().rev() {
+            self.sftp
+                .mkdir(dir, 0o755)
+                .map_err(|e| sftp_error(e, &dir.to_string_lossy()).context("Failed to create directory"))?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, path: &str) -> Result<(), AppError> {
+        let p = Path::new(path);
+        let stat = self.sftp.lstat(p).map_err(|e| sftp_error(e, path))?;
+        let res = if stat.is_dir() {
+            self.sftp.rmdir(p)
+        } else {
+            self.sftp.unlink(p)
+        };
+        res.map_err(|e| sftp_error(e, path))
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+
+    fn cancel(&self) {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        token.cancel();
+        *token = CancellationToken::new();
+    }
+
+    fn close(&self) {
+        self.closed.cancel();
+        if let Err(e) = self.session.disconnect(None, "Closed by FilesUP", None) {
+            tracing::warn!("[SFTP] Disconnect from {} failed: {e}", self.label);
+        }
+    }
+}
+
+/// Open an SFTP connection and mount it. `port` defaults to 22.
+/// An unknown host fails with HOST_KEY_UNKNOWN before anything is sent;
+/// call again with the confirmed `acceptedHostKey` to trust and save it.
+///
+/// Frontend can call:
+///   invoke<SftpConnectionInfo>('connect_sftp', { host: 'files.example.com', port: 22,
+///     auth: { type: 'password', username: 'me', password }, acceptedHostKey: null })
+///   auth: { type: 'key', username: 'me', privateKeyPath: '~/.ssh/id_ed25519', passphrase: null }
+///   auth: { type: 'agent', username: 'me' }
+#[tauri::command]
+pub async fn connect_sftp(
+    app: AppHandle,
+    vfs: State<'_, Vfs>,
+    host: String,
+    port: Option<u16>,
+    auth: SftpAuth,
+    accepted_host_key: Option<String>,
+) -> Result<SftpConnectionInfo, AppError> {
+    let host = host.trim().to_string();
+    if host.is_empty() {
+        return Err(AppError::invalid_input("No host given"));
+    }
+    let port = port.unwrap_or(DEFAULT_PORT);
+    let home_dir = app.path().home_dir().ok();
+
+    let opened = task::spawn_blocking(move || open(&host, port, &auth, accepted_host_key.as_deref(), home_dir));
+    let (conn, home, fingerprint, known) = opened
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("SFTP task failed: {}", e)))??;
+
+    let conn = Arc::new(conn);
+    let id = vfs.mount(conn.clone())?;
+    spawn_keepalive(conn);
+
+    Ok(SftpConnectionInfo {
+        home_uri: remote_uri("sftp", &id, &home),
+        connection_id: id,
+        host_key_fingerprint: fingerprint,
+        host_key_known: known,
+    })
+}
+
+type Opened = (SftpConnection, String, String, bool);
+
+fn open(
+    host: &str,
+    port: u16,
+    auth: &SftpAuth,
+    accepted_host_key: Option<&str>,
+    home_dir: Option<PathBuf>,
+) -> Result<Opened, AppError> {
+    let label = format!("{}:{}", host, port);
+    let connect_error = |e: io::Error| AppError::io(&e).context(&format!("Cannot connect to {}", label));
+
+    let addr = (host, port)
+        .to_socket_addrs()
+        .map_err(connect_error)?
+        .next()
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("Unknown host: {}", host)))?;
+    let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).map_err(connect_error)?;
+
+    let ssh_error = |e: ssh2::Error| AppError::new(ErrorCode::Io, format!("SSH ({}): {}", label, e.message()));
+    let mut session = Session::new().map_err(ssh_error)?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(SESSION_TIMEOUT_MS);
+    session.handshake().map_err(ssh_error)?;
+
+    // Nothing secret goes out before the host key checks out.
+    let (fingerprint, known) = check_host_key(&session, host, port, accepted_host_key, home_dir.as_deref())?;
+
+    match auth {
+        SftpAuth::Password { username, password } => session.userauth_password(username, password),
+        SftpAuth::Key {
+            username,
+            private_key_path,
+            passphrase,
+        } => {
+            let key = expand_home(private_key_path, home_dir.as_deref());
+            session.userauth_pubkey_file(username, None, &key, passphrase.as_deref())
+        }
+        SftpAuth::Agent { username } => session.userauth_agent(username),
+    }
+    .map_err(|e| {
+        AppError::new(
+            ErrorCode::PermissionDenied,
+            format!("Authentication failed for {}: {}", label, e.message()),
+        )
+    })?;
+    if !session.authenticated() {
+        return Err(AppError::new(
+            ErrorCode::PermissionDenied,
+            format!("Authentication failed for {}", label),
+        ));
+    }
+
+    session.set_keepalive(true, KEEPALIVE_SECS);
+    let sftp = session.sftp().map_err(ssh_error)?;
+    let home = sftp
+        .realpath(Path::new("."))
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "/".to_string());
+
+    tracing::info!("[SFTP] Connected to {}", label);
+    let conn = SftpConnection {
+        session,
+        sftp,
+        label,
+        token: Mutex::new(CancellationToken::new()),
+        closed: CancellationToken::new(),
+    };
+    Ok((conn, home, fingerprint, known))
+}
+
+/// Returns the fingerprint and whether the host was already known. An
+/// unknown host passes only when `accepted` is its fingerprint; its key
+/// is then saved to known_hosts.
+fn check_host_key(
+    session: &Session,
+    host: &str,
+    port: u16,
+    accepted: Option<&str>,
+    home_dir: Option<&Path>,
+) -> Result<(String, bool), AppError> {
+    let refuse = |message: String| AppError::new(ErrorCode::AccessDenied, message);
+    let (key, key_type) = session
+        .host_key()
+        .ok_or_else(|| refuse(format!("{} sent no host key; refusing to connect", host)))?;
+    let fingerprint = session
+        .host_key_hash(HashType::Sha256)
+        .map(|hash| {
+            use base64::Engine;
+            format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash))
+        })
+        .ok_or_else(|| refuse(format!("Cannot fingerprint the host key of {}; refusing to connect", host)))?;
+    let ssh_dir = home_dir
+        .map(|h| h.join(".ssh"))
+        .ok_or_else(|| refuse("No home folder to keep known_hosts in; refusing to connect".to_string()))?;
+    let file = ssh_dir.join("known_hosts");
+
+    let ssh_error =
+        |e: ssh2::Error| AppError::new(ErrorCode::Io, format!("known_hosts: {}", e.message())).with_path(&file);
+    let mut known_hosts = session.known_hosts().map_err(ssh_error)?;
+    if file.exists() {
+        known_hosts.read_file(&file, KnownHostFileKind::OpenSSH).map_err(ssh_error)?;
+    }
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok((fingerprint, true)),
+        CheckResult::Mismatch => Err(refuse(format!(
+            "Host key for {} does not match known_hosts ({}); refusing to connect",
+            host, fingerprint
+        ))),
+        CheckResult::Failure => Err(refuse(format!(
+            "Cannot check the host key for {} against known_hosts; refusing to connect",
+            host
+        ))),
+        CheckResult::NotFound if accepted == Some(fingerprint.as_str()) => {
+            // A fresh list holds just the new entry; appending its line
+            // keeps the user's comments and entries libssh2 can't parse,
+            // which write_file would drop.
+            let mut entry = session.known_hosts().map_err(ssh_error)?;
+            let name = if port == DEFAULT_PORT {
+                host.to_string()
+            } else {
+                format!("[{}]:{}", host, port)
+            };
+            entry.add(&name, key, "", key_type.into()).map_err(ssh_error)?;
+            let added = entry.iter().map_err(ssh_error)?.pop();
+            let line = match added {
+                Some(added) => entry.write_string(&added, KnownHostFileKind::OpenSSH).map_err(ssh_error)?,
+                None => return Err(AppError::new(ErrorCode::Internal, "known_hosts: entry was not added")),
+            };
+            append_known_host(&ssh_dir, &file, &line).map_err(|e| AppError::io(&e).with_path(&file))?;
+            tracing::info!("[SFTP] Added the host key of {} ({}) to known_hosts", name, fingerprint);
+            Ok((fingerprint, false))
+        }
+        CheckResult::NotFound => Err(AppError::host_key_unknown(host, &fingerprint)),
+    }
+}
+
+fn append_known_host(ssh_dir: &Path, file: &Path, line: &str) -> io::Result<()> {
+    let mut dir = std::fs::DirBuilder::new();
+    dir.recursive(true);
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+        dir.mode(0o700);
+        options.mode(0o600);
+    }
+    dir.create(ssh_dir)?;
+    let mut known_hosts = options.open(file)?;
+    // The file may not end in a newline.
+    let needs_newline = std::fs::read(file).is_ok_and(|data| data.last().is_some_and(|&b| b != b'\n'));
+    if needs_newline {
+        known_hosts.write_all(b"\n")?;
+    }
+    known_hosts.write_all(line.trim_end().as_bytes())?;
+    known_hosts.write_all(b"\n")
+}
+
+fn expand_home(path: &str, home_dir: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Send keep-alives until the connection is closed.
+fn spawn_keepalive(conn: Arc<SftpConnection>) {
+    std::thread::spawn(move || {
+        let mut last = Instant::now();
+        while !conn.closed.is_cancelled() {
+            std::thread::sleep(Duration::from_secs(1));
+            if last.elapsed() < Duration::from_secs(KEEPALIVE_SECS as u64) {
+                continue;
+            }
+            if let Err(e) = conn.session.keepalive_send() {
+                tracing::warn!("[SFTP] Keep-alive to {} failed: {e}", conn.label);
+            }
+            last = Instant::now();
+        }
+    });
+}
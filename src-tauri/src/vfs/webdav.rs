@@ -0,0 +1,502 @@
+// src-tauri/src/vfs/webdav.rs
+//
+// WebDAV (Nextcloud, ownCloud, Apache mod_dav, ...) as a VfsProvider.
+// connect_webdav takes a saved profile (settings.system.webdav_profiles)
+// plus the password (given, or from the keychain) and mounts the profile URL as
+// "webdav://<connection id>/".
+//
+//   list    PROPFIND Depth: 1
+//   stat    PROPFIND Depth: 0
+//   read    GET, streamed
+//   write   PUT with If-None-Match: * (never overwrites), streamed from
+//           the copy engine through a bounded channel
+//   mkdir   MKCOL (parents first)
+//   delete  DELETE; folders only when empty (WebDAV deletes recursively)
+//
+// TLS certificate failures come back as ErrorCode::Certificate so the UI
+// can offer the profile's `accept_invalid_certs` switch instead of a
+// generic network error.
+
+use super::{remote_uri, Cancellable, Vfs, VfsDirIter, VfsEntry, VfsProvider};
+use crate::error::{AppError, ErrorCode};
+use crate::secrets;
+use crate::settings::{SettingsState, WebDavProfile};
+use percent_encoding::percent_decode_str;
+use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+use reqwest::{Method, StatusCode};
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::State;
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// PROPFIND / MKCOL / DELETE; transfers have no overall limit.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Upload chunks buffered between the copy engine and the PUT body.
+const UPLOAD_QUEUE: usize = 4;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/><d:getcontentlength/><d:getlastmodified/></d:prop>
+</d:propfind>"#;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebDavConnectionInfo {
+    pub connection_id: String,
+    /// Frontend path of the profile URL, "webdav://<id>/".
+    pub root_uri: String,
+}
+
+pub struct WebDavConnection {
+    client: Client,
+    /// Profile URL, always ending in "/".
+    base: Url,
+    username: String,
+    password: String,
+    /// Replaced by a fresh token on cancel().
+    token: Mutex<CancellationToken>,
+}
+
+impl WebDavConnection {
+    fn token(&self) -> CancellationToken {
+        self.token.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// URL of provider path `path` ("/a/b"); collections get a trailing "/".
+    fn url(&self, path: &str, collection: bool) -> Url {
+        let mut url = self.base.clone();
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty();
+            segments.extend(path.split('/').filter(|s| !s.is_empty()));
+            if collection {
+                segments.push("");
+            }
+        }
+        url
+    }
+
+    /// Provider path of an href from a multistatus response.
+    fn path_of(&self, href: &str) -> Option<String> {
+        let url = self.base.join(href).ok()?;
+        let full = percent_decode_str(url.path()).decode_utf8_lossy().to_string();
+        let base = percent_decode_str(self.base.path()).decode_utf8_lossy().to_string();
+        let rest = full.strip_prefix(base.trim_end_matches('/'))?;
+        let rest = rest.trim_end_matches('/');
+        Some(if rest.is_empty() { "/".to_string() } else { rest.to_string() })
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    fn send(&self, req: RequestBuilder, path: &str) -> Result<Response, AppError> {
+        let res = req.send().map_err(|e| transport_error(e, &self.base))?;
+        if res.status().is_success() {
+            Ok(res)
+        } else {
+            Err(status_error(res.status(), path))
+        }
+    }
+
+    fn propfind(&self, path: &str, depth: &str) -> Result<Vec<VfsEntry>, AppError> {
+        let req = self
+            .request(propfind_method(), self.url(path, false))
+            .header("Depth", depth)
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .timeout(REQUEST_TIMEOUT)
+            .body(PROPFIND_BODY);
+        let xml = self
+            .send(req, path)?
+            .text()
+            .map_err(|e| transport_error(e, &self.base))?;
+        self.parse_multistatus(&xml)
+            .map_err(|e| AppError::new(ErrorCode::Io, format!("Invalid PROPFIND response: {}", e)).with_path(path))
+    }
+
+    fn parse_multistatus(&self, xml: &str) -> Result<Vec<VfsEntry>, String> {
+        let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+        let dav = |n: &roxmltree::Node, name: &str| n.tag_name().name() == name && n.tag_name().namespace() == Some("DAV:");
+        let child_text = |n: roxmltree::Node, name: &str| {
+            n.descendants()
+                .find(|c| dav(c, name))
+                .and_then(|c| c.text())
+                .map(|t| t.trim().to_string())
+        };
+
+        let mut entries = Vec::new();
+        for response in doc.descendants().filter(|n| dav(n, "response")) {
+            let Some(path) = child_text(response, "href").and_then(|h| self.path_of(&h)) else {
+                continue;
+            };
+            // Only the propstat that actually returned the properties.
+            let Some(prop) = response
+                .children()
+                .filter(|n| dav(n, "propstat"))
+                .find(|ps| child_text(*ps, "status").is_some_and(|s| s.contains(" 200 ")))
+                .and_then(|ps| ps.children().find(|n| dav(n, "prop")))
+            else {
+                continue;
+            };
+            let is_dir = prop
+                .children()
+                .find(|n| dav(n, "resourcetype"))
+                .is_some_and(|rt| rt.children().any(|n| dav(&n, "collection")));
+            let size = child_text(prop, "getcontentlength")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+            let modified = child_text(prop, "getlastmodified")
+                .and_then(|s| chrono::DateTime::parse_from_rfc2822(&s).ok())
+                .map(|t| t.timestamp().max(0) as u64)
+                .unwrap_or(0);
+            let name = path.rsplit('/').next().unwrap_or("").to_string();
+            entries.push(VfsEntry {
+                hidden: name.starts_with('.'),
+                name,
+                path,
+                is_dir,
+                is_symlink: false,
+                size: if is_dir { 0 } else { size },
+                modified,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn propfind_method() -> Method {
+    Method::from_bytes(b"PROPFIND").expect("valid method")
+}
+
+fn mkcol_method() -> Method {
+    Method::from_bytes(b"MKCOL").expect("valid method")
+}
+
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn status_error(status: StatusCode, path: &str) -> AppError {
+    let code = match status {
+        StatusCode::UNAUTHORIZED => ErrorCode::PermissionDenied,
+        StatusCode::FORBIDDEN => ErrorCode::PermissionDenied,
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        // PUT with If-None-Match: * on an existing file.
+        StatusCode::PRECONDITION_FAILED => ErrorCode::AlreadyExists,
+        StatusCode::INSUFFICIENT_STORAGE => ErrorCode::DiskFull,
+        _ => ErrorCode::Io,
+    };
+    let message = match status {
+        StatusCode::UNAUTHORIZED => "WebDAV: authentication failed".to_string(),
+        _ => format!("WebDAV: server answered {}", status),
+    };
+    AppError::new(code, message).with_path(path)
+}
+
+/// Connection-level failure; certificate problems get their own code.
+fn transport_error(e: reqwest::Error, base: &Url) -> AppError {
+    let mut chain = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(s) = source {
+        chain.push_str(": ");
+        chain.push_str(&s.to_string());
+        source = s.source();
+    }
+    let host = base.host_str().unwrap_or("server");
+    if chain.to_lowercase().contains("certificate") {
+        return AppError::new(
+            ErrorCode::Certificate,
+            format!("The TLS certificate of {} was rejected", host),
+        )
+        .with_details(chain);
+    }
+    AppError::new(ErrorCode::Io, format!("Cannot reach {}", host)).with_details(chain)
+}
+
+enum Chunk {
+    Data(Vec<u8>),
+    End,
+}
+
+/// PUT body fed by UploadWriter. The sender going away without `End`
+/// (error / cancel in the copy engine) fails the body, so the server never
+/// keeps a truncated file.
+struct ChannelBody {
+    rx: Receiver<Chunk>,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+}
+
+impl Read for ChannelBody {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.pos < self.buf.len() {
+                let n = out.len().min(self.buf.len() - self.pos);
+                out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+                self.pos += n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            match self.rx.recv() {
+                Ok(Chunk::Data(data)) => {
+                    self.buf = data;
+                    self.pos = 0;
+                }
+                Ok(Chunk::End) => self.done = true,
+                Err(_) => return Err(io::Error::new(io::ErrorKind::Interrupted, "Upload aborted")),
+            }
+        }
+    }
+}
+
+/// Write side of a streamed PUT; flush() completes the upload and reports
+/// the server's answer.
+struct UploadWriter {
+    tx: Option<SyncSender<Chunk>>,
+    upload: Option<JoinHandle<Result<(), AppError>>>,
+}
+
+impl UploadWriter {
+    fn finish(&mut self) -> io::Result<()> {
+        let Some(tx) = self.tx.take() else {
+            return Ok(());
+        };
+        let _ = tx.send(Chunk::End);
+        drop(tx);
+        match self.upload.take().map(|h| h.join()) {
+            Some(Ok(Ok(()))) | None => Ok(()),
+            Some(Ok(Err(e))) => Err(io::Error::other(e.message)),
+            Some(Err(_)) => Err(io::Error::other("Upload thread panicked")),
+        }
+    }
+}
+
+impl Write for UploadWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let sent = self
+            .tx
+            .as_ref()
+            .is_some_and(|tx| tx.send(Chunk::Data(buf.to_vec())).is_ok());
+        if !sent {
+            // The request already ended: report why.
+            self.finish()?;
+            return Err(io::Error::other("Upload already finished"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finish()
+    }
+}
+
+impl VfsProvider for WebDavConnection {
+    fn scheme(&self) -> &'static str {
+        "webdav"
+    }
+
+    fn list(&self, path: &str) -> Result<VfsDirIter, AppError> {
+        let dir = normalize(path);
+        let token = self.token();
+        let entries = self
+            .propfind(&dir, "1")
+            .map_err(|e| e.context("Failed to read directory"))?;
+        Ok(Box::new(entries.into_iter().filter(move |e| e.path != dir).map(move |e| {
+            if token.is_cancelled() {
+                return Err(AppError::cancelled());
+            }
+            Ok(e)
+        })))
+    }
+
+    fn stat(&self, path: &str) -> Result<VfsEntry, AppError> {
+        let path = normalize(path);
+        self.propfind(&path, "0")?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "WebDAV: no such entry").with_path(&path))
+    }
+
+    fn read(&self, path: &str) -> Result<Box<dyn Read + Send>, AppError> {
+        let res = self.send(self.request(Method::GET, self.url(path, false)), path)?;
+        Ok(Box::new(Cancellable {
+            inner: res,
+            token: self.token(),
+        }))
+    }
+
+    fn write(&self, path: &str) -> Result<Box<dyn Write + Send>, AppError> {
+        let (tx, rx) = mpsc::sync_channel(UPLOAD_QUEUE);
+        let body = ChannelBody {
+            rx,
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+        };
+        let req = self
+            .request(Method::PUT, self.url(path, false))
+            .header("If-None-Match", "*")
+            .body(Body::new(body));
+        let base = self.base.clone();
+        let target = path.to_string();
+        let upload = std::thread::spawn(move || {
+            let res = req.send().map_err(|e| transport_error(e, &base))?;
+            if res.status().is_success() {
+                Ok(())
+            } else {
+                Err(status_error(res.status(), &target))
+            }
+        });
+        Ok(Box::new(Cancellable {
+            inner: UploadWriter {
+                tx: Some(tx),
+                upload: Some(upload),
+            },
+            token: self.token(),
+        }))
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError> {
+        let path = normalize(path);
+        if path == "/" {
+            return Ok(());
+        }
+        let req = self
+            .request(mkcol_method(), self.url(&path, true))
+            .timeout(REQUEST_TIMEOUT);
+        let res = req.send().map_err(|e| transport_error(e, &self.base))?;
+        match res.status() {
+            s if s.is_success() => Ok(()),
+            // Parent missing: create it, then retry.
+            StatusCode::CONFLICT => {
+                let parent = path.rsplit_once('/').map(|(p, _)| p).unwrap_or("/");
+                self.create_dir(parent)?;
+                self.send(
+                    self.request(mkcol_method(), self.url(&path, true)).timeout(REQUEST_TIMEOUT),
+                    &path,
+                )
+                .map(|_| ())
+            }
+            // Something already exists there.
+            StatusCode::METHOD_NOT_ALLOWED => match self.stat(&path) {
+                Ok(entry) if entry.is_dir => Ok(()),
+                _ => Err(AppError::new(ErrorCode::AlreadyExists, format!("Not a directory: {}", path)).with_path(&path)),
+            },
+            s => Err(status_error(s, &path).context("Failed to create directory")),
+        }
+    }
+
+    fn delete(&self, path: &str) -> Result<(), AppError> {
+        let path = normalize(path);
+        let entry = self.stat(&path)?;
+        if entry.is_dir && self.list(&path)?.next().is_some() {
+            return Err(AppError::new(ErrorCode::Io, format!("Directory not empty: {}", path)).with_path(&path));
+        }
+        self.send(
+            self.request(Method::DELETE, self.url(&path, entry.is_dir))
+                .timeout(REQUEST_TIMEOUT),
+            &path,
+        )
+        .map(|_| ())
+    }
+
+    fn join(&self, dir: &str, name: &str) -> String {
+        format!("{}/{}", dir.trim_end_matches('/'), name)
+    }
+
+    fn cancel(&self) {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        token.cancel();
+        *token = CancellationToken::new();
+    }
+}
+
+/// Connect to a saved WebDAV profile and mount it. Without `password` the
+/// one stored in the keychain (secrets.rs, "webdav:<profile>") is used.
+///
+/// Frontend can call:
+///   invoke<WebDavConnectionInfo>('connect_webdav', { profile: 'Nextcloud', password })
+///   invoke<WebDavConnectionInfo>('connect_webdav', { profile: 'Nextcloud' })
+#[tauri::command]
+pub async fn connect_webdav(
+    settings: State<'_, SettingsState>,
+    vfs: State<'_, Vfs>,
+    profile: String,
+    password: Option<String>,
+) -> Result<WebDavConnectionInfo, AppError> {
+    let profile = settings
+        .get()
+        .system
+        .webdav_profiles
+        .into_iter()
+        .find(|p| p.name == profile)
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("No WebDAV profile named {:?}", profile)))?;
+
+    // The blocking client must be built (and first used) off the async runtime.
+    let conn = task::spawn_blocking(move || {
+        let password = match password {
+            Some(password) => password,
+            None => secrets::load("webdav", &profile.name)?.ok_or_else(|| {
+                AppError::new(ErrorCode::NotFound, format!("No saved password for {:?}", profile.name))
+            })?,
+        };
+        open(&profile, password)
+    })
+        .await
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("WebDAV task failed: {}", e)))??;
+
+    let id = vfs.mount(Arc::new(conn))?;
+    Ok(WebDavConnectionInfo {
+        root_uri: remote_uri("webdav", &id, "/"),
+        connection_id: id,
+    })
+}
+
+fn open(profile: &WebDavProfile, password: String) -> Result<WebDavConnection, AppError> {
+    let mut base = Url::parse(profile.url.trim())
+        .map_err(|e| AppError::invalid_input(format!("Invalid WebDAV URL {:?}: {}", profile.url, e)))?;
+    if !base.path().ends_with('/') {
+        base.set_path(&format!("{}/", base.path()));
+    }
+
+    let client = Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(None)
+        .danger_accept_invalid_certs(profile.accept_invalid_certs)
+        .build()
+        .map_err(|e| AppError::new(ErrorCode::Internal, format!("HTTP client error: {}", e)))?;
+
+    let conn = WebDavConnection {
+        client,
+        base,
+        username: profile.username.clone(),
+        password,
+        token: Mutex::new(CancellationToken::new()),
+    };
+    // Checks URL, TLS and credentials in one go.
+    let root = conn.stat("/")?;
+    if !root.is_dir {
+        return Err(AppError::new(
+            ErrorCode::NotADirectory,
+            format!("{} is not a WebDAV collection", profile.url),
+        ));
+    }
+    tracing::info!("[WebDAV] Connected to {}", conn.base.host_str().unwrap_or(""));
+    Ok(conn)
+}
@@ -0,0 +1,225 @@
+// src-tauri/src/volumes.rs
+//
+// Mounted volume enumeration for the sidebar "Devices" section.
+// - list_volumes returns every mounted volume with capacity and flags.
+// - A small watcher thread polls the mount table every POLL_INTERVAL and
+//   emits `fu:volume_added` / `fu:volume_removed` when USB sticks, SD
+//   cards or network shares appear or disappear. Polling keeps this
+//   identical on every OS (no udev / WM_DEVICECHANGE / DiskArbitration).
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use sysinfo::{DiskExt, DiskKind, System, SystemExt};
+use tauri::{AppHandle, Emitter};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeInfo {
+    pub mount_point: String,
+    /// User-visible name ("USB STICK", "Macintosh HD"); may be empty.
+    pub label: String,
+    /// Device / volume identifier as reported by the OS ("/dev/sdb1", ...).
+    pub device: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub is_removable: bool,
+    pub is_read_only: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VolumeRemoved {
+    mount_point: String,
+}
+
+/// Frontend can call:
+///   invoke<VolumeInfo[]>('list_volumes')
+#[tauri::command]
+pub async fn list_volumes() -> Result<Vec<VolumeInfo>, String> {
+    // statvfs on a stale network mount can block; keep it off the IPC thread.
+    tauri::async_runtime::spawn_blocking(|| {
+        let mut sys = System::new();
+        collect_volumes(&mut sys)
+    })
+    .await
+    .map_err(|e| format!("Volume enumeration failed: {}", e))
+}
+
+/// Start the add/remove watcher. Called once from lib.rs setup.
+pub fn start_volume_watcher(app: AppHandle) {
+    thread::spawn(move || {
+        let mut sys = System::new();
+        let mut known: HashMap<String, VolumeInfo> = collect_volumes(&mut sys)
+            .into_iter()
+            .map(|v| (v.mount_point.clone(), v))
+            .collect();
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let current: HashMap<String, VolumeInfo> = collect_volumes(&mut sys)
+                .into_iter()
+                .map(|v| (v.mount_point.clone(), v))
+                .collect();
+
+            for (mount_point, volume) in &current {
+                if !known.contains_key(mount_point)
+                    && app.emit("fu:volume_added", volume.clone()).is_err()
+                {
+                    return;
+                }
+            }
+            for mount_point in known.keys() {
+                if !current.contains_key(mount_point)
+                    && app
+                        .emit(
+                            "fu:volume_removed",
+                            VolumeRemoved {
+                                mount_point: mount_point.clone(),
+                            },
+                        )
+                        .is_err()
+                {
+                    return;
+                }
+            }
+            known = current;
+        }
+    });
+}
+
+/// Kind of the disk holding `path` (longest matching mount point).
+/// None when no mount matches; Some(Unknown(_)) when the OS can't tell.
+pub fn disk_kind_for_path(path: &Path) -> Option<DiskKind> {
+    // Canonical Windows paths are verbatim (\\?\C:\...); mounts are "C:\".
+    let plain = path.to_string_lossy();
+    let path = Path::new(plain.strip_prefix(r"\\?\").unwrap_or(&plain));
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    sys.disks()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.kind())
+}
+
+fn collect_volumes(sys: &mut System) -> Vec<VolumeInfo> {
+    sys.refresh_disks_list();
+    let mut volumes: Vec<VolumeInfo> = sys
+        .disks()
+        .iter()
+        .map(|disk| {
+            let mount_point = disk.mount_point();
+            let device = disk.name().to_string_lossy().to_string();
+            VolumeInfo {
+                mount_point: mount_point.to_string_lossy().to_string(),
+                label: volume_label(mount_point, &device),
+                device,
+                file_system: String::from_utf8_lossy(disk.file_system()).to_string(),
+                total_bytes: disk.total_space(),
+                free_bytes: disk.available_space(),
+                is_removable: disk.is_removable(),
+                is_read_only: is_read_only(mount_point),
+            }
+        })
+        .collect();
+    volumes.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    volumes
+}
+
+#[cfg(unix)]
+fn is_read_only(mount_point: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = CString::new(mount_point.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    stat.f_flag & libc::ST_RDONLY != 0
+}
+
+#[cfg(windows)]
+fn is_read_only(mount_point: &Path) -> bool {
+    const FILE_READ_ONLY_VOLUME: u32 = 0x0008_0000;
+    windows_volume_info(mount_point)
+        .map(|(_, flags)| flags & FILE_READ_ONLY_VOLUME != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_read_only(_mount_point: &Path) -> bool {
+    false
+}
+
+/// (label, filesystem flags) from GetVolumeInformationW.
+#[cfg(windows)]
+fn windows_volume_info(mount_point: &Path) -> Option<(String, u32)> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetVolumeInformationW;
+
+    let root: Vec<u16> = mount_point
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut name = [0u16; 261];
+    let mut flags = 0u32;
+    let ok = unsafe {
+        GetVolumeInformationW(
+            root.as_ptr(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut flags,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    Some((String::from_utf16_lossy(&name[..len]), flags))
+}
+
+#[cfg(windows)]
+fn volume_label(mount_point: &Path, _device: &str) -> String {
+    windows_volume_info(mount_point)
+        .map(|(label, _)| label)
+        .unwrap_or_default()
+}
+
+/// Linux: reverse lookup in /dev/disk/by-label (names are \x20-escaped).
+#[cfg(target_os = "linux")]
+fn volume_label(_mount_point: &Path, device: &str) -> String {
+    let Ok(device) = std::fs::canonicalize(device) else {
+        return String::new();
+    };
+    let Ok(entries) = std::fs::read_dir("/dev/disk/by-label") else {
+        return String::new();
+    };
+    entries
+        .flatten()
+        .find(|e| std::fs::canonicalize(e.path()).is_ok_and(|p| p == device))
+        .map(|e| e.file_name().to_string_lossy().replace("\\x20", " "))
+        .unwrap_or_default()
+}
+
+/// macOS mounts volumes under /Volumes/<label>; "/" is the system disk.
+#[cfg(not(any(windows, target_os = "linux")))]
+fn volume_label(mount_point: &Path, device: &str) -> String {
+    mount_point
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| device.to_string())
+}
@@ -15,6 +15,8 @@ export interface UpdateCheckResult {
 export interface DownloadResult {
   version: string;
   bundle_path: string;
+  /** Base version if bundle_path is a delta bundle, null for a full bundle. */
+  delta_from?: string | null;
 }
 
 export interface ApplyResult {
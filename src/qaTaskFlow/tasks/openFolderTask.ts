@@ -1,5 +1,6 @@
 // src/qaTaskFlow/tasks/openFolderTask.ts
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../../utils/appError';
 
 export interface FileEntry {
   name: string;
@@ -24,6 +25,6 @@ export async function openFolderTask(folderPath: string): Promise<OpenFolderOutp
       count: entries.length
     };
   } catch (err) {
-    throw new Error(`Failed to open folder: ${err}`);
+    throw new Error(`Failed to open folder: ${errorMessage(err)}`);
   }
 }
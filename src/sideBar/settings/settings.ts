@@ -20,6 +20,30 @@
  */
 import { invoke } from '$lib/tauri/ipc';
 
+/** Saved WebDAV / Nextcloud server (password is never stored here). */
+export interface WebDavProfile {
+  name: string;
+  url: string;
+  username: string;
+  acceptInvalidCerts: boolean;
+}
+
+/** Saved S3 / MinIO account (secret key is never stored here). */
+export interface S3Profile {
+  name: string;
+  endpoint: string;  // Empty = AWS
+  region: string;  // Empty = us-east-1
+  bucket: string;  // Empty = list all buckets
+  accessKeyId: string;
+  pathStyle: boolean;
+}
+
+/** System-wide hotkey; `action` is passed to Rust unchanged. */
+export interface GlobalShortcut {
+  accelerator: string;  // e.g. "CmdOrCtrl+Shift+F"
+  action: { type: 'show_window' } | { type: 'quick_scan'; path: string | null };  // path null = Downloads
+}
+
 export interface SystemSettings {
   showStatusBarMetrics: boolean;
   cpuMemIntervalMs: number;
@@ -27,6 +51,23 @@ export interface SystemSettings {
   diskWarnThresholdPercent: number;
   cpuWarnThresholdPercent: number;
   ramWarnThresholdPercent: number;
+  allowElevation: boolean;  // Allow run_elevated (UAC / pkexec) for protected paths
+  safeMode: boolean;  // Read-only: file-changing commands are rejected
+  backgroundPriority: boolean;  // Scans and hashing at background CPU/IO priority
+  ioThrottleBytesPerSec: number;  // Bytes/s shared by copies, syncs and downloads (0 = unlimited)
+  notifyOn: ('folder_scan' | 'duplicate_scan' | 'sync' | 'copy_move' | 'update_download')[];  // Finished operations that notify when unfocused / in tray
+  closeToTray: boolean;  // Background mode: closing keeps the app in the tray
+  minimizeToTray: boolean;  // Hide in the tray when minimized
+  globalShortcuts: GlobalShortcut[];  // System-wide hotkeys (shortcuts.rs)
+  transferMaxConcurrent: number;  // Transfer queue jobs running at once (1-16)
+  aiBundleRedactPatterns: string[];  // Extra redaction regexes
+  webdavProfiles: WebDavProfile[];  // Saved servers for connect_webdav
+  s3Profiles: S3Profile[];  // Saved accounts for connect_s3
+  aiBundleRedact: boolean;  // Redact paths/usernames/secrets in AI bundles
+  aiBundleHistoryKeep: number;  // Archived AI bundles kept (0 = none)
+  temperatureIntervalSec: number;
+  temperatureMetricsEnabled: boolean;
+  networkMetricsEnabled: boolean;
 }
 
 export type Settings = {
@@ -37,6 +78,7 @@ export type Settings = {
   showHiddenFolders: boolean;
   showSystemFiles: boolean;
   activeTabId: string;
+  lastFolder: string;  // Tray "Open last folder"; keep updated while browsing
   theme: string;
   fontFamily: string;
   language: string;
@@ -114,6 +156,7 @@ export const defaultSettings: Settings = {
   showHiddenFolders: false,
   showSystemFiles: false,
   activeTabId: '1',
+  lastFolder: '',
   theme: 'light',
   fontFamily: 'system-ui',
   language: 'en',
@@ -187,6 +230,23 @@ export const defaultSettings: Settings = {
     diskWarnThresholdPercent: 95,
     cpuWarnThresholdPercent: 95,
     ramWarnThresholdPercent: 95,
+    allowElevation: false,
+    safeMode: false,
+    backgroundPriority: false,
+    ioThrottleBytesPerSec: 0,
+    notifyOn: ['folder_scan', 'sync', 'update_download'],
+    closeToTray: false,
+    minimizeToTray: false,
+    globalShortcuts: [],
+    transferMaxConcurrent: 2,
+    aiBundleRedactPatterns: [],
+    webdavProfiles: [],
+    s3Profiles: [],
+    aiBundleRedact: true,
+    aiBundleHistoryKeep: 20,
+    temperatureIntervalSec: 10,
+    temperatureMetricsEnabled: false,
+    networkMetricsEnabled: true,
   },
 };
 
@@ -248,6 +308,7 @@ function rustToJs(rustSettings: any): Settings {
     showHiddenFolders: rustSettings.show_hidden_folders ?? defaultSettings.showHiddenFolders,
     showSystemFiles: rustSettings.show_system_files ?? defaultSettings.showSystemFiles,
     activeTabId: rustSettings.active_tab_id ?? defaultSettings.activeTabId,
+    lastFolder: rustSettings.last_folder ?? defaultSettings.lastFolder,
     theme: rustSettings.theme ?? defaultSettings.theme,
     fontFamily: rustSettings.font_family ?? defaultSettings.fontFamily,
     language: rustSettings.language ?? defaultSettings.language,
@@ -326,6 +387,35 @@ function rustToJs(rustSettings: any): Settings {
         systemRaw.cpu_warn_threshold_percent ?? defaultSettings.system.cpuWarnThresholdPercent,
       ramWarnThresholdPercent:
         systemRaw.ram_warn_threshold_percent ?? defaultSettings.system.ramWarnThresholdPercent,
+      allowElevation: systemRaw.allow_elevation ?? defaultSettings.system.allowElevation,
+      safeMode: systemRaw.safe_mode ?? defaultSettings.system.safeMode,
+      backgroundPriority: systemRaw.background_priority ?? defaultSettings.system.backgroundPriority,
+      ioThrottleBytesPerSec: systemRaw.io_throttle_bytes_per_sec ?? defaultSettings.system.ioThrottleBytesPerSec,
+      notifyOn: systemRaw.notify_on ?? defaultSettings.system.notifyOn,
+      closeToTray: systemRaw.close_to_tray ?? defaultSettings.system.closeToTray,
+      minimizeToTray: systemRaw.minimize_to_tray ?? defaultSettings.system.minimizeToTray,
+      globalShortcuts: systemRaw.global_shortcuts ?? defaultSettings.system.globalShortcuts,
+      transferMaxConcurrent: systemRaw.transfer_max_concurrent ?? defaultSettings.system.transferMaxConcurrent,
+      aiBundleRedactPatterns: systemRaw.ai_bundle_redact_patterns ?? defaultSettings.system.aiBundleRedactPatterns,
+      webdavProfiles: (systemRaw.webdav_profiles ?? defaultSettings.system.webdavProfiles).map((p: any) => ({
+        name: p.name ?? '',
+        url: p.url ?? '',
+        username: p.username ?? '',
+        acceptInvalidCerts: p.accept_invalid_certs ?? false,
+      })),
+      s3Profiles: (systemRaw.s3_profiles ?? defaultSettings.system.s3Profiles).map((p: any) => ({
+        name: p.name ?? '',
+        endpoint: p.endpoint ?? '',
+        region: p.region ?? '',
+        bucket: p.bucket ?? '',
+        accessKeyId: p.access_key_id ?? '',
+        pathStyle: p.path_style ?? false,
+      })),
+      aiBundleRedact: systemRaw.ai_bundle_redact ?? defaultSettings.system.aiBundleRedact,
+      aiBundleHistoryKeep: systemRaw.ai_bundle_history_keep ?? defaultSettings.system.aiBundleHistoryKeep,
+      temperatureIntervalSec: systemRaw.temperature_interval_sec ?? defaultSettings.system.temperatureIntervalSec,
+      temperatureMetricsEnabled: systemRaw.temperature_metrics_enabled ?? defaultSettings.system.temperatureMetricsEnabled,
+      networkMetricsEnabled: systemRaw.network_metrics_enabled ?? defaultSettings.system.networkMetricsEnabled,
     },
   };
 }
@@ -342,6 +432,7 @@ function jsToRust(jsSettings: Settings): any {
     show_hidden_folders: jsSettings.showHiddenFolders,
     show_system_files: jsSettings.showSystemFiles,
     active_tab_id: jsSettings.activeTabId,
+    last_folder: jsSettings.lastFolder,
     theme: jsSettings.theme,
     font_family: jsSettings.fontFamily,
     language: jsSettings.language,
@@ -415,6 +506,35 @@ function jsToRust(jsSettings: Settings): any {
       disk_warn_threshold_percent: jsSettings.system.diskWarnThresholdPercent,
       cpu_warn_threshold_percent: jsSettings.system.cpuWarnThresholdPercent,
       ram_warn_threshold_percent: jsSettings.system.ramWarnThresholdPercent,
+      allow_elevation: jsSettings.system.allowElevation,
+      safe_mode: jsSettings.system.safeMode,
+      background_priority: jsSettings.system.backgroundPriority,
+      io_throttle_bytes_per_sec: jsSettings.system.ioThrottleBytesPerSec,
+      notify_on: jsSettings.system.notifyOn,
+      close_to_tray: jsSettings.system.closeToTray,
+      minimize_to_tray: jsSettings.system.minimizeToTray,
+      global_shortcuts: jsSettings.system.globalShortcuts,
+      transfer_max_concurrent: jsSettings.system.transferMaxConcurrent,
+      ai_bundle_redact_patterns: jsSettings.system.aiBundleRedactPatterns,
+      webdav_profiles: jsSettings.system.webdavProfiles.map((p) => ({
+        name: p.name,
+        url: p.url,
+        username: p.username,
+        accept_invalid_certs: p.acceptInvalidCerts,
+      })),
+      s3_profiles: jsSettings.system.s3Profiles.map((p) => ({
+        name: p.name,
+        endpoint: p.endpoint,
+        region: p.region,
+        bucket: p.bucket,
+        access_key_id: p.accessKeyId,
+        path_style: p.pathStyle,
+      })),
+      ai_bundle_redact: jsSettings.system.aiBundleRedact,
+      ai_bundle_history_keep: jsSettings.system.aiBundleHistoryKeep,
+      temperature_interval_sec: jsSettings.system.temperatureIntervalSec,
+      temperature_metrics_enabled: jsSettings.system.temperatureMetricsEnabled,
+      network_metrics_enabled: jsSettings.system.networkMetricsEnabled,
     },
   };
 }
@@ -0,0 +1,59 @@
+/**
+ * This is src/utils/appError.ts
+ * Used by: anything that calls backend commands returning AppError (list_dir, scans, file ops, updates)
+ * Purpose: Typed view of the structured error the Rust side rejects with (src-tauri/src/error.rs)
+ * Trigger: catch blocks around invoke()
+ * Event Flow: invoke rejects with { code, message, path?, details? } -> isAppError / errorCode / errorMessage
+ * List of functions: isAppError, errorCode, errorMessage
+ */
+
+export type AppErrorCode =
+  | 'NOT_FOUND'
+  | 'PERMISSION_DENIED'
+  | 'ACCESS_DENIED'
+  | 'ALREADY_EXISTS'
+  | 'NOT_A_DIRECTORY'
+  | 'INVALID_INPUT'
+  | 'DISK_FULL'
+  | 'INSUFFICIENT_SPACE'
+  | 'CANCELLED'
+  | 'IO'
+  | 'UPDATE_FAILED'
+  | 'CERTIFICATE'
+  | 'NETWORK_AUTH_REQUIRED'
+  | 'HOST_KEY_UNKNOWN'
+  | 'SAFE_MODE_ENABLED'
+  | 'INTERNAL';
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  path?: string;
+  details?: string;
+  /** Only with INSUFFICIENT_SPACE (bytes). */
+  insufficientSpace?: { required: number; available: number };
+  /** Only with NETWORK_AUTH_REQUIRED: "\\server\share" for connect_network_share. */
+  share?: string;
+  /** Only with HOST_KEY_UNKNOWN: "SHA256:..." to confirm, then pass as acceptedHostKey to connect_sftp. */
+  hostKey?: string;
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as AppError).code === 'string' &&
+    typeof (err as AppError).message === 'string'
+  );
+}
+
+/** Error code, or undefined for plain string / JS errors. */
+export function errorCode(err: unknown): AppErrorCode | undefined {
+  return isAppError(err) ? err.code : undefined;
+}
+
+/** Human-readable message for any rejection value (AppError, Error, string). */
+export function errorMessage(err: unknown): string {
+  if (isAppError(err) || err instanceof Error) return err.message;
+  return String(err);
+}